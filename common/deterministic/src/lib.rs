@@ -0,0 +1,158 @@
+//! Random values and wall clock time which are stable across replays of the worker.
+//!
+//! When the worker runs live, the values are drawn from the host and committed to the oplog, and
+//! when it is replayed the same values are read back from the oplog, so identifiers, idempotency
+//! keys, retry jitter or timestamps derived from them are the same as in the original run.
+//! Without the durability feature the values are drawn from the host directly.
+//!
+//! The module is shared by golem-llm and golem-search, which re-export it as their
+//! `deterministic` module and enable its durability feature with their own.
+
+use std::hash::{BuildHasher, RandomState};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// A random 64 bit number
+pub fn random_u64() -> u64 {
+    random_words(1)[0]
+}
+
+/// A random number in `[0, 1)`
+pub fn random_f64() -> f64 {
+    (random_u64() >> 11) as f64 / (1u64 << 53) as f64
+}
+
+/// A random 128 bit identifier in hexadecimal
+pub fn random_id() -> String {
+    let words = random_words(2);
+    format!("{:016x}{:016x}", words[0], words[1])
+}
+
+/// A random version 4 UUID, in its hyphenated form
+pub fn uuid_v4() -> String {
+    let words = random_words(2);
+    format_uuid_v4(words[0], words[1])
+}
+
+/// The current wall clock time
+pub fn now() -> SystemTime {
+    let nanos = durable::words("now", 1, || {
+        vec![SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_nanos() as u64)
+            .unwrap_or_default()]
+    });
+    UNIX_EPOCH + Duration::from_nanos(nanos[0])
+}
+
+fn random_words(count: usize) -> Vec<u64> {
+    durable::words("random", count, || {
+        let state = RandomState::new();
+        (0..count as u64).map(|i| state.hash_one(i)).collect()
+    })
+}
+
+/// Formats 128 random bits as a UUID, setting the bits of the version and the variant
+fn format_uuid_v4(high: u64, low: u64) -> String {
+    let high = (high & !0xf000) | 0x4000;
+    let low = (low & !(0b11 << 62)) | (0b10 << 62);
+    format!(
+        "{:08x}-{:04x}-{:04x}-{:04x}-{:012x}",
+        high >> 32,
+        (high >> 16) & 0xffff,
+        high & 0xffff,
+        low >> 48,
+        low & 0xffff_ffff_ffff
+    )
+}
+
+#[cfg(not(all(feature = "durability", not(test))))]
+mod durable {
+    pub fn words(
+        _function: &'static str,
+        _count: usize,
+        live: impl FnOnce() -> Vec<u64>,
+    ) -> Vec<u64> {
+        live()
+    }
+}
+
+#[cfg(all(feature = "durability", not(test)))]
+mod durable {
+    use golem_rust::bindings::golem::durability::durability::DurableFunctionType;
+    use golem_rust::durability::Durability;
+    use golem_rust::{FromValueAndType, IntoValue};
+    use std::fmt::{Display, Formatter};
+
+    #[derive(Debug, IntoValue)]
+    struct WordsInput {
+        count: u64,
+    }
+
+    #[derive(Debug, Clone, FromValueAndType, IntoValue)]
+    struct WordsOutput {
+        words: Vec<u64>,
+    }
+
+    #[derive(Debug, FromValueAndType, IntoValue)]
+    struct UnusedError;
+
+    impl Display for UnusedError {
+        fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+            write!(f, "UnusedError")
+        }
+    }
+
+    /// Draws `count` words with `live` and commits them to the oplog, or reads them back from
+    /// the oplog when replaying
+    pub fn words(
+        function: &'static str,
+        count: usize,
+        live: impl FnOnce() -> Vec<u64>,
+    ) -> Vec<u64> {
+        let durability = Durability::<WordsOutput, UnusedError>::new(
+            "golem_deterministic",
+            function,
+            DurableFunctionType::ReadLocal,
+        );
+        if durability.is_live() {
+            let output = durability.persist_infallible(
+                WordsInput {
+                    count: count as u64,
+                },
+                WordsOutput { words: live() },
+            );
+            output.words
+        } else {
+            let output: WordsOutput = durability.replay_infallible();
+            output.words
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn uuids_have_the_version_and_variant_bits() {
+        assert_eq!(format_uuid_v4(0, 0), "00000000-0000-4000-8000-000000000000");
+        assert_eq!(
+            format_uuid_v4(u64::MAX, u64::MAX),
+            "ffffffff-ffff-4fff-bfff-ffffffffffff"
+        );
+
+        let uuid = uuid_v4();
+        assert_eq!(uuid.len(), 36);
+        assert_ne!(uuid, uuid_v4());
+    }
+
+    #[test]
+    fn random_values_are_in_range() {
+        for _ in 0..100 {
+            let value = random_f64();
+            assert!((0.0..1.0).contains(&value), "{value}");
+        }
+        assert_eq!(random_id().len(), 32);
+        assert!(now() > UNIX_EPOCH);
+    }
+}
@@ -1,9 +1,11 @@
+use golem_llm::correlation::{self, CORRELATION_ID_HEADER};
 use golem_llm::error::{error_code_from_status, from_event_source_error, from_reqwest_error};
 use golem_llm::event_source::EventSource;
+use golem_llm::files::MultipartForm;
 use golem_llm::golem::llm::llm::Error;
 use log::trace;
 use reqwest::header::HeaderValue;
-use reqwest::{Client, Method, Response};
+use reqwest::{Client, Method, RequestBuilder, Response};
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
@@ -11,6 +13,9 @@ use std::fmt::Debug;
 
 const BASE_URL: &str = "https://api.anthropic.com";
 
+/// The beta enabling the Files API, required both for uploading and for referencing files
+const FILES_API_BETA: &str = "files-api-2025-04-14";
+
 /// The Anthropic API client for creating model responses.
 pub struct MessagesApi {
     api_key: String,
@@ -25,12 +30,24 @@ impl MessagesApi {
         Self { api_key, client }
     }
 
+    /// Starts a messages request, enabling the Files API beta if the request references files
+    fn request(&self, method: Method, request: &MessagesRequest) -> RequestBuilder {
+        let builder = self
+            .client
+            .request(method, format!("{BASE_URL}/v1/messages"));
+        if request.references_files() {
+            builder.header("anthropic-beta", FILES_API_BETA)
+        } else {
+            builder
+        }
+    }
+
     pub fn send_messages(&self, request: MessagesRequest) -> Result<MessagesResponse, Error> {
         trace!("Sending request to Anthropic API: {request:?}");
 
         let response: Response = self
-            .client
-            .request(Method::POST, format!("{BASE_URL}/v1/messages"))
+            .request(Method::POST, &request)
+            .header(CORRELATION_ID_HEADER, correlation::current())
             .header("anthropic-version", "2023-06-01")
             .header("x-api-key", &self.api_key)
             .json(&request)
@@ -44,8 +61,8 @@ impl MessagesApi {
         trace!("Sending request to Anthropic API: {request:?}");
 
         let response: Response = self
-            .client
-            .request(Method::POST, format!("{BASE_URL}/v1/messages"))
+            .request(Method::POST, &request)
+            .header(CORRELATION_ID_HEADER, correlation::current())
             .header("anthropic-version", "2023-06-01")
             .header("x-api-key", &self.api_key)
             .header(
@@ -86,6 +103,87 @@ pub struct MessagesRequest {
     pub top_p: Option<f32>,
 }
 
+/// The Anthropic Files API client, a beta feature.
+///
+/// Based on https://docs.anthropic.com/en/api/files-create
+pub struct FilesApi {
+    api_key: String,
+    client: Client,
+}
+
+impl FilesApi {
+    pub fn new(api_key: String) -> Self {
+        let client = Client::builder()
+            .build()
+            .expect("Failed to initialize HTTP client");
+        Self { api_key, client }
+    }
+
+    pub fn upload_file(
+        &self,
+        name: &str,
+        mime_type: &str,
+        data: &[u8],
+    ) -> Result<FileMetadata, Error> {
+        trace!(
+            "Uploading file {name} ({} bytes) to Anthropic API",
+            data.len()
+        );
+
+        let form = MultipartForm::new().file("file", name, mime_type, data);
+
+        let response: Response = self
+            .client
+            .request(Method::POST, format!("{BASE_URL}/v1/files"))
+            .header(CORRELATION_ID_HEADER, correlation::current())
+            .header("anthropic-version", "2023-06-01")
+            .header("anthropic-beta", FILES_API_BETA)
+            .header("x-api-key", &self.api_key)
+            .header(reqwest::header::CONTENT_TYPE, form.content_type())
+            .body(form.finish())
+            .send()
+            .map_err(|err| from_reqwest_error("Request failed", err))?;
+
+        parse_response(response)
+    }
+
+    pub fn delete_file(&self, id: &str) -> Result<DeletedFile, Error> {
+        trace!("Deleting file {id} from Anthropic API");
+
+        let response: Response = self
+            .client
+            .request(Method::DELETE, format!("{BASE_URL}/v1/files/{id}"))
+            .header(CORRELATION_ID_HEADER, correlation::current())
+            .header("anthropic-version", "2023-06-01")
+            .header("anthropic-beta", FILES_API_BETA)
+            .header("x-api-key", &self.api_key)
+            .send()
+            .map_err(|err| from_reqwest_error("Request failed", err))?;
+
+        parse_response(response)
+    }
+}
+
+impl MessagesRequest {
+    fn references_files(&self) -> bool {
+        self.messages
+            .iter()
+            .flat_map(|message| &message.content)
+            .any(|content| {
+                matches!(
+                    content,
+                    Content::Image {
+                        source: ImageSource::File { .. },
+                        ..
+                    } | Content::Document {
+                        source: DocumentSource::File { .. },
+                        ..
+                    }
+                )
+            })
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MessagesRequestMetadata {
     pub user_id: Option<String>,
@@ -128,7 +226,12 @@ pub enum Content {
         content: Vec<Content>, // can only be Text or Image
         is_error: bool,
     },
-    // Document
+    #[serde(rename = "document")]
+    Document {
+        source: DocumentSource,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        cache_control: Option<CacheControl>,
+    },
     // Thinking
     // RedactedThinking
 }
@@ -146,6 +249,15 @@ pub enum ImageSource {
     Url { url: String },
     #[serde(rename = "base64")]
     Base64 { data: String, media_type: MediaType },
+    #[serde(rename = "file")]
+    File { file_id: String },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum DocumentSource {
+    #[serde(rename = "file")]
+    File { file_id: String },
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -237,6 +349,20 @@ pub enum Role {
     Assistant,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileMetadata {
+    pub id: String,
+    pub filename: String,
+    pub mime_type: String,
+    pub size_bytes: u64,
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeletedFile {
+    pub id: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ErrorResponse {
     pub error: ErrorResponseDetails,
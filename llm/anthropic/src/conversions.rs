@@ -1,11 +1,12 @@
 use crate::client::{
-    Content, ImageSource as ClientImageSource, MediaType, MessagesRequest, MessagesRequestMetadata,
-    MessagesResponse, StopReason, Tool, ToolChoice,
+    Content, DocumentSource, FileMetadata, ImageSource as ClientImageSource, MediaType,
+    MessagesRequest, MessagesRequestMetadata, MessagesResponse, StopReason, Tool, ToolChoice,
 };
 use base64::{engine::general_purpose, Engine as _};
+use golem_llm::golem::llm::files::{FilePurpose, UploadedFile};
 use golem_llm::golem::llm::llm::{
-    ChatEvent, CompleteResponse, Config, ContentPart, Error, ErrorCode, FinishReason,
-    ImageReference, ImageSource, ImageUrl, Message, ResponseMetadata, Role, ToolCall,
+    ChatEvent, CompleteResponse, Config, ContentPart, Error, ErrorCode, FileReference,
+    FinishReason, ImageReference, ImageSource, ImageUrl, Message, ResponseMetadata, Role, ToolCall,
     ToolDefinition, ToolResult, Usage,
 };
 use std::collections::HashMap;
@@ -136,7 +137,20 @@ pub fn process_response(response: MessagesResponse) -> ChatEvent {
                         }
                     }
                 }
+                ClientImageSource::File { file_id } => {
+                    contents.push(ContentPart::File(FileReference {
+                        id: file_id,
+                        mime_type: None,
+                    }))
+                }
             },
+            Content::Document {
+                source: DocumentSource::File { file_id },
+                ..
+            } => contents.push(ContentPart::File(FileReference {
+                id: file_id,
+                mime_type: None,
+            })),
             Content::ToolUse {
                 id, input, name, ..
             } => tool_calls.push(ToolCall {
@@ -264,6 +278,20 @@ fn message_to_content(message: &Message) -> Vec<Content> {
                     });
                 }
             },
+            ContentPart::File(file) => match &file.mime_type {
+                Some(mime_type) if mime_type.starts_with("image/") => result.push(Content::Image {
+                    source: ClientImageSource::File {
+                        file_id: file.id.clone(),
+                    },
+                    cache_control: None,
+                }),
+                _ => result.push(Content::Document {
+                    source: DocumentSource::File {
+                        file_id: file.id.clone(),
+                    },
+                    cache_control: None,
+                }),
+            },
         }
     }
 
@@ -285,3 +313,14 @@ fn tool_definition_to_tool(tool: &ToolDefinition) -> Result<Tool, Error> {
         }),
     }
 }
+
+pub fn file_metadata_to_uploaded_file(file: FileMetadata, purpose: FilePurpose) -> UploadedFile {
+    UploadedFile {
+        id: file.id,
+        name: file.filename,
+        mime_type: file.mime_type,
+        size_bytes: file.size_bytes,
+        purpose,
+        created_at: Some(file.created_at),
+    }
+}
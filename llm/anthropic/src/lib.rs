@@ -2,16 +2,21 @@ mod client;
 mod conversions;
 
 use crate::client::{
-    Content, ContentBlockDelta, ErrorResponse, MessagesApi, MessagesRequest, StopReason, Usage,
+    Content, ContentBlockDelta, ErrorResponse, FilesApi, MessagesApi, MessagesRequest, StopReason,
+    Usage,
 };
 use crate::conversions::{
-    convert_usage, messages_to_request, process_response, stop_reason_to_finish_reason,
-    tool_results_to_messages,
+    convert_usage, file_metadata_to_uploaded_file, messages_to_request, process_response,
+    stop_reason_to_finish_reason, tool_results_to_messages,
 };
 use golem_llm::chat_stream::{LlmChatStream, LlmChatStreamState};
-use golem_llm::config::with_config_key;
+use golem_llm::config::{get_config_key, with_config_key};
 use golem_llm::durability::{DurableLLM, ExtendedGuest};
+use golem_llm::error::unsupported;
 use golem_llm::event_source::EventSource;
+use golem_llm::files::FileProvider;
+use golem_llm::fine_tuning::FineTuningProvider;
+use golem_llm::golem::llm::files::{FilePurpose, UploadedFile};
 use golem_llm::golem::llm::llm::{
     ChatEvent, ChatStream, Config, ContentPart, Error, ErrorCode, Guest, Message, ResponseMetadata,
     Role, StreamDelta, StreamEvent, ToolCall, ToolResult,
@@ -373,6 +378,35 @@ impl ExtendedGuest for AnthropicComponent {
     }
 }
 
+impl FineTuningProvider for AnthropicComponent {}
+
+impl FileProvider for AnthropicComponent {
+    fn upload(
+        name: String,
+        mime_type: String,
+        data: Vec<u8>,
+        purpose: FilePurpose,
+    ) -> Result<UploadedFile, Error> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        if purpose == FilePurpose::FineTune {
+            return Err(unsupported("fine-tuning files"));
+        }
+
+        let client = FilesApi::new(get_config_key(Self::ENV_VAR_NAME)?);
+        let file = client.upload_file(&name, &mime_type, &data)?;
+        Ok(file_metadata_to_uploaded_file(file, purpose))
+    }
+
+    fn delete(id: String) -> Result<(), Error> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = FilesApi::new(get_config_key(Self::ENV_VAR_NAME)?);
+        client.delete_file(&id)?;
+        Ok(())
+    }
+}
+
 type DurableAnthropicComponent = DurableLLM<AnthropicComponent>;
 
 golem_llm::export_llm!(DurableAnthropicComponent with_types_in golem_llm);
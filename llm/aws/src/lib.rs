@@ -0,0 +1,7 @@
+//! Support for running the AWS SDK in WebAssembly components, shared by the Amazon providers.
+//!
+//! `wasi_client` sends the requests of the SDK clients through `wasi:http`, and `async_utils`
+//! runs their futures on the `wstd` runtime.
+
+pub mod async_utils;
+pub mod wasi_client;
@@ -1,9 +1,14 @@
 use std::sync::Arc;
 
-use aws_sdk_bedrockruntime::{config, error::ConnectorError};
 use aws_smithy_runtime_api::{
-    client::http::{
-        HttpConnector, HttpConnectorFuture, HttpConnectorSettings, SharedHttpConnector,
+    client::{
+        http::{
+            HttpClient, HttpConnector, HttpConnectorFuture, HttpConnectorSettings,
+            SharedHttpConnector,
+        },
+        orchestrator::HttpRequest,
+        result::ConnectorError,
+        runtime_components::RuntimeComponents,
     },
     http::{Headers, Response, StatusCode},
 };
@@ -22,11 +27,17 @@ impl WasiClient {
     }
 }
 
-impl config::HttpClient for WasiClient {
+impl Default for WasiClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HttpClient for WasiClient {
     fn http_connector(
         &self,
         settings: &HttpConnectorSettings,
-        _components: &config::RuntimeComponents,
+        _components: &RuntimeComponents,
     ) -> SharedHttpConnector {
         let mut client = http::Client::new();
 
@@ -66,7 +77,7 @@ unsafe impl Sync for WasiConnector {}
 impl WasiConnector {
     async fn handle(
         &self,
-        request: config::http::HttpRequest,
+        request: HttpRequest,
     ) -> Result<http::Response<http::body::IncomingBody>, ConnectorError> {
         let method = Method::from_bytes(request.method().as_bytes()).expect("Valid http method");
         let url = request.uri().to_owned();
@@ -90,7 +101,7 @@ impl WasiConnector {
 }
 
 impl HttpConnector for SharedWasiConnector {
-    fn call(&self, request: config::http::HttpRequest) -> HttpConnectorFuture {
+    fn call(&self, request: HttpRequest) -> HttpConnectorFuture {
         let inner_clone = Arc::clone(&self.inner);
 
         let future = async move {
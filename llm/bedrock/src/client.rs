@@ -1,13 +1,11 @@
 use crate::{
-    async_utils::UnsafeFuture,
     conversions::{self, from_converse_sdk_error, from_converse_stream_sdk_error, BedrockInput},
     stream::BedrockChatStream,
-    wasi_client::WasiClient,
 };
-use aws_config::BehaviorVersion;
+use aws_config::{BehaviorVersion, SdkConfig};
 use aws_sdk_bedrockruntime::{
     self as bedrock,
-    config::{AsyncSleep, Sleep},
+    config::{http::HttpRequest, AsyncSleep, Sleep},
     operation::{
         converse::builders::ConverseFluentBuilder,
         converse_stream::builders::ConverseStreamFluentBuilder,
@@ -16,8 +14,10 @@ use aws_sdk_bedrockruntime::{
 use aws_types::region;
 use golem_llm::{
     config::{get_config_key, get_config_key_or_none},
+    correlation::{self, CORRELATION_ID_HEADER},
     golem::llm::llm,
 };
+use golem_llm_aws::{async_utils::UnsafeFuture, wasi_client::WasiClient};
 use log::trace;
 use wasi::clocks::monotonic_clock;
 use wstd::runtime::Reactor;
@@ -29,15 +29,7 @@ pub struct Bedrock {
 
 impl Bedrock {
     pub async fn new() -> Result<Self, llm::Error> {
-        let environment = BedrockEnvironment::load_from_env()?;
-
-        let sdk_config = aws_config::defaults(BehaviorVersion::latest())
-            .region(environment.aws_region())
-            .http_client(WasiClient::new())
-            .credentials_provider(environment.aws_credentials())
-            .sleep_impl(WasiSleep::new())
-            .load()
-            .await;
+        let sdk_config = load_sdk_config().await?;
         let client = bedrock::Client::new(&sdk_config);
         Ok(Self { client })
     }
@@ -57,6 +49,8 @@ impl Bedrock {
                 let model_id = input.model_id.clone();
                 let response = self
                     .init_converse(input)
+                    .customize()
+                    .mutate_request(with_correlation_id())
                     .send()
                     .await
                     .map_err(|e| from_converse_sdk_error(model_id, e));
@@ -94,6 +88,8 @@ impl Bedrock {
                 let model_id = input.model_id.clone();
                 let response = self
                     .init_converse_stream(input)
+                    .customize()
+                    .mutate_request(with_correlation_id())
                     .send()
                     .await
                     .map_err(|e| from_converse_stream_sdk_error(model_id, e));
@@ -133,6 +129,29 @@ impl Bedrock {
     }
 }
 
+/// Loads the AWS SDK configuration shared by the Bedrock runtime and control plane clients
+pub async fn load_sdk_config() -> Result<SdkConfig, llm::Error> {
+    let environment = BedrockEnvironment::load_from_env()?;
+
+    Ok(aws_config::defaults(BehaviorVersion::latest())
+        .region(environment.aws_region())
+        .http_client(WasiClient::new())
+        .credentials_provider(environment.aws_credentials())
+        .sleep_impl(WasiSleep::new())
+        .load()
+        .await)
+}
+
+/// Adds the correlation ID of the current operation to the request's headers
+pub fn with_correlation_id() -> impl Fn(&mut HttpRequest) + Send + Sync + 'static {
+    let correlation_id = correlation::current();
+    move |request| {
+        request
+            .headers_mut()
+            .insert(CORRELATION_ID_HEADER, correlation_id.clone());
+    }
+}
+
 #[derive(Debug)]
 pub struct BedrockEnvironment {
     access_key_id: String,
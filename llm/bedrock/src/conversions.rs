@@ -12,6 +12,7 @@ use aws_sdk_bedrockruntime::{
         ToolInputSchema, ToolSpecification, ToolUseBlock,
     },
 };
+use golem_llm::error::unsupported;
 use golem_llm::golem::llm::llm;
 use wstd::http;
 
@@ -180,6 +181,9 @@ async fn content_part_to_bedrock_content_blocks(
             llm::ContentPart::Image(image) => {
                 bedrock_content_blocks.push(image_ref_to_bedrock_image_content_block(image).await?);
             }
+            llm::ContentPart::File(_) => {
+                return Err(unsupported("file references in messages"));
+            }
         }
     }
 
@@ -0,0 +1,284 @@
+use crate::client::{load_sdk_config, with_correlation_id};
+use aws_sdk_bedrock::{
+    self as bedrock,
+    error::{ProvideErrorMetadata, SdkError},
+    types::{
+        CustomizationType, ModelCustomizationJobStatus, OutputDataConfig, TrainingDataConfig,
+        ValidationDataConfig, Validator,
+    },
+};
+use aws_smithy_types::{date_time::Format, DateTime};
+use golem_llm::{
+    config::get_config_key_or_none,
+    correlation,
+    golem::llm::{
+        fine_tuning::{Job, JobConfig, JobStatus},
+        llm,
+    },
+};
+use log::trace;
+use std::collections::HashMap;
+use std::fmt::Debug;
+
+const ROLE_ARN_OPTION: &str = "role_arn";
+const OUTPUT_S3_URI_OPTION: &str = "output_s3_uri";
+const JOB_NAME_OPTION: &str = "job_name";
+const CUSTOMIZATION_TYPE_OPTION: &str = "customization_type";
+
+const ROLE_ARN_ENV_VAR_NAME: &str = "BEDROCK_CUSTOMIZATION_ROLE_ARN";
+const OUTPUT_S3_URI_ENV_VAR_NAME: &str = "BEDROCK_CUSTOMIZATION_OUTPUT_S3_URI";
+
+/// Client of the Bedrock control plane, managing model customization jobs
+#[derive(Debug)]
+pub struct BedrockCustomization {
+    client: bedrock::Client,
+}
+
+impl BedrockCustomization {
+    pub async fn new() -> Result<Self, llm::Error> {
+        let sdk_config = load_sdk_config().await?;
+        let client = bedrock::Client::new(&sdk_config);
+        Ok(Self { client })
+    }
+
+    /// Starts a model customization job. The IAM role assumed by Bedrock and the S3 location of
+    /// the outputs are taken from the `role_arn` and `output_s3_uri` provider options, or from the
+    /// environment.
+    pub async fn create_job(&self, config: JobConfig) -> Result<Job, llm::Error> {
+        let options: HashMap<String, String> = config
+            .provider_options
+            .into_iter()
+            .map(|kv| (kv.key, kv.value))
+            .collect();
+        let role_arn = option_or_env(&options, ROLE_ARN_OPTION, ROLE_ARN_ENV_VAR_NAME)?;
+        let output_s3_uri =
+            option_or_env(&options, OUTPUT_S3_URI_OPTION, OUTPUT_S3_URI_ENV_VAR_NAME)?;
+        let job_name = options
+            .get(JOB_NAME_OPTION)
+            .cloned()
+            .unwrap_or_else(|| format!("golem-llm-{}", correlation::current()));
+        let custom_model_name = match &config.suffix {
+            Some(suffix) => format!("{job_name}-{suffix}"),
+            None => job_name.clone(),
+        };
+        let customization_type = options
+            .get(CUSTOMIZATION_TYPE_OPTION)
+            .map(|value| CustomizationType::from(value.as_str()))
+            .unwrap_or(CustomizationType::FineTuning);
+
+        let validation_data_config = match config.validation_data {
+            Some(s3_uri) => Some(
+                Validator::builder()
+                    .s3_uri(s3_uri)
+                    .build()
+                    .and_then(|validator| {
+                        ValidationDataConfig::builder()
+                            .validators(validator)
+                            .build()
+                    })
+                    .map_err(|err| invalid_request(format!("Invalid validation data: {err}")))?,
+            ),
+            None => None,
+        };
+        let output_data_config = OutputDataConfig::builder()
+            .s3_uri(output_s3_uri)
+            .build()
+            .map_err(|err| invalid_request(format!("Invalid output data config: {err}")))?;
+
+        trace!("Creating Bedrock model customization job {job_name} for {custom_model_name}");
+
+        let output = self
+            .client
+            .create_model_customization_job()
+            .job_name(&job_name)
+            .custom_model_name(custom_model_name)
+            .role_arn(role_arn)
+            .base_model_identifier(&config.base_model)
+            .customization_type(customization_type)
+            .training_data_config(
+                TrainingDataConfig::builder()
+                    .s3_uri(config.training_data)
+                    .build(),
+            )
+            .set_validation_data_config(validation_data_config)
+            .output_data_config(output_data_config)
+            .set_hyper_parameters(Some(
+                config
+                    .hyperparameters
+                    .into_iter()
+                    .map(|kv| (kv.key, kv.value))
+                    .collect(),
+            ))
+            .customize()
+            .mutate_request(with_correlation_id())
+            .send()
+            .await
+            .map_err(|err| from_sdk_error("creating model customization job", err))?;
+
+        self.get_job(output.job_arn().to_string()).await
+    }
+
+    pub async fn get_job(&self, id: String) -> Result<Job, llm::Error> {
+        let output = self
+            .client
+            .get_model_customization_job()
+            .job_identifier(&id)
+            .customize()
+            .mutate_request(with_correlation_id())
+            .send()
+            .await
+            .map_err(|err| from_sdk_error("getting model customization job", err))?;
+
+        trace!("Received Bedrock model customization job {id}: {output:?}");
+
+        Ok(Job {
+            id: output.job_arn().to_string(),
+            base_model: output.base_model_arn().to_string(),
+            status: output
+                .status()
+                .map(to_job_status)
+                .unwrap_or(JobStatus::Queued),
+            fine_tuned_model: output.output_model_arn().map(str::to_string),
+            created_at: format_date_time(output.creation_time()),
+            finished_at: output.end_time().and_then(format_date_time),
+            error_message: output.failure_message().map(str::to_string),
+        })
+    }
+
+    pub async fn cancel_job(&self, id: String) -> Result<Job, llm::Error> {
+        self.client
+            .stop_model_customization_job()
+            .job_identifier(&id)
+            .customize()
+            .mutate_request(with_correlation_id())
+            .send()
+            .await
+            .map_err(|err| from_sdk_error("stopping model customization job", err))?;
+
+        self.get_job(id).await
+    }
+
+    /// Lists all the model customization jobs, following the pagination
+    pub async fn list_jobs(&self) -> Result<Vec<Job>, llm::Error> {
+        let mut jobs = Vec::new();
+        let mut next_token = None;
+        loop {
+            let output = self
+                .client
+                .list_model_customization_jobs()
+                .set_next_token(next_token)
+                .customize()
+                .mutate_request(with_correlation_id())
+                .send()
+                .await
+                .map_err(|err| from_sdk_error("listing model customization jobs", err))?;
+
+            jobs.extend(
+                output
+                    .model_customization_job_summaries()
+                    .iter()
+                    .map(|summary| Job {
+                        id: summary.job_arn().to_string(),
+                        base_model: summary.base_model_arn().to_string(),
+                        status: to_job_status(summary.status()),
+                        fine_tuned_model: summary.custom_model_arn().map(str::to_string),
+                        created_at: format_date_time(summary.creation_time()),
+                        finished_at: summary.end_time().and_then(format_date_time),
+                        error_message: None,
+                    }),
+            );
+
+            next_token = output.next_token().map(str::to_string);
+            if next_token.is_none() {
+                return Ok(jobs);
+            }
+        }
+    }
+
+    /// Lists the ARNs of all the custom models, following the pagination
+    pub async fn list_models(&self) -> Result<Vec<String>, llm::Error> {
+        let mut models = Vec::new();
+        let mut next_token = None;
+        loop {
+            let output = self
+                .client
+                .list_custom_models()
+                .set_next_token(next_token)
+                .customize()
+                .mutate_request(with_correlation_id())
+                .send()
+                .await
+                .map_err(|err| from_sdk_error("listing custom models", err))?;
+
+            models.extend(
+                output
+                    .model_summaries()
+                    .iter()
+                    .map(|summary| summary.model_arn().to_string()),
+            );
+
+            next_token = output.next_token().map(str::to_string);
+            if next_token.is_none() {
+                return Ok(models);
+            }
+        }
+    }
+}
+
+fn option_or_env(
+    options: &HashMap<String, String>,
+    option: &str,
+    env_var_name: &str,
+) -> Result<String, llm::Error> {
+    options
+        .get(option)
+        .cloned()
+        .or_else(|| get_config_key_or_none(env_var_name))
+        .ok_or_else(|| {
+            invalid_request(format!(
+                "Missing the `{option}` provider option or the {env_var_name} config key"
+            ))
+        })
+}
+
+fn to_job_status(status: &ModelCustomizationJobStatus) -> JobStatus {
+    match status {
+        ModelCustomizationJobStatus::Completed => JobStatus::Succeeded,
+        ModelCustomizationJobStatus::Failed => JobStatus::Failed,
+        ModelCustomizationJobStatus::Stopping | ModelCustomizationJobStatus::Stopped => {
+            JobStatus::Cancelled
+        }
+        _ => JobStatus::Running,
+    }
+}
+
+fn format_date_time(date_time: &DateTime) -> Option<String> {
+    date_time.fmt(Format::DateTime).ok()
+}
+
+fn invalid_request(message: String) -> llm::Error {
+    llm::Error {
+        code: llm::ErrorCode::InvalidRequest,
+        message,
+        provider_error_json: None,
+    }
+}
+
+fn from_sdk_error<E: ProvideErrorMetadata + Debug, R: Debug>(
+    operation: &str,
+    sdk_error: SdkError<E, R>,
+) -> llm::Error {
+    let code = match sdk_error.code() {
+        Some("ValidationException") | Some("ResourceNotFoundException") => {
+            llm::ErrorCode::InvalidRequest
+        }
+        Some("AccessDeniedException") => llm::ErrorCode::AuthenticationFailed,
+        Some("ThrottlingException") => llm::ErrorCode::RateLimitExceeded,
+        _ => llm::ErrorCode::InternalError,
+    };
+    llm::Error {
+        code,
+        message: format!("Error {operation} in Bedrock: {sdk_error:?}"),
+        provider_error_json: None,
+    }
+}
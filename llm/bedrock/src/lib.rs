@@ -1,18 +1,21 @@
-use async_utils::get_async_runtime;
 use client::Bedrock;
+use customization::BedrockCustomization;
 use golem_llm::{
     durability::{DurableLLM, ExtendedGuest},
+    files::FileProvider,
+    fine_tuning::FineTuningProvider,
+    golem::llm::fine_tuning::{Job, JobConfig},
     golem::llm::llm::{self, ChatEvent, ChatStream, Config, Guest, Message, ToolCall, ToolResult},
     LOGGING_STATE,
 };
+use golem_llm_aws::async_utils::get_async_runtime;
 use golem_rust::bindings::wasi::clocks::monotonic_clock;
 use stream::BedrockChatStream;
 
-mod async_utils;
 mod client;
 mod conversions;
+mod customization;
 mod stream;
-mod wasi_client;
 
 struct BedrockComponent;
 
@@ -139,6 +142,43 @@ async fn get_bedrock_client() -> Result<Bedrock, llm::Error> {
     Bedrock::new().await
 }
 
+impl FineTuningProvider for BedrockComponent {
+    fn create_job(config: JobConfig) -> Result<Job, llm::Error> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        get_async_runtime()
+            .block_on(async { BedrockCustomization::new().await?.create_job(config).await })
+    }
+
+    fn get_job(id: String) -> Result<Job, llm::Error> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        get_async_runtime().block_on(async { BedrockCustomization::new().await?.get_job(id).await })
+    }
+
+    fn cancel_job(id: String) -> Result<Job, llm::Error> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        get_async_runtime()
+            .block_on(async { BedrockCustomization::new().await?.cancel_job(id).await })
+    }
+
+    fn list_jobs() -> Result<Vec<Job>, llm::Error> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        get_async_runtime().block_on(async { BedrockCustomization::new().await?.list_jobs().await })
+    }
+
+    fn list_models() -> Result<Vec<String>, llm::Error> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        get_async_runtime()
+            .block_on(async { BedrockCustomization::new().await?.list_models().await })
+    }
+}
+
+impl FileProvider for BedrockComponent {}
+
 type DurableBedrockComponent = DurableLLM<BedrockComponent>;
 
 golem_llm::export_llm!(DurableBedrockComponent with_types_in golem_llm);
@@ -5,10 +5,8 @@ use aws_sdk_bedrockruntime::{
 use golem_llm::golem::llm::llm;
 use std::cell::{RefCell, RefMut};
 
-use crate::{
-    async_utils,
-    conversions::{converse_stream_output_to_stream_event, custom_error, merge_metadata},
-};
+use crate::conversions::{converse_stream_output_to_stream_event, custom_error, merge_metadata};
+use golem_llm_aws::async_utils;
 
 type BedrockEventSource =
     EventReceiver<bedrock::types::ConverseStreamOutput, ConverseStreamOutputError>;
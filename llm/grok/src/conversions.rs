@@ -1,5 +1,6 @@
 use crate::client::{CompletionsRequest, CompletionsResponse, Detail, Effort};
 use base64::{engine::general_purpose, Engine as _};
+use golem_llm::files::reject_file_references;
 use golem_llm::golem::llm::llm::{
     ChatEvent, CompleteResponse, Config, ContentPart, Error, ErrorCode, FinishReason, ImageDetail,
     ImageReference, Message, ResponseMetadata, Role, ToolCall, ToolDefinition, ToolResult, Usage,
@@ -10,6 +11,8 @@ pub fn messages_to_request(
     messages: Vec<Message>,
     config: Config,
 ) -> Result<CompletionsRequest, Error> {
+    reject_file_references(&messages)?;
+
     let options = config
         .provider_options
         .into_iter()
@@ -189,6 +192,7 @@ fn convert_content_parts(contents: Vec<ContentPart>) -> crate::client::Content {
                     });
                 }
             },
+            ContentPart::File(_) => {} // Rejected by `messages_to_request`
         }
     }
     crate::client::Content::List(result)
@@ -10,6 +10,8 @@ use golem_llm::chat_stream::{LlmChatStream, LlmChatStreamState};
 use golem_llm::config::with_config_key;
 use golem_llm::durability::{DurableLLM, ExtendedGuest};
 use golem_llm::event_source::EventSource;
+use golem_llm::files::FileProvider;
+use golem_llm::fine_tuning::FineTuningProvider;
 use golem_llm::golem::llm::llm::{
     ChatEvent, ChatStream, Config, ContentPart, Error, FinishReason, Guest, Message,
     ResponseMetadata, StreamDelta, StreamEvent, ToolCall, ToolResult,
@@ -203,6 +205,10 @@ impl ExtendedGuest for GrokComponent {
     }
 }
 
+impl FineTuningProvider for GrokComponent {}
+
+impl FileProvider for GrokComponent {}
+
 type DurableGrokComponent = DurableLLM<GrokComponent>;
 
 golem_llm::export_llm!(DurableGrokComponent with_types_in golem_llm);
@@ -0,0 +1,410 @@
+//! Cost ceiling for LLM calls, protecting against runaway agent loops.
+//!
+//! A budget (in the currency the token prices are given in, typically USD) can be set per call
+//! with the `max_cost` provider option, and for the whole worker with the
+//! `GOLEM_LLM_MAX_COST_PER_WORKER` environment variable. When both are set, the smaller remaining
+//! amount applies.
+//!
+//! There is no built-in pricing table, so the token prices have to be configured as well, either
+//! with the `input_cost_per_million_tokens` and `output_cost_per_million_tokens` provider options
+//! or with the `GOLEM_LLM_INPUT_COST_PER_MILLION_TOKENS` and
+//! `GOLEM_LLM_OUTPUT_COST_PER_MILLION_TOKENS` environment variables.
+//!
+//! Before each call the prompt cost is estimated and the request is rejected with a
+//! `budget-exceeded` error if it does not fit, otherwise `max_tokens` is capped to what the
+//! remaining budget can pay for. Streams are aborted with the same error as soon as their running
+//! cost exceeds the budget.
+
+use crate::config::get_config_key_or_none;
+use crate::golem::llm::llm::{
+    ChatEvent, Config, ContentPart, Error, ErrorCode, Message, StreamEvent, ToolCall, Usage,
+};
+use std::cell::Cell;
+
+const MAX_COST_OPTION: &str = "max_cost";
+const INPUT_PRICE_OPTION: &str = "input_cost_per_million_tokens";
+const OUTPUT_PRICE_OPTION: &str = "output_cost_per_million_tokens";
+const MAX_WORKER_COST_ENV_VAR: &str = "GOLEM_LLM_MAX_COST_PER_WORKER";
+const INPUT_PRICE_ENV_VAR: &str = "GOLEM_LLM_INPUT_COST_PER_MILLION_TOKENS";
+const OUTPUT_PRICE_ENV_VAR: &str = "GOLEM_LLM_OUTPUT_COST_PER_MILLION_TOKENS";
+
+thread_local! {
+    /// The total estimated cost of the calls made by this worker so far
+    static WORKER_SPENT: Cell<f64> = const { Cell::new(0.0) };
+}
+
+/// Sends the request through `send`, enforcing the configured budget
+pub fn send(
+    messages: Vec<Message>,
+    config: Config,
+    send: impl FnOnce(Vec<Message>, Config) -> ChatEvent,
+) -> ChatEvent {
+    match prepare(&messages, config) {
+        Ok((config, None)) => send(messages, config),
+        Ok((config, Some(budget))) => {
+            let event = send(messages, config);
+            budget.record_event(&event);
+            event
+        }
+        Err(error) => ChatEvent::Error(error),
+    }
+}
+
+/// Checks the budget for a request and caps its `max_tokens`. Returns `None` as budget if no
+/// cost ceiling is configured.
+pub fn prepare(
+    messages: &[Message],
+    mut config: Config,
+) -> Result<(Config, Option<Budget>), Error> {
+    let Some(limit) = limit(&config)? else {
+        return Ok((config, None));
+    };
+    let (input_price, output_price) = prices(&config)?;
+
+    let prompt_tokens = estimate_prompt_tokens(messages);
+    let prompt_cost = prompt_tokens as f64 * input_price;
+    if prompt_cost >= limit {
+        return Err(budget_exceeded(prompt_cost, limit));
+    }
+
+    if output_price > 0.0 {
+        let affordable = ((limit - prompt_cost) / output_price)
+            .floor()
+            .min(u32::MAX as f64) as u32;
+        config.max_tokens = Some(
+            config
+                .max_tokens
+                .map_or(affordable, |max| max.min(affordable)),
+        );
+    }
+
+    Ok((
+        config,
+        Some(Budget {
+            input_price,
+            output_price,
+            limit,
+            prompt_tokens,
+        }),
+    ))
+}
+
+/// The budget of a single call
+#[derive(Debug, Clone, PartialEq)]
+pub struct Budget {
+    input_price: f64,
+    output_price: f64,
+    limit: f64,
+    prompt_tokens: u32,
+}
+
+impl Budget {
+    fn cost(&self, input_tokens: u32, output_tokens: u32) -> f64 {
+        input_tokens as f64 * self.input_price + output_tokens as f64 * self.output_price
+    }
+
+    fn record_event(&self, event: &ChatEvent) {
+        let cost = match event {
+            ChatEvent::Message(response) => match &response.metadata.usage {
+                Some(Usage {
+                    input_tokens: Some(input_tokens),
+                    output_tokens: Some(output_tokens),
+                    ..
+                }) => self.cost(*input_tokens, *output_tokens),
+                _ => self.cost(
+                    self.prompt_tokens,
+                    estimate_content_tokens(&response.content)
+                        + estimate_tool_call_tokens(&response.tool_calls),
+                ),
+            },
+            ChatEvent::ToolRequest(tool_calls) => {
+                self.cost(self.prompt_tokens, estimate_tool_call_tokens(tool_calls))
+            }
+            ChatEvent::Error(_) => 0.0,
+        };
+        WORKER_SPENT.with(|spent| spent.set(spent.get() + cost));
+    }
+}
+
+/// Tracks the running cost of a stream and aborts it when the budget is exceeded
+#[derive(Debug, Clone, PartialEq)]
+pub struct StreamBudget {
+    budget: Option<Budget>,
+    output_tokens: u32,
+    pending_error: Option<Error>,
+    exhausted: bool,
+}
+
+impl StreamBudget {
+    pub fn new(budget: Option<Budget>) -> Self {
+        if let Some(budget) = &budget {
+            WORKER_SPENT
+                .with(|spent| spent.set(spent.get() + budget.cost(budget.prompt_tokens, 0)));
+        }
+        Self {
+            budget,
+            output_tokens: 0,
+            pending_error: None,
+            exhausted: false,
+        }
+    }
+
+    /// A stream that was rejected before it started
+    pub fn rejected(error: Error) -> Self {
+        Self {
+            budget: None,
+            output_tokens: 0,
+            pending_error: Some(error),
+            exhausted: true,
+        }
+    }
+
+    /// Returns the events the stream should produce without polling the underlying stream, if it
+    /// was rejected or aborted
+    pub fn exhausted_events(&mut self) -> Option<Vec<StreamEvent>> {
+        if self.exhausted {
+            Some(
+                self.pending_error
+                    .take()
+                    .map(StreamEvent::Error)
+                    .into_iter()
+                    .collect(),
+            )
+        } else {
+            None
+        }
+    }
+
+    /// Accounts for the received events, truncating them with a `budget-exceeded` error when the
+    /// running cost goes over the budget
+    pub fn track(&mut self, events: Vec<StreamEvent>) -> Vec<StreamEvent> {
+        let Some(budget) = &self.budget else {
+            return events;
+        };
+
+        let mut result = Vec::with_capacity(events.len());
+        for event in events {
+            let tokens = match &event {
+                StreamEvent::Delta(delta) => {
+                    delta.content.as_deref().map_or(0, estimate_content_tokens)
+                        + delta
+                            .tool_calls
+                            .as_deref()
+                            .map_or(0, estimate_tool_call_tokens)
+                }
+                _ => 0,
+            };
+            let output_tokens = self.output_tokens + tokens;
+            WORKER_SPENT.with(|spent| spent.set(spent.get() + budget.cost(0, tokens)));
+
+            let cost = budget.cost(budget.prompt_tokens, output_tokens);
+            if cost > budget.limit {
+                result.push(StreamEvent::Error(budget_exceeded(cost, budget.limit)));
+                self.exhausted = true;
+                break;
+            }
+
+            self.output_tokens = output_tokens;
+            result.push(event);
+        }
+        result
+    }
+}
+
+pub fn budget_exceeded(cost: f64, limit: f64) -> Error {
+    Error {
+        code: ErrorCode::BudgetExceeded,
+        message: format!("Estimated cost {cost:.6} exceeds the remaining budget {limit:.6}"),
+        provider_error_json: None,
+    }
+}
+
+fn limit(config: &Config) -> Result<Option<f64>, Error> {
+    let per_call = provider_option(config, MAX_COST_OPTION)
+        .map(|value| parse_amount(MAX_COST_OPTION, &value))
+        .transpose()?;
+    let per_worker = get_config_key_or_none(MAX_WORKER_COST_ENV_VAR)
+        .map(|value| parse_amount(MAX_WORKER_COST_ENV_VAR, &value))
+        .transpose()?
+        .map(|max| max - WORKER_SPENT.with(|spent| spent.get()));
+
+    Ok(match (per_call, per_worker) {
+        (Some(per_call), Some(per_worker)) => Some(per_call.min(per_worker)),
+        (limit, None) | (None, limit) => limit,
+    })
+}
+
+/// Returns the input and output token prices, per token
+fn prices(config: &Config) -> Result<(f64, f64), Error> {
+    let price = |option: &str, env_var: &str| -> Result<f64, Error> {
+        match provider_option(config, option).or_else(|| get_config_key_or_none(env_var)) {
+            Some(value) => Ok(parse_amount(option, &value)? / 1_000_000.0),
+            None => Err(Error {
+                code: ErrorCode::InvalidRequest,
+                message: format!(
+                    "A cost budget is configured, but the token price is missing ({option} or {env_var})"
+                ),
+                provider_error_json: None,
+            }),
+        }
+    };
+    Ok((
+        price(INPUT_PRICE_OPTION, INPUT_PRICE_ENV_VAR)?,
+        price(OUTPUT_PRICE_OPTION, OUTPUT_PRICE_ENV_VAR)?,
+    ))
+}
+
+fn provider_option(config: &Config, key: &str) -> Option<String> {
+    config
+        .provider_options
+        .iter()
+        .find(|kv| kv.key == key)
+        .map(|kv| kv.value.clone())
+}
+
+fn parse_amount(key: &str, value: &str) -> Result<f64, Error> {
+    value
+        .trim()
+        .parse::<f64>()
+        .ok()
+        .filter(|amount| amount.is_finite() && *amount >= 0.0)
+        .ok_or_else(|| Error {
+            code: ErrorCode::InvalidRequest,
+            message: format!("Invalid value for {key}: {value}"),
+            provider_error_json: None,
+        })
+}
+
+/// Rough token estimate of about four characters per token. Images are not counted.
+fn estimate_text_tokens(text: &str) -> u32 {
+    text.chars().count().div_ceil(4) as u32
+}
+
+fn estimate_content_tokens(content: &[ContentPart]) -> u32 {
+    content
+        .iter()
+        .map(|part| match part {
+            ContentPart::Text(text) => estimate_text_tokens(text),
+            ContentPart::Image(_) | ContentPart::File(_) => 0,
+        })
+        .sum()
+}
+
+fn estimate_tool_call_tokens(tool_calls: &[ToolCall]) -> u32 {
+    tool_calls
+        .iter()
+        .map(|call| estimate_text_tokens(&call.name) + estimate_text_tokens(&call.arguments_json))
+        .sum()
+}
+
+fn estimate_prompt_tokens(messages: &[Message]) -> u32 {
+    messages
+        .iter()
+        .map(|message| estimate_content_tokens(&message.content))
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::golem::llm::llm::{Kv, Role, StreamDelta};
+
+    fn config(options: &[(&str, &str)]) -> Config {
+        Config {
+            model: "gpt-4o".to_string(),
+            temperature: None,
+            max_tokens: Some(1000),
+            stop_sequences: None,
+            tools: vec![],
+            tool_choice: None,
+            provider_options: options
+                .iter()
+                .map(|(key, value)| Kv {
+                    key: key.to_string(),
+                    value: value.to_string(),
+                })
+                .collect(),
+        }
+    }
+
+    fn user_message(text: &str) -> Message {
+        Message {
+            role: Role::User,
+            name: None,
+            content: vec![ContentPart::Text(text.to_string())],
+        }
+    }
+
+    fn text_delta(text: &str) -> StreamEvent {
+        StreamEvent::Delta(StreamDelta {
+            content: Some(vec![ContentPart::Text(text.to_string())]),
+            tool_calls: None,
+        })
+    }
+
+    #[test]
+    fn no_budget_leaves_config_untouched() {
+        let original = config(&[]);
+        let (config, budget) = prepare(&[user_message("Hello")], original.clone()).unwrap();
+        assert_eq!(config, original);
+        assert_eq!(budget, None);
+    }
+
+    #[test]
+    fn max_tokens_is_capped_to_budget() {
+        // 1 token prompt at $1/M, output at $10/M, budget of $0.001 leaves room for 99 tokens
+        let (config, budget) = prepare(
+            &[user_message("Hey")],
+            config(&[
+                ("max_cost", "0.001"),
+                ("input_cost_per_million_tokens", "1"),
+                ("output_cost_per_million_tokens", "10"),
+            ]),
+        )
+        .unwrap();
+        assert!(budget.is_some());
+        assert_eq!(config.max_tokens, Some(99));
+    }
+
+    #[test]
+    fn too_expensive_prompt_is_rejected() {
+        let result = prepare(
+            &[user_message(&"x".repeat(4000))],
+            config(&[
+                ("max_cost", "0.0001"),
+                ("input_cost_per_million_tokens", "1"),
+                ("output_cost_per_million_tokens", "1"),
+            ]),
+        );
+        assert_eq!(result.unwrap_err().code, ErrorCode::BudgetExceeded);
+    }
+
+    #[test]
+    fn budget_without_prices_is_invalid() {
+        let result = prepare(&[user_message("Hey")], config(&[("max_cost", "1")]));
+        assert_eq!(result.unwrap_err().code, ErrorCode::InvalidRequest);
+    }
+
+    #[test]
+    fn stream_is_aborted_when_budget_is_exceeded() {
+        let (_, budget) = prepare(
+            &[user_message("Hey")],
+            config(&[
+                ("max_cost", "0.00001"),
+                ("input_cost_per_million_tokens", "1"),
+                ("output_cost_per_million_tokens", "1"),
+            ]),
+        )
+        .unwrap();
+        let mut budget = StreamBudget::new(budget);
+
+        let events = budget.track(vec![text_delta("abcd"), text_delta(&"y".repeat(100))]);
+        assert_eq!(events.len(), 2);
+        assert!(matches!(events[0], StreamEvent::Delta(_)));
+        match &events[1] {
+            StreamEvent::Error(error) => assert_eq!(error.code, ErrorCode::BudgetExceeded),
+            other => panic!("Expected a budget error, got {other:?}"),
+        }
+        assert_eq!(budget.exhausted_events(), Some(vec![]));
+    }
+}
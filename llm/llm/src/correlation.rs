@@ -0,0 +1,122 @@
+//! Correlation IDs identifying the worker's logical operations in the providers' logs.
+//!
+//! Every `send`, `continue` and `stream` call, and every fine-tuning operation, starts a new
+//! correlation ID. The clients send it in the `X-Request-Id` header of their requests, it is
+//! logged when the operation starts, and it is appended to the message of the errors returned by
+//! the operation, so an entry of the worker's oplog can be matched with the provider-side logs of
+//! the same request.
+
+use crate::deterministic;
+use crate::golem::llm::llm::{ChatEvent, Error, StreamEvent};
+use log::debug;
+use std::cell::RefCell;
+
+/// Header carrying the correlation ID of the outgoing requests
+pub const CORRELATION_ID_HEADER: &str = "X-Request-Id";
+
+thread_local! {
+    static CORRELATION_ID: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// Starts a new logical operation and returns its correlation ID
+pub fn begin(operation: &str) -> String {
+    let id = generate();
+    debug!("Starting {operation} with correlation ID {id}");
+    CORRELATION_ID.with_borrow_mut(|current| *current = Some(id.clone()));
+    id
+}
+
+/// The correlation ID of the current logical operation, starting one if there is none yet
+pub fn current() -> String {
+    CORRELATION_ID
+        .with_borrow(|current| current.clone())
+        .unwrap_or_else(|| begin("operation"))
+}
+
+/// Runs a `send` or `continue` operation with a new correlation ID
+pub fn send(operation: &str, f: impl FnOnce() -> ChatEvent) -> ChatEvent {
+    let id = begin(operation);
+    match f() {
+        ChatEvent::Error(error) => ChatEvent::Error(annotate(error, &id)),
+        event => event,
+    }
+}
+
+/// Runs an operation returning a `Result` with a new correlation ID
+pub fn call<T>(operation: &str, f: impl FnOnce() -> Result<T, Error>) -> Result<T, Error> {
+    let id = begin(operation);
+    f().map_err(|error| annotate(error, &id))
+}
+
+/// Appends the correlation ID to the errors among the stream events
+pub fn annotate_events(events: Vec<StreamEvent>, id: &str) -> Vec<StreamEvent> {
+    events
+        .into_iter()
+        .map(|event| match event {
+            StreamEvent::Error(error) => StreamEvent::Error(annotate(error, id)),
+            event => event,
+        })
+        .collect()
+}
+
+/// Appends the correlation ID to the error's message
+pub fn annotate(error: Error, id: &str) -> Error {
+    if error.message.contains(id) {
+        return error;
+    }
+    Error {
+        message: format!("{} (correlation ID: {id})", error.message),
+        ..error
+    }
+}
+
+/// A random 128 bit identifier in hexadecimal, stable across replays
+fn generate() -> String {
+    deterministic::random_id()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::golem::llm::llm::ErrorCode;
+
+    fn error() -> Error {
+        Error {
+            code: ErrorCode::InternalError,
+            message: "Request failed with 500".to_string(),
+            provider_error_json: None,
+        }
+    }
+
+    #[test]
+    fn operations_get_distinct_ids() {
+        let first = begin("send");
+        assert_eq!(current(), first);
+        assert_eq!(first.len(), 32);
+
+        let second = begin("send");
+        assert_ne!(first, second);
+        assert_eq!(current(), second);
+    }
+
+    #[test]
+    fn errors_are_annotated_once() {
+        let id = "0123456789abcdef0123456789abcdef";
+        let annotated = annotate(annotate(error(), id), id);
+        assert_eq!(
+            annotated.message,
+            format!("Request failed with 500 (correlation ID: {id})")
+        );
+    }
+
+    #[test]
+    fn send_annotates_errors_with_the_new_id() {
+        let event = send("send", || ChatEvent::Error(error()));
+        let ChatEvent::Error(error) = event else {
+            panic!("expected an error");
+        };
+        assert!(error
+            .message
+            .ends_with(&format!("(correlation ID: {})", current())));
+    }
+}
@@ -0,0 +1,277 @@
+//! Detection of durable replay divergences.
+//!
+//! A durable chat stream replays the deltas persisted in the oplog, then continues with a live
+//! stream prompted with the partial response, asking the model to carry on from where it was
+//! interrupted. When the model starts its response over instead, the live continuation repeats
+//! the text or the tool calls that were already delivered. Such divergences are logged with a
+//! report comparing digests of the persisted and live responses, and counted by the worker, see
+//! `divergence_count`.
+//!
+//! Only the first live batch is compared, since that is where a restarted response shows.
+
+use crate::golem::llm::llm::{ContentPart, StreamDelta, StreamEvent};
+use log::warn;
+use std::cell::Cell;
+use std::collections::HashSet;
+use std::fmt::{Display, Formatter};
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+/// Length of the repeated beginning of the persisted text from which a live continuation is
+/// considered to have started the response over
+const MIN_REPEATED_CHARS: usize = 16;
+
+thread_local! {
+    /// The number of divergences of the durable chat streams of this worker
+    static DIVERGENCES: Cell<u64> = const { Cell::new(0) };
+}
+
+/// A difference between the response persisted in the oplog and the live continuation of the
+/// provider
+#[derive(Debug, Clone, PartialEq)]
+pub struct DivergenceReport {
+    pub model: String,
+    pub persisted_deltas: usize,
+    pub persisted_digest: String,
+    pub live_deltas: usize,
+    pub live_digest: String,
+    /// Number of characters of the persisted text that the live continuation starts with again
+    pub repeated_chars: usize,
+    /// Number of live tool calls that were already delivered from the oplog
+    pub repeated_tool_calls: usize,
+}
+
+impl Display for DivergenceReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "model={} persisted_deltas={} persisted_digest={} live_deltas={} live_digest={} repeated_chars={} repeated_tool_calls={}",
+            self.model,
+            self.persisted_deltas,
+            self.persisted_digest,
+            self.live_deltas,
+            self.live_digest,
+            self.repeated_chars,
+            self.repeated_tool_calls
+        )
+    }
+}
+
+/// A stable digest of the text and tool calls of a list of deltas, in order: the 64-bit FNV-1a
+/// hash of their text and tool call ids, in hexadecimal
+pub fn digest<'a>(deltas: impl IntoIterator<Item = &'a StreamDelta>) -> String {
+    fnv_digest(deltas.into_iter().flat_map(|delta| {
+        let tool_call_ids = delta
+            .tool_calls
+            .iter()
+            .flatten()
+            .map(|call| call.id.as_str());
+        text_parts(delta).chain(tool_call_ids)
+    }))
+}
+
+/// Compares the deltas persisted in the oplog with the first events of the live continuation of
+/// the stream, returning a report when the continuation repeats the persisted response
+pub fn compare_deltas(
+    model: &str,
+    persisted: &[StreamDelta],
+    live: &[StreamEvent],
+) -> Option<DivergenceReport> {
+    let live = live
+        .iter()
+        .filter_map(|event| match event {
+            StreamEvent::Delta(delta) => Some(delta),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    let persisted_text = persisted.iter().flat_map(text_parts).collect::<String>();
+    let live_text = live
+        .iter()
+        .flat_map(|delta| text_parts(delta))
+        .collect::<String>();
+    let common_chars = persisted_text
+        .chars()
+        .zip(live_text.chars())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let min_repeated_chars = MIN_REPEATED_CHARS.min(persisted_text.chars().count());
+    let repeated_chars = if common_chars >= min_repeated_chars {
+        common_chars
+    } else {
+        0
+    };
+
+    let persisted_ids: HashSet<&str> = persisted
+        .iter()
+        .flat_map(|delta| delta.tool_calls.iter().flatten())
+        .map(|call| call.id.as_str())
+        .collect();
+    let repeated_tool_calls = live
+        .iter()
+        .flat_map(|delta| delta.tool_calls.iter().flatten())
+        .filter(|call| persisted_ids.contains(call.id.as_str()))
+        .count();
+
+    (repeated_chars > 0 || repeated_tool_calls > 0).then(|| DivergenceReport {
+        model: model.to_string(),
+        persisted_deltas: persisted.len(),
+        persisted_digest: digest(persisted),
+        live_deltas: live.len(),
+        live_digest: digest(live.iter().copied()),
+        repeated_chars,
+        repeated_tool_calls,
+    })
+}
+
+/// Logs a divergence and counts it
+pub fn report(report: &DivergenceReport) {
+    warn!("Durable replay diverged from the provider's live response: {report}");
+    DIVERGENCES.set(DIVERGENCES.get() + 1);
+}
+
+/// The number of divergences reported since the worker started
+pub fn divergence_count() -> u64 {
+    DIVERGENCES.get()
+}
+
+fn text_parts(delta: &StreamDelta) -> impl Iterator<Item = &str> {
+    delta
+        .content
+        .iter()
+        .flatten()
+        .filter_map(|part| match part {
+            ContentPart::Text(text) => Some(text.as_str()),
+            _ => None,
+        })
+}
+
+/// The 64-bit FNV-1a hash of `parts`, each followed by a newline, in hexadecimal.
+///
+/// golem-search and golem-llm keep identical copies of this function in their `divergence`
+/// modules, so that the digests of both report the same way.
+fn fnv_digest<'a>(parts: impl IntoIterator<Item = &'a str>) -> String {
+    let mut hash = FNV_OFFSET_BASIS;
+    for part in parts {
+        for byte in part.bytes().chain(std::iter::once(b'\n')) {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(FNV_PRIME);
+        }
+    }
+    format!("{hash:016x}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::golem::llm::llm::ToolCall;
+
+    fn text(text: &str) -> StreamDelta {
+        StreamDelta {
+            content: Some(vec![ContentPart::Text(text.to_string())]),
+            tool_calls: None,
+        }
+    }
+
+    fn tool_call(id: &str) -> StreamDelta {
+        StreamDelta {
+            content: None,
+            tool_calls: Some(vec![ToolCall {
+                id: id.to_string(),
+                name: "get_weather".to_string(),
+                arguments_json: "{}".to_string(),
+            }]),
+        }
+    }
+
+    fn live(deltas: &[StreamDelta]) -> Vec<StreamEvent> {
+        deltas.iter().cloned().map(StreamEvent::Delta).collect()
+    }
+
+    #[test]
+    fn digests_depend_on_the_response_and_its_order() {
+        assert_eq!(
+            digest(&[text("a"), text("b")]),
+            digest(&[text("a"), text("b")])
+        );
+        assert_ne!(
+            digest(&[text("a"), text("b")]),
+            digest(&[text("b"), text("a")])
+        );
+        assert_ne!(digest(&[text("ab")]), digest(&[text("a"), text("b")]));
+        assert_ne!(digest(&[tool_call("1")]), digest(&[tool_call("2")]));
+    }
+
+    #[test]
+    fn continuations_are_not_divergences() {
+        let persisted = [text("The capital of France "), text("is Paris, which")];
+
+        assert_eq!(
+            compare_deltas("gpt-4o", &persisted, &live(&[text(" lies on the Seine.")])),
+            None
+        );
+    }
+
+    #[test]
+    fn restarted_responses_are_divergences() {
+        let persisted = [text("The capital of France "), text("is Paris, which")];
+
+        let report = compare_deltas(
+            "gpt-4o",
+            &persisted,
+            &live(&[text("The capital of France is Paris.")]),
+        )
+        .unwrap();
+        assert_eq!(report.model, "gpt-4o");
+        assert_eq!(report.persisted_deltas, 2);
+        assert_eq!(report.persisted_digest, digest(&persisted));
+        assert_eq!(report.live_deltas, 1);
+        assert_eq!(report.repeated_chars, 30);
+        assert_eq!(report.repeated_tool_calls, 0);
+        assert!(report
+            .to_string()
+            .starts_with("model=gpt-4o persisted_deltas=2"));
+    }
+
+    #[test]
+    fn short_repeated_text_is_a_divergence_only_when_it_is_the_whole_response() {
+        assert_eq!(
+            compare_deltas("gpt-4o", &[text("The answer")], &live(&[text("The end")])),
+            None
+        );
+        assert_eq!(
+            compare_deltas("gpt-4o", &[text("Hi")], &live(&[text("Hi there")]))
+                .map(|report| report.repeated_chars),
+            Some(2)
+        );
+    }
+
+    #[test]
+    fn repeated_tool_calls_are_divergences() {
+        let report = compare_deltas(
+            "gpt-4o",
+            &[tool_call("call_1")],
+            &live(&[tool_call("call_1"), tool_call("call_2")]),
+        )
+        .unwrap();
+        assert_eq!(report.repeated_chars, 0);
+        assert_eq!(report.repeated_tool_calls, 1);
+    }
+
+    #[test]
+    fn reported_divergences_are_counted() {
+        let report = compare_deltas(
+            "gpt-4o",
+            &[tool_call("call_1")],
+            &live(&[tool_call("call_1")]),
+        )
+        .unwrap();
+        let before = divergence_count();
+
+        super::report(&report);
+        super::report(&report);
+
+        assert_eq!(divergence_count(), before + 2);
+    }
+}
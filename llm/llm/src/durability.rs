@@ -64,16 +64,41 @@ pub trait ExtendedGuest: Guest + 'static {
 /// When the durability feature flag is off, wrapping with `DurableLLM` is just a passthrough
 #[cfg(not(feature = "durability"))]
 mod passthrough_impl {
+    use crate::budget::{self, StreamBudget};
+    use crate::correlation;
     use crate::durability::{DurableLLM, ExtendedGuest};
+    use crate::files::{self, FileProvider};
+    use crate::fine_tuning::{self, FineTuningProvider};
+    use crate::golem::llm::files::{FilePurpose, UploadedFile};
+    use crate::golem::llm::fine_tuning::{Job, JobConfig, ModelPage};
     use crate::golem::llm::llm::{
-        ChatEvent, ChatStream, Config, Guest, Message, ToolCall, ToolResult,
+        ChatEvent, ChatStream, Config, Error, Guest, GuestChatStream, Message, PageRequest,
+        StreamEvent, ToolCall, ToolResult,
     };
+    use crate::provenance::{self, Provenance};
+    use crate::redaction::{self, StreamRestorer};
+    use crate::structured_output;
+    use crate::system_prompts;
+    use crate::tool_emulation;
+    use crate::wasi::io::streams::InputStream;
+    use std::cell::RefCell;
+    use std::time::Duration;
 
     impl<Impl: ExtendedGuest> Guest for DurableLLM<Impl> {
-        type ChatStream = Impl::ChatStream;
+        type ChatStream = PassthroughChatStream<Impl>;
 
         fn send(messages: Vec<Message>, config: Config) -> ChatEvent {
-            Impl::send(messages, config)
+            correlation::send("send", || {
+                provenance::send(messages, config, |messages, config| {
+                    system_prompts::send(messages, config, |messages, config| {
+                        budget::send(messages, config, |messages, config| {
+                            structured_output::send(messages, config, |messages, config| {
+                                redaction::send(messages, config, tool_emulation::send::<Impl>)
+                            })
+                        })
+                    })
+                })
+            })
         }
 
         fn continue_(
@@ -81,11 +106,192 @@ mod passthrough_impl {
             tool_results: Vec<(ToolCall, ToolResult)>,
             config: Config,
         ) -> ChatEvent {
-            Impl::continue_(messages, tool_results, config)
+            correlation::send("continue", || {
+                provenance::send(
+                    (messages, tool_results),
+                    config,
+                    |(messages, tool_results), config| {
+                        system_prompts::send(messages, config, |messages, config| {
+                            budget::send(messages, config, |messages, config| {
+                                structured_output::continue_(
+                                    messages,
+                                    tool_results,
+                                    config,
+                                    |messages, tool_results, config| {
+                                        redaction::continue_(
+                                            messages,
+                                            tool_results,
+                                            config,
+                                            tool_emulation::continue_::<Impl>,
+                                        )
+                                    },
+                                )
+                            })
+                        })
+                    },
+                )
+            })
         }
 
         fn stream(messages: Vec<Message>, config: Config) -> ChatStream {
-            Impl::stream(messages, config)
+            let correlation_id = correlation::begin("stream");
+            if let Err(error) = tool_emulation::check_stream(&config) {
+                return ChatStream::new(PassthroughChatStream::<Impl>::rejected(
+                    error,
+                    correlation_id,
+                ));
+            }
+            let provenance = match Provenance::for_request(&config, &messages) {
+                Ok(provenance) => provenance,
+                Err(error) => {
+                    return ChatStream::new(PassthroughChatStream::<Impl>::rejected(
+                        error,
+                        correlation_id,
+                    ))
+                }
+            };
+            let (messages, config) = match system_prompts::apply(messages, config) {
+                Ok(prepared) => prepared,
+                Err(error) => {
+                    return ChatStream::new(PassthroughChatStream::<Impl>::rejected(
+                        error,
+                        correlation_id,
+                    ))
+                }
+            };
+            let (messages, redaction) = redaction::redact_messages(messages);
+            match budget::prepare(&messages, config) {
+                Ok((config, budget)) => ChatStream::new(PassthroughChatStream::<Impl> {
+                    stream: Some(Impl::unwrapped_stream(messages, config)),
+                    budget: RefCell::new(StreamBudget::new(budget)),
+                    restorer: RefCell::new(StreamRestorer::new(redaction)),
+                    provenance,
+                    correlation_id,
+                }),
+                Err(error) => ChatStream::new(PassthroughChatStream::<Impl>::rejected(
+                    error,
+                    correlation_id,
+                )),
+            }
+        }
+    }
+
+    impl<Impl: ExtendedGuest + FineTuningProvider> crate::golem::llm::fine_tuning::Guest
+        for DurableLLM<Impl>
+    {
+        fn create_job(config: JobConfig) -> Result<Job, Error> {
+            correlation::call("create_job", || Impl::create_job(config))
+        }
+
+        fn get_job(id: String) -> Result<Job, Error> {
+            correlation::call("get_job", || Impl::get_job(id))
+        }
+
+        fn wait_for_job(id: String, poll_interval_seconds: u32) -> Result<Job, Error> {
+            fine_tuning::wait_for_job(
+                id,
+                Duration::from_secs(poll_interval_seconds as u64),
+                <Self as crate::golem::llm::fine_tuning::Guest>::get_job,
+            )
+        }
+
+        fn cancel_job(id: String) -> Result<Job, Error> {
+            correlation::call("cancel_job", || Impl::cancel_job(id))
+        }
+
+        fn list_jobs() -> Result<Vec<Job>, Error> {
+            correlation::call("list_jobs", Impl::list_jobs)
+        }
+
+        fn list_models(page: PageRequest) -> Result<ModelPage, Error> {
+            correlation::call("list_models", || {
+                fine_tuning::list_models_page(&page, Impl::list_models)
+            })
+        }
+    }
+
+    impl<Impl: ExtendedGuest + FileProvider> crate::golem::llm::files::Guest for DurableLLM<Impl> {
+        fn upload(
+            name: String,
+            mime_type: String,
+            data: Vec<u8>,
+            purpose: FilePurpose,
+        ) -> Result<UploadedFile, Error> {
+            correlation::call("upload", || Impl::upload(name, mime_type, data, purpose))
+        }
+
+        fn upload_stream(
+            name: String,
+            mime_type: String,
+            data: &InputStream,
+            size_bytes: u64,
+            purpose: FilePurpose,
+            chunk_size: Option<u32>,
+        ) -> Result<UploadedFile, Error> {
+            correlation::call("upload_stream", || {
+                files::upload_stream::<Impl>(name, mime_type, data, size_bytes, purpose, chunk_size)
+            })
+        }
+
+        fn upload_chunk_size(requested: Option<u32>) -> u32 {
+            Impl::upload_chunk_limits().negotiate(requested)
+        }
+
+        fn delete(id: String) -> Result<(), Error> {
+            correlation::call("delete", || Impl::delete(id))
+        }
+    }
+
+    /// The provider's chat stream with the budget and redaction stages applied to its events
+    pub struct PassthroughChatStream<Impl: ExtendedGuest> {
+        stream: Option<Impl::ChatStream>,
+        budget: RefCell<StreamBudget>,
+        restorer: RefCell<StreamRestorer>,
+        provenance: Option<Provenance>,
+        correlation_id: String,
+    }
+
+    impl<Impl: ExtendedGuest> PassthroughChatStream<Impl> {
+        fn rejected(error: Error, correlation_id: String) -> Self {
+            Self {
+                stream: None,
+                budget: RefCell::new(StreamBudget::rejected(error)),
+                restorer: RefCell::new(StreamRestorer::default()),
+                provenance: None,
+                correlation_id,
+            }
+        }
+
+        fn process(&self, events: Vec<StreamEvent>) -> Vec<StreamEvent> {
+            let events = correlation::annotate_events(events, &self.correlation_id);
+            let events = self.budget.borrow_mut().track(events);
+            let events = self.restorer.borrow_mut().restore(events);
+            match &self.provenance {
+                Some(provenance) => provenance.stamp_stream_events(events),
+                None => events,
+            }
+        }
+    }
+
+    impl<Impl: ExtendedGuest> GuestChatStream for PassthroughChatStream<Impl> {
+        fn get_next(&self) -> Option<Vec<StreamEvent>> {
+            if let Some(events) = self.budget.borrow_mut().exhausted_events() {
+                return Some(events);
+            }
+            self.stream
+                .as_ref()?
+                .get_next()
+                .map(|events| self.process(events))
+        }
+
+        fn blocking_get_next(&self) -> Vec<StreamEvent> {
+            if let Some(events) = self.budget.borrow_mut().exhausted_events() {
+                return events;
+            }
+            match &self.stream {
+                Some(stream) => self.process(stream.blocking_get_next()),
+                None => vec![],
+            }
         }
     }
 }
@@ -100,11 +306,24 @@ mod passthrough_impl {
 /// which is implemented using the type classes and builder in the `golem-rust` library.
 #[cfg(feature = "durability")]
 mod durable_impl {
+    use crate::budget::{self, StreamBudget};
+    use crate::correlation;
+    use crate::divergence;
     use crate::durability::{DurableLLM, ExtendedGuest};
+    use crate::files::{self, FileProvider};
+    use crate::fine_tuning::{self, FineTuningProvider};
+    use crate::golem::llm::files::{FilePurpose, UploadedFile};
+    use crate::golem::llm::fine_tuning::{Job, JobConfig, ModelPage};
     use crate::golem::llm::llm::{
-        ChatEvent, ChatStream, Config, Guest, GuestChatStream, Message, StreamDelta, StreamEvent,
-        ToolCall, ToolResult,
+        ChatEvent, ChatStream, Config, Error, Guest, GuestChatStream, Message, PageRequest,
+        StreamDelta, StreamEvent, ToolCall, ToolResult,
     };
+    use crate::provenance::{self, Provenance};
+    use crate::redaction::{self, StreamRestorer};
+    use crate::structured_output;
+    use crate::system_prompts;
+    use crate::tool_emulation;
+    use crate::wasi::io::streams::InputStream;
     use golem_rust::bindings::golem::durability::durability::DurableFunctionType;
     #[cfg(not(feature = "nopoll"))]
     use golem_rust::bindings::golem::durability::durability::LazyInitializedPollable;
@@ -113,75 +332,352 @@ mod durable_impl {
     use golem_rust::{with_persistence_level, FromValueAndType, IntoValue, PersistenceLevel};
     use std::cell::RefCell;
     use std::fmt::{Display, Formatter};
+    use std::time::Duration;
 
     impl<Impl: ExtendedGuest> Guest for DurableLLM<Impl> {
         type ChatStream = DurableChatStream<Impl>;
 
         fn send(messages: Vec<Message>, config: Config) -> ChatEvent {
-            let durability = Durability::<ChatEvent, UnusedError>::new(
+            correlation::send("send", || {
+                provenance::send(messages, config, |messages, config| {
+                    system_prompts::send(messages, config, |messages, config| {
+                        budget::send(messages, config, |messages, config| {
+                            structured_output::send(messages, config, |messages, config| {
+                                redaction::send(messages, config, durable_send::<Impl>)
+                            })
+                        })
+                    })
+                })
+            })
+        }
+
+        fn continue_(
+            messages: Vec<Message>,
+            tool_results: Vec<(ToolCall, ToolResult)>,
+            config: Config,
+        ) -> ChatEvent {
+            correlation::send("continue", || {
+                provenance::send(
+                    (messages, tool_results),
+                    config,
+                    |(messages, tool_results), config| {
+                        system_prompts::send(messages, config, |messages, config| {
+                            budget::send(messages, config, |messages, config| {
+                                structured_output::continue_(
+                                    messages,
+                                    tool_results,
+                                    config,
+                                    |messages, tool_results, config| {
+                                        redaction::continue_(
+                                            messages,
+                                            tool_results,
+                                            config,
+                                            durable_continue::<Impl>,
+                                        )
+                                    },
+                                )
+                            })
+                        })
+                    },
+                )
+            })
+        }
+
+        fn stream(messages: Vec<Message>, config: Config) -> ChatStream {
+            let correlation_id = correlation::begin("stream");
+            if let Err(error) = tool_emulation::check_stream(&config) {
+                return ChatStream::new(DurableChatStream::<Impl>::rejected(error, correlation_id));
+            }
+            let provenance = match Provenance::for_request(&config, &messages) {
+                Ok(provenance) => provenance,
+                Err(error) => {
+                    return ChatStream::new(DurableChatStream::<Impl>::rejected(
+                        error,
+                        correlation_id,
+                    ))
+                }
+            };
+            let (messages, config) = match system_prompts::apply(messages, config) {
+                Ok(prepared) => prepared,
+                Err(error) => {
+                    return ChatStream::new(DurableChatStream::<Impl>::rejected(
+                        error,
+                        correlation_id,
+                    ))
+                }
+            };
+            let (messages, redaction) = redaction::redact_messages(messages);
+            let (config, budget) = match budget::prepare(&messages, config) {
+                Ok(prepared) => prepared,
+                Err(error) => {
+                    return ChatStream::new(DurableChatStream::<Impl>::rejected(
+                        error,
+                        correlation_id,
+                    ))
+                }
+            };
+            let budget = StreamBudget::new(budget);
+            let restorer = StreamRestorer::new(redaction);
+
+            let durability = Durability::<NoOutput, UnusedError>::new(
                 "golem_llm",
-                "send",
+                "stream",
                 DurableFunctionType::WriteRemote,
             );
             if durability.is_live() {
                 let result = with_persistence_level(PersistenceLevel::PersistNothing, || {
-                    Impl::send(messages.clone(), config.clone())
+                    ChatStream::new(DurableChatStream::<Impl>::live(
+                        Impl::unwrapped_stream(messages.clone(), config.clone()),
+                        budget,
+                        restorer,
+                        provenance,
+                        correlation_id,
+                    ))
                 });
-                durability.persist_infallible(SendInput { messages, config }, result)
+                let _ = durability.persist_infallible(SendInput { messages, config }, NoOutput);
+                result
+            } else {
+                let _: NoOutput = durability.replay_infallible();
+                ChatStream::new(DurableChatStream::<Impl>::replay(
+                    messages,
+                    config,
+                    budget,
+                    restorer,
+                    provenance,
+                    correlation_id,
+                ))
+            }
+        }
+    }
+
+    impl<Impl: ExtendedGuest + FineTuningProvider> crate::golem::llm::fine_tuning::Guest
+        for DurableLLM<Impl>
+    {
+        fn create_job(config: JobConfig) -> Result<Job, Error> {
+            let durability = Durability::<Result<Job, Error>, UnusedError>::new(
+                "golem_llm",
+                "create_job",
+                DurableFunctionType::WriteRemote,
+            );
+            if durability.is_live() {
+                let result = with_persistence_level(PersistenceLevel::PersistNothing, || {
+                    correlation::call("create_job", || Impl::create_job(config.clone()))
+                });
+                durability.persist_infallible(config, result)
             } else {
                 durability.replay_infallible()
             }
         }
 
-        fn continue_(
-            messages: Vec<Message>,
-            tool_results: Vec<(ToolCall, ToolResult)>,
-            config: Config,
-        ) -> ChatEvent {
-            let durability = Durability::<ChatEvent, UnusedError>::new(
+        fn get_job(id: String) -> Result<Job, Error> {
+            let durability = Durability::<Result<Job, Error>, UnusedError>::new(
+                "golem_llm",
+                "get_job",
+                DurableFunctionType::ReadRemote,
+            );
+            if durability.is_live() {
+                let result = with_persistence_level(PersistenceLevel::PersistNothing, || {
+                    correlation::call("get_job", || Impl::get_job(id.clone()))
+                });
+                durability.persist_infallible(JobIdInput { id }, result)
+            } else {
+                durability.replay_infallible()
+            }
+        }
+
+        /// Every poll is a durable `get_job` call, so a recovered worker replays the polls it
+        /// already made and continues waiting from the last known status
+        fn wait_for_job(id: String, poll_interval_seconds: u32) -> Result<Job, Error> {
+            fine_tuning::wait_for_job(
+                id,
+                Duration::from_secs(poll_interval_seconds as u64),
+                <Self as crate::golem::llm::fine_tuning::Guest>::get_job,
+            )
+        }
+
+        fn cancel_job(id: String) -> Result<Job, Error> {
+            let durability = Durability::<Result<Job, Error>, UnusedError>::new(
                 "golem_llm",
-                "continue",
+                "cancel_job",
                 DurableFunctionType::WriteRemote,
             );
             if durability.is_live() {
                 let result = with_persistence_level(PersistenceLevel::PersistNothing, || {
-                    Impl::continue_(messages.clone(), tool_results.clone(), config.clone())
+                    correlation::call("cancel_job", || Impl::cancel_job(id.clone()))
                 });
-                durability.persist_infallible(
-                    ContinueInput {
-                        messages,
-                        tool_results,
-                        config,
-                    },
-                    result,
-                )
+                durability.persist_infallible(JobIdInput { id }, result)
             } else {
                 durability.replay_infallible()
             }
         }
 
-        fn stream(messages: Vec<Message>, config: Config) -> ChatStream {
-            let durability = Durability::<NoOutput, UnusedError>::new(
+        fn list_jobs() -> Result<Vec<Job>, Error> {
+            let durability = Durability::<Result<Vec<Job>, Error>, UnusedError>::new(
                 "golem_llm",
-                "stream",
+                "list_jobs",
+                DurableFunctionType::ReadRemote,
+            );
+            if durability.is_live() {
+                let result = with_persistence_level(PersistenceLevel::PersistNothing, || {
+                    correlation::call("list_jobs", Impl::list_jobs)
+                });
+                durability.persist_infallible(NoInput, result)
+            } else {
+                durability.replay_infallible()
+            }
+        }
+
+        fn list_models(page: PageRequest) -> Result<ModelPage, Error> {
+            let durability = Durability::<Result<ModelPage, Error>, UnusedError>::new(
+                "golem_llm",
+                "list_models",
+                DurableFunctionType::ReadRemote,
+            );
+            if durability.is_live() {
+                let result = with_persistence_level(PersistenceLevel::PersistNothing, || {
+                    correlation::call("list_models", || {
+                        fine_tuning::list_models_page(&page, Impl::list_models)
+                    })
+                });
+                durability.persist_infallible(ListModelsInput { page }, result)
+            } else {
+                durability.replay_infallible()
+            }
+        }
+    }
+
+    impl<Impl: ExtendedGuest + FileProvider> crate::golem::llm::files::Guest for DurableLLM<Impl> {
+        /// The uploaded bytes are not stored in the oplog, only the file's name, type and size
+        fn upload(
+            name: String,
+            mime_type: String,
+            data: Vec<u8>,
+            purpose: FilePurpose,
+        ) -> Result<UploadedFile, Error> {
+            let durability = Durability::<Result<UploadedFile, Error>, UnusedError>::new(
+                "golem_llm",
+                "upload",
                 DurableFunctionType::WriteRemote,
             );
             if durability.is_live() {
+                let input = UploadInput {
+                    name: name.clone(),
+                    mime_type: mime_type.clone(),
+                    size_bytes: data.len() as u64,
+                    purpose,
+                };
                 let result = with_persistence_level(PersistenceLevel::PersistNothing, || {
-                    ChatStream::new(DurableChatStream::<Impl>::live(Impl::unwrapped_stream(
-                        messages.clone(),
-                        config.clone(),
-                    )))
+                    correlation::call("upload", || Impl::upload(name, mime_type, data, purpose))
                 });
-                let _ = durability.persist_infallible(SendInput { messages, config }, NoOutput);
-                result
+                durability.persist_infallible(input, result)
             } else {
-                let _: NoOutput = durability.replay_infallible();
-                ChatStream::new(DurableChatStream::<Impl>::replay(messages, config))
+                durability.replay_infallible()
+            }
+        }
+
+        /// Like `upload`, only the file's name, type and size are stored in the oplog. The stream
+        /// is not read again on replay.
+        fn upload_stream(
+            name: String,
+            mime_type: String,
+            data: &InputStream,
+            size_bytes: u64,
+            purpose: FilePurpose,
+            chunk_size: Option<u32>,
+        ) -> Result<UploadedFile, Error> {
+            let durability = Durability::<Result<UploadedFile, Error>, UnusedError>::new(
+                "golem_llm",
+                "upload_stream",
+                DurableFunctionType::WriteRemote,
+            );
+            if durability.is_live() {
+                let input = UploadInput {
+                    name: name.clone(),
+                    mime_type: mime_type.clone(),
+                    size_bytes,
+                    purpose,
+                };
+                let result = with_persistence_level(PersistenceLevel::PersistNothing, || {
+                    correlation::call("upload_stream", || {
+                        files::upload_stream::<Impl>(
+                            name, mime_type, data, size_bytes, purpose, chunk_size,
+                        )
+                    })
+                });
+                durability.persist_infallible(input, result)
+            } else {
+                durability.replay_infallible()
+            }
+        }
+
+        fn upload_chunk_size(requested: Option<u32>) -> u32 {
+            Impl::upload_chunk_limits().negotiate(requested)
+        }
+
+        fn delete(id: String) -> Result<(), Error> {
+            let durability = Durability::<Result<(), Error>, UnusedError>::new(
+                "golem_llm",
+                "delete",
+                DurableFunctionType::WriteRemote,
+            );
+            if durability.is_live() {
+                let result = with_persistence_level(PersistenceLevel::PersistNothing, || {
+                    correlation::call("delete", || Impl::delete(id.clone()))
+                });
+                durability.persist_infallible(FileIdInput { id }, result)
+            } else {
+                durability.replay_infallible()
             }
         }
     }
 
+    fn durable_send<Impl: ExtendedGuest>(messages: Vec<Message>, config: Config) -> ChatEvent {
+        let durability = Durability::<ChatEvent, UnusedError>::new(
+            "golem_llm",
+            "send",
+            DurableFunctionType::WriteRemote,
+        );
+        if durability.is_live() {
+            let result = with_persistence_level(PersistenceLevel::PersistNothing, || {
+                tool_emulation::send::<Impl>(messages.clone(), config.clone())
+            });
+            durability.persist_infallible(SendInput { messages, config }, result)
+        } else {
+            durability.replay_infallible()
+        }
+    }
+
+    fn durable_continue<Impl: ExtendedGuest>(
+        messages: Vec<Message>,
+        tool_results: Vec<(ToolCall, ToolResult)>,
+        config: Config,
+    ) -> ChatEvent {
+        let durability = Durability::<ChatEvent, UnusedError>::new(
+            "golem_llm",
+            "continue",
+            DurableFunctionType::WriteRemote,
+        );
+        if durability.is_live() {
+            let result = with_persistence_level(PersistenceLevel::PersistNothing, || {
+                tool_emulation::continue_::<Impl>(
+                    messages.clone(),
+                    tool_results.clone(),
+                    config.clone(),
+                )
+            });
+            durability.persist_infallible(
+                ContinueInput {
+                    messages,
+                    tool_results,
+                    config,
+                },
+                result,
+            )
+        } else {
+            durability.replay_infallible()
+        }
+    }
+
     /// Represents the durable chat stream's state
     ///
     /// In live mode it directly calls the underlying LLM stream which is implemented on
@@ -216,10 +712,20 @@ mod durable_impl {
     pub struct DurableChatStream<Impl: ExtendedGuest> {
         state: RefCell<Option<DurableChatStreamState<Impl>>>,
         subscription: RefCell<Option<Pollable>>,
+        budget: RefCell<StreamBudget>,
+        restorer: RefCell<StreamRestorer>,
+        provenance: Option<Provenance>,
+        correlation_id: String,
     }
 
     impl<Impl: ExtendedGuest> DurableChatStream<Impl> {
-        fn live(stream: Impl::ChatStream) -> Self {
+        fn live(
+            stream: Impl::ChatStream,
+            budget: StreamBudget,
+            restorer: StreamRestorer,
+            provenance: Option<Provenance>,
+            correlation_id: String,
+        ) -> Self {
             Self {
                 state: RefCell::new(Some(DurableChatStreamState::Live {
                     stream,
@@ -227,10 +733,21 @@ mod durable_impl {
                     pollables: Vec::new(),
                 })),
                 subscription: RefCell::new(None),
+                budget: RefCell::new(budget),
+                restorer: RefCell::new(restorer),
+                provenance,
+                correlation_id,
             }
         }
 
-        fn replay(original_messages: Vec<Message>, config: Config) -> Self {
+        fn replay(
+            original_messages: Vec<Message>,
+            config: Config,
+            budget: StreamBudget,
+            restorer: StreamRestorer,
+            provenance: Option<Provenance>,
+            correlation_id: String,
+        ) -> Self {
             Self {
                 state: RefCell::new(Some(DurableChatStreamState::Replay {
                     original_messages,
@@ -241,6 +758,38 @@ mod durable_impl {
                     finished: false,
                 })),
                 subscription: RefCell::new(None),
+                budget: RefCell::new(budget),
+                restorer: RefCell::new(restorer),
+                provenance,
+                correlation_id,
+            }
+        }
+
+        /// A stream rejected by the budget guard before reaching the provider. It only emits
+        /// the error, without any durability entries.
+        fn rejected(error: Error, correlation_id: String) -> Self {
+            Self {
+                state: RefCell::new(Some(DurableChatStreamState::Replay {
+                    original_messages: Vec::new(),
+                    config: Config {
+                        model: String::new(),
+                        temperature: None,
+                        max_tokens: None,
+                        stop_sequences: None,
+                        tools: Vec::new(),
+                        tool_choice: None,
+                        provider_options: Vec::new(),
+                    },
+                    #[cfg(not(feature = "nopoll"))]
+                    pollables: Vec::new(),
+                    partial_result: Vec::new(),
+                    finished: true,
+                })),
+                subscription: RefCell::new(None),
+                budget: RefCell::new(StreamBudget::rejected(error)),
+                restorer: RefCell::new(StreamRestorer::default()),
+                provenance: None,
+                correlation_id,
             }
         }
         #[cfg(not(feature = "nopoll"))]
@@ -292,6 +841,49 @@ mod durable_impl {
 
     impl<Impl: ExtendedGuest> GuestChatStream for DurableChatStream<Impl> {
         fn get_next(&self) -> Option<Vec<StreamEvent>> {
+            if let Some(events) = self.budget.borrow_mut().exhausted_events() {
+                return Some(events);
+            }
+            self.durable_get_next().map(|events| {
+                let events = correlation::annotate_events(events, &self.correlation_id);
+                let events = self.budget.borrow_mut().track(events);
+                let events = self.restorer.borrow_mut().restore(events);
+                match &self.provenance {
+                    Some(provenance) => provenance.stamp_stream_events(events),
+                    None => events,
+                }
+            })
+        }
+
+        fn blocking_get_next(&self) -> Vec<StreamEvent> {
+            if let Some(events) = self.budget.borrow_mut().exhausted_events() {
+                return events;
+            }
+            #[cfg(not(feature = "nopoll"))]
+            let mut subscription = self.subscription.borrow_mut();
+            #[cfg(not(feature = "nopoll"))]
+            if subscription.is_none() {
+                *subscription = Some(self.subscribe());
+            }
+            #[cfg(not(feature = "nopoll"))]
+            let subscription = subscription.as_mut().unwrap();
+            let mut result = Vec::new();
+            loop {
+                #[cfg(not(feature = "nopoll"))]
+                subscription.block();
+                match self.get_next() {
+                    Some(events) => {
+                        result.extend(events);
+                        break result;
+                    }
+                    None => continue,
+                }
+            }
+        }
+    }
+
+    impl<Impl: ExtendedGuest> DurableChatStream<Impl> {
+        fn durable_get_next(&self) -> Option<Vec<StreamEvent>> {
             let durability = Durability::<Option<Vec<StreamEvent>>, UnusedError>::new(
                 "golem_llm",
                 "get_next",
@@ -333,6 +925,15 @@ mod durable_impl {
                                     }
 
                                     let next = stream.get_next();
+                                    if let Some(report) = next.as_deref().and_then(|events| {
+                                        divergence::compare_deltas(
+                                            &config.model,
+                                            partial_result,
+                                            events,
+                                        )
+                                    }) {
+                                        divergence::report(&report);
+                                    }
                                     (stream, next)
                                 });
                             durability.persist_infallible(NoInput, first_live_result.clone());
@@ -397,29 +998,6 @@ mod durable_impl {
                 result
             }
         }
-
-        fn blocking_get_next(&self) -> Vec<StreamEvent> {
-            #[cfg(not(feature = "nopoll"))]
-            let mut subscription = self.subscription.borrow_mut();
-            #[cfg(not(feature = "nopoll"))]
-            if subscription.is_none() {
-                *subscription = Some(self.subscribe());
-            }
-            #[cfg(not(feature = "nopoll"))]
-            let subscription = subscription.as_mut().unwrap();
-            let mut result = Vec::new();
-            loop {
-                #[cfg(not(feature = "nopoll"))]
-                subscription.block();
-                match self.get_next() {
-                    Some(events) => {
-                        result.extend(events);
-                        break result;
-                    }
-                    None => continue,
-                }
-            }
-        }
     }
 
     #[derive(Debug, Clone, PartialEq, IntoValue)]
@@ -435,6 +1013,29 @@ mod durable_impl {
         config: Config,
     }
 
+    #[derive(Debug, IntoValue)]
+    struct JobIdInput {
+        id: String,
+    }
+
+    #[derive(Debug, IntoValue)]
+    struct ListModelsInput {
+        page: PageRequest,
+    }
+
+    #[derive(Debug, IntoValue)]
+    struct UploadInput {
+        name: String,
+        mime_type: String,
+        size_bytes: u64,
+        purpose: FilePurpose,
+    }
+
+    #[derive(Debug, IntoValue)]
+    struct FileIdInput {
+        id: String,
+    }
+
     #[derive(Debug, IntoValue)]
     struct NoInput;
 
@@ -454,9 +1055,9 @@ mod durable_impl {
     mod tests {
         use crate::durability::durable_impl::SendInput;
         use crate::golem::llm::llm::{
-            ChatEvent, CompleteResponse, Config, ContentPart, Error, ErrorCode, FinishReason,
-            ImageDetail, ImageReference, ImageSource, ImageUrl, Message, ResponseMetadata, Role,
-            ToolCall, Usage,
+            ChatEvent, CompleteResponse, Config, ContentPart, Error, ErrorCode, FileReference,
+            FinishReason, ImageDetail, ImageReference, ImageSource, ImageUrl, Message,
+            ResponseMetadata, Role, ToolCall, Usage,
         };
         use golem_rust::value_and_type::{FromValueAndType, IntoValueAndType};
         use golem_rust::wasm_rpc::WitTypeNode;
@@ -529,6 +1130,10 @@ mod durable_impl {
                 mime_type: "image/jpeg".to_string(),
                 detail: Some(ImageDetail::Auto),
             })));
+            roundtrip_test(ContentPart::File(FileReference {
+                id: "file-abc123".to_string(),
+                mime_type: Some("application/pdf".to_string()),
+            }));
         }
 
         #[test]
@@ -0,0 +1,381 @@
+//! File uploads.
+//!
+//! Providers with a file API implement `FileProvider`; the others rely on its default
+//! implementations, which report the operations as unsupported. Uploaded files are referenced
+//! from messages with `ContentPart::File`.
+//!
+//! Large files are uploaded from a `wasi:io` input stream with `upload-stream`, which reads the
+//! stream in chunks of a size negotiated with the provider and passes them to
+//! `FileProvider::upload_chunks` one by one. Providers without a multi-part upload API collect
+//! the chunks in memory, and reject files larger than `MAX_BUFFERED_UPLOAD_BYTES`.
+
+use crate::error::unsupported;
+use crate::golem::llm::files::{FilePurpose, UploadedFile};
+use crate::golem::llm::llm::{ContentPart, Error, ErrorCode, Message};
+use crate::wasi::io::streams::{InputStream, StreamError};
+
+/// The largest file the default `FileProvider::upload_chunks` collects in memory to send it with
+/// `FileProvider::upload`
+pub const MAX_BUFFERED_UPLOAD_BYTES: u64 = 100 * 1024 * 1024;
+
+/// Sizes of the chunks a provider accepts in streamed uploads
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ChunkLimits {
+    pub min: u32,
+    pub max: u32,
+    /// The chunk size used when the caller doesn't request one
+    pub default: u32,
+}
+
+impl ChunkLimits {
+    /// The chunk size used for a requested one
+    pub fn negotiate(&self, requested: Option<u32>) -> u32 {
+        requested.unwrap_or(self.default).clamp(self.min, self.max)
+    }
+}
+
+impl Default for ChunkLimits {
+    fn default() -> Self {
+        Self {
+            min: 4 * 1024,
+            max: 16 * 1024 * 1024,
+            default: 1024 * 1024,
+        }
+    }
+}
+
+/// The file operations of a provider
+pub trait FileProvider {
+    fn upload(
+        _name: String,
+        _mime_type: String,
+        _data: Vec<u8>,
+        _purpose: FilePurpose,
+    ) -> Result<UploadedFile, Error> {
+        Err(unsupported("file uploads"))
+    }
+
+    fn delete(_id: String) -> Result<(), Error> {
+        Err(unsupported("file uploads"))
+    }
+
+    fn upload_chunk_limits() -> ChunkLimits {
+        ChunkLimits::default()
+    }
+
+    /// Uploads a file received in chunks. By default the chunks of files up to
+    /// `MAX_BUFFERED_UPLOAD_BYTES` are collected and sent with `upload`; providers with a
+    /// multi-part upload API override it to send each chunk as it comes.
+    fn upload_chunks(
+        name: String,
+        mime_type: String,
+        size_bytes: u64,
+        purpose: FilePurpose,
+        chunks: impl Iterator<Item = Result<Vec<u8>, Error>>,
+    ) -> Result<UploadedFile, Error> {
+        if size_bytes > MAX_BUFFERED_UPLOAD_BYTES {
+            return Err(unsupported(format!(
+                "streamed uploads of files larger than {MAX_BUFFERED_UPLOAD_BYTES} bytes"
+            )));
+        }
+
+        // The buffer grows with the chunks received instead of trusting the announced size
+        let mut data = Vec::new();
+        for chunk in chunks {
+            data.extend_from_slice(&chunk?);
+        }
+        Self::upload(name, mime_type, data, purpose)
+    }
+}
+
+/// Uploads a file of `size_bytes` bytes read from `data`, in chunks of the size negotiated with
+/// the provider
+pub fn upload_stream<P: FileProvider>(
+    name: String,
+    mime_type: String,
+    data: &InputStream,
+    size_bytes: u64,
+    purpose: FilePurpose,
+    chunk_size: Option<u32>,
+) -> Result<UploadedFile, Error> {
+    let chunk_size = P::upload_chunk_limits().negotiate(chunk_size);
+    let chunks = Chunks::new(size_bytes, chunk_size, |len| {
+        match data.blocking_read(len) {
+            Ok(bytes) => Ok(Some(bytes)),
+            Err(StreamError::Closed) => Ok(None),
+            Err(StreamError::LastOperationFailed(error)) => Err(Error {
+                code: ErrorCode::InternalError,
+                message: format!(
+                    "Failed to read the uploaded file: {}",
+                    error.to_debug_string()
+                ),
+                provider_error_json: None,
+            }),
+        }
+    });
+    P::upload_chunks(name, mime_type, size_bytes, purpose, chunks)
+}
+
+/// Splits `size_bytes` bytes returned by a reader into chunks of `chunk_size` bytes, the last
+/// chunk holding the remainder. The reader returns at most the requested number of bytes, and
+/// `None` once the data ended.
+struct Chunks<R> {
+    read: R,
+    remaining: u64,
+    chunk_size: u64,
+}
+
+impl<R: FnMut(u64) -> Result<Option<Vec<u8>>, Error>> Chunks<R> {
+    fn new(size_bytes: u64, chunk_size: u32, read: R) -> Self {
+        Self {
+            read,
+            remaining: size_bytes,
+            chunk_size: chunk_size.max(1) as u64,
+        }
+    }
+}
+
+impl<R: FnMut(u64) -> Result<Option<Vec<u8>>, Error>> Iterator for Chunks<R> {
+    type Item = Result<Vec<u8>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        let len = self.chunk_size.min(self.remaining);
+        let mut chunk = Vec::new();
+        while (chunk.len() as u64) < len {
+            match (self.read)(len - chunk.len() as u64) {
+                Ok(Some(bytes)) => chunk.extend_from_slice(&bytes),
+                Ok(None) => {
+                    let missing = self.remaining - chunk.len() as u64;
+                    self.remaining = 0;
+                    return Some(Err(Error {
+                        code: ErrorCode::InvalidRequest,
+                        message: format!(
+                            "The uploaded file ended {missing} bytes before its announced size"
+                        ),
+                        provider_error_json: None,
+                    }));
+                }
+                Err(error) => {
+                    self.remaining = 0;
+                    return Some(Err(error));
+                }
+            }
+        }
+
+        self.remaining -= len;
+        Some(Ok(chunk))
+    }
+}
+
+/// Fails if any of the messages references a file, for the providers which cannot accept them
+pub fn reject_file_references(messages: &[Message]) -> Result<(), Error> {
+    let references_files = messages
+        .iter()
+        .flat_map(|message| &message.content)
+        .any(|part| matches!(part, ContentPart::File(_)));
+    if references_files {
+        Err(unsupported("file references in messages"))
+    } else {
+        Ok(())
+    }
+}
+
+/// A `multipart/form-data` request body, for the file APIs taking the file as a form field
+pub struct MultipartForm {
+    boundary: String,
+    body: Vec<u8>,
+}
+
+impl MultipartForm {
+    pub fn new() -> Self {
+        Self {
+            boundary: format!("golem-llm-{}", crate::correlation::current()),
+            body: Vec::new(),
+        }
+    }
+
+    pub fn text(mut self, name: &str, value: &str) -> Self {
+        self.body.extend_from_slice(
+            format!(
+                "--{}\r\nContent-Disposition: form-data; name=\"{name}\"\r\n\r\n{value}\r\n",
+                self.boundary
+            )
+            .as_bytes(),
+        );
+        self
+    }
+
+    pub fn file(mut self, name: &str, file_name: &str, mime_type: &str, data: &[u8]) -> Self {
+        self.body.extend_from_slice(
+            format!(
+                "--{}\r\nContent-Disposition: form-data; name=\"{name}\"; filename=\"{}\"\r\nContent-Type: {mime_type}\r\n\r\n",
+                self.boundary,
+                file_name.replace('"', "%22")
+            )
+            .as_bytes(),
+        );
+        self.body.extend_from_slice(data);
+        self.body.extend_from_slice(b"\r\n");
+        self
+    }
+
+    /// The value of the request's `Content-Type` header
+    pub fn content_type(&self) -> String {
+        format!("multipart/form-data; boundary={}", self.boundary)
+    }
+
+    pub fn finish(mut self) -> Vec<u8> {
+        self.body
+            .extend_from_slice(format!("--{}--\r\n", self.boundary).as_bytes());
+        self.body
+    }
+}
+
+impl Default for MultipartForm {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A reader returning `data` at most `max_read` bytes at a time
+    fn reader(
+        data: &[u8],
+        max_read: usize,
+    ) -> impl FnMut(u64) -> Result<Option<Vec<u8>>, Error> + '_ {
+        let mut position = 0;
+        move |len| {
+            if position == data.len() {
+                return Ok(None);
+            }
+            let end = data.len().min(position + max_read.min(len as usize));
+            let bytes = data[position..end].to_vec();
+            position = end;
+            Ok(Some(bytes))
+        }
+    }
+
+    #[test]
+    fn chunk_size_negotiation() {
+        let limits = ChunkLimits {
+            min: 10,
+            max: 100,
+            default: 50,
+        };
+        assert_eq!(limits.negotiate(None), 50);
+        assert_eq!(limits.negotiate(Some(1)), 10);
+        assert_eq!(limits.negotiate(Some(70)), 70);
+        assert_eq!(limits.negotiate(Some(1000)), 100);
+    }
+
+    #[test]
+    fn chunks_of_short_reads() {
+        let data = (0..=255).collect::<Vec<u8>>();
+        let chunks = Chunks::new(data.len() as u64, 100, reader(&data, 7))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(
+            chunks.iter().map(Vec::len).collect::<Vec<_>>(),
+            vec![100, 100, 56]
+        );
+        assert_eq!(chunks.concat(), data);
+    }
+
+    #[test]
+    fn chunks_stop_at_the_announced_size() {
+        let data = [1u8; 30];
+        let chunks = Chunks::new(20, 8, reader(&data, 30))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(chunks.concat().len(), 20);
+    }
+
+    #[test]
+    fn chunks_of_a_truncated_stream() {
+        let data = [1u8; 10];
+        let mut chunks = Chunks::new(20, 8, reader(&data, 30));
+        assert!(chunks.next().unwrap().is_ok());
+        let error = chunks.next().unwrap().unwrap_err();
+        assert_eq!(error.code, ErrorCode::InvalidRequest);
+        assert!(chunks.next().is_none());
+    }
+
+    struct BufferingProvider;
+
+    impl FileProvider for BufferingProvider {
+        fn upload(
+            name: String,
+            mime_type: String,
+            data: Vec<u8>,
+            purpose: FilePurpose,
+        ) -> Result<UploadedFile, Error> {
+            Ok(UploadedFile {
+                id: "file-1".to_string(),
+                name,
+                mime_type,
+                size_bytes: data.len() as u64,
+                purpose,
+                created_at: None,
+            })
+        }
+    }
+
+    #[test]
+    fn buffered_uploads_collect_the_chunks() {
+        let data = (0..=255).collect::<Vec<u8>>();
+        let file = BufferingProvider::upload_chunks(
+            "data.bin".to_string(),
+            "application/octet-stream".to_string(),
+            data.len() as u64,
+            FilePurpose::UserData,
+            Chunks::new(data.len() as u64, 100, reader(&data, 7)),
+        )
+        .unwrap();
+        assert_eq!(file.size_bytes, 256);
+    }
+
+    #[test]
+    fn buffered_uploads_reject_files_above_the_limit() {
+        let error = BufferingProvider::upload_chunks(
+            "huge.bin".to_string(),
+            "application/octet-stream".to_string(),
+            MAX_BUFFERED_UPLOAD_BYTES + 1,
+            FilePurpose::UserData,
+            std::iter::empty(),
+        )
+        .unwrap_err();
+        assert_eq!(error.code, ErrorCode::Unsupported);
+    }
+
+    #[test]
+    fn multipart_form_encoding() {
+        let form = MultipartForm::new().text("purpose", "user_data").file(
+            "file",
+            "report \"q3\".pdf",
+            "application/pdf",
+            b"%PDF",
+        );
+        let boundary = form.boundary.clone();
+        assert_eq!(
+            form.content_type(),
+            format!("multipart/form-data; boundary={boundary}")
+        );
+
+        let body = String::from_utf8(form.finish()).unwrap();
+        assert_eq!(
+            body,
+            format!(
+                "--{boundary}\r\nContent-Disposition: form-data; name=\"purpose\"\r\n\r\nuser_data\r\n\
+                 --{boundary}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"report %22q3%22.pdf\"\r\n\
+                 Content-Type: application/pdf\r\n\r\n%PDF\r\n--{boundary}--\r\n"
+            )
+        );
+    }
+}
@@ -0,0 +1,120 @@
+//! Fine-tuning job management.
+//!
+//! Providers supporting fine-tuning implement `FineTuningProvider`; the others rely on its
+//! default implementations, which report the operations as unsupported. `DurableLLM` exports the
+//! `fine-tuning` interface on top of it, persisting every call to the provider, implementing
+//! `wait-for-job` as a series of durable `get-job` calls and paginating `list-models`.
+
+use crate::error::unsupported;
+use crate::golem::llm::fine_tuning::{Job, JobConfig, JobStatus, ModelPage};
+use crate::golem::llm::llm::{Error, PageRequest};
+use crate::pagination::paginate;
+use golem_rust::bindings::wasi::clocks::monotonic_clock;
+use log::trace;
+use std::time::Duration;
+
+/// The fine-tuning operations of a provider
+pub trait FineTuningProvider {
+    fn create_job(_config: JobConfig) -> Result<Job, Error> {
+        Err(unsupported("fine-tuning"))
+    }
+
+    fn get_job(_id: String) -> Result<Job, Error> {
+        Err(unsupported("fine-tuning"))
+    }
+
+    fn cancel_job(_id: String) -> Result<Job, Error> {
+        Err(unsupported("fine-tuning"))
+    }
+
+    fn list_jobs() -> Result<Vec<Job>, Error> {
+        Err(unsupported("fine-tuning"))
+    }
+
+    /// Lists all the fine-tuned models, `list-models` returns the requested page of them
+    fn list_models() -> Result<Vec<String>, Error> {
+        Err(unsupported("fine-tuning"))
+    }
+}
+
+/// Whether the job reached a status it will not leave anymore
+pub fn is_finished(status: JobStatus) -> bool {
+    matches!(
+        status,
+        JobStatus::Succeeded | JobStatus::Failed | JobStatus::Cancelled
+    )
+}
+
+/// Calls `get_job` every `poll_interval` until the job is finished
+pub fn wait_for_job(
+    id: String,
+    poll_interval: Duration,
+    get_job: impl Fn(String) -> Result<Job, Error>,
+) -> Result<Job, Error> {
+    loop {
+        let job = get_job(id.clone())?;
+        if is_finished(job.status) {
+            return Ok(job);
+        }
+        trace!(
+            "Fine-tuning job {id} is {:?}, checking again in {poll_interval:?}",
+            job.status
+        );
+        monotonic_clock::subscribe_duration(poll_interval.as_nanos() as u64).block();
+    }
+}
+
+/// Returns the requested page of the models listed by `list_models`
+pub fn list_models_page(
+    page: &PageRequest,
+    list_models: impl FnOnce() -> Result<Vec<String>, Error>,
+) -> Result<ModelPage, Error> {
+    let (items, next_page_token) = paginate(list_models()?, page)?;
+    Ok(ModelPage {
+        items,
+        next_page_token,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    fn job(status: JobStatus) -> Job {
+        Job {
+            id: "ftjob-1".to_string(),
+            base_model: "gpt-4o-mini-2024-07-18".to_string(),
+            status,
+            fine_tuned_model: None,
+            created_at: None,
+            finished_at: None,
+            error_message: None,
+        }
+    }
+
+    #[test]
+    fn finished_statuses() {
+        assert!(!is_finished(JobStatus::Queued));
+        assert!(!is_finished(JobStatus::Running));
+        assert!(is_finished(JobStatus::Succeeded));
+        assert!(is_finished(JobStatus::Failed));
+        assert!(is_finished(JobStatus::Cancelled));
+    }
+
+    #[test]
+    fn waiting_stops_at_errors() {
+        let calls = Cell::new(0);
+        let result = wait_for_job("ftjob-1".to_string(), Duration::ZERO, |_| {
+            calls.set(calls.get() + 1);
+            Err(unsupported("fine-tuning"))
+        });
+        assert!(result.is_err());
+        assert_eq!(calls.get(), 1);
+
+        let result = wait_for_job("ftjob-1".to_string(), Duration::ZERO, |_| {
+            Ok(job(JobStatus::Succeeded))
+        });
+        assert_eq!(result, Ok(job(JobStatus::Succeeded)));
+    }
+}
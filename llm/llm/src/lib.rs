@@ -1,7 +1,20 @@
+pub mod budget;
 pub mod chat_stream;
 pub mod config;
+pub mod correlation;
+pub mod divergence;
 pub mod durability;
 pub mod error;
+pub mod files;
+pub mod fine_tuning;
+pub mod pagination;
+pub mod provenance;
+pub mod redaction;
+pub mod structured_output;
+pub mod system_prompts;
+pub mod tool_emulation;
+
+pub use golem_deterministic as deterministic;
 
 #[allow(dead_code)]
 pub mod event_source;
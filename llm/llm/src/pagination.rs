@@ -0,0 +1,95 @@
+//! The pagination convention of listings.
+//!
+//! A listing takes a `page-request` and returns a page record with the items and the token of the
+//! next page. The tokens handed out here encode the offset of the page in the listing, which
+//! providers without native pagination apply to the full listing with `paginate`.
+
+use crate::golem::llm::llm::{Error, ErrorCode, PageRequest, PageToken};
+
+/// Number of items in a page when the request doesn't set a size
+pub const DEFAULT_PAGE_SIZE: u32 = 100;
+
+/// The number of items requested, at least one
+pub fn page_size(page: &PageRequest) -> u32 {
+    page.size.unwrap_or(DEFAULT_PAGE_SIZE).max(1)
+}
+
+/// The offset of the requested page in the listing
+pub fn page_offset(page: &PageRequest) -> Result<u32, Error> {
+    match &page.token {
+        None => Ok(0),
+        Some(token) => token.parse().map_err(|_| Error {
+            code: ErrorCode::InvalidRequest,
+            message: format!("Invalid page token `{token}`"),
+            provider_error_json: None,
+        }),
+    }
+}
+
+/// The token of the page following the page at `offset`, none if that page was the last one
+pub fn next_page_token(offset: u32, received: u32, has_more: bool) -> Option<PageToken> {
+    (has_more && received > 0).then(|| (offset + received).to_string())
+}
+
+/// Returns the requested page of a complete listing, and the token of the next page
+pub fn paginate<T>(
+    items: Vec<T>,
+    page: &PageRequest,
+) -> Result<(Vec<T>, Option<PageToken>), Error> {
+    let offset = page_offset(page)?;
+    let size = page_size(page);
+    let total = items.len();
+
+    let items = items
+        .into_iter()
+        .skip(offset as usize)
+        .take(size as usize)
+        .collect::<Vec<_>>();
+    let received = items.len() as u32;
+    let has_more = (offset as usize).saturating_add(received as usize) < total;
+
+    Ok((items, next_page_token(offset, received, has_more)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(token: Option<&str>, size: Option<u32>) -> PageRequest {
+        PageRequest {
+            token: token.map(str::to_string),
+            size,
+        }
+    }
+
+    #[test]
+    fn pages_cover_the_listing() {
+        let items = (0..5).collect::<Vec<_>>();
+        let mut page = request(None, Some(2));
+        let mut seen = Vec::new();
+        loop {
+            let (page_items, next_page_token) = paginate(items.clone(), &page).unwrap();
+            assert!(page_items.len() <= 2);
+            seen.extend(page_items);
+            match next_page_token {
+                Some(token) => page.token = Some(token),
+                None => break,
+            }
+        }
+        assert_eq!(seen, items);
+    }
+
+    #[test]
+    fn last_full_page_has_no_next_token() {
+        let (items, next_page_token) =
+            paginate(vec![1, 2, 3, 4], &request(Some("2"), Some(2))).unwrap();
+        assert_eq!(items, vec![3, 4]);
+        assert_eq!(next_page_token, None);
+    }
+
+    #[test]
+    fn invalid_token() {
+        let error = paginate(vec![1], &request(Some("next"), None)).unwrap_err();
+        assert_eq!(error.code, ErrorCode::InvalidRequest);
+    }
+}
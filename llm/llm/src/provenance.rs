@@ -0,0 +1,467 @@
+//! Provenance metadata stamped on generated content.
+//!
+//! Stamping is enabled per request with the `provenance` provider option, or for every request
+//! with the `GOLEM_LLM_PROVENANCE` environment variable, set to one of:
+//!
+//! - `sidecar`: a `provenance` record with the model, the time of the request and a hash of the
+//!   request is added to the `provider-metadata-json` of the response, next to the provider's own
+//!   metadata,
+//! - `embed`: the sidecar record, and a comment with the same information appended to the text of
+//!   the response. When the text is a single fenced code block in a known language, the comment
+//!   is written in that language's comment syntax as the first line of the block, otherwise it is
+//!   an HTML comment, which Markdown renderers hide,
+//! - `off`: no stamping, the default.
+//!
+//! Streams get the sidecar record on their finish event only, as the text was already delivered
+//! by then. The time of the request is drawn through `deterministic::now`, so replayed responses
+//! are stamped exactly like the original ones.
+
+use crate::config::get_config_key_or_none;
+use crate::deterministic;
+use crate::golem::llm::llm::{
+    ChatEvent, CompleteResponse, Config, ContentPart, Error, ErrorCode, ResponseMetadata,
+    StreamEvent,
+};
+use serde_json::{json, Map, Value};
+use std::fmt::Debug;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const PROVENANCE_OPTION: &str = "provenance";
+const PROVENANCE_ENV_VAR: &str = "GOLEM_LLM_PROVENANCE";
+
+/// Key of the provenance record in the provider metadata of a response
+const METADATA_KEY: &str = "provenance";
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Mode {
+    Off,
+    Sidecar,
+    Embed,
+}
+
+/// The provenance of the content generated for a request
+#[derive(Debug, Clone, PartialEq)]
+pub struct Provenance {
+    model: String,
+    timestamp: String,
+    request_hash: String,
+    embed: bool,
+}
+
+impl Provenance {
+    /// The provenance of the request described by `request`, or `None` when stamping is off for
+    /// the request
+    pub fn for_request(config: &Config, request: &impl Debug) -> Result<Option<Self>, Error> {
+        let mode = mode(config)?;
+        if mode == Mode::Off {
+            return Ok(None);
+        }
+        Ok(Some(Self {
+            model: config.model.clone(),
+            timestamp: format_timestamp(deterministic::now()),
+            request_hash: request_hash(&config.model, request),
+            embed: mode == Mode::Embed,
+        }))
+    }
+
+    /// The sidecar record of the provenance
+    fn record(&self, response_id: Option<&str>) -> Value {
+        let mut record = json!({
+            "model": self.model,
+            "timestamp": self.timestamp,
+            "request_hash": self.request_hash,
+        });
+        if let Some(response_id) = response_id.filter(|id| !id.is_empty()) {
+            record["response_id"] = json!(response_id);
+        }
+        record
+    }
+
+    fn comment_text(&self) -> String {
+        format!(
+            "generated by {} at {} (request {})",
+            self.model, self.timestamp, self.request_hash
+        )
+    }
+
+    /// Adds the sidecar record to the provider metadata of a response
+    fn stamp_metadata(
+        &self,
+        metadata: ResponseMetadata,
+        response_id: Option<&str>,
+    ) -> ResponseMetadata {
+        let provider_metadata_json = Some(with_record(
+            metadata.provider_metadata_json.as_deref(),
+            self.record(response_id),
+        ));
+        ResponseMetadata {
+            provider_metadata_json,
+            ..metadata
+        }
+    }
+
+    /// Stamps a complete response with the sidecar record and, if enabled, the embedded comment
+    pub fn stamp_response(&self, response: CompleteResponse) -> CompleteResponse {
+        let metadata = self.stamp_metadata(response.metadata, Some(&response.id));
+        let mut content = response.content;
+        if self.embed {
+            if let Some(ContentPart::Text(text)) = content
+                .iter_mut()
+                .rev()
+                .find(|part| matches!(part, ContentPart::Text(_)))
+            {
+                *text = embed_comment(text, &self.comment_text());
+            }
+        }
+        CompleteResponse {
+            content,
+            metadata,
+            ..response
+        }
+    }
+
+    pub fn stamp_event(&self, event: ChatEvent) -> ChatEvent {
+        match event {
+            ChatEvent::Message(response) => ChatEvent::Message(self.stamp_response(response)),
+            event => event,
+        }
+    }
+
+    /// Adds the sidecar record to the finish events of a stream
+    pub fn stamp_stream_events(&self, events: Vec<StreamEvent>) -> Vec<StreamEvent> {
+        events
+            .into_iter()
+            .map(|event| match event {
+                StreamEvent::Finish(metadata) => {
+                    StreamEvent::Finish(self.stamp_metadata(metadata, None))
+                }
+                event => event,
+            })
+            .collect()
+    }
+}
+
+/// Sends a request through `send`, stamping the response with its provenance if enabled for this
+/// request. The request is hashed as it is passed to `send`, such as its messages, or its
+/// messages and tool results.
+pub fn send<R: Debug>(
+    request: R,
+    config: Config,
+    send: impl FnOnce(R, Config) -> ChatEvent,
+) -> ChatEvent {
+    match Provenance::for_request(&config, &request) {
+        Ok(Some(provenance)) => provenance.stamp_event(send(request, config)),
+        Ok(None) => send(request, config),
+        Err(error) => ChatEvent::Error(error),
+    }
+}
+
+fn mode(config: &Config) -> Result<Mode, Error> {
+    let value = config
+        .provider_options
+        .iter()
+        .find(|kv| kv.key == PROVENANCE_OPTION)
+        .map(|kv| kv.value.clone())
+        .or_else(|| get_config_key_or_none(PROVENANCE_ENV_VAR));
+    let Some(value) = value else {
+        return Ok(Mode::Off);
+    };
+    match value.trim().to_lowercase().as_str() {
+        "" | "off" => Ok(Mode::Off),
+        "sidecar" => Ok(Mode::Sidecar),
+        "embed" => Ok(Mode::Embed),
+        other => Err(Error {
+            code: ErrorCode::InvalidRequest,
+            message: format!(
+                "Unknown provenance mode `{other}`, expected `off`, `sidecar` or `embed`"
+            ),
+            provider_error_json: None,
+        }),
+    }
+}
+
+/// A stable hash of the model and the request: the 64-bit FNV-1a hash of their debug
+/// representation, in hexadecimal
+fn request_hash(model: &str, request: &impl Debug) -> String {
+    let mut hash = FNV_OFFSET_BASIS;
+    for byte in format!("{model}\n{request:?}").bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(FNV_PRIME);
+    }
+    format!("{hash:016x}")
+}
+
+/// Adds the provenance record to the provider metadata. Metadata that is not a JSON object is
+/// kept under `provider_metadata`.
+fn with_record(provider_metadata_json: Option<&str>, record: Value) -> String {
+    let mut metadata = match provider_metadata_json.map(serde_json::from_str::<Value>) {
+        None => Map::new(),
+        Some(Ok(Value::Object(metadata))) => metadata,
+        Some(Ok(value)) => Map::from_iter([("provider_metadata".to_string(), value)]),
+        Some(Err(_)) => Map::from_iter([(
+            "provider_metadata".to_string(),
+            Value::String(provider_metadata_json.unwrap_or_default().to_string()),
+        )]),
+    };
+    metadata.insert(METADATA_KEY.to_string(), record);
+    Value::Object(metadata).to_string()
+}
+
+/// The line comment prefix of a code block language
+fn line_comment(language: &str) -> Option<&'static str> {
+    match language.to_lowercase().as_str() {
+        "rust" | "rs" | "c" | "cpp" | "c++" | "cs" | "csharp" | "go" | "java" | "javascript"
+        | "js" | "jsx" | "kotlin" | "kt" | "scala" | "swift" | "typescript" | "ts" | "tsx"
+        | "dart" | "php" | "zig" => Some("//"),
+        "python" | "py" | "ruby" | "rb" | "sh" | "bash" | "zsh" | "shell" | "yaml" | "yml"
+        | "toml" | "r" | "perl" | "pl" | "powershell" | "ps1" | "dockerfile" | "makefile"
+        | "elixir" | "ex" => Some("#"),
+        "sql" | "lua" | "haskell" | "hs" | "elm" => Some("--"),
+        "lisp" | "clojure" | "clj" | "scheme" => Some(";;"),
+        _ => None,
+    }
+}
+
+/// Adds a provenance comment to a generated text: inside the code block when the text is a
+/// single fenced code block in a known language, otherwise as a trailing HTML comment
+fn embed_comment(text: &str, comment: &str) -> String {
+    let comment = comment.replace("--", "- -");
+    let trimmed = text.trim();
+    if let Some(block) = trimmed.strip_prefix("```") {
+        let fences = trimmed.matches("```").count();
+        if let Some((language, code)) = block.split_once('\n') {
+            if let Some(prefix) = line_comment(language.trim()) {
+                if fences == 2 && code.trim_end().ends_with("```") {
+                    return format!("```{language}\n{prefix} {comment}\n{code}");
+                }
+            }
+        }
+    }
+    format!("{}\n\n<!-- {comment} -->", text.trim_end())
+}
+
+/// Formats a time as an RFC 3339 timestamp in UTC, with second precision
+fn format_timestamp(time: SystemTime) -> String {
+    let seconds = time
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default();
+    let days = (seconds / 86_400) as i64;
+    let seconds_of_day = seconds % 86_400;
+
+    // Civil date from the number of days since the epoch (Howard Hinnant's algorithm)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+        seconds_of_day / 3_600,
+        seconds_of_day % 3_600 / 60,
+        seconds_of_day % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::golem::llm::llm::{FinishReason, Kv, Message, Role};
+    use std::time::Duration;
+
+    fn config(provenance: Option<&str>) -> Config {
+        Config {
+            model: "gpt-4o".to_string(),
+            temperature: None,
+            max_tokens: None,
+            stop_sequences: None,
+            tools: vec![],
+            tool_choice: None,
+            provider_options: provenance
+                .map(|value| Kv {
+                    key: PROVENANCE_OPTION.to_string(),
+                    value: value.to_string(),
+                })
+                .into_iter()
+                .collect(),
+        }
+    }
+
+    fn messages(text: &str) -> Vec<Message> {
+        vec![Message {
+            role: Role::User,
+            name: None,
+            content: vec![ContentPart::Text(text.to_string())],
+        }]
+    }
+
+    fn provenance(embed: bool) -> Provenance {
+        Provenance {
+            model: "gpt-4o".to_string(),
+            timestamp: "2025-01-02T03:04:05Z".to_string(),
+            request_hash: "0123456789abcdef".to_string(),
+            embed,
+        }
+    }
+
+    fn metadata(provider_metadata_json: Option<&str>) -> ResponseMetadata {
+        ResponseMetadata {
+            finish_reason: Some(FinishReason::Stop),
+            usage: None,
+            provider_id: None,
+            timestamp: None,
+            provider_metadata_json: provider_metadata_json.map(str::to_string),
+        }
+    }
+
+    fn response(text: &str) -> CompleteResponse {
+        CompleteResponse {
+            id: "resp-1".to_string(),
+            content: vec![ContentPart::Text(text.to_string())],
+            tool_calls: vec![],
+            metadata: metadata(Some(r#"{"system_fingerprint":"fp_1"}"#)),
+        }
+    }
+
+    #[test]
+    fn stamping_is_off_by_default() {
+        assert_eq!(
+            Provenance::for_request(&config(None), &messages("Hi")).unwrap(),
+            None
+        );
+        assert_eq!(
+            Provenance::for_request(&config(Some("off")), &messages("Hi")).unwrap(),
+            None
+        );
+        let error =
+            Provenance::for_request(&config(Some("watermark")), &messages("Hi")).unwrap_err();
+        assert_eq!(error.code, ErrorCode::InvalidRequest);
+    }
+
+    #[test]
+    fn requests_are_hashed_with_their_model() {
+        let provenance =
+            Provenance::for_request(&config(Some("sidecar")), &messages("Hi")).unwrap();
+        let provenance = provenance.unwrap();
+        assert!(!provenance.embed);
+        assert_eq!(
+            provenance.request_hash,
+            request_hash("gpt-4o", &messages("Hi"))
+        );
+        assert_eq!(provenance.request_hash.len(), 16);
+        assert_ne!(
+            request_hash("gpt-4o", &messages("Hi")),
+            request_hash("gpt-4o", &messages("Hello"))
+        );
+        assert_ne!(
+            request_hash("gpt-4o", &messages("Hi")),
+            request_hash("gpt-4o-mini", &messages("Hi"))
+        );
+    }
+
+    #[test]
+    fn sidecar_records_are_merged_into_provider_metadata() {
+        let stamped = provenance(false).stamp_response(response("Hello"));
+        assert_eq!(
+            stamped.content,
+            vec![ContentPart::Text("Hello".to_string())]
+        );
+        let metadata: Value =
+            serde_json::from_str(stamped.metadata.provider_metadata_json.as_deref().unwrap())
+                .unwrap();
+        assert_eq!(
+            metadata,
+            json!({
+                "system_fingerprint": "fp_1",
+                "provenance": {
+                    "model": "gpt-4o",
+                    "timestamp": "2025-01-02T03:04:05Z",
+                    "request_hash": "0123456789abcdef",
+                    "response_id": "resp-1",
+                },
+            })
+        );
+
+        assert_eq!(
+            with_record(Some("not json"), json!({})),
+            r#"{"provenance":{},"provider_metadata":"not json"}"#
+        );
+        assert_eq!(with_record(None, json!({})), r#"{"provenance":{}}"#);
+    }
+
+    #[test]
+    fn finish_events_of_streams_get_the_sidecar_record() {
+        let events =
+            provenance(true).stamp_stream_events(vec![StreamEvent::Finish(metadata(None))]);
+        let [StreamEvent::Finish(metadata)] = events.as_slice() else {
+            panic!("expected a finish event");
+        };
+        assert_eq!(
+            metadata.provider_metadata_json.as_deref(),
+            Some(
+                r#"{"provenance":{"model":"gpt-4o","request_hash":"0123456789abcdef","timestamp":"2025-01-02T03:04:05Z"}}"#
+            )
+        );
+    }
+
+    #[test]
+    fn comments_are_embedded_in_markdown_and_code() {
+        let comment = provenance(true).comment_text();
+        assert_eq!(
+            comment,
+            "generated by gpt-4o at 2025-01-02T03:04:05Z (request 0123456789abcdef)"
+        );
+
+        let stamped = provenance(true).stamp_response(response("# Title\n\nSome text.\n"));
+        assert_eq!(
+            stamped.content,
+            vec![ContentPart::Text(format!(
+                "# Title\n\nSome text.\n\n<!-- {comment} -->"
+            ))]
+        );
+
+        assert_eq!(
+            embed_comment("```rust\nfn main() {}\n```", &comment),
+            format!("```rust\n// {comment}\nfn main() {{}}\n```")
+        );
+        assert_eq!(
+            embed_comment("```python\nprint(1)\n```\n", &comment),
+            format!("```python\n# {comment}\nprint(1)\n```")
+        );
+        assert_eq!(
+            embed_comment("```\nplain\n```", &comment),
+            format!("```\nplain\n```\n\n<!-- {comment} -->")
+        );
+        assert_eq!(
+            embed_comment("```js\na\n```\n\n```js\nb\n```", &comment),
+            format!("```js\na\n```\n\n```js\nb\n```\n\n<!-- {comment} -->")
+        );
+        assert_eq!(embed_comment("x", "model--v2"), "x\n\n<!-- model- -v2 -->");
+    }
+
+    #[test]
+    fn timestamps_are_formatted_as_rfc_3339() {
+        assert_eq!(format_timestamp(UNIX_EPOCH), "1970-01-01T00:00:00Z");
+        assert_eq!(
+            format_timestamp(UNIX_EPOCH + Duration::from_secs(951_782_400)),
+            "2000-02-29T00:00:00Z"
+        );
+        assert_eq!(
+            format_timestamp(UNIX_EPOCH + Duration::from_secs(1_735_787_045)),
+            "2025-01-02T03:04:05Z"
+        );
+    }
+}
@@ -0,0 +1,478 @@
+//! Sensitive data redaction applied to the messages before they leave the component.
+//!
+//! The `GOLEM_LLM_REDACT` environment variable selects the built-in detectors as a comma
+//! separated list of `email`, `phone` and `credit_card` (or `all`), and `GOLEM_LLM_REDACT_PATTERNS`
+//! can add custom regular expressions as a JSON array of strings.
+//!
+//! Every match is replaced by a placeholder such as `[REDACTED_EMAIL_1]`, the same value always
+//! getting the same placeholder within a request. Placeholders found in the response, including
+//! the tool call arguments and streamed deltas, are substituted back with the original values, so
+//! callers never see them.
+
+use crate::config::get_config_key_or_none;
+use crate::golem::llm::llm::{
+    ChatEvent, Config, ContentPart, Message, StreamDelta, StreamEvent, ToolCall, ToolResult,
+};
+use log::warn;
+use regex::Regex;
+use std::rc::Rc;
+
+const REDACT_ENV_VAR: &str = "GOLEM_LLM_REDACT";
+const REDACT_PATTERNS_ENV_VAR: &str = "GOLEM_LLM_REDACT_PATTERNS";
+const PLACEHOLDER_PREFIX: &str = "[REDACTED_";
+
+struct Rule {
+    kind: &'static str,
+    regex: Regex,
+    validate: fn(&str) -> bool,
+}
+
+thread_local! {
+    static RULES: Rc<Vec<Rule>> = Rc::new(load_rules());
+}
+
+fn load_rules() -> Vec<Rule> {
+    let mut rules = Vec::new();
+
+    let enabled = get_config_key_or_none(REDACT_ENV_VAR).unwrap_or_default();
+    let enabled = enabled
+        .split(',')
+        .map(|kind| kind.trim().to_lowercase())
+        .filter(|kind| !kind.is_empty())
+        .collect::<Vec<_>>();
+    let is_enabled = |kind: &str| enabled.iter().any(|k| k == kind || k == "all");
+
+    // Credit cards go first, as the phone number pattern would match them as well
+    if is_enabled("credit_card") {
+        rules.push(Rule {
+            kind: "CREDIT_CARD",
+            regex: Regex::new(r"\b\d(?:[ -]?\d){12,18}\b").unwrap(),
+            validate: luhn_valid,
+        });
+    }
+    if is_enabled("email") {
+        rules.push(Rule {
+            kind: "EMAIL",
+            regex: Regex::new(r"[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}").unwrap(),
+            validate: |_| true,
+        });
+    }
+    if is_enabled("phone") {
+        rules.push(Rule {
+            kind: "PHONE",
+            regex: Regex::new(r"\+?\(?\d{1,4}\)?(?:[ .-]?\(?\d{2,4}\)?){2,4}").unwrap(),
+            validate: |candidate| candidate.chars().filter(|c| c.is_ascii_digit()).count() >= 7,
+        });
+    }
+
+    if let Some(patterns) = get_config_key_or_none(REDACT_PATTERNS_ENV_VAR) {
+        match serde_json::from_str::<Vec<String>>(&patterns) {
+            Ok(patterns) => {
+                for pattern in patterns {
+                    match Regex::new(&pattern) {
+                        Ok(regex) => rules.push(Rule {
+                            kind: "CUSTOM",
+                            regex,
+                            validate: |_| true,
+                        }),
+                        Err(err) => warn!("Ignoring invalid redaction pattern {pattern}: {err}"),
+                    }
+                }
+            }
+            Err(err) => warn!("{REDACT_PATTERNS_ENV_VAR} must be a JSON array of strings: {err}"),
+        }
+    }
+
+    rules
+}
+
+fn luhn_valid(candidate: &str) -> bool {
+    let digits = candidate
+        .chars()
+        .filter_map(|c| c.to_digit(10))
+        .collect::<Vec<_>>();
+    let sum: u32 = digits
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, &digit)| {
+            if i % 2 == 1 {
+                let doubled = digit * 2;
+                if doubled > 9 {
+                    doubled - 9
+                } else {
+                    doubled
+                }
+            } else {
+                digit
+            }
+        })
+        .sum();
+    sum % 10 == 0
+}
+
+/// The placeholders introduced while redacting a request, used to restore the original values in
+/// the response
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Redaction {
+    placeholders: Vec<(String, String)>,
+}
+
+impl Redaction {
+    fn redact_with(&mut self, rules: &[Rule], text: &str) -> String {
+        let mut text = text.to_string();
+        for rule in rules {
+            text = rule
+                .regex
+                .replace_all(&text, |captures: &regex::Captures| {
+                    let value = &captures[0];
+                    if (rule.validate)(value) {
+                        self.placeholder_for(rule.kind, value)
+                    } else {
+                        value.to_string()
+                    }
+                })
+                .into_owned();
+        }
+        text
+    }
+
+    fn placeholder_for(&mut self, kind: &str, value: &str) -> String {
+        if let Some((placeholder, _)) = self.placeholders.iter().find(|(_, v)| v == value) {
+            return placeholder.clone();
+        }
+        let placeholder = format!(
+            "{PLACEHOLDER_PREFIX}{kind}_{}]",
+            self.placeholders.len() + 1
+        );
+        self.placeholders
+            .push((placeholder.clone(), value.to_string()));
+        placeholder
+    }
+
+    fn is_empty(&self) -> bool {
+        self.placeholders.is_empty()
+    }
+
+    /// Substitutes the original values back into a text
+    pub fn restore_text(&self, text: &str) -> String {
+        let mut text = text.to_string();
+        for (placeholder, value) in &self.placeholders {
+            text = text.replace(placeholder, value);
+        }
+        text
+    }
+
+    /// Substitutes the original values back into a JSON document, escaping them as needed
+    fn restore_json(&self, json: &str) -> String {
+        let mut json = json.to_string();
+        for (placeholder, value) in &self.placeholders {
+            let escaped = serde_json::to_string(value).unwrap_or_default();
+            json = json.replace(placeholder, &escaped[1..escaped.len() - 1]);
+        }
+        json
+    }
+
+    fn restore_content(&self, content: Vec<ContentPart>) -> Vec<ContentPart> {
+        content
+            .into_iter()
+            .map(|part| match part {
+                ContentPart::Text(text) => ContentPart::Text(self.restore_text(&text)),
+                other => other,
+            })
+            .collect()
+    }
+
+    fn restore_tool_calls(&self, tool_calls: Vec<ToolCall>) -> Vec<ToolCall> {
+        tool_calls
+            .into_iter()
+            .map(|tool_call| ToolCall {
+                arguments_json: self.restore_json(&tool_call.arguments_json),
+                ..tool_call
+            })
+            .collect()
+    }
+
+    /// Substitutes the original values back into a response
+    pub fn restore_event(&self, event: ChatEvent) -> ChatEvent {
+        if self.is_empty() {
+            return event;
+        }
+        match event {
+            ChatEvent::Message(mut response) => {
+                response.content = self.restore_content(response.content);
+                response.tool_calls = self.restore_tool_calls(response.tool_calls);
+                ChatEvent::Message(response)
+            }
+            ChatEvent::ToolRequest(tool_calls) => {
+                ChatEvent::ToolRequest(self.restore_tool_calls(tool_calls))
+            }
+            ChatEvent::Error(error) => ChatEvent::Error(error),
+        }
+    }
+}
+
+/// Sends the request through `send` with redacted messages, restoring the original values in
+/// the response
+pub fn send(
+    messages: Vec<Message>,
+    config: Config,
+    send: impl FnOnce(Vec<Message>, Config) -> ChatEvent,
+) -> ChatEvent {
+    let (messages, redaction) = redact_messages(messages);
+    redaction.restore_event(send(messages, config))
+}
+
+/// Continues the conversation through `continue_` with redacted messages and tool results,
+/// restoring the original values in the response
+pub fn continue_(
+    messages: Vec<Message>,
+    tool_results: Vec<(ToolCall, ToolResult)>,
+    config: Config,
+    continue_: impl FnOnce(Vec<Message>, Vec<(ToolCall, ToolResult)>, Config) -> ChatEvent,
+) -> ChatEvent {
+    let (messages, tool_results, redaction) = redact_continuation(messages, tool_results);
+    redaction.restore_event(continue_(messages, tool_results, config))
+}
+
+/// Redacts the text content of the messages. Returns the messages unchanged if redaction is not
+/// configured.
+pub fn redact_messages(messages: Vec<Message>) -> (Vec<Message>, Redaction) {
+    let mut redaction = Redaction::default();
+    let rules = RULES.with(|rules| rules.clone());
+    if rules.is_empty() {
+        return (messages, redaction);
+    }
+
+    let messages = messages
+        .into_iter()
+        .map(|message| Message {
+            content: message
+                .content
+                .into_iter()
+                .map(|part| match part {
+                    ContentPart::Text(text) => {
+                        ContentPart::Text(redaction.redact_with(&rules, &text))
+                    }
+                    other => other,
+                })
+                .collect(),
+            ..message
+        })
+        .collect();
+    (messages, redaction)
+}
+
+/// Redacts the messages and the tool results of a `continue` request
+pub fn redact_continuation(
+    messages: Vec<Message>,
+    tool_results: Vec<(ToolCall, ToolResult)>,
+) -> (Vec<Message>, Vec<(ToolCall, ToolResult)>, Redaction) {
+    let (messages, mut redaction) = redact_messages(messages);
+    let rules = RULES.with(|rules| rules.clone());
+    if rules.is_empty() {
+        return (messages, tool_results, redaction);
+    }
+
+    let tool_results = tool_results
+        .into_iter()
+        .map(|(tool_call, tool_result)| {
+            let tool_result = match tool_result {
+                ToolResult::Success(mut success) => {
+                    success.result_json = redaction.redact_with(&rules, &success.result_json);
+                    ToolResult::Success(success)
+                }
+                ToolResult::Error(mut failure) => {
+                    failure.error_message = redaction.redact_with(&rules, &failure.error_message);
+                    ToolResult::Error(failure)
+                }
+            };
+            (tool_call, tool_result)
+        })
+        .collect();
+    (messages, tool_results, redaction)
+}
+
+/// Restores the original values in streamed deltas. Text that may be the beginning of a
+/// placeholder split between two deltas is held back until the next delta arrives.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StreamRestorer {
+    redaction: Redaction,
+    pending: String,
+}
+
+impl StreamRestorer {
+    pub fn new(redaction: Redaction) -> Self {
+        Self {
+            redaction,
+            pending: String::new(),
+        }
+    }
+
+    pub fn restore(&mut self, events: Vec<StreamEvent>) -> Vec<StreamEvent> {
+        if self.redaction.is_empty() {
+            return events;
+        }
+
+        let mut result = Vec::with_capacity(events.len());
+        for event in events {
+            match event {
+                StreamEvent::Delta(mut delta) => {
+                    if let Some(content) = delta.content.take() {
+                        let mut restored = Vec::with_capacity(content.len());
+                        for part in content {
+                            match part {
+                                ContentPart::Text(text) => {
+                                    let text = self.restore_partial(&text);
+                                    if !text.is_empty() {
+                                        restored.push(ContentPart::Text(text));
+                                    }
+                                }
+                                other => restored.push(other),
+                            }
+                        }
+                        delta.content = Some(restored);
+                    }
+                    delta.tool_calls = delta
+                        .tool_calls
+                        .map(|tool_calls| self.redaction.restore_tool_calls(tool_calls));
+                    result.push(StreamEvent::Delta(delta));
+                }
+                other => {
+                    if let Some(flushed) = self.flush() {
+                        result.push(flushed);
+                    }
+                    result.push(other);
+                }
+            }
+        }
+        result
+    }
+
+    fn restore_partial(&mut self, text: &str) -> String {
+        self.pending.push_str(text);
+        let split = match self.pending.rfind('[') {
+            Some(start)
+                if !self.pending[start..].contains(']')
+                    && PLACEHOLDER_PREFIX.len() + 32 > self.pending.len() - start =>
+            {
+                start
+            }
+            _ => self.pending.len(),
+        };
+        let ready = self.pending[..split].to_string();
+        self.pending = self.pending[split..].to_string();
+        self.redaction.restore_text(&ready)
+    }
+
+    fn flush(&mut self) -> Option<StreamEvent> {
+        if self.pending.is_empty() {
+            None
+        } else {
+            let text = std::mem::take(&mut self.pending);
+            Some(StreamEvent::Delta(StreamDelta {
+                content: Some(vec![ContentPart::Text(self.redaction.restore_text(&text))]),
+                tool_calls: None,
+            }))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::golem::llm::llm::ResponseMetadata;
+
+    fn all_rules() -> Vec<Rule> {
+        std::env::set_var(REDACT_ENV_VAR, "all");
+        std::env::set_var(REDACT_PATTERNS_ENV_VAR, r#"["ACC-\\d{6}"]"#);
+        load_rules()
+    }
+
+    fn text_delta(text: &str) -> StreamEvent {
+        StreamEvent::Delta(StreamDelta {
+            content: Some(vec![ContentPart::Text(text.to_string())]),
+            tool_calls: None,
+        })
+    }
+
+    fn delta_text(events: &[StreamEvent]) -> String {
+        events
+            .iter()
+            .filter_map(|event| match event {
+                StreamEvent::Delta(delta) => delta.content.clone(),
+                _ => None,
+            })
+            .flatten()
+            .filter_map(|part| match part {
+                ContentPart::Text(text) => Some(text),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn redacts_and_restores_builtin_kinds() {
+        let rules = all_rules();
+        let mut redaction = Redaction::default();
+        let text = "Mail john.doe@example.com or call +1 415 555 0100, card 4111 1111 1111 1111, account ACC-123456. Again: john.doe@example.com";
+        let redacted = redaction.redact_with(&rules, text);
+
+        assert!(!redacted.contains("john.doe@example.com"));
+        assert!(!redacted.contains("4111"));
+        assert!(!redacted.contains("555"));
+        assert!(!redacted.contains("ACC-123456"));
+        assert_eq!(redacted.matches("[REDACTED_EMAIL_").count(), 2);
+        assert_eq!(redaction.placeholders.len(), 4);
+        assert_eq!(redaction.restore_text(&redacted), text);
+    }
+
+    #[test]
+    fn numbers_failing_luhn_check_are_kept() {
+        let rules = all_rules()
+            .into_iter()
+            .filter(|rule| rule.kind == "CREDIT_CARD")
+            .collect::<Vec<_>>();
+        let mut redaction = Redaction::default();
+        let text = "Order 1234 5678 9012 3456 shipped";
+        assert_eq!(redaction.redact_with(&rules, text), text);
+    }
+
+    #[test]
+    fn restores_tool_call_arguments_as_json() {
+        let mut redaction = Redaction::default();
+        let placeholder = redaction.placeholder_for("CUSTOM", "say \"hi\"");
+        let event = ChatEvent::ToolRequest(vec![ToolCall {
+            id: "call-1".to_string(),
+            name: "speak".to_string(),
+            arguments_json: format!(r#"{{"text":"{placeholder}"}}"#),
+        }]);
+
+        match redaction.restore_event(event) {
+            ChatEvent::ToolRequest(calls) => {
+                assert_eq!(calls[0].arguments_json, r#"{"text":"say \"hi\""}"#)
+            }
+            other => panic!("Expected a tool request, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn restores_placeholders_split_across_deltas() {
+        let mut redaction = Redaction::default();
+        let placeholder = redaction.placeholder_for("EMAIL", "jane@example.com");
+        let (first, second) = placeholder.split_at(5);
+        let mut restorer = StreamRestorer::new(redaction);
+
+        let mut events = restorer.restore(vec![text_delta(&format!("Write to {first}"))]);
+        events.extend(restorer.restore(vec![text_delta(&format!("{second} today ["))]));
+        events.extend(restorer.restore(vec![StreamEvent::Finish(ResponseMetadata {
+            finish_reason: None,
+            usage: None,
+            provider_id: None,
+            timestamp: None,
+            provider_metadata_json: None,
+        })]));
+
+        assert_eq!(delta_text(&events), "Write to jane@example.com today [");
+    }
+}
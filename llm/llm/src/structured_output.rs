@@ -0,0 +1,602 @@
+//! Structured output: JSON responses validated against a schema, with corrective retries.
+//!
+//! Structured output is enabled per request with the `response_schema` provider option, holding
+//! a JSON Schema. The schema is described to the model in an extra system message, and the text
+//! of the completion is parsed as JSON (optionally in a fenced `json` block) and validated
+//! against it. When it does not match, the invalid answer is sent back to the model along with
+//! the validation error, for example "your JSON failed validation at $.items[2].price", up to
+//! `response_schema_max_retries` times (the `GOLEM_LLM_RESPONSE_SCHEMA_MAX_RETRIES` environment
+//! variable, or 2 by default) before an error is returned.
+//!
+//! The validator covers the commonly used keywords: `type`, `enum`, `const`, `properties`,
+//! `required`, `additionalProperties`, `items`, `minItems`, `maxItems`, `minLength`,
+//! `maxLength`, `pattern`, `minimum`, `maximum`, `exclusiveMinimum`, `exclusiveMaximum` and
+//! `anyOf`. Other keywords are ignored. Structured output applies to `send` and `continue`, whose
+//! tool results are sent again with every retry; streaming requests are passed through unchanged.
+
+use crate::config::get_config_key_or_none;
+use crate::golem::llm::llm::{
+    ChatEvent, CompleteResponse, Config, ContentPart, Error, ErrorCode, Message, Role, ToolCall,
+    ToolResult, Usage,
+};
+use log::trace;
+use regex::Regex;
+use serde_json::Value;
+
+const RESPONSE_SCHEMA_OPTION: &str = "response_schema";
+const MAX_RETRIES_OPTION: &str = "response_schema_max_retries";
+const MAX_RETRIES_ENV_VAR: &str = "GOLEM_LLM_RESPONSE_SCHEMA_MAX_RETRIES";
+const DEFAULT_MAX_RETRIES: u32 = 2;
+
+/// Sends the messages through `send`, validating the response against the response schema and
+/// retrying with corrective feedback if structured output is enabled for this request
+pub fn send(
+    messages: Vec<Message>,
+    config: Config,
+    send: impl Fn(Vec<Message>, Config) -> ChatEvent,
+) -> ChatEvent {
+    let Some(schema) = option(&config, RESPONSE_SCHEMA_OPTION) else {
+        return send(messages, config);
+    };
+    let schema = match serde_json::from_str::<Value>(schema) {
+        Ok(schema) => schema,
+        Err(error) => {
+            return ChatEvent::Error(Error {
+                code: ErrorCode::InvalidRequest,
+                message: format!("Invalid response schema: {error}"),
+                provider_error_json: None,
+            })
+        }
+    };
+    let max_retries = max_retries(&config);
+
+    let mut messages = inject_schema(messages, &schema);
+    let mut usage: Option<Usage> = None;
+    let mut attempt = 0;
+    loop {
+        let mut response = match send(messages.clone(), config.clone()) {
+            ChatEvent::Message(response) => response,
+            other => return other,
+        };
+        usage = add_usage(usage, response.metadata.usage);
+        response.metadata.usage = usage.clone();
+
+        let text = response_text(&response);
+        let failure = match parse_json(&text) {
+            Ok(value) => match validate(&schema, &value) {
+                Ok(()) => return ChatEvent::Message(response),
+                Err(failure) => failure,
+            },
+            Err(failure) => failure,
+        };
+
+        if attempt == max_retries {
+            return ChatEvent::Error(Error {
+                code: ErrorCode::InternalError,
+                message: format!(
+                    "Response does not match the response schema after {max_retries} retries: \
+                     {failure}"
+                ),
+                provider_error_json: None,
+            });
+        }
+        attempt += 1;
+        trace!("Retrying invalid structured response ({attempt}/{max_retries}): {failure}");
+
+        messages.push(text_message(Role::Assistant, text));
+        messages.push(text_message(
+            Role::User,
+            format!(
+                "Your {failure}. Respond again with only the corrected JSON value, matching the \
+                 schema."
+            ),
+        ));
+    }
+}
+
+/// Continues the conversation through `continue_`, validating the response against the response
+/// schema and retrying with corrective feedback if structured output is enabled for this request
+pub fn continue_(
+    messages: Vec<Message>,
+    tool_results: Vec<(ToolCall, ToolResult)>,
+    config: Config,
+    continue_: impl Fn(Vec<Message>, Vec<(ToolCall, ToolResult)>, Config) -> ChatEvent,
+) -> ChatEvent {
+    send(messages, config, |messages, config| {
+        continue_(messages, tool_results.clone(), config)
+    })
+}
+
+fn option<'a>(config: &'a Config, key: &str) -> Option<&'a str> {
+    config
+        .provider_options
+        .iter()
+        .find(|kv| kv.key == key)
+        .map(|kv| kv.value.as_str())
+}
+
+fn max_retries(config: &Config) -> u32 {
+    option(config, MAX_RETRIES_OPTION)
+        .map(str::to_string)
+        .or_else(|| get_config_key_or_none(MAX_RETRIES_ENV_VAR))
+        .and_then(|value| value.trim().parse().ok())
+        .unwrap_or(DEFAULT_MAX_RETRIES)
+}
+
+fn text_message(role: Role, text: String) -> Message {
+    Message {
+        role,
+        name: None,
+        content: vec![ContentPart::Text(text)],
+    }
+}
+
+fn inject_schema(messages: Vec<Message>, schema: &Value) -> Vec<Message> {
+    let mut extended_messages = Vec::with_capacity(messages.len() + 1);
+    extended_messages.push(text_message(
+        Role::System,
+        format!(
+            "Respond with only a JSON value matching the following JSON Schema, without any \
+             other text:\n{schema}"
+        ),
+    ));
+    extended_messages.extend(messages);
+    extended_messages
+}
+
+/// Adds up the token usage of the attempts, so the caller sees the cost of the retries
+fn add_usage(total: Option<Usage>, usage: Option<Usage>) -> Option<Usage> {
+    fn add(a: Option<u32>, b: Option<u32>) -> Option<u32> {
+        match (a, b) {
+            (Some(a), Some(b)) => Some(a + b),
+            (a, b) => a.or(b),
+        }
+    }
+
+    match (total, usage) {
+        (Some(total), Some(usage)) => Some(Usage {
+            input_tokens: add(total.input_tokens, usage.input_tokens),
+            output_tokens: add(total.output_tokens, usage.output_tokens),
+            total_tokens: add(total.total_tokens, usage.total_tokens),
+        }),
+        (total, usage) => total.or(usage),
+    }
+}
+
+fn response_text(response: &CompleteResponse) -> String {
+    response
+        .content
+        .iter()
+        .filter_map(|part| match part {
+            ContentPart::Text(text) => Some(text.as_str()),
+            ContentPart::Image(_) | ContentPart::File(_) => None,
+        })
+        .collect::<Vec<_>>()
+        .join("")
+}
+
+/// Parses the JSON value of a completion, which may be wrapped in a fenced code block
+fn parse_json(text: &str) -> Result<Value, String> {
+    let text = text.trim();
+    let body = text
+        .strip_prefix("```")
+        .and_then(|rest| rest.strip_suffix("```"))
+        .map(|block| block.strip_prefix("json").unwrap_or(block))
+        .unwrap_or(text);
+    serde_json::from_str(body.trim())
+        .map_err(|error| format!("response is not valid JSON: {error}"))
+}
+
+/// Validates `value` against `schema`, describing the first mismatch found
+fn validate(schema: &Value, value: &Value) -> Result<(), String> {
+    validate_at(schema, value, "$")
+        .map_err(|(path, reason)| format!("JSON failed validation at {path}: {reason}"))
+}
+
+fn child_path(path: &str, key: &str) -> String {
+    let identifier = key
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if identifier {
+        format!("{path}.{key}")
+    } else {
+        format!("{path}[{}]", Value::String(key.to_string()))
+    }
+}
+
+fn type_matches(expected: &str, value: &Value) -> bool {
+    match expected {
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "number" => value.is_number(),
+        "integer" => {
+            value.is_i64() || value.is_u64() || value.as_f64().is_some_and(|n| n.fract() == 0.0)
+        }
+        "boolean" => value.is_boolean(),
+        "null" => value.is_null(),
+        _ => true,
+    }
+}
+
+fn validate_at(schema: &Value, value: &Value, path: &str) -> Result<(), (String, String)> {
+    let Some(schema) = schema.as_object() else {
+        // `true`, `{}` and unsupported schemas accept every value
+        return if schema == &Value::Bool(false) {
+            Err((path.to_string(), "no value is allowed".to_string()))
+        } else {
+            Ok(())
+        };
+    };
+    let fail = |reason: String| Err((path.to_string(), reason));
+
+    if let Some(expected) = schema.get("type") {
+        let types = match expected {
+            Value::String(expected) => vec![expected.as_str()],
+            Value::Array(expected) => expected.iter().filter_map(Value::as_str).collect(),
+            _ => vec![],
+        };
+        if !types.is_empty() && !types.iter().any(|expected| type_matches(expected, value)) {
+            return fail(format!("expected {}, got {value}", types.join(" or ")));
+        }
+    }
+
+    if let Some(allowed) = schema.get("enum").and_then(Value::as_array) {
+        if !allowed.contains(value) {
+            return fail(format!(
+                "{value} is not one of {}",
+                Value::Array(allowed.clone())
+            ));
+        }
+    }
+    if let Some(constant) = schema.get("const") {
+        if constant != value {
+            return fail(format!("expected {constant}, got {value}"));
+        }
+    }
+
+    if let Some(any_of) = schema.get("anyOf").and_then(Value::as_array) {
+        if !any_of
+            .iter()
+            .any(|schema| validate_at(schema, value, path).is_ok())
+        {
+            return fail("does not match any of the allowed schemas".to_string());
+        }
+    }
+
+    match value {
+        Value::Object(object) => {
+            if let Some(required) = schema.get("required").and_then(Value::as_array) {
+                for key in required.iter().filter_map(Value::as_str) {
+                    if !object.contains_key(key) {
+                        return fail(format!("missing required property `{key}`"));
+                    }
+                }
+            }
+            let properties = schema.get("properties").and_then(Value::as_object);
+            for (key, property) in object {
+                let path = child_path(path, key);
+                match properties.and_then(|properties| properties.get(key)) {
+                    Some(property_schema) => validate_at(property_schema, property, &path)?,
+                    None => match schema.get("additionalProperties") {
+                        Some(Value::Bool(false)) => {
+                            return Err((path, "property is not allowed".to_string()))
+                        }
+                        Some(additional) => validate_at(additional, property, &path)?,
+                        None => {}
+                    },
+                }
+            }
+        }
+        Value::Array(items) => {
+            if let Some(min) = schema.get("minItems").and_then(Value::as_u64) {
+                if (items.len() as u64) < min {
+                    return fail(format!(
+                        "expected at least {min} items, got {}",
+                        items.len()
+                    ));
+                }
+            }
+            if let Some(max) = schema.get("maxItems").and_then(Value::as_u64) {
+                if items.len() as u64 > max {
+                    return fail(format!("expected at most {max} items, got {}", items.len()));
+                }
+            }
+            if let Some(item_schema) = schema.get("items") {
+                for (i, item) in items.iter().enumerate() {
+                    validate_at(item_schema, item, &format!("{path}[{i}]"))?;
+                }
+            }
+        }
+        Value::String(string) => {
+            let length = string.chars().count() as u64;
+            if let Some(min) = schema.get("minLength").and_then(Value::as_u64) {
+                if length < min {
+                    return fail(format!("expected at least {min} characters, got {length}"));
+                }
+            }
+            if let Some(max) = schema.get("maxLength").and_then(Value::as_u64) {
+                if length > max {
+                    return fail(format!("expected at most {max} characters, got {length}"));
+                }
+            }
+            if let Some(pattern) = schema.get("pattern").and_then(Value::as_str) {
+                if let Ok(regex) = Regex::new(pattern) {
+                    if !regex.is_match(string) {
+                        return fail(format!("{value} does not match the pattern `{pattern}`"));
+                    }
+                }
+            }
+        }
+        Value::Number(number) => {
+            let number = number.as_f64().unwrap_or_default();
+            let bound = |key: &str| schema.get(key).and_then(Value::as_f64);
+            if let Some(minimum) = bound("minimum").filter(|minimum| number < *minimum) {
+                return fail(format!("expected at least {minimum}, got {value}"));
+            }
+            if let Some(maximum) = bound("maximum").filter(|maximum| number > *maximum) {
+                return fail(format!("expected at most {maximum}, got {value}"));
+            }
+            if let Some(minimum) = bound("exclusiveMinimum").filter(|minimum| number <= *minimum) {
+                return fail(format!("expected more than {minimum}, got {value}"));
+            }
+            if let Some(maximum) = bound("exclusiveMaximum").filter(|maximum| number >= *maximum) {
+                return fail(format!("expected less than {maximum}, got {value}"));
+            }
+        }
+        Value::Bool(_) | Value::Null => {}
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::golem::llm::llm::{FinishReason, Kv, ResponseMetadata, ToolSuccess};
+    use serde_json::json;
+    use std::cell::RefCell;
+
+    fn config(provider_options: Vec<Kv>) -> Config {
+        Config {
+            model: "gpt-4o".to_string(),
+            temperature: None,
+            max_tokens: None,
+            stop_sequences: None,
+            tools: vec![],
+            tool_choice: None,
+            provider_options,
+        }
+    }
+
+    fn kv(key: &str, value: &str) -> Kv {
+        Kv {
+            key: key.to_string(),
+            value: value.to_string(),
+        }
+    }
+
+    fn response(text: &str) -> ChatEvent {
+        ChatEvent::Message(CompleteResponse {
+            id: "resp-1".to_string(),
+            content: vec![ContentPart::Text(text.to_string())],
+            tool_calls: vec![],
+            metadata: ResponseMetadata {
+                finish_reason: Some(FinishReason::Stop),
+                usage: Some(Usage {
+                    input_tokens: Some(10),
+                    output_tokens: Some(5),
+                    total_tokens: Some(15),
+                }),
+                provider_id: None,
+                timestamp: None,
+                provider_metadata_json: None,
+            },
+        })
+    }
+
+    fn items_schema() -> Value {
+        json!({
+            "type": "object",
+            "required": ["items"],
+            "properties": {
+                "items": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "required": ["name", "price"],
+                        "properties": {
+                            "name": { "type": "string", "minLength": 1 },
+                            "price": { "type": "number", "minimum": 0 }
+                        },
+                        "additionalProperties": false
+                    }
+                }
+            }
+        })
+    }
+
+    #[test]
+    fn validation_reports_the_path_of_the_mismatch() {
+        let schema = items_schema();
+        let valid = json!({ "items": [{ "name": "tea", "price": 2.5 }] });
+        assert_eq!(validate(&schema, &valid), Ok(()));
+
+        let invalid = json!({ "items": [
+            { "name": "tea", "price": 2.5 },
+            { "name": "cake", "price": 4 },
+            { "name": "jam", "price": "3" }
+        ] });
+        assert_eq!(
+            validate(&schema, &invalid),
+            Err(
+                r#"JSON failed validation at $.items[2].price: expected number, got "3""#
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            validate(&schema, &json!({})),
+            Err("JSON failed validation at $: missing required property `items`".to_string())
+        );
+        assert_eq!(
+            validate(
+                &schema,
+                &json!({ "items": [{ "name": "tea", "price": 1, "unit price": 1 }] })
+            ),
+            Err(
+                r#"JSON failed validation at $.items[0]["unit price"]: property is not allowed"#
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn validation_keywords() {
+        let schema = json!({
+            "anyOf": [
+                { "type": "integer", "exclusiveMaximum": 10 },
+                { "type": "string", "enum": ["low", "high"] }
+            ]
+        });
+        assert!(validate(&schema, &json!(3)).is_ok());
+        assert!(validate(&schema, &json!("high")).is_ok());
+        assert!(validate(&schema, &json!(10)).is_err());
+        assert!(validate(&schema, &json!(2.5)).is_err());
+        assert!(validate(&json!({ "pattern": "^[a-z]+$" }), &json!("Tea")).is_err());
+        assert!(validate(&json!({ "maxItems": 1 }), &json!([1, 2])).is_err());
+        assert!(validate(&json!(true), &json!(null)).is_ok());
+    }
+
+    #[test]
+    fn parses_fenced_json() {
+        assert_eq!(
+            parse_json("```json\n{\"a\": 1}\n```"),
+            Ok(json!({ "a": 1 }))
+        );
+        assert_eq!(parse_json(" [1, 2] "), Ok(json!([1, 2])));
+        assert!(parse_json("Sure! {\"a\": 1}").is_err());
+    }
+
+    #[test]
+    fn disabled_without_response_schema() {
+        let calls = RefCell::new(0);
+        let event = send(vec![], config(vec![]), |messages, _| {
+            *calls.borrow_mut() += 1;
+            assert!(messages.is_empty());
+            response("not json")
+        });
+        assert_eq!(*calls.borrow(), 1);
+        assert!(matches!(event, ChatEvent::Message(_)));
+    }
+
+    #[test]
+    fn retries_with_corrective_feedback() {
+        let schema = items_schema().to_string();
+        let sent = RefCell::new(vec![]);
+        let event = send(
+            vec![text_message(Role::User, "List the items".to_string())],
+            config(vec![kv("response_schema", &schema)]),
+            |messages, _| {
+                sent.borrow_mut().push(messages);
+                if sent.borrow().len() == 1 {
+                    response(r#"{"items": [{"name": "tea", "price": "free"}]}"#)
+                } else {
+                    response(r#"{"items": [{"name": "tea", "price": 0}]}"#)
+                }
+            },
+        );
+
+        let sent = sent.into_inner();
+        assert_eq!(sent.len(), 2);
+        assert_eq!(sent[0].len(), 2);
+        assert_eq!(sent[0][0].role, Role::System);
+        assert_eq!(sent[1].len(), 4);
+        assert_eq!(sent[1][2].role, Role::Assistant);
+        match &sent[1][3].content[0] {
+            ContentPart::Text(text) => {
+                assert!(text.starts_with("Your JSON failed validation at $.items[0].price"))
+            }
+            _ => panic!("Expected a text message"),
+        }
+        match event {
+            ChatEvent::Message(response) => {
+                assert_eq!(response.metadata.usage.unwrap().total_tokens, Some(30))
+            }
+            other => panic!("Expected a message, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn continuations_are_validated_with_their_tool_results() {
+        let tool_call = ToolCall {
+            id: "call_1".to_string(),
+            name: "list_items".to_string(),
+            arguments_json: "{}".to_string(),
+        };
+        let tool_result = ToolResult::Success(ToolSuccess {
+            id: "call_1".to_string(),
+            name: "list_items".to_string(),
+            result_json: r#"["tea"]"#.to_string(),
+            execution_time_ms: None,
+        });
+        let calls = RefCell::new(0);
+        let event = continue_(
+            vec![text_message(Role::User, "List the items".to_string())],
+            vec![(tool_call, tool_result)],
+            config(vec![kv("response_schema", &items_schema().to_string())]),
+            |messages, tool_results, _| {
+                *calls.borrow_mut() += 1;
+                assert_eq!(messages[0].role, Role::System);
+                assert_eq!(tool_results.len(), 1);
+                if *calls.borrow() == 1 {
+                    response("tea")
+                } else {
+                    response(r#"{"items": [{"name": "tea", "price": 1}]}"#)
+                }
+            },
+        );
+
+        assert_eq!(*calls.borrow(), 2);
+        assert!(matches!(event, ChatEvent::Message(_)));
+    }
+
+    #[test]
+    fn fails_after_max_retries() {
+        let calls = RefCell::new(0);
+        let event = send(
+            vec![],
+            config(vec![
+                kv("response_schema", r#"{"type": "object"}"#),
+                kv("response_schema_max_retries", "1"),
+            ]),
+            |_, _| {
+                *calls.borrow_mut() += 1;
+                response("[]")
+            },
+        );
+        assert_eq!(*calls.borrow(), 2);
+        match event {
+            ChatEvent::Error(error) => {
+                assert_eq!(error.code, ErrorCode::InternalError);
+                assert!(error.message.contains("after 1 retries"));
+            }
+            other => panic!("Expected an error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn invalid_response_schema_is_rejected() {
+        let event = send(vec![], config(vec![kv("response_schema", "{")]), |_, _| {
+            panic!("Nothing should be sent")
+        });
+        assert!(matches!(
+            event,
+            ChatEvent::Error(Error {
+                code: ErrorCode::InvalidRequest,
+                ..
+            })
+        ));
+    }
+}
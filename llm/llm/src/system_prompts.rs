@@ -0,0 +1,192 @@
+//! Named system prompts, defined in the component configuration instead of the application.
+//!
+//! The `GOLEM_LLM_SYSTEM_PROMPTS` environment variable holds the prompt library as a JSON object
+//! mapping prompt names to their text, and a single prompt can be overridden with a
+//! `GOLEM_LLM_SYSTEM_PROMPT_<NAME>` environment variable, where `<NAME>` is the prompt name in
+//! upper case with any other character than letters and digits replaced by `_`. A request uses a
+//! prompt by naming it in the `system_prompt` provider option, which adds its text as a system
+//! message before the other messages, so prompts can be updated by changing the configuration
+//! without rebuilding the components calling the LLM.
+
+use crate::config::get_config_key_or_none;
+use crate::golem::llm::llm::{ChatEvent, Config, ContentPart, Error, ErrorCode, Message, Role};
+use serde_json::{Map, Value};
+
+const SYSTEM_PROMPT_OPTION: &str = "system_prompt";
+const SYSTEM_PROMPTS_ENV_VAR: &str = "GOLEM_LLM_SYSTEM_PROMPTS";
+const SYSTEM_PROMPT_ENV_VAR_PREFIX: &str = "GOLEM_LLM_SYSTEM_PROMPT_";
+
+/// Sends the messages through `send`, preceded by the system prompt named by the request if any
+pub fn send(
+    messages: Vec<Message>,
+    config: Config,
+    send: impl FnOnce(Vec<Message>, Config) -> ChatEvent,
+) -> ChatEvent {
+    match apply(messages, config) {
+        Ok((messages, config)) => send(messages, config),
+        Err(error) => ChatEvent::Error(error),
+    }
+}
+
+/// Adds the system prompt named by the request before the messages
+pub fn apply(messages: Vec<Message>, config: Config) -> Result<(Vec<Message>, Config), Error> {
+    let Some(name) = config
+        .provider_options
+        .iter()
+        .find(|kv| kv.key == SYSTEM_PROMPT_OPTION)
+        .map(|kv| kv.value.trim())
+    else {
+        return Ok((messages, config));
+    };
+
+    let prompt = find_prompt(
+        name,
+        get_config_key_or_none(override_env_var(name)),
+        get_config_key_or_none(SYSTEM_PROMPTS_ENV_VAR).as_deref(),
+    )?;
+
+    let mut extended_messages = Vec::with_capacity(messages.len() + 1);
+    extended_messages.push(Message {
+        role: Role::System,
+        name: None,
+        content: vec![ContentPart::Text(prompt)],
+    });
+    extended_messages.extend(messages);
+    Ok((extended_messages, config))
+}
+
+/// The environment variable overriding the prompt `name`
+fn override_env_var(name: &str) -> String {
+    let name = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect::<String>();
+    format!("{SYSTEM_PROMPT_ENV_VAR_PREFIX}{name}")
+}
+
+/// The text of the prompt `name`, from its override or else from the prompt library
+fn find_prompt(
+    name: &str,
+    override_prompt: Option<String>,
+    library: Option<&str>,
+) -> Result<String, Error> {
+    if let Some(prompt) = override_prompt {
+        return Ok(prompt);
+    }
+
+    let library = match library {
+        Some(library) => {
+            serde_json::from_str::<Map<String, Value>>(library).map_err(|err| Error {
+                code: ErrorCode::InternalError,
+                message: format!(
+                    "{SYSTEM_PROMPTS_ENV_VAR} must be a JSON object of prompt names and texts: \
+                     {err}"
+                ),
+                provider_error_json: None,
+            })?
+        }
+        None => Map::new(),
+    };
+
+    match library.get(name) {
+        Some(Value::String(prompt)) => Ok(prompt.clone()),
+        Some(_) => Err(Error {
+            code: ErrorCode::InternalError,
+            message: format!("System prompt `{name}` of {SYSTEM_PROMPTS_ENV_VAR} is not a string"),
+            provider_error_json: None,
+        }),
+        None => Err(Error {
+            code: ErrorCode::InvalidRequest,
+            message: format!("Unknown system prompt `{name}`"),
+            provider_error_json: None,
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::golem::llm::llm::Kv;
+
+    const LIBRARY: &str = r#"{"support-agent": "You are a helpful support agent.", "count": 3}"#;
+
+    #[test]
+    fn override_env_vars_are_named_after_prompts() {
+        assert_eq!(
+            override_env_var("support-agent.v2"),
+            "GOLEM_LLM_SYSTEM_PROMPT_SUPPORT_AGENT_V2"
+        );
+    }
+
+    #[test]
+    fn prompts_are_found_in_the_library() {
+        assert_eq!(
+            find_prompt("support-agent", None, Some(LIBRARY)).unwrap(),
+            "You are a helpful support agent."
+        );
+        assert_eq!(
+            find_prompt(
+                "support-agent",
+                Some("You are a terse support agent.".to_string()),
+                Some(LIBRARY)
+            )
+            .unwrap(),
+            "You are a terse support agent."
+        );
+    }
+
+    #[test]
+    fn unknown_and_invalid_prompts_are_rejected() {
+        assert_eq!(
+            find_prompt("sales-agent", None, Some(LIBRARY))
+                .unwrap_err()
+                .code,
+            ErrorCode::InvalidRequest
+        );
+        assert_eq!(
+            find_prompt("sales-agent", None, None).unwrap_err().code,
+            ErrorCode::InvalidRequest
+        );
+        assert_eq!(
+            find_prompt("count", None, Some(LIBRARY)).unwrap_err().code,
+            ErrorCode::InternalError
+        );
+        assert_eq!(
+            find_prompt("support-agent", None, Some("[]"))
+                .unwrap_err()
+                .code,
+            ErrorCode::InternalError
+        );
+    }
+
+    #[test]
+    fn requests_without_a_prompt_are_unchanged() {
+        let messages = vec![Message {
+            role: Role::User,
+            name: None,
+            content: vec![ContentPart::Text("Hello".to_string())],
+        }];
+        let config = Config {
+            model: "model".to_string(),
+            temperature: None,
+            max_tokens: None,
+            stop_sequences: None,
+            tools: vec![],
+            tool_choice: None,
+            provider_options: vec![Kv {
+                key: "top_p".to_string(),
+                value: "0.9".to_string(),
+            }],
+        };
+        assert_eq!(
+            apply(messages.clone(), config.clone()).unwrap(),
+            (messages, config)
+        );
+    }
+}
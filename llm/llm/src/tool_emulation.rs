@@ -0,0 +1,345 @@
+//! Function-calling emulation for providers and models without a native tool API.
+//!
+//! When enabled, the tool definitions are removed from the request and described in an extra
+//! system message instead, asking the model to answer with fenced `tool_call` JSON blocks when it
+//! wants to use a tool. These blocks are parsed from the completion and surfaced as regular
+//! `ToolCall` values, and tool results passed to `continue` are turned back into plain messages.
+//!
+//! Emulation is enabled per request with the `emulate_tools` provider option, or for a list of
+//! models (or `*` for all of them) with the `GOLEM_LLM_EMULATE_TOOLS` environment variable. It
+//! only applies to `send` and `continue`: tool calls cannot be extracted before the whole
+//! completion is known, so streaming requests with emulation enabled are rejected as unsupported.
+
+use crate::config::get_config_key_or_none;
+use crate::error::unsupported;
+use crate::golem::llm::llm::{
+    ChatEvent, Config, ContentPart, Error, Guest, Message, Role, ToolCall, ToolDefinition,
+    ToolResult,
+};
+use serde_json::Value;
+
+const EMULATE_TOOLS_OPTION: &str = "emulate_tools";
+const EMULATE_TOOLS_ENV_VAR: &str = "GOLEM_LLM_EMULATE_TOOLS";
+const TOOL_CALL_FENCE: &str = "```tool_call";
+
+/// Sends the messages through `Impl`, emulating tool calls if enabled for this request
+pub fn send<Impl: Guest>(messages: Vec<Message>, config: Config) -> ChatEvent {
+    if is_enabled(&config) {
+        let (messages, config) = inject_tools(messages, config);
+        extract_tool_calls(Impl::send(messages, config))
+    } else {
+        Impl::send(messages, config)
+    }
+}
+
+/// Continues the conversation through `Impl`, emulating tool calls if enabled for this request
+pub fn continue_<Impl: Guest>(
+    messages: Vec<Message>,
+    tool_results: Vec<(ToolCall, ToolResult)>,
+    config: Config,
+) -> ChatEvent {
+    if is_enabled(&config) {
+        let mut messages = messages;
+        messages.extend(tool_results_to_messages(tool_results));
+        let (messages, config) = inject_tools(messages, config);
+        extract_tool_calls(Impl::send(messages, config))
+    } else {
+        Impl::continue_(messages, tool_results, config)
+    }
+}
+
+/// Rejects streaming requests for which tool emulation is enabled
+pub fn check_stream(config: &Config) -> Result<(), Error> {
+    if is_enabled(config) {
+        Err(unsupported(
+            "tool emulation of streaming requests, use send or continue instead",
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+fn is_enabled(config: &Config) -> bool {
+    if config.tools.is_empty() {
+        return false;
+    }
+
+    if let Some(option) = config
+        .provider_options
+        .iter()
+        .find(|kv| kv.key == EMULATE_TOOLS_OPTION)
+    {
+        return option.value.eq_ignore_ascii_case("true");
+    }
+
+    get_config_key_or_none(EMULATE_TOOLS_ENV_VAR)
+        .map(|models| {
+            models
+                .split(',')
+                .map(|model| model.trim())
+                .any(|model| model == "*" || model == config.model)
+        })
+        .unwrap_or(false)
+}
+
+fn inject_tools(messages: Vec<Message>, mut config: Config) -> (Vec<Message>, Config) {
+    let tools = std::mem::take(&mut config.tools);
+    config.tool_choice = None;
+
+    let mut extended_messages = Vec::with_capacity(messages.len() + 1);
+    extended_messages.push(Message {
+        role: Role::System,
+        name: None,
+        content: vec![ContentPart::Text(tools_prompt(&tools))],
+    });
+    extended_messages.extend(messages);
+
+    (extended_messages, config)
+}
+
+fn tools_prompt(tools: &[ToolDefinition]) -> String {
+    let mut prompt = String::from(
+        "You have access to the following tools. To call a tool, respond with one or more \
+         fenced code blocks in the following format, and nothing else:\n\
+         ```tool_call\n{\"name\": \"<tool name>\", \"arguments\": {<arguments as a JSON object>}}\n```\n\
+         If no tool is needed, answer normally.\n\nAvailable tools:\n",
+    );
+    for tool in tools {
+        prompt.push_str(&format!("- name: {}\n", tool.name));
+        if let Some(description) = &tool.description {
+            prompt.push_str(&format!("  description: {description}\n"));
+        }
+        prompt.push_str(&format!("  parameters: {}\n", tool.parameters_schema));
+    }
+    prompt
+}
+
+fn tool_results_to_messages(tool_results: Vec<(ToolCall, ToolResult)>) -> Vec<Message> {
+    let mut messages = Vec::new();
+    for (tool_call, tool_result) in tool_results {
+        messages.push(Message {
+            role: Role::Assistant,
+            name: None,
+            content: vec![ContentPart::Text(format!(
+                "{TOOL_CALL_FENCE}\n{{\"name\": {}, \"arguments\": {}}}\n```",
+                Value::String(tool_call.name.clone()),
+                tool_call.arguments_json
+            ))],
+        });
+        let result = match tool_result {
+            ToolResult::Success(success) => format!(
+                "Result of the {} tool call: {}",
+                success.name, success.result_json
+            ),
+            ToolResult::Error(failure) => format!(
+                "The {} tool call failed: {}",
+                failure.name, failure.error_message
+            ),
+        };
+        messages.push(Message {
+            role: Role::User,
+            name: None,
+            content: vec![ContentPart::Text(result)],
+        });
+    }
+    messages
+}
+
+fn extract_tool_calls(event: ChatEvent) -> ChatEvent {
+    match event {
+        ChatEvent::Message(response) => {
+            let text = response
+                .content
+                .iter()
+                .filter_map(|part| match part {
+                    ContentPart::Text(text) => Some(text.as_str()),
+                    ContentPart::Image(_) | ContentPart::File(_) => None,
+                })
+                .collect::<Vec<_>>()
+                .join("");
+            let tool_calls = parse_tool_calls(&response.id, &text);
+            if tool_calls.is_empty() {
+                ChatEvent::Message(response)
+            } else {
+                ChatEvent::ToolRequest(tool_calls)
+            }
+        }
+        other => other,
+    }
+}
+
+/// Parses the fenced `tool_call` blocks of a completion. Blocks that are not valid JSON or do
+/// not name a tool are ignored.
+fn parse_tool_calls(response_id: &str, text: &str) -> Vec<ToolCall> {
+    let mut tool_calls = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find(TOOL_CALL_FENCE) {
+        let block = &rest[start + TOOL_CALL_FENCE.len()..];
+        let Some(end) = block.find("```") else {
+            break;
+        };
+        let body = block[..end].trim();
+        rest = &block[end + 3..];
+
+        let Ok(json) = serde_json::from_str::<Value>(body) else {
+            continue;
+        };
+        let Some(name) = json.get("name").and_then(|name| name.as_str()) else {
+            continue;
+        };
+        let arguments = json
+            .get("arguments")
+            .cloned()
+            .unwrap_or(Value::Object(Default::default()));
+        tool_calls.push(ToolCall {
+            id: format!("{response_id}-call-{}", tool_calls.len()),
+            name: name.to_string(),
+            arguments_json: arguments.to_string(),
+        });
+    }
+    tool_calls
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::golem::llm::llm::{
+        CompleteResponse, ErrorCode, FinishReason, Kv, ResponseMetadata, ToolSuccess,
+    };
+
+    fn config(tools: Vec<ToolDefinition>, provider_options: Vec<Kv>) -> Config {
+        Config {
+            model: "llama3".to_string(),
+            temperature: None,
+            max_tokens: None,
+            stop_sequences: None,
+            tools,
+            tool_choice: Some("auto".to_string()),
+            provider_options,
+        }
+    }
+
+    fn weather_tool() -> ToolDefinition {
+        ToolDefinition {
+            name: "get_weather".to_string(),
+            description: Some("Gets the current weather".to_string()),
+            parameters_schema: r#"{"type":"object","properties":{"city":{"type":"string"}}}"#
+                .to_string(),
+        }
+    }
+
+    fn response(text: &str) -> CompleteResponse {
+        CompleteResponse {
+            id: "resp-1".to_string(),
+            content: vec![ContentPart::Text(text.to_string())],
+            tool_calls: vec![],
+            metadata: ResponseMetadata {
+                finish_reason: Some(FinishReason::Stop),
+                usage: None,
+                provider_id: None,
+                timestamp: None,
+                provider_metadata_json: None,
+            },
+        }
+    }
+
+    #[test]
+    fn enabled_by_provider_option() {
+        let enabled = config(
+            vec![weather_tool()],
+            vec![Kv {
+                key: "emulate_tools".to_string(),
+                value: "true".to_string(),
+            }],
+        );
+        assert!(is_enabled(&enabled));
+
+        let no_tools = config(
+            vec![],
+            vec![Kv {
+                key: "emulate_tools".to_string(),
+                value: "true".to_string(),
+            }],
+        );
+        assert!(!is_enabled(&no_tools));
+    }
+
+    #[test]
+    fn streaming_requests_are_rejected_when_enabled() {
+        let enabled = config(
+            vec![weather_tool()],
+            vec![Kv {
+                key: "emulate_tools".to_string(),
+                value: "true".to_string(),
+            }],
+        );
+        assert_eq!(
+            check_stream(&enabled).map_err(|error| error.code),
+            Err(ErrorCode::Unsupported)
+        );
+        assert!(check_stream(&config(vec![weather_tool()], vec![])).is_ok());
+    }
+
+    #[test]
+    fn inject_tools_moves_definitions_to_system_prompt() {
+        let messages = vec![Message {
+            role: Role::User,
+            name: None,
+            content: vec![ContentPart::Text("Weather in Paris?".to_string())],
+        }];
+        let (messages, config) = inject_tools(messages, config(vec![weather_tool()], vec![]));
+
+        assert!(config.tools.is_empty());
+        assert_eq!(config.tool_choice, None);
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].role, Role::System);
+        match &messages[0].content[0] {
+            ContentPart::Text(text) => assert!(text.contains("get_weather")),
+            _ => panic!("Expected a text system prompt"),
+        }
+    }
+
+    #[test]
+    fn extracts_fenced_tool_calls() {
+        let text = "I'll check.\n```tool_call\n{\"name\": \"get_weather\", \"arguments\": {\"city\": \"Paris\"}}\n```\n```tool_call\nnot json\n```";
+        match extract_tool_calls(ChatEvent::Message(response(text))) {
+            ChatEvent::ToolRequest(calls) => {
+                assert_eq!(calls.len(), 1);
+                assert_eq!(calls[0].id, "resp-1-call-0");
+                assert_eq!(calls[0].name, "get_weather");
+                assert_eq!(calls[0].arguments_json, r#"{"city":"Paris"}"#);
+            }
+            other => panic!("Expected a tool request, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn plain_answers_are_kept() {
+        let event = extract_tool_calls(ChatEvent::Message(response("It is sunny.")));
+        assert!(matches!(event, ChatEvent::Message(_)));
+    }
+
+    #[test]
+    fn tool_results_become_messages() {
+        let call = ToolCall {
+            id: "resp-1-call-0".to_string(),
+            name: "get_weather".to_string(),
+            arguments_json: r#"{"city":"Paris"}"#.to_string(),
+        };
+        let result = ToolResult::Success(ToolSuccess {
+            id: call.id.clone(),
+            name: call.name.clone(),
+            result_json: r#"{"temperature": 21}"#.to_string(),
+            execution_time_ms: None,
+        });
+
+        let messages = tool_results_to_messages(vec![(call, result)]);
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].role, Role::Assistant);
+        assert_eq!(messages[1].role, Role::User);
+        match &messages[1].content[0] {
+            ContentPart::Text(text) => assert!(text.contains("21")),
+            _ => panic!("Expected a text message"),
+        }
+    }
+}
@@ -2,6 +2,7 @@ use std::{fmt::Debug, fs, path::Path};
 
 use base64::{engine::general_purpose, Engine};
 use golem_llm::{
+    correlation::{self, CORRELATION_ID_HEADER},
     error::{error_code_from_status, from_event_source_error},
     event_source::EventSource,
     golem::llm::llm::{Error, ErrorCode},
@@ -51,6 +52,7 @@ impl OllamaApi {
         let response: Response = self
             .client
             .request(Method::POST, url)
+            .header(CORRELATION_ID_HEADER, correlation::current())
             .headers(headers)
             .json(&modified_params)
             .send()
@@ -82,6 +84,7 @@ impl OllamaApi {
         let response = self
             .client
             .request(Method::POST, url)
+            .header(CORRELATION_ID_HEADER, correlation::current())
             .headers(headers)
             .body(json_body)
             .send()
@@ -5,6 +5,7 @@ use crate::client::{
     MessageRole, OllamaModelOptions, Tool,
 };
 use base64::{engine::general_purpose, Engine};
+use golem_llm::files::reject_file_references;
 use golem_llm::golem::llm::llm::{
     ChatEvent, CompleteResponse, Config, ContentPart, Error, ErrorCode, FinishReason,
     ImageReference, Message, ResponseMetadata, Role, ToolCall as golem_llm_ToolCall, ToolResult,
@@ -17,6 +18,8 @@ pub fn messages_to_request(
     config: Config,
     tool_results: Option<Vec<(golem_llm_ToolCall, ToolResult)>>,
 ) -> Result<CompletionsRequest, Error> {
+    reject_file_references(&messages)?;
+
     let options = config
         .provider_options
         .into_iter()
@@ -59,6 +62,7 @@ pub fn messages_to_request(
                         attached_image.push(base64_data);
                     }
                 },
+                ContentPart::File(_) => {} // Rejected above
             }
         }
 
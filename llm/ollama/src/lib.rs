@@ -6,6 +6,8 @@ use golem_llm::{
     chat_stream::{LlmChatStream, LlmChatStreamState},
     durability::{DurableLLM, ExtendedGuest},
     event_source::EventSource,
+    files::FileProvider,
+    fine_tuning::FineTuningProvider,
     golem::llm::llm::{
         ChatEvent, ChatStream, Config, ContentPart, Error, FinishReason, Guest, Message,
         ResponseMetadata, Role, StreamDelta, StreamEvent, ToolCall, ToolResult, Usage,
@@ -301,6 +303,10 @@ impl ExtendedGuest for OllamaComponent {
     }
 }
 
+impl FineTuningProvider for OllamaComponent {}
+
+impl FileProvider for OllamaComponent {}
+
 type DurableOllamaComponent = DurableLLM<OllamaComponent>;
 
 golem_llm::export_llm!(DurableOllamaComponent with_types_in golem_llm);
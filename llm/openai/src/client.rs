@@ -1,5 +1,7 @@
+use golem_llm::correlation::{self, CORRELATION_ID_HEADER};
 use golem_llm::error::{error_code_from_status, from_event_source_error, from_reqwest_error};
 use golem_llm::event_source::EventSource;
+use golem_llm::files::MultipartForm;
 use golem_llm::golem::llm::llm::Error;
 use log::trace;
 use reqwest::header::HeaderValue;
@@ -38,6 +40,7 @@ impl ResponsesApi {
         let response: Response = self
             .client
             .request(Method::POST, format!("{BASE_URL}/v1/responses"))
+            .header(CORRELATION_ID_HEADER, correlation::current())
             .bearer_auth(&self.openai_api_key)
             .json(&request)
             .send()
@@ -55,6 +58,7 @@ impl ResponsesApi {
         let response: Response = self
             .client
             .request(Method::POST, format!("{BASE_URL}/v1/responses"))
+            .header(CORRELATION_ID_HEADER, correlation::current())
             .bearer_auth(&self.openai_api_key)
             .header(
                 reqwest::header::ACCEPT,
@@ -71,6 +75,243 @@ impl ResponsesApi {
     }
 }
 
+/// The OpenAI API client for managing fine-tuning jobs.
+///
+/// Based on https://platform.openai.com/docs/api-reference/fine-tuning
+pub struct FineTuningApi {
+    openai_api_key: String,
+    client: Client,
+}
+
+impl FineTuningApi {
+    pub fn new(openai_api_key: String) -> Self {
+        let client = Client::builder()
+            .build()
+            .expect("Failed to initialize HTTP client");
+        Self {
+            openai_api_key,
+            client,
+        }
+    }
+
+    pub fn create_job(&self, request: CreateFineTuningJobRequest) -> Result<FineTuningJob, Error> {
+        trace!("Sending request to OpenAI API: {request:?}");
+
+        let response: Response = self
+            .client
+            .request(Method::POST, format!("{BASE_URL}/v1/fine_tuning/jobs"))
+            .header(CORRELATION_ID_HEADER, correlation::current())
+            .bearer_auth(&self.openai_api_key)
+            .json(&request)
+            .send()
+            .map_err(|err| from_reqwest_error("Request failed", err))?;
+
+        parse_response(response)
+    }
+
+    pub fn retrieve_job(&self, id: &str) -> Result<FineTuningJob, Error> {
+        trace!("Retrieving fine-tuning job {id} from OpenAI API");
+
+        let response: Response = self
+            .client
+            .request(Method::GET, format!("{BASE_URL}/v1/fine_tuning/jobs/{id}"))
+            .header(CORRELATION_ID_HEADER, correlation::current())
+            .bearer_auth(&self.openai_api_key)
+            .send()
+            .map_err(|err| from_reqwest_error("Request failed", err))?;
+
+        parse_response(response)
+    }
+
+    pub fn cancel_job(&self, id: &str) -> Result<FineTuningJob, Error> {
+        trace!("Cancelling fine-tuning job {id} in OpenAI API");
+
+        let response: Response = self
+            .client
+            .request(
+                Method::POST,
+                format!("{BASE_URL}/v1/fine_tuning/jobs/{id}/cancel"),
+            )
+            .header(CORRELATION_ID_HEADER, correlation::current())
+            .bearer_auth(&self.openai_api_key)
+            .send()
+            .map_err(|err| from_reqwest_error("Request failed", err))?;
+
+        parse_response(response)
+    }
+
+    /// Lists all the fine-tuning jobs of the organization, following the pagination
+    pub fn list_jobs(&self) -> Result<Vec<FineTuningJob>, Error> {
+        let mut jobs = Vec::new();
+        let mut after: Option<String> = None;
+        loop {
+            trace!("Listing fine-tuning jobs from OpenAI API after {after:?}");
+
+            let mut request = self
+                .client
+                .request(Method::GET, format!("{BASE_URL}/v1/fine_tuning/jobs"))
+                .header(CORRELATION_ID_HEADER, correlation::current())
+                .bearer_auth(&self.openai_api_key)
+                .query(&[("limit", "100")]);
+            if let Some(after) = &after {
+                request = request.query(&[("after", after)]);
+            }
+            let response: Response = request
+                .send()
+                .map_err(|err| from_reqwest_error("Request failed", err))?;
+
+            let page: FineTuningJobList = parse_response(response)?;
+            after = page.data.last().map(|job| job.id.clone());
+            jobs.extend(page.data);
+            if !page.has_more || after.is_none() {
+                return Ok(jobs);
+            }
+        }
+    }
+}
+
+/// The OpenAI API client for uploading files.
+///
+/// Based on https://platform.openai.com/docs/api-reference/files
+pub struct FilesApi {
+    openai_api_key: String,
+    client: Client,
+}
+
+impl FilesApi {
+    pub fn new(openai_api_key: String) -> Self {
+        let client = Client::builder()
+            .build()
+            .expect("Failed to initialize HTTP client");
+        Self {
+            openai_api_key,
+            client,
+        }
+    }
+
+    pub fn upload_file(
+        &self,
+        name: &str,
+        mime_type: &str,
+        data: &[u8],
+        purpose: &str,
+    ) -> Result<FileObject, Error> {
+        trace!(
+            "Uploading file {name} ({} bytes) to OpenAI API for {purpose}",
+            data.len()
+        );
+
+        let form = MultipartForm::new()
+            .text("purpose", purpose)
+            .file("file", name, mime_type, data);
+
+        let response: Response = self
+            .client
+            .request(Method::POST, format!("{BASE_URL}/v1/files"))
+            .header(CORRELATION_ID_HEADER, correlation::current())
+            .bearer_auth(&self.openai_api_key)
+            .header(reqwest::header::CONTENT_TYPE, form.content_type())
+            .body(form.finish())
+            .send()
+            .map_err(|err| from_reqwest_error("Request failed", err))?;
+
+        parse_response(response)
+    }
+
+    /// Starts a multi-part upload of a file of `bytes` bytes
+    pub fn create_upload(&self, request: &CreateUploadRequest) -> Result<Upload, Error> {
+        trace!(
+            "Creating upload of file {} ({} bytes) to OpenAI API",
+            request.filename,
+            request.bytes
+        );
+
+        let response: Response = self
+            .client
+            .request(Method::POST, format!("{BASE_URL}/v1/uploads"))
+            .header(CORRELATION_ID_HEADER, correlation::current())
+            .bearer_auth(&self.openai_api_key)
+            .json(request)
+            .send()
+            .map_err(|err| from_reqwest_error("Request failed", err))?;
+
+        parse_response(response)
+    }
+
+    pub fn add_upload_part(&self, upload_id: &str, data: &[u8]) -> Result<UploadPart, Error> {
+        trace!(
+            "Adding part of {} bytes to upload {upload_id} in OpenAI API",
+            data.len()
+        );
+
+        let form = MultipartForm::new().file("data", "part", "application/octet-stream", data);
+
+        let response: Response = self
+            .client
+            .request(
+                Method::POST,
+                format!("{BASE_URL}/v1/uploads/{upload_id}/parts"),
+            )
+            .header(CORRELATION_ID_HEADER, correlation::current())
+            .bearer_auth(&self.openai_api_key)
+            .header(reqwest::header::CONTENT_TYPE, form.content_type())
+            .body(form.finish())
+            .send()
+            .map_err(|err| from_reqwest_error("Request failed", err))?;
+
+        parse_response(response)
+    }
+
+    pub fn complete_upload(&self, upload_id: &str, part_ids: Vec<String>) -> Result<Upload, Error> {
+        trace!("Completing upload {upload_id} in OpenAI API");
+
+        let response: Response = self
+            .client
+            .request(
+                Method::POST,
+                format!("{BASE_URL}/v1/uploads/{upload_id}/complete"),
+            )
+            .header(CORRELATION_ID_HEADER, correlation::current())
+            .bearer_auth(&self.openai_api_key)
+            .json(&CompleteUploadRequest { part_ids })
+            .send()
+            .map_err(|err| from_reqwest_error("Request failed", err))?;
+
+        parse_response(response)
+    }
+
+    pub fn cancel_upload(&self, upload_id: &str) -> Result<Upload, Error> {
+        trace!("Cancelling upload {upload_id} in OpenAI API");
+
+        let response: Response = self
+            .client
+            .request(
+                Method::POST,
+                format!("{BASE_URL}/v1/uploads/{upload_id}/cancel"),
+            )
+            .header(CORRELATION_ID_HEADER, correlation::current())
+            .bearer_auth(&self.openai_api_key)
+            .send()
+            .map_err(|err| from_reqwest_error("Request failed", err))?;
+
+        parse_response(response)
+    }
+
+    pub fn delete_file(&self, id: &str) -> Result<DeletedFile, Error> {
+        trace!("Deleting file {id} from OpenAI API");
+
+        let response: Response = self
+            .client
+            .request(Method::DELETE, format!("{BASE_URL}/v1/files/{id}"))
+            .header(CORRELATION_ID_HEADER, correlation::current())
+            .bearer_auth(&self.openai_api_key)
+            .send()
+            .map_err(|err| from_reqwest_error("Request failed", err))?;
+
+        parse_response(response)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CreateModelResponseRequest {
     pub input: Input,
@@ -206,10 +447,15 @@ pub enum InnerInputItem {
     TextOutput { text: String },
     #[serde(rename = "input_image")]
     ImageInput {
-        image_url: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        image_url: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        file_id: Option<String>,
         #[serde(default)]
         detail: Detail,
     },
+    #[serde(rename = "input_file")]
+    FileInput { file_id: String },
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -270,6 +516,96 @@ pub struct ResponseOutputItemDone {
     pub output_index: u32,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateFineTuningJobRequest {
+    pub model: String,
+    pub training_file: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub validation_file: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suffix: Option<String>,
+    #[serde(skip_serializing_if = "serde_json::Map::is_empty")]
+    pub hyperparameters: serde_json::Map<String, serde_json::Value>,
+    #[serde(flatten)]
+    pub provider_options: serde_json::Map<String, serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FineTuningJob {
+    pub id: String,
+    pub model: String,
+    pub status: FineTuningJobStatus,
+    pub fine_tuned_model: Option<String>,
+    pub created_at: u64,
+    pub finished_at: Option<u64>,
+    pub error: Option<FineTuningJobError>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FineTuningJobStatus {
+    ValidatingFiles,
+    Queued,
+    Running,
+    Succeeded,
+    Failed,
+    Cancelled,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FineTuningJobError {
+    pub code: Option<String>,
+    pub message: Option<String>,
+    pub param: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FineTuningJobList {
+    pub data: Vec<FineTuningJob>,
+    pub has_more: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileObject {
+    pub id: String,
+    pub bytes: u64,
+    pub created_at: u64,
+    pub filename: String,
+    pub purpose: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateUploadRequest {
+    pub filename: String,
+    pub purpose: String,
+    pub bytes: u64,
+    pub mime_type: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompleteUploadRequest {
+    pub part_ids: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Upload {
+    pub id: String,
+    pub status: String,
+    /// The created file, once the upload is completed
+    pub file: Option<FileObject>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UploadPart {
+    pub id: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeletedFile {
+    pub id: String,
+    pub deleted: bool,
+}
+
 fn parse_response<T: DeserializeOwned + Debug>(response: Response) -> Result<T, Error> {
     let status = response.status();
     if status.is_success() {
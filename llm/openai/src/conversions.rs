@@ -1,12 +1,16 @@
 use crate::client::{
-    CreateModelResponseRequest, CreateModelResponseResponse, Detail, InnerInput, InnerInputItem,
-    Input, InputItem, OutputItem, OutputMessageContent, Tool,
+    CreateFineTuningJobRequest, CreateModelResponseRequest, CreateModelResponseResponse, Detail,
+    FileObject, FineTuningJob, FineTuningJobStatus, InnerInput, InnerInputItem, Input, InputItem,
+    OutputItem, OutputMessageContent, Tool,
 };
 use base64::{engine::general_purpose, Engine as _};
 use golem_llm::error::error_code_from_status;
+use golem_llm::golem::llm::files::{FilePurpose, UploadedFile};
+use golem_llm::golem::llm::fine_tuning::{Job, JobConfig, JobStatus};
 use golem_llm::golem::llm::llm::{
     ChatEvent, CompleteResponse, Config, ContentPart, Error, ErrorCode, ImageDetail,
-    ImageReference, Message, ResponseMetadata, Role, ToolCall, ToolDefinition, ToolResult, Usage,
+    ImageReference, Kv, Message, ResponseMetadata, Role, ToolCall, ToolDefinition, ToolResult,
+    Usage,
 };
 use reqwest::StatusCode;
 use std::collections::HashMap;
@@ -124,7 +128,8 @@ pub fn llm_message_to_openai_message(message: Message) -> InputItem {
             },
             ContentPart::Image(image_reference) => match image_reference {
                 ImageReference::Url(image_url) => InnerInputItem::ImageInput {
-                    image_url: image_url.url,
+                    image_url: Some(image_url.url),
+                    file_id: None,
                     detail: match image_url.detail {
                         Some(ImageDetail::Auto) => Detail::Auto,
                         Some(ImageDetail::Low) => Detail::Low,
@@ -138,7 +143,8 @@ pub fn llm_message_to_openai_message(message: Message) -> InputItem {
                     let data_url = format!("data:{mime_type};base64,{base64_data}");
 
                     InnerInputItem::ImageInput {
-                        image_url: data_url,
+                        image_url: Some(data_url),
+                        file_id: None,
                         detail: match image_source.detail {
                             Some(ImageDetail::Auto) => Detail::Auto,
                             Some(ImageDetail::Low) => Detail::Low,
@@ -148,6 +154,14 @@ pub fn llm_message_to_openai_message(message: Message) -> InputItem {
                     }
                 }
             },
+            ContentPart::File(file) => match file.mime_type {
+                Some(mime_type) if mime_type.starts_with("image/") => InnerInputItem::ImageInput {
+                    image_url: None,
+                    file_id: Some(file.id),
+                    detail: Detail::default(),
+                },
+                _ => InnerInputItem::FileInput { file_id: file.id },
+            },
         };
         items.push(item);
     }
@@ -238,3 +252,67 @@ pub fn create_response_metadata(response: &CreateModelResponseResponse) -> Respo
         provider_metadata_json: response.metadata.as_ref().map(|m| m.to_string()),
     }
 }
+
+pub fn create_fine_tuning_job_request(config: JobConfig) -> CreateFineTuningJobRequest {
+    CreateFineTuningJobRequest {
+        model: config.base_model,
+        training_file: config.training_data,
+        validation_file: config.validation_data,
+        suffix: config.suffix,
+        hyperparameters: kvs_to_json_map(config.hyperparameters),
+        provider_options: kvs_to_json_map(config.provider_options),
+    }
+}
+
+/// Values which are valid JSON (numbers, booleans, objects) are sent as such, the others as
+/// strings, so both `n_epochs=3` and `n_epochs=auto` are accepted
+fn kvs_to_json_map(kvs: Vec<Kv>) -> serde_json::Map<String, serde_json::Value> {
+    kvs.into_iter()
+        .map(|kv| {
+            let value = serde_json::from_str(&kv.value)
+                .unwrap_or_else(|_| serde_json::Value::String(kv.value));
+            (kv.key, value)
+        })
+        .collect()
+}
+
+pub fn fine_tuning_job_to_job(job: FineTuningJob) -> Job {
+    Job {
+        id: job.id,
+        base_model: job.model,
+        status: match job.status {
+            FineTuningJobStatus::ValidatingFiles | FineTuningJobStatus::Queued => JobStatus::Queued,
+            FineTuningJobStatus::Running => JobStatus::Running,
+            FineTuningJobStatus::Succeeded => JobStatus::Succeeded,
+            FineTuningJobStatus::Failed => JobStatus::Failed,
+            FineTuningJobStatus::Cancelled => JobStatus::Cancelled,
+        },
+        fine_tuned_model: job.fine_tuned_model,
+        created_at: Some(job.created_at.to_string()),
+        finished_at: job.finished_at.map(|finished_at| finished_at.to_string()),
+        error_message: job.error.and_then(|error| error.message),
+    }
+}
+
+pub fn to_openai_file_purpose(purpose: FilePurpose) -> &'static str {
+    match purpose {
+        FilePurpose::UserData => "user_data",
+        FilePurpose::Assistants => "assistants",
+        FilePurpose::FineTune => "fine-tune",
+    }
+}
+
+pub fn file_object_to_uploaded_file(
+    file: FileObject,
+    mime_type: String,
+    purpose: FilePurpose,
+) -> UploadedFile {
+    UploadedFile {
+        id: file.id,
+        name: file.filename,
+        mime_type,
+        size_bytes: file.bytes,
+        purpose,
+        created_at: Some(file.created_at.to_string()),
+    }
+}
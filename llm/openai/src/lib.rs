@@ -1,15 +1,21 @@
 use crate::client::{
-    CreateModelResponseResponse, InputItem, OutputItem, ResponseOutputItemDone,
-    ResponseOutputTextDelta, ResponsesApi,
+    CreateModelResponseResponse, CreateUploadRequest, FilesApi, FineTuningApi, FineTuningJobStatus,
+    InputItem, OutputItem, ResponseOutputItemDone, ResponseOutputTextDelta, ResponsesApi, Upload,
 };
 use crate::conversions::{
-    create_request, create_response_metadata, messages_to_input_items, parse_error_code,
-    process_model_response, tool_defs_to_tools, tool_results_to_input_items,
+    create_fine_tuning_job_request, create_request, create_response_metadata,
+    file_object_to_uploaded_file, fine_tuning_job_to_job, messages_to_input_items,
+    parse_error_code, process_model_response, to_openai_file_purpose, tool_defs_to_tools,
+    tool_results_to_input_items,
 };
 use golem_llm::chat_stream::{LlmChatStream, LlmChatStreamState};
-use golem_llm::config::with_config_key;
+use golem_llm::config::{get_config_key, with_config_key};
 use golem_llm::durability::{DurableLLM, ExtendedGuest};
 use golem_llm::event_source::EventSource;
+use golem_llm::files::{ChunkLimits, FileProvider};
+use golem_llm::fine_tuning::FineTuningProvider;
+use golem_llm::golem::llm::files::{FilePurpose, UploadedFile};
+use golem_llm::golem::llm::fine_tuning::{Job, JobConfig};
 use golem_llm::golem::llm::llm::{
     ChatEvent, ChatStream, Config, ContentPart, Error, ErrorCode, Guest, Message, StreamDelta,
     StreamEvent, ToolCall, ToolResult,
@@ -249,6 +255,125 @@ impl ExtendedGuest for OpenAIComponent {
     }
 }
 
+impl FineTuningProvider for OpenAIComponent {
+    fn create_job(config: JobConfig) -> Result<Job, Error> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = FineTuningApi::new(get_config_key(Self::ENV_VAR_NAME)?);
+        let job = client.create_job(create_fine_tuning_job_request(config))?;
+        Ok(fine_tuning_job_to_job(job))
+    }
+
+    fn get_job(id: String) -> Result<Job, Error> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = FineTuningApi::new(get_config_key(Self::ENV_VAR_NAME)?);
+        Ok(fine_tuning_job_to_job(client.retrieve_job(&id)?))
+    }
+
+    fn cancel_job(id: String) -> Result<Job, Error> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = FineTuningApi::new(get_config_key(Self::ENV_VAR_NAME)?);
+        Ok(fine_tuning_job_to_job(client.cancel_job(&id)?))
+    }
+
+    fn list_jobs() -> Result<Vec<Job>, Error> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = FineTuningApi::new(get_config_key(Self::ENV_VAR_NAME)?);
+        let jobs = client.list_jobs()?;
+        Ok(jobs.into_iter().map(fine_tuning_job_to_job).collect())
+    }
+
+    /// The models produced by the succeeded fine-tuning jobs
+    fn list_models() -> Result<Vec<String>, Error> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = FineTuningApi::new(get_config_key(Self::ENV_VAR_NAME)?);
+        let jobs = client.list_jobs()?;
+        Ok(jobs
+            .into_iter()
+            .filter(|job| job.status == FineTuningJobStatus::Succeeded)
+            .filter_map(|job| job.fine_tuned_model)
+            .collect())
+    }
+}
+
+impl FileProvider for OpenAIComponent {
+    fn upload(
+        name: String,
+        mime_type: String,
+        data: Vec<u8>,
+        purpose: FilePurpose,
+    ) -> Result<UploadedFile, Error> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = FilesApi::new(get_config_key(Self::ENV_VAR_NAME)?);
+        let file = client.upload_file(&name, &mime_type, &data, to_openai_file_purpose(purpose))?;
+        Ok(file_object_to_uploaded_file(file, mime_type, purpose))
+    }
+
+    fn delete(id: String) -> Result<(), Error> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = FilesApi::new(get_config_key(Self::ENV_VAR_NAME)?);
+        client.delete_file(&id)?;
+        Ok(())
+    }
+
+    /// Upload parts can be up to 64 MB
+    fn upload_chunk_limits() -> ChunkLimits {
+        ChunkLimits {
+            min: 1024 * 1024,
+            max: 64 * 1024 * 1024,
+            default: 8 * 1024 * 1024,
+        }
+    }
+
+    /// Sends every chunk as a part of a multi-part upload, cancelling the upload if any of them
+    /// fails
+    fn upload_chunks(
+        name: String,
+        mime_type: String,
+        size_bytes: u64,
+        purpose: FilePurpose,
+        chunks: impl Iterator<Item = Result<Vec<u8>, Error>>,
+    ) -> Result<UploadedFile, Error> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = FilesApi::new(get_config_key(Self::ENV_VAR_NAME)?);
+        let upload = client.create_upload(&CreateUploadRequest {
+            filename: name,
+            purpose: to_openai_file_purpose(purpose).to_string(),
+            bytes: size_bytes,
+            mime_type: mime_type.clone(),
+        })?;
+
+        let part_ids = chunks
+            .map(|chunk| Ok(client.add_upload_part(&upload.id, &chunk?)?.id))
+            .collect::<Result<Vec<_>, Error>>();
+        let completed = part_ids.and_then(|part_ids| client.complete_upload(&upload.id, part_ids));
+
+        match completed {
+            Ok(Upload {
+                file: Some(file), ..
+            }) => Ok(file_object_to_uploaded_file(file, mime_type, purpose)),
+            Ok(upload) => Err(Error {
+                code: ErrorCode::InternalError,
+                message: format!("Upload {} completed without a file", upload.id),
+                provider_error_json: None,
+            }),
+            Err(error) => {
+                if let Err(cancel_error) = client.cancel_upload(&upload.id) {
+                    trace!("Failed to cancel upload {}: {cancel_error:?}", upload.id);
+                }
+                Err(error)
+            }
+        }
+    }
+}
+
 type DurableOpenAIComponent = DurableLLM<OpenAIComponent>;
 
 golem_llm::export_llm!(DurableOpenAIComponent with_types_in golem_llm);
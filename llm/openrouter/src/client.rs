@@ -1,3 +1,4 @@
+use golem_llm::correlation::{self, CORRELATION_ID_HEADER};
 use golem_llm::error::{error_code_from_status, from_event_source_error, from_reqwest_error};
 use golem_llm::event_source::EventSource;
 use golem_llm::golem::llm::llm::{Error, ErrorCode};
@@ -30,6 +31,7 @@ impl CompletionsApi {
         let response: Response = self
             .client
             .request(Method::POST, format!("{BASE_URL}/api/v1/chat/completions"))
+            .header(CORRELATION_ID_HEADER, correlation::current())
             .bearer_auth(self.api_key.clone())
             .json(&request)
             .send()
@@ -44,6 +46,7 @@ impl CompletionsApi {
         let response: Response = self
             .client
             .request(Method::POST, format!("{BASE_URL}/api/v1/chat/completions"))
+            .header(CORRELATION_ID_HEADER, correlation::current())
             .bearer_auth(self.api_key.clone())
             .header(
                 reqwest::header::ACCEPT,
@@ -2,6 +2,7 @@ use crate::client::{
     CompletionsRequest, CompletionsResponse, Detail, FunctionName, ToolChoiceFunction,
 };
 use base64::{engine::general_purpose, Engine as _};
+use golem_llm::files::reject_file_references;
 use golem_llm::golem::llm::llm::{
     ChatEvent, CompleteResponse, Config, ContentPart, Error, ErrorCode, FinishReason, ImageDetail,
     ImageReference, Message, ResponseMetadata, Role, ToolCall, ToolDefinition, ToolResult, Usage,
@@ -12,6 +13,8 @@ pub fn messages_to_request(
     messages: Vec<Message>,
     config: Config,
 ) -> Result<CompletionsRequest, Error> {
+    reject_file_references(&messages)?;
+
     let options = config
         .provider_options
         .into_iter()
@@ -190,6 +193,7 @@ fn convert_content_parts(contents: Vec<ContentPart>) -> crate::client::Content {
                     });
                 }
             },
+            ContentPart::File(_) => {} // Rejected by `messages_to_request`
         }
     }
     crate::client::Content::List(result)
@@ -200,7 +204,7 @@ fn convert_content_parts_to_string(contents: Vec<ContentPart>) -> String {
     for content in contents {
         match content {
             ContentPart::Text(text) => result.push_str(&text),
-            ContentPart::Image(_) => {} // Correctly ignores any image content
+            ContentPart::Image(_) | ContentPart::File(_) => {} // Correctly ignores any non-text content
         }
     }
     result
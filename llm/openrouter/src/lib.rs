@@ -11,6 +11,8 @@ use golem_llm::config::with_config_key;
 use golem_llm::durability::{DurableLLM, ExtendedGuest};
 use golem_llm::error::error_code_from_status;
 use golem_llm::event_source::EventSource;
+use golem_llm::files::FileProvider;
+use golem_llm::fine_tuning::FineTuningProvider;
 use golem_llm::golem::llm::llm::{
     ChatEvent, ChatStream, Config, ContentPart, Error, FinishReason, Guest, Message,
     ResponseMetadata, Role, StreamDelta, StreamEvent, ToolCall, ToolResult,
@@ -367,6 +369,10 @@ impl ExtendedGuest for OpenRouterComponent {
     }
 }
 
+impl FineTuningProvider for OpenRouterComponent {}
+
+impl FileProvider for OpenRouterComponent {}
+
 type DurableOpenRouterComponent = DurableLLM<OpenRouterComponent>;
 
 golem_llm::export_llm!(DurableOpenRouterComponent with_types_in golem_llm);
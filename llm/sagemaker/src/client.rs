@@ -0,0 +1,158 @@
+use aws_config::BehaviorVersion;
+use aws_sdk_sagemakerruntime::{
+    self as sagemaker,
+    config::{http::HttpRequest, AsyncSleep, Sleep},
+    error::SdkError,
+    operation::{invoke_endpoint::InvokeEndpointError, RequestId},
+    primitives::Blob,
+};
+use aws_types::region;
+use golem_llm::{
+    config::{get_config_key, get_config_key_or_none},
+    correlation::{self, CORRELATION_ID_HEADER},
+    golem::llm::llm,
+};
+use golem_llm_aws::{async_utils::UnsafeFuture, wasi_client::WasiClient};
+use log::trace;
+use serde_json::Value;
+use wasi::clocks::monotonic_clock;
+use wstd::runtime::Reactor;
+
+#[derive(Debug)]
+pub struct SageMaker {
+    client: sagemaker::Client,
+}
+
+impl SageMaker {
+    pub async fn new() -> Result<Self, llm::Error> {
+        let environment = SageMakerEnvironment::load_from_env()?;
+
+        let sdk_config = aws_config::defaults(BehaviorVersion::latest())
+            .region(environment.aws_region())
+            .http_client(WasiClient::new())
+            .credentials_provider(environment.aws_credentials())
+            .sleep_impl(WasiSleep::new())
+            .load()
+            .await;
+        let client = sagemaker::Client::new(&sdk_config);
+        Ok(Self { client })
+    }
+
+    /// Invokes a real-time inference endpoint with a JSON payload, returning the JSON response
+    /// and the request's ID
+    pub async fn invoke_endpoint(
+        &self,
+        endpoint_name: &str,
+        payload: Value,
+    ) -> Result<(Value, Option<String>), llm::Error> {
+        trace!("Sending request to SageMaker endpoint {endpoint_name}: {payload}");
+
+        let output = self
+            .client
+            .invoke_endpoint()
+            .endpoint_name(endpoint_name)
+            .content_type("application/json")
+            .accept("application/json")
+            .body(Blob::new(payload.to_string()))
+            .customize()
+            .mutate_request(with_correlation_id())
+            .send()
+            .await
+            .map_err(|err| from_invoke_endpoint_sdk_error(endpoint_name, err))?;
+
+        let request_id = output.request_id().map(str::to_string);
+        let body = output.body.map(Blob::into_inner).unwrap_or_default();
+        let response = serde_json::from_slice(&body).map_err(|err| llm::Error {
+            code: llm::ErrorCode::InternalError,
+            message: format!("Failed to decode response body: {err}"),
+            provider_error_json: Some(String::from_utf8_lossy(&body).to_string()),
+        })?;
+
+        trace!("Received response from SageMaker endpoint {endpoint_name}: {response}");
+
+        Ok((response, request_id))
+    }
+}
+
+/// Adds the correlation ID of the current operation to the request's headers
+fn with_correlation_id() -> impl Fn(&mut HttpRequest) + Send + Sync + 'static {
+    let correlation_id = correlation::current();
+    move |request| {
+        request
+            .headers_mut()
+            .insert(CORRELATION_ID_HEADER, correlation_id.clone());
+    }
+}
+
+fn from_invoke_endpoint_sdk_error(
+    endpoint_name: &str,
+    sdk_error: SdkError<InvokeEndpointError>,
+) -> llm::Error {
+    let code = match &sdk_error {
+        SdkError::ServiceError(error) => match error.err() {
+            InvokeEndpointError::ValidationError(_) | InvokeEndpointError::ModelError(_) => {
+                llm::ErrorCode::InvalidRequest
+            }
+            _ => llm::ErrorCode::InternalError,
+        },
+        _ => llm::ErrorCode::InternalError,
+    };
+    llm::Error {
+        code,
+        message: format!("Error calling SageMaker endpoint {endpoint_name}: {sdk_error:?}"),
+        provider_error_json: None,
+    }
+}
+
+#[derive(Debug)]
+pub struct SageMakerEnvironment {
+    access_key_id: String,
+    region: String,
+    secret_access_key: String,
+    session_token: Option<String>,
+}
+
+impl SageMakerEnvironment {
+    pub fn load_from_env() -> Result<Self, llm::Error> {
+        Ok(Self {
+            access_key_id: get_config_key("AWS_ACCESS_KEY_ID")?,
+            region: get_config_key("AWS_REGION")?,
+            secret_access_key: get_config_key("AWS_SECRET_ACCESS_KEY")?,
+            session_token: get_config_key_or_none("AWS_SESSION_TOKEN"),
+        })
+    }
+
+    fn aws_region(&self) -> region::Region {
+        region::Region::new(self.region.clone())
+    }
+
+    fn aws_credentials(&self) -> sagemaker::config::Credentials {
+        sagemaker::config::Credentials::new(
+            self.access_key_id.clone(),
+            self.secret_access_key.clone(),
+            self.session_token.clone(),
+            None,
+            "llm-sagemaker",
+        )
+    }
+}
+
+#[derive(Debug, Clone)]
+struct WasiSleep;
+
+impl WasiSleep {
+    fn new() -> Self {
+        Self
+    }
+}
+
+impl AsyncSleep for WasiSleep {
+    fn sleep(&self, duration: std::time::Duration) -> Sleep {
+        let reactor = Reactor::current();
+        let nanos = duration.as_nanos() as u64;
+        let pollable = reactor.schedule(monotonic_clock::subscribe_duration(nanos));
+
+        let fut = pollable.wait_for();
+        Sleep::new(Box::pin(UnsafeFuture::new(fut)))
+    }
+}
@@ -0,0 +1,139 @@
+use client::SageMaker;
+use golem_llm::{
+    config::get_config_key_or_none,
+    durability::{DurableLLM, ExtendedGuest},
+    files::FileProvider,
+    fine_tuning::FineTuningProvider,
+    golem::llm::llm::{
+        ChatEvent, ChatStream, Config, Error, ErrorCode, Guest, Message, ToolCall, ToolResult,
+    },
+    LOGGING_STATE,
+};
+use golem_llm_aws::async_utils::get_async_runtime;
+use golem_rust::bindings::wasi::clocks::monotonic_clock;
+use stream::SageMakerChatStream;
+use templates::{
+    parse_response, render_request, tool_results_to_messages, DEFAULT_REQUEST_TEMPLATE,
+    DEFAULT_RESPONSE_PATH, REQUEST_TEMPLATE_OPTION, RESPONSE_PATH_OPTION,
+};
+
+mod client;
+mod stream;
+mod templates;
+
+struct SageMakerComponent;
+
+impl SageMakerComponent {
+    const REQUEST_TEMPLATE_ENV_VAR_NAME: &'static str = "SAGEMAKER_REQUEST_TEMPLATE";
+    const RESPONSE_PATH_ENV_VAR_NAME: &'static str = "SAGEMAKER_RESPONSE_PATH";
+
+    /// Invokes the endpoint named by the config's model. The templates are taken from the
+    /// request's provider options, then from the environment, then default to the Hugging Face
+    /// TGI format.
+    fn invoke(messages: Vec<Message>, config: Config) -> ChatEvent {
+        if !config.tools.is_empty() {
+            return ChatEvent::Error(Error {
+                code: ErrorCode::Unsupported,
+                message: "SageMaker endpoints do not support tools natively, enable the `emulate_tools` provider option to use them".to_string(),
+                provider_error_json: None,
+            });
+        }
+
+        let request_template = Self::option_or_env(
+            &config,
+            REQUEST_TEMPLATE_OPTION,
+            Self::REQUEST_TEMPLATE_ENV_VAR_NAME,
+        )
+        .unwrap_or_else(|| DEFAULT_REQUEST_TEMPLATE.to_string());
+        let response_path = Self::option_or_env(
+            &config,
+            RESPONSE_PATH_OPTION,
+            Self::RESPONSE_PATH_ENV_VAR_NAME,
+        )
+        .unwrap_or_else(|| DEFAULT_RESPONSE_PATH.to_string());
+
+        let payload = match render_request(&request_template, &messages, &config) {
+            Ok(payload) => payload,
+            Err(err) => return ChatEvent::Error(err),
+        };
+
+        let runtime = get_async_runtime();
+
+        runtime.block_on(async {
+            let sagemaker = get_sagemaker_client().await;
+
+            match sagemaker {
+                Ok(client) => match client.invoke_endpoint(&config.model, payload).await {
+                    Ok((response, request_id)) => {
+                        match parse_response(&response, &response_path, request_id) {
+                            Ok(response) => ChatEvent::Message(response),
+                            Err(err) => ChatEvent::Error(err),
+                        }
+                    }
+                    Err(err) => ChatEvent::Error(err),
+                },
+                Err(err) => ChatEvent::Error(err),
+            }
+        })
+    }
+
+    fn option_or_env(config: &Config, option: &str, env_var_name: &str) -> Option<String> {
+        config
+            .provider_options
+            .iter()
+            .find(|kv| kv.key == option)
+            .map(|kv| kv.value.clone())
+            .or_else(|| get_config_key_or_none(env_var_name))
+    }
+}
+
+impl Guest for SageMakerComponent {
+    type ChatStream = SageMakerChatStream;
+
+    fn send(messages: Vec<Message>, config: Config) -> ChatEvent {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        Self::invoke(messages, config)
+    }
+
+    fn continue_(
+        messages: Vec<Message>,
+        tool_results: Vec<(ToolCall, ToolResult)>,
+        config: Config,
+    ) -> ChatEvent {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let mut messages = messages;
+        messages.extend(tool_results_to_messages(tool_results));
+        Self::invoke(messages, config)
+    }
+
+    fn stream(messages: Vec<Message>, config: Config) -> ChatStream {
+        ChatStream::new(Self::unwrapped_stream(messages, config))
+    }
+}
+
+impl ExtendedGuest for SageMakerComponent {
+    fn unwrapped_stream(messages: Vec<Message>, config: Config) -> Self::ChatStream {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        SageMakerChatStream::new(Self::invoke(messages, config))
+    }
+
+    fn subscribe(_stream: &Self::ChatStream) -> golem_rust::wasm_rpc::Pollable {
+        // this function will never get called in sagemaker implementation because of `golem-llm/nopoll` feature flag
+        monotonic_clock::subscribe_duration(0)
+    }
+}
+
+async fn get_sagemaker_client() -> Result<SageMaker, Error> {
+    SageMaker::new().await
+}
+
+impl FineTuningProvider for SageMakerComponent {}
+
+impl FileProvider for SageMakerComponent {}
+
+type DurableSageMakerComponent = DurableLLM<SageMakerComponent>;
+
+golem_llm::export_llm!(DurableSageMakerComponent with_types_in golem_llm);
@@ -0,0 +1,41 @@
+use golem_llm::golem::llm::llm::{self, ChatEvent, StreamDelta, StreamEvent};
+use std::cell::RefCell;
+
+/// The endpoints' streaming payloads are specific to each model container, so the stream invokes
+/// the endpoint once and emits the whole completion as a single delta followed by the finish
+/// event.
+pub struct SageMakerChatStream {
+    events: RefCell<Option<Vec<StreamEvent>>>,
+}
+
+impl SageMakerChatStream {
+    pub fn new(event: ChatEvent) -> SageMakerChatStream {
+        let events = match event {
+            ChatEvent::Message(response) => vec![
+                StreamEvent::Delta(StreamDelta {
+                    content: Some(response.content),
+                    tool_calls: (!response.tool_calls.is_empty()).then_some(response.tool_calls),
+                }),
+                StreamEvent::Finish(response.metadata),
+            ],
+            ChatEvent::ToolRequest(tool_calls) => vec![StreamEvent::Delta(StreamDelta {
+                content: None,
+                tool_calls: Some(tool_calls),
+            })],
+            ChatEvent::Error(error) => vec![StreamEvent::Error(error)],
+        };
+        SageMakerChatStream {
+            events: RefCell::new(Some(events)),
+        }
+    }
+}
+
+impl llm::GuestChatStream for SageMakerChatStream {
+    fn get_next(&self) -> Option<Vec<StreamEvent>> {
+        Some(self.events.borrow_mut().take().unwrap_or_default())
+    }
+
+    fn blocking_get_next(&self) -> Vec<StreamEvent> {
+        self.get_next().unwrap_or_default()
+    }
+}
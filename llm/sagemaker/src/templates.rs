@@ -0,0 +1,363 @@
+use golem_llm::golem::llm::llm::{
+    CompleteResponse, Config, ContentPart, Error, ErrorCode, FinishReason, Message,
+    ResponseMetadata, Role, ToolCall, ToolResult,
+};
+use serde_json::{json, Map, Value};
+
+/// Request template of the Hugging Face TGI and LMI containers, used when none is configured
+pub const DEFAULT_REQUEST_TEMPLATE: &str = r#"{
+    "inputs": "{{prompt}}",
+    "parameters": {
+        "max_new_tokens": "{{max_tokens}}",
+        "temperature": "{{temperature}}",
+        "stop": "{{stop_sequences}}",
+        "return_full_text": false
+    }
+}"#;
+
+/// Path of the generated text in the responses of the Hugging Face TGI and LMI containers
+pub const DEFAULT_RESPONSE_PATH: &str = "0.generated_text";
+
+pub const REQUEST_TEMPLATE_OPTION: &str = "request_template";
+pub const RESPONSE_PATH_OPTION: &str = "response_path";
+
+/// Renders the request payload of the endpoint from a JSON template.
+///
+/// A string value consisting of a single `{{variable}}` placeholder is replaced by the variable's
+/// JSON value, and removed from its object or array if the variable is not set. Placeholders
+/// embedded in longer strings are replaced by the variable's text. The variables are `prompt`,
+/// `messages`, `system`, `model`, `max_tokens`, `temperature`, `stop_sequences` and every
+/// provider option of the request.
+pub fn render_request(
+    template: &str,
+    messages: &[Message],
+    config: &Config,
+) -> Result<Value, Error> {
+    let template: Value = serde_json::from_str(template).map_err(|err| Error {
+        code: ErrorCode::InvalidRequest,
+        message: format!("Failed to parse the request template: {err}"),
+        provider_error_json: None,
+    })?;
+
+    let mut variables = Map::new();
+    for kv in &config.provider_options {
+        if kv.key != REQUEST_TEMPLATE_OPTION && kv.key != RESPONSE_PATH_OPTION {
+            let value =
+                serde_json::from_str(&kv.value).unwrap_or_else(|_| Value::String(kv.value.clone()));
+            variables.insert(kv.key.clone(), value);
+        }
+    }
+    variables.insert(
+        "prompt".to_string(),
+        Value::String(render_prompt(messages)?),
+    );
+    variables.insert("messages".to_string(), render_messages(messages)?);
+    if let Some(system) = system_prompt(messages) {
+        variables.insert("system".to_string(), Value::String(system));
+    }
+    variables.insert("model".to_string(), Value::String(config.model.clone()));
+    if let Some(max_tokens) = config.max_tokens {
+        variables.insert("max_tokens".to_string(), json!(max_tokens));
+    }
+    if let Some(temperature) = config.temperature {
+        variables.insert("temperature".to_string(), json!(temperature));
+    }
+    if let Some(stop_sequences) = &config.stop_sequences {
+        variables.insert("stop_sequences".to_string(), json!(stop_sequences));
+    }
+
+    Ok(substitute(template, &variables).unwrap_or(Value::Null))
+}
+
+/// Extracts the completion from the endpoint's response, following a dotted path of object keys
+/// and array indices such as `0.generated_text` or `choices.0.message.content`.
+pub fn parse_response(
+    response: &Value,
+    path: &str,
+    id: Option<String>,
+) -> Result<CompleteResponse, Error> {
+    let text = path
+        .split('.')
+        .filter(|segment| !segment.is_empty())
+        .try_fold(response, |value, segment| match value {
+            Value::Array(items) => segment.parse::<usize>().ok().and_then(|i| items.get(i)),
+            Value::Object(fields) => fields.get(segment),
+            _ => None,
+        })
+        .and_then(Value::as_str)
+        .ok_or_else(|| Error {
+            code: ErrorCode::InternalError,
+            message: format!("The endpoint's response has no text at `{path}`"),
+            provider_error_json: Some(response.to_string()),
+        })?;
+
+    Ok(CompleteResponse {
+        id: id.clone().unwrap_or_default(),
+        content: vec![ContentPart::Text(text.to_string())],
+        tool_calls: vec![],
+        metadata: ResponseMetadata {
+            finish_reason: Some(FinishReason::Stop),
+            usage: None,
+            provider_id: id,
+            timestamp: None,
+            provider_metadata_json: None,
+        },
+    })
+}
+
+/// Turns the tool results passed to `continue` into tool messages
+pub fn tool_results_to_messages(tool_results: Vec<(ToolCall, ToolResult)>) -> Vec<Message> {
+    tool_results
+        .into_iter()
+        .map(|(tool_call, tool_result)| {
+            let text = match tool_result {
+                ToolResult::Success(success) => success.result_json,
+                ToolResult::Error(error) => format!("Error: {}", error.error_message),
+            };
+            Message {
+                role: Role::Tool,
+                name: Some(tool_call.name),
+                content: vec![ContentPart::Text(text)],
+            }
+        })
+        .collect()
+}
+
+fn substitute(value: Value, variables: &Map<String, Value>) -> Option<Value> {
+    match value {
+        Value::String(text) => {
+            if let Some(name) = placeholder(&text) {
+                return variables.get(name).cloned();
+            }
+            Some(Value::String(substitute_text(&text, variables)))
+        }
+        Value::Array(items) => Some(Value::Array(
+            items
+                .into_iter()
+                .filter_map(|item| substitute(item, variables))
+                .collect(),
+        )),
+        Value::Object(fields) => Some(Value::Object(
+            fields
+                .into_iter()
+                .filter_map(|(key, value)| Some((key, substitute(value, variables)?)))
+                .collect(),
+        )),
+        value => Some(value),
+    }
+}
+
+/// Replaces the placeholders embedded in a string in a single pass, so that placeholders
+/// appearing in the substituted values, such as in the prompt, are kept as they are
+fn substitute_text(text: &str, variables: &Map<String, Value>) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        let Some(end) = rest[start + 2..].find("}}") else {
+            break;
+        };
+        let end = start + 2 + end + 2;
+        result.push_str(&rest[..start]);
+        match variables.get(rest[start + 2..end - 2].trim()) {
+            Some(Value::String(value)) => result.push_str(value),
+            Some(value) => result.push_str(&value.to_string()),
+            None => result.push_str(&rest[start..end]),
+        }
+        rest = &rest[end..];
+    }
+    result.push_str(rest);
+    result
+}
+
+fn placeholder(text: &str) -> Option<&str> {
+    let name = text.strip_prefix("{{")?.strip_suffix("}}")?.trim();
+    (!name.contains(['{', '}'])).then_some(name)
+}
+
+/// Renders the conversation as a single prompt for models without a chat template
+fn render_prompt(messages: &[Message]) -> Result<String, Error> {
+    let mut prompt = String::new();
+    for message in messages {
+        let speaker = match message.role {
+            Role::System => "System",
+            Role::User => "User",
+            Role::Assistant => "Assistant",
+            Role::Tool => "Tool",
+        };
+        prompt.push_str(&format!("{speaker}: {}\n\n", message_text(message)?));
+    }
+    prompt.push_str("Assistant:");
+    Ok(prompt)
+}
+
+/// Renders the conversation as a list of `role` and `content` objects, for containers applying
+/// the model's chat template themselves
+fn render_messages(messages: &[Message]) -> Result<Value, Error> {
+    let mut result = Vec::new();
+    for message in messages {
+        let role = match message.role {
+            Role::System => "system",
+            Role::User => "user",
+            Role::Assistant => "assistant",
+            Role::Tool => "tool",
+        };
+        result.push(json!({ "role": role, "content": message_text(message)? }));
+    }
+    Ok(Value::Array(result))
+}
+
+fn system_prompt(messages: &[Message]) -> Option<String> {
+    let system = messages
+        .iter()
+        .filter(|message| message.role == Role::System)
+        .filter_map(|message| message_text(message).ok())
+        .collect::<Vec<_>>();
+    (!system.is_empty()).then(|| system.join("\n"))
+}
+
+fn message_text(message: &Message) -> Result<String, Error> {
+    let mut parts = Vec::new();
+    for content_part in &message.content {
+        match content_part {
+            ContentPart::Text(text) => parts.push(text.as_str()),
+            ContentPart::Image(_) | ContentPart::File(_) => {
+                return Err(Error {
+                    code: ErrorCode::Unsupported,
+                    message: "SageMaker endpoints only accept text messages".to_string(),
+                    provider_error_json: None,
+                })
+            }
+        }
+    }
+    Ok(parts.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use golem_llm::golem::llm::llm::Kv;
+
+    fn config(provider_options: Vec<(&str, &str)>) -> Config {
+        Config {
+            model: "llama-3".to_string(),
+            temperature: Some(0.5),
+            max_tokens: None,
+            stop_sequences: None,
+            tools: vec![],
+            tool_choice: None,
+            provider_options: provider_options
+                .into_iter()
+                .map(|(key, value)| Kv {
+                    key: key.to_string(),
+                    value: value.to_string(),
+                })
+                .collect(),
+        }
+    }
+
+    fn message(role: Role, text: &str) -> Message {
+        Message {
+            role,
+            name: None,
+            content: vec![ContentPart::Text(text.to_string())],
+        }
+    }
+
+    #[test]
+    fn placeholders_are_replaced_by_json_values() {
+        let request = render_request(
+            r#"{"inputs": "{{prompt}}", "parameters": {"temperature": "{{temperature}}", "top_p": "{{top_p}}"}}"#,
+            &[message(Role::User, "Hello")],
+            &config(vec![("top_p", "0.9")]),
+        )
+        .unwrap();
+
+        assert_eq!(
+            request,
+            json!({
+                "inputs": "User: Hello\n\nAssistant:",
+                "parameters": { "temperature": 0.5, "top_p": 0.9 }
+            })
+        );
+    }
+
+    #[test]
+    fn substituted_text_is_escaped_and_not_substituted_again() {
+        let messages = [
+            message(Role::System, "Be brief"),
+            message(Role::User, "Say \"{{system}}\"\nand {{model}}"),
+        ];
+        let request = render_request(
+            r#"{"inputs": "[{{model}}] {{prompt}}", "messages": "{{messages}}"}"#,
+            &messages,
+            &config(vec![]),
+        )
+        .unwrap();
+
+        let payload = serde_json::to_string(&request).unwrap();
+        let request: Value = serde_json::from_str(&payload).unwrap();
+        assert_eq!(
+            request["inputs"],
+            "[llama-3] System: Be brief\n\nUser: Say \"{{system}}\"\nand {{model}}\n\nAssistant:"
+        );
+        assert_eq!(
+            request["messages"][1]["content"],
+            "Say \"{{system}}\"\nand {{model}}"
+        );
+    }
+
+    #[test]
+    fn missing_placeholders_are_removed_or_kept() {
+        let request = render_request(
+            r#"{"inputs": "{{prompt}}", "parameters": {"max_new_tokens": "{{max_tokens}}", "stop": ["{{stop_sequences}}"]}, "note": "{{unknown}} as text"}"#,
+            &[message(Role::User, "Hello")],
+            &config(vec![]),
+        )
+        .unwrap();
+
+        assert_eq!(request["parameters"], json!({ "stop": [] }));
+        assert_eq!(request["note"], "{{unknown}} as text");
+    }
+
+    #[test]
+    fn invalid_templates_are_invalid_requests() {
+        let error = render_request("{\"inputs\": ", &[], &config(vec![])).unwrap_err();
+
+        assert_eq!(error.code, ErrorCode::InvalidRequest);
+    }
+
+    #[test]
+    fn responses_are_read_at_their_path() {
+        let tgi = json!([{ "generated_text": "Hi there" }]);
+        let openai = json!({ "choices": [{ "message": { "content": "Hello" } }] });
+
+        let response =
+            parse_response(&tgi, DEFAULT_RESPONSE_PATH, Some("req-1".to_string())).unwrap();
+        assert_eq!(
+            response.content,
+            vec![ContentPart::Text("Hi there".to_string())]
+        );
+        assert_eq!(response.id, "req-1");
+
+        let response = parse_response(&openai, "choices.0.message.content", None).unwrap();
+        assert_eq!(
+            response.content,
+            vec![ContentPart::Text("Hello".to_string())]
+        );
+    }
+
+    #[test]
+    fn responses_without_text_at_their_path_are_errors() {
+        let response = json!({ "choices": [{ "message": { "content": null } }] });
+
+        for path in [
+            "choices.1.message.content",
+            "choices.0.message.content",
+            "outputs",
+        ] {
+            let error = parse_response(&response, path, None).unwrap_err();
+            assert_eq!(error.code, ErrorCode::InternalError);
+            assert_eq!(error.provider_error_json, Some(response.to_string()));
+        }
+    }
+}
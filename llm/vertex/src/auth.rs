@@ -0,0 +1,175 @@
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine as _;
+use golem_llm::deterministic;
+use golem_llm::error::{error_code_from_status, from_reqwest_error};
+use golem_llm::golem::llm::llm::{Error, ErrorCode};
+use log::trace;
+use reqwest::{Client, Method, Response};
+use rsa::pkcs1v15::SigningKey;
+use rsa::pkcs8::DecodePrivateKey;
+use rsa::sha2::Sha256;
+use rsa::signature::{SignatureEncoding, Signer};
+use rsa::RsaPrivateKey;
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::time::UNIX_EPOCH;
+
+const SCOPE: &str = "https://www.googleapis.com/auth/cloud-platform";
+const DEFAULT_TOKEN_URI: &str = "https://oauth2.googleapis.com/token";
+const JWT_BEARER_GRANT_TYPE: &str = "urn%3Aietf%3Aparams%3Aoauth%3Agrant-type%3Ajwt-bearer";
+
+/// Lifetime of the signed assertions, the maximum accepted by Google
+const ASSERTION_LIFETIME_SECS: u64 = 3600;
+/// Access tokens are minted again when they expire in less than this
+const TOKEN_REFRESH_MARGIN_SECS: u64 = 60;
+
+/// The fields of a service account key file used to mint access tokens
+#[derive(Clone, Deserialize)]
+pub struct ServiceAccountKey {
+    pub project_id: Option<String>,
+    pub private_key: String,
+    pub client_email: String,
+    pub token_uri: Option<String>,
+}
+
+impl ServiceAccountKey {
+    pub fn from_json(json: &str) -> Result<Self, Error> {
+        serde_json::from_str(json).map_err(|err| Error {
+            code: ErrorCode::AuthenticationFailed,
+            message: format!("Failed to parse service account key: {err}"),
+            provider_error_json: None,
+        })
+    }
+}
+
+#[derive(Serialize)]
+struct Claims<'a> {
+    iss: &'a str,
+    scope: &'a str,
+    aud: &'a str,
+    iat: u64,
+    exp: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    expires_in: u64,
+}
+
+struct CachedToken {
+    client_email: String,
+    access_token: String,
+    expires_at: u64,
+}
+
+thread_local! {
+    static TOKEN: RefCell<Option<CachedToken>> = const { RefCell::new(None) };
+}
+
+/// Returns an OAuth2 access token for the service account, minting a new one with a signed
+/// JWT assertion when there is no cached token or it is about to expire.
+pub fn access_token(client: &Client, key: &ServiceAccountKey) -> Result<String, Error> {
+    let now = now_secs();
+
+    let cached = TOKEN.with_borrow(|token| {
+        token
+            .as_ref()
+            .filter(|token| {
+                token.client_email == key.client_email
+                    && token.expires_at > now + TOKEN_REFRESH_MARGIN_SECS
+            })
+            .map(|token| token.access_token.clone())
+    });
+    if let Some(access_token) = cached {
+        return Ok(access_token);
+    }
+
+    let token = mint_access_token(client, key, now)?;
+    let access_token = token.access_token.clone();
+    TOKEN.with_borrow_mut(|cached| {
+        *cached = Some(CachedToken {
+            client_email: key.client_email.clone(),
+            access_token: token.access_token,
+            expires_at: now + token.expires_in,
+        })
+    });
+    Ok(access_token)
+}
+
+fn mint_access_token(
+    client: &Client,
+    key: &ServiceAccountKey,
+    now: u64,
+) -> Result<TokenResponse, Error> {
+    let token_uri = key.token_uri.as_deref().unwrap_or(DEFAULT_TOKEN_URI);
+    let assertion = sign_assertion(key, token_uri, now)?;
+
+    trace!("Requesting access token for {}", key.client_email);
+
+    let response: Response = client
+        .request(Method::POST, token_uri)
+        .header(
+            reqwest::header::CONTENT_TYPE,
+            "application/x-www-form-urlencoded",
+        )
+        .body(format!(
+            "grant_type={JWT_BEARER_GRANT_TYPE}&assertion={assertion}"
+        ))
+        .send()
+        .map_err(|err| from_reqwest_error("Token request failed", err))?;
+
+    let status = response.status();
+    if status.is_success() {
+        response
+            .json::<TokenResponse>()
+            .map_err(|err| from_reqwest_error("Failed to decode token response body", err))
+    } else {
+        let error_body = response
+            .text()
+            .map_err(|err| from_reqwest_error("Failed to receive error response body", err))?;
+
+        Err(Error {
+            code: error_code_from_status(status),
+            message: format!("Token request failed with {status}"),
+            provider_error_json: Some(error_body),
+        })
+    }
+}
+
+/// Creates the RS256 signed JWT assertion exchanged for an access token
+fn sign_assertion(key: &ServiceAccountKey, token_uri: &str, now: u64) -> Result<String, Error> {
+    let header = URL_SAFE_NO_PAD.encode(r#"{"alg":"RS256","typ":"JWT"}"#);
+    let claims = serde_json::to_vec(&Claims {
+        iss: &key.client_email,
+        scope: SCOPE,
+        aud: token_uri,
+        iat: now,
+        exp: now + ASSERTION_LIFETIME_SECS,
+    })
+    .map_err(|err| Error {
+        code: ErrorCode::InternalError,
+        message: format!("Failed to encode JWT claims: {err}"),
+        provider_error_json: None,
+    })?;
+    let signing_input = format!("{header}.{}", URL_SAFE_NO_PAD.encode(claims));
+
+    let private_key = RsaPrivateKey::from_pkcs8_pem(&key.private_key).map_err(|err| Error {
+        code: ErrorCode::AuthenticationFailed,
+        message: format!("Failed to parse service account private key: {err}"),
+        provider_error_json: None,
+    })?;
+    let signature = SigningKey::<Sha256>::new(private_key).sign(signing_input.as_bytes());
+
+    Ok(format!(
+        "{signing_input}.{}",
+        URL_SAFE_NO_PAD.encode(signature.to_bytes())
+    ))
+}
+
+fn now_secs() -> u64 {
+    deterministic::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
@@ -0,0 +1,302 @@
+use crate::client::{
+    Blob, Content, FileData, FunctionCall, FunctionCallingConfig, FunctionCallingMode,
+    FunctionDeclaration, FunctionResponse, GenerateContentRequest, GenerateContentResponse,
+    GenerationConfig, Part, Tool, ToolConfig, UsageMetadata,
+};
+use base64::{engine::general_purpose, Engine as _};
+use golem_llm::golem::llm::llm::{
+    ChatEvent, CompleteResponse, Config, ContentPart, Error, ErrorCode, FinishReason,
+    ImageReference, ImageSource, Message, ResponseMetadata, Role, ToolCall, ToolDefinition,
+    ToolResult, Usage,
+};
+use std::collections::HashMap;
+
+pub fn messages_to_request(
+    messages: Vec<Message>,
+    config: Config,
+) -> Result<GenerateContentRequest, Error> {
+    let options = config
+        .provider_options
+        .into_iter()
+        .map(|kv| (kv.key, kv.value))
+        .collect::<HashMap<_, _>>();
+
+    let mut contents = Vec::new();
+    let mut system_parts = Vec::new();
+    for message in &messages {
+        match message.role {
+            Role::System => system_parts.extend(message_to_parts(message)),
+            Role::User | Role::Tool => contents.push(Content {
+                role: Some(crate::client::Role::User),
+                parts: message_to_parts(message),
+            }),
+            Role::Assistant => contents.push(Content {
+                role: Some(crate::client::Role::Model),
+                parts: message_to_parts(message),
+            }),
+        }
+    }
+
+    let tools = if config.tools.is_empty() {
+        None
+    } else {
+        let mut function_declarations = Vec::new();
+        for tool in &config.tools {
+            function_declarations.push(tool_definition_to_function_declaration(tool)?);
+        }
+        Some(vec![Tool {
+            function_declarations,
+        }])
+    };
+
+    Ok(GenerateContentRequest {
+        contents,
+        system_instruction: if system_parts.is_empty() {
+            None
+        } else {
+            Some(Content {
+                role: None,
+                parts: system_parts,
+            })
+        },
+        tools,
+        tool_config: config.tool_choice.map(convert_tool_choice),
+        generation_config: GenerationConfig {
+            temperature: config.temperature,
+            max_output_tokens: config.max_tokens,
+            stop_sequences: config.stop_sequences,
+            top_p: options
+                .get("top_p")
+                .and_then(|top_p_s| top_p_s.parse::<f32>().ok()),
+            top_k: options
+                .get("top_k")
+                .and_then(|top_k_s| top_k_s.parse::<u32>().ok()),
+            seed: options
+                .get("seed")
+                .and_then(|seed_s| seed_s.parse::<i32>().ok()),
+        },
+    })
+}
+
+fn convert_tool_choice(tool_name: String) -> ToolConfig {
+    let (mode, allowed_function_names) = match tool_name.as_str() {
+        "auto" => (FunctionCallingMode::Auto, None),
+        "none" => (FunctionCallingMode::None, None),
+        "any" | "required" => (FunctionCallingMode::Any, None),
+        _ => (FunctionCallingMode::Any, Some(vec![tool_name])),
+    };
+    ToolConfig {
+        function_calling_config: FunctionCallingConfig {
+            mode,
+            allowed_function_names,
+        },
+    }
+}
+
+pub fn process_response(response: GenerateContentResponse) -> ChatEvent {
+    let Some(candidate) = response.candidates.into_iter().next() else {
+        return ChatEvent::Error(Error {
+            code: ErrorCode::InvalidRequest,
+            message: "The response does not contain any candidates".to_string(),
+            provider_error_json: None,
+        });
+    };
+
+    let mut contents = Vec::new();
+    let mut tool_calls = Vec::new();
+    for part in candidate
+        .content
+        .map(|content| content.parts)
+        .unwrap_or_default()
+    {
+        if let Some(text) = part.text {
+            contents.push(ContentPart::Text(text));
+        }
+        if let Some(blob) = part.inline_data {
+            match general_purpose::STANDARD.decode(blob.data) {
+                Ok(data) => {
+                    contents.push(ContentPart::Image(ImageReference::Inline(ImageSource {
+                        data,
+                        mime_type: blob.mime_type,
+                        detail: None,
+                    })))
+                }
+                Err(e) => {
+                    return ChatEvent::Error(Error {
+                        code: ErrorCode::InvalidRequest,
+                        message: format!("Failed to decode base64 image data: {e}"),
+                        provider_error_json: None,
+                    });
+                }
+            }
+        }
+        if let Some(function_call) = part.function_call {
+            let index = tool_calls.len();
+            tool_calls.push(function_call_to_tool_call(function_call, index));
+        }
+    }
+
+    if contents.is_empty() {
+        ChatEvent::ToolRequest(tool_calls)
+    } else {
+        let metadata = ResponseMetadata {
+            finish_reason: candidate
+                .finish_reason
+                .as_deref()
+                .map(finish_reason_from_str),
+            usage: response.usage_metadata.map(convert_usage),
+            provider_id: None,
+            timestamp: response.create_time,
+            provider_metadata_json: None,
+        };
+
+        ChatEvent::Message(CompleteResponse {
+            id: response.response_id.unwrap_or_default(),
+            content: contents,
+            tool_calls,
+            metadata,
+        })
+    }
+}
+
+/// Gemini does not identify function calls, so the ID is made of the function's name and the
+/// position of the call in the response
+pub fn function_call_to_tool_call(function_call: FunctionCall, index: usize) -> ToolCall {
+    ToolCall {
+        id: format!("{}-{index}", function_call.name),
+        name: function_call.name,
+        arguments_json: serde_json::to_string(&function_call.args).unwrap(),
+    }
+}
+
+pub fn tool_results_to_contents(tool_results: Vec<(ToolCall, ToolResult)>) -> Vec<Content> {
+    let mut contents = Vec::new();
+
+    for (tool_call, tool_result) in tool_results {
+        contents.push(Content {
+            role: Some(crate::client::Role::Model),
+            parts: vec![Part {
+                function_call: Some(FunctionCall {
+                    name: tool_call.name.clone(),
+                    args: serde_json::from_str(&tool_call.arguments_json).unwrap_or_default(),
+                }),
+                ..Default::default()
+            }],
+        });
+        let response = match tool_result {
+            ToolResult::Success(success) => serde_json::json!({
+                "result": serde_json::from_str::<serde_json::Value>(&success.result_json)
+                    .unwrap_or(serde_json::Value::String(success.result_json)),
+            }),
+            ToolResult::Error(error) => serde_json::json!({
+                "error": error.error_message,
+            }),
+        };
+        contents.push(Content {
+            role: Some(crate::client::Role::User),
+            parts: vec![Part {
+                function_response: Some(FunctionResponse {
+                    name: tool_call.name,
+                    response,
+                }),
+                ..Default::default()
+            }],
+        });
+    }
+
+    contents
+}
+
+pub fn finish_reason_from_str(finish_reason: &str) -> FinishReason {
+    match finish_reason {
+        "STOP" => FinishReason::Stop,
+        "MAX_TOKENS" => FinishReason::Length,
+        "SAFETY" | "RECITATION" | "BLOCKLIST" | "PROHIBITED_CONTENT" | "SPII" => {
+            FinishReason::ContentFilter
+        }
+        "MALFORMED_FUNCTION_CALL" => FinishReason::Error,
+        _ => FinishReason::Other,
+    }
+}
+
+pub fn convert_usage(usage: UsageMetadata) -> Usage {
+    Usage {
+        input_tokens: usage.prompt_token_count,
+        output_tokens: usage.candidates_token_count,
+        total_tokens: usage.total_token_count,
+    }
+}
+
+fn message_to_parts(message: &Message) -> Vec<Part> {
+    let mut result = Vec::new();
+
+    for content_part in &message.content {
+        match content_part {
+            ContentPart::Text(text) => result.push(Part {
+                text: Some(text.clone()),
+                ..Default::default()
+            }),
+            ContentPart::Image(ImageReference::Url(image_url)) => result.push(Part {
+                file_data: Some(FileData {
+                    mime_type: mime_type_from_url(&image_url.url).to_string(),
+                    file_uri: image_url.url.clone(),
+                }),
+                ..Default::default()
+            }),
+            ContentPart::Image(ImageReference::Inline(image_source)) => result.push(Part {
+                inline_data: Some(Blob {
+                    mime_type: image_source.mime_type.clone(),
+                    data: general_purpose::STANDARD.encode(&image_source.data),
+                }),
+                ..Default::default()
+            }),
+            ContentPart::File(file) => result.push(Part {
+                file_data: Some(FileData {
+                    mime_type: file
+                        .mime_type
+                        .clone()
+                        .unwrap_or_else(|| mime_type_from_url(&file.id).to_string()),
+                    file_uri: file.id.clone(),
+                }),
+                ..Default::default()
+            }),
+        }
+    }
+
+    result
+}
+
+/// Vertex AI requires the MIME type of referenced files, guessed here from the URL's extension
+fn mime_type_from_url(url: &str) -> &'static str {
+    let path = url.split(['?', '#']).next().unwrap_or(url).to_lowercase();
+    if path.ends_with(".png") {
+        "image/png"
+    } else if path.ends_with(".gif") {
+        "image/gif"
+    } else if path.ends_with(".webp") {
+        "image/webp"
+    } else if path.ends_with(".pdf") {
+        "application/pdf"
+    } else if path.ends_with(".txt") {
+        "text/plain"
+    } else {
+        "image/jpeg"
+    }
+}
+
+fn tool_definition_to_function_declaration(
+    tool: &ToolDefinition,
+) -> Result<FunctionDeclaration, Error> {
+    match serde_json::from_str(&tool.parameters_schema) {
+        Ok(value) => Ok(FunctionDeclaration {
+            name: tool.name.clone(),
+            description: tool.description.clone(),
+            parameters: value,
+        }),
+        Err(error) => Err(Error {
+            code: ErrorCode::InternalError,
+            message: format!("Failed to parse tool parameters for {}: {error}", tool.name),
+            provider_error_json: None,
+        }),
+    }
+}
@@ -0,0 +1,247 @@
+mod auth;
+mod client;
+mod conversions;
+
+use crate::auth::ServiceAccountKey;
+use crate::client::{GenerateContentRequest, GenerateContentResponse, GenerativeModelsApi};
+use crate::conversions::{
+    convert_usage, finish_reason_from_str, function_call_to_tool_call, messages_to_request,
+    process_response, tool_results_to_contents,
+};
+use golem_llm::chat_stream::{LlmChatStream, LlmChatStreamState};
+use golem_llm::config::{get_config_key, get_config_key_or_none};
+use golem_llm::durability::{DurableLLM, ExtendedGuest};
+use golem_llm::event_source::EventSource;
+use golem_llm::files::FileProvider;
+use golem_llm::fine_tuning::FineTuningProvider;
+use golem_llm::golem::llm::llm::{
+    ChatEvent, ChatStream, Config, ContentPart, Error, ErrorCode, FinishReason, Guest, Message,
+    ResponseMetadata, StreamDelta, StreamEvent, ToolCall, ToolResult, Usage,
+};
+use golem_llm::LOGGING_STATE;
+use golem_rust::wasm_rpc::Pollable;
+use log::trace;
+use std::cell::{Ref, RefCell, RefMut};
+
+struct VertexChatStream {
+    stream: RefCell<Option<EventSource>>,
+    failure: Option<Error>,
+    finished: RefCell<bool>,
+    finish_reason: RefCell<Option<FinishReason>>,
+    usage: RefCell<Option<Usage>>,
+    tool_calls: RefCell<usize>,
+}
+
+impl VertexChatStream {
+    pub fn new(stream: EventSource) -> LlmChatStream<Self> {
+        LlmChatStream::new(VertexChatStream {
+            stream: RefCell::new(Some(stream)),
+            failure: None,
+            finished: RefCell::new(false),
+            finish_reason: RefCell::new(None),
+            usage: RefCell::new(None),
+            tool_calls: RefCell::new(0),
+        })
+    }
+
+    pub fn failed(error: Error) -> LlmChatStream<Self> {
+        LlmChatStream::new(VertexChatStream {
+            stream: RefCell::new(None),
+            failure: Some(error),
+            finished: RefCell::new(false),
+            finish_reason: RefCell::new(None),
+            usage: RefCell::new(None),
+            tool_calls: RefCell::new(0),
+        })
+    }
+}
+
+impl LlmChatStreamState for VertexChatStream {
+    fn failure(&self) -> &Option<Error> {
+        &self.failure
+    }
+
+    fn is_finished(&self) -> bool {
+        *self.finished.borrow()
+    }
+
+    fn set_finished(&self) {
+        *self.finished.borrow_mut() = true;
+    }
+
+    fn stream(&self) -> Ref<Option<EventSource>> {
+        self.stream.borrow()
+    }
+
+    fn stream_mut(&self) -> RefMut<Option<EventSource>> {
+        self.stream.borrow_mut()
+    }
+
+    fn decode_message(&self, raw: &str) -> Result<Option<StreamEvent>, String> {
+        trace!("Received raw stream event: {raw}");
+        let chunk: GenerateContentResponse = serde_json::from_str(raw)
+            .map_err(|err| format!("Failed to deserialize stream event: {err}"))?;
+
+        if let Some(usage) = chunk.usage_metadata {
+            *self.usage.borrow_mut() = Some(convert_usage(usage));
+        }
+
+        let Some(candidate) = chunk.candidates.into_iter().next() else {
+            return Ok(None);
+        };
+        if let Some(finish_reason) = &candidate.finish_reason {
+            *self.finish_reason.borrow_mut() = Some(finish_reason_from_str(finish_reason));
+        }
+
+        let mut content = Vec::new();
+        let mut tool_calls = Vec::new();
+        for part in candidate
+            .content
+            .map(|content| content.parts)
+            .unwrap_or_default()
+        {
+            if let Some(text) = part.text {
+                content.push(ContentPart::Text(text));
+            }
+            if let Some(function_call) = part.function_call {
+                let mut index = self.tool_calls.borrow_mut();
+                tool_calls.push(function_call_to_tool_call(function_call, *index));
+                *index += 1;
+            }
+        }
+
+        if !content.is_empty() || !tool_calls.is_empty() {
+            Ok(Some(StreamEvent::Delta(StreamDelta {
+                content: (!content.is_empty()).then_some(content),
+                tool_calls: (!tool_calls.is_empty()).then_some(tool_calls),
+            })))
+        } else if candidate.finish_reason.is_some() {
+            Ok(Some(StreamEvent::Finish(ResponseMetadata {
+                finish_reason: *self.finish_reason.borrow(),
+                usage: self.usage.borrow().clone(),
+                provider_id: None,
+                timestamp: chunk.create_time,
+                provider_metadata_json: None,
+            })))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+struct VertexComponent;
+
+impl VertexComponent {
+    const SERVICE_ACCOUNT_KEY_ENV_VAR_NAME: &'static str = "VERTEX_SERVICE_ACCOUNT_KEY";
+    const PROJECT_ID_ENV_VAR_NAME: &'static str = "VERTEX_PROJECT_ID";
+    const LOCATION_ENV_VAR_NAME: &'static str = "VERTEX_LOCATION";
+    const DEFAULT_LOCATION: &'static str = "us-central1";
+
+    fn create_client() -> Result<GenerativeModelsApi, Error> {
+        let key =
+            ServiceAccountKey::from_json(&get_config_key(Self::SERVICE_ACCOUNT_KEY_ENV_VAR_NAME)?)?;
+        let project_id = get_config_key_or_none(Self::PROJECT_ID_ENV_VAR_NAME)
+            .or_else(|| key.project_id.clone())
+            .ok_or_else(|| Error {
+                code: ErrorCode::InternalError,
+                message: format!(
+                    "Missing config key: {}, and the service account key has no project_id",
+                    Self::PROJECT_ID_ENV_VAR_NAME
+                ),
+                provider_error_json: None,
+            })?;
+        let location = get_config_key_or_none(Self::LOCATION_ENV_VAR_NAME)
+            .unwrap_or_else(|| Self::DEFAULT_LOCATION.to_string());
+
+        Ok(GenerativeModelsApi::new(key, project_id, location))
+    }
+
+    fn request(
+        client: GenerativeModelsApi,
+        model: &str,
+        request: GenerateContentRequest,
+    ) -> ChatEvent {
+        match client.generate_content(model, request) {
+            Ok(response) => process_response(response),
+            Err(err) => ChatEvent::Error(err),
+        }
+    }
+
+    fn streaming_request(
+        client: GenerativeModelsApi,
+        model: &str,
+        request: GenerateContentRequest,
+    ) -> LlmChatStream<VertexChatStream> {
+        match client.stream_generate_content(model, request) {
+            Ok(stream) => VertexChatStream::new(stream),
+            Err(err) => VertexChatStream::failed(err),
+        }
+    }
+}
+
+impl Guest for VertexComponent {
+    type ChatStream = LlmChatStream<VertexChatStream>;
+
+    fn send(messages: Vec<Message>, config: Config) -> ChatEvent {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let model = config.model.clone();
+        match Self::create_client()
+            .and_then(|client| Ok((client, messages_to_request(messages, config)?)))
+        {
+            Ok((client, request)) => Self::request(client, &model, request),
+            Err(err) => ChatEvent::Error(err),
+        }
+    }
+
+    fn continue_(
+        messages: Vec<Message>,
+        tool_results: Vec<(ToolCall, ToolResult)>,
+        config: Config,
+    ) -> ChatEvent {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let model = config.model.clone();
+        match Self::create_client()
+            .and_then(|client| Ok((client, messages_to_request(messages, config)?)))
+        {
+            Ok((client, mut request)) => {
+                request
+                    .contents
+                    .extend(tool_results_to_contents(tool_results));
+                Self::request(client, &model, request)
+            }
+            Err(err) => ChatEvent::Error(err),
+        }
+    }
+
+    fn stream(messages: Vec<Message>, config: Config) -> ChatStream {
+        ChatStream::new(Self::unwrapped_stream(messages, config))
+    }
+}
+
+impl ExtendedGuest for VertexComponent {
+    fn unwrapped_stream(messages: Vec<Message>, config: Config) -> LlmChatStream<VertexChatStream> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let model = config.model.clone();
+        match Self::create_client()
+            .and_then(|client| Ok((client, messages_to_request(messages, config)?)))
+        {
+            Ok((client, request)) => Self::streaming_request(client, &model, request),
+            Err(err) => VertexChatStream::failed(err),
+        }
+    }
+
+    fn subscribe(stream: &Self::ChatStream) -> Pollable {
+        stream.subscribe()
+    }
+}
+
+impl FineTuningProvider for VertexComponent {}
+
+impl FileProvider for VertexComponent {}
+
+type DurableVertexComponent = DurableLLM<VertexComponent>;
+
+golem_llm::export_llm!(DurableVertexComponent with_types_in golem_llm);
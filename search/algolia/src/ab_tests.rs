@@ -0,0 +1,156 @@
+//! A/B tests of indexes.
+//!
+//! The component exports the `golem:search-algolia/ab-tests` interface, managing the A/B tests
+//! of Algolia Analytics, so a worker can run relevance experiments between an index and one of
+//! its replicas. The tests are served by the Analytics host of the region of the application, set
+//! with `ALGOLIA_ANALYTICS_URL` outside of the US.
+
+use crate::client::{
+    AbTest as AlgoliaAbTest, AbTestVariant, AbTestVariantResults as AlgoliaAbTestVariantResults,
+    CreateAbTestRequest,
+};
+use crate::AlgoliaComponent;
+use chrono::{DateTime, SecondsFormat};
+use golem_search::error::invalid_query;
+use golem_search::golem::search::types::SearchError;
+use golem_search::LOGGING_STATE;
+
+#[allow(dead_code)]
+mod bindings {
+    wit_bindgen::generate!({
+        path: "wit",
+        world: "golem:search-algolia/ab-tests-library@1.0.0",
+        with: {
+            "golem:search/types@1.0.0": golem_search::golem::search::types,
+        },
+        generate_all,
+    });
+}
+
+use bindings::exports::golem::search_algolia::ab_tests::{
+    AbTest, AbTestVariantResults, Guest, NewAbTest,
+};
+
+struct AlgoliaAbTests;
+
+impl Guest for AlgoliaAbTests {
+    fn create_ab_test(test: NewAbTest) -> Result<u64, SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = AlgoliaComponent::create_client()?;
+        let request = new_ab_test_to_algolia_request(test)?;
+
+        let response = client.create_ab_test(&request)?;
+
+        Ok(response.ab_test_id)
+    }
+
+    fn stop_ab_test(id: u64) -> Result<(), SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = AlgoliaComponent::create_client()?;
+
+        client.stop_ab_test(id)?;
+
+        Ok(())
+    }
+
+    fn get_ab_test(id: u64) -> Result<AbTest, SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = AlgoliaComponent::create_client()?;
+
+        Ok(algolia_ab_test_to_ab_test(client.get_ab_test(id)?))
+    }
+}
+
+fn new_ab_test_to_algolia_request(test: NewAbTest) -> Result<CreateAbTestRequest, SearchError> {
+    let end_at = i64::try_from(test.end_at)
+        .ok()
+        .and_then(|end_at| DateTime::from_timestamp(end_at, 0))
+        .ok_or_else(|| invalid_query(format!("Invalid A/B test end {}", test.end_at)))?;
+
+    Ok(CreateAbTestRequest {
+        name: test.name,
+        variants: test
+            .variants
+            .into_iter()
+            .map(|variant| AbTestVariant {
+                index: variant.index,
+                traffic_percentage: variant.traffic_percentage,
+                description: variant.description,
+            })
+            .collect(),
+        end_at: end_at.to_rfc3339_opts(SecondsFormat::Secs, true),
+    })
+}
+
+fn algolia_ab_test_to_ab_test(ab_test: AlgoliaAbTest) -> AbTest {
+    AbTest {
+        id: ab_test.ab_test_id,
+        name: ab_test.name,
+        status: ab_test.status,
+        end_at: ab_test.end_at,
+        variants: ab_test
+            .variants
+            .into_iter()
+            .map(algolia_variant_results_to_variant_results)
+            .collect(),
+        click_significance: ab_test.click_significance,
+        conversion_significance: ab_test.conversion_significance,
+    }
+}
+
+fn algolia_variant_results_to_variant_results(
+    variant: AlgoliaAbTestVariantResults,
+) -> AbTestVariantResults {
+    AbTestVariantResults {
+        index: variant.index,
+        traffic_percentage: variant.traffic_percentage,
+        search_count: variant.search_count,
+        user_count: variant.user_count,
+        click_count: variant.click_count,
+        conversion_count: variant.conversion_count,
+        click_through_rate: variant.click_through_rate,
+        conversion_rate: variant.conversion_rate,
+    }
+}
+
+bindings::export!(AlgoliaAbTests with_types_in bindings);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bindings::exports::golem::search_algolia::ab_tests::AbTestVariant as NewAbTestVariant;
+
+    fn variant(index: &str) -> NewAbTestVariant {
+        NewAbTestVariant {
+            index: index.to_string(),
+            traffic_percentage: 50,
+            description: None,
+        }
+    }
+
+    #[test]
+    fn end_dates_are_sent_as_rfc_3339() {
+        let request = new_ab_test_to_algolia_request(NewAbTest {
+            name: "Sort by popularity".to_string(),
+            variants: vec![variant("products"), variant("products_popularity_desc")],
+            end_at: 1_706_745_600,
+        })
+        .unwrap();
+
+        assert_eq!(request.end_at, "2024-02-01T00:00:00Z");
+        assert_eq!(request.variants[1].index, "products_popularity_desc");
+    }
+
+    #[test]
+    fn out_of_range_end_dates_are_rejected() {
+        assert!(new_ab_test_to_algolia_request(NewAbTest {
+            name: "Too late".to_string(),
+            variants: vec![variant("products"), variant("products_popularity_desc")],
+            end_at: u64::MAX,
+        })
+        .is_err());
+    }
+}
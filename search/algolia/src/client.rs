@@ -1,10 +1,17 @@
-use golem_search::error::{from_reqwest_error, internal_error, search_error_from_status};
+use golem_search::correlation::{self, CORRELATION_ID_HEADER};
+use golem_search::error::{from_reqwest_error, from_send_error, search_error_from_failed_response};
 use golem_search::golem::search::types::SearchError;
-use log::trace;
+use golem_search::logging::RedactedResponse;
+use golem_search::retry::RetryPolicy;
+use log::{trace, warn};
 use reqwest::{Client, Method, RequestBuilder, Response};
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use std::fmt::Debug;
+use std::time::Duration;
+
+/// Algolia's default `paginationLimitedTo`, the deepest hit a search can return
+pub const PAGINATION_LIMIT: u32 = 1_000;
 
 // Custom deserializer to handle null values as empty vectors
 fn deserialize_nullable_vec<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
@@ -59,13 +66,9 @@ pub struct IndexSettings {
         default
     )]
     pub custom_ranking: Vec<String>,
-    #[serde(
-        skip_serializing_if = "Vec::is_empty",
-        rename = "replicas",
-        deserialize_with = "deserialize_nullable_vec",
-        default
-    )]
-    pub replicas: Vec<String>,
+    /// Not sent when `None`, while an empty list detaches all the replicas of the index
+    #[serde(skip_serializing_if = "Option::is_none", rename = "replicas", default)]
+    pub replicas: Option<Vec<String>>,
 }
 
 /// The Algolia Search API client for managing indices and performing search
@@ -77,6 +80,12 @@ pub struct AlgoliaSearchApi {
     api_key: String,
     search_url: String,
     write_url: String,
+    analytics_url: String,
+    retry_policy: RetryPolicy,
+    request_timeout: Option<Duration>,
+    wait_for_writes: bool,
+    task_timeout: Duration,
+    task_poll_interval: Duration,
 }
 
 impl AlgoliaSearchApi {
@@ -94,15 +103,69 @@ impl AlgoliaSearchApi {
             client,
             search_url,
             write_url,
+            analytics_url: "https://analytics.algolia.com".to_string(),
+            retry_policy: RetryPolicy::from_config(),
+            request_timeout: None,
+            wait_for_writes: false,
+            task_timeout: Duration::from_secs(10),
+            task_poll_interval: Duration::from_millis(500),
         }
     }
 
+    /// Sends the search, write and analytics requests to `base_url` instead of the application's
+    /// Algolia hosts
+    pub fn with_base_url(mut self, base_url: &str) -> Self {
+        self.search_url = base_url.to_string();
+        self.write_url = base_url.to_string();
+        self.analytics_url = base_url.to_string();
+        self
+    }
+
+    /// Sends the analytics requests, such as the ones of A/B tests, to the Analytics host of
+    /// another region, like `https://analytics.de.algolia.com`
+    pub fn with_analytics_url(mut self, analytics_url: &str) -> Self {
+        self.analytics_url = analytics_url.to_string();
+        self
+    }
+
+    /// Applies a timeout to every request sent by this client
+    pub fn with_request_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.request_timeout = timeout;
+        self
+    }
+
+    /// Makes the writes wait for their indexing task, see `wait_for_write`
+    pub fn with_wait_for_writes(mut self, wait_for_writes: bool) -> Self {
+        self.wait_for_writes = wait_for_writes;
+        self
+    }
+
+    /// Limits the time `wait_for_task` waits for a task to be published, 10 seconds by default
+    pub fn with_task_timeout(mut self, timeout: Duration) -> Self {
+        self.task_timeout = timeout;
+        self
+    }
+
+    /// Sets the pause between the polls of `wait_for_task`, 500 milliseconds by default
+    pub fn with_task_poll_interval(mut self, interval: Duration) -> Self {
+        self.task_poll_interval = interval;
+        self
+    }
+
     fn create_request(&self, method: Method, url: &str) -> RequestBuilder {
-        self.client
+        let mut builder = self
+            .client
             .request(method, url)
+            .header(CORRELATION_ID_HEADER, correlation::current())
             .header("X-Algolia-Application-Id", &self.application_id)
             .header("X-Algolia-API-Key", &self.api_key)
-            .header("Content-Type", "application/json")
+            .header("Content-Type", "application/json");
+
+        if let Some(timeout) = self.request_timeout {
+            builder = builder.timeout(timeout);
+        }
+
+        builder
     }
 
     pub fn delete_index(&self, index_name: &str) -> Result<DeleteIndexResponse, SearchError> {
@@ -111,9 +174,9 @@ impl AlgoliaSearchApi {
         let url = format!("{}/1/indexes/{}", self.write_url, index_name);
 
         let response = self
-            .create_request(Method::DELETE, &url)
-            .send()
-            .map_err(|e| internal_error(format!("Failed to delete index: {e}")))?;
+            .retry_policy
+            .execute(|| self.create_request(Method::DELETE, &url).send())
+            .map_err(|e| from_send_error("Failed to delete index", e))?;
 
         parse_response(response)
     }
@@ -124,9 +187,9 @@ impl AlgoliaSearchApi {
         let url = format!("{}/1/indexes", self.write_url);
 
         let response = self
-            .create_request(Method::GET, &url)
-            .send()
-            .map_err(|e| internal_error(format!("Failed to list indexes: {e}")))?;
+            .retry_policy
+            .execute(|| self.create_request(Method::GET, &url).send())
+            .map_err(|e| from_send_error("Failed to list indexes", e))?;
 
         parse_response(response)
     }
@@ -144,7 +207,7 @@ impl AlgoliaSearchApi {
             .create_request(Method::POST, &url)
             .json(object)
             .send()
-            .map_err(|e| internal_error(format!("Failed to save object: {e}")))?;
+            .map_err(|e| from_send_error("Failed to save object", e))?;
 
         parse_response(response)
     }
@@ -171,7 +234,70 @@ impl AlgoliaSearchApi {
             .create_request(Method::POST, &url)
             .json(&batch_request)
             .send()
-            .map_err(|e| internal_error(format!("Failed to save objects: {e}")))?;
+            .map_err(|e| from_send_error("Failed to save objects", e))?;
+
+        parse_response(response)
+    }
+
+    /// Updates the attributes of an object, creating it when missing if `create_if_not_exists` is
+    /// set
+    pub fn partial_update_object(
+        &self,
+        index_name: &str,
+        object: &AlgoliaObject,
+        create_if_not_exists: bool,
+    ) -> Result<PartialUpdateObjectResponse, SearchError> {
+        let object_id = object.object_id.as_deref().unwrap_or_default();
+        trace!("Partially updating object {object_id} in index: {index_name}");
+
+        let url = format!(
+            "{}/1/indexes/{}/{}/partial?createIfNotExists={create_if_not_exists}",
+            self.write_url,
+            index_name,
+            urlencoding::encode(object_id)
+        );
+
+        let response = self
+            .create_request(Method::POST, &url)
+            .json(&object.content)
+            .send()
+            .map_err(|e| from_send_error("Failed to partially update object", e))?;
+
+        parse_response(response)
+    }
+
+    pub fn partial_update_objects(
+        &self,
+        index_name: &str,
+        objects: &[AlgoliaObject],
+        create_if_not_exists: bool,
+    ) -> Result<SaveObjectsResponse, SearchError> {
+        trace!(
+            "Partially updating {} objects in index: {index_name}",
+            objects.len()
+        );
+
+        let action = if create_if_not_exists {
+            "partialUpdateObject"
+        } else {
+            "partialUpdateObjectNoCreate"
+        };
+        let url = format!("{}/1/indexes/{}/batch", self.write_url, index_name);
+        let batch_request = BatchRequest {
+            requests: objects
+                .iter()
+                .map(|obj| BatchOperation {
+                    action: action.to_string(),
+                    body: obj.clone(),
+                })
+                .collect(),
+        };
+
+        let response = self
+            .create_request(Method::POST, &url)
+            .json(&batch_request)
+            .send()
+            .map_err(|e| from_send_error("Failed to partially update objects", e))?;
 
         parse_response(response)
     }
@@ -186,9 +312,9 @@ impl AlgoliaSearchApi {
         let url = format!("{}/1/indexes/{}/{}", self.write_url, index_name, object_id);
 
         let response = self
-            .create_request(Method::DELETE, &url)
-            .send()
-            .map_err(|e| internal_error(format!("Failed to delete object: {e}")))?;
+            .retry_policy
+            .execute(|| self.create_request(Method::DELETE, &url).send())
+            .map_err(|e| from_send_error("Failed to delete object", e))?;
 
         parse_response(response)
     }
@@ -218,10 +344,13 @@ impl AlgoliaSearchApi {
         };
 
         let response = self
-            .create_request(Method::POST, &url)
-            .json(&batch_request)
-            .send()
-            .map_err(|e| internal_error(format!("Failed to delete objects: {e}")))?;
+            .retry_policy
+            .execute(|| {
+                self.create_request(Method::POST, &url)
+                    .json(&batch_request)
+                    .send()
+            })
+            .map_err(|e| from_send_error("Failed to delete objects", e))?;
 
         parse_response(response)
     }
@@ -235,7 +364,9 @@ impl AlgoliaSearchApi {
 
         let url = format!("{}/1/indexes/{}/{}", self.search_url, index_name, object_id);
 
-        let response = self.create_request(Method::GET, &url).send();
+        let response = self
+            .retry_policy
+            .execute(|| self.create_request(Method::GET, &url).send());
 
         match response {
             Ok(resp) => {
@@ -246,7 +377,7 @@ impl AlgoliaSearchApi {
                     Ok(Some(object))
                 }
             }
-            Err(e) => Err(internal_error(format!("Failed to get object: {e}"))),
+            Err(e) => Err(from_send_error("Failed to get object", e)),
         }
     }
 
@@ -259,14 +390,82 @@ impl AlgoliaSearchApi {
 
         let url = format!("{}/1/indexes/{}/query", self.search_url, index_name);
 
-        let response = self.create_request(Method::POST, &url).json(query).send();
+        let response = self
+            .retry_policy
+            .execute(|| self.create_request(Method::POST, &url).json(query).send());
 
         match response {
             Ok(resp) => parse_response(resp),
-            Err(e) => {
-                let error_msg = format!("Failed to search: {url}: {e}");
-                Err(internal_error(error_msg))
-            }
+            Err(e) => Err(from_send_error(format!("Failed to search: {url}"), e)),
+        }
+    }
+
+    /// Finds the values of a facet starting with the facet query of the request
+    pub fn search_facet_values(
+        &self,
+        index_name: &str,
+        facet_name: &str,
+        request: &SearchFacetValuesRequest,
+    ) -> Result<SearchFacetValuesResponse, SearchError> {
+        trace!("Searching values of facet {facet_name} of index {index_name}: {request:?}");
+
+        let url = format!(
+            "{}/1/indexes/{}/facets/{}/query",
+            self.search_url,
+            index_name,
+            urlencoding::encode(facet_name)
+        );
+
+        let response = self
+            .retry_policy
+            .execute(|| self.create_request(Method::POST, &url).json(request).send());
+
+        match response {
+            Ok(resp) => parse_response(resp),
+            Err(e) => Err(from_send_error(
+                format!("Failed to search facet values: {url}"),
+                e,
+            )),
+        }
+    }
+
+    /// Runs the searches of several indexes in one request
+    pub fn multi_search(
+        &self,
+        request: &MultiSearchRequest,
+    ) -> Result<MultiSearchResponse, SearchError> {
+        trace!("Searching {} indexes", request.requests.len());
+
+        let url = format!("{}/1/indexes/*/queries", self.search_url);
+
+        let response = self
+            .retry_policy
+            .execute(|| self.create_request(Method::POST, &url).json(request).send());
+
+        match response {
+            Ok(resp) => parse_response(resp),
+            Err(e) => Err(from_send_error(format!("Failed to multi-search: {url}"), e)),
+        }
+    }
+
+    /// Reads a page of the records of an index matching a query, continuing from the cursor of
+    /// the request when it has one
+    pub fn browse(
+        &self,
+        index_name: &str,
+        request: &BrowseRequest,
+    ) -> Result<BrowseResponse, SearchError> {
+        trace!("Browsing index {index_name} with request: {request:?}");
+
+        let url = format!("{}/1/indexes/{}/browse", self.search_url, index_name);
+
+        let response = self
+            .retry_policy
+            .execute(|| self.create_request(Method::POST, &url).json(request).send());
+
+        match response {
+            Ok(resp) => parse_response(resp),
+            Err(e) => Err(from_send_error(format!("Failed to browse: {url}"), e)),
         }
     }
 
@@ -276,9 +475,9 @@ impl AlgoliaSearchApi {
         let url = format!("{}/1/indexes/{}/settings", self.write_url, index_name);
 
         let response = self
-            .create_request(Method::GET, &url)
-            .send()
-            .map_err(|e| internal_error(format!("Failed to get settings: {e}")))?;
+            .retry_policy
+            .execute(|| self.create_request(Method::GET, &url).send())
+            .map_err(|e| from_send_error("Failed to get settings", e))?;
 
         parse_response(response)
     }
@@ -293,51 +492,170 @@ impl AlgoliaSearchApi {
         let url = format!("{}/1/indexes/{}/settings", self.write_url, index_name);
 
         let response = self
-            .create_request(Method::PUT, &url)
-            .json(settings)
-            .send()
-            .map_err(|e| internal_error(format!("Failed to set settings: {e}")))?;
+            .retry_policy
+            .execute(|| self.create_request(Method::PUT, &url).json(settings).send())
+            .map_err(|e| from_send_error("Failed to set settings", e))?;
 
         parse_response(response)
     }
 
-    pub fn _wait_for_task(&self, index_name: &str, task_id: u64) -> Result<(), SearchError> {
+    /// Polls a task until Algolia published it, failing with `SearchError::Timeout` once the
+    /// task timeout of the client ran out. Failed polls are logged and retried.
+    pub fn wait_for_task(&self, index_name: &str, task_id: u64) -> Result<(), SearchError> {
         trace!("Waiting for task {task_id} on index {index_name}");
         let url = format!(
             "{}/1/indexes/{}/task/{}",
             self.write_url, index_name, task_id
         );
 
-        for _ in 0..20 {
-            // Poll for up to 10 seconds
-            let response = self.create_request(Method::GET, &url).send();
-            match response {
-                Ok(resp) => {
-                    let body_str = match resp.text() {
-                        Ok(s) => s,
-                        Err(_e) => {
-                            continue;
-                        }
-                    };
-                    let body: serde_json::Value = match serde_json::from_str(&body_str) {
-                        Ok(b) => b,
-                        Err(_e) => {
-                            continue;
-                        }
-                    };
-                    if body.get("status").and_then(|s| s.as_str()) == Some("published") {
-                        return Ok(());
-                    }
-                }
-                Err(e) => {
-                    eprintln!("[Algolia] Error waiting for task: {e:?}");
-                }
+        let mut waited = Duration::ZERO;
+        loop {
+            let status = self
+                .create_request(Method::GET, &url)
+                .send()
+                .map_err(|e| from_send_error("Failed to get the task status", e))
+                .and_then(parse_response::<TaskStatusResponse>);
+            match status {
+                Ok(status) if status.status == "published" => return Ok(()),
+                Ok(_) => {}
+                Err(e) => warn!("Error waiting for task {task_id}: {e:?}"),
             }
-            std::thread::sleep(std::time::Duration::from_millis(500));
+
+            if waited >= self.task_timeout {
+                warn!(
+                    "Task {task_id} was not published within {:?}",
+                    self.task_timeout
+                );
+                return Err(SearchError::Timeout);
+            }
+            let pause = self.task_poll_interval.min(self.task_timeout - waited);
+            std::thread::sleep(pause);
+            waited += pause;
+        }
+    }
+
+    /// Waits for the task of a write when the client is configured to, so that the write is
+    /// visible to searches once it returns. Otherwise Algolia indexes the write asynchronously.
+    pub fn wait_for_write(&self, index_name: &str, task_id: u64) -> Result<(), SearchError> {
+        if self.wait_for_writes {
+            self.wait_for_task(index_name, task_id)
+        } else {
+            Ok(())
+        }
+    }
+
+    pub fn create_ab_test(
+        &self,
+        request: &CreateAbTestRequest,
+    ) -> Result<AbTestTaskResponse, SearchError> {
+        trace!("Creating A/B test: {}", request.name);
+
+        let url = format!("{}/2/abtests", self.analytics_url);
+
+        let response = self
+            .create_request(Method::POST, &url)
+            .json(request)
+            .send()
+            .map_err(|e| from_send_error("Failed to create A/B test", e))?;
+
+        parse_response(response)
+    }
+
+    pub fn stop_ab_test(&self, ab_test_id: u64) -> Result<AbTestTaskResponse, SearchError> {
+        trace!("Stopping A/B test {ab_test_id}");
+
+        let url = format!("{}/2/abtests/{}/stop", self.analytics_url, ab_test_id);
+
+        let response = self
+            .retry_policy
+            .execute(|| self.create_request(Method::POST, &url).send())
+            .map_err(|e| from_send_error("Failed to stop A/B test", e))?;
+
+        parse_response(response)
+    }
+
+    pub fn get_ab_test(&self, ab_test_id: u64) -> Result<AbTest, SearchError> {
+        trace!("Getting A/B test {ab_test_id}");
+
+        let url = format!("{}/2/abtests/{}", self.analytics_url, ab_test_id);
+
+        let response = self
+            .retry_policy
+            .execute(|| self.create_request(Method::GET, &url).send())
+            .map_err(|e| from_send_error("Failed to get A/B test", e))?;
+
+        parse_response(response)
+    }
+
+    /// Saves the synonyms, replacing every other synonym of the index when `replace_existing` is
+    /// set
+    pub fn save_synonyms(
+        &self,
+        index_name: &str,
+        synonyms: &[AlgoliaSynonym],
+        replace_existing: bool,
+    ) -> Result<SaveSynonymsResponse, SearchError> {
+        trace!("Saving {} synonyms to index: {index_name}", synonyms.len());
+
+        let url = format!(
+            "{}/1/indexes/{}/synonyms/batch?replaceExistingSynonyms={replace_existing}",
+            self.write_url, index_name
+        );
+
+        let response = self
+            .create_request(Method::POST, &url)
+            .json(synonyms)
+            .send()
+            .map_err(|e| from_send_error("Failed to save synonyms", e))?;
+
+        parse_response(response)
+    }
+
+    pub fn search_synonyms(
+        &self,
+        index_name: &str,
+        request: &SearchSynonymsRequest,
+    ) -> Result<SearchSynonymsResponse, SearchError> {
+        trace!("Searching synonyms of index {index_name} with request: {request:?}");
+
+        let url = format!(
+            "{}/1/indexes/{}/synonyms/search",
+            self.search_url, index_name
+        );
+
+        let response = self
+            .retry_policy
+            .execute(|| self.create_request(Method::POST, &url).json(request).send());
+
+        match response {
+            Ok(resp) => parse_response(resp),
+            Err(e) => Err(from_send_error(
+                format!("Failed to search synonyms: {url}"),
+                e,
+            )),
         }
-        Err(internal_error(format!(
-            "Task {task_id} did not complete in time."
-        )))
+    }
+
+    pub fn delete_synonym(
+        &self,
+        index_name: &str,
+        synonym_id: &str,
+    ) -> Result<DeleteSynonymResponse, SearchError> {
+        trace!("Deleting synonym {synonym_id} from index: {index_name}");
+
+        let url = format!(
+            "{}/1/indexes/{}/synonyms/{}",
+            self.write_url,
+            index_name,
+            urlencoding::encode(synonym_id)
+        );
+
+        let response = self
+            .retry_policy
+            .execute(|| self.create_request(Method::DELETE, &url).send())
+            .map_err(|e| from_send_error("Failed to delete synonym", e))?;
+
+        parse_response(response)
     }
 }
 
@@ -400,6 +718,69 @@ pub struct SearchResponse {
     pub params: String,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchFacetValuesRequest {
+    #[serde(rename = "facetQuery")]
+    pub facet_query: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub query: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filters: Option<String>,
+    #[serde(rename = "maxFacetHits")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_facet_hits: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchFacetValuesResponse {
+    #[serde(rename = "facetHits")]
+    pub facet_hits: Vec<FacetHit>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FacetHit {
+    pub value: String,
+    pub highlighted: String,
+    pub count: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MultiSearchRequest {
+    pub requests: Vec<IndexSearchQuery>,
+}
+
+/// The search of one index of a multi-search request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexSearchQuery {
+    #[serde(rename = "indexName")]
+    pub index_name: String,
+    #[serde(flatten)]
+    pub query: SearchQuery,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MultiSearchResponse {
+    pub results: Vec<SearchResponse>,
+}
+
+/// The parameters of a browse request: the search parameters of the query, without paging, and
+/// the cursor returned by the previous page
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BrowseRequest {
+    #[serde(flatten)]
+    pub query: SearchQuery,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BrowseResponse {
+    pub hits: Vec<SearchHit>,
+    /// Absent from the last page of the records
+    #[serde(default)]
+    pub cursor: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SearchHit {
     #[serde(rename = "objectID")]
@@ -490,6 +871,16 @@ pub struct SaveObjectsResponse {
     pub object_ids: Vec<String>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PartialUpdateObjectResponse {
+    #[serde(rename = "taskID")]
+    pub task_id: u64,
+    #[serde(rename = "objectID")]
+    pub object_id: String,
+    #[serde(rename = "updatedAt")]
+    pub updated_at: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DeleteObjectResponse {
     #[serde(rename = "taskID")]
@@ -506,6 +897,12 @@ pub struct DeleteObjectsResponse {
     pub object_ids: Vec<String>,
 }
 
+#[derive(Debug, Clone, Deserialize)]
+pub struct TaskStatusResponse {
+    /// `published` once the task was applied, `notPublished` before
+    pub status: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SetSettingsResponse {
     #[serde(rename = "updatedAt")]
@@ -514,6 +911,116 @@ pub struct SetSettingsResponse {
     pub task_id: u64,
 }
 
+/// A synonym of the synonyms API. Which of the optional fields are set depends on the type of the
+/// synonym: `synonym`, `onewaysynonym` or `placeholder`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AlgoliaSynonym {
+    #[serde(rename = "objectID")]
+    pub object_id: String,
+    #[serde(rename = "type")]
+    pub synonym_type: String,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub synonyms: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub input: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub placeholder: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub replacements: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchSynonymsRequest {
+    pub query: String,
+    pub page: u32,
+    #[serde(rename = "hitsPerPage")]
+    pub hits_per_page: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchSynonymsResponse {
+    pub hits: Vec<AlgoliaSynonym>,
+    #[serde(rename = "nbHits")]
+    pub nb_hits: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SaveSynonymsResponse {
+    #[serde(rename = "taskID")]
+    pub task_id: u64,
+    #[serde(rename = "updatedAt")]
+    pub updated_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeleteSynonymResponse {
+    #[serde(rename = "taskID")]
+    pub task_id: u64,
+    #[serde(rename = "deletedAt")]
+    pub deleted_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateAbTestRequest {
+    pub name: String,
+    pub variants: Vec<AbTestVariant>,
+    /// RFC 3339 date
+    #[serde(rename = "endAt")]
+    pub end_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AbTestVariant {
+    pub index: String,
+    #[serde(rename = "trafficPercentage")]
+    pub traffic_percentage: u32,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AbTestTaskResponse {
+    #[serde(rename = "abTestID")]
+    pub ab_test_id: u64,
+    pub index: String,
+    #[serde(rename = "taskID")]
+    pub task_id: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AbTest {
+    #[serde(rename = "abTestID")]
+    pub ab_test_id: u64,
+    pub name: String,
+    pub status: String,
+    #[serde(rename = "endAt")]
+    pub end_at: String,
+    pub variants: Vec<AbTestVariantResults>,
+    #[serde(rename = "clickSignificance", default)]
+    pub click_significance: Option<f64>,
+    #[serde(rename = "conversionSignificance", default)]
+    pub conversion_significance: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AbTestVariantResults {
+    pub index: String,
+    #[serde(rename = "trafficPercentage")]
+    pub traffic_percentage: u32,
+    #[serde(rename = "searchCount", default)]
+    pub search_count: Option<u64>,
+    #[serde(rename = "userCount", default)]
+    pub user_count: Option<u64>,
+    #[serde(rename = "clickCount", default)]
+    pub click_count: Option<u64>,
+    #[serde(rename = "conversionCount", default)]
+    pub conversion_count: Option<u64>,
+    #[serde(rename = "clickThroughRate", default)]
+    pub click_through_rate: Option<f64>,
+    #[serde(rename = "conversionRate", default)]
+    pub conversion_rate: Option<f64>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BatchRequest {
     pub requests: Vec<BatchOperation>,
@@ -528,23 +1035,416 @@ pub struct BatchOperation {
 fn parse_response<T: DeserializeOwned + Debug>(response: Response) -> Result<T, SearchError> {
     let status = response.status();
 
-    trace!("Received response from Algolia API: {response:?}");
+    trace!(
+        "Received response from Algolia API: {:?}",
+        RedactedResponse(&response)
+    );
 
     if status.is_success() {
         let body = response
             .json::<T>()
             .map_err(|err| from_reqwest_error("Failed to decode response body", err))?;
 
-        trace!("Received response from xAI API: {body:?}");
+        trace!("Received response from Algolia API: {body:?}");
 
         Ok(body)
     } else {
-        let error_body = response
-            .text()
-            .map_err(|err| from_reqwest_error("Failed to receive error response body", err))?;
+        let error = search_error_from_failed_response(response);
+
+        trace!("Received {status} response from Algolia API: {error:?}");
+
+        Err(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use golem_search::golem::search::types::ProviderErrorDetails;
+    use golem_search_test_support::{canned, MockResponse, MockServer};
+    use serde_json::json;
+
+    fn client(server: &MockServer) -> AlgoliaSearchApi {
+        AlgoliaSearchApi::new("TESTAPP".to_string(), "test-key".to_string())
+            .with_base_url(server.url())
+    }
+
+    fn query(text: &str) -> SearchQuery {
+        SearchQuery {
+            query: Some(text.to_string()),
+            filters: None,
+            numeric_filters: None,
+            page: None,
+            hits_per_page: Some(10),
+            offset: None,
+            length: None,
+            facets: vec![],
+            attributes_to_retrieve: vec![],
+            typo_tolerance: None,
+            analytics: None,
+        }
+    }
+
+    #[test]
+    fn search_sends_credentials_and_parses_hits() {
+        let server = MockServer::start();
+        server.mock(
+            "POST",
+            "/1/indexes/products/query",
+            MockResponse::json(
+                200,
+                canned::algolia::search(vec![canned::algolia::hit(
+                    "1",
+                    json!({ "title": "Laptop" }),
+                )]),
+            ),
+        );
+
+        let response = client(&server)
+            .search("products", &query("laptop"))
+            .unwrap();
+
+        assert_eq!(response.nb_hits, 1);
+        assert_eq!(response.hits[0].object_id, "1");
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(
+            requests[0].header("X-Algolia-Application-Id"),
+            Some("TESTAPP")
+        );
+        assert_eq!(requests[0].header("X-Algolia-API-Key"), Some("test-key"));
+        assert!(requests[0].header(CORRELATION_ID_HEADER).is_some());
+        assert_eq!(requests[0].json()["query"], "laptop");
+        assert_eq!(requests[0].json()["hitsPerPage"], 10);
+    }
+
+    #[test]
+    fn missing_objects_are_none() {
+        let server = MockServer::start();
+        server.mock(
+            "GET",
+            "/1/indexes/products/42",
+            MockResponse::json(404, canned::algolia::error(404, "ObjectID does not exist")),
+        );
+
+        assert!(client(&server)
+            .get_object("products", "42")
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn failed_responses_are_mapped_to_provider_errors() {
+        let server = MockServer::start();
+        server.mock(
+            "GET",
+            "/1/indexes",
+            MockResponse::json(
+                403,
+                canned::algolia::error(403, "Invalid Application-ID or API key"),
+            )
+            .with_header("x-request-id", "req-1"),
+        );
+
+        let error = client(&server).list_indexes().unwrap_err();
+
+        assert_eq!(
+            error,
+            SearchError::ProviderError(ProviderErrorDetails {
+                status: 403,
+                code: None,
+                message: "Invalid Application-ID or API key".to_string(),
+                request_id: Some("req-1".to_string()),
+            })
+        );
+    }
+
+    #[test]
+    fn partial_updates_only_send_the_changed_attributes() {
+        let server = MockServer::start();
+        server
+            .mock(
+                "POST",
+                "/1/indexes/products/42/partial",
+                MockResponse::json(
+                    200,
+                    json!({
+                        "taskID": 3,
+                        "objectID": "42",
+                        "updatedAt": "2024-01-01T00:00:00.000Z"
+                    }),
+                ),
+            )
+            .mock(
+                "POST",
+                "/1/indexes/products/batch",
+                MockResponse::json(200, json!({ "taskID": 4, "objectIDs": ["42"] })),
+            );
+
+        let object = AlgoliaObject {
+            object_id: Some("42".to_string()),
+            content: json!({ "stock": { "_operation": "Decrement", "value": 1 } }),
+        };
+
+        client(&server)
+            .partial_update_object("products", &object, false)
+            .unwrap();
+        client(&server)
+            .partial_update_objects("products", &[object], true)
+            .unwrap();
+
+        let requests = server.requests();
+        assert_eq!(
+            requests[0].query.as_deref(),
+            Some("createIfNotExists=false")
+        );
+        assert_eq!(
+            requests[0].json(),
+            json!({ "stock": { "_operation": "Decrement", "value": 1 } })
+        );
+        assert_eq!(
+            requests[1].json()["requests"][0]["action"],
+            "partialUpdateObject"
+        );
+        assert_eq!(requests[1].json()["requests"][0]["body"]["objectID"], "42");
+    }
 
-        trace!("Received {status} response from xAI API: {error_body:?}");
+    #[test]
+    fn facet_values_are_searched_on_the_facet() {
+        let server = MockServer::start();
+        server.mock(
+            "POST",
+            "/1/indexes/books/facets/author/query",
+            MockResponse::json(
+                200,
+                json!({
+                    "facetHits": [
+                        { "value": "Tolkien", "highlighted": "<em>Tol</em>kien", "count": 12 }
+                    ],
+                    "exhaustiveFacetsCount": true,
+                    "processingTimeMS": 1
+                }),
+            ),
+        );
+
+        let request = SearchFacetValuesRequest {
+            facet_query: "Tol".to_string(),
+            query: None,
+            filters: Some("language:en".to_string()),
+            max_facet_hits: Some(5),
+        };
+
+        let response = client(&server)
+            .search_facet_values("books", "author", &request)
+            .unwrap();
+
+        assert_eq!(response.facet_hits[0].value, "Tolkien");
+        assert_eq!(response.facet_hits[0].count, 12);
+        assert_eq!(
+            server.requests()[0].json(),
+            json!({ "facetQuery": "Tol", "filters": "language:en", "maxFacetHits": 5 })
+        );
+    }
+
+    #[test]
+    fn ab_tests_are_read_with_missing_statistics() {
+        let server = MockServer::start();
+        server.mock(
+            "GET",
+            "/2/abtests/224",
+            MockResponse::json(
+                200,
+                json!({
+                    "abTestID": 224,
+                    "name": "Sort by popularity",
+                    "status": "active",
+                    "createdAt": "2024-01-01T00:00:00Z",
+                    "endAt": "2024-02-01T00:00:00Z",
+                    "clickSignificance": null,
+                    "variants": [
+                        { "index": "products", "trafficPercentage": 50, "searchCount": 120 },
+                        { "index": "products_popularity_desc", "trafficPercentage": 50 }
+                    ]
+                }),
+            ),
+        );
+
+        let ab_test = client(&server).get_ab_test(224).unwrap();
+
+        assert_eq!(ab_test.status, "active");
+        assert_eq!(ab_test.click_significance, None);
+        assert_eq!(ab_test.variants[0].search_count, Some(120));
+        assert_eq!(ab_test.variants[1].search_count, None);
+    }
+
+    #[test]
+    fn multi_search_sends_every_query_in_one_request() {
+        let server = MockServer::start();
+        server.mock(
+            "POST",
+            "/1/indexes/*/queries",
+            MockResponse::json(
+                200,
+                json!({
+                    "results": [
+                        canned::algolia::search(vec![canned::algolia::hit(
+                            "1",
+                            json!({ "title": "Laptop" }),
+                        )]),
+                        canned::algolia::search(vec![]),
+                    ]
+                }),
+            ),
+        );
+
+        let request = MultiSearchRequest {
+            requests: vec![
+                IndexSearchQuery {
+                    index_name: "products".to_string(),
+                    query: query("laptop"),
+                },
+                IndexSearchQuery {
+                    index_name: "articles".to_string(),
+                    query: query("laptop"),
+                },
+            ],
+        };
+
+        let response = client(&server).multi_search(&request).unwrap();
+
+        assert_eq!(response.results.len(), 2);
+        assert_eq!(response.results[0].hits[0].object_id, "1");
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].json()["requests"][0]["indexName"], "products");
+        assert_eq!(requests[0].json()["requests"][1]["indexName"], "articles");
+        assert_eq!(requests[0].json()["requests"][1]["query"], "laptop");
+    }
+
+    #[test]
+    fn writes_wait_for_their_task_when_configured() {
+        let server = MockServer::start();
+        server.mock(
+            "GET",
+            "/1/indexes/products/task/7",
+            MockResponse::json(200, json!({ "status": "published" })),
+        );
+
+        client(&server).wait_for_write("products", 7).unwrap();
+        assert!(server.requests().is_empty());
+
+        client(&server)
+            .with_wait_for_writes(true)
+            .wait_for_write("products", 7)
+            .unwrap();
+        assert_eq!(server.requests().len(), 1);
+    }
+
+    #[test]
+    fn tasks_are_polled_until_published() {
+        let server = MockServer::start();
+        server
+            .mock(
+                "GET",
+                "/1/indexes/products/task/7",
+                MockResponse::json(200, json!({ "status": "notPublished" })),
+            )
+            .mock(
+                "GET",
+                "/1/indexes/products/task/7",
+                MockResponse::text(500, "not json"),
+            )
+            .mock(
+                "GET",
+                "/1/indexes/products/task/7",
+                MockResponse::json(200, json!({ "status": "published" })),
+            );
+
+        client(&server)
+            .with_task_poll_interval(Duration::from_millis(1))
+            .wait_for_task("products", 7)
+            .unwrap();
+        assert_eq!(server.requests().len(), 3);
+    }
+
+    #[test]
+    fn tasks_not_published_in_time_are_timeouts() {
+        let server = MockServer::start();
+        server.mock(
+            "GET",
+            "/1/indexes/products/task/7",
+            MockResponse::json(200, json!({ "status": "notPublished" })),
+        );
+
+        let error = client(&server)
+            .with_task_timeout(Duration::from_millis(30))
+            .with_task_poll_interval(Duration::from_millis(10))
+            .wait_for_task("products", 7)
+            .unwrap_err();
+
+        assert_eq!(error, SearchError::Timeout);
+        assert_eq!(server.requests().len(), 4);
+    }
+
+    #[test]
+    fn synonyms_are_saved_in_one_batch() {
+        let server = MockServer::start();
+        server.mock(
+            "POST",
+            "/1/indexes/products/synonyms/batch",
+            MockResponse::json(
+                200,
+                json!({ "taskID": 7, "updatedAt": "2024-01-01T00:00:00.000Z" }),
+            ),
+        );
+
+        let synonym = AlgoliaSynonym {
+            object_id: "phones".to_string(),
+            synonym_type: "onewaysynonym".to_string(),
+            synonyms: Some(vec!["smartphone".to_string()]),
+            input: Some("phone".to_string()),
+            placeholder: None,
+            replacements: None,
+        };
+
+        let response = client(&server)
+            .save_synonyms("products", &[synonym], true)
+            .unwrap();
+
+        assert_eq!(response.task_id, 7);
+
+        let requests = server.requests();
+        assert_eq!(
+            requests[0].query.as_deref(),
+            Some("replaceExistingSynonyms=true")
+        );
+        assert_eq!(
+            requests[0].json(),
+            json!([{
+                "objectID": "phones",
+                "type": "onewaysynonym",
+                "synonyms": ["smartphone"],
+                "input": "phone"
+            }])
+        );
+    }
 
-        Err(search_error_from_status(status))
+    #[test]
+    fn transient_failures_are_retried() {
+        let server = MockServer::start();
+        server
+            .mock("GET", "/1/indexes", MockResponse::text(503, "unavailable"))
+            .mock(
+                "GET",
+                "/1/indexes",
+                MockResponse::json(200, canned::algolia::list_indexes()),
+            );
+
+        let response = client(&server).list_indexes().unwrap();
+
+        assert_eq!(response.items[0].name, "products");
+        assert_eq!(server.requests().len(), 2);
     }
 }
@@ -1,12 +1,29 @@
 use crate::client::{
-    AlgoliaObject, IndexSettings, SearchHit as AlgoliaSearchHit, SearchQuery as AlgoliaSearchQuery,
-    SearchResponse,
+    AlgoliaObject, BrowseRequest, IndexSettings, SearchHit as AlgoliaSearchHit,
+    SearchQuery as AlgoliaSearchQuery, SearchResponse,
 };
+use golem_search::config::search_filters_match_any;
+use golem_search::error::invalid_query;
 use golem_search::golem::search::types::{
-    Doc, FieldType, Schema, SchemaField, SearchHit, SearchQuery, SearchResults,
+    Doc, FieldType, Schema, SchemaField, SearchError, SearchHit, SearchQuery, SearchResults,
 };
 use serde_json::{Map, Value};
 
+/// Algolia's default number of hits per page
+const DEFAULT_PER_PAGE: u32 = 20;
+
+/// Algolia's default ranking criteria, which sort replicas apply after their sort attribute
+const RELEVANCE_RANKING: [&str; 8] = [
+    "typo",
+    "geo",
+    "words",
+    "filters",
+    "proximity",
+    "attribute",
+    "exact",
+    "custom",
+];
+
 pub fn doc_to_algolia_object(doc: Doc) -> Result<AlgoliaObject, String> {
     let content: Value = serde_json::from_str(&doc.content)
         .map_err(|e| format!("Failed to parse document content as JSON: {e}"))?;
@@ -26,11 +43,17 @@ pub fn algolia_object_to_doc(obj: AlgoliaObject) -> Doc {
 }
 
 pub fn search_query_to_algolia_query(query: SearchQuery) -> AlgoliaSearchQuery {
+    let filter_separator = if search_filters_match_any(&query) {
+        " OR "
+    } else {
+        " AND "
+    };
     let mut algolia_query = AlgoliaSearchQuery {
         query: query.q,
         filters: None,
         numeric_filters: None,
-        page: query.page,
+        // Algolia counts pages from 0
+        page: query.page.map(|page| page.saturating_sub(1)),
         hits_per_page: query.per_page,
         offset: query.offset,
         length: None,
@@ -41,16 +64,23 @@ pub fn search_query_to_algolia_query(query: SearchQuery) -> AlgoliaSearchQuery {
     };
 
     // Handle filters - Algolia uses the filters field for general attribute filtering
-    if !query.filters.is_empty() {
+    match query.filters.as_slice() {
+        [] => {}
         // Each filter should be in the format "attribute:value" or "attribute>value", etc.
-        algolia_query.filters = Some(query.filters.join(" AND "));
+        [filter] => algolia_query.filters = Some(filter.clone()),
+        // Filters combining conditions themselves keep their own precedence when joined
+        filters => {
+            algolia_query.filters = Some(
+                filters
+                    .iter()
+                    .map(|filter| format!("({filter})"))
+                    .collect::<Vec<_>>()
+                    .join(filter_separator),
+            )
+        }
     }
 
-    // Handle sort - convert to Algolia's ranking format
-    if !query.sort.is_empty() {
-        // Note: Algolia handles sorting differently via index replicas or custom ranking
-        // For now, we'll include this in the provider params if available
-    }
+    // Sorts are applied by searching the sort replica of the index, see `sort_replica`
 
     // Note: Algolia handles highlighting automatically in the index settings
     // and returns _highlightResult in search responses. Query-level highlight
@@ -82,6 +112,138 @@ pub fn search_query_to_algolia_query(query: SearchQuery) -> AlgoliaSearchQuery {
     algolia_query
 }
 
+/// Whether the stream of a query has to keep its hits in order, requested with any sort entry.
+/// Such streams page through searches, which stop after the first thousand hits, while the
+/// others browse every matching record.
+pub fn is_ordered(query: &SearchQuery) -> bool {
+    !query.sort.is_empty()
+}
+
+/// Whether a sort entry asks for relevance order, with `_score` or `score`
+fn is_relevance_sort(sort: &str) -> bool {
+    let key = sort.trim().trim_start_matches('-');
+    let key = key.rsplit_once(':').map_or(key, |(key, _)| key);
+    key == "_score" || key == "score"
+}
+
+/// The name of the standard replica of `index` ranking its records by `field`
+pub fn replica_name(index: &str, field: &str, descending: bool) -> String {
+    let order = if descending { "desc" } else { "asc" };
+    format!("{index}_{field}_{order}")
+}
+
+/// The replica of `index` to search for a single `field`, `field:asc`, `field:desc` or `-field`
+/// sort entry, as Algolia sorts through replicas ranking by a single attribute. Relevance entries
+/// are the ranking of the index itself, so they need no replica.
+pub fn sort_replica(index: &str, sort: &[String]) -> Result<Option<String>, SearchError> {
+    let sort = sort
+        .iter()
+        .map(|sort| sort.trim())
+        .filter(|sort| !is_relevance_sort(sort))
+        .collect::<Vec<_>>();
+    let sort = match sort.as_slice() {
+        [] => return Ok(None),
+        [sort] => *sort,
+        _ => return Err(invalid_query("Algolia sorts by a single field")),
+    };
+    let (field, descending) = if let Some(field) = sort.strip_prefix('-') {
+        (field, true)
+    } else {
+        match sort.rsplit_once(':') {
+            Some((field, order)) if order.eq_ignore_ascii_case("asc") => (field, false),
+            Some((field, order)) if order.eq_ignore_ascii_case("desc") => (field, true),
+            Some(_) => return Err(invalid_query(format!("Invalid sort `{sort}`"))),
+            None => (sort, false),
+        }
+    };
+    if field.is_empty() {
+        return Err(invalid_query(format!("Invalid sort `{sort}`")));
+    }
+    Ok(Some(replica_name(index, field, descending)))
+}
+
+/// Whether `replica` is named like a standard sort replica of `index`, see `replica_name`
+pub fn is_sort_replica(index: &str, replica: &str) -> bool {
+    replica
+        .strip_prefix(index)
+        .and_then(|rest| rest.strip_prefix('_'))
+        .and_then(|rest| {
+            rest.strip_suffix("_asc")
+                .or_else(|| rest.strip_suffix("_desc"))
+        })
+        .is_some_and(|field| !field.is_empty())
+}
+
+/// Fails with `InvalidQuery` unless `replica` is one of the `replicas` of `index`. The sort
+/// replicas of an index only exist for the sortable fields of its schema.
+pub fn check_sort_replica(
+    index: &str,
+    replica: &str,
+    replicas: &[String],
+) -> Result<(), SearchError> {
+    if replicas.iter().any(|existing| existing == replica) {
+        Ok(())
+    } else {
+        Err(invalid_query(format!(
+            "Index `{index}` has no sort replica `{replica}`, the sorted field is not sortable in its schema"
+        )))
+    }
+}
+
+/// The ascending and descending sort replicas of the sortable fields of a schema, with their
+/// settings ranking the records by the field before Algolia's relevance criteria
+pub fn schema_sort_replicas(index: &str, schema: &Schema) -> Vec<(String, IndexSettings)> {
+    schema
+        .fields
+        .iter()
+        .filter(|field| field.sort)
+        .flat_map(|field| {
+            [false, true].map(|descending| {
+                let order = if descending { "desc" } else { "asc" };
+                let mut ranking = vec![format!("{order}({})", field.name)];
+                ranking.extend(RELEVANCE_RANKING.iter().map(|rule| rule.to_string()));
+
+                let settings = IndexSettings {
+                    ranking,
+                    ..Default::default()
+                };
+                (replica_name(index, &field.name, descending), settings)
+            })
+        })
+        .collect()
+}
+
+/// Number of hits of a query to skip before the first hit of its stream
+pub fn stream_offset(query: &SearchQuery) -> u32 {
+    query.offset.unwrap_or_else(|| {
+        query
+            .page
+            .unwrap_or(1)
+            .saturating_sub(1)
+            .saturating_mul(query.per_page.unwrap_or(DEFAULT_PER_PAGE))
+    })
+}
+
+/// The hit the page of a query ends at, which Algolia caps at its `paginationLimitedTo`
+pub fn query_depth(query: &SearchQuery) -> u32 {
+    stream_offset(query).saturating_add(query.per_page.unwrap_or(DEFAULT_PER_PAGE))
+}
+
+/// The browse request of a query, reading the records matching its text and filters in pages
+/// of its `per_page` hits. Browsing has no pages to jump to and doesn't count facets.
+pub fn search_query_to_browse_request(query: SearchQuery, cursor: Option<String>) -> BrowseRequest {
+    let mut algolia_query = search_query_to_algolia_query(query);
+    algolia_query.page = None;
+    algolia_query.offset = None;
+    algolia_query.length = None;
+    algolia_query.facets = vec![];
+
+    BrowseRequest {
+        query: algolia_query,
+        cursor,
+    }
+}
+
 pub fn algolia_response_to_search_results(response: SearchResponse) -> SearchResults {
     let hits = response
         .hits
@@ -91,13 +253,15 @@ pub fn algolia_response_to_search_results(response: SearchResponse) -> SearchRes
 
     SearchResults {
         total: Some(response.nb_hits),
-        page: Some(response.page),
+        page: Some(response.page + 1),
         per_page: Some(response.hits_per_page),
         hits,
         facets: response
             .facets
             .map(|f| serde_json::to_string(&f).unwrap_or_default()),
         took_ms: Some(response.processing_time_ms),
+        relaxation: None,
+        navigation: None,
     }
 }
 
@@ -268,7 +432,7 @@ pub fn create_retry_query(original_query: &SearchQuery, partial_hits: &[SearchHi
 #[cfg(test)]
 mod tests {
     use super::*;
-    use golem_search::golem::search::types::{HighlightConfig, SearchConfig};
+    use golem_search::golem::search::types::{FilterMode, HighlightConfig, SearchConfig};
 
     #[test]
     fn test_doc_to_algolia_object() {
@@ -347,16 +511,53 @@ mod tests {
         assert_eq!(algolia_query.query, Some("test query".to_string()));
         assert_eq!(
             algolia_query.filters,
-            Some("category:electronics AND price:>100".to_string())
+            Some("(category:electronics) AND (price:>100)".to_string())
         );
         assert_eq!(
             algolia_query.facets,
             vec!["category".to_string(), "brand".to_string()]
         );
-        assert_eq!(algolia_query.page, Some(1));
+        assert_eq!(algolia_query.page, Some(0));
         assert_eq!(algolia_query.hits_per_page, Some(20));
     }
 
+    #[test]
+    fn test_search_query_with_any_filter_mode() {
+        let search_query = SearchQuery {
+            q: None,
+            filters: vec![
+                "brand:apple OR brand:samsung".to_string(),
+                "category:phones".to_string(),
+            ],
+            sort: vec![],
+            facets: vec![],
+            page: None,
+            per_page: None,
+            offset: None,
+            highlight: None,
+            config: Some(SearchConfig {
+                attributes_to_retrieve: vec![],
+                typo_tolerance: None,
+                timeout_ms: None,
+                boost_fields: vec![],
+                exact_match_boost: None,
+                language: None,
+                provider_params: None,
+                profile: None,
+                filter_mode: Some(FilterMode::Any),
+                post_filters: vec![],
+                rescore: None,
+                fallbacks: vec![],
+            }),
+        };
+
+        let algolia_query = search_query_to_algolia_query(search_query);
+        assert_eq!(
+            algolia_query.filters,
+            Some("(brand:apple OR brand:samsung) OR (category:phones)".to_string())
+        );
+    }
+
     #[test]
     fn test_search_query_with_config() {
         let search_query = SearchQuery {
@@ -378,6 +579,11 @@ mod tests {
                 provider_params: Some(
                     r#"{"analytics": true, "numericFilters": ["price>100"]}"#.to_string(),
                 ),
+                profile: None,
+                filter_mode: None,
+                post_filters: vec![],
+                rescore: None,
+                fallbacks: vec![],
             }),
         };
 
@@ -475,7 +681,7 @@ mod tests {
 
         let search_results = algolia_response_to_search_results(algolia_response);
         assert_eq!(search_results.total, Some(1));
-        assert_eq!(search_results.page, Some(0));
+        assert_eq!(search_results.page, Some(1));
         assert_eq!(search_results.per_page, Some(20));
         assert_eq!(search_results.hits.len(), 1);
         assert_eq!(search_results.hits[0].id, "doc1");
@@ -547,4 +753,123 @@ mod tests {
         assert_eq!(extract_field_from_ranking("invalid"), None);
         assert_eq!(extract_field_from_ranking("desc()"), Some("".to_string()));
     }
+
+    #[test]
+    fn test_browse_request() {
+        let mut search_query = SearchQuery {
+            q: Some("laptop".to_string()),
+            filters: vec!["brand:acme".to_string()],
+            sort: vec![],
+            facets: vec!["category".to_string()],
+            page: Some(3),
+            per_page: Some(50),
+            offset: None,
+            highlight: None,
+            config: None,
+        };
+        assert!(!is_ordered(&search_query));
+        assert_eq!(stream_offset(&search_query), 100);
+
+        let request = search_query_to_browse_request(search_query.clone(), None);
+        assert_eq!(request.query.query, Some("laptop".to_string()));
+        assert_eq!(request.query.filters, Some("brand:acme".to_string()));
+        assert_eq!(request.query.hits_per_page, Some(50));
+        assert_eq!(request.query.page, None);
+        assert!(request.query.facets.is_empty());
+        assert_eq!(request.cursor, None);
+
+        let request = search_query_to_browse_request(search_query.clone(), Some("abc".to_string()));
+        assert_eq!(
+            serde_json::to_value(&request).unwrap()["cursor"],
+            serde_json::json!("abc")
+        );
+
+        search_query.offset = Some(7);
+        assert_eq!(stream_offset(&search_query), 7);
+
+        search_query.sort = vec!["_score".to_string()];
+        assert!(is_ordered(&search_query));
+    }
+
+    #[test]
+    fn test_sort_replica() {
+        let sort = |entries: &[&str]| {
+            sort_replica(
+                "products",
+                &entries.iter().map(|s| s.to_string()).collect::<Vec<_>>(),
+            )
+        };
+
+        assert_eq!(sort(&[]).unwrap(), None);
+        assert_eq!(sort(&["_score", "-score"]).unwrap(), None);
+        assert_eq!(
+            sort(&["year:desc"]).unwrap(),
+            Some("products_year_desc".to_string())
+        );
+        assert_eq!(
+            sort(&["-year", "_score"]).unwrap(),
+            Some("products_year_desc".to_string())
+        );
+        assert_eq!(
+            sort(&["price"]).unwrap(),
+            Some("products_price_asc".to_string())
+        );
+        assert!(sort(&["year:desc", "price:asc"]).is_err());
+        assert!(sort(&["year:up"]).is_err());
+    }
+
+    #[test]
+    fn test_is_sort_replica() {
+        assert!(is_sort_replica("products", "products_year_asc"));
+        assert!(is_sort_replica("products", "products_release_year_desc"));
+        assert!(!is_sort_replica("products", "products_asc"));
+        assert!(!is_sort_replica("products", "products_by_margin"));
+        assert!(!is_sort_replica("products", "articles_year_asc"));
+    }
+
+    #[test]
+    fn test_check_sort_replica() {
+        let replicas = vec![
+            "products_year_asc".to_string(),
+            "products_year_desc".to_string(),
+        ];
+
+        assert!(check_sort_replica("products", "products_year_desc", &replicas).is_ok());
+        assert!(matches!(
+            check_sort_replica("products", "products_price_asc", &replicas),
+            Err(SearchError::InvalidQuery(_))
+        ));
+    }
+
+    #[test]
+    fn test_schema_sort_replicas() {
+        let schema = Schema {
+            fields: vec![
+                SchemaField {
+                    name: "title".to_string(),
+                    field_type: FieldType::Text,
+                    required: false,
+                    facet: false,
+                    sort: false,
+                    index: true,
+                },
+                SchemaField {
+                    name: "year".to_string(),
+                    field_type: FieldType::Integer,
+                    required: false,
+                    facet: false,
+                    sort: true,
+                    index: false,
+                },
+            ],
+            primary_key: None,
+        };
+
+        let replicas = schema_sort_replicas("products", &schema);
+        assert_eq!(replicas.len(), 2);
+        assert_eq!(replicas[0].0, "products_year_asc");
+        assert_eq!(replicas[1].0, "products_year_desc");
+        assert_eq!(replicas[1].1.ranking[0], "desc(year)");
+        assert_eq!(replicas[1].1.ranking[1], "typo");
+    }
 }
@@ -0,0 +1,91 @@
+//! Search of facet values.
+//!
+//! The component exports the `golem:search-algolia/facet-values` interface, finding the values of
+//! a facet which start with the typed text through the facet values search of Algolia, without
+//! retrieving the whole distribution of the facet. The facet has to be declared searchable in the
+//! settings of the index.
+
+use crate::client::{FacetHit, SearchFacetValuesRequest};
+use crate::AlgoliaComponent;
+use golem_search::golem::search::types::{IndexName, SearchError};
+use golem_search::LOGGING_STATE;
+
+#[allow(dead_code)]
+mod bindings {
+    wit_bindgen::generate!({
+        path: "wit",
+        world: "golem:search-algolia/facet-values-library@1.0.0",
+        with: {
+            "golem:search/types@1.0.0": golem_search::golem::search::types,
+        },
+        generate_all,
+    });
+}
+
+use bindings::exports::golem::search_algolia::facet_values::{FacetQuery, FacetValue, Guest};
+
+struct AlgoliaFacetValues;
+
+impl Guest for AlgoliaFacetValues {
+    fn search_facet_values(
+        index: IndexName,
+        query: FacetQuery,
+    ) -> Result<Vec<FacetValue>, SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = AlgoliaComponent::create_client()?;
+        let facet = query.facet.clone();
+        let request = facet_query_to_algolia_request(query);
+
+        let response = client.search_facet_values(&index, &facet, &request)?;
+
+        Ok(response
+            .facet_hits
+            .into_iter()
+            .map(facet_hit_to_facet_value)
+            .collect())
+    }
+}
+
+fn facet_hit_to_facet_value(hit: FacetHit) -> FacetValue {
+    FacetValue {
+        value: hit.value,
+        highlighted: hit.highlighted,
+        count: hit.count,
+    }
+}
+
+fn facet_query_to_algolia_request(query: FacetQuery) -> SearchFacetValuesRequest {
+    SearchFacetValuesRequest {
+        facet_query: query.text,
+        query: query.q,
+        filters: (!query.filters.is_empty()).then(|| query.filters.join(" AND ")),
+        max_facet_hits: query.max_values,
+    }
+}
+
+bindings::export!(AlgoliaFacetValues with_types_in bindings);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filters_must_all_match() {
+        let request = facet_query_to_algolia_request(FacetQuery {
+            facet: "author".to_string(),
+            text: "tol".to_string(),
+            q: Some("rings".to_string()),
+            filters: vec!["language:en".to_string(), "year > 1950".to_string()],
+            max_values: None,
+        });
+
+        assert_eq!(request.facet_query, "tol");
+        assert_eq!(request.query, Some("rings".to_string()));
+        assert_eq!(
+            request.filters,
+            Some("language:en AND year > 1950".to_string())
+        );
+        assert_eq!(request.max_facet_hits, None);
+    }
+}
@@ -1,22 +1,39 @@
-use crate::client::AlgoliaSearchApi;
+use crate::client::{AlgoliaSearchApi, SetSettingsResponse, PAGINATION_LIMIT};
 use crate::conversions::{
-    algolia_object_to_doc, algolia_response_to_search_results, algolia_settings_to_schema,
-    create_retry_query, doc_to_algolia_object, schema_to_algolia_settings,
-    search_query_to_algolia_query,
+    algolia_hit_to_search_hit, algolia_object_to_doc, algolia_response_to_search_results,
+    algolia_settings_to_schema, check_sort_replica, create_retry_query, doc_to_algolia_object,
+    is_ordered, is_sort_replica, query_depth, schema_sort_replicas, schema_to_algolia_settings,
+    search_query_to_algolia_query, search_query_to_browse_request, sort_replica, stream_offset,
 };
 use golem_rust::wasm_rpc::Pollable;
-use golem_search::config::with_config_keys;
+use golem_search::config::{
+    get_optional_config, profile_key, search_profile, search_timeout, with_profile_config_keys,
+    worker_profile,
+};
 use golem_search::durability::{DurableSearch, ExtendedGuest};
 use golem_search::golem::search::core::{Guest, GuestSearchStream, SearchStream};
 use golem_search::golem::search::types::{
-    Doc, DocumentId, IndexName, Schema, SearchError, SearchHit, SearchQuery, SearchResults,
+    Doc, DocumentId, IndexName, IndexPage, PageRequest, Schema, SearchError, SearchHit,
+    SearchQuery, SearchResults,
 };
+use golem_search::pagination::{index_page, page_source, search_navigation};
 use golem_search::LOGGING_STATE;
 use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::time::Duration;
 
+mod ab_tests;
 mod client;
 mod conversions;
-
+mod facet_values;
+mod multi_search;
+mod partial_updates;
+mod secured_keys;
+mod synonyms;
+
+/// Search stream over the hits of a query. Streams browse the matching records with cursors,
+/// which has no limit on the number of hits, unless the query is sorted, which only searches
+/// keep, in which case they page through searches of the index or of its sort replica.
 struct AlgoliaSearchStream {
     client: AlgoliaSearchApi,
     index_name: String,
@@ -24,6 +41,12 @@ struct AlgoliaSearchStream {
     current_page: Cell<u32>,
     finished: Cell<bool>,
     last_response: RefCell<Option<SearchResults>>,
+    browse: bool,
+    /// The cursor of the next page of a browsing stream
+    cursor: RefCell<Option<String>>,
+    /// Number of hits a browsing stream still has to skip to reach the page or offset of the
+    /// query
+    skip: Cell<u32>,
 }
 
 impl AlgoliaSearchStream {
@@ -32,46 +55,36 @@ impl AlgoliaSearchStream {
             client,
             index_name,
             query: query.clone(),
-            current_page: Cell::new(query.page.unwrap_or(0)),
+            current_page: Cell::new(query.page.unwrap_or(1)),
             finished: Cell::new(false),
             last_response: RefCell::new(None),
+            browse: !is_ordered(&query),
+            cursor: RefCell::new(None),
+            skip: Cell::new(stream_offset(&query)),
         }
     }
 
     pub fn subscribe(&self) -> Pollable {
         golem_rust::bindings::wasi::clocks::monotonic_clock::subscribe_duration(0)
     }
-}
-
-impl GuestSearchStream for AlgoliaSearchStream {
-    fn get_next(&self) -> Option<Vec<SearchHit>> {
-        if self.finished.get() {
-            return Some(vec![]);
-        }
 
+    fn next_search_page(&self) -> Option<Vec<SearchHit>> {
         let mut search_query = self.query.clone();
         search_query.page = Some(self.current_page.get());
 
-        let algolia_query = search_query_to_algolia_query(search_query);
+        let algolia_query = search_query_to_algolia_query(search_query.clone());
 
         match self.client.search(&self.index_name, &algolia_query) {
             Ok(response) => {
                 let search_results = algolia_response_to_search_results(response);
 
-                let current_page = self.current_page.get();
-                let total_pages = if let (Some(total), Some(per_page)) =
-                    (search_results.total, search_results.per_page)
+                if search_navigation(&search_query, &search_results)
+                    .is_none_or(|navigation| !navigation.has_next)
                 {
-                    total.div_ceil(per_page)
-                } else {
-                    current_page + 1
-                };
-
-                if current_page >= total_pages || search_results.hits.is_empty() {
                     self.finished.set(true);
                 }
 
-                self.current_page.set(current_page + 1);
+                self.current_page.set(self.current_page.get() + 1);
 
                 let hits = search_results.hits.clone();
                 *self.last_response.borrow_mut() = Some(search_results);
@@ -85,6 +98,55 @@ impl GuestSearchStream for AlgoliaSearchStream {
         }
     }
 
+    /// Reads browse pages until one has hits left after skipping the hits before the page or
+    /// offset of the query, or until the last page
+    fn next_browse_page(&self) -> Option<Vec<SearchHit>> {
+        loop {
+            let request =
+                search_query_to_browse_request(self.query.clone(), self.cursor.borrow().clone());
+
+            match self.client.browse(&self.index_name, &request) {
+                Ok(response) => {
+                    if response.cursor.is_none() {
+                        self.finished.set(true);
+                    }
+                    *self.cursor.borrow_mut() = response.cursor;
+
+                    let mut hits = response
+                        .hits
+                        .into_iter()
+                        .map(algolia_hit_to_search_hit)
+                        .collect::<Vec<_>>();
+                    let skipped = self.skip.get().min(hits.len() as u32);
+                    hits.drain(..skipped as usize);
+                    self.skip.set(self.skip.get() - skipped);
+
+                    if !hits.is_empty() || self.finished.get() {
+                        return Some(hits);
+                    }
+                }
+                Err(_) => {
+                    self.finished.set(true);
+                    return Some(vec![]);
+                }
+            }
+        }
+    }
+}
+
+impl GuestSearchStream for AlgoliaSearchStream {
+    fn get_next(&self) -> Option<Vec<SearchHit>> {
+        if self.finished.get() {
+            return Some(vec![]);
+        }
+
+        if self.browse {
+            self.next_browse_page()
+        } else {
+            self.next_search_page()
+        }
+    }
+
     fn blocking_get_next(&self) -> Vec<SearchHit> {
         self.get_next().unwrap_or_default()
     }
@@ -92,12 +154,122 @@ impl GuestSearchStream for AlgoliaSearchStream {
 
 struct AlgoliaComponent;
 
+thread_local! {
+    /// The provider clients by configuration profile, reused across calls instead of being rebuilt
+    /// for each of them
+    static CLIENTS: RefCell<HashMap<Option<String>, AlgoliaSearchApi>> = RefCell::new(HashMap::new());
+}
+
 impl AlgoliaComponent {
     const APPLICATION_ID_ENV_VAR: &'static str = "ALGOLIA_APPLICATION_ID";
     const API_KEY_ENV_VAR: &'static str = "ALGOLIA_API_KEY";
+    /// The Analytics host of the region of the application, the US one when not set
+    const ANALYTICS_URL_ENV_VAR: &'static str = "ALGOLIA_ANALYTICS_URL";
+    /// Makes the writes return once Algolia indexed them, when `true`
+    const WAIT_FOR_WRITES_ENV_VAR: &'static str = "ALGOLIA_WAIT_FOR_WRITES";
+    /// How long waiting for a task may take, in milliseconds, 10 seconds when not set
+    const TASK_TIMEOUT_ENV_VAR: &'static str = "ALGOLIA_TASK_TIMEOUT_MS";
 
     fn create_client() -> Result<AlgoliaSearchApi, SearchError> {
-        with_config_keys(
+        Self::create_profile_client(worker_profile().as_deref())
+    }
+
+    /// Returns the client of `profile` cached for this worker, creating it from the configuration
+    /// on first use
+    fn create_profile_client(profile: Option<&str>) -> Result<AlgoliaSearchApi, SearchError> {
+        CLIENTS.with_borrow_mut(|clients| {
+            let profile = profile.map(str::to_string);
+            if let Some(client) = clients.get(&profile) {
+                return Ok(client.clone());
+            }
+
+            let client = Self::new_client(profile.as_deref())?;
+            clients.insert(profile, client.clone());
+            Ok(client)
+        })
+    }
+
+    /// Sets the settings of an index derived from a schema, with a standard replica for each
+    /// order of its sortable fields. Algolia creates the replicas once it applied the settings of
+    /// the index, which are waited for so the ranking of the replicas can then be set. The standard
+    /// replicas among the `existing_replicas` whose field is no longer sortable are detached and
+    /// deleted, while the other replicas are kept.
+    fn set_schema_settings(
+        client: &AlgoliaSearchApi,
+        index: &str,
+        schema: Schema,
+        existing_replicas: &[String],
+    ) -> Result<SetSettingsResponse, SearchError> {
+        let replicas = schema_sort_replicas(index, &schema);
+        let stale_replicas = existing_replicas
+            .iter()
+            .filter(|replica| is_sort_replica(index, replica))
+            .filter(|replica| !replicas.iter().any(|(name, _)| name == *replica))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        let mut replica_names = existing_replicas
+            .iter()
+            .filter(|replica| !is_sort_replica(index, replica))
+            .cloned()
+            .collect::<Vec<_>>();
+        replica_names.extend(replicas.iter().map(|(name, _)| name.clone()));
+
+        let mut settings = schema_to_algolia_settings(schema);
+        if !replica_names.is_empty() || !stale_replicas.is_empty() {
+            settings.replicas = Some(replica_names);
+        }
+
+        let response = client.set_settings(index, &settings)?;
+
+        if !replicas.is_empty() || !stale_replicas.is_empty() {
+            client.wait_for_task(index, response.task_id)?;
+
+            for (replica, replica_settings) in replicas {
+                client.set_settings(&replica, &replica_settings)?;
+            }
+            for replica in stale_replicas {
+                client.delete_index(&replica)?;
+            }
+        }
+
+        Ok(response)
+    }
+
+    /// The replicas of an index, none when the index doesn't exist yet
+    fn index_replicas(client: &AlgoliaSearchApi, index: &str) -> Result<Vec<String>, SearchError> {
+        match client.get_settings(index) {
+            Ok(settings) => Ok(settings.replicas.unwrap_or_default()),
+            Err(SearchError::IndexNotFound) => Ok(Vec::new()),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// The index to search for a sort: the index itself, or its sort replica when the sort is on
+    /// a field, which has to be sortable in the schema of the index
+    pub(crate) fn search_index(
+        client: &AlgoliaSearchApi,
+        index: String,
+        sort: &[String],
+    ) -> Result<String, SearchError> {
+        let Some(replica) = sort_replica(&index, sort)? else {
+            return Ok(index);
+        };
+        check_sort_replica(&index, &replica, &Self::index_replicas(client, &index)?)?;
+        Ok(replica)
+    }
+
+    fn new_client(profile: Option<&str>) -> Result<AlgoliaSearchApi, SearchError> {
+        let wait_for_writes =
+            get_optional_config(profile_key(Self::WAIT_FOR_WRITES_ENV_VAR, profile))
+                .is_some_and(|value| value.trim().eq_ignore_ascii_case("true"));
+        let analytics_url = get_optional_config(profile_key(Self::ANALYTICS_URL_ENV_VAR, profile));
+        let task_timeout = get_optional_config(profile_key(Self::TASK_TIMEOUT_ENV_VAR, profile))
+            .and_then(|value| value.trim().parse::<u64>().ok())
+            .map(Duration::from_millis);
+
+        with_profile_config_keys(
+            profile,
             &[Self::APPLICATION_ID_ENV_VAR, Self::API_KEY_ENV_VAR],
             |keys| {
                 if keys.len() != 2 {
@@ -109,7 +281,16 @@ impl AlgoliaComponent {
                 let application_id = keys[0].clone();
                 let api_key = keys[1].clone();
 
-                Ok(AlgoliaSearchApi::new(application_id, api_key))
+                let mut client = AlgoliaSearchApi::new(application_id, api_key)
+                    .with_wait_for_writes(wait_for_writes);
+                if let Some(analytics_url) = &analytics_url {
+                    client = client.with_analytics_url(analytics_url);
+                }
+                if let Some(task_timeout) = task_timeout {
+                    client = client.with_task_timeout(task_timeout);
+                }
+
+                Ok(client)
             },
         )
     }
@@ -118,13 +299,19 @@ impl AlgoliaComponent {
 impl Guest for AlgoliaComponent {
     type SearchStream = AlgoliaSearchStream;
 
-    fn create_index(_name: IndexName, _schema: Option<Schema>) -> Result<(), SearchError> {
+    /// Creates the index by setting its settings, derived from the schema when one is given, and
+    /// waits for the task to be published. Algolia also creates indexes on the first insertion of
+    /// documents, so creating an index is optional.
+    fn create_index(name: IndexName, schema: Option<Schema>) -> Result<(), SearchError> {
         LOGGING_STATE.with_borrow_mut(|state| state.init());
 
-        // Algolia doesn't require explicit index creation - indices are created automatically
-        // when you first add documents.
-        // providers that don't support index creation should return unsupported.
-        Err(SearchError::Unsupported)
+        let client = Self::create_client()?;
+
+        let response = match schema {
+            Some(schema) => Self::set_schema_settings(&client, &name, schema, &[])?,
+            None => client.set_settings(&name, &Default::default())?,
+        };
+        client.wait_for_task(&name, response.task_id)
     }
 
     fn delete_index(name: IndexName) -> Result<(), SearchError> {
@@ -132,22 +319,34 @@ impl Guest for AlgoliaComponent {
 
         let client = Self::create_client()?;
 
-        match client.delete_index(&name) {
-            Ok(response) => {
-                let _ = response;
-                Ok(())
+        // Replicas outlive their primary index, so the sort replicas created for its schema are
+        // deleted once the deletion of the index detached them
+        let replicas = Self::index_replicas(&client, &name)?
+            .into_iter()
+            .filter(|replica| is_sort_replica(&name, replica))
+            .collect::<Vec<_>>();
+
+        let response = client.delete_index(&name)?;
+
+        if !replicas.is_empty() {
+            client.wait_for_task(&name, response.task_id)?;
+            for replica in replicas {
+                client.delete_index(&replica)?;
             }
-            Err(e) => Err(e),
         }
+        Ok(())
     }
 
-    fn list_indexes() -> Result<Vec<IndexName>, SearchError> {
+    fn list_indexes(page: PageRequest) -> Result<IndexPage, SearchError> {
         LOGGING_STATE.with_borrow_mut(|state| state.init());
 
         let client = Self::create_client()?;
 
         match client.list_indexes() {
-            Ok(response) => Ok(response.items.into_iter().map(|item| item.name).collect()),
+            Ok(response) => index_page(
+                response.items.into_iter().map(|item| item.name).collect(),
+                &page,
+            ),
             Err(e) => Err(e),
         }
     }
@@ -159,10 +358,7 @@ impl Guest for AlgoliaComponent {
         let algolia_object = doc_to_algolia_object(doc).map_err(SearchError::InvalidQuery)?;
 
         match client.save_object(&index, &algolia_object) {
-            Ok(response) => {
-                let _ = response;
-                Ok(())
-            }
+            Ok(response) => client.wait_for_write(&index, response.task_id),
             Err(e) => Err(e),
         }
     }
@@ -179,10 +375,7 @@ impl Guest for AlgoliaComponent {
         }
 
         match client.save_objects(&index, &algolia_objects) {
-            Ok(response) => {
-                let _ = response;
-                Ok(())
-            }
+            Ok(response) => client.wait_for_write(&index, response.task_id),
             Err(e) => Err(e),
         }
     }
@@ -193,10 +386,7 @@ impl Guest for AlgoliaComponent {
         let client = Self::create_client()?;
 
         match client.delete_object(&index, &id) {
-            Ok(response) => {
-                let _ = response;
-                Ok(())
-            }
+            Ok(response) => client.wait_for_write(&index, response.task_id),
             Err(e) => Err(e),
         }
     }
@@ -207,10 +397,7 @@ impl Guest for AlgoliaComponent {
         let client = Self::create_client()?;
 
         match client.delete_objects(&index, &ids) {
-            Ok(response) => {
-                let _ = response;
-                Ok(())
-            }
+            Ok(response) => client.wait_for_write(&index, response.task_id),
             Err(e) => Err(e),
         }
     }
@@ -230,7 +417,10 @@ impl Guest for AlgoliaComponent {
     fn search(index: IndexName, query: SearchQuery) -> Result<SearchResults, SearchError> {
         LOGGING_STATE.with_borrow_mut(|state| state.init());
 
-        let client = Self::create_client()?;
+        let client = Self::create_profile_client(search_profile(&query).as_deref())?
+            .with_request_timeout(search_timeout(&query));
+        page_source(query_depth(&query), PAGINATION_LIMIT, false)?;
+        let index = Self::search_index(&client, index, &query.sort)?;
         let algolia_query = search_query_to_algolia_query(query);
 
         match client.search(&index, &algolia_query) {
@@ -242,7 +432,9 @@ impl Guest for AlgoliaComponent {
     fn stream_search(index: IndexName, query: SearchQuery) -> Result<SearchStream, SearchError> {
         LOGGING_STATE.with_borrow_mut(|state| state.init());
 
-        let client = Self::create_client()?;
+        let client = Self::create_profile_client(search_profile(&query).as_deref())?
+            .with_request_timeout(search_timeout(&query));
+        let index = Self::search_index(&client, index, &query.sort)?;
         let stream = AlgoliaSearchStream::new(client, index, query);
         Ok(SearchStream::new(stream))
     }
@@ -262,11 +454,11 @@ impl Guest for AlgoliaComponent {
         LOGGING_STATE.with_borrow_mut(|state| state.init());
 
         let client = Self::create_client()?;
-        let settings = schema_to_algolia_settings(schema);
 
-        client.set_settings(&index, &settings)?;
+        let existing_replicas = Self::index_replicas(&client, &index)?;
+        let response = Self::set_schema_settings(&client, &index, schema, &existing_replicas)?;
 
-        Ok(())
+        client.wait_for_write(&index, response.task_id)
     }
 }
 
@@ -274,8 +466,9 @@ impl ExtendedGuest for AlgoliaComponent {
     fn unwrapped_stream(index: IndexName, query: SearchQuery) -> Self::SearchStream {
         LOGGING_STATE.with_borrow_mut(|state| state.init());
 
-        let client = Self::create_client()
+        let client = Self::create_profile_client(search_profile(&query).as_deref())
             .unwrap_or_else(|_| AlgoliaSearchApi::new("dummy".to_string(), "dummy".to_string()));
+        let index = Self::search_index(&client, index.clone(), &query.sort).unwrap_or(index);
 
         AlgoliaSearchStream::new(client, index, query)
     }
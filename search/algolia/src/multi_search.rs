@@ -0,0 +1,165 @@
+//! Searches of several indexes in one request.
+//!
+//! The component exports the `golem:search-algolia/multi-search` interface, sending the searches
+//! of several indexes to the multi-queries endpoint of Algolia, so a worker pays for a single
+//! round trip instead of one per index. The queries are converted as by `search`, including the
+//! routing of sorted queries to the sort replicas of their index.
+
+use crate::client::{IndexSearchQuery, MultiSearchRequest, PAGINATION_LIMIT};
+use crate::conversions::{algolia_response_to_search_results, search_query_to_algolia_query};
+use crate::AlgoliaComponent;
+use golem_search::golem::search::types::{SearchError, SearchResults};
+use golem_search::pagination::page_source;
+use golem_search::LOGGING_STATE;
+
+#[allow(dead_code)]
+mod bindings {
+    wit_bindgen::generate!({
+        path: "wit",
+        world: "golem:search-algolia/multi-search-library@1.0.0",
+        with: {
+            "golem:search/types@1.0.0": golem_search::golem::search::types,
+        },
+        generate_all,
+    });
+}
+
+use bindings::exports::golem::search_algolia::multi_search::{Guest, IndexQuery};
+
+struct AlgoliaMultiSearch;
+
+impl Guest for AlgoliaMultiSearch {
+    fn multi_search(queries: Vec<IndexQuery>) -> Result<Vec<SearchResults>, SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = AlgoliaComponent::create_client()?;
+        let request = index_queries_to_multi_search_request(queries, |index, sort| {
+            AlgoliaComponent::search_index(&client, index, sort)
+        })?;
+
+        let response = client.multi_search(&request)?;
+
+        Ok(response
+            .results
+            .into_iter()
+            .map(algolia_response_to_search_results)
+            .collect())
+    }
+}
+
+/// Builds the request of the queries, searching the index `search_index` returns for the index and
+/// the sort of each query
+fn index_queries_to_multi_search_request(
+    queries: Vec<IndexQuery>,
+    search_index: impl Fn(String, &[String]) -> Result<String, SearchError>,
+) -> Result<MultiSearchRequest, SearchError> {
+    let requests = queries
+        .into_iter()
+        .map(|IndexQuery { index, query }| {
+            page_source(query_depth(&query), PAGINATION_LIMIT, false)?;
+            let index_name = search_index(index, &query.sort)?;
+            Ok(IndexSearchQuery {
+                index_name,
+                query: search_query_to_algolia_query(query),
+            })
+        })
+        .collect::<Result<Vec<_>, SearchError>>()?;
+
+    Ok(MultiSearchRequest { requests })
+}
+
+bindings::export!(AlgoliaMultiSearch with_types_in bindings);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conversions::{check_sort_replica, sort_replica};
+    use golem_search::golem::search::types::SearchQuery;
+
+    fn query(text: &str, sort: &[&str]) -> SearchQuery {
+        SearchQuery {
+            q: Some(text.to_string()),
+            filters: vec![],
+            sort: sort.iter().map(|s| s.to_string()).collect(),
+            facets: vec![],
+            page: None,
+            per_page: None,
+            offset: None,
+            highlight: None,
+            config: None,
+        }
+    }
+
+    fn sort_replica_or_index(index: String, sort: &[String]) -> Result<String, SearchError> {
+        Ok(sort_replica(&index, sort)?.unwrap_or(index))
+    }
+
+    #[test]
+    fn sorted_queries_search_the_sort_replica() {
+        let request = index_queries_to_multi_search_request(
+            vec![
+                IndexQuery {
+                    index: "products".to_string(),
+                    query: query("laptop", &["year:desc"]),
+                },
+                IndexQuery {
+                    index: "articles".to_string(),
+                    query: query("laptop", &[]),
+                },
+            ],
+            sort_replica_or_index,
+        )
+        .unwrap();
+
+        assert_eq!(request.requests[0].index_name, "products_year_desc");
+        assert_eq!(request.requests[1].index_name, "articles");
+        assert_eq!(request.requests[1].query.query, Some("laptop".to_string()));
+    }
+
+    #[test]
+    fn invalid_sorts_fail_the_request() {
+        assert!(index_queries_to_multi_search_request(
+            vec![IndexQuery {
+                index: "products".to_string(),
+                query: query("laptop", &["year:desc", "price:asc"]),
+            }],
+            sort_replica_or_index
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn sorts_on_unsortable_fields_fail_the_request() {
+        let replicas = vec!["products_year_desc".to_string()];
+        let result = index_queries_to_multi_search_request(
+            vec![IndexQuery {
+                index: "products".to_string(),
+                query: query("laptop", &["price:asc"]),
+            }],
+            |index, sort| {
+                let replica = sort_replica(&index, sort)?.unwrap_or(index.clone());
+                check_sort_replica(&index, &replica, &replicas)?;
+                Ok(replica)
+            },
+        );
+
+        assert!(matches!(result, Err(SearchError::InvalidQuery(_))));
+    }
+
+    #[test]
+    fn pages_beyond_the_pagination_limit_fail_the_request() {
+        let mut deep = query("laptop", &[]);
+        deep.page = Some(11);
+        deep.per_page = Some(100);
+
+        let result = index_queries_to_multi_search_request(
+            vec![IndexQuery {
+                index: "products".to_string(),
+                query: deep,
+            }],
+            sort_replica_or_index,
+        );
+
+        assert!(matches!(result, Err(SearchError::InvalidQuery(_))));
+    }
+}
@@ -0,0 +1,62 @@
+//! Partial updates of documents.
+//!
+//! The component exports the `golem:search-algolia/partial-updates` interface, updating some
+//! attributes of documents through the partial update operations of Algolia, so that counters
+//! and flags can change without rewriting the whole document.
+
+use crate::conversions::doc_to_algolia_object;
+use crate::AlgoliaComponent;
+use golem_search::golem::search::types::{Doc, IndexName, SearchError};
+use golem_search::LOGGING_STATE;
+
+#[allow(dead_code)]
+mod bindings {
+    wit_bindgen::generate!({
+        path: "wit",
+        world: "golem:search-algolia/partial-updates-library@1.0.0",
+        with: {
+            "golem:search/types@1.0.0": golem_search::golem::search::types,
+        },
+        generate_all,
+    });
+}
+
+use bindings::exports::golem::search_algolia::partial_updates::Guest;
+
+struct AlgoliaPartialUpdates;
+
+impl Guest for AlgoliaPartialUpdates {
+    fn patch(index: IndexName, doc: Doc, create_if_not_exists: bool) -> Result<(), SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = AlgoliaComponent::create_client()?;
+        let algolia_object = doc_to_algolia_object(doc).map_err(SearchError::InvalidQuery)?;
+
+        let response =
+            client.partial_update_object(&index, &algolia_object, create_if_not_exists)?;
+
+        client.wait_for_write(&index, response.task_id)
+    }
+
+    fn patch_many(
+        index: IndexName,
+        docs: Vec<Doc>,
+        create_if_not_exists: bool,
+    ) -> Result<(), SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = AlgoliaComponent::create_client()?;
+        let algolia_objects = docs
+            .into_iter()
+            .map(doc_to_algolia_object)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(SearchError::InvalidQuery)?;
+
+        let response =
+            client.partial_update_objects(&index, &algolia_objects, create_if_not_exists)?;
+
+        client.wait_for_write(&index, response.task_id)
+    }
+}
+
+bindings::export!(AlgoliaPartialUpdates with_types_in bindings);
@@ -0,0 +1,174 @@
+//! Secured API keys.
+//!
+//! The component exports the `golem:search-algolia/secured-keys` interface, so a worker can mint
+//! per-tenant keys for frontends searching Algolia directly. A secured key is the search API key
+//! of the configuration restricted by query parameters, signed with an HMAC of the parent key, so
+//! no request to Algolia is needed to generate one.
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use golem_search::config::{profile_key, validate_config_key, worker_profile};
+use golem_search::error::internal_error;
+use golem_search::golem::search::types::SearchError;
+use golem_search::LOGGING_STATE;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+#[allow(dead_code)]
+mod bindings {
+    wit_bindgen::generate!({
+        path: "wit",
+        world: "golem:search-algolia/secured-keys-library@1.0.0",
+        with: {
+            "golem:search/types@1.0.0": golem_search::golem::search::types,
+        },
+        generate_all,
+    });
+}
+
+use bindings::exports::golem::search_algolia::secured_keys::{Guest, SecuredKeyOptions};
+
+const SEARCH_API_KEY_ENV_VAR: &str = "ALGOLIA_SEARCH_API_KEY";
+
+struct AlgoliaSecuredKeys;
+
+impl Guest for AlgoliaSecuredKeys {
+    /// Signs the key with `ALGOLIA_SEARCH_API_KEY` rather than the admin key of the component,
+    /// which Algolia doesn't accept as the parent of secured keys
+    fn generate_secured_api_key(options: SecuredKeyOptions) -> Result<String, SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        generate_profile_secured_api_key(worker_profile().as_deref(), &options)
+    }
+}
+
+/// Generates a secured key from the search API key of `profile`, so that the key belongs to the
+/// same application as the other operations of the worker
+fn generate_profile_secured_api_key(
+    profile: Option<&str>,
+    options: &SecuredKeyOptions,
+) -> Result<String, SearchError> {
+    let parent_key = validate_config_key(profile_key(SEARCH_API_KEY_ENV_VAR, profile))?;
+    generate_secured_api_key(&parent_key, options)
+}
+
+/// Builds the query parameters of the restrictions, in the form Algolia decodes from secured keys
+fn secured_key_params(options: &SecuredKeyOptions) -> String {
+    let mut params = Vec::new();
+
+    if !options.filters.is_empty() {
+        let filters = options
+            .filters
+            .iter()
+            .map(|filter| format!("({filter})"))
+            .collect::<Vec<_>>()
+            .join(" AND ");
+        params.push(("filters", filters));
+    }
+    if let Some(valid_until) = options.valid_until {
+        params.push(("validUntil", valid_until.to_string()));
+    }
+    if !options.restrict_indices.is_empty() {
+        params.push(("restrictIndices", options.restrict_indices.join(",")));
+    }
+    if let Some(user_token) = &options.user_token {
+        params.push(("userToken", user_token.clone()));
+    }
+
+    params
+        .into_iter()
+        .map(|(name, value)| format!("{name}={}", urlencoding::encode(&value)))
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+/// Returns the base64 of the hex HMAC-SHA256 of the parameters, keyed with the parent key,
+/// followed by the parameters
+fn generate_secured_api_key(
+    parent_key: &str,
+    options: &SecuredKeyOptions,
+) -> Result<String, SearchError> {
+    let params = secured_key_params(options);
+
+    let mut mac = Hmac::<Sha256>::new_from_slice(parent_key.as_bytes())
+        .map_err(|e| internal_error(format!("Failed to sign the secured API key: {e}")))?;
+    mac.update(params.as_bytes());
+    let signature = mac
+        .finalize()
+        .into_bytes()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect::<String>();
+
+    Ok(STANDARD.encode(format!("{signature}{params}")))
+}
+
+bindings::export!(AlgoliaSecuredKeys with_types_in bindings);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options() -> SecuredKeyOptions {
+        SecuredKeyOptions {
+            filters: vec![],
+            valid_until: None,
+            restrict_indices: vec![],
+            user_token: None,
+        }
+    }
+
+    #[test]
+    fn restrictions_are_encoded_as_query_parameters() {
+        let options = SecuredKeyOptions {
+            filters: vec!["tenant:acme".to_string(), "visible:true".to_string()],
+            valid_until: Some(1_700_000_000),
+            restrict_indices: vec!["products".to_string(), "articles".to_string()],
+            user_token: Some("user 42".to_string()),
+        };
+
+        assert_eq!(
+            secured_key_params(&options),
+            "filters=%28tenant%3Aacme%29%20AND%20%28visible%3Atrue%29\
+             &validUntil=1700000000\
+             &restrictIndices=products%2Carticles\
+             &userToken=user%2042"
+        );
+    }
+
+    #[test]
+    fn keys_carry_the_signature_and_the_restrictions() {
+        let options = SecuredKeyOptions {
+            valid_until: Some(1_700_000_000),
+            ..options()
+        };
+
+        let key = generate_secured_api_key("parent-key", &options).unwrap();
+        let decoded = String::from_utf8(STANDARD.decode(key).unwrap()).unwrap();
+
+        let (signature, params) = decoded.split_at(64);
+        assert!(signature.chars().all(|c| c.is_ascii_hexdigit()));
+        assert_eq!(params, "validUntil=1700000000");
+    }
+
+    #[test]
+    fn keys_depend_on_the_parent_key() {
+        let first = generate_secured_api_key("first-key", &options()).unwrap();
+        let second = generate_secured_api_key("second-key", &options()).unwrap();
+
+        assert_ne!(first, second);
+    }
+
+    #[test]
+    fn keys_are_signed_with_the_search_key_of_the_profile() {
+        std::env::set_var("ALGOLIA_SECURED_KEYS_TEST_SEARCH_API_KEY", "tenant-key");
+
+        let key = generate_profile_secured_api_key(Some("secured-keys-test"), &options()).unwrap();
+
+        assert_eq!(
+            key,
+            generate_secured_api_key("tenant-key", &options()).unwrap()
+        );
+        assert!(generate_profile_secured_api_key(Some("missing-profile"), &options()).is_err());
+    }
+}
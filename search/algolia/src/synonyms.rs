@@ -0,0 +1,197 @@
+//! Synonyms of the indexes.
+//!
+//! The component exports the `golem:search-algolia/synonyms` interface, managing the synonyms of
+//! an index through the synonyms API of Algolia. Regular, one-way and placeholder synonyms are
+//! supported; the alternative corrections Algolia also stores as synonyms are left out of the
+//! listings.
+
+use crate::client::{AlgoliaSynonym, SearchSynonymsRequest};
+use crate::AlgoliaComponent;
+use golem_search::golem::search::types::{IndexName, PageRequest, SearchError};
+use golem_search::pagination::{next_page_token, page_offset, page_size};
+use golem_search::LOGGING_STATE;
+use log::warn;
+
+#[allow(dead_code)]
+mod bindings {
+    wit_bindgen::generate!({
+        path: "wit",
+        world: "golem:search-algolia/synonyms-library@1.0.0",
+        with: {
+            "golem:search/types@1.0.0": golem_search::golem::search::types,
+        },
+        generate_all,
+    });
+}
+
+use bindings::exports::golem::search_algolia::synonyms::{
+    Guest, OneWaySynonym, PlaceholderSynonym, RegularSynonym, Synonym, SynonymKind, SynonymPage,
+};
+
+struct AlgoliaSynonyms;
+
+impl Guest for AlgoliaSynonyms {
+    fn save_synonyms(
+        index: IndexName,
+        synonyms: Vec<Synonym>,
+        replace_existing: bool,
+    ) -> Result<(), SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = AlgoliaComponent::create_client()?;
+        let synonyms = synonyms
+            .into_iter()
+            .map(synonym_to_algolia_synonym)
+            .collect::<Vec<_>>();
+
+        let response = client.save_synonyms(&index, &synonyms, replace_existing)?;
+
+        client.wait_for_write(&index, response.task_id)
+    }
+
+    fn search_synonyms(
+        index: IndexName,
+        query: Option<String>,
+        page: PageRequest,
+    ) -> Result<SynonymPage, SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = AlgoliaComponent::create_client()?;
+        let offset = page_offset(&page)?;
+        let size = page_size(&page);
+        let request = SearchSynonymsRequest {
+            query: query.unwrap_or_default(),
+            page: offset / size,
+            hits_per_page: size,
+        };
+
+        let response = client.search_synonyms(&index, &request)?;
+
+        let received = response.hits.len() as u32;
+        let has_more = request.page * size + received < response.nb_hits;
+
+        Ok(SynonymPage {
+            items: response
+                .hits
+                .into_iter()
+                .filter_map(algolia_synonym_to_synonym)
+                .collect(),
+            next_page_token: next_page_token(request.page * size, received, has_more),
+        })
+    }
+
+    fn delete_synonym(index: IndexName, id: String) -> Result<(), SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = AlgoliaComponent::create_client()?;
+
+        let response = client.delete_synonym(&index, &id)?;
+
+        client.wait_for_write(&index, response.task_id)
+    }
+}
+
+fn synonym_to_algolia_synonym(synonym: Synonym) -> AlgoliaSynonym {
+    let mut algolia_synonym = AlgoliaSynonym {
+        object_id: synonym.id,
+        synonym_type: String::new(),
+        synonyms: None,
+        input: None,
+        placeholder: None,
+        replacements: None,
+    };
+
+    match synonym.kind {
+        SynonymKind::Regular(regular) => {
+            algolia_synonym.synonym_type = "synonym".to_string();
+            algolia_synonym.synonyms = Some(regular.synonyms);
+        }
+        SynonymKind::OneWay(one_way) => {
+            algolia_synonym.synonym_type = "onewaysynonym".to_string();
+            algolia_synonym.input = Some(one_way.input);
+            algolia_synonym.synonyms = Some(one_way.synonyms);
+        }
+        SynonymKind::Placeholder(placeholder) => {
+            algolia_synonym.synonym_type = "placeholder".to_string();
+            algolia_synonym.placeholder = Some(placeholder.placeholder);
+            algolia_synonym.replacements = Some(placeholder.replacements);
+        }
+    }
+
+    algolia_synonym
+}
+
+/// Returns none for the types of synonyms the interface doesn't represent
+fn algolia_synonym_to_synonym(synonym: AlgoliaSynonym) -> Option<Synonym> {
+    let kind = match synonym.synonym_type.as_str() {
+        "synonym" => SynonymKind::Regular(RegularSynonym {
+            synonyms: synonym.synonyms.unwrap_or_default(),
+        }),
+        "onewaysynonym" => SynonymKind::OneWay(OneWaySynonym {
+            input: synonym.input.unwrap_or_default(),
+            synonyms: synonym.synonyms.unwrap_or_default(),
+        }),
+        "placeholder" => SynonymKind::Placeholder(PlaceholderSynonym {
+            placeholder: synonym.placeholder.unwrap_or_default(),
+            replacements: synonym.replacements.unwrap_or_default(),
+        }),
+        other => {
+            warn!(
+                "Skipping synonym {} of unsupported type {other}",
+                synonym.object_id
+            );
+            return None;
+        }
+    };
+
+    Some(Synonym {
+        id: synonym.object_id,
+        kind,
+    })
+}
+
+bindings::export!(AlgoliaSynonyms with_types_in bindings);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn placeholder_synonyms_round_trip() {
+        let synonym = Synonym {
+            id: "street-number".to_string(),
+            kind: SynonymKind::Placeholder(PlaceholderSynonym {
+                placeholder: "<number>".to_string(),
+                replacements: vec!["1".to_string(), "one".to_string()],
+            }),
+        };
+
+        let algolia_synonym = synonym_to_algolia_synonym(synonym);
+
+        assert_eq!(algolia_synonym.synonym_type, "placeholder");
+        assert_eq!(algolia_synonym.placeholder.as_deref(), Some("<number>"));
+
+        let synonym = algolia_synonym_to_synonym(algolia_synonym).unwrap();
+
+        assert_eq!(synonym.id, "street-number");
+        assert!(matches!(
+            synonym.kind,
+            SynonymKind::Placeholder(PlaceholderSynonym { ref placeholder, .. })
+                if placeholder == "<number>"
+        ));
+    }
+
+    #[test]
+    fn alternative_corrections_are_skipped() {
+        let synonym = AlgoliaSynonym {
+            object_id: "typo".to_string(),
+            synonym_type: "altcorrection1".to_string(),
+            synonyms: None,
+            input: None,
+            placeholder: None,
+            replacements: None,
+        };
+
+        assert!(algolia_synonym_to_synonym(synonym).is_none());
+    }
+}
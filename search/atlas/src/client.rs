@@ -0,0 +1,435 @@
+use golem_search::correlation::{self, CORRELATION_ID_HEADER};
+use golem_search::error::{
+    from_reqwest_error, from_send_error, search_error_from_failed_response, unsupported,
+};
+use golem_search::golem::search::types::SearchError;
+use golem_search::logging::{redact_secret, RedactedResponse};
+use golem_search::retry::RetryPolicy;
+use log::trace;
+use reqwest::{Client, Method, RequestBuilder, Response};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map as JsonMap, Value as JsonValue};
+use std::fmt::Debug;
+use std::time::Duration;
+
+/// Media type of the versioned Atlas Administration API resources
+const ADMIN_API_MEDIA_TYPE: &str = "application/vnd.atlas.2024-05-30+json";
+
+/// The Atlas client: documents are stored and searched through the Data API of an App Services
+/// application, while Atlas Search indexes are managed through the Atlas Administration API,
+/// authenticated with the credentials of a service account.
+#[derive(Clone)]
+pub struct AtlasApi {
+    client: Client,
+    data_api_url: String,
+    api_key: String,
+    data_source: String,
+    database: String,
+    admin: Option<AdminCredentials>,
+    retry_policy: RetryPolicy,
+    request_timeout: Option<Duration>,
+    headers: Vec<(String, String)>,
+}
+
+/// The Administration API URL, project and service account managing the search indexes
+#[derive(Clone)]
+pub struct AdminCredentials {
+    pub url: String,
+    pub project_id: String,
+    pub client_id: String,
+    pub client_secret: String,
+}
+
+impl Debug for AtlasApi {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AtlasApi")
+            .field("data_api_url", &self.data_api_url)
+            .field("api_key", &redact_secret(&self.api_key))
+            .field("data_source", &self.data_source)
+            .field("database", &self.database)
+            .field(
+                "admin_client_id",
+                &self.admin.as_ref().map(|admin| &admin.client_id),
+            )
+            .field("retry_policy", &self.retry_policy)
+            .field("request_timeout", &self.request_timeout)
+            .finish()
+    }
+}
+
+pub type AtlasDoc = JsonMap<String, JsonValue>;
+
+#[derive(Debug, Deserialize)]
+pub struct DocumentsResponse {
+    #[serde(default)]
+    pub documents: Vec<AtlasDoc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DocumentResponse {
+    #[serde(default)]
+    pub document: Option<AtlasDoc>,
+}
+
+/// An Atlas Search index, as described by the Administration API
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchIndex {
+    pub name: String,
+    pub collection_name: String,
+    pub database: String,
+    #[serde(default)]
+    pub latest_definition: Option<JsonValue>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CreateSearchIndexRequest<'a> {
+    collection_name: &'a str,
+    database: &'a str,
+    name: &'a str,
+    r#type: &'a str,
+    definition: &'a JsonValue,
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+}
+
+impl Debug for TokenResponse {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TokenResponse")
+            .field("access_token", &redact_secret(&self.access_token))
+            .finish()
+    }
+}
+
+impl AtlasApi {
+    pub fn new(
+        data_api_url: String,
+        api_key: String,
+        data_source: String,
+        database: String,
+        admin: Option<AdminCredentials>,
+    ) -> Self {
+        let client = Client::builder()
+            .build()
+            .expect("Failed to initialize HTTP client");
+
+        Self {
+            client,
+            data_api_url: data_api_url.trim_end_matches('/').to_string(),
+            api_key,
+            data_source,
+            database,
+            admin: admin.map(|admin| AdminCredentials {
+                url: admin.url.trim_end_matches('/').to_string(),
+                ..admin
+            }),
+            retry_policy: RetryPolicy::from_config(),
+            request_timeout: None,
+            headers: Vec::new(),
+        }
+    }
+
+    /// Applies a timeout to every request sent by this client
+    pub fn with_request_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.request_timeout = timeout;
+        self
+    }
+
+    /// Adds custom headers to every request sent by this client
+    pub fn with_headers(mut self, headers: Vec<(String, String)>) -> Self {
+        self.headers = headers;
+        self
+    }
+
+    fn create_request(&self, method: Method, url: &str) -> RequestBuilder {
+        trace!("[Atlas] HTTP {method} {url}");
+
+        let mut req = self
+            .client
+            .request(method, url)
+            .header("Content-Type", "application/json")
+            .header("Accept", "application/json")
+            .header(CORRELATION_ID_HEADER, correlation::current())
+            .header("api-key", &self.api_key);
+
+        if let Some(timeout) = self.request_timeout {
+            req = req.timeout(timeout);
+        }
+
+        for (name, value) in &self.headers {
+            req = req.header(name, value);
+        }
+
+        req
+    }
+
+    /// Runs a Data API action on a collection of the database
+    fn action(
+        &self,
+        action: &str,
+        collection: &str,
+        mut body: JsonMap<String, JsonValue>,
+    ) -> Result<Response, reqwest::Error> {
+        let url = format!("{}/action/{action}", self.data_api_url);
+        body.insert("dataSource".to_string(), json!(self.data_source));
+        body.insert("database".to_string(), json!(self.database));
+        body.insert("collection".to_string(), json!(collection));
+        let body = JsonValue::Object(body);
+
+        self.retry_policy
+            .execute(|| self.create_request(Method::POST, &url).json(&body).send())
+    }
+
+    fn admin(&self) -> Result<&AdminCredentials, SearchError> {
+        self.admin
+            .as_ref()
+            .ok_or_else(|| unsupported("index management without Administration API credentials"))
+    }
+
+    /// Requests an access token of the service account
+    fn access_token(&self) -> Result<String, SearchError> {
+        let admin = self.admin()?;
+        let url = format!("{}/api/oauth/token", admin.url);
+
+        trace!("[Atlas] HTTP POST {url}");
+
+        let response = self
+            .retry_policy
+            .execute(|| {
+                self.client
+                    .post(&url)
+                    .basic_auth(&admin.client_id, Some(&admin.client_secret))
+                    .header("Content-Type", "application/x-www-form-urlencoded")
+                    .header("Accept", "application/json")
+                    .body("grant_type=client_credentials")
+                    .send()
+            })
+            .map_err(|e| from_send_error("Failed to request access token", e))?;
+
+        Ok(parse_response::<TokenResponse>(response)?.access_token)
+    }
+
+    /// Sends a request to the search index resources of the cluster
+    fn send_admin(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<&impl Serialize>,
+        operation: &str,
+    ) -> Result<Response, SearchError> {
+        let admin = self.admin()?;
+        let token = self.access_token()?;
+        let url = format!(
+            "{}/api/atlas/v2/groups/{}/clusters/{}/search/indexes{path}",
+            admin.url,
+            urlencoding::encode(&admin.project_id),
+            urlencoding::encode(&self.data_source)
+        );
+
+        self.retry_policy
+            .execute(|| {
+                trace!("[Atlas] HTTP {method} {url}");
+
+                let req = self
+                    .client
+                    .request(method.clone(), &url)
+                    .bearer_auth(&token)
+                    .header("Content-Type", ADMIN_API_MEDIA_TYPE)
+                    .header("Accept", ADMIN_API_MEDIA_TYPE)
+                    .header(CORRELATION_ID_HEADER, correlation::current());
+                match body {
+                    Some(body) => req.json(body).send(),
+                    None => req.send(),
+                }
+            })
+            .map_err(|e| from_send_error(format!("Failed to {operation}"), e))
+    }
+
+    /// The path of a search index of a collection, relative to the cluster's search indexes
+    fn index_path(&self, collection: &str, name: &str) -> String {
+        format!(
+            "/{}/{}/{}",
+            urlencoding::encode(&self.database),
+            urlencoding::encode(collection),
+            urlencoding::encode(name)
+        )
+    }
+}
+
+fn parse_response<T: DeserializeOwned + Debug>(response: Response) -> Result<T, SearchError> {
+    let status = response.status();
+
+    trace!(
+        "Received response from Atlas API: {:?}",
+        RedactedResponse(&response)
+    );
+
+    if status.is_success() {
+        let body = response
+            .json::<T>()
+            .map_err(|err| from_reqwest_error("Failed to decode response body", err))?;
+
+        trace!("Received response from Atlas API: {body:?}");
+
+        Ok(body)
+    } else {
+        let error = search_error_from_failed_response(response);
+
+        trace!("Received {status} response from Atlas API: {error:?}");
+
+        Err(error)
+    }
+}
+
+/// Checks the status of a response with no body of interest
+fn check_response(response: Response) -> Result<(), SearchError> {
+    if response.status().is_success() {
+        Ok(())
+    } else {
+        Err(search_error_from_failed_response(response))
+    }
+}
+
+fn body(fields: JsonValue) -> JsonMap<String, JsonValue> {
+    match fields {
+        JsonValue::Object(fields) => fields,
+        _ => JsonMap::new(),
+    }
+}
+
+impl AtlasApi {
+    pub fn insert_documents(
+        &self,
+        collection: &str,
+        docs: Vec<AtlasDoc>,
+    ) -> Result<(), SearchError> {
+        trace!("Inserting {} documents into {collection}", docs.len());
+
+        let response = self
+            .action("insertMany", collection, body(json!({ "documents": docs })))
+            .map_err(|e| from_send_error("Failed to insert documents", e))?;
+        check_response(response)
+    }
+
+    pub fn delete_documents(&self, collection: &str, filter: JsonValue) -> Result<(), SearchError> {
+        trace!("Deleting documents of {collection}");
+
+        let response = self
+            .action("deleteMany", collection, body(json!({ "filter": filter })))
+            .map_err(|e| from_send_error("Failed to delete documents", e))?;
+        check_response(response)
+    }
+
+    pub fn find_document(
+        &self,
+        collection: &str,
+        filter: JsonValue,
+    ) -> Result<Option<AtlasDoc>, SearchError> {
+        trace!("Finding document of {collection}");
+
+        let response = self
+            .action("findOne", collection, body(json!({ "filter": filter })))
+            .map_err(|e| from_send_error("Failed to get document", e))?;
+        Ok(parse_response::<DocumentResponse>(response)?.document)
+    }
+
+    /// Runs an aggregation pipeline, mapping requests that ran out of time to
+    /// `SearchError::Timeout`
+    pub fn aggregate(
+        &self,
+        collection: &str,
+        pipeline: Vec<JsonValue>,
+    ) -> Result<Vec<AtlasDoc>, SearchError> {
+        trace!("Aggregating {collection}: {pipeline:?}");
+
+        let response = self
+            .action(
+                "aggregate",
+                collection,
+                body(json!({ "pipeline": pipeline })),
+            )
+            .map_err(|e| from_send_error("Failed to search", e))?;
+        Ok(parse_response::<DocumentsResponse>(response)?.documents)
+    }
+
+    pub fn create_search_index(
+        &self,
+        collection: &str,
+        name: &str,
+        definition: &JsonValue,
+    ) -> Result<(), SearchError> {
+        trace!("Creating search index {name} of {collection}");
+
+        let request = CreateSearchIndexRequest {
+            collection_name: collection,
+            database: &self.database,
+            name,
+            r#type: "search",
+            definition,
+        };
+        let response = self.send_admin(Method::POST, "", Some(&request), "create search index")?;
+        check_response(response)
+    }
+
+    pub fn get_search_index(
+        &self,
+        collection: &str,
+        name: &str,
+    ) -> Result<SearchIndex, SearchError> {
+        trace!("Getting search index {name} of {collection}");
+
+        let response = self.send_admin(
+            Method::GET,
+            &self.index_path(collection, name),
+            None::<&()>,
+            "get search index",
+        )?;
+        parse_response(response)
+    }
+
+    pub fn update_search_index(
+        &self,
+        collection: &str,
+        name: &str,
+        definition: &JsonValue,
+    ) -> Result<(), SearchError> {
+        trace!("Updating search index {name} of {collection}");
+
+        let response = self.send_admin(
+            Method::PATCH,
+            &self.index_path(collection, name),
+            Some(&json!({ "definition": definition })),
+            "update search index",
+        )?;
+        check_response(response)
+    }
+
+    pub fn delete_search_index(&self, collection: &str, name: &str) -> Result<(), SearchError> {
+        trace!("Deleting search index {name} of {collection}");
+
+        let response = self.send_admin(
+            Method::DELETE,
+            &self.index_path(collection, name),
+            None::<&()>,
+            "delete search index",
+        )?;
+        check_response(response)
+    }
+
+    /// Lists the search indexes of the database's collections
+    pub fn list_search_indexes(&self) -> Result<Vec<SearchIndex>, SearchError> {
+        trace!("Listing search indexes");
+
+        let response = self.send_admin(Method::GET, "", None::<&()>, "list search indexes")?;
+        let indexes = parse_response::<Vec<SearchIndex>>(response)?;
+        Ok(indexes
+            .into_iter()
+            .filter(|index| index.database == self.database)
+            .collect())
+    }
+}
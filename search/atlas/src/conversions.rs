@@ -0,0 +1,967 @@
+use crate::client::AtlasDoc;
+use golem_search::config::{get_max_pagination_depth_config, search_filters_match_any};
+use golem_search::error::invalid_query;
+use golem_search::golem::search::types::{
+    Doc, FieldType, Schema, SchemaField, SearchError, SearchHit, SearchQuery,
+};
+use serde_json::{json, Map as JsonMap, Value as JsonValue};
+
+const DEFAULT_PER_PAGE: u32 = 20;
+
+/// Maximum number of values counted per facet
+const MAX_FACET_VALUES: u32 = 100;
+
+/// The field holding the document ids
+pub const ID_FIELD: &str = "_id";
+
+/// Fields added to the documents of search results, holding the score, highlights and pagination
+/// token of each hit and the count and facets of the search
+const SCORE_FIELD: &str = "__score";
+const HIGHLIGHTS_FIELD: &str = "__highlights";
+const TOKEN_FIELD: &str = "__token";
+const META_FIELD: &str = "__meta";
+
+const DEFAULT_PRE_TAG: &str = "<em>";
+const DEFAULT_POST_TAG: &str = "</em>";
+
+pub fn doc_to_atlas(doc: Doc) -> Result<AtlasDoc, SearchError> {
+    let mut fields = match serde_json::from_str::<JsonValue>(&doc.content) {
+        Ok(JsonValue::Object(fields)) => fields,
+        Ok(_) => return Err(invalid_query("Document content must be a JSON object")),
+        Err(e) => return Err(invalid_query(format!("Invalid JSON in document: {e}"))),
+    };
+    fields.insert(ID_FIELD.to_string(), JsonValue::String(doc.id));
+    Ok(fields)
+}
+
+/// Splits a document into its id and its fields. Ids of documents not inserted through this
+/// component may be extended JSON object ids, `{"$oid": "..."}`.
+fn split_doc(mut doc: AtlasDoc) -> (String, AtlasDoc) {
+    let id = match doc.remove(ID_FIELD) {
+        Some(JsonValue::String(id)) => id,
+        Some(JsonValue::Object(id)) if id.get("$oid").is_some_and(JsonValue::is_string) => {
+            id["$oid"].as_str().unwrap_or_default().to_string()
+        }
+        Some(id) => id.to_string(),
+        None => String::new(),
+    };
+    (id, doc)
+}
+
+pub fn atlas_to_doc(doc: AtlasDoc) -> Doc {
+    let (id, fields) = split_doc(doc);
+    Doc {
+        id,
+        content: JsonValue::Object(fields).to_string(),
+    }
+}
+
+/// The filter matching the documents of the given ids
+pub fn ids_filter(ids: &[String]) -> JsonValue {
+    json!({ ID_FIELD: { "$in": ids } })
+}
+
+pub fn id_filter(id: &str) -> JsonValue {
+    json!({ ID_FIELD: id })
+}
+
+/// The field mappings of a schema field. Keyword fields are indexed as tokens, which sort, facet
+/// and match exactly, and text fields also get a token mapping when faceted or sorted.
+fn schema_field_to_mapping(field: &SchemaField) -> JsonValue {
+    let mut mappings = match field.field_type {
+        FieldType::Text => vec![json!({ "type": "string" })],
+        FieldType::Keyword => vec![json!({ "type": "token" })],
+        FieldType::Integer => {
+            vec![json!({ "type": "number", "representation": "int64", "indexDoubles": false })]
+        }
+        FieldType::Float => vec![json!({ "type": "number" })],
+        FieldType::Boolean => vec![json!({ "type": "boolean" })],
+        FieldType::Date => vec![json!({ "type": "date" })],
+        FieldType::GeoPoint => vec![json!({ "type": "geo" })],
+    };
+    if field.field_type == FieldType::Text && (field.facet || field.sort) {
+        mappings.push(json!({ "type": "token" }));
+    }
+    match mappings.len() {
+        1 => mappings.remove(0),
+        _ => JsonValue::Array(mappings),
+    }
+}
+
+/// The definition of a search index. Without a schema, the index maps the fields of the
+/// documents dynamically.
+pub fn schema_to_definition(schema: Option<&Schema>) -> JsonValue {
+    match schema {
+        Some(schema) => {
+            let fields = schema
+                .fields
+                .iter()
+                .filter(|field| field.index || field.facet || field.sort)
+                .map(|field| (field.name.clone(), schema_field_to_mapping(field)))
+                .collect::<JsonMap<_, _>>();
+            json!({ "mappings": { "dynamic": false, "fields": fields } })
+        }
+        None => json!({ "mappings": { "dynamic": true } }),
+    }
+}
+
+fn mapping_to_schema_field(name: &str, mapping: &JsonValue) -> Option<SchemaField> {
+    let mappings = match mapping {
+        JsonValue::Array(mappings) => mappings.iter().collect::<Vec<_>>(),
+        mapping => vec![mapping],
+    };
+    let types = mappings
+        .iter()
+        .filter_map(|mapping| mapping.get("type")?.as_str())
+        .collect::<Vec<_>>();
+    let has = |r#type: &str| types.contains(&r#type);
+
+    let field_type = if has("string") || has("autocomplete") {
+        FieldType::Text
+    } else if has("token") || has("stringFacet") {
+        FieldType::Keyword
+    } else if has("number") || has("numberFacet") {
+        let int64 = mappings.iter().any(|mapping| {
+            mapping.get("representation").and_then(JsonValue::as_str) == Some("int64")
+        });
+        if int64 {
+            FieldType::Integer
+        } else {
+            FieldType::Float
+        }
+    } else if has("boolean") {
+        FieldType::Boolean
+    } else if has("date") || has("dateFacet") {
+        FieldType::Date
+    } else if has("geo") {
+        FieldType::GeoPoint
+    } else {
+        return None;
+    };
+    let facetable = match field_type {
+        FieldType::Text => has("token") || has("stringFacet"),
+        FieldType::Boolean | FieldType::GeoPoint => false,
+        _ => true,
+    };
+    Some(SchemaField {
+        name: name.to_string(),
+        field_type,
+        required: false,
+        facet: facetable,
+        sort: facetable || field_type == FieldType::Boolean,
+        index: true,
+    })
+}
+
+/// The schema of a search index definition, from its static field mappings
+pub fn definition_to_schema(definition: &JsonValue) -> Schema {
+    let fields = definition
+        .pointer("/mappings/fields")
+        .and_then(JsonValue::as_object)
+        .map(|fields| {
+            fields
+                .iter()
+                .filter_map(|(name, mapping)| mapping_to_schema_field(name, mapping))
+                .collect()
+        })
+        .unwrap_or_default();
+    Schema {
+        fields,
+        primary_key: Some(ID_FIELD.to_string()),
+    }
+}
+
+fn is_field_name(key: &str) -> bool {
+    !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.')
+}
+
+/// Whether a value looks like an ISO 8601 date, such as `2020-01-01` or `2020-01-01T00:00:00Z`
+fn is_date(value: &str) -> bool {
+    let bytes = value.as_bytes();
+    bytes.len() >= 10
+        && bytes[..4].iter().all(u8::is_ascii_digit)
+        && bytes[4] == b'-'
+        && bytes[5..7].iter().all(u8::is_ascii_digit)
+        && bytes[7] == b'-'
+        && bytes[8..10].iter().all(u8::is_ascii_digit)
+}
+
+/// The value of a filter: quoted values are strings, numbers and booleans keep their type, and
+/// dates become extended JSON dates
+fn filter_value(value: &str) -> JsonValue {
+    let value = value.trim();
+    if let Some(unquoted) = value
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+    {
+        return json!(unquoted);
+    }
+    match serde_json::from_str::<JsonValue>(value) {
+        Ok(value @ (JsonValue::Number(_) | JsonValue::Bool(_))) => value,
+        _ if is_date(value) => json!({ "$date": value }),
+        _ => json!(value),
+    }
+}
+
+/// A filter clause, and whether it excludes its matches
+struct FilterClause {
+    operator: JsonValue,
+    negated: bool,
+}
+
+/// Converts a filter to a search operator. Filters are either search operators given as JSON
+/// objects, or `field:value`, `field=value`, `field!=value`, and `field>value` style comparisons
+/// with `>`, `>=`, `<` and `<=`, which become `equals` and `range` operators.
+fn filter_to_clause(filter: &str) -> Result<FilterClause, SearchError> {
+    let filter = filter.trim();
+    if filter.starts_with('{') {
+        let operator = serde_json::from_str::<JsonMap<String, JsonValue>>(filter)
+            .map_err(|e| invalid_query(format!("Invalid filter `{filter}`: {e}")))?;
+        return Ok(FilterClause {
+            operator: JsonValue::Object(operator),
+            negated: false,
+        });
+    }
+
+    let position = filter
+        .find([':', '=', '!', '<', '>'])
+        .ok_or_else(|| invalid_query(format!("Unsupported filter `{filter}`")))?;
+    let key = filter[..position].trim();
+    let rest = &filter[position..];
+    let (op, value) = [">=", "<=", "!=", ">", "<", "=", ":"]
+        .into_iter()
+        .find_map(|op| Some((op, rest.strip_prefix(op)?)))
+        .ok_or_else(|| invalid_query(format!("Unsupported filter `{filter}`")))?;
+    if !is_field_name(key) || value.trim().is_empty() {
+        return Err(invalid_query(format!("Unsupported filter `{filter}`")));
+    }
+    let value = filter_value(value);
+
+    let operator = match op {
+        ":" | "=" | "!=" => json!({ "equals": { "path": key, "value": value } }),
+        ">" => json!({ "range": { "path": key, "gt": value } }),
+        ">=" => json!({ "range": { "path": key, "gte": value } }),
+        "<" => json!({ "range": { "path": key, "lt": value } }),
+        _ => json!({ "range": { "path": key, "lte": value } }),
+    };
+    Ok(FilterClause {
+        operator,
+        negated: op == "!=",
+    })
+}
+
+/// The operator matching the text of a query, or every document without text. Boosted fields
+/// are searched separately, each with its boost.
+fn text_operator(query: &SearchQuery) -> JsonValue {
+    let config = query.config.as_ref();
+    let Some(q) = query.q.as_deref().map(str::trim).filter(|q| !q.is_empty()) else {
+        return json!({
+            "wildcard": {
+                "query": "*",
+                "path": { "wildcard": "*" },
+                "allowAnalyzedField": true,
+            }
+        });
+    };
+    let fuzzy = config.and_then(|config| config.typo_tolerance) == Some(true);
+    let text = |path: JsonValue, boost: Option<f32>| {
+        let mut text = json!({ "query": q, "path": path });
+        if fuzzy {
+            text["fuzzy"] = json!({});
+        }
+        if let Some(boost) = boost {
+            text["score"] = json!({ "boost": { "value": boost } });
+        }
+        json!({ "text": text })
+    };
+
+    match config.filter(|config| !config.boost_fields.is_empty()) {
+        Some(config) => json!({
+            "compound": {
+                "should": config
+                    .boost_fields
+                    .iter()
+                    .map(|(field, boost)| text(json!(field), Some(*boost)))
+                    .collect::<Vec<_>>(),
+                "minimumShouldMatch": 1,
+            }
+        }),
+        None => text(json!({ "wildcard": "*" }), None),
+    }
+}
+
+/// The compound operator of a query: its text must match and its filters restrict the matches,
+/// either all of them or, when any filter may match, at least one of them
+fn query_to_operator(query: &SearchQuery) -> Result<JsonValue, SearchError> {
+    let clauses = query
+        .filters
+        .iter()
+        .map(|filter| filter_to_clause(filter))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut compound = JsonMap::new();
+    compound.insert("must".to_string(), json!([text_operator(query)]));
+
+    if search_filters_match_any(query) && clauses.len() > 1 {
+        let should = clauses
+            .into_iter()
+            .map(|clause| {
+                if clause.negated {
+                    json!({ "compound": { "mustNot": [clause.operator] } })
+                } else {
+                    clause.operator
+                }
+            })
+            .collect::<Vec<_>>();
+        compound.insert(
+            "filter".to_string(),
+            json!([{ "compound": { "should": should, "minimumShouldMatch": 1 } }]),
+        );
+    } else {
+        let (must_not, filter): (Vec<_>, Vec<_>) =
+            clauses.into_iter().partition(|clause| clause.negated);
+        if !filter.is_empty() {
+            compound.insert(
+                "filter".to_string(),
+                json!(filter
+                    .into_iter()
+                    .map(|clause| clause.operator)
+                    .collect::<Vec<_>>()),
+            );
+        }
+        if !must_not.is_empty() {
+            compound.insert(
+                "mustNot".to_string(),
+                json!(must_not
+                    .into_iter()
+                    .map(|clause| clause.operator)
+                    .collect::<Vec<_>>()),
+            );
+        }
+    }
+
+    Ok(json!({ "compound": compound }))
+}
+
+/// The sort of a query, from a single `field`, `field:asc`, `field:desc` or `-field` sort entry.
+/// Sorting by several fields is not supported, as the order of the fields of JSON objects is not
+/// kept.
+fn query_to_sort(sort: &[String]) -> Result<Option<JsonValue>, SearchError> {
+    let sort = match sort {
+        [] => return Ok(None),
+        [sort] => sort.trim(),
+        _ => return Err(invalid_query("Sorting by several fields is not supported")),
+    };
+    let (key, order) = if let Some(key) = sort.strip_prefix('-') {
+        (key, -1)
+    } else {
+        match sort.rsplit_once(':') {
+            Some((key, order)) if order.eq_ignore_ascii_case("asc") => (key, 1),
+            Some((key, order)) if order.eq_ignore_ascii_case("desc") => (key, -1),
+            Some(_) => return Err(invalid_query(format!("Invalid sort `{sort}`"))),
+            None => (sort, 1),
+        }
+    };
+    if !is_field_name(key) {
+        return Err(invalid_query(format!("Invalid sort `{sort}`")));
+    }
+    Ok(Some(json!({ key: order })))
+}
+
+/// The string facets counting the values of the query's facets
+fn query_to_facets(facets: &[String]) -> Result<JsonMap<String, JsonValue>, SearchError> {
+    facets
+        .iter()
+        .map(|facet| {
+            if !is_field_name(facet) {
+                return Err(invalid_query(format!("Invalid facet `{facet}`")));
+            }
+            Ok((
+                facet.clone(),
+                json!({ "type": "string", "path": facet, "numBuckets": MAX_FACET_VALUES }),
+            ))
+        })
+        .collect()
+}
+
+/// Number of hits to skip and number of hits per page of a query
+pub fn query_window(query: &SearchQuery) -> Result<(u32, u32), SearchError> {
+    let per_page = query.per_page.unwrap_or(DEFAULT_PER_PAGE);
+    let offset = query.offset.unwrap_or_else(|| {
+        query
+            .page
+            .unwrap_or(1)
+            .saturating_sub(1)
+            .saturating_mul(per_page)
+    });
+    if offset.saturating_add(per_page) > get_max_pagination_depth_config() {
+        return Err(invalid_query(format!(
+            "Requested hits beyond the maximum pagination depth of {}",
+            get_max_pagination_depth_config()
+        )));
+    }
+    Ok((offset, per_page))
+}
+
+/// Builds the aggregation pipeline of a search on the search index `index`, reading the page
+/// after the pagination token `search_after` instead of skipping hits when given one. The
+/// provider parameters are added to the `$search` stage, for example
+/// `{"concurrent": true}`.
+pub fn query_to_pipeline(
+    query: &SearchQuery,
+    index: &str,
+    search_after: Option<&str>,
+) -> Result<Vec<JsonValue>, SearchError> {
+    let (offset, per_page) = query_window(query)?;
+    let config = query.config.as_ref();
+    let operator = query_to_operator(query)?;
+
+    let mut search = JsonMap::new();
+    search.insert("index".to_string(), json!(index));
+    let facets = query_to_facets(&query.facets)?;
+    if facets.is_empty() {
+        if let JsonValue::Object(operator) = operator {
+            search.extend(operator);
+        }
+    } else {
+        search.insert(
+            "facet".to_string(),
+            json!({ "operator": operator, "facets": facets }),
+        );
+    }
+    if search_after.is_none() {
+        search.insert("count".to_string(), json!({ "type": "total" }));
+    }
+    if let Some(sort) = query_to_sort(&query.sort)? {
+        search.insert("sort".to_string(), sort);
+    }
+    if let Some(highlight) = &query.highlight {
+        let path = if highlight.fields.is_empty() {
+            json!({ "wildcard": "*" })
+        } else {
+            json!(highlight.fields)
+        };
+        search.insert("highlight".to_string(), json!({ "path": path }));
+    }
+    if let Some(search_after) = search_after {
+        search.insert("searchAfter".to_string(), json!(search_after));
+    }
+    if let Some(provider_params) = config.and_then(|config| config.provider_params.as_deref()) {
+        let provider_params =
+            serde_json::from_str::<JsonMap<String, JsonValue>>(provider_params)
+                .map_err(|e| invalid_query(format!("Invalid provider parameters: {e}")))?;
+        search.extend(
+            provider_params
+                .into_iter()
+                .filter(|(key, _)| key != "headers"),
+        );
+    }
+
+    let mut pipeline = vec![json!({ "$search": search })];
+    if search_after.is_none() && offset > 0 {
+        pipeline.push(json!({ "$skip": offset }));
+    }
+    pipeline.push(json!({ "$limit": per_page }));
+
+    let mut meta = JsonMap::new();
+    meta.insert(SCORE_FIELD.to_string(), json!({ "$meta": "searchScore" }));
+    meta.insert(
+        TOKEN_FIELD.to_string(),
+        json!({ "$meta": "searchSequenceToken" }),
+    );
+    if query.highlight.is_some() {
+        meta.insert(
+            HIGHLIGHTS_FIELD.to_string(),
+            json!({ "$meta": "searchHighlights" }),
+        );
+    }
+    if search_after.is_none() {
+        meta.insert(META_FIELD.to_string(), json!("$$SEARCH_META"));
+    }
+
+    let attributes = config
+        .map(|config| config.attributes_to_retrieve.clone())
+        .unwrap_or_default();
+    if attributes.is_empty() {
+        pipeline.push(json!({ "$addFields": meta }));
+    } else {
+        let mut project = meta;
+        project.insert(ID_FIELD.to_string(), json!(1));
+        for attribute in attributes {
+            project.insert(attribute, json!(1));
+        }
+        pipeline.push(json!({ "$project": project }));
+    }
+
+    Ok(pipeline)
+}
+
+/// Joins the texts of a highlight, wrapping its hits in the highlighting tags
+fn highlight_snippet(highlight: &JsonValue, pre_tag: &str, post_tag: &str) -> Option<String> {
+    let texts = highlight.get("texts")?.as_array()?;
+    Some(
+        texts
+            .iter()
+            .filter_map(|text| {
+                let value = text.get("value")?.as_str()?;
+                Some(match text.get("type").and_then(JsonValue::as_str) {
+                    Some("hit") => format!("{pre_tag}{value}{post_tag}"),
+                    _ => value.to_string(),
+                })
+            })
+            .collect(),
+    )
+}
+
+/// Highlights in the same shape as the other providers: `{"field": ["snippet"]}`
+fn highlights_to_snippets(highlights: &JsonValue, query: &SearchQuery) -> Option<String> {
+    let highlight = query.highlight.as_ref()?;
+    let pre_tag = highlight.pre_tag.as_deref().unwrap_or(DEFAULT_PRE_TAG);
+    let post_tag = highlight.post_tag.as_deref().unwrap_or(DEFAULT_POST_TAG);
+
+    let mut snippets = JsonMap::new();
+    for highlight in highlights.as_array()? {
+        let (Some(path), Some(snippet)) = (
+            highlight.get("path").and_then(JsonValue::as_str),
+            highlight_snippet(highlight, pre_tag, post_tag),
+        ) else {
+            continue;
+        };
+        let snippet = match highlight_max_length(query) {
+            Some(max_length) => snippet.chars().take(max_length).collect(),
+            None => snippet,
+        };
+        if let JsonValue::Array(field_snippets) = snippets
+            .entry(path.to_string())
+            .or_insert_with(|| json!([]))
+        {
+            field_snippets.push(json!(snippet));
+        }
+    }
+    (!snippets.is_empty()).then(|| JsonValue::Object(snippets).to_string())
+}
+
+fn highlight_max_length(query: &SearchQuery) -> Option<usize> {
+    query
+        .highlight
+        .as_ref()?
+        .max_length
+        .map(|max_length| max_length as usize)
+}
+
+/// Facet counts in the same shape as the other providers: `{"field": {"value": count}}`
+fn meta_to_facets(meta: &JsonValue) -> Option<String> {
+    let counts = meta
+        .get("facet")?
+        .as_object()?
+        .iter()
+        .filter_map(|(field, facet)| {
+            let counts = facet
+                .get("buckets")?
+                .as_array()?
+                .iter()
+                .filter_map(|bucket| {
+                    let value = match bucket.get(ID_FIELD)? {
+                        JsonValue::String(value) => value.clone(),
+                        value => value.to_string(),
+                    };
+                    Some((value, bucket.get("count")?.clone()))
+                })
+                .collect::<JsonMap<_, _>>();
+            Some((field.clone(), JsonValue::Object(counts)))
+        })
+        .collect::<JsonMap<_, _>>();
+    (!counts.is_empty()).then(|| JsonValue::Object(counts).to_string())
+}
+
+/// The results of a search pipeline
+pub struct ParsedResults {
+    pub hits: Vec<SearchHit>,
+    pub total: Option<u32>,
+    pub facets: Option<String>,
+    /// The pagination token of the last hit
+    pub last_token: Option<String>,
+}
+
+/// Parses the documents of a search pipeline. The count and facets of the search are copied to
+/// every document, so a page without documents has neither.
+pub fn parse_documents(documents: Vec<AtlasDoc>, query: &SearchQuery) -> ParsedResults {
+    let mut meta = None;
+    let mut last_token = None;
+    let hits = documents
+        .into_iter()
+        .map(|mut doc| {
+            let score = doc.remove(SCORE_FIELD).and_then(|score| score.as_f64());
+            let highlights = doc
+                .remove(HIGHLIGHTS_FIELD)
+                .and_then(|highlights| highlights_to_snippets(&highlights, query));
+            last_token = doc
+                .remove(TOKEN_FIELD)
+                .and_then(|token| token.as_str().map(str::to_string));
+            if let Some(doc_meta) = doc.remove(META_FIELD) {
+                meta = Some(doc_meta);
+            }
+            let (id, fields) = split_doc(doc);
+            SearchHit {
+                id,
+                score,
+                content: Some(JsonValue::Object(fields).to_string()),
+                highlights,
+            }
+        })
+        .collect();
+
+    ParsedResults {
+        hits,
+        total: meta
+            .as_ref()
+            .and_then(|meta| meta.pointer("/count/total")?.as_u64())
+            .map(|total| total as u32),
+        facets: meta.as_ref().and_then(meta_to_facets),
+        last_token,
+    }
+}
+
+pub fn create_retry_query(original_query: &SearchQuery, partial_hits: &[SearchHit]) -> SearchQuery {
+    let mut retry_query = original_query.clone();
+
+    let current_offset = original_query.offset.unwrap_or(0);
+    let per_page = original_query.per_page.unwrap_or(DEFAULT_PER_PAGE);
+    let hits_received = partial_hits.len() as u32;
+
+    retry_query.offset = Some(current_offset + hits_received.min(per_page));
+
+    retry_query
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use golem_search::golem::search::types::{FilterMode, HighlightConfig, SearchConfig};
+
+    fn query(q: Option<&str>, filters: &[&str]) -> SearchQuery {
+        SearchQuery {
+            q: q.map(str::to_string),
+            filters: filters.iter().map(|filter| filter.to_string()).collect(),
+            sort: vec![],
+            facets: vec![],
+            page: None,
+            per_page: None,
+            offset: None,
+            highlight: None,
+            config: None,
+        }
+    }
+
+    fn field(name: &str, field_type: FieldType, facet: bool, sort: bool) -> SchemaField {
+        SchemaField {
+            name: name.to_string(),
+            field_type,
+            required: false,
+            facet,
+            sort,
+            index: true,
+        }
+    }
+
+    fn config(provider_params: Option<&str>, filter_mode: Option<FilterMode>) -> SearchConfig {
+        SearchConfig {
+            timeout_ms: None,
+            boost_fields: vec![],
+            attributes_to_retrieve: vec![],
+            language: None,
+            typo_tolerance: None,
+            exact_match_boost: None,
+            provider_params: provider_params.map(str::to_string),
+            profile: None,
+            filter_mode,
+            post_filters: vec![],
+            rescore: None,
+            fallbacks: vec![],
+        }
+    }
+
+    #[test]
+    fn test_doc_conversions() {
+        let doc = doc_to_atlas(Doc {
+            id: "doc1".to_string(),
+            content: r#"{"title":"Dune"}"#.to_string(),
+        })
+        .unwrap();
+        assert_eq!(
+            JsonValue::Object(doc.clone()),
+            json!({ "_id": "doc1", "title": "Dune" })
+        );
+        let doc = atlas_to_doc(doc);
+        assert_eq!(doc.id, "doc1");
+        assert_eq!(doc.content, r#"{"title":"Dune"}"#);
+
+        let doc = atlas_to_doc(
+            json!({ "_id": { "$oid": "65f1c0ffee" }, "title": "Dune" })
+                .as_object()
+                .unwrap()
+                .clone(),
+        );
+        assert_eq!(doc.id, "65f1c0ffee");
+
+        assert!(doc_to_atlas(Doc {
+            id: "doc1".to_string(),
+            content: "[]".to_string(),
+        })
+        .is_err());
+    }
+
+    #[test]
+    fn test_schema_round_trip() {
+        let schema = Schema {
+            fields: vec![
+                field("title", FieldType::Text, false, true),
+                field("genre", FieldType::Keyword, true, false),
+                field("year", FieldType::Integer, false, true),
+                field("published", FieldType::Date, false, false),
+            ],
+            primary_key: None,
+        };
+        let definition = schema_to_definition(Some(&schema));
+        assert_eq!(
+            definition,
+            json!({
+                "mappings": {
+                    "dynamic": false,
+                    "fields": {
+                        "title": [{ "type": "string" }, { "type": "token" }],
+                        "genre": { "type": "token" },
+                        "year": { "type": "number", "representation": "int64", "indexDoubles": false },
+                        "published": { "type": "date" },
+                    }
+                }
+            })
+        );
+        assert_eq!(
+            schema_to_definition(None),
+            json!({ "mappings": { "dynamic": true } })
+        );
+
+        let round_trip = definition_to_schema(&definition);
+        let mut fields = round_trip
+            .fields
+            .iter()
+            .map(|field| (field.name.as_str(), field.field_type, field.sort))
+            .collect::<Vec<_>>();
+        fields.sort_by_key(|(name, _, _)| *name);
+        assert_eq!(
+            fields,
+            vec![
+                ("genre", FieldType::Keyword, true),
+                ("published", FieldType::Date, true),
+                ("title", FieldType::Text, true),
+                ("year", FieldType::Integer, true),
+            ]
+        );
+        assert_eq!(round_trip.primary_key.as_deref(), Some("_id"));
+    }
+
+    #[test]
+    fn test_filters_to_operators() {
+        let clause = |filter| filter_to_clause(filter).unwrap().operator;
+        assert_eq!(
+            clause("genre:fiction"),
+            json!({ "equals": { "path": "genre", "value": "fiction" } })
+        );
+        assert_eq!(
+            clause("in_stock=true"),
+            json!({ "equals": { "path": "in_stock", "value": true } })
+        );
+        assert_eq!(
+            clause("year>1950"),
+            json!({ "range": { "path": "year", "gt": 1950 } })
+        );
+        assert_eq!(
+            clause("published<=2020-01-01T00:00:00Z"),
+            json!({ "range": { "path": "published", "lte": { "$date": "2020-01-01T00:00:00Z" } } })
+        );
+        assert_eq!(
+            clause("code=\"1950\""),
+            json!({ "equals": { "path": "code", "value": "1950" } })
+        );
+        assert!(filter_to_clause("genre!=poetry").unwrap().negated);
+        assert_eq!(
+            clause(r#"{"in": {"path": "genre", "value": ["a", "b"]}}"#),
+            json!({ "in": { "path": "genre", "value": ["a", "b"] } })
+        );
+        assert!(filter_to_clause("no operator").is_err());
+        assert!(filter_to_clause("{not json").is_err());
+    }
+
+    #[test]
+    fn test_query_to_pipeline() {
+        let mut query = query(Some("dune"), &["genre:fiction", "genre!=poetry"]);
+        query.sort = vec!["-year".to_string()];
+        query.facets = vec!["genre".to_string()];
+        query.page = Some(3);
+        query.per_page = Some(10);
+        query.highlight = Some(HighlightConfig {
+            fields: vec!["title".to_string()],
+            pre_tag: None,
+            post_tag: None,
+            max_length: None,
+        });
+        let mut config = config(
+            Some(r#"{"concurrent": true, "headers": {"X-Tenant": "a"}}"#),
+            None,
+        );
+        config.boost_fields = vec![("title".to_string(), 2.0)];
+        config.typo_tolerance = Some(true);
+        query.config = Some(config);
+
+        assert_eq!(
+            query_to_pipeline(&query, "default", None).unwrap(),
+            vec![
+                json!({
+                    "$search": {
+                        "index": "default",
+                        "facet": {
+                            "operator": {
+                                "compound": {
+                                    "must": [{
+                                        "compound": {
+                                            "should": [{
+                                                "text": {
+                                                    "query": "dune",
+                                                    "path": "title",
+                                                    "fuzzy": {},
+                                                    "score": { "boost": { "value": 2.0 } },
+                                                }
+                                            }],
+                                            "minimumShouldMatch": 1,
+                                        }
+                                    }],
+                                    "filter": [{ "equals": { "path": "genre", "value": "fiction" } }],
+                                    "mustNot": [{ "equals": { "path": "genre", "value": "poetry" } }],
+                                }
+                            },
+                            "facets": {
+                                "genre": { "type": "string", "path": "genre", "numBuckets": 100 }
+                            },
+                        },
+                        "count": { "type": "total" },
+                        "sort": { "year": -1 },
+                        "highlight": { "path": ["title"] },
+                        "concurrent": true,
+                    }
+                }),
+                json!({ "$skip": 20 }),
+                json!({ "$limit": 10 }),
+                json!({
+                    "$addFields": {
+                        "__score": { "$meta": "searchScore" },
+                        "__token": { "$meta": "searchSequenceToken" },
+                        "__highlights": { "$meta": "searchHighlights" },
+                        "__meta": "$$SEARCH_META",
+                    }
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_query_to_pipeline_with_search_after() {
+        let mut query = query(None, &["genre:fiction", "genre!=poetry"]);
+        let mut config = config(None, Some(FilterMode::Any));
+        config.attributes_to_retrieve = vec!["title".to_string()];
+        query.config = Some(config);
+
+        assert_eq!(
+            query_to_pipeline(&query, "default", Some("CJYBFQA")).unwrap(),
+            vec![
+                json!({
+                    "$search": {
+                        "index": "default",
+                        "compound": {
+                            "must": [{
+                                "wildcard": {
+                                    "query": "*",
+                                    "path": { "wildcard": "*" },
+                                    "allowAnalyzedField": true,
+                                }
+                            }],
+                            "filter": [{
+                                "compound": {
+                                    "should": [
+                                        { "equals": { "path": "genre", "value": "fiction" } },
+                                        { "compound": { "mustNot": [{ "equals": { "path": "genre", "value": "poetry" } }] } },
+                                    ],
+                                    "minimumShouldMatch": 1,
+                                }
+                            }],
+                        },
+                        "searchAfter": "CJYBFQA",
+                    }
+                }),
+                json!({ "$limit": 20 }),
+                json!({
+                    "$project": {
+                        "__score": { "$meta": "searchScore" },
+                        "__token": { "$meta": "searchSequenceToken" },
+                        "_id": 1,
+                        "title": 1,
+                    }
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_documents() {
+        let mut query = query(Some("dune"), &[]);
+        query.highlight = Some(HighlightConfig {
+            fields: vec![],
+            pre_tag: Some("<b>".to_string()),
+            post_tag: Some("</b>".to_string()),
+            max_length: None,
+        });
+        let documents = vec![
+            json!({
+                "_id": "doc1",
+                "title": "Dune",
+                "__score": 2.5,
+                "__token": "CJYBFQA",
+                "__highlights": [{
+                    "path": "title",
+                    "texts": [{ "value": "Dune", "type": "hit" }, { "value": " Messiah", "type": "text" }],
+                    "score": 1.2,
+                }],
+                "__meta": {
+                    "count": { "total": 42 },
+                    "facet": { "genre": { "buckets": [{ "_id": "fiction", "count": 8 }] } },
+                },
+            }),
+            json!({ "_id": "doc2", "title": "Children of Dune", "__score": 1.5, "__token": "CJcBFQB" }),
+        ]
+        .into_iter()
+        .map(|doc| doc.as_object().unwrap().clone())
+        .collect();
+
+        let results = parse_documents(documents, &query);
+        assert_eq!(results.total, Some(42));
+        assert_eq!(
+            results.facets.as_deref(),
+            Some(r#"{"genre":{"fiction":8}}"#)
+        );
+        assert_eq!(results.last_token.as_deref(), Some("CJcBFQB"));
+        assert_eq!(results.hits.len(), 2);
+        assert_eq!(results.hits[0].id, "doc1");
+        assert_eq!(results.hits[0].score, Some(2.5));
+        assert_eq!(
+            results.hits[0].content.as_deref(),
+            Some(r#"{"title":"Dune"}"#)
+        );
+        assert_eq!(
+            results.hits[0].highlights.as_deref(),
+            Some(r#"{"title":["<b>Dune</b> Messiah"]}"#)
+        );
+    }
+}
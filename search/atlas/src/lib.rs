@@ -0,0 +1,396 @@
+use crate::client::{AdminCredentials, AtlasApi};
+use crate::conversions::{
+    atlas_to_doc, create_retry_query, definition_to_schema, doc_to_atlas, id_filter, ids_filter,
+    parse_documents, query_to_pipeline, query_window, schema_to_definition,
+};
+use golem_rust::wasm_rpc::Pollable;
+use golem_search::config::{
+    get_config_with_default, get_optional_config, profile_key, search_headers, search_profile,
+    search_timeout, worker_headers, worker_profile,
+};
+use golem_search::durability::{DurableSearch, ExtendedGuest};
+use golem_search::error::index_not_found;
+use golem_search::golem::search::core::{Guest, GuestSearchStream, SearchStream};
+use golem_search::golem::search::types::{
+    Doc, DocumentId, IndexName, IndexPage, PageRequest, Schema, SearchError, SearchHit,
+    SearchQuery, SearchResults,
+};
+use golem_search::pagination::index_page;
+use golem_search::LOGGING_STATE;
+use serde_json::json;
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+
+mod client;
+mod conversions;
+
+/// Search stream over the matches of a query. Streams starting at the first hit are read with
+/// the `searchAfter` pagination tokens of the hits, streams resumed at an offset are paged
+/// through with offsets, as tokens can't skip hits.
+struct AtlasSearchStream {
+    client: AtlasApi,
+    index_name: String,
+    query: SearchQuery,
+    offset: Cell<u32>,
+    /// Whether the stream is read with pagination tokens
+    token_stream: bool,
+    /// The pagination token of the last hit read, `None` before the first page
+    search_after: RefCell<Option<String>>,
+    finished: Cell<bool>,
+}
+
+impl AtlasSearchStream {
+    pub fn new(client: AtlasApi, index_name: String, query: SearchQuery) -> Self {
+        let offset = query_window(&query).map(|(offset, _)| offset).unwrap_or(0);
+        Self {
+            client,
+            index_name,
+            query,
+            offset: Cell::new(offset),
+            token_stream: offset == 0,
+            search_after: RefCell::new(None),
+            finished: Cell::new(false),
+        }
+    }
+
+    pub fn subscribe(&self) -> Pollable {
+        golem_rust::bindings::wasi::clocks::monotonic_clock::subscribe_duration(0)
+    }
+
+    /// Reads the next page of hits, and whether there may be more
+    fn next_page(&self, per_page: u32) -> Result<(Vec<SearchHit>, bool), SearchError> {
+        let mut query = self.query.clone();
+        query.page = None;
+        query.facets = vec![];
+
+        if self.token_stream {
+            query.offset = None;
+            let search_after = self.search_after.borrow().clone();
+            let pipeline = query_to_pipeline(
+                &query,
+                &AtlasComponent::search_index(search_profile(&self.query).as_deref()),
+                search_after.as_deref(),
+            )?;
+            let documents = self.client.aggregate(&self.index_name, pipeline)?;
+            let results = parse_documents(documents, &query);
+            let more = results.hits.len() as u32 >= per_page && results.last_token.is_some();
+            *self.search_after.borrow_mut() = results.last_token;
+            Ok((results.hits, more))
+        } else {
+            query.offset = Some(self.offset.get());
+            let results = AtlasComponent::search_page(&self.client, &self.index_name, &query)?;
+            let more = results.hits.len() as u32 >= per_page;
+            Ok((results.hits, more))
+        }
+    }
+}
+
+impl GuestSearchStream for AtlasSearchStream {
+    fn get_next(&self) -> Option<Vec<SearchHit>> {
+        if self.finished.get() {
+            return Some(vec![]);
+        }
+
+        let per_page = query_window(&self.query)
+            .map(|(_, per_page)| per_page)
+            .unwrap_or(0);
+
+        match self.next_page(per_page) {
+            Ok((hits, more)) => {
+                if !more || hits.is_empty() {
+                    self.finished.set(true);
+                }
+                self.offset.set(self.offset.get() + hits.len() as u32);
+                Some(hits)
+            }
+            Err(_) => {
+                self.finished.set(true);
+                Some(vec![])
+            }
+        }
+    }
+
+    fn blocking_get_next(&self) -> Vec<SearchHit> {
+        self.get_next().unwrap_or_default()
+    }
+}
+
+struct AtlasComponent;
+
+thread_local! {
+    /// The provider clients by configuration profile, reused across calls instead of being rebuilt
+    /// for each of them
+    static CLIENTS: RefCell<HashMap<Option<String>, AtlasApi>> = RefCell::new(HashMap::new());
+}
+
+impl AtlasComponent {
+    const DATA_API_URL_ENV_VAR: &'static str = "ATLAS_DATA_API_URL";
+    const DATA_API_KEY_ENV_VAR: &'static str = "ATLAS_DATA_API_KEY";
+    const DATA_SOURCE_ENV_VAR: &'static str = "ATLAS_DATA_SOURCE";
+    const DATABASE_ENV_VAR: &'static str = "ATLAS_DATABASE";
+    const SEARCH_INDEX_ENV_VAR: &'static str = "ATLAS_SEARCH_INDEX";
+    const ADMIN_URL_ENV_VAR: &'static str = "ATLAS_ADMIN_URL";
+    const PROJECT_ID_ENV_VAR: &'static str = "ATLAS_PROJECT_ID";
+    const CLIENT_ID_ENV_VAR: &'static str = "ATLAS_CLIENT_ID";
+    const CLIENT_SECRET_ENV_VAR: &'static str = "ATLAS_CLIENT_SECRET";
+
+    fn create_client() -> Result<AtlasApi, SearchError> {
+        Self::create_profile_client(worker_profile().as_deref())
+            .map(|client| client.with_headers(worker_headers()))
+    }
+
+    /// Returns the client of `profile` cached for this worker, creating it from the configuration
+    /// on first use
+    fn create_profile_client(profile: Option<&str>) -> Result<AtlasApi, SearchError> {
+        CLIENTS.with_borrow_mut(|clients| {
+            let profile = profile.map(str::to_string);
+            if let Some(client) = clients.get(&profile) {
+                return Ok(client.clone());
+            }
+
+            let client = Self::new_client(profile.as_deref())?;
+            clients.insert(profile, client.clone());
+            Ok(client)
+        })
+    }
+
+    /// Creates a client from the `ATLAS_DATA_API_URL` and `ATLAS_DATA_API_KEY` of the Data API,
+    /// the `ATLAS_DATA_SOURCE` cluster and the `ATLAS_DATABASE` holding a collection per index.
+    /// Managing the search indexes also needs the `ATLAS_PROJECT_ID` of the cluster and the
+    /// `ATLAS_CLIENT_ID` and `ATLAS_CLIENT_SECRET` of a service account, with the Administration
+    /// API at `ATLAS_ADMIN_URL`, `https://cloud.mongodb.com` by default.
+    fn new_client(profile: Option<&str>) -> Result<AtlasApi, SearchError> {
+        let required = |name: &str, what: &str| {
+            get_optional_config(profile_key(name, profile))
+                .filter(|value| !value.is_empty())
+                .ok_or_else(|| SearchError::Internal(format!("Missing Atlas {what}")))
+        };
+        let optional = |name: &str| {
+            get_optional_config(profile_key(name, profile)).filter(|value| !value.is_empty())
+        };
+
+        let data_api_url = required(Self::DATA_API_URL_ENV_VAR, "Data API URL")?;
+        let api_key = required(Self::DATA_API_KEY_ENV_VAR, "Data API key")?;
+        let data_source = required(Self::DATA_SOURCE_ENV_VAR, "data source")?;
+        let database = required(Self::DATABASE_ENV_VAR, "database")?;
+
+        let admin = match (
+            optional(Self::PROJECT_ID_ENV_VAR),
+            optional(Self::CLIENT_ID_ENV_VAR),
+            optional(Self::CLIENT_SECRET_ENV_VAR),
+        ) {
+            (Some(project_id), Some(client_id), Some(client_secret)) => Some(AdminCredentials {
+                url: optional(Self::ADMIN_URL_ENV_VAR)
+                    .unwrap_or_else(|| "https://cloud.mongodb.com".to_string()),
+                project_id,
+                client_id,
+                client_secret,
+            }),
+            _ => None,
+        };
+
+        Ok(AtlasApi::new(
+            data_api_url,
+            api_key,
+            data_source,
+            database,
+            admin,
+        ))
+    }
+
+    /// The name of the search index of the collections in `profile`, `default` by default
+    fn search_index(profile: Option<&str>) -> String {
+        get_config_with_default(profile_key(Self::SEARCH_INDEX_ENV_VAR, profile), "default")
+    }
+
+    fn search_page(
+        client: &AtlasApi,
+        index: &str,
+        query: &SearchQuery,
+    ) -> Result<SearchResults, SearchError> {
+        let (offset, per_page) = query_window(query)?;
+        let pipeline = query_to_pipeline(
+            query,
+            &Self::search_index(search_profile(query).as_deref()),
+            None,
+        )?;
+
+        let documents = client.aggregate(index, pipeline)?;
+        let results = parse_documents(documents, query);
+
+        Ok(SearchResults {
+            total: results.total,
+            page: Some(offset / per_page.max(1) + 1),
+            per_page: Some(per_page),
+            hits: results.hits,
+            facets: results.facets,
+            took_ms: None,
+            relaxation: None,
+            navigation: None,
+        })
+    }
+}
+
+impl Guest for AtlasComponent {
+    type SearchStream = AtlasSearchStream;
+
+    /// Creates the search index of the collection named after the index. Without a schema, the
+    /// fields of the documents are mapped dynamically.
+    fn create_index(name: IndexName, schema: Option<Schema>) -> Result<(), SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = Self::create_client()?;
+        client.create_search_index(
+            &name,
+            &Self::search_index(worker_profile().as_deref()),
+            &schema_to_definition(schema.as_ref()),
+        )
+    }
+
+    /// Deletes the search index of the collection and its documents. The Data API can't drop
+    /// collections, so the empty collection is kept.
+    fn delete_index(name: IndexName) -> Result<(), SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = Self::create_client()?;
+        client.delete_search_index(&name, &Self::search_index(worker_profile().as_deref()))?;
+        client.delete_documents(&name, json!({}))
+    }
+
+    fn list_indexes(page: PageRequest) -> Result<IndexPage, SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = Self::create_client()?;
+        let search_index = Self::search_index(worker_profile().as_deref());
+        let names = client
+            .list_search_indexes()?
+            .into_iter()
+            .filter(|index| index.name == search_index)
+            .map(|index| index.collection_name)
+            .collect();
+        index_page(names, &page)
+    }
+
+    fn upsert(index: IndexName, doc: Doc) -> Result<(), SearchError> {
+        Self::upsert_many(index, vec![doc])
+    }
+
+    /// Replaces the documents by deleting the existing documents of their ids before inserting
+    /// them, as the Data API has no bulk upsert
+    fn upsert_many(index: IndexName, docs: Vec<Doc>) -> Result<(), SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        if docs.is_empty() {
+            return Ok(());
+        }
+
+        let client = Self::create_client()?;
+        let ids = docs.iter().map(|doc| doc.id.clone()).collect::<Vec<_>>();
+        let docs = docs
+            .into_iter()
+            .map(doc_to_atlas)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        client.delete_documents(&index, ids_filter(&ids))?;
+        client.insert_documents(&index, docs)
+    }
+
+    fn delete(index: IndexName, id: DocumentId) -> Result<(), SearchError> {
+        Self::delete_many(index, vec![id])
+    }
+
+    fn delete_many(index: IndexName, ids: Vec<DocumentId>) -> Result<(), SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        if ids.is_empty() {
+            return Ok(());
+        }
+
+        let client = Self::create_client()?;
+        client.delete_documents(&index, ids_filter(&ids))
+    }
+
+    fn get(index: IndexName, id: DocumentId) -> Result<Option<Doc>, SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = Self::create_client()?;
+        Ok(client
+            .find_document(&index, id_filter(&id))?
+            .map(atlas_to_doc))
+    }
+
+    fn search(index: IndexName, query: SearchQuery) -> Result<SearchResults, SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = Self::create_profile_client(search_profile(&query).as_deref())?
+            .with_request_timeout(search_timeout(&query))
+            .with_headers(search_headers(&query));
+
+        Self::search_page(&client, &index, &query)
+    }
+
+    fn stream_search(index: IndexName, query: SearchQuery) -> Result<SearchStream, SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = Self::create_profile_client(search_profile(&query).as_deref())?
+            .with_request_timeout(search_timeout(&query))
+            .with_headers(search_headers(&query));
+        let stream = AtlasSearchStream::new(client, index, query);
+        Ok(SearchStream::new(stream))
+    }
+
+    fn get_schema(index: IndexName) -> Result<Schema, SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = Self::create_client()?;
+        let search_index =
+            client.get_search_index(&index, &Self::search_index(worker_profile().as_deref()))?;
+        let definition = search_index.latest_definition.ok_or_else(index_not_found)?;
+        Ok(definition_to_schema(&definition))
+    }
+
+    /// Replaces the field mappings of the search index with the schema, which Atlas applies by
+    /// rebuilding the index in the background
+    fn update_schema(index: IndexName, schema: Schema) -> Result<(), SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = Self::create_client()?;
+        client.update_search_index(
+            &index,
+            &Self::search_index(worker_profile().as_deref()),
+            &schema_to_definition(Some(&schema)),
+        )
+    }
+}
+
+impl ExtendedGuest for AtlasComponent {
+    fn unwrapped_stream(index: IndexName, query: SearchQuery) -> Self::SearchStream {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = Self::create_profile_client(search_profile(&query).as_deref())
+            .unwrap_or_else(|_| {
+                AtlasApi::new(
+                    "".to_string(),
+                    "".to_string(),
+                    "".to_string(),
+                    "".to_string(),
+                    None,
+                )
+            })
+            .with_headers(search_headers(&query));
+
+        AtlasSearchStream::new(client, index, query)
+    }
+
+    fn retry_query(original_query: &SearchQuery, partial_hits: &[SearchHit]) -> SearchQuery {
+        create_retry_query(original_query, partial_hits)
+    }
+
+    fn subscribe(stream: &Self::SearchStream) -> Pollable {
+        stream.subscribe()
+    }
+}
+
+type DurableAtlasComponent = DurableSearch<AtlasComponent>;
+
+golem_search::export_search!(DurableAtlasComponent with_types_in golem_search);
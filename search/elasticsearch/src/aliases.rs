@@ -0,0 +1,163 @@
+//! Aliases of indices.
+//!
+//! The component exports the `golem:search-elasticsearch/aliases` interface, managing the aliases
+//! of indices through the `_aliases` API. The operations of the search interface accept an alias
+//! wherever they take an index, so a worker can reindex into a new index and swap the alias to it
+//! atomically, blue/green style, without its readers and writers changing index names.
+
+use crate::ElasticsearchComponent;
+use golem_search::golem::search::types::{IndexName, SearchError};
+use golem_search::LOGGING_STATE;
+use serde_json::{json, Map, Value};
+
+#[allow(dead_code)]
+mod bindings {
+    wit_bindgen::generate!({
+        path: "wit",
+        world: "golem:search-elasticsearch/aliases-library@1.0.0",
+        with: {
+            "golem:search/types@1.0.0": golem_search::golem::search::types,
+        },
+        generate_all,
+    });
+}
+
+use bindings::exports::golem::search_elasticsearch::aliases::{AliasIndex, Guest};
+
+struct ElasticsearchAliases;
+
+impl Guest for ElasticsearchAliases {
+    fn put_alias(index: IndexName, alias: String, is_write_index: bool) -> Result<(), SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = ElasticsearchComponent::create_client()?;
+
+        client.update_aliases(&[json!({
+            "add": {
+                "index": index,
+                "alias": alias,
+                "is_write_index": is_write_index
+            }
+        })])
+    }
+
+    fn remove_alias(index: IndexName, alias: String) -> Result<(), SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = ElasticsearchComponent::create_client()?;
+
+        client.update_aliases(&[json!({
+            "remove": {
+                "index": index,
+                "alias": alias
+            }
+        })])
+    }
+
+    fn get_alias(alias: String) -> Result<Vec<AliasIndex>, SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = ElasticsearchComponent::create_client()?;
+
+        Ok(elasticsearch_alias_to_alias_indices(
+            &alias,
+            client.get_alias(&alias)?,
+        ))
+    }
+
+    fn swap_alias(alias: String, index: IndexName) -> Result<(), SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = ElasticsearchComponent::create_client()?;
+        let current = elasticsearch_alias_to_alias_indices(&alias, client.get_alias(&alias)?);
+
+        client.update_aliases(&swap_alias_actions(&alias, &index, &current))
+    }
+}
+
+/// The indices of an alias in the response of a get alias request, which lists the aliases of
+/// each index by alias name
+fn elasticsearch_alias_to_alias_indices(
+    alias: &str,
+    indices: Map<String, Value>,
+) -> Vec<AliasIndex> {
+    indices
+        .into_iter()
+        .filter_map(|(index, aliases)| {
+            let properties = aliases.get("aliases")?.get(alias)?;
+            Some(AliasIndex {
+                index,
+                is_write_index: properties
+                    .get("is_write_index")
+                    .and_then(Value::as_bool)
+                    .unwrap_or(false),
+            })
+        })
+        .collect()
+}
+
+/// Removes the alias from its current indices and adds it to the index as its write index
+fn swap_alias_actions(alias: &str, index: &str, current: &[AliasIndex]) -> Vec<Value> {
+    current
+        .iter()
+        .filter(|current| current.index != index)
+        .map(|current| {
+            json!({
+                "remove": {
+                    "index": current.index,
+                    "alias": alias
+                }
+            })
+        })
+        .chain([json!({
+            "add": {
+                "index": index,
+                "alias": alias,
+                "is_write_index": true
+            }
+        })])
+        .collect()
+}
+
+bindings::export!(ElasticsearchAliases with_types_in bindings);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn swaps_move_the_alias_to_the_new_index() {
+        let response = json!({
+            "products-v1": {
+                "aliases": {
+                    "products": { "is_write_index": true }
+                }
+            }
+        });
+        let current =
+            elasticsearch_alias_to_alias_indices("products", response.as_object().unwrap().clone());
+
+        assert_eq!(current.len(), 1);
+        assert!(current[0].is_write_index);
+
+        let actions = swap_alias_actions("products", "products-v2", &current);
+
+        assert_eq!(actions.len(), 2);
+        assert_eq!(actions[0]["remove"]["index"], "products-v1");
+        assert_eq!(actions[1]["add"]["index"], "products-v2");
+        assert_eq!(actions[1]["add"]["is_write_index"], true);
+    }
+
+    #[test]
+    fn swaps_to_an_index_of_the_alias_keep_it() {
+        let current = vec![AliasIndex {
+            index: "products-v2".to_string(),
+            is_write_index: false,
+        }];
+
+        let actions = swap_alias_actions("products", "products-v2", &current);
+
+        assert_eq!(actions.len(), 1);
+        assert!(actions[0].get("add").is_some());
+    }
+}
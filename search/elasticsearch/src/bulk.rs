@@ -0,0 +1,81 @@
+//! Bulk writes with per-document failures.
+//!
+//! The component exports the `golem:search-elasticsearch/bulk` interface, sending the same bulk
+//! requests as `upsert-many` and `delete-many` but returning the documents whose operation failed
+//! instead of failing the whole call, so that a worker retries only those documents.
+
+use crate::conversions::{build_bulk_delete_operations, build_bulk_operations};
+use crate::ElasticsearchComponent;
+use golem_search::bulk::{bulk_failures, BulkFailure as ElasticsearchBulkFailure};
+use golem_search::golem::search::types::{Doc, DocumentId, IndexName, SearchError};
+use golem_search::LOGGING_STATE;
+
+#[allow(dead_code)]
+mod bindings {
+    wit_bindgen::generate!({
+        path: "wit",
+        world: "golem:search-elasticsearch/bulk-library@1.0.0",
+        with: {
+            "golem:search/types@1.0.0": golem_search::golem::search::types,
+        },
+        generate_all,
+    });
+}
+
+use bindings::exports::golem::search_elasticsearch::bulk::{BulkFailure, Guest};
+
+struct ElasticsearchBulk;
+
+impl Guest for ElasticsearchBulk {
+    fn upsert_many(index: IndexName, docs: Vec<Doc>) -> Result<Vec<BulkFailure>, SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        if docs.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let client = ElasticsearchComponent::create_client()?;
+        let bulk_operations =
+            build_bulk_operations(&index, &docs, "index").map_err(SearchError::InvalidQuery)?;
+
+        let response = client.bulk_index(&bulk_operations)?;
+
+        Ok(bulk_failures(&response.items)
+            .into_iter()
+            .map(elasticsearch_failure_to_bulk_failure)
+            .collect())
+    }
+
+    fn delete_many(
+        index: IndexName,
+        ids: Vec<DocumentId>,
+    ) -> Result<Vec<BulkFailure>, SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        if ids.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let client = ElasticsearchComponent::create_client()?;
+        let bulk_operations =
+            build_bulk_delete_operations(&index, &ids).map_err(SearchError::InvalidQuery)?;
+
+        let response = client.bulk_index(&bulk_operations)?;
+
+        Ok(bulk_failures(&response.items)
+            .into_iter()
+            .map(elasticsearch_failure_to_bulk_failure)
+            .collect())
+    }
+}
+
+fn elasticsearch_failure_to_bulk_failure(failure: ElasticsearchBulkFailure) -> BulkFailure {
+    BulkFailure {
+        id: failure.id,
+        status: failure.status,
+        code: failure.code,
+        reason: failure.reason,
+    }
+}
+
+bindings::export!(ElasticsearchBulk with_types_in bindings);
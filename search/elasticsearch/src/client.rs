@@ -1,11 +1,25 @@
-use golem_search::error::{from_reqwest_error, internal_error, search_error_from_status};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use golem_search::correlation::{self, CORRELATION_ID_HEADER};
+use golem_search::error::{
+    from_reqwest_error, from_send_error, internal_error, search_error_from_failed_response,
+};
 use golem_search::golem::search::types::SearchError;
+use golem_search::logging::RedactedResponse;
+use golem_search::pagination::collect_page;
+use golem_search::retry::RetryPolicy;
 use log::trace;
 use reqwest::{Client, Method, RequestBuilder, Response};
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use serde_json::{Map, Value};
 use std::fmt::Debug;
+use std::time::Duration;
+
+/// Default `index.max_result_window`, the deepest hit a regular search can return
+pub const MAX_RESULT_WINDOW: u32 = 10_000;
+
+const SCROLL_PAGINATION_BATCH_SIZE: u32 = 1_000;
 
 /// The Elasticsearch Search API client for managing indices and performing search
 /// Based on the Elasticsearch REST API
@@ -16,6 +30,10 @@ pub struct ElasticsearchApi {
     api_key: Option<String>,
     username: Option<String>,
     password: Option<String>,
+    service_token: Option<String>,
+    retry_policy: RetryPolicy,
+    request_timeout: Option<Duration>,
+    headers: Vec<(String, String)>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -34,7 +52,7 @@ pub struct ElasticsearchMappings {
     pub dynamic: Option<bool>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ElasticsearchQuery {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub query: Option<Value>,
@@ -50,6 +68,21 @@ pub struct ElasticsearchQuery {
     pub aggs: Option<Value>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub _source: Option<Value>,
+    /// The point in time searched instead of the index, with its keep alive
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pit: Option<Value>,
+    /// The sort values of the last hit of the previous page
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub search_after: Option<Vec<Value>>,
+    /// The approximate nearest neighbor search of a query vector
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub knn: Option<Value>,
+    /// Fields computed at search time, usable in the query, sorts and aggregations
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub runtime_mappings: Option<Map<String, Value>>,
+    /// Fields returned alongside the source of the hits, such as runtime fields
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fields: Option<Vec<String>>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -60,6 +93,9 @@ pub struct ElasticsearchSearchResponse {
     pub hits: ElasticsearchHits,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub aggregations: Option<Value>,
+    /// The id of the point in time to search next, for searches of a point in time
+    #[serde(default)]
+    pub pit_id: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -89,6 +125,12 @@ pub struct ElasticsearchHit {
     pub source: Option<Value>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub highlight: Option<Value>,
+    /// The sort values of the hit, for sorted searches
+    #[serde(default)]
+    pub sort: Option<Vec<Value>>,
+    /// The values of the requested fields, which aren't part of the source
+    #[serde(default)]
+    pub fields: Option<Map<String, Value>>,
 }
 
 #[derive(Debug, Serialize)]
@@ -166,12 +208,103 @@ pub struct ElasticsearchScrollResponse {
     pub aggregations: Option<Value>,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct ElasticsearchPointInTimeResponse {
+    pub id: String,
+}
+
+/// The task of a request run in the background, such as a reindex or an update by query
+#[derive(Debug, Deserialize)]
+pub struct ElasticsearchTaskResponse {
+    pub task: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ElasticsearchTask {
+    pub completed: bool,
+    pub task: ElasticsearchTaskInfo,
+    /// The response of the request, once completed
+    #[serde(default)]
+    pub response: Option<ElasticsearchBulkByScrollResponse>,
+    /// The error which failed the request
+    #[serde(default)]
+    pub error: Option<Value>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ElasticsearchTaskInfo {
+    #[serde(default)]
+    pub status: Option<ElasticsearchBulkByScrollStatus>,
+}
+
+/// Progress of a request processing documents in scroll batches, such as a reindex or an update
+/// by query
+#[derive(Debug, Default, Deserialize)]
+pub struct ElasticsearchBulkByScrollStatus {
+    #[serde(default)]
+    pub total: u64,
+    #[serde(default)]
+    pub created: u64,
+    #[serde(default)]
+    pub updated: u64,
+    #[serde(default)]
+    pub deleted: u64,
+    /// Documents the script of an update left unchanged
+    #[serde(default)]
+    pub noops: u64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ElasticsearchBulkByScrollResponse {
+    #[serde(default)]
+    pub failures: Vec<Value>,
+}
+
+/// A composable index template, applying its settings and mappings to the indices created with a
+/// name matching one of its patterns
+#[derive(Debug, Serialize)]
+pub struct ElasticsearchIndexTemplate {
+    pub index_patterns: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub priority: Option<u32>,
+    pub template: ElasticsearchSettings,
+}
+
 #[derive(Debug, Serialize)]
 pub struct ScrollRequest {
     pub scroll: String,
     pub scroll_id: String,
 }
 
+/// The URL of the Elasticsearch endpoint of an Elastic Cloud deployment, given its Cloud ID: the
+/// deployment name and, after a colon, the base64 encoding of the host, optionally with a port,
+/// and the ids of the Elasticsearch and Kibana instances, separated by dollar signs
+pub fn cloud_id_to_url(cloud_id: &str) -> Result<String, SearchError> {
+    let invalid = || internal_error(format!("Invalid Elastic Cloud ID: {cloud_id}"));
+
+    let (_, encoded) = cloud_id.trim().split_once(':').ok_or_else(invalid)?;
+    let decoded = STANDARD
+        .decode(encoded)
+        .ok()
+        .and_then(|decoded| String::from_utf8(decoded).ok())
+        .ok_or_else(invalid)?;
+
+    let mut parts = decoded.split('$');
+    let host = parts
+        .next()
+        .filter(|host| !host.is_empty())
+        .ok_or_else(invalid)?;
+    let elasticsearch_id = parts
+        .next()
+        .filter(|id| !id.is_empty())
+        .ok_or_else(invalid)?;
+
+    Ok(match host.split_once(':') {
+        Some((host, port)) => format!("https://{elasticsearch_id}.{host}:{port}"),
+        None => format!("https://{elasticsearch_id}.{host}"),
+    })
+}
+
 impl ElasticsearchApi {
     pub fn new(
         base_url: String,
@@ -189,23 +322,73 @@ impl ElasticsearchApi {
             api_key,
             username,
             password,
+            service_token: None,
+            retry_policy: RetryPolicy::from_config(),
+            request_timeout: None,
+            headers: Vec::new(),
+        }
+    }
+
+    /// Authenticates every request with a service account token instead of the other credentials
+    pub fn with_service_token(mut self, service_token: Option<String>) -> Self {
+        self.service_token = service_token;
+        self
+    }
+
+    /// Applies a timeout to every request sent by this client
+    pub fn with_request_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.request_timeout = timeout;
+        self
+    }
+
+    /// Adds custom headers to every request sent by this client
+    pub fn with_headers(mut self, headers: Vec<(String, String)>) -> Self {
+        self.headers = headers;
+        self
+    }
+
+    /// Authenticates a request with the service token, the API key or the basic credentials of
+    /// the client, in this order of preference
+    fn authenticate(&self, builder: RequestBuilder) -> RequestBuilder {
+        if let Some(service_token) = &self.service_token {
+            builder.bearer_auth(service_token)
+        } else if let Some(api_key) = &self.api_key {
+            builder.header("Authorization", format!("ApiKey {api_key}"))
+        } else if let (Some(username), Some(password)) = (&self.username, &self.password) {
+            builder.basic_auth(username, Some(password))
+        } else {
+            builder
         }
     }
 
     fn create_request(&self, method: Method, url: &str) -> RequestBuilder {
+        self.create_request_with_content_type(method, url, "application/json")
+    }
+
+    fn create_request_with_content_type(
+        &self,
+        method: Method,
+        url: &str,
+        content_type: &str,
+    ) -> RequestBuilder {
         let mut builder = self
             .client
             .request(method, url)
-            .header("Content-Type", "application/json");
+            .header(CORRELATION_ID_HEADER, correlation::current())
+            // Elasticsearch reports the X-Opaque-Id header in its slow logs and tasks
+            .header("X-Opaque-Id", correlation::current())
+            .header("Content-Type", content_type);
 
-        // Add authentication
-        if let Some(api_key) = &self.api_key {
-            builder = builder.header("Authorization", format!("ApiKey {api_key}"));
-        } else if let (Some(username), Some(password)) = (&self.username, &self.password) {
-            builder = builder.basic_auth(username, Some(password));
+        if let Some(timeout) = self.request_timeout {
+            builder = builder.timeout(timeout);
         }
 
-        builder
+        // Custom headers never carry reserved names, so they cannot clash with the ones below
+        for (name, value) in &self.headers {
+            builder = builder.header(name, value);
+        }
+
+        self.authenticate(builder)
     }
 
     pub fn create_index(
@@ -217,20 +400,23 @@ impl ElasticsearchApi {
 
         let url = format!("{}/{}", self.base_url, index_name);
 
-        let mut request = self.create_request(Method::PUT, &url);
+        let response = self
+            .retry_policy
+            .execute(|| {
+                let mut request = self.create_request(Method::PUT, &url);
 
-        if let Some(settings) = settings {
-            request = request.json(&settings);
-        }
+                if let Some(settings) = &settings {
+                    request = request.json(settings);
+                }
 
-        let response = request
-            .send()
-            .map_err(|e| internal_error(format!("Failed to create index: {e}")))?;
+                request.send()
+            })
+            .map_err(|e| from_send_error("Failed to create index", e))?;
 
         if response.status().is_success() {
             Ok(())
         } else {
-            Err(search_error_from_status(response.status()))
+            Err(search_error_from_failed_response(response))
         }
     }
 
@@ -240,14 +426,14 @@ impl ElasticsearchApi {
         let url = format!("{}/{}", self.base_url, index_name);
 
         let response = self
-            .create_request(Method::DELETE, &url)
-            .send()
-            .map_err(|e| internal_error(format!("Failed to delete index: {e}")))?;
+            .retry_policy
+            .execute(|| self.create_request(Method::DELETE, &url).send())
+            .map_err(|e| from_send_error("Failed to delete index", e))?;
 
         if response.status().is_success() {
             Ok(())
         } else {
-            Err(search_error_from_status(response.status()))
+            Err(search_error_from_failed_response(response))
         }
     }
 
@@ -257,9 +443,9 @@ impl ElasticsearchApi {
         let url = format!("{}/_cat/indices?format=json", self.base_url);
 
         let response = self
-            .create_request(Method::GET, &url)
-            .send()
-            .map_err(|e| internal_error(format!("Failed to list indices: {e}")))?;
+            .retry_policy
+            .execute(|| self.create_request(Method::GET, &url).send())
+            .map_err(|e| from_send_error("Failed to list indices", e))?;
 
         parse_response(response)
     }
@@ -275,16 +461,15 @@ impl ElasticsearchApi {
         let url = format!("{}/{}/_doc/{}", self.base_url, index_name, id);
 
         let response = self
-            .create_request(Method::PUT, &url)
-            .json(document)
-            .send()
-            .map_err(|e| internal_error(format!("Failed to index document: {e}")))?;
+            .retry_policy
+            .execute(|| self.create_request(Method::PUT, &url).json(document).send())
+            .map_err(|e| from_send_error("Failed to index document", e))?;
 
         if response.status().is_success() {
             self.refresh_index(index_name)?;
             Ok(())
         } else {
-            Err(search_error_from_status(response.status()))
+            Err(search_error_from_failed_response(response))
         }
     }
 
@@ -293,23 +478,14 @@ impl ElasticsearchApi {
 
         let url = format!("{}/_bulk", self.base_url);
 
-        // Building request without create_request to avoid Content-Type conflicts
-        let mut builder = self
-            .client
-            .post(&url)
-            .header("Content-Type", "application/x-ndjson")
-            .body(operations.to_string());
-
-        // Add authentication
-        if let Some(api_key) = &self.api_key {
-            builder = builder.header("Authorization", format!("ApiKey {api_key}"));
-        } else if let (Some(username), Some(password)) = (&self.username, &self.password) {
-            builder = builder.basic_auth(username, Some(password));
-        }
-
-        let response = builder
-            .send()
-            .map_err(|e| internal_error(format!("Failed to perform bulk operation: {e}")))?;
+        let response = self
+            .retry_policy
+            .execute(|| {
+                self.create_request_with_content_type(Method::POST, &url, "application/x-ndjson")
+                    .body(operations.to_string())
+                    .send()
+            })
+            .map_err(|e| from_send_error("Failed to perform bulk operation", e))?;
 
         parse_response(response)
     }
@@ -320,14 +496,14 @@ impl ElasticsearchApi {
         let url = format!("{}/{}/_doc/{}", self.base_url, index_name, id);
 
         let response = self
-            .create_request(Method::DELETE, &url)
-            .send()
-            .map_err(|e| internal_error(format!("Failed to delete document: {e}")))?;
+            .retry_policy
+            .execute(|| self.create_request(Method::DELETE, &url).send())
+            .map_err(|e| from_send_error("Failed to delete document", e))?;
 
         if response.status().is_success() {
             Ok(())
         } else {
-            Err(search_error_from_status(response.status()))
+            Err(search_error_from_failed_response(response))
         }
     }
 
@@ -337,9 +513,9 @@ impl ElasticsearchApi {
         let url = format!("{}/{}/_doc/{}", self.base_url, index_name, id);
 
         let response = self
-            .create_request(Method::GET, &url)
-            .send()
-            .map_err(|e| internal_error(format!("Failed to get document: {e}")))?;
+            .retry_policy
+            .execute(|| self.create_request(Method::GET, &url).send())
+            .map_err(|e| from_send_error("Failed to get document", e))?;
 
         if response.status() == 404 {
             Ok(None)
@@ -352,7 +528,7 @@ impl ElasticsearchApi {
                 Ok(None)
             }
         } else {
-            Err(search_error_from_status(response.status()))
+            Err(search_error_from_failed_response(response))
         }
     }
 
@@ -366,10 +542,9 @@ impl ElasticsearchApi {
         let url = format!("{}/{}/_search", self.base_url, index_name);
 
         let response = self
-            .create_request(Method::POST, &url)
-            .json(query)
-            .send()
-            .map_err(|e| internal_error(format!("Failed to search: {e}")))?;
+            .retry_policy
+            .execute(|| self.create_request(Method::POST, &url).json(query).send())
+            .map_err(|e| from_send_error("Failed to search", e))?;
 
         parse_response(response)
     }
@@ -388,14 +563,54 @@ impl ElasticsearchApi {
         );
 
         let response = self
-            .create_request(Method::POST, &url)
-            .json(query)
-            .send()
-            .map_err(|e| internal_error(format!("Failed to search with scroll: {e}")))?;
+            .retry_policy
+            .execute(|| self.create_request(Method::POST, &url).json(query).send())
+            .map_err(|e| from_send_error("Failed to search with scroll", e))?;
 
         parse_response(response)
     }
 
+    /// Returns the page selected by `from` and `size` of `query` by iterating over a scroll,
+    /// which is not limited by the index's `max_result_window` like a regular search is
+    pub fn search_with_scroll_pagination(
+        &self,
+        index_name: &str,
+        query: &ElasticsearchQuery,
+    ) -> Result<ElasticsearchSearchResponse, SearchError> {
+        let from = query.from.unwrap_or(0) as usize;
+        let size = query.size.unwrap_or(10) as usize;
+        trace!("Paginating index {index_name} with scroll, from: {from}, size: {size}");
+
+        let mut scroll_query = query.clone();
+        scroll_query.from = None;
+        scroll_query.size = Some(SCROLL_PAGINATION_BATCH_SIZE);
+
+        let first = self.search_with_scroll(index_name, &scroll_query, "1m")?;
+        let mut scroll_id = first.scroll_id;
+        let result = collect_page(from, size, first.hits.hits, || {
+            let next = self.scroll(&scroll_id, "1m")?;
+            scroll_id = next.scroll_id;
+            Ok(next.hits.hits)
+        });
+
+        if let Err(error) = self.clear_scroll(&scroll_id) {
+            trace!("Failed to clear pagination scroll: {error:?}");
+        }
+        let hits = result?;
+
+        Ok(ElasticsearchSearchResponse {
+            took: first.took,
+            timed_out: first.timed_out,
+            hits: ElasticsearchHits {
+                total: first.hits.total,
+                max_score: first.hits.max_score,
+                hits,
+            },
+            aggregations: first.aggregations,
+            pit_id: None,
+        })
+    }
+
     pub fn scroll(
         &self,
         scroll_id: &str,
@@ -411,10 +626,13 @@ impl ElasticsearchApi {
         };
 
         let response = self
-            .create_request(Method::POST, &url)
-            .json(&scroll_request)
-            .send()
-            .map_err(|e| internal_error(format!("Failed to continue scroll: {e}")))?;
+            .retry_policy
+            .execute(|| {
+                self.create_request(Method::POST, &url)
+                    .json(&scroll_request)
+                    .send()
+            })
+            .map_err(|e| from_send_error("Failed to continue scroll", e))?;
 
         parse_response(response)
     }
@@ -429,10 +647,13 @@ impl ElasticsearchApi {
         });
 
         let response = self
-            .create_request(Method::DELETE, &url)
-            .json(&clear_request)
-            .send()
-            .map_err(|e| internal_error(format!("Failed to clear scroll: {e}")))?;
+            .retry_policy
+            .execute(|| {
+                self.create_request(Method::DELETE, &url)
+                    .json(&clear_request)
+                    .send()
+            })
+            .map_err(|e| from_send_error("Failed to clear scroll", e))?;
 
         if response.status().is_success() {
             Ok(())
@@ -446,15 +667,300 @@ impl ElasticsearchApi {
         }
     }
 
+    /// Opens a point in time of the index, a view of its documents as they are now which stays
+    /// consistent across the searches made within `keep_alive` of each other
+    pub fn open_point_in_time(
+        &self,
+        index_name: &str,
+        keep_alive: &str,
+    ) -> Result<String, SearchError> {
+        trace!("Opening point in time of index {index_name}, keep alive: {keep_alive}");
+
+        let url = format!(
+            "{}/{}/_pit?keep_alive={}",
+            self.base_url, index_name, keep_alive
+        );
+
+        let response = self
+            .retry_policy
+            .execute(|| self.create_request(Method::POST, &url).send())
+            .map_err(|e| from_send_error("Failed to open point in time", e))?;
+
+        parse_response::<ElasticsearchPointInTimeResponse>(response).map(|pit| pit.id)
+    }
+
+    /// Searches the point in time of the `pit` of the query
+    pub fn search_point_in_time(
+        &self,
+        query: &ElasticsearchQuery,
+    ) -> Result<ElasticsearchSearchResponse, SearchError> {
+        trace!("Searching point in time with query: {query:?}");
+
+        let url = format!("{}/_search", self.base_url);
+
+        let response = self
+            .retry_policy
+            .execute(|| self.create_request(Method::POST, &url).json(query).send())
+            .map_err(|e| from_send_error("Failed to search point in time", e))?;
+
+        parse_response(response)
+    }
+
+    pub fn close_point_in_time(&self, pit_id: &str) -> Result<(), SearchError> {
+        trace!("Closing point in time");
+
+        let url = format!("{}/_pit", self.base_url);
+
+        let close_request = serde_json::json!({
+            "id": pit_id
+        });
+
+        let response = self
+            .retry_policy
+            .execute(|| {
+                self.create_request(Method::DELETE, &url)
+                    .json(&close_request)
+                    .send()
+            })
+            .map_err(|e| from_send_error("Failed to close point in time", e))?;
+
+        if !response.status().is_success() {
+            trace!("Failed to close point in time: {}", response.status());
+        }
+        Ok(())
+    }
+
+    pub fn put_index_template(
+        &self,
+        name: &str,
+        template: &ElasticsearchIndexTemplate,
+    ) -> Result<(), SearchError> {
+        trace!("Putting index template: {name}");
+
+        let url = format!("{}/_index_template/{}", self.base_url, name);
+
+        let response = self
+            .retry_policy
+            .execute(|| self.create_request(Method::PUT, &url).json(template).send())
+            .map_err(|e| from_send_error("Failed to put index template", e))?;
+
+        if response.status().is_success() {
+            Ok(())
+        } else {
+            Err(search_error_from_failed_response(response))
+        }
+    }
+
+    pub fn delete_index_template(&self, name: &str) -> Result<(), SearchError> {
+        trace!("Deleting index template: {name}");
+
+        let url = format!("{}/_index_template/{}", self.base_url, name);
+
+        let response = self
+            .retry_policy
+            .execute(|| self.create_request(Method::DELETE, &url).send())
+            .map_err(|e| from_send_error("Failed to delete index template", e))?;
+
+        if response.status().is_success() {
+            Ok(())
+        } else {
+            Err(search_error_from_failed_response(response))
+        }
+    }
+
+    /// Starts copying the documents of an index to another in the background, returning the id of
+    /// the task
+    pub fn reindex(&self, request: &Value) -> Result<String, SearchError> {
+        trace!("Reindexing: {request}");
+
+        let url = format!("{}/_reindex?wait_for_completion=false", self.base_url);
+
+        let response = self
+            .retry_policy
+            .execute(|| self.create_request(Method::POST, &url).json(request).send())
+            .map_err(|e| from_send_error("Failed to reindex", e))?;
+
+        parse_response::<ElasticsearchTaskResponse>(response).map(|task| task.task)
+    }
+
+    /// Starts updating the documents of an index matching the query of the request in the
+    /// background, returning the id of the task
+    pub fn update_by_query(
+        &self,
+        index_name: &str,
+        request: &Value,
+        proceed_on_conflicts: bool,
+    ) -> Result<String, SearchError> {
+        trace!("Updating documents of index {index_name} by query: {request}");
+
+        let mut url = format!(
+            "{}/{}/_update_by_query?wait_for_completion=false",
+            self.base_url, index_name
+        );
+        if proceed_on_conflicts {
+            url.push_str("&conflicts=proceed");
+        }
+
+        let response = self
+            .retry_policy
+            .execute(|| self.create_request(Method::POST, &url).json(request).send())
+            .map_err(|e| from_send_error("Failed to update by query", e))?;
+
+        parse_response::<ElasticsearchTaskResponse>(response).map(|task| task.task)
+    }
+
+    pub fn get_task(&self, task_id: &str) -> Result<ElasticsearchTask, SearchError> {
+        trace!("Getting task: {task_id}");
+
+        let url = format!("{}/_tasks/{}", self.base_url, task_id);
+
+        let response = self
+            .retry_policy
+            .execute(|| self.create_request(Method::GET, &url).send())
+            .map_err(|e| from_send_error("Failed to get task", e))?;
+
+        parse_response(response)
+    }
+
+    /// Applies the `add` and `remove` actions of aliases atomically
+    pub fn update_aliases(&self, actions: &[Value]) -> Result<(), SearchError> {
+        trace!("Updating aliases: {actions:?}");
+
+        let url = format!("{}/_aliases", self.base_url);
+        let request = serde_json::json!({ "actions": actions });
+
+        let response = self
+            .retry_policy
+            .execute(|| {
+                self.create_request(Method::POST, &url)
+                    .json(&request)
+                    .send()
+            })
+            .map_err(|e| from_send_error("Failed to update aliases", e))?;
+
+        if response.status().is_success() {
+            Ok(())
+        } else {
+            Err(search_error_from_failed_response(response))
+        }
+    }
+
+    /// The indices of an alias with the aliases' properties, keyed by index, empty when the alias
+    /// doesn't exist
+    pub fn get_alias(&self, alias: &str) -> Result<Map<String, Value>, SearchError> {
+        trace!("Getting alias: {alias}");
+
+        let url = format!("{}/_alias/{}", self.base_url, alias);
+
+        let response = self
+            .retry_policy
+            .execute(|| self.create_request(Method::GET, &url).send())
+            .map_err(|e| from_send_error("Failed to get alias", e))?;
+
+        if response.status() == 404 {
+            Ok(Map::new())
+        } else {
+            parse_response(response)
+        }
+    }
+
+    /// Finds a document by id through a search, which unlike a get works on aliases of several
+    /// indices
+    pub fn search_document(
+        &self,
+        index_name: &str,
+        id: &str,
+    ) -> Result<Option<Value>, SearchError> {
+        trace!("Searching document {id} in index: {index_name}");
+
+        let url = format!("{}/{}/_search", self.base_url, index_name);
+        let request = serde_json::json!({
+            "query": { "ids": { "values": [id] } },
+            "size": 1
+        });
+
+        let response = self
+            .retry_policy
+            .execute(|| {
+                self.create_request(Method::POST, &url)
+                    .json(&request)
+                    .send()
+            })
+            .map_err(|e| from_send_error("Failed to search document", e))?;
+
+        let response: ElasticsearchSearchResponse = parse_response(response)?;
+        Ok(response
+            .hits
+            .hits
+            .into_iter()
+            .next()
+            .and_then(|hit| hit.source))
+    }
+
+    pub fn put_lifecycle_policy(&self, name: &str, policy: &Value) -> Result<(), SearchError> {
+        trace!("Putting lifecycle policy: {name}");
+
+        let url = format!("{}/_ilm/policy/{}", self.base_url, name);
+
+        let response = self
+            .retry_policy
+            .execute(|| self.create_request(Method::PUT, &url).json(policy).send())
+            .map_err(|e| from_send_error("Failed to put lifecycle policy", e))?;
+
+        if response.status().is_success() {
+            Ok(())
+        } else {
+            Err(search_error_from_failed_response(response))
+        }
+    }
+
+    pub fn delete_lifecycle_policy(&self, name: &str) -> Result<(), SearchError> {
+        trace!("Deleting lifecycle policy: {name}");
+
+        let url = format!("{}/_ilm/policy/{}", self.base_url, name);
+
+        let response = self
+            .retry_policy
+            .execute(|| self.create_request(Method::DELETE, &url).send())
+            .map_err(|e| from_send_error("Failed to delete lifecycle policy", e))?;
+
+        if response.status().is_success() {
+            Ok(())
+        } else {
+            Err(search_error_from_failed_response(response))
+        }
+    }
+
+    pub fn put_index_settings(
+        &self,
+        index_name: &str,
+        settings: &Value,
+    ) -> Result<(), SearchError> {
+        trace!("Putting settings for index: {index_name}");
+
+        let url = format!("{}/{}/_settings", self.base_url, index_name);
+
+        let response = self
+            .retry_policy
+            .execute(|| self.create_request(Method::PUT, &url).json(settings).send())
+            .map_err(|e| from_send_error("Failed to put index settings", e))?;
+
+        if response.status().is_success() {
+            Ok(())
+        } else {
+            Err(search_error_from_failed_response(response))
+        }
+    }
+
     pub fn get_mappings(&self, index_name: &str) -> Result<Value, SearchError> {
         trace!("Getting mappings for index: {index_name}");
 
         let url = format!("{}/{}/_mapping", self.base_url, index_name);
 
         let response = self
-            .create_request(Method::GET, &url)
-            .send()
-            .map_err(|e| internal_error(format!("Failed to get mappings: {e}")))?;
+            .retry_policy
+            .execute(|| self.create_request(Method::GET, &url).send())
+            .map_err(|e| from_send_error("Failed to get mappings", e))?;
 
         parse_response(response)
     }
@@ -469,15 +975,14 @@ impl ElasticsearchApi {
         let url = format!("{}/{}/_mapping", self.base_url, index_name);
 
         let response = self
-            .create_request(Method::PUT, &url)
-            .json(mappings)
-            .send()
-            .map_err(|e| internal_error(format!("Failed to put mappings: {e}")))?;
+            .retry_policy
+            .execute(|| self.create_request(Method::PUT, &url).json(mappings).send())
+            .map_err(|e| from_send_error("Failed to put mappings", e))?;
 
         if response.status().is_success() {
             Ok(())
         } else {
-            Err(search_error_from_status(response.status()))
+            Err(search_error_from_failed_response(response))
         }
     }
 
@@ -487,9 +992,9 @@ impl ElasticsearchApi {
         let url = format!("{}/{}/_refresh", self.base_url, index_name);
 
         let response = self
-            .create_request(Method::POST, &url)
-            .send()
-            .map_err(|e| internal_error(format!("Failed to refresh index: {e}")))?;
+            .retry_policy
+            .execute(|| self.create_request(Method::POST, &url).send())
+            .map_err(|e| from_send_error("Failed to refresh index", e))?;
 
         if response.status().is_success() {
             Ok(())
@@ -503,7 +1008,10 @@ impl ElasticsearchApi {
 fn parse_response<T: DeserializeOwned + Debug>(response: Response) -> Result<T, SearchError> {
     let status = response.status();
 
-    trace!("Received response from Elasticsearch API: {response:?}");
+    trace!(
+        "Received response from Elasticsearch API: {:?}",
+        RedactedResponse(&response)
+    );
 
     if status.is_success() {
         let body = response
@@ -514,12 +1022,44 @@ fn parse_response<T: DeserializeOwned + Debug>(response: Response) -> Result<T,
 
         Ok(body)
     } else {
-        let error_body = response
-            .text()
-            .map_err(|err| from_reqwest_error("Failed to receive error response body", err))?;
+        let error = search_error_from_failed_response(response);
 
-        trace!("Received {status} response from Elasticsearch API: {error_body:?}");
+        trace!("Received {status} response from Elasticsearch API: {error:?}");
 
-        Err(search_error_from_status(status))
+        Err(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cloud_ids_are_decoded_to_the_elasticsearch_endpoint() {
+        let encoded = STANDARD.encode("us-central1.gcp.cloud.es.io$abc123$def456");
+
+        assert_eq!(
+            cloud_id_to_url(&format!("my-deployment:{encoded}")).unwrap(),
+            "https://abc123.us-central1.gcp.cloud.es.io"
+        );
+    }
+
+    #[test]
+    fn cloud_ids_keep_the_port_of_the_host() {
+        let encoded = STANDARD.encode("eu-west-1.aws.found.io:9243$abc123$def456");
+
+        assert_eq!(
+            cloud_id_to_url(&format!("my-deployment:{encoded}")).unwrap(),
+            "https://abc123.eu-west-1.aws.found.io:9243"
+        );
+    }
+
+    #[test]
+    fn malformed_cloud_ids_are_rejected() {
+        assert!(cloud_id_to_url("no-separator").is_err());
+        assert!(cloud_id_to_url("my-deployment:not base64").is_err());
+        assert!(
+            cloud_id_to_url(&format!("my-deployment:{}", STANDARD.encode("host-only"))).is_err()
+        );
     }
 }
@@ -2,10 +2,42 @@ use crate::client::{
     ElasticsearchHit, ElasticsearchMappings, ElasticsearchQuery, ElasticsearchSearchResponse,
     ElasticsearchSettings,
 };
+use golem_search::config::search_filters_match_any;
+use golem_search::error::invalid_query;
 use golem_search::golem::search::types::{
-    Doc, FieldType, Schema, SchemaField, SearchHit, SearchQuery, SearchResults,
+    Doc, FieldType, Schema, SchemaField, SearchError, SearchHit, SearchQuery, SearchResults,
 };
+use log::warn;
+use serde::{Deserialize, Serialize};
 use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+
+/// Document field holding the vector embedding of a document, mapped as a `dense_vector` in
+/// indexes created with vector dimensions
+pub const VECTOR_FIELD: &str = "vector";
+
+/// How the fields of a schema are mapped beyond their type
+#[derive(Debug, Default)]
+pub struct MappingOptions {
+    /// Dimensions of the embeddings of the documents, mapping their vectors as a `dense_vector`
+    /// indexed for kNN search
+    pub vector_dimensions: Option<u32>,
+    /// Analysis of the fields, by field name
+    pub field_analysis: HashMap<String, FieldAnalysis>,
+}
+
+/// The analysis of a field, naming built-in or index-defined analyzers and normalizers
+#[derive(Debug, Default, Deserialize)]
+pub struct FieldAnalysis {
+    /// Analyzer of a text field at index time, and at search time without a `search_analyzer`
+    pub analyzer: Option<String>,
+    pub search_analyzer: Option<String>,
+    /// Normalizer of a keyword field, or of the keyword sub-field of a text field
+    pub normalizer: Option<String>,
+}
+
+/// Most candidates Elasticsearch accepts to consider per shard for a vector query
+const MAX_KNN_CANDIDATES: u32 = 10_000;
 
 pub fn doc_to_elasticsearch_document(doc: Doc) -> Result<Value, String> {
     // Validate document ID length (Elasticsearch limit is 512 bytes)
@@ -41,6 +73,7 @@ pub fn elasticsearch_document_to_doc(id: String, source: Value) -> Doc {
 }
 
 pub fn search_query_to_elasticsearch_query(query: SearchQuery) -> ElasticsearchQuery {
+    let match_any = search_filters_match_any(&query);
     let mut es_query = ElasticsearchQuery {
         query: None,
         from: query.offset,
@@ -49,8 +82,18 @@ pub fn search_query_to_elasticsearch_query(query: SearchQuery) -> ElasticsearchQ
         highlight: None,
         aggs: None,
         _source: None,
+        pit: None,
+        search_after: None,
+        knn: None,
+        runtime_mappings: None,
+        fields: None,
     };
 
+    if let Some(runtime_mappings) = query_runtime_mappings(&query) {
+        es_query.fields = Some(runtime_mappings.keys().cloned().collect());
+        es_query.runtime_mappings = Some(runtime_mappings);
+    }
+
     let mut bool_query = json!({
         "bool": {
             "must": [],
@@ -120,6 +163,16 @@ pub fn search_query_to_elasticsearch_query(query: SearchQuery) -> ElasticsearchQ
         }
     }
 
+    if match_any && !bool_query["bool"]["filter"].as_array().unwrap().is_empty() {
+        let filters = bool_query["bool"]["filter"].take();
+        bool_query["bool"]["filter"] = json!([{
+            "bool": {
+                "should": filters,
+                "minimum_should_match": 1
+            }
+        }]);
+    }
+
     if !bool_query["bool"]["must"].as_array().unwrap().is_empty()
         || !bool_query["bool"]["filter"].as_array().unwrap().is_empty()
     {
@@ -185,12 +238,17 @@ pub fn search_query_to_elasticsearch_query(query: SearchQuery) -> ElasticsearchQ
     if !query.facets.is_empty() {
         let mut aggs = json!({});
         for facet in query.facets {
-            aggs[&facet] = json!({
-                "terms": {
-                    "field": format!("{}.keyword", facet),
-                    "size": 10
-                }
-            });
+            if let Ok(structured_facet) = serde_json::from_str::<StructuredFacet>(&facet) {
+                let (name, aggregation) = structured_facet_to_aggregation(structured_facet);
+                aggs[name] = aggregation;
+            } else {
+                aggs[&facet] = json!({
+                    "terms": {
+                        "field": format!("{}.keyword", facet),
+                        "size": 10
+                    }
+                });
+            }
         }
         es_query.aggs = Some(aggs);
     }
@@ -222,6 +280,213 @@ pub fn search_query_to_elasticsearch_query(query: SearchQuery) -> ElasticsearchQ
     es_query
 }
 
+/// The runtime fields of the query, given as the `runtime_mappings` object of its provider
+/// parameters, in the syntax of the search API
+fn query_runtime_mappings(query: &SearchQuery) -> Option<Map<String, Value>> {
+    let params = query.config.as_ref()?.provider_params.as_deref()?;
+    match serde_json::from_str::<Value>(params)
+        .ok()?
+        .get_mut("runtime_mappings")?
+        .take()
+    {
+        Value::Object(runtime_mappings) => Some(runtime_mappings),
+        other => {
+            warn!("Ignoring runtime mappings which aren't an object: {other}");
+            None
+        }
+    }
+}
+
+/// The value of a field returned in the `fields` of a hit, which are always arrays, unwrapped
+/// when single
+fn field_values(values: Value) -> Value {
+    match values {
+        Value::Array(mut values) if values.len() == 1 => values.remove(0),
+        values => values,
+    }
+}
+
+/// The kNN search of the query vector, given as `{"vector": [...]}` in the provider parameters of
+/// the query, along with the optional number `k` of neighbors, by default the `depth` of the
+/// requested page, and the `num_candidates` considered per shard
+pub fn query_to_knn(query: &SearchQuery, depth: u32) -> Result<Option<Value>, SearchError> {
+    let Some(params) = query
+        .config
+        .as_ref()
+        .and_then(|config| config.provider_params.as_deref())
+    else {
+        return Ok(None);
+    };
+    let params = serde_json::from_str::<Map<String, Value>>(params)
+        .map_err(|e| invalid_query(format!("Invalid provider parameters: {e}")))?;
+
+    let Some(vector) = params.get(VECTOR_FIELD) else {
+        return Ok(None);
+    };
+    let vector = vector
+        .as_array()
+        .filter(|values| !values.is_empty() && values.iter().all(Value::is_number))
+        .ok_or_else(|| invalid_query("The query `vector` must be a non-empty array of numbers"))?;
+
+    let positive = |name: &str| match params.get(name) {
+        None => Ok(None),
+        Some(value) => value
+            .as_u64()
+            .filter(|value| *value > 0)
+            .map(|value| Some(value.min(MAX_KNN_CANDIDATES as u64) as u32))
+            .ok_or_else(|| invalid_query(format!("The query `{name}` must be a positive integer"))),
+    };
+    let k = positive("k")?.unwrap_or(depth.clamp(1, MAX_KNN_CANDIDATES));
+    let num_candidates = positive("num_candidates")?
+        .unwrap_or_else(|| k.saturating_mul(10).clamp(100, MAX_KNN_CANDIDATES))
+        .max(k);
+
+    Ok(Some(json!({
+        "field": VECTOR_FIELD,
+        "query_vector": vector,
+        "k": k,
+        "num_candidates": num_candidates
+    })))
+}
+
+/// Adds a kNN search to a query. The filters of the query restrict the neighbors, and the text
+/// query, if any, is kept to rank the hits by the sum of both scores. Without a text query, only
+/// the neighbors are returned.
+pub fn with_knn(mut es_query: ElasticsearchQuery, mut knn: Value) -> ElasticsearchQuery {
+    let mut query = es_query.query.take();
+
+    if let Some(filter) = query
+        .as_mut()
+        .and_then(|query| query.pointer_mut("/bool/filter"))
+        .filter(|filter| filter.as_array().is_some_and(|filter| !filter.is_empty()))
+    {
+        knn["filter"] = filter.clone();
+    }
+
+    let has_text_query = query
+        .as_ref()
+        .and_then(|query| query.pointer("/bool/must"))
+        .and_then(Value::as_array)
+        .is_some_and(|must| !must.is_empty());
+    if has_text_query {
+        es_query.query = query;
+    }
+
+    es_query.knn = Some(knn);
+    es_query
+}
+
+/// A facet given as a JSON object instead of a field name, such as
+/// `{"name": "price_ranges", "range": {"field": "price", "ranges": [{"to": 100}, {"from": 100}]}}`.
+/// Its results are returned under its name in the facets of the search results, in the format of
+/// the matching Elasticsearch aggregation.
+#[derive(Debug, Deserialize)]
+struct StructuredFacet {
+    /// The key of the facet in the results, `{field}_{kind}` when not given
+    name: Option<String>,
+    #[serde(flatten)]
+    kind: StructuredFacetKind,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum StructuredFacetKind {
+    /// Counts of the documents in each of the ranges of a numeric field
+    Range {
+        field: String,
+        ranges: Vec<FacetRange>,
+    },
+    /// Counts of the documents in each interval of a date field, a calendar unit such as `month`
+    /// or a fixed duration such as `12h`
+    DateHistogram {
+        field: String,
+        interval: String,
+        format: Option<String>,
+    },
+    /// Count, minimum, maximum, average and sum of a numeric field
+    Stats { field: String },
+}
+
+/// A range of values, from `from` included to `to` excluded, unbounded on the missing side
+#[derive(Debug, Serialize, Deserialize)]
+struct FacetRange {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    key: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    from: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    to: Option<f64>,
+}
+
+/// The calendar units of date histograms, any other interval being a fixed duration
+const CALENDAR_INTERVALS: [&str; 7] = ["minute", "hour", "day", "week", "month", "quarter", "year"];
+
+/// The name and the aggregation of a structured facet
+fn structured_facet_to_aggregation(facet: StructuredFacet) -> (String, Value) {
+    let (default_name, aggregation) = match facet.kind {
+        StructuredFacetKind::Range { field, ranges } => (
+            format!("{field}_range"),
+            json!({ "range": { "field": field, "ranges": ranges } }),
+        ),
+        StructuredFacetKind::DateHistogram {
+            field,
+            interval,
+            format,
+        } => {
+            let interval_key = if CALENDAR_INTERVALS.contains(&interval.as_str()) {
+                "calendar_interval"
+            } else {
+                "fixed_interval"
+            };
+            let mut histogram = json!({ "field": field, interval_key: interval });
+            if let Some(format) = format {
+                histogram["format"] = json!(format);
+            }
+            (
+                format!("{field}_date_histogram"),
+                json!({ "date_histogram": histogram }),
+            )
+        }
+        StructuredFacetKind::Stats { field } => (
+            format!("{field}_stats"),
+            json!({ "stats": { "field": field } }),
+        ),
+    };
+
+    (facet.name.unwrap_or(default_name), aggregation)
+}
+
+/// The query of a page of a stream over the point in time `pit_id`, continuing after the hit with
+/// the `search_after` sort values when given. The sort of the query, or relevance without one,
+/// ends with the `_shard_doc` tiebreaker, so that hits with equal sort values are neither skipped
+/// nor repeated across pages. Only the first page skips the offset of the query and counts facets.
+pub fn search_query_to_point_in_time_query(
+    query: SearchQuery,
+    pit_id: &str,
+    keep_alive: &str,
+    search_after: Option<Vec<Value>>,
+) -> ElasticsearchQuery {
+    let size = query.per_page.unwrap_or(100);
+    let mut es_query = search_query_to_elasticsearch_query(query);
+
+    let mut sort = es_query
+        .sort
+        .take()
+        .unwrap_or_else(|| vec![json!({ "_score": { "order": "desc" } })]);
+    sort.push(json!({ "_shard_doc": { "order": "asc" } }));
+    es_query.sort = Some(sort);
+    es_query.size = Some(size);
+    es_query.pit = Some(json!({ "id": pit_id, "keep_alive": keep_alive }));
+
+    if search_after.is_some() {
+        es_query.from = None;
+        es_query.aggs = None;
+    }
+    es_query.search_after = search_after;
+
+    es_query
+}
+
 pub fn elasticsearch_response_to_search_results(
     response: ElasticsearchSearchResponse,
 ) -> SearchResults {
@@ -247,13 +512,29 @@ pub fn elasticsearch_response_to_search_results(
             .aggregations
             .map(|aggs| serde_json::to_string(&aggs).unwrap_or_else(|_| "{}".to_string())),
         took_ms: Some(response.took),
+        relaxation: None,
+        navigation: None,
     }
 }
 
 fn elasticsearch_hit_to_search_hit(hit: ElasticsearchHit) -> SearchHit {
-    let content = hit
-        .source
-        .map(|source| serde_json::to_string(&source).unwrap_or_else(|_| "{}".to_string()));
+    let source = match (hit.source, hit.fields) {
+        (Some(Value::Object(mut source)), Some(fields)) => {
+            for (name, values) in fields {
+                source.entry(name).or_insert_with(|| field_values(values));
+            }
+            Some(Value::Object(source))
+        }
+        (None, Some(fields)) => Some(Value::Object(
+            fields
+                .into_iter()
+                .map(|(name, values)| (name, field_values(values)))
+                .collect(),
+        )),
+        (source, _) => source,
+    };
+    let content =
+        source.map(|source| serde_json::to_string(&source).unwrap_or_else(|_| "{}".to_string()));
 
     let highlights = hit
         .highlight
@@ -267,28 +548,50 @@ fn elasticsearch_hit_to_search_hit(hit: ElasticsearchHit) -> SearchHit {
     }
 }
 
-pub fn schema_to_elasticsearch_settings(schema: Schema) -> ElasticsearchSettings {
+/// The settings of an index with the fields of a schema, mapped along the options
+pub fn schema_to_elasticsearch_settings(
+    schema: Schema,
+    options: &MappingOptions,
+) -> ElasticsearchSettings {
     let mut properties = Map::new();
 
     for field in schema.fields {
         let mut field_mapping = Map::new();
+        let analysis = options.field_analysis.get(&field.name);
 
         match field.field_type {
             FieldType::Text => {
                 field_mapping.insert("type".to_string(), Value::String("text".to_string()));
 
-                field_mapping.insert(
-                    "fields".to_string(),
-                    json!({
-                        "keyword": {
-                            "type": "keyword",
-                            "ignore_above": 256
-                        }
-                    }),
-                );
+                let mut keyword = json!({
+                    "type": "keyword",
+                    "ignore_above": 256
+                });
+                if let Some(analysis) = analysis {
+                    if let Some(analyzer) = &analysis.analyzer {
+                        field_mapping.insert("analyzer".to_string(), json!(analyzer));
+                    }
+                    if let Some(search_analyzer) = &analysis.search_analyzer {
+                        field_mapping.insert("search_analyzer".to_string(), json!(search_analyzer));
+                    }
+                    if let Some(normalizer) = &analysis.normalizer {
+                        keyword["normalizer"] = json!(normalizer);
+                    }
+                }
+
+                field_mapping.insert("fields".to_string(), json!({ "keyword": keyword }));
             }
             FieldType::Keyword => {
                 field_mapping.insert("type".to_string(), Value::String("keyword".to_string()));
+
+                if let Some(analysis) = analysis {
+                    if analysis.analyzer.is_some() || analysis.search_analyzer.is_some() {
+                        warn!("Ignoring the analyzers of keyword field {}", field.name);
+                    }
+                    if let Some(normalizer) = &analysis.normalizer {
+                        field_mapping.insert("normalizer".to_string(), json!(normalizer));
+                    }
+                }
             }
             FieldType::Integer => {
                 field_mapping.insert("type".to_string(), Value::String("integer".to_string()));
@@ -326,6 +629,17 @@ pub fn schema_to_elasticsearch_settings(schema: Schema) -> ElasticsearchSettings
             "type": "keyword"
         }),
     );
+    if let Some(dimensions) = options.vector_dimensions {
+        properties.insert(
+            VECTOR_FIELD.to_string(),
+            json!({
+                "type": "dense_vector",
+                "dims": dimensions,
+                "index": true,
+                "similarity": "cosine"
+            }),
+        );
+    }
 
     let mappings = ElasticsearchMappings {
         properties: Some(properties),
@@ -338,10 +652,17 @@ pub fn schema_to_elasticsearch_settings(schema: Schema) -> ElasticsearchSettings
     }
 }
 
+/// The schema of the mappings of an index. Mappings are keyed by the concrete index, so the
+/// mappings of an alias are the ones of its first index.
 pub fn elasticsearch_mappings_to_schema(mappings: Value, index_name: &str) -> Schema {
     let mut fields = Vec::new();
 
-    if let Some(index_mappings) = mappings.get(index_name) {
+    let index_mappings = mappings.get(index_name).or_else(|| {
+        mappings
+            .as_object()
+            .and_then(|indices| indices.values().next())
+    });
+    if let Some(index_mappings) = index_mappings {
         if let Some(properties) = index_mappings
             .get("mappings")
             .and_then(|m| m.get("properties"))
@@ -357,6 +678,8 @@ pub fn elasticsearch_mappings_to_schema(mappings: Value, index_name: &str) -> Sc
                         "boolean" => FieldType::Boolean,
                         "date" => FieldType::Date,
                         "geo_point" => FieldType::GeoPoint,
+                        // Vectors are part of the documents, not fields of the schema
+                        "dense_vector" => continue,
                         _ => FieldType::Text,
                     };
 
@@ -446,7 +769,7 @@ mod tests {
     use crate::client::{
         ElasticsearchHit, ElasticsearchHits, ElasticsearchSearchResponse, ElasticsearchTotal,
     };
-    use golem_search::golem::search::types::{HighlightConfig, SearchConfig};
+    use golem_search::golem::search::types::{FilterMode, HighlightConfig, SearchConfig};
 
     #[test]
     fn test_doc_to_elasticsearch_document() {
@@ -529,6 +852,96 @@ mod tests {
         assert!(es_query.aggs.is_some());
     }
 
+    #[test]
+    fn test_search_query_to_point_in_time_query() {
+        let search_query = SearchQuery {
+            q: Some("laptop".to_string()),
+            filters: vec![],
+            sort: vec![],
+            facets: vec!["category".to_string()],
+            page: None,
+            per_page: Some(50),
+            offset: Some(10),
+            highlight: None,
+            config: None,
+        };
+
+        let first = search_query_to_point_in_time_query(search_query.clone(), "pit-1", "1m", None);
+        assert_eq!(first.from, Some(10));
+        assert_eq!(first.size, Some(50));
+        assert!(first.aggs.is_some());
+        assert_eq!(
+            first.pit,
+            Some(serde_json::json!({ "id": "pit-1", "keep_alive": "1m" }))
+        );
+        assert_eq!(
+            first.sort,
+            Some(vec![
+                serde_json::json!({ "_score": { "order": "desc" } }),
+                serde_json::json!({ "_shard_doc": { "order": "asc" } }),
+            ])
+        );
+        assert_eq!(first.search_after, None);
+
+        let next = search_query_to_point_in_time_query(
+            search_query,
+            "pit-2",
+            "1m",
+            Some(vec![serde_json::json!(1.5), serde_json::json!(42)]),
+        );
+        assert_eq!(next.from, None);
+        assert!(next.aggs.is_none());
+        assert_eq!(
+            next.search_after,
+            Some(vec![serde_json::json!(1.5), serde_json::json!(42)])
+        );
+    }
+
+    #[test]
+    fn test_search_query_with_structured_facets() {
+        let search_query = SearchQuery {
+            q: None,
+            filters: vec![],
+            sort: vec![],
+            facets: vec![
+                "category".to_string(),
+                r#"{"name": "price_ranges", "range": {"field": "price", "ranges": [{"to": 100}, {"from": 100}]}}"#.to_string(),
+                r#"{"date_histogram": {"field": "published", "interval": "month"}}"#.to_string(),
+                r#"{"date_histogram": {"field": "updated", "interval": "12h"}}"#.to_string(),
+                r#"{"stats": {"field": "price"}}"#.to_string(),
+            ],
+            page: None,
+            per_page: None,
+            offset: None,
+            highlight: None,
+            config: None,
+        };
+
+        let aggs = search_query_to_elasticsearch_query(search_query)
+            .aggs
+            .unwrap();
+        assert_eq!(
+            aggs["category"],
+            json!({ "terms": { "field": "category.keyword", "size": 10 } })
+        );
+        assert_eq!(
+            aggs["price_ranges"],
+            json!({ "range": { "field": "price", "ranges": [{ "to": 100.0 }, { "from": 100.0 }] } })
+        );
+        assert_eq!(
+            aggs["published_date_histogram"],
+            json!({ "date_histogram": { "field": "published", "calendar_interval": "month" } })
+        );
+        assert_eq!(
+            aggs["updated_date_histogram"],
+            json!({ "date_histogram": { "field": "updated", "fixed_interval": "12h" } })
+        );
+        assert_eq!(
+            aggs["price_stats"],
+            json!({ "stats": { "field": "price" } })
+        );
+    }
+
     #[test]
     fn test_search_query_no_query() {
         let search_query = SearchQuery {
@@ -549,6 +962,152 @@ mod tests {
         assert_eq!(es_query.query.unwrap()["match_all"], serde_json::json!({}));
     }
 
+    #[test]
+    fn test_search_query_with_any_filter_mode() {
+        let search_query = SearchQuery {
+            q: None,
+            filters: vec![
+                "category:electronics".to_string(),
+                "category:books".to_string(),
+            ],
+            sort: vec![],
+            facets: vec![],
+            page: None,
+            per_page: None,
+            offset: None,
+            highlight: None,
+            config: Some(SearchConfig {
+                attributes_to_retrieve: vec![],
+                typo_tolerance: None,
+                timeout_ms: None,
+                boost_fields: vec![],
+                exact_match_boost: None,
+                language: None,
+                provider_params: None,
+                profile: None,
+                filter_mode: Some(FilterMode::Any),
+                post_filters: vec![],
+                rescore: None,
+                fallbacks: vec![],
+            }),
+        };
+
+        let es_query = search_query_to_elasticsearch_query(search_query);
+        let filter = &es_query.query.unwrap()["bool"]["filter"];
+        assert_eq!(filter.as_array().unwrap().len(), 1);
+        assert_eq!(filter[0]["bool"]["minimum_should_match"], 1);
+        assert_eq!(
+            filter[0]["bool"]["should"],
+            serde_json::json!([
+                { "term": { "category": "electronics" } },
+                { "term": { "category": "books" } }
+            ])
+        );
+    }
+
+    fn vector_query(q: Option<&str>, filters: Vec<String>, params: &str) -> SearchQuery {
+        SearchQuery {
+            q: q.map(str::to_string),
+            filters,
+            sort: vec![],
+            facets: vec![],
+            page: None,
+            per_page: Some(5),
+            offset: None,
+            highlight: None,
+            config: Some(SearchConfig {
+                attributes_to_retrieve: vec![],
+                typo_tolerance: None,
+                timeout_ms: None,
+                boost_fields: vec![],
+                exact_match_boost: None,
+                language: None,
+                provider_params: Some(params.to_string()),
+                profile: None,
+                filter_mode: None,
+                post_filters: vec![],
+                rescore: None,
+                fallbacks: vec![],
+            }),
+        }
+    }
+
+    #[test]
+    fn test_search_query_with_runtime_mappings() {
+        let query = vector_query(
+            None,
+            vec![r#"{"range": {"discounted_price": {"lt": 10}}}"#.to_string()],
+            r#"{"runtime_mappings": {"discounted_price": {"type": "double", "script": "emit(doc['price'].value * 0.9)"}}}"#,
+        );
+
+        let es_query = search_query_to_elasticsearch_query(query);
+        assert_eq!(
+            es_query.runtime_mappings.unwrap()["discounted_price"]["type"],
+            "double"
+        );
+        assert_eq!(es_query.fields, Some(vec!["discounted_price".to_string()]));
+
+        let hit: ElasticsearchHit = serde_json::from_value(json!({
+            "_index": "products",
+            "_id": "1",
+            "_score": 1.0,
+            "_source": { "price": 10.0 },
+            "fields": { "discounted_price": [9.0] }
+        }))
+        .unwrap();
+        let content: Value =
+            serde_json::from_str(&elasticsearch_hit_to_search_hit(hit).content.unwrap()).unwrap();
+        assert_eq!(content["discounted_price"], 9.0);
+        assert_eq!(content["price"], 10.0);
+    }
+
+    #[test]
+    fn test_vector_query_to_knn() {
+        let query = vector_query(
+            None,
+            vec!["category:books".to_string()],
+            r#"{"vector": [0.1, 0.2, 0.3]}"#,
+        );
+
+        let knn = query_to_knn(&query, 5).unwrap().unwrap();
+        assert_eq!(knn["field"], VECTOR_FIELD);
+        assert_eq!(knn["k"], 5);
+        assert_eq!(knn["num_candidates"], 100);
+
+        let es_query = with_knn(search_query_to_elasticsearch_query(query), knn);
+        assert!(es_query.query.is_none());
+        assert_eq!(
+            es_query.knn.unwrap()["filter"][0]["term"]["category"],
+            "books"
+        );
+    }
+
+    #[test]
+    fn test_hybrid_vector_query_keeps_text_query() {
+        let query = vector_query(
+            Some("rust"),
+            vec![],
+            r#"{"vector": [0.1, 0.2], "k": 20, "num_candidates": 50}"#,
+        );
+
+        let knn = query_to_knn(&query, 5).unwrap().unwrap();
+        assert_eq!(knn["k"], 20);
+        assert_eq!(knn["num_candidates"], 50);
+
+        let es_query = with_knn(search_query_to_elasticsearch_query(query), knn);
+        assert!(es_query.query.is_some());
+        assert!(es_query.knn.unwrap().get("filter").is_none());
+    }
+
+    #[test]
+    fn test_invalid_vector_query() {
+        let query = vector_query(None, vec![], r#"{"vector": ["a"]}"#);
+        assert!(query_to_knn(&query, 5).is_err());
+
+        let query = vector_query(None, vec![], r#"{"other": 1}"#);
+        assert!(query_to_knn(&query, 5).unwrap().is_none());
+    }
+
     #[test]
     fn test_search_query_with_config() {
         let search_query = SearchQuery {
@@ -568,6 +1127,11 @@ mod tests {
                 exact_match_boost: None,
                 language: None,
                 provider_params: None,
+                profile: None,
+                filter_mode: None,
+                post_filters: vec![],
+                rescore: None,
+                fallbacks: vec![],
             }),
         };
 
@@ -596,9 +1160,12 @@ mod tests {
                     score: Some(1.0),
                     source: Some(serde_json::json!({"title": "Test Document"})),
                     highlight: Some(serde_json::json!({"title": ["Test <em>Document</em>"]})),
+                    sort: None,
+                    fields: None,
                 }],
             },
             aggregations: Some(serde_json::json!({"category": {"buckets": []}})),
+            pit_id: None,
         };
 
         let search_results = elasticsearch_response_to_search_results(es_response);
@@ -642,7 +1209,28 @@ mod tests {
             primary_key: Some("id".to_string()),
         };
 
-        let settings = schema_to_elasticsearch_settings(schema);
+        let options = MappingOptions {
+            vector_dimensions: Some(3),
+            field_analysis: HashMap::from([
+                (
+                    "title".to_string(),
+                    FieldAnalysis {
+                        analyzer: Some("french".to_string()),
+                        search_analyzer: None,
+                        normalizer: Some("lowercase".to_string()),
+                    },
+                ),
+                (
+                    "category".to_string(),
+                    FieldAnalysis {
+                        analyzer: None,
+                        search_analyzer: None,
+                        normalizer: Some("lowercase".to_string()),
+                    },
+                ),
+            ]),
+        };
+        let settings = schema_to_elasticsearch_settings(schema, &options);
         assert!(settings.mappings.is_some());
         let mappings = settings.mappings.unwrap();
         assert!(mappings.properties.is_some());
@@ -650,6 +1238,32 @@ mod tests {
         assert!(properties.contains_key("title"));
         assert!(properties.contains_key("category"));
         assert!(properties.contains_key("price"));
+        assert_eq!(properties["title"]["analyzer"], "french");
+        assert_eq!(
+            properties["title"]["fields"]["keyword"]["normalizer"],
+            "lowercase"
+        );
+        assert_eq!(properties["category"]["normalizer"], "lowercase");
+        assert_eq!(properties[VECTOR_FIELD]["type"], "dense_vector");
+        assert_eq!(properties[VECTOR_FIELD]["dims"], 3);
+    }
+
+    #[test]
+    fn test_elasticsearch_mappings_of_alias_to_schema() {
+        let mappings = json!({
+            "products-v2": {
+                "mappings": {
+                    "properties": {
+                        "title": { "type": "text" },
+                        "vector": { "type": "dense_vector", "dims": 3 }
+                    }
+                }
+            }
+        });
+
+        let schema = elasticsearch_mappings_to_schema(mappings, "products");
+        assert_eq!(schema.fields.len(), 1);
+        assert_eq!(schema.fields[0].name, "title");
     }
 
     #[test]
@@ -0,0 +1,156 @@
+//! Index templates.
+//!
+//! The component exports the `golem:search-elasticsearch/index-templates` interface, installing
+//! composable index templates derived from a schema, so that the indices Elasticsearch creates
+//! implicitly on the first write of a document come up with the mappings, analyzer and shard
+//! settings of the schema instead of dynamically mapped fields.
+
+use crate::client::ElasticsearchIndexTemplate;
+use crate::conversions::{schema_to_elasticsearch_settings, MappingOptions};
+use crate::lifecycle_policies::lifecycle_settings;
+use crate::ElasticsearchComponent;
+use golem_search::golem::search::types::SearchError;
+use golem_search::LOGGING_STATE;
+use serde_json::{json, Map, Value};
+
+#[allow(dead_code)]
+mod bindings {
+    wit_bindgen::generate!({
+        path: "wit",
+        world: "golem:search-elasticsearch/index-templates-library@1.0.0",
+        with: {
+            "golem:search/types@1.0.0": golem_search::golem::search::types,
+        },
+        generate_all,
+    });
+}
+
+use bindings::exports::golem::search_elasticsearch::index_templates::{Guest, IndexTemplate};
+
+struct ElasticsearchIndexTemplates;
+
+impl Guest for ElasticsearchIndexTemplates {
+    fn put_index_template(template: IndexTemplate) -> Result<(), SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = ElasticsearchComponent::create_client()?;
+        let name = template.name.clone();
+        let template =
+            index_template_to_elasticsearch(template, &ElasticsearchComponent::mapping_options()?);
+
+        client.put_index_template(&name, &template)
+    }
+
+    fn delete_index_template(name: String) -> Result<(), SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = ElasticsearchComponent::create_client()?;
+
+        client.delete_index_template(&name)
+    }
+}
+
+fn index_template_to_elasticsearch(
+    template: IndexTemplate,
+    options: &MappingOptions,
+) -> ElasticsearchIndexTemplate {
+    let mut settings = Map::new();
+    if let Some(shards) = template.number_of_shards {
+        settings.insert("number_of_shards".to_string(), json!(shards));
+    }
+    if let Some(replicas) = template.number_of_replicas {
+        settings.insert("number_of_replicas".to_string(), json!(replicas));
+    }
+    if let Some(analyzer) = template.analyzer {
+        settings.insert(
+            "analysis".to_string(),
+            json!({
+                "analyzer": {
+                    "default": {
+                        "type": analyzer
+                    }
+                }
+            }),
+        );
+    }
+
+    if let Some(lifecycle) = lifecycle_settings(template.lifecycle_policy, template.rollover_alias)
+    {
+        settings.insert("lifecycle".to_string(), lifecycle);
+    }
+
+    let mut index_settings = schema_to_elasticsearch_settings(template.schema, options);
+    if !settings.is_empty() {
+        index_settings.settings = Some(Map::from_iter([(
+            "index".to_string(),
+            Value::Object(settings),
+        )]));
+    }
+
+    ElasticsearchIndexTemplate {
+        index_patterns: template.index_patterns,
+        priority: template.priority,
+        template: index_settings,
+    }
+}
+
+bindings::export!(ElasticsearchIndexTemplates with_types_in bindings);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use golem_search::golem::search::types::{FieldType, Schema, SchemaField};
+
+    fn template(analyzer: Option<&str>, number_of_shards: Option<u32>) -> IndexTemplate {
+        IndexTemplate {
+            name: "logs".to_string(),
+            index_patterns: vec!["logs-*".to_string()],
+            schema: Schema {
+                fields: vec![SchemaField {
+                    name: "message".to_string(),
+                    field_type: FieldType::Text,
+                    required: false,
+                    facet: false,
+                    sort: false,
+                    index: true,
+                }],
+                primary_key: None,
+            },
+            priority: Some(100),
+            number_of_shards,
+            number_of_replicas: None,
+            analyzer: analyzer.map(str::to_string),
+            lifecycle_policy: None,
+            rollover_alias: None,
+        }
+    }
+
+    #[test]
+    fn templates_carry_the_schema_mappings_and_index_settings() {
+        let template = index_template_to_elasticsearch(
+            template(Some("english"), Some(2)),
+            &MappingOptions::default(),
+        );
+        let body = serde_json::to_value(&template).unwrap();
+
+        assert_eq!(body["index_patterns"], json!(["logs-*"]));
+        assert_eq!(body["priority"], 100);
+        assert_eq!(
+            body["template"]["mappings"]["properties"]["message"]["type"],
+            "text"
+        );
+        assert_eq!(body["template"]["settings"]["index"]["number_of_shards"], 2);
+        assert_eq!(
+            body["template"]["settings"]["index"]["analysis"]["analyzer"]["default"]["type"],
+            "english"
+        );
+    }
+
+    #[test]
+    fn templates_without_settings_leave_the_defaults() {
+        let template =
+            index_template_to_elasticsearch(template(None, None), &MappingOptions::default());
+
+        assert!(template.template.settings.is_none());
+    }
+}
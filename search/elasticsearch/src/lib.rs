@@ -1,53 +1,80 @@
-use crate::client::ElasticsearchApi;
+use crate::client::{cloud_id_to_url, ElasticsearchApi, MAX_RESULT_WINDOW};
 use crate::conversions::{
     build_bulk_delete_operations, build_bulk_operations, create_retry_query,
     doc_to_elasticsearch_document, elasticsearch_document_to_doc, elasticsearch_mappings_to_schema,
-    elasticsearch_response_to_search_results, schema_to_elasticsearch_settings,
-    search_query_to_elasticsearch_query,
+    elasticsearch_response_to_search_results, query_to_knn, schema_to_elasticsearch_settings,
+    search_query_to_elasticsearch_query, search_query_to_point_in_time_query, with_knn,
+    MappingOptions,
 };
 use golem_rust::wasm_rpc::Pollable;
-use golem_search::config::with_config_keys;
+use golem_search::bulk::{bulk_failures, bulk_failures_to_search_error};
+use golem_search::config::{
+    get_optional_config, profile_key, search_headers, search_profile, search_timeout,
+    worker_headers, worker_profile,
+};
 use golem_search::durability::{DurableSearch, ExtendedGuest};
+use golem_search::error::{internal_error, invalid_query};
 use golem_search::golem::search::core::{Guest, GuestSearchStream, SearchStream};
 use golem_search::golem::search::types::{
-    Doc, DocumentId, IndexName, Schema, SearchError, SearchHit, SearchQuery, SearchResults,
+    Doc, DocumentId, IndexName, IndexPage, PageRequest, Schema, SearchError, SearchHit,
+    SearchQuery, SearchResults,
 };
+use golem_search::pagination::{index_page, page_source, PageSource};
 use golem_search::LOGGING_STATE;
 use log::trace;
+use serde_json::Value;
 use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
 
+mod aliases;
+mod bulk;
 mod client;
 mod conversions;
-
-/// Uses scroll API for streaming large result sets
+mod index_templates;
+mod lifecycle_policies;
+mod reindex;
+mod update_by_query;
+
+/// Streams the hits of a query through a point in time, paginated with `search_after`, which
+/// neither holds a scroll context open nor is limited by the index's `max_result_window`. Falls
+/// back to `from`/`size` pagination when the point in time can't be opened or searched.
 struct ElasticsearchSearchStream {
     client: ElasticsearchApi,
     index_name: String,
     query: SearchQuery,
-    scroll_id: RefCell<Option<String>>,
+    pit_id: RefCell<Option<String>>,
+    /// The sort values of the last hit streamed from the point in time
+    search_after: RefCell<Option<Vec<Value>>>,
     finished: Cell<bool>,
     current_offset: Cell<u32>,
-    use_scroll: Cell<bool>,
-    scroll_failed: Cell<bool>,
+    pit_failed: Cell<bool>,
 }
 
 impl ElasticsearchSearchStream {
+    const PIT_KEEP_ALIVE: &'static str = "1m";
+
     pub fn new(client: ElasticsearchApi, index_name: String, query: SearchQuery) -> Self {
         Self {
             client,
             index_name,
             query: query.clone(),
-            scroll_id: RefCell::new(None),
+            pit_id: RefCell::new(None),
+            search_after: RefCell::new(None),
             finished: Cell::new(false),
             current_offset: Cell::new(query.offset.unwrap_or(0)),
-            use_scroll: Cell::new(true), // Start with scroll, fallback to pagination if needed
-            scroll_failed: Cell::new(false),
+            pit_failed: Cell::new(false),
         }
     }
 
     pub fn subscribe(&self) -> Pollable {
         golem_rust::bindings::wasi::clocks::monotonic_clock::subscribe_duration(0)
     }
+
+    fn close_point_in_time(&self) {
+        if let Some(pit_id) = self.pit_id.borrow_mut().take() {
+            let _ = self.client.close_point_in_time(&pit_id);
+        }
+    }
 }
 
 impl GuestSearchStream for ElasticsearchSearchStream {
@@ -56,11 +83,11 @@ impl GuestSearchStream for ElasticsearchSearchStream {
             return Some(vec![]);
         }
 
-        if self.use_scroll.get() && !self.scroll_failed.get() {
-            self.try_scroll_next().unwrap_or_else(|| {
-                trace!("Scroll failed, falling back to pagination");
-                self.scroll_failed.set(true);
-                self.use_scroll.set(false);
+        if !self.pit_failed.get() {
+            self.try_point_in_time_next().unwrap_or_else(|| {
+                trace!("Point in time search failed, falling back to pagination");
+                self.pit_failed.set(true);
+                self.close_point_in_time();
                 self.try_pagination_next()
             })
         } else {
@@ -74,74 +101,63 @@ impl GuestSearchStream for ElasticsearchSearchStream {
 }
 
 impl ElasticsearchSearchStream {
-    fn try_scroll_next(&self) -> Option<Option<Vec<SearchHit>>> {
-        if self.scroll_id.borrow().is_none() {
-            let mut es_query = search_query_to_elasticsearch_query(self.query.clone());
-            es_query.from = Some(0);
-            es_query.size = Some(self.query.per_page.unwrap_or(100)); // Larger page size for scroll
-
+    /// Returns none when the point in time failed before streaming any hit, so that the stream
+    /// can fall back to pagination
+    fn try_point_in_time_next(&self) -> Option<Option<Vec<SearchHit>>> {
+        if self.pit_id.borrow().is_none() {
             match self
                 .client
-                .search_with_scroll(&self.index_name, &es_query, "1m")
+                .open_point_in_time(&self.index_name, Self::PIT_KEEP_ALIVE)
             {
-                Ok(response) => {
-                    *self.scroll_id.borrow_mut() = Some(response.scroll_id);
-
-                    let search_results = elasticsearch_response_to_search_results(
-                        crate::client::ElasticsearchSearchResponse {
-                            took: response.took,
-                            timed_out: response.timed_out,
-                            hits: response.hits,
-                            aggregations: response.aggregations,
-                        },
-                    );
-
-                    if search_results.hits.is_empty() {
-                        self.finished.set(true);
-                        return Some(Some(vec![]));
-                    }
-
-                    Some(Some(search_results.hits))
-                }
+                Ok(pit_id) => *self.pit_id.borrow_mut() = Some(pit_id),
                 Err(e) => {
-                    trace!("Initial scroll search failed: {e:?}");
-                    None // Signal to fallback to pagination
+                    trace!("Opening point in time failed: {e:?}");
+                    return None;
                 }
             }
-        } else {
-            let scroll_id = self.scroll_id.borrow().clone().unwrap();
-
-            match self.client.scroll(&scroll_id, "1m") {
-                Ok(response) => {
-                    *self.scroll_id.borrow_mut() = Some(response.scroll_id);
-
-                    let search_results = elasticsearch_response_to_search_results(
-                        crate::client::ElasticsearchSearchResponse {
-                            took: response.took,
-                            timed_out: response.timed_out,
-                            hits: response.hits,
-                            aggregations: response.aggregations,
-                        },
-                    );
-
-                    if search_results.hits.is_empty() {
-                        self.finished.set(true);
-
-                        if let Some(scroll_id) = self.scroll_id.borrow().as_ref() {
-                            let _ = self.client.clear_scroll(scroll_id);
-                        }
-                    }
+        }
 
-                    Some(Some(search_results.hits))
+        let pit_id = self.pit_id.borrow().clone().unwrap();
+        let search_after = self.search_after.borrow().clone();
+        let started = search_after.is_some();
+        let es_query = search_query_to_point_in_time_query(
+            self.query.clone(),
+            &pit_id,
+            Self::PIT_KEEP_ALIVE,
+            search_after,
+        );
+
+        match self.client.search_point_in_time(&es_query) {
+            Ok(mut response) => {
+                // Elasticsearch can return a new id for the point in time after each search
+                if let Some(pit_id) = response.pit_id.take() {
+                    *self.pit_id.borrow_mut() = Some(pit_id);
                 }
-                Err(e) => {
-                    trace!("Scroll continuation failed: {e:?}");
+                *self.search_after.borrow_mut() =
+                    response.hits.hits.last().and_then(|hit| hit.sort.clone());
 
-                    if let Some(scroll_id) = self.scroll_id.borrow().as_ref() {
-                        let _ = self.client.clear_scroll(scroll_id);
-                    }
-                    None
+                let search_results = elasticsearch_response_to_search_results(response);
+
+                let received_count = search_results.hits.len() as u32;
+                self.current_offset
+                    .set(self.current_offset.get() + received_count);
+
+                if search_results.hits.is_empty() || self.search_after.borrow().is_none() {
+                    self.finished.set(true);
+                    self.close_point_in_time();
                 }
+
+                Some(Some(search_results.hits))
+            }
+            Err(e) if started => {
+                trace!("Point in time continuation failed: {e:?}");
+                self.finished.set(true);
+                self.close_point_in_time();
+                Some(Some(vec![]))
+            }
+            Err(e) => {
+                trace!("Initial point in time search failed: {e:?}");
+                None
             }
         }
     }
@@ -183,47 +199,87 @@ impl ElasticsearchSearchStream {
 
 struct ElasticsearchComponent;
 
+thread_local! {
+    /// The provider clients by configuration profile, reused across calls instead of being rebuilt
+    /// for each of them
+    static CLIENTS: RefCell<HashMap<Option<String>, ElasticsearchApi>> = RefCell::new(HashMap::new());
+}
+
 impl ElasticsearchComponent {
     const URL_ENV_VAR: &'static str = "ELASTICSEARCH_URL";
+    /// Alternative to the URL for Elastic Cloud deployments
+    const CLOUD_ID_ENV_VAR: &'static str = "ELASTICSEARCH_CLOUD_ID";
     const USERNAME_ENV_VAR: &'static str = "ELASTICSEARCH_USERNAME";
     const PASSWORD_ENV_VAR: &'static str = "ELASTICSEARCH_PASSWORD";
     const API_KEY_ENV_VAR: &'static str = "ELASTICSEARCH_API_KEY";
+    const SERVICE_TOKEN_ENV_VAR: &'static str = "ELASTICSEARCH_SERVICE_TOKEN";
+    const VECTOR_DIMENSIONS_ENV_VAR: &'static str = "ELASTICSEARCH_VECTOR_DIMENSIONS";
+    const FIELD_ANALYSIS_ENV_VAR: &'static str = "ELASTICSEARCH_FIELD_ANALYSIS";
+
+    /// The options of the mappings of schemas: the dimensions of the document vectors, mapping
+    /// the vector field as a `dense_vector` when set, and the analysis of the fields, a JSON
+    /// object of the `analyzer`, `search_analyzer` and `normalizer` of each field
+    fn mapping_options() -> Result<MappingOptions, SearchError> {
+        let profile = worker_profile();
+        let config = |key: &str| get_optional_config(profile_key(key, profile.as_deref()));
+        let vector_dimensions =
+            config(Self::VECTOR_DIMENSIONS_ENV_VAR).and_then(|d| d.trim().parse().ok());
+        let field_analysis = match config(Self::FIELD_ANALYSIS_ENV_VAR) {
+            Some(analysis) if !analysis.trim().is_empty() => serde_json::from_str(&analysis)
+                .map_err(|e| {
+                    internal_error(format!("Invalid {}: {e}", Self::FIELD_ANALYSIS_ENV_VAR))
+                })?,
+            _ => HashMap::new(),
+        };
+
+        Ok(MappingOptions {
+            vector_dimensions,
+            field_analysis,
+        })
+    }
 
     fn create_client() -> Result<ElasticsearchApi, SearchError> {
-        with_config_keys(
-            &[
-                Self::URL_ENV_VAR,
-                Self::USERNAME_ENV_VAR,
-                Self::PASSWORD_ENV_VAR,
-                Self::API_KEY_ENV_VAR,
-            ],
-            |keys| {
-                if keys.is_empty() || keys[0].is_empty() {
-                    return Err(SearchError::Internal(
-                        "Missing Elasticsearch URL".to_string(),
-                    ));
-                }
+        Self::create_profile_client(worker_profile().as_deref())
+            .map(|client| client.with_headers(worker_headers()))
+    }
 
-                let url = keys[0].clone();
-                let username = if keys.len() > 1 && !keys[1].is_empty() {
-                    Some(keys[1].clone())
-                } else {
-                    None
-                };
-                let password = if keys.len() > 2 && !keys[2].is_empty() {
-                    Some(keys[2].clone())
-                } else {
-                    None
-                };
-                let api_key = if keys.len() > 3 && !keys[3].is_empty() {
-                    Some(keys[3].clone())
-                } else {
-                    None
-                };
-
-                Ok(ElasticsearchApi::new(url, username, password, api_key))
-            },
+    /// Returns the client of `profile` cached for this worker, creating it from the configuration
+    /// on first use
+    fn create_profile_client(profile: Option<&str>) -> Result<ElasticsearchApi, SearchError> {
+        CLIENTS.with_borrow_mut(|clients| {
+            let profile = profile.map(str::to_string);
+            if let Some(client) = clients.get(&profile) {
+                return Ok(client.clone());
+            }
+
+            let client = Self::new_client(profile.as_deref())?;
+            clients.insert(profile, client.clone());
+            Ok(client)
+        })
+    }
+
+    fn new_client(profile: Option<&str>) -> Result<ElasticsearchApi, SearchError> {
+        let config = |key: &str| {
+            get_optional_config(profile_key(key, profile)).filter(|value| !value.is_empty())
+        };
+
+        let url = match (config(Self::URL_ENV_VAR), config(Self::CLOUD_ID_ENV_VAR)) {
+            (Some(url), _) => url,
+            (None, Some(cloud_id)) => cloud_id_to_url(&cloud_id)?,
+            (None, None) => {
+                return Err(SearchError::Internal(
+                    "Missing Elasticsearch URL or Elastic Cloud ID".to_string(),
+                ))
+            }
+        };
+
+        Ok(ElasticsearchApi::new(
+            url,
+            config(Self::USERNAME_ENV_VAR),
+            config(Self::PASSWORD_ENV_VAR),
+            config(Self::API_KEY_ENV_VAR),
         )
+        .with_service_token(config(Self::SERVICE_TOKEN_ENV_VAR)))
     }
 }
 
@@ -234,7 +290,13 @@ impl Guest for ElasticsearchComponent {
         LOGGING_STATE.with_borrow_mut(|state| state.init());
 
         let client = Self::create_client()?;
-        let settings = schema.map(schema_to_elasticsearch_settings);
+        let settings = match schema {
+            Some(schema) => Some(schema_to_elasticsearch_settings(
+                schema,
+                &Self::mapping_options()?,
+            )),
+            None => None,
+        };
 
         client.create_index(&name, settings)
     }
@@ -246,12 +308,12 @@ impl Guest for ElasticsearchComponent {
         client.delete_index(&name)
     }
 
-    fn list_indexes() -> Result<Vec<IndexName>, SearchError> {
+    fn list_indexes(page: PageRequest) -> Result<IndexPage, SearchError> {
         LOGGING_STATE.with_borrow_mut(|state| state.init());
 
         let client = Self::create_client()?;
         match client.list_indices() {
-            Ok(indices) => Ok(indices.into_iter().map(|idx| idx.index).collect()),
+            Ok(indices) => index_page(indices.into_iter().map(|idx| idx.index).collect(), &page),
             Err(e) => Err(e),
         }
     }
@@ -272,21 +334,20 @@ impl Guest for ElasticsearchComponent {
     fn upsert_many(index: IndexName, docs: Vec<Doc>) -> Result<(), SearchError> {
         LOGGING_STATE.with_borrow_mut(|state| state.init());
 
+        if docs.is_empty() {
+            return Ok(());
+        }
+
         let client = Self::create_client()?;
         let bulk_operations =
             build_bulk_operations(&index, &docs, "index").map_err(SearchError::InvalidQuery)?;
 
-        match client.bulk_index(&bulk_operations) {
-            Ok(response) => {
-                if response.errors {
-                    Err(SearchError::Internal(
-                        "Some bulk operations failed".to_string(),
-                    ))
-                } else {
-                    Ok(())
-                }
-            }
-            Err(e) => Err(e),
+        let response = client.bulk_index(&bulk_operations)?;
+        let failures = bulk_failures(&response.items);
+        if failures.is_empty() {
+            Ok(())
+        } else {
+            Err(bulk_failures_to_search_error(&failures, docs.len()))
         }
     }
 
@@ -300,21 +361,20 @@ impl Guest for ElasticsearchComponent {
     fn delete_many(index: IndexName, ids: Vec<DocumentId>) -> Result<(), SearchError> {
         LOGGING_STATE.with_borrow_mut(|state| state.init());
 
+        if ids.is_empty() {
+            return Ok(());
+        }
+
         let client = Self::create_client()?;
         let bulk_operations =
             build_bulk_delete_operations(&index, &ids).map_err(SearchError::InvalidQuery)?;
 
-        match client.bulk_index(&bulk_operations) {
-            Ok(response) => {
-                if response.errors {
-                    Err(SearchError::Internal(
-                        "Some bulk delete operations failed".to_string(),
-                    ))
-                } else {
-                    Ok(())
-                }
-            }
-            Err(e) => Err(e),
+        let response = client.bulk_index(&bulk_operations)?;
+        let failures = bulk_failures(&response.items);
+        if failures.is_empty() {
+            Ok(())
+        } else {
+            Err(bulk_failures_to_search_error(&failures, ids.len()))
         }
     }
 
@@ -322,29 +382,51 @@ impl Guest for ElasticsearchComponent {
         LOGGING_STATE.with_borrow_mut(|state| state.init());
 
         let client = Self::create_client()?;
-        match client.get_document(&index, &id) {
-            Ok(Some(document)) => Ok(Some(elasticsearch_document_to_doc(id, document))),
-            Ok(None) => Ok(None),
-            Err(e) => Err(e),
-        }
+        let document = match client.get_document(&index, &id) {
+            // Aliases of several indices reject gets, but not searches
+            Err(SearchError::InvalidQuery(_)) => client.search_document(&index, &id)?,
+            result => result?,
+        };
+
+        Ok(document.map(|document| elasticsearch_document_to_doc(id, document)))
     }
 
     fn search(index: IndexName, query: SearchQuery) -> Result<SearchResults, SearchError> {
         LOGGING_STATE.with_borrow_mut(|state| state.init());
 
-        let client = Self::create_client()?;
-        let es_query = search_query_to_elasticsearch_query(query);
-
-        match client.search(&index, &es_query) {
-            Ok(response) => Ok(elasticsearch_response_to_search_results(response)),
-            Err(e) => Err(e),
+        let client = Self::create_profile_client(search_profile(&query).as_deref())?
+            .with_request_timeout(search_timeout(&query))
+            .with_headers(search_headers(&query));
+        let knn_query = query.clone();
+        let mut es_query = search_query_to_elasticsearch_query(query);
+
+        // Elasticsearch returns 10 hits when the query sets no size
+        let per_page = es_query.size.unwrap_or(10);
+        let depth = es_query.from.unwrap_or(0).saturating_add(per_page);
+        if let Some(knn) = query_to_knn(&knn_query, depth)? {
+            es_query = with_knn(es_query, knn);
         }
+        let response = match page_source(depth, MAX_RESULT_WINDOW, true)? {
+            PageSource::Search => client.search(&index, &es_query)?,
+            PageSource::Cursor => client.search_with_scroll_pagination(&index, &es_query)?,
+        };
+
+        let mut results = elasticsearch_response_to_search_results(response);
+        results.per_page = Some(per_page);
+        Ok(results)
     }
 
     fn stream_search(index: IndexName, query: SearchQuery) -> Result<SearchStream, SearchError> {
         LOGGING_STATE.with_borrow_mut(|state| state.init());
 
-        let client = Self::create_client()?;
+        let client = Self::create_profile_client(search_profile(&query).as_deref())?
+            .with_request_timeout(search_timeout(&query))
+            .with_headers(search_headers(&query));
+        if query_to_knn(&query, 0)?.is_some() {
+            return Err(invalid_query(
+                "Vector queries return the nearest neighbors only and can't be streamed",
+            ));
+        }
         let stream = ElasticsearchSearchStream::new(client, index, query);
         Ok(SearchStream::new(stream))
     }
@@ -363,7 +445,7 @@ impl Guest for ElasticsearchComponent {
         LOGGING_STATE.with_borrow_mut(|state| state.init());
 
         let client = Self::create_client()?;
-        let settings = schema_to_elasticsearch_settings(schema);
+        let settings = schema_to_elasticsearch_settings(schema, &Self::mapping_options()?);
 
         if let Some(mappings) = settings.mappings {
             client.put_mappings(&index, &mappings)
@@ -377,9 +459,11 @@ impl ExtendedGuest for ElasticsearchComponent {
     fn unwrapped_stream(index: IndexName, query: SearchQuery) -> Self::SearchStream {
         LOGGING_STATE.with_borrow_mut(|state| state.init());
 
-        let client = Self::create_client().unwrap_or_else(|_| {
-            ElasticsearchApi::new("http://localhost:9200".to_string(), None, None, None)
-        });
+        let client = Self::create_profile_client(search_profile(&query).as_deref())
+            .unwrap_or_else(|_| {
+                ElasticsearchApi::new("http://localhost:9200".to_string(), None, None, None)
+            })
+            .with_headers(search_headers(&query));
 
         ElasticsearchSearchStream::new(client, index, query)
     }
@@ -395,10 +479,8 @@ impl ExtendedGuest for ElasticsearchComponent {
 
 impl Drop for ElasticsearchSearchStream {
     fn drop(&mut self) {
-        // Clear any active scroll when the stream is dropped
-        if let Some(scroll_id) = self.scroll_id.borrow().as_ref() {
-            let _ = self.client.clear_scroll(scroll_id);
-        }
+        // Close any open point in time when the stream is dropped
+        self.close_point_in_time();
     }
 }
 
@@ -0,0 +1,196 @@
+//! Index lifecycle management.
+//!
+//! The component exports the `golem:search-elasticsearch/lifecycle-policies` interface, creating
+//! ILM policies which roll the write index of an alias over and delete old indices, and attaching
+//! them to indices. Index templates reference a policy to manage the indices created on rollover.
+
+use crate::ElasticsearchComponent;
+use golem_search::error::invalid_query;
+use golem_search::golem::search::types::{IndexName, SearchError};
+use golem_search::LOGGING_STATE;
+use serde_json::{json, Map, Value};
+
+#[allow(dead_code)]
+mod bindings {
+    wit_bindgen::generate!({
+        path: "wit",
+        world: "golem:search-elasticsearch/lifecycle-policies-library@1.0.0",
+        with: {
+            "golem:search/types@1.0.0": golem_search::golem::search::types,
+        },
+        generate_all,
+    });
+}
+
+use bindings::exports::golem::search_elasticsearch::lifecycle_policies::{
+    Guest, LifecyclePolicy, Rollover,
+};
+
+struct ElasticsearchLifecyclePolicies;
+
+impl Guest for ElasticsearchLifecyclePolicies {
+    fn put_lifecycle_policy(policy: LifecyclePolicy) -> Result<(), SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = ElasticsearchComponent::create_client()?;
+        let name = policy.name.clone();
+        let policy = lifecycle_policy_to_elasticsearch(policy)?;
+
+        client.put_lifecycle_policy(&name, &policy)
+    }
+
+    fn delete_lifecycle_policy(name: String) -> Result<(), SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = ElasticsearchComponent::create_client()?;
+
+        client.delete_lifecycle_policy(&name)
+    }
+
+    fn attach_lifecycle_policy(
+        index: IndexName,
+        policy: String,
+        rollover_alias: Option<String>,
+    ) -> Result<(), SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = ElasticsearchComponent::create_client()?;
+        let settings = json!({
+            "index": {
+                "lifecycle": lifecycle_settings(Some(policy), rollover_alias)
+            }
+        });
+
+        client.put_index_settings(&index, &settings)
+    }
+}
+
+/// The `index.lifecycle` settings managing an index with a policy, none without a policy
+pub fn lifecycle_settings(policy: Option<String>, rollover_alias: Option<String>) -> Option<Value> {
+    let policy = policy?;
+    let mut settings = Map::new();
+    settings.insert("name".to_string(), Value::String(policy));
+    if let Some(alias) = rollover_alias {
+        settings.insert("rollover_alias".to_string(), Value::String(alias));
+    }
+    Some(Value::Object(settings))
+}
+
+fn lifecycle_policy_to_elasticsearch(policy: LifecyclePolicy) -> Result<Value, SearchError> {
+    let mut phases = Map::new();
+
+    if let Some(rollover) = policy.rollover {
+        phases.insert(
+            "hot".to_string(),
+            json!({
+                "actions": {
+                    "rollover": rollover_to_elasticsearch(rollover)?
+                }
+            }),
+        );
+    }
+    if let Some(days) = policy.delete_after_days {
+        phases.insert(
+            "delete".to_string(),
+            json!({
+                "min_age": format!("{days}d"),
+                "actions": {
+                    "delete": {}
+                }
+            }),
+        );
+    }
+
+    if phases.is_empty() {
+        return Err(invalid_query(format!(
+            "Lifecycle policy {} neither rolls indices over nor deletes them",
+            policy.name
+        )));
+    }
+
+    Ok(json!({
+        "policy": {
+            "phases": phases
+        }
+    }))
+}
+
+fn rollover_to_elasticsearch(rollover: Rollover) -> Result<Value, SearchError> {
+    let mut conditions = Map::new();
+    if let Some(max_age) = rollover.max_age {
+        conditions.insert("max_age".to_string(), Value::String(max_age));
+    }
+    if let Some(max_size) = rollover.max_primary_shard_size {
+        conditions.insert(
+            "max_primary_shard_size".to_string(),
+            Value::String(max_size),
+        );
+    }
+    if let Some(max_docs) = rollover.max_docs {
+        conditions.insert("max_docs".to_string(), json!(max_docs));
+    }
+
+    if conditions.is_empty() {
+        return Err(invalid_query("A rollover needs at least one condition"));
+    }
+    Ok(Value::Object(conditions))
+}
+
+bindings::export!(ElasticsearchLifecyclePolicies with_types_in bindings);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn policies_roll_over_then_delete() {
+        let policy = lifecycle_policy_to_elasticsearch(LifecyclePolicy {
+            name: "logs".to_string(),
+            rollover: Some(Rollover {
+                max_age: Some("1d".to_string()),
+                max_primary_shard_size: Some("50gb".to_string()),
+                max_docs: None,
+            }),
+            delete_after_days: Some(30),
+        })
+        .unwrap();
+
+        let phases = &policy["policy"]["phases"];
+        assert_eq!(phases["hot"]["actions"]["rollover"]["max_age"], "1d");
+        assert!(phases["hot"]["actions"]["rollover"]
+            .get("max_docs")
+            .is_none());
+        assert_eq!(phases["delete"]["min_age"], "30d");
+    }
+
+    #[test]
+    fn policies_without_phases_are_rejected() {
+        assert!(lifecycle_policy_to_elasticsearch(LifecyclePolicy {
+            name: "noop".to_string(),
+            rollover: None,
+            delete_after_days: None,
+        })
+        .is_err());
+
+        assert!(lifecycle_policy_to_elasticsearch(LifecyclePolicy {
+            name: "noop".to_string(),
+            rollover: Some(Rollover {
+                max_age: None,
+                max_primary_shard_size: None,
+                max_docs: None,
+            }),
+            delete_after_days: None,
+        })
+        .is_err());
+    }
+
+    #[test]
+    fn settings_name_the_policy_and_alias() {
+        let settings =
+            lifecycle_settings(Some("logs".to_string()), Some("logs-write".to_string())).unwrap();
+
+        assert_eq!(settings["name"], "logs");
+        assert_eq!(settings["rollover_alias"], "logs-write");
+        assert!(lifecycle_settings(None, Some("logs-write".to_string())).is_none());
+    }
+}
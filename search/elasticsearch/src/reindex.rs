@@ -0,0 +1,156 @@
+//! Copies of indices.
+//!
+//! The component exports the `golem:search-elasticsearch/reindex` interface, copying the
+//! documents of an index to another through the `_reindex` API. Copies run as background tasks of
+//! Elasticsearch, which a worker polls for progress instead of holding a request open for the
+//! whole copy.
+
+use crate::client::ElasticsearchTask;
+use crate::conversions::search_query_to_elasticsearch_query;
+use crate::ElasticsearchComponent;
+use golem_search::golem::search::types::{SearchError, SearchQuery};
+use golem_search::LOGGING_STATE;
+use serde_json::{json, Value};
+
+#[allow(dead_code)]
+mod bindings {
+    wit_bindgen::generate!({
+        path: "wit",
+        world: "golem:search-elasticsearch/reindex-library@1.0.0",
+        with: {
+            "golem:search/types@1.0.0": golem_search::golem::search::types,
+        },
+        generate_all,
+    });
+}
+
+use bindings::exports::golem::search_elasticsearch::reindex::{
+    Guest, ReindexRequest, ReindexStatus,
+};
+
+struct ElasticsearchReindex;
+
+impl Guest for ElasticsearchReindex {
+    fn reindex(request: ReindexRequest) -> Result<String, SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = ElasticsearchComponent::create_client()?;
+
+        client.reindex(&reindex_request_to_elasticsearch(request))
+    }
+
+    fn get_reindex_status(task_id: String) -> Result<ReindexStatus, SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = ElasticsearchComponent::create_client()?;
+
+        Ok(elasticsearch_task_to_reindex_status(
+            client.get_task(&task_id)?,
+        ))
+    }
+}
+
+fn reindex_request_to_elasticsearch(request: ReindexRequest) -> Value {
+    let mut source = json!({ "index": request.source });
+    if !request.filters.is_empty() {
+        let filter_query = search_query_to_elasticsearch_query(SearchQuery {
+            q: None,
+            filters: request.filters,
+            sort: vec![],
+            facets: vec![],
+            page: None,
+            per_page: None,
+            offset: None,
+            highlight: None,
+            config: None,
+        });
+        source["query"] = filter_query.query.unwrap_or(json!({ "match_all": {} }));
+    }
+
+    let mut body = json!({
+        "source": source,
+        "dest": { "index": request.destination }
+    });
+    if let Some(script) = request.script {
+        body["script"] = json!({
+            "source": script,
+            "lang": "painless"
+        });
+    }
+    if let Some(max_docs) = request.max_docs {
+        body["max_docs"] = json!(max_docs);
+    }
+    body
+}
+
+fn elasticsearch_task_to_reindex_status(task: ElasticsearchTask) -> ReindexStatus {
+    let status = task.task.status.unwrap_or_default();
+
+    let mut failures = task
+        .response
+        .map(|response| response.failures)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|failure| failure.to_string())
+        .collect::<Vec<_>>();
+    if let Some(error) = task.error {
+        failures.push(error.to_string());
+    }
+
+    ReindexStatus {
+        completed: task.completed,
+        total: status.total,
+        created: status.created,
+        updated: status.updated,
+        deleted: status.deleted,
+        failures,
+    }
+}
+
+bindings::export!(ElasticsearchReindex with_types_in bindings);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filtered_copies_query_the_source() {
+        let body = reindex_request_to_elasticsearch(ReindexRequest {
+            source: "products".to_string(),
+            destination: "products-v2".to_string(),
+            filters: vec!["category:books".to_string()],
+            script: Some("ctx._source.remove('draft')".to_string()),
+            max_docs: None,
+        });
+
+        assert_eq!(body["source"]["index"], "products");
+        assert_eq!(
+            body["source"]["query"]["bool"]["filter"][0]["term"]["category"],
+            "books"
+        );
+        assert_eq!(body["dest"]["index"], "products-v2");
+        assert_eq!(body["script"]["lang"], "painless");
+        assert!(body.get("max_docs").is_none());
+    }
+
+    #[test]
+    fn completed_tasks_report_their_failures() {
+        let task: ElasticsearchTask = serde_json::from_value(json!({
+            "completed": true,
+            "task": {
+                "status": { "total": 3, "created": 2, "updated": 0, "deleted": 0 }
+            },
+            "response": {
+                "failures": [{ "id": "3", "cause": { "type": "mapper_parsing_exception" } }]
+            }
+        }))
+        .unwrap();
+
+        let status = elasticsearch_task_to_reindex_status(task);
+
+        assert!(status.completed);
+        assert_eq!(status.total, 3);
+        assert_eq!(status.created, 2);
+        assert_eq!(status.failures.len(), 1);
+    }
+}
@@ -0,0 +1,155 @@
+//! Updates by query.
+//!
+//! The component exports the `golem:search-elasticsearch/update-by-query` interface, applying a
+//! painless script or a set of field values to every document matching a query through the
+//! `_update_by_query` API, so that fields are migrated in place instead of exporting and
+//! re-importing the documents. Updates run as background tasks polled like reindexes.
+
+use crate::client::ElasticsearchTask;
+use crate::conversions::search_query_to_elasticsearch_query;
+use crate::ElasticsearchComponent;
+use golem_search::error::invalid_query;
+use golem_search::golem::search::types::{IndexName, SearchError, SearchQuery};
+use golem_search::LOGGING_STATE;
+use serde_json::{json, Map, Value};
+
+#[allow(dead_code)]
+mod bindings {
+    wit_bindgen::generate!({
+        path: "wit",
+        world: "golem:search-elasticsearch/update-by-query-library@1.0.0",
+        with: {
+            "golem:search/types@1.0.0": golem_search::golem::search::types,
+        },
+        generate_all,
+    });
+}
+
+use bindings::exports::golem::search_elasticsearch::update_by_query::{
+    Guest, Mutation, UpdateRequest, UpdateStatus,
+};
+
+/// Painless script setting the `fields` parameter on the source of the documents
+const SET_FIELDS_SCRIPT: &str =
+    "for (entry in params.fields.entrySet()) { ctx._source[entry.getKey()] = entry.getValue(); }";
+
+struct ElasticsearchUpdateByQuery;
+
+impl Guest for ElasticsearchUpdateByQuery {
+    fn update_by_query(index: IndexName, request: UpdateRequest) -> Result<String, SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = ElasticsearchComponent::create_client()?;
+        let proceed_on_conflicts = request.proceed_on_conflicts;
+        let body = update_request_to_elasticsearch(request)?;
+
+        client.update_by_query(&index, &body, proceed_on_conflicts)
+    }
+
+    fn get_update_status(task_id: String) -> Result<UpdateStatus, SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = ElasticsearchComponent::create_client()?;
+
+        Ok(elasticsearch_task_to_update_status(
+            client.get_task(&task_id)?,
+        ))
+    }
+}
+
+fn update_request_to_elasticsearch(request: UpdateRequest) -> Result<Value, SearchError> {
+    let script = match request.mutation {
+        Mutation::Script(source) => json!({
+            "source": source,
+            "lang": "painless"
+        }),
+        Mutation::Fields(fields) => {
+            let fields = serde_json::from_str::<Map<String, Value>>(&fields)
+                .map_err(|e| invalid_query(format!("Invalid fields to update: {e}")))?;
+            json!({
+                "source": SET_FIELDS_SCRIPT,
+                "lang": "painless",
+                "params": { "fields": fields }
+            })
+        }
+    };
+
+    let es_query = search_query_to_elasticsearch_query(SearchQuery {
+        q: request.q,
+        filters: request.filters,
+        sort: vec![],
+        facets: vec![],
+        page: None,
+        per_page: None,
+        offset: None,
+        highlight: None,
+        config: None,
+    });
+
+    Ok(json!({
+        "query": es_query.query.unwrap_or(json!({ "match_all": {} })),
+        "script": script
+    }))
+}
+
+fn elasticsearch_task_to_update_status(task: ElasticsearchTask) -> UpdateStatus {
+    let status = task.task.status.unwrap_or_default();
+
+    let mut failures = task
+        .response
+        .map(|response| response.failures)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|failure| failure.to_string())
+        .collect::<Vec<_>>();
+    if let Some(error) = task.error {
+        failures.push(error.to_string());
+    }
+
+    UpdateStatus {
+        completed: task.completed,
+        total: status.total,
+        updated: status.updated,
+        noops: status.noops,
+        failures,
+    }
+}
+
+bindings::export!(ElasticsearchUpdateByQuery with_types_in bindings);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(mutation: Mutation) -> UpdateRequest {
+        UpdateRequest {
+            q: None,
+            filters: vec!["category:books".to_string()],
+            mutation,
+            proceed_on_conflicts: true,
+        }
+    }
+
+    #[test]
+    fn field_maps_are_set_by_a_script() {
+        let body = update_request_to_elasticsearch(request(Mutation::Fields(
+            r#"{"status": "archived", "views": 0}"#.to_string(),
+        )))
+        .unwrap();
+
+        assert_eq!(body["script"]["source"], SET_FIELDS_SCRIPT);
+        assert_eq!(body["script"]["params"]["fields"]["status"], "archived");
+        assert_eq!(
+            body["query"]["bool"]["filter"][0]["term"]["category"],
+            "books"
+        );
+    }
+
+    #[test]
+    fn invalid_field_maps_are_rejected() {
+        assert!(
+            update_request_to_elasticsearch(request(Mutation::Fields("[1, 2]".to_string())))
+                .is_err()
+        );
+    }
+}
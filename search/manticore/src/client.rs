@@ -0,0 +1,273 @@
+use golem_search::correlation::{self, CORRELATION_ID_HEADER};
+use golem_search::error::{
+    from_reqwest_error, from_send_error, internal_error, search_error_from_failed_response,
+};
+use golem_search::golem::search::types::SearchError;
+use golem_search::logging::RedactedResponse;
+use golem_search::retry::RetryPolicy;
+use log::trace;
+use reqwest::{Client, Method, RequestBuilder, Response};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map as JsonMap, Value as JsonValue};
+use std::fmt::Debug;
+use std::time::Duration;
+
+/// The Manticore Search client, using the JSON HTTP API for documents and searches and the SQL
+/// endpoint for managing tables
+#[derive(Debug, Clone)]
+pub struct ManticoreApi {
+    client: Client,
+    base_url: String,
+    retry_policy: RetryPolicy,
+    request_timeout: Option<Duration>,
+    headers: Vec<(String, String)>,
+}
+
+pub type ManticoreDocument = JsonMap<String, JsonValue>;
+
+/// A result set of the SQL endpoint in raw mode
+#[derive(Debug, Deserialize)]
+pub struct SqlResult {
+    #[serde(default)]
+    pub data: Vec<JsonMap<String, JsonValue>>,
+    #[serde(default)]
+    pub error: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BulkResponse {
+    #[serde(default)]
+    pub errors: bool,
+    #[serde(default)]
+    pub error: String,
+    #[serde(default)]
+    pub items: Vec<JsonValue>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SearchRequest {
+    /// The table to search
+    pub index: String,
+    pub query: JsonValue,
+    pub limit: u32,
+    pub offset: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sort: Option<Vec<JsonValue>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub highlight: Option<JsonValue>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub aggs: Option<JsonMap<String, JsonValue>>,
+    #[serde(rename = "_source", skip_serializing_if = "Option::is_none")]
+    pub source: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub options: Option<JsonMap<String, JsonValue>>,
+    /// The provider parameters of the query, added to the request as they are
+    #[serde(flatten)]
+    pub extra: JsonMap<String, JsonValue>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SearchHit {
+    /// A number, or a string in recent versions of Manticore
+    #[serde(rename = "_id")]
+    pub id: JsonValue,
+    #[serde(rename = "_score", default)]
+    pub score: Option<f64>,
+    #[serde(rename = "_source", default)]
+    pub source: ManticoreDocument,
+    #[serde(default)]
+    pub highlight: Option<JsonMap<String, JsonValue>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SearchHits {
+    #[serde(default)]
+    pub total: Option<u64>,
+    #[serde(default)]
+    pub hits: Vec<SearchHit>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SearchResponse {
+    #[serde(default)]
+    pub took: Option<u32>,
+    pub hits: SearchHits,
+    #[serde(default)]
+    pub aggregations: Option<JsonMap<String, JsonValue>>,
+}
+
+impl ManticoreApi {
+    pub fn new(base_url: String) -> Self {
+        let client = Client::builder()
+            .build()
+            .expect("Failed to initialize HTTP client");
+
+        Self {
+            client,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            retry_policy: RetryPolicy::from_config(),
+            request_timeout: None,
+            headers: Vec::new(),
+        }
+    }
+
+    /// Applies a timeout to every request sent by this client
+    pub fn with_request_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.request_timeout = timeout;
+        self
+    }
+
+    /// Adds custom headers to every request sent by this client
+    pub fn with_headers(mut self, headers: Vec<(String, String)>) -> Self {
+        self.headers = headers;
+        self
+    }
+
+    fn create_request(&self, method: Method, url: &str, content_type: &str) -> RequestBuilder {
+        trace!("[Manticore] HTTP {method} {url}");
+
+        let mut req = self
+            .client
+            .request(method, url)
+            .header("Content-Type", content_type)
+            .header(CORRELATION_ID_HEADER, correlation::current());
+
+        if let Some(timeout) = self.request_timeout {
+            req = req.timeout(timeout);
+        }
+
+        for (name, value) in &self.headers {
+            req = req.header(name, value);
+        }
+
+        req
+    }
+}
+
+/// The error of a failed response. Manticore reports missing tables as failed requests with a
+/// message, which are mapped to `SearchError::IndexNotFound`.
+fn manticore_error(response: Response) -> SearchError {
+    match search_error_from_failed_response(response) {
+        SearchError::InvalidQuery(message) if is_missing_table(&message) => {
+            SearchError::IndexNotFound
+        }
+        SearchError::ProviderError(details) if is_missing_table(&details.message) => {
+            SearchError::IndexNotFound
+        }
+        error => error,
+    }
+}
+
+fn is_missing_table(message: &str) -> bool {
+    let message = message.to_lowercase();
+    message.contains("unknown table")
+        || message.contains("unknown local table")
+        || message.contains("no such table")
+        || message.contains("no such index")
+}
+
+fn parse_response<T: DeserializeOwned + Debug>(response: Response) -> Result<T, SearchError> {
+    let status = response.status();
+
+    trace!(
+        "Received response from Manticore API: {:?}",
+        RedactedResponse(&response)
+    );
+
+    if status.is_success() {
+        let body = response
+            .json::<T>()
+            .map_err(|err| from_reqwest_error("Failed to decode response body", err))?;
+
+        trace!("Received response from Manticore API: {body:?}");
+
+        Ok(body)
+    } else {
+        let error = manticore_error(response);
+
+        trace!("Received {status} response from Manticore API: {error:?}");
+
+        Err(error)
+    }
+}
+
+impl ManticoreApi {
+    /// Runs an SQL statement through the `/sql` endpoint in raw mode, returning the rows of its
+    /// result set
+    pub fn sql(&self, statement: &str) -> Result<Vec<JsonMap<String, JsonValue>>, SearchError> {
+        trace!("Running SQL statement: {statement}");
+
+        let url = format!("{}/sql?mode=raw", self.base_url);
+        let body = format!("query={}", urlencoding::encode(statement));
+        let response = self
+            .retry_policy
+            .execute(|| {
+                self.create_request(Method::POST, &url, "application/x-www-form-urlencoded")
+                    .body(body.clone())
+                    .send()
+            })
+            .map_err(|e| from_send_error("Failed to run SQL statement", e))?;
+
+        let results: Vec<SqlResult> = parse_response(response)?;
+        let mut rows = Vec::new();
+        for result in results {
+            if !result.error.is_empty() {
+                return Err(if is_missing_table(&result.error) {
+                    SearchError::IndexNotFound
+                } else {
+                    SearchError::InvalidQuery(result.error)
+                });
+            }
+            rows.extend(result.data);
+        }
+        Ok(rows)
+    }
+
+    /// Sends `replace` and `delete` operations, one JSON object per line, to the `/bulk`
+    /// endpoint
+    pub fn bulk(&self, operations: &str) -> Result<(), SearchError> {
+        trace!("Performing bulk operation");
+
+        let url = format!("{}/bulk", self.base_url);
+        let response = self
+            .retry_policy
+            .execute(|| {
+                self.create_request(Method::POST, &url, "application/x-ndjson")
+                    .body(operations.to_string())
+                    .send()
+            })
+            .map_err(|e| from_send_error("Failed to perform bulk operation", e))?;
+
+        let response: BulkResponse = parse_response(response)?;
+        if response.errors || !response.error.is_empty() {
+            let message = if response.error.is_empty() {
+                JsonValue::Array(response.items).to_string()
+            } else {
+                response.error
+            };
+            return Err(if is_missing_table(&message) {
+                SearchError::IndexNotFound
+            } else {
+                internal_error(format!("Bulk operation failed: {message}"))
+            });
+        }
+        Ok(())
+    }
+
+    pub fn search(&self, request: &SearchRequest) -> Result<SearchResponse, SearchError> {
+        trace!("Searching table: {}", request.index);
+
+        let url = format!("{}/search", self.base_url);
+        let response = self
+            .retry_policy
+            .execute(|| {
+                self.create_request(Method::POST, &url, "application/json")
+                    .json(request)
+                    .send()
+            })
+            .map_err(|e| from_send_error("Failed to search", e))?;
+
+        parse_response(response)
+    }
+}
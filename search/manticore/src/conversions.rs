@@ -0,0 +1,1016 @@
+use crate::client::{ManticoreDocument, SearchRequest, SearchResponse};
+use golem_search::config::{get_max_pagination_depth_config, search_filters_match_any};
+use golem_search::error::invalid_query;
+use golem_search::golem::search::types::{
+    Doc, FieldType, Schema, SchemaField, SearchError, SearchHit, SearchQuery,
+};
+use serde_json::{json, Map as JsonMap, Value as JsonValue};
+
+/// String attribute holding the id of a document, as Manticore ids are numbers
+pub const ID_FIELD: &str = "golem_id";
+/// JSON attribute holding the whole document, returned as the content of the hits
+pub const DOC_FIELD: &str = "doc";
+/// Full-text field of the tables created without a schema, holding the text of the string fields
+/// of the documents
+pub const CONTENT_FIELD: &str = "golem_text";
+
+const DEFAULT_PER_PAGE: u32 = 20;
+const MAX_FACET_VALUES: u32 = 100;
+
+/// Manticore only returns the first `max_matches` matches of a query, 1000 by default
+const DEFAULT_MAX_MATCHES: u32 = 1_000;
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+/// A column of a table, as listed by `DESCRIBE`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Column {
+    pub name: String,
+    pub column_type: String,
+}
+
+/// Whether a name can be used as a table or column name without quoting
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn is_field_name(key: &str) -> bool {
+    !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.')
+}
+
+pub fn check_table_name(name: &str) -> Result<(), SearchError> {
+    if is_identifier(name) {
+        Ok(())
+    } else {
+        Err(invalid_query(format!(
+            "Invalid Manticore table name `{name}`"
+        )))
+    }
+}
+
+/// The column definition of a schema field. Text fields are full-text fields, and faceted or
+/// sorted text fields are also stored as string attributes. Dates are stored as strings, which
+/// sort in order for ISO 8601 dates.
+fn schema_field_to_column(field: &SchemaField) -> Result<String, SearchError> {
+    if !is_identifier(&field.name)
+        || [ID_FIELD, DOC_FIELD, CONTENT_FIELD].contains(&field.name.as_str())
+    {
+        return Err(invalid_query(format!(
+            "Invalid Manticore field name `{}`",
+            field.name
+        )));
+    }
+    let column_type = match field.field_type {
+        FieldType::Text if field.facet || field.sort => "string attribute indexed",
+        FieldType::Text => "text",
+        FieldType::Keyword | FieldType::Date => "string",
+        FieldType::Integer => "bigint",
+        FieldType::Float => "float",
+        FieldType::Boolean => "bool",
+        FieldType::GeoPoint => {
+            return Err(invalid_query(format!(
+                "Manticore has no geo point fields: `{}`",
+                field.name
+            )))
+        }
+    };
+    Ok(format!("{} {column_type}", field.name))
+}
+
+/// The statement creating the table of an index, with the fields of the schema, or with a single
+/// full-text field for the text of the documents when there is no schema
+pub fn create_table_statement(index: &str, schema: Option<&Schema>) -> Result<String, SearchError> {
+    check_table_name(index)?;
+
+    let mut columns = match schema {
+        Some(schema) => schema
+            .fields
+            .iter()
+            .map(schema_field_to_column)
+            .collect::<Result<Vec<_>, _>>()?,
+        None => vec![format!("{CONTENT_FIELD} text")],
+    };
+    columns.push(format!("{ID_FIELD} string"));
+    columns.push(format!("{DOC_FIELD} json"));
+
+    Ok(format!("CREATE TABLE {index} ({})", columns.join(", ")))
+}
+
+/// The statements adding the fields of a schema missing from the columns of a table. Manticore
+/// can't change the type of an existing column, so the other fields are left as they are.
+pub fn add_columns_statements(
+    index: &str,
+    columns: &[Column],
+    schema: &Schema,
+) -> Result<Vec<String>, SearchError> {
+    check_table_name(index)?;
+
+    schema
+        .fields
+        .iter()
+        .filter(|field| !columns.iter().any(|column| column.name == field.name))
+        .map(|field| {
+            Ok(format!(
+                "ALTER TABLE {index} ADD COLUMN {}",
+                schema_field_to_column(field)?
+            ))
+        })
+        .collect()
+}
+
+/// The columns of a table, from the rows of `DESCRIBE`
+pub fn rows_to_columns(rows: Vec<JsonMap<String, JsonValue>>) -> Vec<Column> {
+    rows.into_iter()
+        .filter_map(|row| {
+            Some(Column {
+                name: row.get("Field")?.as_str()?.to_string(),
+                column_type: row.get("Type")?.as_str()?.to_lowercase(),
+            })
+        })
+        .collect()
+}
+
+/// The schema of a table, from its columns. Text fields that are also string attributes are read
+/// back as faceted text fields.
+pub fn columns_to_schema(columns: &[Column]) -> Schema {
+    let mut fields: Vec<SchemaField> = Vec::new();
+    for column in columns {
+        if ["id", ID_FIELD, DOC_FIELD, CONTENT_FIELD].contains(&column.name.as_str()) {
+            continue;
+        }
+        let field_type = match column.column_type.as_str() {
+            "text" | "field" => FieldType::Text,
+            "string" => FieldType::Keyword,
+            "int" | "uint" | "bigint" | "timestamp" => FieldType::Integer,
+            "float" | "double" => FieldType::Float,
+            "bool" => FieldType::Boolean,
+            _ => continue,
+        };
+        if let Some(field) = fields.iter_mut().find(|field| field.name == column.name) {
+            field.field_type = FieldType::Text;
+            field.facet = true;
+            continue;
+        }
+        fields.push(SchemaField {
+            name: column.name.clone(),
+            field_type,
+            required: false,
+            facet: false,
+            sort: false,
+            index: true,
+        });
+    }
+
+    Schema {
+        fields,
+        primary_key: Some(ID_FIELD.to_string()),
+    }
+}
+
+/// The numeric id of a document: the id itself when it is a positive number, or a 63-bit FNV-1a
+/// hash of it otherwise. The original id is kept in the `golem_id` attribute.
+pub fn document_key(id: &str) -> u64 {
+    if let Ok(key) = id.parse::<u64>() {
+        if key > 0 && key <= i64::MAX as u64 && key.to_string() == id {
+            return key;
+        }
+    }
+    let mut hash = FNV_OFFSET_BASIS;
+    for byte in id.bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(FNV_PRIME);
+    }
+    (hash & i64::MAX as u64).max(1)
+}
+
+/// The value of a document field for a column of a table, or `None` when it has the wrong type
+fn column_value(column_type: &str, value: &JsonValue) -> Option<JsonValue> {
+    match column_type {
+        "text" | "field" | "string" => match value {
+            JsonValue::String(_) => Some(value.clone()),
+            JsonValue::Null => None,
+            value => Some(JsonValue::String(value.to_string())),
+        },
+        "int" | "uint" | "bigint" | "timestamp" => value.as_i64().map(JsonValue::from),
+        "float" | "double" => value.as_f64().map(JsonValue::from),
+        "bool" => value.as_bool().map(JsonValue::from),
+        _ => None,
+    }
+}
+
+/// The `replace` operation of a bulk request upserting a document. The fields of the document
+/// that are columns of the table are copied to them, and the whole document is kept in the `doc`
+/// attribute.
+pub fn replace_operation(index: &str, doc: Doc, columns: &[Column]) -> Result<String, SearchError> {
+    let document = match serde_json::from_str::<JsonValue>(&doc.content) {
+        Ok(JsonValue::Object(document)) => document,
+        Ok(_) => {
+            return Err(invalid_query(format!(
+                "Content of document {} is not a JSON object",
+                doc.id
+            )))
+        }
+        Err(e) => {
+            return Err(invalid_query(format!(
+                "Invalid JSON content of document {}: {e}",
+                doc.id
+            )))
+        }
+    };
+
+    let mut fields = JsonMap::new();
+    for column in columns {
+        if column.name == CONTENT_FIELD {
+            let text = document
+                .values()
+                .filter_map(JsonValue::as_str)
+                .collect::<Vec<_>>()
+                .join("\n");
+            fields.insert(CONTENT_FIELD.to_string(), JsonValue::String(text));
+            continue;
+        }
+        if let Some(value) = document
+            .get(&column.name)
+            .and_then(|value| column_value(&column.column_type, value))
+        {
+            fields.insert(column.name.clone(), value);
+        }
+    }
+    fields.insert(ID_FIELD.to_string(), JsonValue::String(doc.id.clone()));
+    fields.insert(DOC_FIELD.to_string(), JsonValue::Object(document));
+
+    Ok(json!({
+        "replace": { "index": index, "id": document_key(&doc.id), "doc": fields }
+    })
+    .to_string())
+}
+
+/// The `delete` operation of a bulk request deleting a document
+pub fn delete_operation(index: &str, id: &str) -> String {
+    json!({ "delete": { "index": index, "id": document_key(id) } }).to_string()
+}
+
+fn hit_id(hit: &crate::client::SearchHit) -> String {
+    match hit.source.get(ID_FIELD) {
+        Some(JsonValue::String(id)) => id.clone(),
+        _ => match &hit.id {
+            JsonValue::String(id) => id.clone(),
+            id => id.to_string(),
+        },
+    }
+}
+
+/// The document of a hit: the `doc` attribute, returned as an object or as a string by different
+/// Manticore versions, or the other attributes for tables without one
+fn hit_document(source: &ManticoreDocument) -> ManticoreDocument {
+    match source.get(DOC_FIELD) {
+        Some(JsonValue::Object(document)) => document.clone(),
+        Some(JsonValue::String(document)) => serde_json::from_str(document).unwrap_or_default(),
+        _ => source
+            .iter()
+            .filter(|(name, _)| ![ID_FIELD, CONTENT_FIELD].contains(&name.as_str()))
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect(),
+    }
+}
+
+/// A hit with the document of the `doc` attribute as its content, keeping only the attributes to
+/// retrieve of the query when it has some
+pub fn hit_to_search_hit(hit: crate::client::SearchHit, attributes: &[String]) -> SearchHit {
+    let mut document = hit_document(&hit.source);
+    if !attributes.is_empty() {
+        document.retain(|name, _| attributes.contains(name));
+    }
+    SearchHit {
+        id: hit_id(&hit),
+        score: hit.score,
+        content: Some(JsonValue::Object(document).to_string()),
+        highlights: hit
+            .highlight
+            .filter(|highlight| !highlight.is_empty())
+            .map(|highlight| JsonValue::Object(highlight).to_string()),
+    }
+}
+
+/// The document of the hit of a lookup by id, unless the hit is another document whose id has
+/// the same hash
+pub fn hit_to_doc(hit: crate::client::SearchHit, id: &str) -> Option<Doc> {
+    if hit_id(&hit) != id {
+        return None;
+    }
+    Some(Doc {
+        id: id.to_string(),
+        content: JsonValue::Object(hit_document(&hit.source)).to_string(),
+    })
+}
+
+/// A filter value: numbers and booleans as they are, anything else as a string
+fn filter_value(value: &str) -> JsonValue {
+    let value = value.trim();
+    if let Ok(number) = value.parse::<i64>() {
+        return JsonValue::from(number);
+    }
+    if let Ok(number) = value.parse::<f64>() {
+        return JsonValue::from(number);
+    }
+    match value {
+        "true" => JsonValue::Bool(true),
+        "false" => JsonValue::Bool(false),
+        value => JsonValue::String(
+            value
+                .strip_prefix('"')
+                .and_then(|value| value.strip_suffix('"'))
+                .unwrap_or(value)
+                .to_string(),
+        ),
+    }
+}
+
+/// Converts a filter to a Manticore query clause. Filters are `field:value`, `field=value`,
+/// `field!=value`, and `field>value` style comparisons with `>`, `>=`, `<` and `<=`. Fields of
+/// tables without a schema are filtered through their `doc` attribute, as in `doc.genre:scifi`.
+fn filter_to_manticore(filter: &str) -> Result<JsonValue, SearchError> {
+    let filter = filter.trim();
+    let position = filter
+        .find([':', '=', '!', '<', '>'])
+        .ok_or_else(|| invalid_query(format!("Unsupported filter `{filter}`")))?;
+    let key = filter[..position].trim();
+    let rest = &filter[position..];
+    let (op, value) = [">=", "<=", "!=", ">", "<", "=", ":"]
+        .into_iter()
+        .find_map(|op| Some((op, rest.strip_prefix(op)?)))
+        .ok_or_else(|| invalid_query(format!("Unsupported filter `{filter}`")))?;
+    if !is_field_name(key) || value.trim().is_empty() {
+        return Err(invalid_query(format!("Unsupported filter `{filter}`")));
+    }
+    let value = filter_value(value);
+
+    Ok(match op {
+        ":" | "=" => json!({ "equals": { key: value } }),
+        "!=" => json!({ "bool": { "must_not": [{ "equals": { key: value } }] } }),
+        ">=" => json!({ "range": { key: { "gte": value } } }),
+        "<=" => json!({ "range": { key: { "lte": value } } }),
+        ">" => json!({ "range": { key: { "gt": value } } }),
+        _ => json!({ "range": { key: { "lt": value } } }),
+    })
+}
+
+/// The clause matching all the filters of a query, or any of them when its filter mode asks for
+/// it
+pub fn query_to_filter(query: &SearchQuery) -> Result<Option<JsonValue>, SearchError> {
+    let mut clauses = query
+        .filters
+        .iter()
+        .map(|filter| filter_to_manticore(filter))
+        .collect::<Result<Vec<_>, _>>()?;
+    if clauses.len() <= 1 {
+        return Ok(clauses.pop());
+    }
+    let occurrence = if search_filters_match_any(query) {
+        "should"
+    } else {
+        "must"
+    };
+    Ok(Some(json!({ "bool": { occurrence: clauses } })))
+}
+
+/// The sort of a query, from its `field`, `field:asc`, `field:desc` or `-field` sort entries,
+/// where `score` sorts by relevance
+fn query_to_sort(sort: &[String]) -> Result<Option<Vec<JsonValue>>, SearchError> {
+    let sort = sort
+        .iter()
+        .map(|sort| {
+            let sort = sort.trim();
+            let (key, order) = if let Some(key) = sort.strip_prefix('-') {
+                (key, "desc")
+            } else {
+                match sort.rsplit_once(':') {
+                    Some((key, order)) if order.eq_ignore_ascii_case("asc") => (key, "asc"),
+                    Some((key, order)) if order.eq_ignore_ascii_case("desc") => (key, "desc"),
+                    Some(_) => return Err(invalid_query(format!("Invalid sort `{sort}`"))),
+                    None => (sort, "asc"),
+                }
+            };
+            if !is_field_name(key) {
+                return Err(invalid_query(format!("Invalid sort `{sort}`")));
+            }
+            let key = if key == "score" { "_score" } else { key };
+            Ok(json!({ key: order }))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok((!sort.is_empty()).then_some(sort))
+}
+
+/// The terms aggregations counting the values of the query's facets
+fn query_to_aggs(facets: &[String]) -> Result<JsonMap<String, JsonValue>, SearchError> {
+    facets
+        .iter()
+        .map(|facet| {
+            if !is_field_name(facet) {
+                return Err(invalid_query(format!("Invalid facet `{facet}`")));
+            }
+            Ok((
+                facet.clone(),
+                json!({ "terms": { "field": facet, "size": MAX_FACET_VALUES } }),
+            ))
+        })
+        .collect()
+}
+
+/// Facet counts in the same shape as the other providers: `{"field": {"value": count}}`
+pub fn facets_to_counts(aggregations: &JsonMap<String, JsonValue>) -> Option<String> {
+    let counts = aggregations
+        .iter()
+        .filter_map(|(field, aggregation)| {
+            let counts = aggregation
+                .get("buckets")?
+                .as_array()?
+                .iter()
+                .filter_map(|bucket| {
+                    let value = match bucket.get("key")? {
+                        JsonValue::String(value) => value.clone(),
+                        value => value.to_string(),
+                    };
+                    Some((value, bucket.get("doc_count")?.clone()))
+                })
+                .collect::<JsonMap<_, _>>();
+            Some((field.clone(), JsonValue::Object(counts)))
+        })
+        .collect::<JsonMap<_, _>>();
+    (!counts.is_empty()).then(|| JsonValue::Object(counts).to_string())
+}
+
+/// Number of hits to skip and number of hits per page of a query
+pub fn query_window(query: &SearchQuery) -> Result<(u32, u32), SearchError> {
+    let per_page = query.per_page.unwrap_or(DEFAULT_PER_PAGE);
+    let offset = query.offset.unwrap_or_else(|| {
+        query
+            .page
+            .unwrap_or(1)
+            .saturating_sub(1)
+            .saturating_mul(per_page)
+    });
+    if offset.saturating_add(per_page) > get_max_pagination_depth_config() {
+        return Err(invalid_query(format!(
+            "Requested hits beyond the maximum pagination depth of {}",
+            get_max_pagination_depth_config()
+        )));
+    }
+    Ok((offset, per_page))
+}
+
+/// Builds the search request of a query. The text is matched against all the full-text fields,
+/// weighted by the boosts of the query rounded to whole field weights, and pages beyond the first thousand matches raise
+/// `max_matches`. The provider parameters of the query are added to the request, for example
+/// `{"options": {"ranker": "bm25"}}`.
+pub fn query_to_search_request(
+    index: &str,
+    query: &SearchQuery,
+) -> Result<SearchRequest, SearchError> {
+    let (offset, per_page) = query_window(query)?;
+    let config = query.config.as_ref();
+
+    let mut must = Vec::new();
+    if let Some(q) = query.q.as_deref().map(str::trim).filter(|q| !q.is_empty()) {
+        must.push(json!({ "match": { "*": q } }));
+    }
+    if let Some(filter) = query_to_filter(query)? {
+        must.push(filter);
+    }
+    let search_query = if must.is_empty() {
+        json!({ "match_all": {} })
+    } else {
+        json!({ "bool": { "must": must } })
+    };
+
+    let mut options = JsonMap::new();
+    if offset + per_page > DEFAULT_MAX_MATCHES {
+        options.insert("max_matches".to_string(), json!(offset + per_page));
+    }
+    if let Some(config) = config.filter(|config| !config.boost_fields.is_empty()) {
+        let weights = config
+            .boost_fields
+            .iter()
+            .map(|(field, boost)| (field.clone(), json!(boost.round().max(1.0) as u32)))
+            .collect::<JsonMap<_, _>>();
+        options.insert("field_weights".to_string(), JsonValue::Object(weights));
+    }
+
+    let highlight = query.highlight.as_ref().map(|highlight| {
+        let mut request = JsonMap::new();
+        if !highlight.fields.is_empty() {
+            request.insert("fields".to_string(), json!(highlight.fields));
+        }
+        if let Some(pre_tag) = &highlight.pre_tag {
+            request.insert("pre_tags".to_string(), json!(pre_tag));
+        }
+        if let Some(post_tag) = &highlight.post_tag {
+            request.insert("post_tags".to_string(), json!(post_tag));
+        }
+        if let Some(max_length) = highlight.max_length {
+            request.insert("limit".to_string(), json!(max_length));
+        }
+        JsonValue::Object(request)
+    });
+
+    let aggs = query_to_aggs(&query.facets)?;
+
+    let mut extra = JsonMap::new();
+    if let Some(provider_params) = config.and_then(|config| config.provider_params.as_deref()) {
+        let provider_params =
+            serde_json::from_str::<JsonMap<String, JsonValue>>(provider_params)
+                .map_err(|e| invalid_query(format!("Invalid provider parameters: {e}")))?;
+        for (key, value) in provider_params {
+            match (key.as_str(), value) {
+                ("headers", _) => {}
+                ("options", JsonValue::Object(provider_options)) => {
+                    options.extend(provider_options)
+                }
+                (_, value) => {
+                    extra.insert(key, value);
+                }
+            }
+        }
+    }
+
+    Ok(SearchRequest {
+        index: index.to_string(),
+        query: search_query,
+        limit: per_page,
+        offset,
+        sort: query_to_sort(&query.sort)?,
+        highlight,
+        aggs: (!aggs.is_empty()).then_some(aggs),
+        source: None,
+        options: (!options.is_empty()).then_some(options),
+        extra,
+    })
+}
+
+/// The request looking a document up by its numeric id
+pub fn lookup_request(index: &str, id: &str) -> SearchRequest {
+    SearchRequest {
+        index: index.to_string(),
+        query: json!({ "equals": { "id": document_key(id) } }),
+        limit: 1,
+        offset: 0,
+        sort: None,
+        highlight: None,
+        aggs: None,
+        source: None,
+        options: None,
+        extra: JsonMap::new(),
+    }
+}
+
+/// The hits, total and facet counts of a search response
+pub fn parse_search_response(
+    response: SearchResponse,
+    query: &SearchQuery,
+) -> (Vec<SearchHit>, Option<u32>, Option<String>) {
+    let attributes = query
+        .config
+        .as_ref()
+        .map(|config| config.attributes_to_retrieve.as_slice())
+        .unwrap_or_default();
+    let hits = response
+        .hits
+        .hits
+        .into_iter()
+        .map(|hit| hit_to_search_hit(hit, attributes))
+        .collect();
+    (
+        hits,
+        response.hits.total.map(|total| total as u32),
+        response.aggregations.as_ref().and_then(facets_to_counts),
+    )
+}
+
+pub fn create_retry_query(original_query: &SearchQuery, partial_hits: &[SearchHit]) -> SearchQuery {
+    let mut retry_query = original_query.clone();
+
+    let current_offset = original_query.offset.unwrap_or(0);
+    let per_page = original_query.per_page.unwrap_or(DEFAULT_PER_PAGE);
+    let hits_received = partial_hits.len() as u32;
+
+    retry_query.offset = Some(current_offset + hits_received.min(per_page));
+
+    retry_query
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::SearchHits;
+    use golem_search::golem::search::types::{FilterMode, HighlightConfig, SearchConfig};
+
+    fn query(q: Option<&str>, filters: &[&str]) -> SearchQuery {
+        SearchQuery {
+            q: q.map(str::to_string),
+            filters: filters.iter().map(|filter| filter.to_string()).collect(),
+            sort: vec![],
+            facets: vec![],
+            page: None,
+            per_page: None,
+            offset: None,
+            highlight: None,
+            config: None,
+        }
+    }
+
+    fn field(name: &str, field_type: FieldType, facet: bool) -> SchemaField {
+        SchemaField {
+            name: name.to_string(),
+            field_type,
+            required: false,
+            facet,
+            sort: false,
+            index: true,
+        }
+    }
+
+    fn config(provider_params: Option<&str>, filter_mode: Option<FilterMode>) -> SearchConfig {
+        SearchConfig {
+            timeout_ms: None,
+            boost_fields: vec![],
+            attributes_to_retrieve: vec![],
+            language: None,
+            typo_tolerance: None,
+            exact_match_boost: None,
+            provider_params: provider_params.map(str::to_string),
+            profile: None,
+            filter_mode,
+            post_filters: vec![],
+            rescore: None,
+            fallbacks: vec![],
+        }
+    }
+
+    fn column(name: &str, column_type: &str) -> Column {
+        Column {
+            name: name.to_string(),
+            column_type: column_type.to_string(),
+        }
+    }
+
+    fn object(value: JsonValue) -> JsonMap<String, JsonValue> {
+        match value {
+            JsonValue::Object(object) => object,
+            _ => panic!("Not a JSON object"),
+        }
+    }
+
+    fn book_schema() -> Schema {
+        Schema {
+            fields: vec![
+                field("title", FieldType::Text, false),
+                field("genre", FieldType::Text, true),
+                field("isbn", FieldType::Keyword, false),
+                field("year", FieldType::Integer, false),
+                field("rating", FieldType::Float, false),
+                field("available", FieldType::Boolean, false),
+            ],
+            primary_key: None,
+        }
+    }
+
+    #[test]
+    fn test_create_table_statement() {
+        assert_eq!(
+            create_table_statement("books", Some(&book_schema())).unwrap(),
+            "CREATE TABLE books (title text, genre string attribute indexed, isbn string, \
+             year bigint, rating float, available bool, golem_id string, doc json)"
+        );
+        assert_eq!(
+            create_table_statement("notes", None).unwrap(),
+            "CREATE TABLE notes (golem_text text, golem_id string, doc json)"
+        );
+
+        assert!(create_table_statement("books; DROP TABLE x", None).is_err());
+        for field in [
+            field("location", FieldType::GeoPoint, false),
+            field("author name", FieldType::Text, false),
+            field("doc", FieldType::Keyword, false),
+        ] {
+            let schema = Schema {
+                fields: vec![field],
+                primary_key: None,
+            };
+            assert!(matches!(
+                create_table_statement("books", Some(&schema)),
+                Err(SearchError::InvalidQuery(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_columns_to_schema() {
+        let columns = rows_to_columns(vec![
+            object(json!({ "Field": "id", "Type": "bigint", "Properties": "" })),
+            object(json!({ "Field": "title", "Type": "text", "Properties": "indexed stored" })),
+            object(json!({ "Field": "genre", "Type": "text", "Properties": "indexed" })),
+            object(json!({ "Field": "genre", "Type": "string", "Properties": "" })),
+            object(json!({ "Field": "isbn", "Type": "string", "Properties": "" })),
+            object(json!({ "Field": "year", "Type": "bigint", "Properties": "" })),
+            object(json!({ "Field": "rating", "Type": "float", "Properties": "" })),
+            object(json!({ "Field": "available", "Type": "bool", "Properties": "" })),
+            object(json!({ "Field": "golem_id", "Type": "string", "Properties": "" })),
+            object(json!({ "Field": "doc", "Type": "json", "Properties": "" })),
+        ]);
+        assert_eq!(
+            columns_to_schema(&columns),
+            Schema {
+                primary_key: Some(ID_FIELD.to_string()),
+                ..book_schema()
+            }
+        );
+    }
+
+    #[test]
+    fn test_add_columns_statements() {
+        let columns = vec![column("title", "text"), column("year", "bigint")];
+        let schema = Schema {
+            fields: vec![
+                field("title", FieldType::Text, false),
+                field("year", FieldType::Float, false),
+                field("pages", FieldType::Integer, false),
+            ],
+            primary_key: None,
+        };
+        assert_eq!(
+            add_columns_statements("books", &columns, &schema).unwrap(),
+            vec!["ALTER TABLE books ADD COLUMN pages bigint"]
+        );
+    }
+
+    #[test]
+    fn test_document_key() {
+        assert_eq!(document_key("42"), 42);
+        assert_ne!(document_key("042"), 42);
+        assert_ne!(document_key("0"), 0);
+        assert_eq!(document_key("doc1"), document_key("doc1"));
+        assert_ne!(document_key("doc1"), document_key("doc2"));
+        assert!(document_key("doc1") <= i64::MAX as u64);
+        assert!(document_key(&u64::MAX.to_string()) <= i64::MAX as u64);
+    }
+
+    #[test]
+    fn test_replace_operation() {
+        let doc = Doc {
+            id: "doc1".to_string(),
+            content: r#"{"title":"Dune","year":1965,"rating":"high","tags":["a"]}"#.to_string(),
+        };
+        let columns = vec![
+            column("title", "text"),
+            column("year", "bigint"),
+            column("rating", "float"),
+            column(ID_FIELD, "string"),
+            column(DOC_FIELD, "json"),
+        ];
+        let operation: JsonValue =
+            serde_json::from_str(&replace_operation("books", doc.clone(), &columns).unwrap())
+                .unwrap();
+        assert_eq!(
+            operation,
+            json!({
+                "replace": {
+                    "index": "books",
+                    "id": document_key("doc1"),
+                    "doc": {
+                        "title": "Dune",
+                        "year": 1965,
+                        "golem_id": "doc1",
+                        "doc": { "title": "Dune", "year": 1965, "rating": "high", "tags": ["a"] },
+                    },
+                },
+            })
+        );
+
+        let columns = vec![column(CONTENT_FIELD, "text")];
+        let operation: JsonValue =
+            serde_json::from_str(&replace_operation("notes", doc, &columns).unwrap()).unwrap();
+        assert_eq!(
+            operation["replace"]["doc"][CONTENT_FIELD],
+            json!("high\nDune")
+        );
+
+        let doc = Doc {
+            id: "doc1".to_string(),
+            content: "[1]".to_string(),
+        };
+        assert!(matches!(
+            replace_operation("books", doc, &[]),
+            Err(SearchError::InvalidQuery(_))
+        ));
+    }
+
+    #[test]
+    fn test_delete_operation() {
+        assert_eq!(
+            delete_operation("books", "42"),
+            r#"{"delete":{"id":42,"index":"books"}}"#
+        );
+    }
+
+    #[test]
+    fn test_hits() {
+        let hit: crate::client::SearchHit = serde_json::from_value(json!({
+            "_id": "7334",
+            "_score": 1680,
+            "_source": {
+                "title": "Dune",
+                "golem_id": "doc1",
+                "doc": { "title": "Dune", "year": 1965 },
+            },
+            "highlight": { "title": ["<b>Dune</b>"] },
+        }))
+        .unwrap();
+
+        let search_hit = hit_to_search_hit(hit.clone(), &[]);
+        assert_eq!(search_hit.id, "doc1");
+        assert_eq!(search_hit.score, Some(1680.0));
+        assert_eq!(
+            search_hit.content.as_deref(),
+            Some(r#"{"title":"Dune","year":1965}"#)
+        );
+        assert_eq!(
+            search_hit.highlights.as_deref(),
+            Some(r#"{"title":["<b>Dune</b>"]}"#)
+        );
+        assert_eq!(
+            hit_to_search_hit(hit.clone(), &["year".to_string()])
+                .content
+                .as_deref(),
+            Some(r#"{"year":1965}"#)
+        );
+
+        assert_eq!(
+            hit_to_doc(hit.clone(), "doc1"),
+            Some(Doc {
+                id: "doc1".to_string(),
+                content: r#"{"title":"Dune","year":1965}"#.to_string(),
+            })
+        );
+        assert_eq!(hit_to_doc(hit, "doc2"), None);
+    }
+
+    #[test]
+    fn test_filters_to_manticore() {
+        assert_eq!(
+            filter_to_manticore("genre:sci-fi").unwrap(),
+            json!({ "equals": { "genre": "sci-fi" } })
+        );
+        assert_eq!(
+            filter_to_manticore(r#"title = "Dune Messiah""#).unwrap(),
+            json!({ "equals": { "title": "Dune Messiah" } })
+        );
+        assert_eq!(
+            filter_to_manticore("doc.available=true").unwrap(),
+            json!({ "equals": { "doc.available": true } })
+        );
+        assert_eq!(
+            filter_to_manticore("genre!=fantasy").unwrap(),
+            json!({ "bool": { "must_not": [{ "equals": { "genre": "fantasy" } }] } })
+        );
+        assert_eq!(
+            filter_to_manticore("year>=1965").unwrap(),
+            json!({ "range": { "year": { "gte": 1965 } } })
+        );
+        assert_eq!(
+            filter_to_manticore("rating<4.5").unwrap(),
+            json!({ "range": { "rating": { "lt": 4.5 } } })
+        );
+        assert!(filter_to_manticore("year").is_err());
+        assert!(filter_to_manticore("year>").is_err());
+        assert!(filter_to_manticore("a b:c").is_err());
+    }
+
+    #[test]
+    fn test_query_to_filter() {
+        assert_eq!(query_to_filter(&query(None, &[])).unwrap(), None);
+
+        let mut search = query(None, &["genre:scifi", "year>=1965"]);
+        assert_eq!(
+            query_to_filter(&search).unwrap(),
+            Some(json!({ "bool": { "must": [
+                { "equals": { "genre": "scifi" } },
+                { "range": { "year": { "gte": 1965 } } },
+            ] } }))
+        );
+
+        search.config = Some(config(None, Some(FilterMode::MatchAny)));
+        assert_eq!(
+            query_to_filter(&search).unwrap(),
+            Some(json!({ "bool": { "should": [
+                { "equals": { "genre": "scifi" } },
+                { "range": { "year": { "gte": 1965 } } },
+            ] } }))
+        );
+    }
+
+    #[test]
+    fn test_query_to_search_request() {
+        let mut search = query(Some("dune"), &["genre:scifi"]);
+        search.sort = vec!["year:desc".to_string(), "score".to_string()];
+        search.facets = vec!["genre".to_string()];
+        search.page = Some(60);
+        search.per_page = Some(20);
+        search.highlight = Some(HighlightConfig {
+            fields: vec!["title".to_string()],
+            pre_tag: Some("<em>".to_string()),
+            post_tag: Some("</em>".to_string()),
+            max_length: Some(100),
+        });
+        let mut search_config = config(
+            Some(r#"{"options":{"ranker":"bm25"},"profile":true,"headers":{"x-trace":"1"}}"#),
+            None,
+        );
+        search_config.boost_fields = vec![("title".to_string(), 2.0)];
+        search.config = Some(search_config);
+
+        let request = query_to_search_request("books", &search).unwrap();
+        assert_eq!(
+            serde_json::to_value(&request).unwrap(),
+            json!({
+                "index": "books",
+                "query": { "bool": { "must": [
+                    { "match": { "*": "dune" } },
+                    { "equals": { "genre": "scifi" } },
+                ] } },
+                "limit": 20,
+                "offset": 1180,
+                "sort": [{ "year": "desc" }, { "_score": "asc" }],
+                "highlight": {
+                    "fields": ["title"],
+                    "pre_tags": "<em>",
+                    "post_tags": "</em>",
+                    "limit": 100,
+                },
+                "aggs": { "genre": { "terms": { "field": "genre", "size": 100 } } },
+                "options": {
+                    "max_matches": 1200,
+                    "field_weights": { "title": 2 },
+                    "ranker": "bm25",
+                },
+                "profile": true,
+            })
+        );
+
+        let request = query_to_search_request("books", &query(None, &[])).unwrap();
+        assert_eq!(request.query, json!({ "match_all": {} }));
+        assert_eq!(request.options, None);
+
+        assert!(matches!(
+            query_to_search_request("books", &{
+                let mut search = query(None, &[]);
+                search.sort = vec!["year:up".to_string()];
+                search
+            }),
+            Err(SearchError::InvalidQuery(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_search_response() {
+        let response = SearchResponse {
+            took: Some(3),
+            hits: SearchHits {
+                total: Some(2),
+                hits: vec![],
+            },
+            aggregations: Some(object(json!({
+                "genre": { "buckets": [
+                    { "key": "scifi", "doc_count": 2 },
+                    { "key": 1965, "doc_count": 1 },
+                ] },
+            }))),
+        };
+        let (hits, total, facets) = parse_search_response(response, &query(None, &[]));
+        assert!(hits.is_empty());
+        assert_eq!(total, Some(2));
+        assert_eq!(facets.as_deref(), Some(r#"{"genre":{"1965":1,"scifi":2}}"#));
+    }
+
+    #[test]
+    fn test_create_retry_query() {
+        let mut search = query(Some("dune"), &[]);
+        search.offset = Some(10);
+        search.per_page = Some(5);
+        let hits = vec![
+            SearchHit {
+                id: "doc1".to_string(),
+                score: None,
+                content: None,
+                highlights: None,
+            };
+            7
+        ];
+        assert_eq!(create_retry_query(&search, &hits).offset, Some(15));
+    }
+}
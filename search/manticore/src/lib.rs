@@ -0,0 +1,312 @@
+use crate::client::ManticoreApi;
+use crate::conversions::{
+    add_columns_statements, check_table_name, columns_to_schema, create_retry_query,
+    create_table_statement, delete_operation, hit_to_doc, lookup_request, parse_search_response,
+    query_to_search_request, query_window, replace_operation, rows_to_columns, Column,
+};
+use golem_rust::wasm_rpc::Pollable;
+use golem_search::config::{
+    search_headers, search_profile, search_timeout, with_profile_config_keys, worker_headers,
+    worker_profile,
+};
+use golem_search::durability::{DurableSearch, ExtendedGuest};
+use golem_search::golem::search::core::{Guest, GuestSearchStream, SearchStream};
+use golem_search::golem::search::types::{
+    Doc, DocumentId, IndexName, IndexPage, PageRequest, Schema, SearchError, SearchHit,
+    SearchQuery, SearchResults,
+};
+use golem_search::pagination::index_page;
+use golem_search::LOGGING_STATE;
+use serde_json::Value as JsonValue;
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+
+mod client;
+mod conversions;
+
+/// Search stream over the pages of a query, as Manticore has no native streaming
+struct ManticoreSearchStream {
+    client: ManticoreApi,
+    index_name: String,
+    query: SearchQuery,
+    offset: Cell<u32>,
+    finished: Cell<bool>,
+}
+
+impl ManticoreSearchStream {
+    pub fn new(client: ManticoreApi, index_name: String, query: SearchQuery) -> Self {
+        let offset = query_window(&query).map(|(offset, _)| offset).unwrap_or(0);
+        Self {
+            client,
+            index_name,
+            query,
+            offset: Cell::new(offset),
+            finished: Cell::new(false),
+        }
+    }
+
+    pub fn subscribe(&self) -> Pollable {
+        golem_rust::bindings::wasi::clocks::monotonic_clock::subscribe_duration(0)
+    }
+}
+
+impl GuestSearchStream for ManticoreSearchStream {
+    fn get_next(&self) -> Option<Vec<SearchHit>> {
+        if self.finished.get() {
+            return Some(vec![]);
+        }
+
+        let mut query = self.query.clone();
+        query.page = None;
+        query.offset = Some(self.offset.get());
+        let per_page = query_window(&query)
+            .map(|(_, per_page)| per_page)
+            .unwrap_or(0);
+
+        match ManticoreComponent::search_page(&self.client, &self.index_name, &query) {
+            Ok(results) => {
+                let received = results.hits.len() as u32;
+                if received < per_page || received == 0 {
+                    self.finished.set(true);
+                }
+                self.offset.set(self.offset.get() + received);
+                Some(results.hits)
+            }
+            Err(_) => {
+                self.finished.set(true);
+                Some(vec![])
+            }
+        }
+    }
+
+    fn blocking_get_next(&self) -> Vec<SearchHit> {
+        self.get_next().unwrap_or_default()
+    }
+}
+
+struct ManticoreComponent;
+
+thread_local! {
+    /// The provider clients by configuration profile, reused across calls instead of being rebuilt
+    /// for each of them
+    static CLIENTS: RefCell<HashMap<Option<String>, ManticoreApi>> = RefCell::new(HashMap::new());
+}
+
+impl ManticoreComponent {
+    const URL_ENV_VAR: &'static str = "MANTICORE_URL";
+
+    fn create_client() -> Result<ManticoreApi, SearchError> {
+        Self::create_profile_client(worker_profile().as_deref())
+            .map(|client| client.with_headers(worker_headers()))
+    }
+
+    /// Returns the client of `profile` cached for this worker, creating it from the configuration
+    /// on first use
+    fn create_profile_client(profile: Option<&str>) -> Result<ManticoreApi, SearchError> {
+        CLIENTS.with_borrow_mut(|clients| {
+            let profile = profile.map(str::to_string);
+            if let Some(client) = clients.get(&profile) {
+                return Ok(client.clone());
+            }
+
+            let client = Self::new_client(profile.as_deref())?;
+            clients.insert(profile, client.clone());
+            Ok(client)
+        })
+    }
+
+    fn new_client(profile: Option<&str>) -> Result<ManticoreApi, SearchError> {
+        with_profile_config_keys(profile, &[Self::URL_ENV_VAR], |keys| {
+            if keys.is_empty() {
+                return Err(SearchError::Internal("Missing Manticore URL".to_string()));
+            }
+
+            Ok(ManticoreApi::new(keys[0].clone()))
+        })
+    }
+
+    fn columns(client: &ManticoreApi, index: &str) -> Result<Vec<Column>, SearchError> {
+        check_table_name(index)?;
+        Ok(rows_to_columns(client.sql(&format!("DESCRIBE {index}"))?))
+    }
+
+    fn search_page(
+        client: &ManticoreApi,
+        index: &str,
+        query: &SearchQuery,
+    ) -> Result<SearchResults, SearchError> {
+        let request = query_to_search_request(index, query)?;
+        let response = client.search(&request)?;
+        let took_ms = response.took;
+        let (hits, total, facets) = parse_search_response(response, query);
+
+        Ok(SearchResults {
+            total,
+            page: Some(request.offset / request.limit.max(1) + 1),
+            per_page: Some(request.limit),
+            hits,
+            facets,
+            took_ms,
+            relaxation: None,
+            navigation: None,
+        })
+    }
+}
+
+impl Guest for ManticoreComponent {
+    type SearchStream = ManticoreSearchStream;
+
+    /// Creates a real-time table with the fields of the schema, or with a single full-text field
+    /// holding the text of the documents when there is no schema
+    fn create_index(name: IndexName, schema: Option<Schema>) -> Result<(), SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let statement = create_table_statement(&name, schema.as_ref())?;
+        let client = Self::create_client()?;
+        client.sql(&statement).map(|_| ())
+    }
+
+    fn delete_index(name: IndexName) -> Result<(), SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        check_table_name(&name)?;
+        let client = Self::create_client()?;
+        client.sql(&format!("DROP TABLE {name}")).map(|_| ())
+    }
+
+    fn list_indexes(page: PageRequest) -> Result<IndexPage, SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = Self::create_client()?;
+
+        let rows = client.sql("SHOW TABLES")?;
+        let names = rows
+            .into_iter()
+            .filter_map(|row| match row.get("Table").or_else(|| row.get("Index")) {
+                Some(JsonValue::String(name)) => Some(name.clone()),
+                _ => None,
+            })
+            .collect();
+        index_page(names, &page)
+    }
+
+    fn upsert(index: IndexName, doc: Doc) -> Result<(), SearchError> {
+        Self::upsert_many(index, vec![doc])
+    }
+
+    /// Replaces documents with a bulk request. The columns of the table are read first, as only
+    /// the fields of the documents that are columns can be written to them.
+    fn upsert_many(index: IndexName, docs: Vec<Doc>) -> Result<(), SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        if docs.is_empty() {
+            return Ok(());
+        }
+
+        let client = Self::create_client()?;
+        let columns = Self::columns(&client, &index)?;
+        let operations = docs
+            .into_iter()
+            .map(|doc| replace_operation(&index, doc, &columns))
+            .collect::<Result<Vec<_>, _>>()?;
+        client.bulk(&(operations.join("\n") + "\n"))
+    }
+
+    fn delete(index: IndexName, id: DocumentId) -> Result<(), SearchError> {
+        Self::delete_many(index, vec![id])
+    }
+
+    fn delete_many(index: IndexName, ids: Vec<DocumentId>) -> Result<(), SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        if ids.is_empty() {
+            return Ok(());
+        }
+
+        let client = Self::create_client()?;
+        let operations = ids
+            .iter()
+            .map(|id| delete_operation(&index, id))
+            .collect::<Vec<_>>();
+        client.bulk(&(operations.join("\n") + "\n"))
+    }
+
+    fn get(index: IndexName, id: DocumentId) -> Result<Option<Doc>, SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = Self::create_client()?;
+
+        let response = client.search(&lookup_request(&index, &id))?;
+        Ok(response
+            .hits
+            .hits
+            .into_iter()
+            .next()
+            .and_then(|hit| hit_to_doc(hit, &id)))
+    }
+
+    fn search(index: IndexName, query: SearchQuery) -> Result<SearchResults, SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = Self::create_profile_client(search_profile(&query).as_deref())?
+            .with_request_timeout(search_timeout(&query))
+            .with_headers(search_headers(&query));
+
+        Self::search_page(&client, &index, &query)
+    }
+
+    fn stream_search(index: IndexName, query: SearchQuery) -> Result<SearchStream, SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = Self::create_profile_client(search_profile(&query).as_deref())?
+            .with_request_timeout(search_timeout(&query))
+            .with_headers(search_headers(&query));
+        let stream = ManticoreSearchStream::new(client, index, query);
+        Ok(SearchStream::new(stream))
+    }
+
+    fn get_schema(index: IndexName) -> Result<Schema, SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = Self::create_client()?;
+
+        Ok(columns_to_schema(&Self::columns(&client, &index)?))
+    }
+
+    /// Adds the fields of the schema missing from the table as new columns. Documents upserted
+    /// before keep no value for them until they are upserted again.
+    fn update_schema(index: IndexName, schema: Schema) -> Result<(), SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = Self::create_client()?;
+        let columns = Self::columns(&client, &index)?;
+        for statement in add_columns_statements(&index, &columns, &schema)? {
+            client.sql(&statement)?;
+        }
+        Ok(())
+    }
+}
+
+impl ExtendedGuest for ManticoreComponent {
+    fn unwrapped_stream(index: IndexName, query: SearchQuery) -> Self::SearchStream {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = Self::create_profile_client(search_profile(&query).as_deref())
+            .unwrap_or_else(|_| ManticoreApi::new("http://localhost:9308".to_string()))
+            .with_headers(search_headers(&query));
+
+        ManticoreSearchStream::new(client, index, query)
+    }
+
+    fn retry_query(original_query: &SearchQuery, partial_hits: &[SearchHit]) -> SearchQuery {
+        create_retry_query(original_query, partial_hits)
+    }
+
+    fn subscribe(stream: &Self::SearchStream) -> Pollable {
+        stream.subscribe()
+    }
+}
+
+type DurableManticoreComponent = DurableSearch<ManticoreComponent>;
+
+golem_search::export_search!(DurableManticoreComponent with_types_in golem_search);
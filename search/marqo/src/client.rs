@@ -0,0 +1,358 @@
+use golem_search::correlation::{self, CORRELATION_ID_HEADER};
+use golem_search::error::{
+    from_reqwest_error, from_send_error, invalid_query, search_error_from_failed_response,
+};
+use golem_search::golem::search::types::SearchError;
+use golem_search::logging::{redact_secret, RedactedResponse};
+use golem_search::retry::RetryPolicy;
+use log::trace;
+use reqwest::{Client, Method, RequestBuilder, Response};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map as JsonMap, Value as JsonValue};
+use std::fmt::Debug;
+use std::time::Duration;
+
+#[derive(Clone)]
+pub struct MarqoApi {
+    client: Client,
+    base_url: String,
+    api_key: Option<String>,
+    retry_policy: RetryPolicy,
+    request_timeout: Option<Duration>,
+    headers: Vec<(String, String)>,
+}
+
+impl Debug for MarqoApi {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MarqoApi")
+            .field("base_url", &self.base_url)
+            .field("api_key", &self.api_key.as_deref().map(redact_secret))
+            .field("retry_policy", &self.retry_policy)
+            .field("request_timeout", &self.request_timeout)
+            .finish()
+    }
+}
+
+pub type MarqoDocument = JsonMap<String, JsonValue>;
+
+/// A field of a structured index
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IndexField {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub field_type: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub features: Vec<String>,
+}
+
+/// Settings of an index, as sent when creating it and returned by its settings endpoint
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexSettings {
+    /// `structured` or `unstructured`
+    #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+    pub index_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub all_fields: Vec<IndexField>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tensor_fields: Vec<String>,
+}
+
+impl IndexSettings {
+    pub fn is_structured(&self) -> bool {
+        self.index_type.as_deref() == Some("structured")
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexSummary {
+    pub index_name: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct IndexList {
+    #[serde(default)]
+    pub results: Vec<IndexSummary>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AddDocumentsRequest {
+    pub documents: Vec<MarqoDocument>,
+    /// The fields to vectorise, only accepted by unstructured indexes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tensor_fields: Option<Vec<String>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AddDocumentsItem {
+    #[serde(rename = "_id", default)]
+    pub id: Option<String>,
+    #[serde(default)]
+    pub message: Option<String>,
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AddDocumentsResponse {
+    #[serde(default)]
+    pub errors: bool,
+    #[serde(default)]
+    pub items: Vec<AddDocumentsItem>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchRequest {
+    pub q: String,
+    pub limit: u32,
+    pub offset: u32,
+    pub search_method: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filter: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub attributes_to_retrieve: Option<Vec<String>>,
+    pub show_highlights: bool,
+    /// The provider parameters of the query, such as `hybridParameters` or `scoreModifiers`
+    #[serde(flatten)]
+    pub extra: JsonMap<String, JsonValue>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchResponse {
+    #[serde(default)]
+    pub hits: Vec<MarqoDocument>,
+    #[serde(default)]
+    pub processing_time_ms: Option<f64>,
+}
+
+impl MarqoApi {
+    pub fn new(base_url: String, api_key: Option<String>) -> Self {
+        let client = Client::builder()
+            .build()
+            .expect("Failed to initialize HTTP client");
+
+        Self {
+            client,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            api_key,
+            retry_policy: RetryPolicy::from_config(),
+            request_timeout: None,
+            headers: Vec::new(),
+        }
+    }
+
+    /// Applies a timeout to every request sent by this client
+    pub fn with_request_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.request_timeout = timeout;
+        self
+    }
+
+    /// Adds custom headers to every request sent by this client
+    pub fn with_headers(mut self, headers: Vec<(String, String)>) -> Self {
+        self.headers = headers;
+        self
+    }
+
+    fn create_request(&self, method: Method, url: &str) -> RequestBuilder {
+        trace!("[Marqo] HTTP {method} {url}");
+
+        let mut req = self
+            .client
+            .request(method, url)
+            .header("Content-Type", "application/json")
+            .header(CORRELATION_ID_HEADER, correlation::current());
+
+        if let Some(api_key) = &self.api_key {
+            req = req.header("x-api-key", api_key);
+        }
+
+        if let Some(timeout) = self.request_timeout {
+            req = req.timeout(timeout);
+        }
+
+        for (name, value) in &self.headers {
+            req = req.header(name, value);
+        }
+
+        req
+    }
+
+    fn send(
+        &self,
+        method: Method,
+        url: &str,
+        body: Option<&impl Serialize>,
+        operation: &str,
+    ) -> Result<Response, SearchError> {
+        self.retry_policy
+            .execute(|| {
+                let req = self.create_request(method.clone(), url);
+                match body {
+                    Some(body) => req.json(body).send(),
+                    None => req.send(),
+                }
+            })
+            .map_err(|e| from_send_error(format!("Failed to {operation}"), e))
+    }
+}
+
+fn parse_response<T: DeserializeOwned + Debug>(response: Response) -> Result<T, SearchError> {
+    let status = response.status();
+
+    trace!(
+        "Received response from Marqo API: {:?}",
+        RedactedResponse(&response)
+    );
+
+    if status.is_success() {
+        let body = response
+            .json::<T>()
+            .map_err(|err| from_reqwest_error("Failed to decode response body", err))?;
+
+        trace!("Received response from Marqo API: {body:?}");
+
+        Ok(body)
+    } else {
+        let error = search_error_from_failed_response(response);
+
+        trace!("Received {status} response from Marqo API: {error:?}");
+
+        Err(error)
+    }
+}
+
+impl MarqoApi {
+    pub fn list_indexes(&self) -> Result<IndexList, SearchError> {
+        trace!("Listing indexes");
+
+        let url = format!("{}/indexes", self.base_url);
+        parse_response(self.send(Method::GET, &url, None::<&()>, "list indexes")?)
+    }
+
+    pub fn create_index(&self, name: &str, settings: &IndexSettings) -> Result<(), SearchError> {
+        trace!("Creating index: {name}");
+
+        let url = format!("{}/indexes/{}", self.base_url, name);
+        let response = self
+            .create_request(Method::POST, &url)
+            .json(settings)
+            .send()
+            .map_err(|e| from_send_error("Failed to create index", e))?;
+
+        parse_response::<JsonValue>(response).map(|_| ())
+    }
+
+    pub fn delete_index(&self, name: &str) -> Result<(), SearchError> {
+        trace!("Deleting index: {name}");
+
+        let url = format!("{}/indexes/{}", self.base_url, name);
+        parse_response::<JsonValue>(self.send(Method::DELETE, &url, None::<&()>, "delete index")?)
+            .map(|_| ())
+    }
+
+    pub fn get_settings(&self, index: &str) -> Result<IndexSettings, SearchError> {
+        trace!("Getting settings of index: {index}");
+
+        let url = format!("{}/indexes/{}/settings", self.base_url, index);
+        parse_response(self.send(Method::GET, &url, None::<&()>, "get index settings")?)
+    }
+
+    /// Adds or replaces documents. Marqo reports the documents it failed to store in the
+    /// response, the first of these errors is returned.
+    pub fn add_documents(
+        &self,
+        index: &str,
+        request: &AddDocumentsRequest,
+    ) -> Result<(), SearchError> {
+        trace!(
+            "Adding {} documents to index: {index}",
+            request.documents.len()
+        );
+
+        let url = format!("{}/indexes/{}/documents", self.base_url, index);
+        let response = self
+            .create_request(Method::POST, &url)
+            .json(request)
+            .send()
+            .map_err(|e| from_send_error("Failed to add documents", e))?;
+
+        let response = parse_response::<AddDocumentsResponse>(response)?;
+        if !response.errors {
+            return Ok(());
+        }
+        let failure = response
+            .items
+            .into_iter()
+            .find(|item| item.error.is_some() || item.message.is_some());
+        match failure {
+            Some(item) => Err(invalid_query(format!(
+                "Failed to store document {}: {}",
+                item.id.unwrap_or_default(),
+                item.message.or(item.error).unwrap_or_default()
+            ))),
+            None => Err(invalid_query("Failed to store documents")),
+        }
+    }
+
+    pub fn delete_documents(&self, index: &str, ids: &[String]) -> Result<(), SearchError> {
+        trace!("Deleting {} documents from index: {index}", ids.len());
+
+        let url = format!("{}/indexes/{}/documents/delete-batch", self.base_url, index);
+        parse_response::<JsonValue>(self.send(
+            Method::POST,
+            &url,
+            Some(&ids),
+            "delete documents",
+        )?)
+        .map(|_| ())
+    }
+
+    /// Gets a document, or `None` when the index has no document with this id. Marqo answers
+    /// 404 for both missing documents and missing indexes, which are told apart by the error code.
+    pub fn get_document(
+        &self,
+        index: &str,
+        id: &str,
+    ) -> Result<Option<MarqoDocument>, SearchError> {
+        trace!("Getting document {id} from index: {index}");
+
+        let url = format!("{}/indexes/{}/documents/{}", self.base_url, index, id);
+        let response = self.send(Method::GET, &url, None::<&()>, "get document")?;
+
+        if response.status() == 404 {
+            let code = response
+                .json::<JsonValue>()
+                .ok()
+                .and_then(|body| body.get("code")?.as_str().map(str::to_string));
+            match code.as_deref() {
+                Some("index_not_found") => Err(SearchError::IndexNotFound),
+                _ => Ok(None),
+            }
+        } else {
+            Ok(Some(parse_response(response)?))
+        }
+    }
+
+    pub fn search(
+        &self,
+        index: &str,
+        request: &SearchRequest,
+    ) -> Result<SearchResponse, SearchError> {
+        trace!("Searching index: {index}");
+
+        let url = format!("{}/indexes/{}/search", self.base_url, index);
+        let response = self
+            .retry_policy
+            .execute(|| self.create_request(Method::POST, &url).json(request).send())
+            .map_err(|e| from_send_error("Failed to search", e))?;
+
+        parse_response(response)
+    }
+}
@@ -0,0 +1,777 @@
+use crate::client::{AddDocumentsRequest, IndexField, IndexSettings, MarqoDocument, SearchRequest};
+use golem_search::config::{get_max_pagination_depth_config, search_filters_match_any};
+use golem_search::error::{invalid_query, unsupported};
+use golem_search::golem::search::types::{
+    Doc, FieldType, Schema, SchemaField, SearchError, SearchHit, SearchQuery,
+};
+use serde_json::{Map as JsonMap, Value as JsonValue};
+use std::collections::BTreeSet;
+
+/// Field holding the id of a Marqo document
+pub const ID_FIELD: &str = "_id";
+const SCORE_FIELD: &str = "_score";
+const HIGHLIGHTS_FIELD: &str = "_highlights";
+
+const DEFAULT_PER_PAGE: u32 = 20;
+
+/// The search methods of Marqo, chosen with the `searchMethod` provider parameter
+const SEARCH_METHODS: [&str; 3] = ["TENSOR", "LEXICAL", "HYBRID"];
+const DEFAULT_SEARCH_METHOD: &str = "TENSOR";
+
+/// The Marqo type and features of a schema field. Text fields are searchable both lexically and
+/// through their embeddings, the other fields can be filtered on.
+fn schema_field_to_index_field(field: &SchemaField) -> Result<IndexField, SearchError> {
+    if field.name.contains('.') {
+        return Err(invalid_query(format!(
+            "Marqo field names can't contain dots: `{}`",
+            field.name
+        )));
+    }
+    let (field_type, mut features) = match field.field_type {
+        FieldType::Text => ("text", vec!["lexical_search"]),
+        FieldType::Keyword | FieldType::Date => ("text", vec!["filter"]),
+        FieldType::Integer => ("long", vec!["filter"]),
+        FieldType::Float => ("double", vec!["filter"]),
+        FieldType::Boolean => ("bool", vec!["filter"]),
+        FieldType::GeoPoint => {
+            return Err(invalid_query(format!(
+                "Marqo has no geo point fields: `{}`",
+                field.name
+            )))
+        }
+    };
+    if field.field_type == FieldType::Text && field.facet {
+        features.push("filter");
+    }
+    Ok(IndexField {
+        name: field.name.clone(),
+        field_type: field_type.to_string(),
+        features: features.into_iter().map(str::to_string).collect(),
+    })
+}
+
+/// The settings of a new index: a structured index with the fields of the schema, whose text
+/// fields are vectorised, or an unstructured index when there is no schema
+pub fn schema_to_settings(
+    schema: Option<&Schema>,
+    model: &str,
+) -> Result<IndexSettings, SearchError> {
+    let Some(schema) = schema else {
+        return Ok(IndexSettings {
+            index_type: Some("unstructured".to_string()),
+            model: Some(model.to_string()),
+            all_fields: vec![],
+            tensor_fields: vec![],
+        });
+    };
+
+    let all_fields = schema
+        .fields
+        .iter()
+        .map(schema_field_to_index_field)
+        .collect::<Result<Vec<_>, _>>()?;
+    let tensor_fields = schema
+        .fields
+        .iter()
+        .filter(|field| field.field_type == FieldType::Text)
+        .map(|field| field.name.clone())
+        .collect();
+
+    Ok(IndexSettings {
+        index_type: Some("structured".to_string()),
+        model: Some(model.to_string()),
+        all_fields,
+        tensor_fields,
+    })
+}
+
+/// The schema of an index, from the fields of a structured index. Unstructured indexes have no
+/// fields.
+pub fn settings_to_schema(settings: IndexSettings) -> Schema {
+    let fields = settings
+        .all_fields
+        .into_iter()
+        .filter_map(|field| {
+            let filter = field.features.iter().any(|feature| feature == "filter");
+            let field_type = match field.field_type.as_str() {
+                "text"
+                    if field
+                        .features
+                        .iter()
+                        .any(|feature| feature == "lexical_search") =>
+                {
+                    FieldType::Text
+                }
+                "text" => FieldType::Keyword,
+                "int" | "long" => FieldType::Integer,
+                "float" | "double" => FieldType::Float,
+                "bool" => FieldType::Boolean,
+                _ => return None,
+            };
+            Some(SchemaField {
+                name: field.name,
+                facet: field_type == FieldType::Text && filter,
+                field_type,
+                required: false,
+                sort: false,
+                index: true,
+            })
+        })
+        .collect();
+
+    Schema {
+        fields,
+        primary_key: Some(ID_FIELD.to_string()),
+    }
+}
+
+/// A Marqo document with the fields of the document content and its id in `_id`
+pub fn doc_to_marqo(doc: Doc) -> Result<MarqoDocument, SearchError> {
+    let mut document = match serde_json::from_str::<JsonValue>(&doc.content) {
+        Ok(JsonValue::Object(document)) => document,
+        Ok(_) => {
+            return Err(invalid_query(format!(
+                "Content of document {} is not a JSON object",
+                doc.id
+            )))
+        }
+        Err(e) => {
+            return Err(invalid_query(format!(
+                "Invalid JSON content of document {}: {e}",
+                doc.id
+            )))
+        }
+    };
+    document.insert(ID_FIELD.to_string(), JsonValue::String(doc.id));
+    Ok(document)
+}
+
+/// The request adding documents to an index. Structured indexes vectorise the tensor fields of
+/// their settings, while for unstructured indexes the string fields of the documents are sent
+/// as the fields to vectorise.
+pub fn documents_request(
+    settings: &IndexSettings,
+    docs: Vec<Doc>,
+) -> Result<AddDocumentsRequest, SearchError> {
+    let documents = docs
+        .into_iter()
+        .map(doc_to_marqo)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let tensor_fields = (!settings.is_structured()).then(|| {
+        documents
+            .iter()
+            .flat_map(|document| document.iter())
+            .filter(|(name, value)| name.as_str() != ID_FIELD && value.is_string())
+            .map(|(name, _)| name.clone())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect()
+    });
+
+    Ok(AddDocumentsRequest {
+        documents,
+        tensor_fields,
+    })
+}
+
+/// The fields of a document or hit, without the `_id`, `_score`, `_highlights` and other fields
+/// added by Marqo
+fn document_content(document: &MarqoDocument) -> String {
+    let content = document
+        .iter()
+        .filter(|(name, _)| !name.starts_with('_'))
+        .map(|(name, value)| (name.clone(), value.clone()))
+        .collect::<JsonMap<_, _>>();
+    JsonValue::Object(content).to_string()
+}
+
+fn document_id(document: &MarqoDocument) -> String {
+    match document.get(ID_FIELD) {
+        Some(JsonValue::String(id)) => id.clone(),
+        Some(id) => id.to_string(),
+        None => String::new(),
+    }
+}
+
+pub fn marqo_to_doc(document: MarqoDocument) -> Doc {
+    Doc {
+        id: document_id(&document),
+        content: document_content(&document),
+    }
+}
+
+/// Highlights in the same shape as the other providers: `{"field": ["snippet"]}`. Marqo returns
+/// the best matching chunk of each field instead of marking the matched terms, so the chunks are
+/// used as snippets, cut to the maximum length of the highlight configuration.
+fn hit_highlights(hit: &MarqoDocument, query: &SearchQuery) -> Option<String> {
+    let highlight = query.highlight.as_ref()?;
+    let chunks = match hit.get(HIGHLIGHTS_FIELD)? {
+        JsonValue::Array(chunks) => chunks.clone(),
+        JsonValue::Object(chunks) => vec![JsonValue::Object(chunks.clone())],
+        _ => return None,
+    };
+
+    let mut highlights = JsonMap::new();
+    for chunk in chunks.iter().filter_map(JsonValue::as_object) {
+        for (field, text) in chunk {
+            if !highlight.fields.is_empty() && !highlight.fields.contains(field) {
+                continue;
+            }
+            let Some(text) = text.as_str() else {
+                continue;
+            };
+            let text = match highlight.max_length {
+                Some(max_length) => text.chars().take(max_length as usize).collect(),
+                None => text.to_string(),
+            };
+            if let JsonValue::Array(snippets) = highlights
+                .entry(field.clone())
+                .or_insert_with(|| JsonValue::Array(vec![]))
+            {
+                snippets.push(JsonValue::String(text));
+            }
+        }
+    }
+    (!highlights.is_empty()).then(|| JsonValue::Object(highlights).to_string())
+}
+
+pub fn hit_to_search_hit(hit: MarqoDocument, query: &SearchQuery) -> SearchHit {
+    SearchHit {
+        id: document_id(&hit),
+        score: hit.get(SCORE_FIELD).and_then(JsonValue::as_f64),
+        content: Some(document_content(&hit)),
+        highlights: hit_highlights(&hit, query),
+    }
+}
+
+/// Escapes the characters of a value that are special in Marqo filter strings. Numbers are kept
+/// as they are so that negative bounds can be used in ranges.
+fn escape_value(value: &str) -> String {
+    let value = value.trim();
+    if value.parse::<f64>().is_ok() {
+        return value.to_string();
+    }
+    let value = value
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+        .unwrap_or(value);
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(
+            c,
+            '(' | ')'
+                | '['
+                | ']'
+                | '{'
+                | '}'
+                | ':'
+                | '"'
+                | '\\'
+                | '+'
+                | '-'
+                | '!'
+                | '^'
+                | '~'
+                | '*'
+                | '?'
+                | '|'
+                | '&'
+                | '/'
+        ) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Converts a filter to the Marqo filter DSL. Filters are `field:value`, `field=value`,
+/// `field!=value`, and `field>value` style comparisons with `>`, `>=`, `<` and `<=`. Marqo ranges
+/// include their bounds, so the bound of a strict comparison is excluded with `NOT`.
+fn filter_to_marqo(filter: &str) -> Result<String, SearchError> {
+    let filter = filter.trim();
+    let position = filter
+        .find([':', '=', '!', '<', '>'])
+        .ok_or_else(|| invalid_query(format!("Unsupported filter `{filter}`")))?;
+    let key = filter[..position].trim();
+    let rest = &filter[position..];
+    let (op, value) = [">=", "<=", "!=", ">", "<", "=", ":"]
+        .into_iter()
+        .find_map(|op| Some((op, rest.strip_prefix(op)?)))
+        .ok_or_else(|| invalid_query(format!("Unsupported filter `{filter}`")))?;
+    if key.is_empty() || value.trim().is_empty() {
+        return Err(invalid_query(format!("Unsupported filter `{filter}`")));
+    }
+    let value = escape_value(value);
+
+    Ok(match op {
+        ":" | "=" => format!("{key}:({value})"),
+        "!=" => format!("NOT {key}:({value})"),
+        ">=" => format!("{key}:[{value} TO *]"),
+        "<=" => format!("{key}:[* TO {value}]"),
+        ">" => format!("({key}:[{value} TO *] AND NOT {key}:({value}))"),
+        _ => format!("({key}:[* TO {value}] AND NOT {key}:({value}))"),
+    })
+}
+
+/// The Marqo filter string of a query, matching all of its filters, or any of them when its
+/// filter mode asks for it
+pub fn query_to_filter(query: &SearchQuery) -> Result<Option<String>, SearchError> {
+    let clauses = query
+        .filters
+        .iter()
+        .map(|filter| filter_to_marqo(filter))
+        .collect::<Result<Vec<_>, _>>()?;
+    if clauses.is_empty() {
+        return Ok(None);
+    }
+    if clauses.len() == 1 {
+        return Ok(clauses.into_iter().next());
+    }
+    let operator = if search_filters_match_any(query) {
+        " OR "
+    } else {
+        " AND "
+    };
+    Ok(Some(
+        clauses
+            .iter()
+            .map(|clause| format!("({clause})"))
+            .collect::<Vec<_>>()
+            .join(operator),
+    ))
+}
+
+/// Number of hits to skip and number of hits per page of a query
+pub fn query_window(query: &SearchQuery) -> Result<(u32, u32), SearchError> {
+    let per_page = query.per_page.unwrap_or(DEFAULT_PER_PAGE);
+    let offset = query.offset.unwrap_or_else(|| {
+        query
+            .page
+            .unwrap_or(1)
+            .saturating_sub(1)
+            .saturating_mul(per_page)
+    });
+    if offset.saturating_add(per_page) > get_max_pagination_depth_config() {
+        return Err(invalid_query(format!(
+            "Requested hits beyond the maximum pagination depth of {}",
+            get_max_pagination_depth_config()
+        )));
+    }
+    Ok((offset, per_page))
+}
+
+/// Builds the search request of a query. The provider parameters of the query are added to the
+/// request, with `searchMethod` choosing between `TENSOR` (the default), `LEXICAL` and `HYBRID`
+/// search. Queries without text match every document with a lexical `*` search. Marqo can't
+/// sort results by a field or count facets.
+pub fn query_to_search_request(query: &SearchQuery) -> Result<SearchRequest, SearchError> {
+    if !query.sort.is_empty() {
+        return Err(unsupported("sorting"));
+    }
+    if !query.facets.is_empty() {
+        return Err(unsupported("facets"));
+    }
+
+    let (offset, per_page) = query_window(query)?;
+    let config = query.config.as_ref();
+
+    let mut extra = JsonMap::new();
+    if let Some(provider_params) = config.and_then(|config| config.provider_params.as_deref()) {
+        let provider_params =
+            serde_json::from_str::<JsonMap<String, JsonValue>>(provider_params)
+                .map_err(|e| invalid_query(format!("Invalid provider parameters: {e}")))?;
+        extra.extend(
+            provider_params
+                .into_iter()
+                .filter(|(key, _)| key != "headers"),
+        );
+    }
+
+    let search_method = match extra.remove("searchMethod") {
+        Some(JsonValue::String(method)) => {
+            let method = method.to_uppercase();
+            if !SEARCH_METHODS.contains(&method.as_str()) {
+                return Err(invalid_query(format!(
+                    "Unsupported Marqo search method `{method}`"
+                )));
+            }
+            method
+        }
+        Some(method) => {
+            return Err(invalid_query(format!(
+                "Unsupported Marqo search method `{method}`"
+            )))
+        }
+        None => DEFAULT_SEARCH_METHOD.to_string(),
+    };
+    let (q, search_method) = match query.q.as_deref().map(str::trim).filter(|q| !q.is_empty()) {
+        Some(q) => (q.to_string(), search_method),
+        None => ("*".to_string(), "LEXICAL".to_string()),
+    };
+
+    let attributes = config
+        .map(|config| config.attributes_to_retrieve.clone())
+        .unwrap_or_default();
+
+    Ok(SearchRequest {
+        q,
+        limit: per_page,
+        offset,
+        search_method,
+        filter: query_to_filter(query)?,
+        attributes_to_retrieve: (!attributes.is_empty()).then_some(attributes),
+        show_highlights: query.highlight.is_some(),
+        extra,
+    })
+}
+
+pub fn create_retry_query(original_query: &SearchQuery, partial_hits: &[SearchHit]) -> SearchQuery {
+    let mut retry_query = original_query.clone();
+
+    let current_offset = original_query.offset.unwrap_or(0);
+    let per_page = original_query.per_page.unwrap_or(DEFAULT_PER_PAGE);
+    let hits_received = partial_hits.len() as u32;
+
+    retry_query.offset = Some(current_offset + hits_received.min(per_page));
+
+    retry_query
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use golem_search::golem::search::types::{FilterMode, HighlightConfig, SearchConfig};
+    use serde_json::json;
+
+    fn query(q: Option<&str>, filters: &[&str]) -> SearchQuery {
+        SearchQuery {
+            q: q.map(str::to_string),
+            filters: filters.iter().map(|filter| filter.to_string()).collect(),
+            sort: vec![],
+            facets: vec![],
+            page: None,
+            per_page: None,
+            offset: None,
+            highlight: None,
+            config: None,
+        }
+    }
+
+    fn field(name: &str, field_type: FieldType, facet: bool) -> SchemaField {
+        SchemaField {
+            name: name.to_string(),
+            field_type,
+            required: false,
+            facet,
+            sort: false,
+            index: true,
+        }
+    }
+
+    fn config(provider_params: Option<&str>, filter_mode: Option<FilterMode>) -> SearchConfig {
+        SearchConfig {
+            timeout_ms: None,
+            boost_fields: vec![],
+            attributes_to_retrieve: vec![],
+            language: None,
+            typo_tolerance: None,
+            exact_match_boost: None,
+            provider_params: provider_params.map(str::to_string),
+            profile: None,
+            filter_mode,
+            post_filters: vec![],
+            rescore: None,
+            fallbacks: vec![],
+        }
+    }
+
+    fn document(value: JsonValue) -> MarqoDocument {
+        match value {
+            JsonValue::Object(document) => document,
+            _ => panic!("Not a JSON object"),
+        }
+    }
+
+    #[test]
+    fn test_schema_to_settings() {
+        let schema = Schema {
+            fields: vec![
+                field("title", FieldType::Text, false),
+                field("genre", FieldType::Text, true),
+                field("isbn", FieldType::Keyword, false),
+                field("year", FieldType::Integer, false),
+                field("rating", FieldType::Float, false),
+                field("available", FieldType::Boolean, false),
+            ],
+            primary_key: None,
+        };
+        let settings = schema_to_settings(Some(&schema), "hf/e5-base-v2").unwrap();
+        assert!(settings.is_structured());
+        assert_eq!(settings.tensor_fields, vec!["title", "genre"]);
+        assert_eq!(
+            serde_json::to_value(&settings.all_fields).unwrap(),
+            json!([
+                { "name": "title", "type": "text", "features": ["lexical_search"] },
+                { "name": "genre", "type": "text", "features": ["lexical_search", "filter"] },
+                { "name": "isbn", "type": "text", "features": ["filter"] },
+                { "name": "year", "type": "long", "features": ["filter"] },
+                { "name": "rating", "type": "double", "features": ["filter"] },
+                { "name": "available", "type": "bool", "features": ["filter"] },
+            ])
+        );
+
+        let settings = schema_to_settings(None, "hf/e5-base-v2").unwrap();
+        assert_eq!(
+            serde_json::to_value(&settings).unwrap(),
+            json!({ "type": "unstructured", "model": "hf/e5-base-v2" })
+        );
+    }
+
+    #[test]
+    fn test_schema_to_settings_rejects_unsupported_fields() {
+        for field in [
+            field("location", FieldType::GeoPoint, false),
+            field("author.name", FieldType::Text, false),
+        ] {
+            let schema = Schema {
+                fields: vec![field],
+                primary_key: None,
+            };
+            assert!(matches!(
+                schema_to_settings(Some(&schema), "hf/e5-base-v2"),
+                Err(SearchError::InvalidQuery(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_settings_to_schema() {
+        let schema = Schema {
+            fields: vec![
+                field("title", FieldType::Text, false),
+                field("genre", FieldType::Text, true),
+                field("isbn", FieldType::Keyword, false),
+                field("year", FieldType::Integer, false),
+                field("rating", FieldType::Float, false),
+                field("available", FieldType::Boolean, false),
+            ],
+            primary_key: Some(ID_FIELD.to_string()),
+        };
+        let settings = schema_to_settings(Some(&schema), "hf/e5-base-v2").unwrap();
+        assert_eq!(settings_to_schema(settings), schema);
+    }
+
+    #[test]
+    fn test_documents_request() {
+        let docs = vec![
+            Doc {
+                id: "doc1".to_string(),
+                content: r#"{"title":"Dune","year":1965}"#.to_string(),
+            },
+            Doc {
+                id: "doc2".to_string(),
+                content: r#"{"author":"Frank Herbert"}"#.to_string(),
+            },
+        ];
+
+        let unstructured = schema_to_settings(None, "hf/e5-base-v2").unwrap();
+        let request = documents_request(&unstructured, docs.clone()).unwrap();
+        assert_eq!(
+            serde_json::to_value(&request).unwrap(),
+            json!({
+                "documents": [
+                    { "_id": "doc1", "title": "Dune", "year": 1965 },
+                    { "_id": "doc2", "author": "Frank Herbert" },
+                ],
+                "tensorFields": ["author", "title"],
+            })
+        );
+
+        let structured = IndexSettings {
+            index_type: Some("structured".to_string()),
+            ..unstructured
+        };
+        let request = documents_request(&structured, docs).unwrap();
+        assert_eq!(request.tensor_fields, None);
+    }
+
+    #[test]
+    fn test_doc_to_marqo_rejects_invalid_content() {
+        let doc = Doc {
+            id: "doc1".to_string(),
+            content: "[1, 2]".to_string(),
+        };
+        assert!(matches!(
+            doc_to_marqo(doc),
+            Err(SearchError::InvalidQuery(_))
+        ));
+    }
+
+    #[test]
+    fn test_marqo_to_doc() {
+        let doc = marqo_to_doc(document(json!({
+            "_id": "doc1",
+            "title": "Dune",
+            "_tensor_facets": [],
+        })));
+        assert_eq!(
+            doc,
+            Doc {
+                id: "doc1".to_string(),
+                content: r#"{"title":"Dune"}"#.to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_hit_to_search_hit() {
+        let hit = document(json!({
+            "_id": "doc1",
+            "_score": 0.75,
+            "_highlights": [{ "title": "Dune Messiah" }, { "plot": "The desert planet" }],
+            "title": "Dune Messiah",
+        }));
+
+        let mut search = query(Some("dune"), &[]);
+        let hit_without_highlights = hit_to_search_hit(hit.clone(), &search);
+        assert_eq!(hit_without_highlights.id, "doc1");
+        assert_eq!(hit_without_highlights.score, Some(0.75));
+        assert_eq!(
+            hit_without_highlights.content.as_deref(),
+            Some(r#"{"title":"Dune Messiah"}"#)
+        );
+        assert_eq!(hit_without_highlights.highlights, None);
+
+        search.highlight = Some(HighlightConfig {
+            fields: vec!["title".to_string()],
+            pre_tag: None,
+            post_tag: None,
+            max_length: Some(4),
+        });
+        assert_eq!(
+            hit_to_search_hit(hit, &search).highlights.as_deref(),
+            Some(r#"{"title":["Dune"]}"#)
+        );
+    }
+
+    #[test]
+    fn test_filters_to_marqo() {
+        assert_eq!(filter_to_marqo("genre:sci-fi").unwrap(), r"genre:(sci\-fi)");
+        assert_eq!(
+            filter_to_marqo(r#"title = "Dune Messiah""#).unwrap(),
+            "title:(Dune Messiah)"
+        );
+        assert_eq!(
+            filter_to_marqo("genre!=fantasy").unwrap(),
+            "NOT genre:(fantasy)"
+        );
+        assert_eq!(filter_to_marqo("year>=1965").unwrap(), "year:[1965 TO *]");
+        assert_eq!(filter_to_marqo("year<=1965").unwrap(), "year:[* TO 1965]");
+        assert_eq!(filter_to_marqo("delta>=-1.5").unwrap(), "delta:[-1.5 TO *]");
+        assert_eq!(
+            filter_to_marqo("year>1965").unwrap(),
+            "(year:[1965 TO *] AND NOT year:(1965))"
+        );
+        assert_eq!(
+            filter_to_marqo("year<1965").unwrap(),
+            "(year:[* TO 1965] AND NOT year:(1965))"
+        );
+        assert!(filter_to_marqo("year").is_err());
+        assert!(filter_to_marqo("year>").is_err());
+    }
+
+    #[test]
+    fn test_query_to_filter() {
+        assert_eq!(query_to_filter(&query(None, &[])).unwrap(), None);
+        assert_eq!(
+            query_to_filter(&query(None, &["genre:scifi"])).unwrap(),
+            Some("genre:(scifi)".to_string())
+        );
+
+        let mut search = query(None, &["genre:scifi", "year>=1965"]);
+        assert_eq!(
+            query_to_filter(&search).unwrap(),
+            Some("(genre:(scifi)) AND (year:[1965 TO *])".to_string())
+        );
+
+        search.config = Some(config(None, Some(FilterMode::MatchAny)));
+        assert_eq!(
+            query_to_filter(&search).unwrap(),
+            Some("(genre:(scifi)) OR (year:[1965 TO *])".to_string())
+        );
+    }
+
+    #[test]
+    fn test_query_to_search_request() {
+        let mut search = query(Some("desert planet"), &["genre:scifi"]);
+        search.page = Some(2);
+        search.per_page = Some(10);
+        let request = query_to_search_request(&search).unwrap();
+        assert_eq!(
+            serde_json::to_value(&request).unwrap(),
+            json!({
+                "q": "desert planet",
+                "limit": 10,
+                "offset": 10,
+                "searchMethod": "TENSOR",
+                "filter": "genre:(scifi)",
+                "showHighlights": false,
+            })
+        );
+
+        search.config = Some(config(
+            Some(r#"{"searchMethod":"hybrid","hybridParameters":{"alpha":0.5},"headers":{}}"#),
+            None,
+        ));
+        let request = query_to_search_request(&search).unwrap();
+        assert_eq!(request.search_method, "HYBRID");
+        assert_eq!(
+            JsonValue::Object(request.extra),
+            json!({ "hybridParameters": { "alpha": 0.5 } })
+        );
+    }
+
+    #[test]
+    fn test_query_without_text_is_lexical() {
+        let mut search = query(None, &[]);
+        search.config = Some(config(Some(r#"{"searchMethod":"TENSOR"}"#), None));
+        let request = query_to_search_request(&search).unwrap();
+        assert_eq!(request.q, "*");
+        assert_eq!(request.search_method, "LEXICAL");
+    }
+
+    #[test]
+    fn test_query_to_search_request_rejects_unsupported_queries() {
+        let mut sorted = query(Some("dune"), &[]);
+        sorted.sort = vec!["year".to_string()];
+        let mut faceted = query(Some("dune"), &[]);
+        faceted.facets = vec!["genre".to_string()];
+        let mut invalid_method = query(Some("dune"), &[]);
+        invalid_method.config = Some(config(Some(r#"{"searchMethod":"fuzzy"}"#), None));
+        let mut invalid_params = query(Some("dune"), &[]);
+        invalid_params.config = Some(config(Some("[]"), None));
+
+        for search in [sorted, faceted, invalid_method, invalid_params] {
+            assert!(query_to_search_request(&search).is_err());
+        }
+    }
+
+    #[test]
+    fn test_create_retry_query() {
+        let mut original = query(Some("dune"), &[]);
+        original.offset = Some(20);
+        original.per_page = Some(10);
+        let hits = vec![
+            SearchHit {
+                id: "doc1".to_string(),
+                score: None,
+                content: None,
+                highlights: None,
+            };
+            3
+        ];
+        assert_eq!(create_retry_query(&original, &hits).offset, Some(23));
+    }
+}
@@ -0,0 +1,304 @@
+use crate::client::MarqoApi;
+use crate::conversions::{
+    create_retry_query, documents_request, hit_to_search_hit, marqo_to_doc,
+    query_to_search_request, query_window, schema_to_settings, settings_to_schema,
+};
+use golem_rust::wasm_rpc::Pollable;
+use golem_search::config::{
+    get_config_with_default, get_optional_config, profile_key, search_headers, search_profile,
+    search_timeout, with_profile_config_keys, worker_headers, worker_profile,
+};
+use golem_search::durability::{DurableSearch, ExtendedGuest};
+use golem_search::error::unsupported;
+use golem_search::golem::search::core::{Guest, GuestSearchStream, SearchStream};
+use golem_search::golem::search::types::{
+    Doc, DocumentId, IndexName, IndexPage, PageRequest, Schema, SearchError, SearchHit,
+    SearchQuery, SearchResults,
+};
+use golem_search::pagination::index_page;
+use golem_search::LOGGING_STATE;
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+
+mod client;
+mod conversions;
+
+/// Search stream over the pages of a query, as Marqo has no native streaming
+struct MarqoSearchStream {
+    client: MarqoApi,
+    index_name: String,
+    query: SearchQuery,
+    offset: Cell<u32>,
+    finished: Cell<bool>,
+}
+
+impl MarqoSearchStream {
+    pub fn new(client: MarqoApi, index_name: String, query: SearchQuery) -> Self {
+        let offset = query_window(&query).map(|(offset, _)| offset).unwrap_or(0);
+        Self {
+            client,
+            index_name,
+            query,
+            offset: Cell::new(offset),
+            finished: Cell::new(false),
+        }
+    }
+
+    pub fn subscribe(&self) -> Pollable {
+        golem_rust::bindings::wasi::clocks::monotonic_clock::subscribe_duration(0)
+    }
+}
+
+impl GuestSearchStream for MarqoSearchStream {
+    fn get_next(&self) -> Option<Vec<SearchHit>> {
+        if self.finished.get() {
+            return Some(vec![]);
+        }
+
+        let mut query = self.query.clone();
+        query.page = None;
+        query.offset = Some(self.offset.get());
+        let per_page = query_window(&query)
+            .map(|(_, per_page)| per_page)
+            .unwrap_or(0);
+
+        match MarqoComponent::search_page(&self.client, &self.index_name, &query) {
+            Ok(results) => {
+                let received = results.hits.len() as u32;
+                if received < per_page || received == 0 {
+                    self.finished.set(true);
+                }
+                self.offset.set(self.offset.get() + received);
+                Some(results.hits)
+            }
+            Err(_) => {
+                self.finished.set(true);
+                Some(vec![])
+            }
+        }
+    }
+
+    fn blocking_get_next(&self) -> Vec<SearchHit> {
+        self.get_next().unwrap_or_default()
+    }
+}
+
+struct MarqoComponent;
+
+thread_local! {
+    /// The provider clients by configuration profile, reused across calls instead of being rebuilt
+    /// for each of them
+    static CLIENTS: RefCell<HashMap<Option<String>, MarqoApi>> = RefCell::new(HashMap::new());
+}
+
+impl MarqoComponent {
+    const URL_ENV_VAR: &'static str = "MARQO_URL";
+    const API_KEY_ENV_VAR: &'static str = "MARQO_API_KEY";
+    const MODEL_ENV_VAR: &'static str = "MARQO_MODEL";
+
+    fn create_client() -> Result<MarqoApi, SearchError> {
+        Self::create_profile_client(worker_profile().as_deref())
+            .map(|client| client.with_headers(worker_headers()))
+    }
+
+    /// Returns the client of `profile` cached for this worker, creating it from the configuration
+    /// on first use
+    fn create_profile_client(profile: Option<&str>) -> Result<MarqoApi, SearchError> {
+        CLIENTS.with_borrow_mut(|clients| {
+            let profile = profile.map(str::to_string);
+            if let Some(client) = clients.get(&profile) {
+                return Ok(client.clone());
+            }
+
+            let client = Self::new_client(profile.as_deref())?;
+            clients.insert(profile, client.clone());
+            Ok(client)
+        })
+    }
+
+    fn new_client(profile: Option<&str>) -> Result<MarqoApi, SearchError> {
+        with_profile_config_keys(profile, &[Self::URL_ENV_VAR], |keys| {
+            if keys.is_empty() {
+                return Err(SearchError::Internal("Missing Marqo URL".to_string()));
+            }
+
+            let base_url = keys[0].clone();
+
+            let api_key = get_optional_config(profile_key(Self::API_KEY_ENV_VAR, profile));
+
+            Ok(MarqoApi::new(base_url, api_key))
+        })
+    }
+
+    /// The embedding model of `MARQO_MODEL` used by new indexes, `hf/e5-base-v2` by default
+    fn model() -> String {
+        get_config_with_default(
+            profile_key(Self::MODEL_ENV_VAR, worker_profile().as_deref()),
+            "hf/e5-base-v2",
+        )
+    }
+
+    /// Runs a query as a single Marqo search. Marqo doesn't report the number of matches, so the
+    /// results have no total.
+    fn search_page(
+        client: &MarqoApi,
+        index: &str,
+        query: &SearchQuery,
+    ) -> Result<SearchResults, SearchError> {
+        let request = query_to_search_request(query)?;
+        let response = client.search(index, &request)?;
+
+        Ok(SearchResults {
+            total: None,
+            page: Some(request.offset / request.limit.max(1) + 1),
+            per_page: Some(request.limit),
+            hits: response
+                .hits
+                .into_iter()
+                .map(|hit| hit_to_search_hit(hit, query))
+                .collect(),
+            facets: None,
+            took_ms: response.processing_time_ms.map(|time| time as u32),
+            relaxation: None,
+            navigation: None,
+        })
+    }
+}
+
+impl Guest for MarqoComponent {
+    type SearchStream = MarqoSearchStream;
+
+    /// Creates a structured index with the fields of the schema, or an unstructured index when
+    /// there is no schema, embedding text with the model of `MARQO_MODEL`
+    fn create_index(name: IndexName, schema: Option<Schema>) -> Result<(), SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let settings = schema_to_settings(schema.as_ref(), &Self::model())?;
+        let client = Self::create_client()?;
+        client.create_index(&name, &settings)
+    }
+
+    fn delete_index(name: IndexName) -> Result<(), SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = Self::create_client()?;
+        client.delete_index(&name)
+    }
+
+    fn list_indexes(page: PageRequest) -> Result<IndexPage, SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = Self::create_client()?;
+
+        let response = client.list_indexes()?;
+        index_page(
+            response
+                .results
+                .into_iter()
+                .map(|index| index.index_name)
+                .collect(),
+            &page,
+        )
+    }
+
+    fn upsert(index: IndexName, doc: Doc) -> Result<(), SearchError> {
+        Self::upsert_many(index, vec![doc])
+    }
+
+    /// Adds or replaces documents. The settings of the index are read first, as only unstructured
+    /// indexes are told which fields to embed.
+    fn upsert_many(index: IndexName, docs: Vec<Doc>) -> Result<(), SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        if docs.is_empty() {
+            return Ok(());
+        }
+
+        let client = Self::create_client()?;
+        let settings = client.get_settings(&index)?;
+        client.add_documents(&index, &documents_request(&settings, docs)?)
+    }
+
+    fn delete(index: IndexName, id: DocumentId) -> Result<(), SearchError> {
+        Self::delete_many(index, vec![id])
+    }
+
+    fn delete_many(index: IndexName, ids: Vec<DocumentId>) -> Result<(), SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        if ids.is_empty() {
+            return Ok(());
+        }
+
+        let client = Self::create_client()?;
+        client.delete_documents(&index, &ids)
+    }
+
+    fn get(index: IndexName, id: DocumentId) -> Result<Option<Doc>, SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = Self::create_client()?;
+
+        Ok(client.get_document(&index, &id)?.map(marqo_to_doc))
+    }
+
+    fn search(index: IndexName, query: SearchQuery) -> Result<SearchResults, SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = Self::create_profile_client(search_profile(&query).as_deref())?
+            .with_request_timeout(search_timeout(&query))
+            .with_headers(search_headers(&query));
+
+        Self::search_page(&client, &index, &query)
+    }
+
+    fn stream_search(index: IndexName, query: SearchQuery) -> Result<SearchStream, SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = Self::create_profile_client(search_profile(&query).as_deref())?
+            .with_request_timeout(search_timeout(&query))
+            .with_headers(search_headers(&query));
+        let stream = MarqoSearchStream::new(client, index, query);
+        Ok(SearchStream::new(stream))
+    }
+
+    /// Returns the fields of a structured index, or an empty schema for unstructured indexes
+    fn get_schema(index: IndexName) -> Result<Schema, SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = Self::create_client()?;
+
+        Ok(settings_to_schema(client.get_settings(&index)?))
+    }
+
+    /// Marqo can't change the fields of an existing index
+    fn update_schema(_index: IndexName, _schema: Schema) -> Result<(), SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        Err(unsupported("schema updates"))
+    }
+}
+
+impl ExtendedGuest for MarqoComponent {
+    fn unwrapped_stream(index: IndexName, query: SearchQuery) -> Self::SearchStream {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = Self::create_profile_client(search_profile(&query).as_deref())
+            .unwrap_or_else(|_| MarqoApi::new("http://localhost:8882".to_string(), None))
+            .with_headers(search_headers(&query));
+
+        MarqoSearchStream::new(client, index, query)
+    }
+
+    fn retry_query(original_query: &SearchQuery, partial_hits: &[SearchHit]) -> SearchQuery {
+        create_retry_query(original_query, partial_hits)
+    }
+
+    fn subscribe(stream: &Self::SearchStream) -> Pollable {
+        stream.subscribe()
+    }
+}
+
+type DurableMarqoComponent = DurableSearch<MarqoComponent>;
+
+golem_search::export_search!(DurableMarqoComponent with_types_in golem_search);
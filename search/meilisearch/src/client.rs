@@ -1,5 +1,8 @@
-use golem_search::error::{from_reqwest_error, internal_error, search_error_from_status};
+use golem_search::correlation::{self, CORRELATION_ID_HEADER};
+use golem_search::error::{from_reqwest_error, from_send_error, search_error_from_failed_response};
 use golem_search::golem::search::types::SearchError;
+use golem_search::logging::{redact_secret, RedactedResponse};
+use golem_search::retry::RetryPolicy;
 use log::trace;
 use reqwest::{Client, RequestBuilder, Response};
 use serde::de::DeserializeOwned;
@@ -8,11 +11,25 @@ use serde_json::{Map as JsonMap, Value as JsonValue};
 use std::fmt::Debug;
 use std::time::Duration;
 
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct MeilisearchApi {
     client: Client,
     base_url: String,
     api_key: Option<String>,
+    retry_policy: RetryPolicy,
+    request_timeout: Option<Duration>,
+    headers: Vec<(String, String)>,
+}
+
+impl Debug for MeilisearchApi {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MeilisearchApi")
+            .field("base_url", &self.base_url)
+            .field("api_key", &self.api_key.as_deref().map(redact_secret))
+            .field("retry_policy", &self.retry_policy)
+            .field("request_timeout", &self.request_timeout)
+            .finish()
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -201,9 +218,24 @@ impl MeilisearchApi {
             client,
             base_url,
             api_key,
+            retry_policy: RetryPolicy::from_config(),
+            request_timeout: None,
+            headers: Vec::new(),
         }
     }
 
+    /// Applies a timeout to every request sent by this client
+    pub fn with_request_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.request_timeout = timeout;
+        self
+    }
+
+    /// Adds custom headers to every request sent by this client
+    pub fn with_headers(mut self, headers: Vec<(String, String)>) -> Self {
+        self.headers = headers;
+        self
+    }
+
     fn create_request(&self, method: &str, url: &str) -> RequestBuilder {
         trace!("[Meilisearch] HTTP {method} {url}");
 
@@ -221,7 +253,17 @@ impl MeilisearchApi {
         if let Some(api_key) = &self.api_key {
             req = req.header("Authorization", format!("Bearer {api_key}"));
         }
-        req = req.header("Content-Type", "application/json");
+        req = req
+            .header("Content-Type", "application/json")
+            .header(CORRELATION_ID_HEADER, correlation::current());
+
+        if let Some(timeout) = self.request_timeout {
+            req = req.timeout(timeout);
+        }
+
+        for (name, value) in &self.headers {
+            req = req.header(name, value);
+        }
 
         req
     }
@@ -230,7 +272,10 @@ impl MeilisearchApi {
 fn parse_response<T: DeserializeOwned + Debug>(response: Response) -> Result<T, SearchError> {
     let status = response.status();
 
-    trace!("Received response from Meilisearch API: {response:?}");
+    trace!(
+        "Received response from Meilisearch API: {:?}",
+        RedactedResponse(&response)
+    );
 
     if status.is_success() {
         let body = response
@@ -241,26 +286,28 @@ fn parse_response<T: DeserializeOwned + Debug>(response: Response) -> Result<T,
 
         Ok(body)
     } else {
-        let error_body = response
-            .text()
-            .map_err(|err| from_reqwest_error("Failed to receive error response body", err))?;
+        let error = search_error_from_failed_response(response);
 
-        trace!("Received {status} response from Meilisearch API: {error_body:?}");
+        trace!("Received {status} response from Meilisearch API: {error:?}");
 
-        Err(search_error_from_status(status))
+        Err(error)
     }
 }
 
 impl MeilisearchApi {
-    pub fn list_indexes(&self) -> Result<MeilisearchIndexListResponse, SearchError> {
-        trace!("Listing indexes");
+    pub fn list_indexes(
+        &self,
+        offset: u32,
+        limit: u32,
+    ) -> Result<MeilisearchIndexListResponse, SearchError> {
+        trace!("Listing indexes from {offset}, up to {limit}");
 
-        let url = format!("{}/indexes", self.base_url);
+        let url = format!("{}/indexes?offset={offset}&limit={limit}", self.base_url);
 
         let response = self
-            .create_request("GET", &url)
-            .send()
-            .map_err(|e| internal_error(format!("Failed to list indexes: {e}")))?;
+            .retry_policy
+            .execute(|| self.create_request("GET", &url).send())
+            .map_err(|e| from_send_error("Failed to list indexes", e))?;
 
         parse_response(response)
     }
@@ -271,9 +318,9 @@ impl MeilisearchApi {
         let url = format!("{}/indexes/{}", self.base_url, index_uid);
 
         let response = self
-            .create_request("GET", &url)
-            .send()
-            .map_err(|e| internal_error(format!("Failed to get index: {e}")))?;
+            .retry_policy
+            .execute(|| self.create_request("GET", &url).send())
+            .map_err(|e| from_send_error("Failed to get index", e))?;
 
         parse_response(response)
     }
@@ -290,7 +337,7 @@ impl MeilisearchApi {
             .create_request("POST", &url)
             .json(request)
             .send()
-            .map_err(|e| internal_error(format!("Failed to create index: {e}")))?;
+            .map_err(|e| from_send_error("Failed to create index", e))?;
 
         parse_response(response)
     }
@@ -301,9 +348,9 @@ impl MeilisearchApi {
         let url = format!("{}/indexes/{}", self.base_url, index_uid);
 
         let response = self
-            .create_request("DELETE", &url)
-            .send()
-            .map_err(|e| internal_error(format!("Failed to delete index: {e}")))?;
+            .retry_policy
+            .execute(|| self.create_request("DELETE", &url).send())
+            .map_err(|e| from_send_error("Failed to delete index", e))?;
 
         parse_response(response)
     }
@@ -318,10 +365,9 @@ impl MeilisearchApi {
         let url = format!("{}/indexes/{}/documents/fetch", self.base_url, index_uid);
 
         let response = self
-            .create_request("POST", &url)
-            .json(request)
-            .send()
-            .map_err(|e| internal_error(format!("Failed to get documents: {e}")))?;
+            .retry_policy
+            .execute(|| self.create_request("POST", &url).json(request).send())
+            .map_err(|e| from_send_error("Failed to get documents", e))?;
 
         parse_response(response)
     }
@@ -339,9 +385,9 @@ impl MeilisearchApi {
         );
 
         let response = self
-            .create_request("GET", &url)
-            .send()
-            .map_err(|e| internal_error(format!("Failed to get document: {e}")))?;
+            .retry_policy
+            .execute(|| self.create_request("GET", &url).send())
+            .map_err(|e| from_send_error("Failed to get document", e))?;
 
         if response.status() == 404 {
             Ok(None)
@@ -367,7 +413,7 @@ impl MeilisearchApi {
             .create_request("POST", &url)
             .json(documents)
             .send()
-            .map_err(|e| internal_error(format!("Failed to add documents: {e}")))?;
+            .map_err(|e| from_send_error("Failed to add documents", e))?;
 
         parse_response(response)
     }
@@ -386,10 +432,9 @@ impl MeilisearchApi {
         let url = format!("{}/indexes/{}/documents", self.base_url, index_uid);
 
         let response = self
-            .create_request("PUT", &url)
-            .json(documents)
-            .send()
-            .map_err(|e| internal_error(format!("Failed to update documents: {e}")))?;
+            .retry_policy
+            .execute(|| self.create_request("PUT", &url).json(documents).send())
+            .map_err(|e| from_send_error("Failed to update documents", e))?;
 
         parse_response(response)
     }
@@ -407,9 +452,9 @@ impl MeilisearchApi {
         );
 
         let response = self
-            .create_request("DELETE", &url)
-            .send()
-            .map_err(|e| internal_error(format!("Failed to delete document: {e}")))?;
+            .retry_policy
+            .execute(|| self.create_request("DELETE", &url).send())
+            .map_err(|e| from_send_error("Failed to delete document", e))?;
 
         parse_response(response)
     }
@@ -431,10 +476,9 @@ impl MeilisearchApi {
         );
 
         let response = self
-            .create_request("POST", &url)
-            .json(document_ids)
-            .send()
-            .map_err(|e| internal_error(format!("Failed to delete documents: {e}")))?;
+            .retry_policy
+            .execute(|| self.create_request("POST", &url).json(document_ids).send())
+            .map_err(|e| from_send_error("Failed to delete documents", e))?;
 
         parse_response(response)
     }
@@ -445,9 +489,9 @@ impl MeilisearchApi {
         let url = format!("{}/indexes/{}/documents", self.base_url, index_uid);
 
         let response = self
-            .create_request("DELETE", &url)
-            .send()
-            .map_err(|e| internal_error(format!("Failed to delete all documents: {e}")))?;
+            .retry_policy
+            .execute(|| self.create_request("DELETE", &url).send())
+            .map_err(|e| from_send_error("Failed to delete all documents", e))?;
 
         parse_response(response)
     }
@@ -462,10 +506,9 @@ impl MeilisearchApi {
         let url = format!("{}/indexes/{}/search", self.base_url, index_uid);
 
         let response = self
-            .create_request("POST", &url)
-            .json(request)
-            .send()
-            .map_err(|e| internal_error(format!("Failed to search: {e}")))?;
+            .retry_policy
+            .execute(|| self.create_request("POST", &url).json(request).send())
+            .map_err(|e| from_send_error("Failed to search", e))?;
 
         parse_response(response)
     }
@@ -476,9 +519,9 @@ impl MeilisearchApi {
         let url = format!("{}/indexes/{}/settings", self.base_url, index_uid);
 
         let response = self
-            .create_request("GET", &url)
-            .send()
-            .map_err(|e| internal_error(format!("Failed to get settings: {e}")))?;
+            .retry_policy
+            .execute(|| self.create_request("GET", &url).send())
+            .map_err(|e| from_send_error("Failed to get settings", e))?;
 
         parse_response(response)
     }
@@ -493,10 +536,9 @@ impl MeilisearchApi {
         let url = format!("{}/indexes/{}/settings", self.base_url, index_uid);
 
         let response = self
-            .create_request("PATCH", &url)
-            .json(settings)
-            .send()
-            .map_err(|e| internal_error(format!("Failed to update settings: {e}")))?;
+            .retry_policy
+            .execute(|| self.create_request("PATCH", &url).json(settings).send())
+            .map_err(|e| from_send_error("Failed to update settings", e))?;
 
         parse_response(response)
     }
@@ -507,9 +549,9 @@ impl MeilisearchApi {
         let url = format!("{}/indexes/{}/settings", self.base_url, index_uid);
 
         let response = self
-            .create_request("DELETE", &url)
-            .send()
-            .map_err(|e| internal_error(format!("Failed to reset settings: {e}")))?;
+            .retry_policy
+            .execute(|| self.create_request("DELETE", &url).send())
+            .map_err(|e| from_send_error("Failed to reset settings", e))?;
 
         parse_response(response)
     }
@@ -521,9 +563,9 @@ impl MeilisearchApi {
         let url = format!("{}/tasks/{}", self.base_url, task_uid);
 
         let response = self
-            .create_request("GET", &url)
-            .send()
-            .map_err(|e| internal_error(format!("Failed to get task: {e}")))?;
+            .retry_policy
+            .execute(|| self.create_request("GET", &url).send())
+            .map_err(|e| from_send_error("Failed to get task", e))?;
 
         parse_response(response)
     }
@@ -1,6 +1,7 @@
 use crate::client::{
     MeilisearchDocument, MeilisearchSearchRequest, MeilisearchSearchResponse, MeilisearchSettings,
 };
+use golem_search::config::search_filters_match_any;
 use golem_search::golem::search::types::{
     Doc, FieldType, Schema, SchemaField, SearchHit, SearchQuery, SearchResults,
 };
@@ -38,6 +39,7 @@ pub fn meilisearch_document_to_doc(mut doc: MeilisearchDocument) -> Doc {
 }
 
 pub fn search_query_to_meilisearch_request(query: SearchQuery) -> MeilisearchSearchRequest {
+    let match_any = search_filters_match_any(&query);
     let mut request = MeilisearchSearchRequest {
         q: query.q,
         offset: query.offset,
@@ -75,7 +77,7 @@ pub fn search_query_to_meilisearch_request(query: SearchQuery) -> MeilisearchSea
     };
 
     if !query.filters.is_empty() {
-        request.filter = Some(convert_filters_to_meilisearch(query.filters));
+        request.filter = Some(convert_filters_to_meilisearch(query.filters, match_any));
     }
 
     request
@@ -100,13 +102,15 @@ pub fn meilisearch_response_to_search_results(
 
     SearchResults {
         total: Some(response.estimated_total_hits),
-        page: None, // We'd need to calculate this from offset and limit
+        page: None, // Meilisearch paginates by offset and limit, not by page
         per_page: Some(response.limit),
         hits,
         facets: response
             .facet_distribution
             .map(|facets| serde_json::to_string(&facets).unwrap_or_default()),
         took_ms: Some(response.processing_time_ms),
+        relaxation: None,
+        navigation: None,
     }
 }
 
@@ -226,12 +230,20 @@ pub fn create_retry_query(original_query: &SearchQuery, partial_hits: &[SearchHi
     retry_query
 }
 
-fn convert_filters_to_meilisearch(filters: Vec<String>) -> String {
-    // Join multiple filters with AND
+fn convert_filters_to_meilisearch(filters: Vec<String>, match_any: bool) -> String {
+    // Join multiple filters with AND, or with OR when any of them may match
     // In Meilisearch, filter syntax supports expressions like:
     // "genre = horror AND year > 2000"
     // "color = red OR color = blue"
-    filters.join(" AND ")
+    if match_any {
+        filters
+            .iter()
+            .map(|filter| format!("({filter})"))
+            .collect::<Vec<_>>()
+            .join(" OR ")
+    } else {
+        filters.join(" AND ")
+    }
 }
 
 // for later development :-
@@ -394,6 +406,11 @@ mod tests {
                 provider_params: Some(
                     r#"{"attributes_to_retrieve": ["title", "price"]}"#.to_string(),
                 ),
+                profile: None,
+                filter_mode: None,
+                post_filters: vec![],
+                rescore: None,
+                fallbacks: vec![],
             }),
         };
 
@@ -587,8 +604,14 @@ mod tests {
             "category = electronics".to_string(),
             "price > 100".to_string(),
         ];
-        let meilisearch_filter = convert_filters_to_meilisearch(filters);
+        let meilisearch_filter = convert_filters_to_meilisearch(filters.clone(), false);
         assert_eq!(meilisearch_filter, "category = electronics AND price > 100");
+
+        let meilisearch_filter = convert_filters_to_meilisearch(filters, true);
+        assert_eq!(
+            meilisearch_filter,
+            "(category = electronics) OR (price > 100)"
+        );
     }
 
     #[test]
@@ -5,14 +5,20 @@ use crate::conversions::{
     schema_to_meilisearch_settings, search_query_to_meilisearch_request,
 };
 use golem_rust::wasm_rpc::Pollable;
-use golem_search::config::with_config_keys;
+use golem_search::config::{
+    get_optional_config, profile_key, search_headers, search_profile, search_timeout,
+    with_profile_config_keys, worker_headers, worker_profile,
+};
 use golem_search::durability::{DurableSearch, ExtendedGuest};
 use golem_search::golem::search::core::{Guest, GuestSearchStream, SearchStream};
 use golem_search::golem::search::types::{
-    Doc, DocumentId, IndexName, Schema, SearchError, SearchHit, SearchQuery, SearchResults,
+    Doc, DocumentId, IndexName, IndexPage, PageRequest, Schema, SearchError, SearchHit,
+    SearchQuery, SearchResults,
 };
+use golem_search::pagination::{next_page_token, page_offset, page_size};
 use golem_search::LOGGING_STATE;
 use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
 
 mod client;
 mod conversions;
@@ -103,12 +109,38 @@ impl GuestSearchStream for MeilisearchSearchStream {
 
 struct MeilisearchComponent;
 
+thread_local! {
+    /// The provider clients by configuration profile, reused across calls instead of being rebuilt
+    /// for each of them
+    static CLIENTS: RefCell<HashMap<Option<String>, MeilisearchApi>> = RefCell::new(HashMap::new());
+}
+
 impl MeilisearchComponent {
     const BASE_URL_ENV_VAR: &'static str = "MEILISEARCH_BASE_URL";
     const API_KEY_ENV_VAR: &'static str = "MEILISEARCH_API_KEY";
 
     fn create_client() -> Result<MeilisearchApi, SearchError> {
-        with_config_keys(&[Self::BASE_URL_ENV_VAR], |keys| {
+        Self::create_profile_client(worker_profile().as_deref())
+            .map(|client| client.with_headers(worker_headers()))
+    }
+
+    /// Returns the client of `profile` cached for this worker, creating it from the configuration
+    /// on first use
+    fn create_profile_client(profile: Option<&str>) -> Result<MeilisearchApi, SearchError> {
+        CLIENTS.with_borrow_mut(|clients| {
+            let profile = profile.map(str::to_string);
+            if let Some(client) = clients.get(&profile) {
+                return Ok(client.clone());
+            }
+
+            let client = Self::new_client(profile.as_deref())?;
+            clients.insert(profile, client.clone());
+            Ok(client)
+        })
+    }
+
+    fn new_client(profile: Option<&str>) -> Result<MeilisearchApi, SearchError> {
+        with_profile_config_keys(profile, &[Self::BASE_URL_ENV_VAR], |keys| {
             if keys.is_empty() {
                 return Err(SearchError::Internal(
                     "Missing Meilisearch base URL".to_string(),
@@ -117,7 +149,7 @@ impl MeilisearchComponent {
 
             let base_url = keys[0].clone();
 
-            let api_key = std::env::var(Self::API_KEY_ENV_VAR).ok();
+            let api_key = get_optional_config(profile_key(Self::API_KEY_ENV_VAR, profile));
 
             Ok(MeilisearchApi::new(base_url, api_key))
         })
@@ -161,17 +193,24 @@ impl Guest for MeilisearchComponent {
         Ok(())
     }
 
-    fn list_indexes() -> Result<Vec<IndexName>, SearchError> {
+    fn list_indexes(page: PageRequest) -> Result<IndexPage, SearchError> {
         LOGGING_STATE.with_borrow_mut(|state| state.init());
 
         let client = Self::create_client()?;
 
-        let response = client.list_indexes()?;
-        Ok(response
-            .results
-            .into_iter()
-            .map(|index| index.task_uid)
-            .collect())
+        let offset = page_offset(&page)?;
+        let response = client.list_indexes(offset, page_size(&page))?;
+        let received = response.results.len() as u32;
+        let has_more = response.offset + received < response.total;
+
+        Ok(IndexPage {
+            items: response
+                .results
+                .into_iter()
+                .map(|index| index.task_uid)
+                .collect(),
+            next_page_token: next_page_token(offset, received, has_more),
+        })
     }
 
     fn upsert(index: IndexName, doc: Doc) -> Result<(), SearchError> {
@@ -241,7 +280,9 @@ impl Guest for MeilisearchComponent {
     fn search(index: IndexName, query: SearchQuery) -> Result<SearchResults, SearchError> {
         LOGGING_STATE.with_borrow_mut(|state| state.init());
 
-        let client = Self::create_client()?;
+        let client = Self::create_profile_client(search_profile(&query).as_deref())?
+            .with_request_timeout(search_timeout(&query))
+            .with_headers(search_headers(&query));
         let meilisearch_request = search_query_to_meilisearch_request(query);
 
         let response = client.search(&index, &meilisearch_request)?;
@@ -251,7 +292,9 @@ impl Guest for MeilisearchComponent {
     fn stream_search(index: IndexName, query: SearchQuery) -> Result<SearchStream, SearchError> {
         LOGGING_STATE.with_borrow_mut(|state| state.init());
 
-        let client = Self::create_client()?;
+        let client = Self::create_profile_client(search_profile(&query).as_deref())?
+            .with_request_timeout(search_timeout(&query))
+            .with_headers(search_headers(&query));
         let stream = MeilisearchSearchStream::new(client, index, query);
         Ok(SearchStream::new(stream))
     }
@@ -281,8 +324,9 @@ impl ExtendedGuest for MeilisearchComponent {
     fn unwrapped_stream(index: IndexName, query: SearchQuery) -> Self::SearchStream {
         LOGGING_STATE.with_borrow_mut(|state| state.init());
 
-        let client = Self::create_client()
-            .unwrap_or_else(|_| MeilisearchApi::new("http://localhost:7700".to_string(), None));
+        let client = Self::create_profile_client(search_profile(&query).as_deref())
+            .unwrap_or_else(|_| MeilisearchApi::new("http://localhost:7700".to_string(), None))
+            .with_headers(search_headers(&query));
 
         MeilisearchSearchStream::new(client, index, query)
     }
@@ -0,0 +1,79 @@
+//! Bulk writes with per-document failures.
+//!
+//! The component exports the `golem:search-opensearch/bulk` interface, sending the same bulk
+//! requests as `upsert-many` and `delete-many` but returning the documents whose operation failed
+//! instead of failing the whole call, so that a worker retries only those documents.
+
+use crate::conversions::{build_bulk_delete_operations, build_bulk_operations};
+use crate::OpenSearchComponent;
+use golem_search::bulk::{bulk_failures, BulkFailure as OpenSearchBulkFailure};
+use golem_search::golem::search::types::{Doc, DocumentId, IndexName, SearchError};
+use golem_search::LOGGING_STATE;
+
+#[allow(dead_code)]
+mod bindings {
+    wit_bindgen::generate!({
+        path: "wit",
+        world: "golem:search-opensearch/bulk-library@1.0.0",
+        with: {
+            "golem:search/types@1.0.0": golem_search::golem::search::types,
+        },
+        generate_all,
+    });
+}
+
+use bindings::exports::golem::search_opensearch::bulk::{BulkFailure, Guest};
+
+struct OpenSearchBulk;
+
+impl Guest for OpenSearchBulk {
+    fn upsert_many(index: IndexName, docs: Vec<Doc>) -> Result<Vec<BulkFailure>, SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        if docs.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let client = OpenSearchComponent::create_client()?;
+        let bulk_body = build_bulk_operations(&index, docs).map_err(SearchError::InvalidQuery)?;
+
+        let response = client.bulk_index(&bulk_body)?;
+
+        Ok(bulk_failures(&response.items)
+            .into_iter()
+            .map(opensearch_failure_to_bulk_failure)
+            .collect())
+    }
+
+    fn delete_many(
+        index: IndexName,
+        ids: Vec<DocumentId>,
+    ) -> Result<Vec<BulkFailure>, SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        if ids.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let client = OpenSearchComponent::create_client()?;
+        let bulk_body = build_bulk_delete_operations(&index, &ids);
+
+        let response = client.bulk_index(&bulk_body)?;
+
+        Ok(bulk_failures(&response.items)
+            .into_iter()
+            .map(opensearch_failure_to_bulk_failure)
+            .collect())
+    }
+}
+
+fn opensearch_failure_to_bulk_failure(failure: OpenSearchBulkFailure) -> BulkFailure {
+    BulkFailure {
+        id: failure.id,
+        status: failure.status,
+        code: failure.code,
+        reason: failure.reason,
+    }
+}
+
+bindings::export!(OpenSearchBulk with_types_in bindings);
@@ -1,6 +1,12 @@
-use golem_search::config::{get_max_retries_config, get_timeout_config};
-use golem_search::error::{from_reqwest_error, internal_error, search_error_from_status};
+use crate::sigv4::SigV4Signer;
+use chrono::Utc;
+use golem_search::config::get_timeout_config;
+use golem_search::correlation::{self, CORRELATION_ID_HEADER};
+use golem_search::error::{from_reqwest_error, from_send_error, search_error_from_failed_response};
 use golem_search::golem::search::types::SearchError;
+use golem_search::logging::RedactedResponse;
+use golem_search::pagination::collect_page;
+use golem_search::retry::RetryPolicy;
 use log::trace;
 use reqwest::{Client, Method, RequestBuilder, Response};
 use serde::de::DeserializeOwned;
@@ -9,6 +15,17 @@ use serde_json::{json, Map, Value};
 use std::fmt::Debug;
 use std::time::Duration;
 
+/// Header of the security plugin selecting the tenant of a request
+const SECURITY_TENANT_HEADER: &str = "securitytenant";
+
+/// Header of the security plugin running a request as another user
+const IMPERSONATE_AS_HEADER: &str = "opendistro_security_impersonate_as";
+
+/// Default `index.max_result_window`, the deepest hit a regular search can return
+pub const MAX_RESULT_WINDOW: u32 = 10_000;
+
+const SCROLL_PAGINATION_BATCH_SIZE: u32 = 1_000;
+
 /// The OpenSearch API client for managing indices and performing search
 /// Based on the OpenSearch REST API
 #[derive(Clone)]
@@ -18,7 +35,32 @@ pub struct OpenSearchApi {
     api_key: Option<String>,
     username: Option<String>,
     password: Option<String>,
-    max_retries: u32,
+    /// Signs the requests with AWS credentials instead of the other credentials
+    sigv4: Option<SigV4Signer>,
+    retry_policy: RetryPolicy,
+    request_timeout: Option<Duration>,
+    headers: Vec<(String, String)>,
+    security: SecurityContext,
+}
+
+/// The tenant and impersonated user of the requests, for clusters with the security plugin
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SecurityContext {
+    /// The tenant addressed, such as `global_tenant`, `private` or the name of a custom tenant
+    pub tenant: Option<String>,
+    /// The user whose roles the requests run with, for authenticated users allowed to
+    /// impersonate it by `plugins.security.authcz.rest_impersonation_user`
+    pub impersonate_as: Option<String>,
+}
+
+impl SecurityContext {
+    /// This context, with the values of `other` for the ones it doesn't set
+    pub fn or(self, other: SecurityContext) -> SecurityContext {
+        SecurityContext {
+            tenant: self.tenant.or(other.tenant),
+            impersonate_as: self.impersonate_as.or(other.impersonate_as),
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -37,7 +79,7 @@ pub struct OpenSearchMappings {
     pub dynamic: Option<bool>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OpenSearchQuery {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub query: Option<Value>,
@@ -53,6 +95,12 @@ pub struct OpenSearchQuery {
     pub aggs: Option<Value>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub _source: Option<Value>,
+    /// The point in time searched instead of the index, with its keep alive
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pit: Option<Value>,
+    /// The sort values of the last hit of the previous page
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub search_after: Option<Vec<Value>>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -63,6 +111,9 @@ pub struct OpenSearchSearchResponse {
     pub hits: OpenSearchHits,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub aggregations: Option<Value>,
+    /// The id of the point in time to search next, for searches of a point in time
+    #[serde(default)]
+    pub pit_id: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -93,6 +144,9 @@ pub struct OpenSearchHit {
     pub source: Option<Value>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub highlight: Option<Value>,
+    /// The sort values of the hit, for sorted searches
+    #[serde(default)]
+    pub sort: Option<Vec<Value>>,
 }
 
 #[derive(Debug, Serialize)]
@@ -170,6 +224,42 @@ pub struct OpenSearchScrollResponse {
     pub aggregations: Option<Value>,
 }
 
+/// A result set of the SQL plugin in the JDBC format, whose rows list the values of the columns of
+/// the schema in order
+#[derive(Debug, Deserialize)]
+pub struct OpenSearchSqlResponse {
+    pub schema: Vec<OpenSearchSqlColumn>,
+    pub datarows: Vec<Vec<Value>>,
+    #[serde(default)]
+    pub total: Option<u32>,
+}
+
+#[derive(Debug, Deserialize)]
+#[allow(dead_code)]
+pub struct OpenSearchSqlColumn {
+    pub name: String,
+    /// The name given to the column by the query, such as `n` for `count(*) AS n`
+    #[serde(default)]
+    pub alias: Option<String>,
+    #[serde(rename = "type")]
+    pub column_type: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OpenSearchPointInTimeResponse {
+    pub pit_id: String,
+}
+
+/// A composable index template, applying its settings and mappings to the indices created with a
+/// name matching one of its patterns
+#[derive(Debug, Serialize)]
+pub struct OpenSearchIndexTemplate {
+    pub index_patterns: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub priority: Option<u32>,
+    pub template: OpenSearchSettings,
+}
+
 #[derive(Debug, Serialize)]
 pub struct ScrollRequest {
     pub scroll: String,
@@ -184,7 +274,6 @@ impl OpenSearchApi {
         api_key: Option<String>,
     ) -> Self {
         let timeout_secs = get_timeout_config();
-        let max_retries = get_max_retries_config();
 
         let client = Client::builder()
             .timeout(Duration::from_secs(timeout_secs))
@@ -197,116 +286,53 @@ impl OpenSearchApi {
             api_key,
             username,
             password,
-            max_retries,
+            sigv4: None,
+            retry_policy: RetryPolicy::from_config(),
+            request_timeout: None,
+            headers: Vec::new(),
+            security: SecurityContext::default(),
         }
     }
 
-    fn should_retry_error(&self, error: &reqwest::Error) -> bool {
-        error.is_timeout() || error.is_request()
+    /// Signs every request with AWS Signature Version 4, as Amazon OpenSearch Service expects
+    pub fn with_sigv4(mut self, signer: Option<SigV4Signer>) -> Self {
+        self.sigv4 = signer;
+        self
     }
 
-    fn calculate_backoff_delay(attempt: u32, is_rate_limited: bool) -> Duration {
-        let base_delay_ms = if is_rate_limited { 1000 } else { 200 }; // 1s for rate limit, 200ms for others
-        let max_delay_ms = 30000; // 30 seconds max
+    /// Applies a timeout to every request sent by this client
+    pub fn with_request_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.request_timeout = timeout;
+        self
+    }
 
-        let delay_ms = std::cmp::min(max_delay_ms, base_delay_ms * (2_u64.pow(attempt)));
+    /// Adds custom headers to every request sent by this client
+    pub fn with_headers(mut self, headers: Vec<(String, String)>) -> Self {
+        self.headers = headers;
+        self
+    }
 
-        Duration::from_millis(delay_ms)
+    /// Sets the tenant and impersonated user of every request sent by this client, keeping the
+    /// current ones where `security` doesn't set them
+    pub fn with_security(mut self, security: SecurityContext) -> Self {
+        self.security = security.or(self.security);
+        self
     }
 
-    fn execute_with_retry_sync<F>(&self, operation: F) -> Result<Response, SearchError>
-    where
-        F: Fn() -> Result<Response, reqwest::Error> + Send + Sync,
-    {
-        let mut last_error = None;
-
-        for attempt in 0..=self.max_retries {
-            match operation() {
-                Ok(response) => {
-                    match response.status().as_u16() {
-                        429 => {
-                            // Rate limited - should retry with longer delay
-                            if attempt < self.max_retries {
-                                let delay = Self::calculate_backoff_delay(attempt, true);
-                                trace!(
-                                    "Rate limited (429), retrying in {:?} (attempt {}/{})",
-                                    delay,
-                                    attempt + 1,
-                                    self.max_retries + 1
-                                );
-                                std::thread::sleep(delay);
-                                continue;
-                            } else {
-                                return Ok(response);
-                            }
-                        }
-                        502..=504 => {
-                            // Server errors - should retry
-                            if attempt < self.max_retries {
-                                let delay = Self::calculate_backoff_delay(attempt, false);
-                                trace!(
-                                    "Server error ({}), retrying in {:?} (attempt {}/{})",
-                                    response.status().as_u16(),
-                                    delay,
-                                    attempt + 1,
-                                    self.max_retries + 1
-                                );
-                                std::thread::sleep(delay);
-                                continue;
-                            } else {
-                                return Ok(response);
-                            }
-                        }
-                        _ => return Ok(response),
-                    }
-                }
-                Err(e) => {
-                    last_error = Some(e);
-
-                    if let Some(ref error) = last_error {
-                        if self.should_retry_error(error) && attempt < self.max_retries {
-                            let is_rate_limited = error.status().is_some_and(|s| s.as_u16() == 429);
-                            let delay = Self::calculate_backoff_delay(attempt, is_rate_limited);
-
-                            trace!(
-                                "Request failed, retrying in {:?} (attempt {}/{}): {:?}",
-                                delay,
-                                attempt + 1,
-                                self.max_retries + 1,
-                                error
-                            );
-                            std::thread::sleep(delay);
-                        } else if !self.should_retry_error(error) {
-                            trace!("Request failed with non-retryable error: {error:?}");
-                            break;
-                        }
-                    }
-                }
+    /// Sends a request, signing it first when the client signs its requests
+    fn send(&self, builder: RequestBuilder) -> Result<Response, reqwest::Error> {
+        match &self.sigv4 {
+            Some(signer) => {
+                let mut request = builder.build()?;
+                signer.sign(&mut request, Utc::now());
+                self.client.execute(request)
             }
+            None => builder.send(),
         }
-
-        let error = last_error.unwrap();
-        Err(internal_error(format!(
-            "Request failed after {} attempts: {}",
-            self.max_retries + 1,
-            error
-        )))
     }
 
     fn create_request(&self, method: Method, url: &str) -> RequestBuilder {
-        let mut builder = self
-            .client
-            .request(method, url)
-            .header("Content-Type", "application/json");
-
-        // Add authentication
-        if let Some(api_key) = &self.api_key {
-            builder = builder.header("Authorization", format!("ApiKey {api_key}"));
-        } else if let (Some(username), Some(password)) = (&self.username, &self.password) {
-            builder = builder.basic_auth(username, Some(password));
-        }
-
-        builder
+        self.create_request_with_content_type(method, url, "application/json")
     }
 
     fn create_request_with_content_type(
@@ -318,13 +344,33 @@ impl OpenSearchApi {
         let mut builder = self
             .client
             .request(method, url)
+            .header(CORRELATION_ID_HEADER, correlation::current())
+            .header("X-Opaque-Id", correlation::current())
             .header("Content-Type", content_type);
 
-        // Add authentication
-        if let Some(api_key) = &self.api_key {
-            builder = builder.header("Authorization", format!("ApiKey {api_key}"));
-        } else if let (Some(username), Some(password)) = (&self.username, &self.password) {
-            builder = builder.basic_auth(username, Some(password));
+        if let Some(timeout) = self.request_timeout {
+            builder = builder.timeout(timeout);
+        }
+
+        // Custom headers never carry reserved names, so they cannot clash with the ones below
+        for (name, value) in &self.headers {
+            builder = builder.header(name, value);
+        }
+
+        if let Some(tenant) = &self.security.tenant {
+            builder = builder.header(SECURITY_TENANT_HEADER, tenant);
+        }
+        if let Some(user) = &self.security.impersonate_as {
+            builder = builder.header(IMPERSONATE_AS_HEADER, user);
+        }
+
+        // Add authentication, unless requests are signed when sent
+        if self.sigv4.is_none() {
+            if let Some(api_key) = &self.api_key {
+                builder = builder.header("Authorization", format!("ApiKey {api_key}"));
+            } else if let (Some(username), Some(password)) = (&self.username, &self.password) {
+                builder = builder.basic_auth(username, Some(password));
+            }
         }
 
         builder
@@ -339,20 +385,23 @@ impl OpenSearchApi {
 
         let url = format!("{}/{}", self.base_url, index_name);
 
-        let response = self.execute_with_retry_sync(|| {
-            let mut request = self.create_request(Method::PUT, &url);
+        let response = self
+            .retry_policy
+            .execute(|| {
+                let mut request = self.create_request(Method::PUT, &url);
 
-            if let Some(ref settings) = settings {
-                request = request.json(settings);
-            }
+                if let Some(ref settings) = settings {
+                    request = request.json(settings);
+                }
 
-            request.send()
-        })?;
+                self.send(request)
+            })
+            .map_err(|e| from_send_error("Failed to create index", e))?;
 
         if response.status().is_success() {
             Ok(())
         } else {
-            Err(search_error_from_status(response.status()))
+            Err(search_error_from_failed_response(response))
         }
     }
 
@@ -361,13 +410,15 @@ impl OpenSearchApi {
 
         let url = format!("{}/{}", self.base_url, index_name);
 
-        let response =
-            self.execute_with_retry_sync(|| self.create_request(Method::DELETE, &url).send())?;
+        let response = self
+            .retry_policy
+            .execute(|| self.send(self.create_request(Method::DELETE, &url)))
+            .map_err(|e| from_send_error("Failed to delete index", e))?;
 
         if response.status().is_success() {
             Ok(())
         } else {
-            Err(search_error_from_status(response.status()))
+            Err(search_error_from_failed_response(response))
         }
     }
 
@@ -376,8 +427,10 @@ impl OpenSearchApi {
 
         let url = format!("{}/_cat/indices?format=json", self.base_url);
 
-        let response =
-            self.execute_with_retry_sync(|| self.create_request(Method::GET, &url).send())?;
+        let response = self
+            .retry_policy
+            .execute(|| self.send(self.create_request(Method::GET, &url)))
+            .map_err(|e| from_send_error("Failed to list indices", e))?;
 
         parse_response(response)
     }
@@ -392,14 +445,15 @@ impl OpenSearchApi {
 
         let url = format!("{}/{}/_doc/{}", self.base_url, index_name, id);
 
-        let response = self.execute_with_retry_sync(|| {
-            self.create_request(Method::PUT, &url).json(document).send()
-        })?;
+        let response = self
+            .retry_policy
+            .execute(|| self.send(self.create_request(Method::PUT, &url).json(document)))
+            .map_err(|e| from_send_error("Failed to index document", e))?;
 
         if response.status().is_success() {
             Ok(())
         } else {
-            Err(search_error_from_status(response.status()))
+            Err(search_error_from_failed_response(response))
         }
     }
 
@@ -408,11 +462,19 @@ impl OpenSearchApi {
 
         let url = format!("{}/_bulk", self.base_url);
 
-        let response = self.execute_with_retry_sync(|| {
-            self.create_request_with_content_type(Method::POST, &url, "application/x-ndjson")
-                .body(operations.to_string())
-                .send()
-        })?;
+        let response = self
+            .retry_policy
+            .execute(|| {
+                self.send(
+                    self.create_request_with_content_type(
+                        Method::POST,
+                        &url,
+                        "application/x-ndjson",
+                    )
+                    .body(operations.to_string()),
+                )
+            })
+            .map_err(|e| from_send_error("Failed to perform bulk operation", e))?;
 
         parse_response(response)
     }
@@ -422,13 +484,15 @@ impl OpenSearchApi {
 
         let url = format!("{}/{}/_doc/{}", self.base_url, index_name, id);
 
-        let response =
-            self.execute_with_retry_sync(|| self.create_request(Method::DELETE, &url).send())?;
+        let response = self
+            .retry_policy
+            .execute(|| self.send(self.create_request(Method::DELETE, &url)))
+            .map_err(|e| from_send_error("Failed to delete document", e))?;
 
         if response.status().is_success() {
             Ok(())
         } else {
-            Err(search_error_from_status(response.status()))
+            Err(search_error_from_failed_response(response))
         }
     }
 
@@ -437,8 +501,10 @@ impl OpenSearchApi {
 
         let url = format!("{}/{}/_doc/{}", self.base_url, index_name, id);
 
-        let response =
-            self.execute_with_retry_sync(|| self.create_request(Method::GET, &url).send())?;
+        let response = self
+            .retry_policy
+            .execute(|| self.send(self.create_request(Method::GET, &url)))
+            .map_err(|e| from_send_error("Failed to get document", e))?;
 
         if response.status() == 404 {
             Ok(None)
@@ -450,7 +516,7 @@ impl OpenSearchApi {
                 Ok(None)
             }
         } else {
-            Err(search_error_from_status(response.status()))
+            Err(search_error_from_failed_response(response))
         }
     }
 
@@ -463,9 +529,10 @@ impl OpenSearchApi {
 
         let url = format!("{}/{}/_search", self.base_url, index_name);
 
-        let response = self.execute_with_retry_sync(|| {
-            self.create_request(Method::POST, &url).json(query).send()
-        })?;
+        let response = self
+            .retry_policy
+            .execute(|| self.send(self.create_request(Method::POST, &url).json(query)))
+            .map_err(|e| from_send_error("Failed to search", e))?;
 
         parse_response(response)
     }
@@ -483,13 +550,55 @@ impl OpenSearchApi {
             self.base_url, index_name, scroll_timeout
         );
 
-        let response = self.execute_with_retry_sync(|| {
-            self.create_request(Method::POST, &url).json(query).send()
-        })?;
+        let response = self
+            .retry_policy
+            .execute(|| self.send(self.create_request(Method::POST, &url).json(query)))
+            .map_err(|e| from_send_error("Failed to search with scroll", e))?;
 
         parse_response(response)
     }
 
+    /// Returns the page selected by `from` and `size` of `query` by iterating over a scroll,
+    /// which is not limited by the index's `max_result_window` like a regular search is
+    pub fn search_with_scroll_pagination(
+        &self,
+        index_name: &str,
+        query: &OpenSearchQuery,
+    ) -> Result<OpenSearchSearchResponse, SearchError> {
+        let from = query.from.unwrap_or(0) as usize;
+        let size = query.size.unwrap_or(10) as usize;
+        trace!("Paginating index {index_name} with scroll, from: {from}, size: {size}");
+
+        let mut scroll_query = query.clone();
+        scroll_query.from = None;
+        scroll_query.size = Some(SCROLL_PAGINATION_BATCH_SIZE);
+
+        let first = self.search_with_scroll(index_name, &scroll_query, "1m")?;
+        let mut scroll_id = first.scroll_id;
+        let result = collect_page(from, size, first.hits.hits, || {
+            let next = self.scroll(&scroll_id, "1m")?;
+            scroll_id = next.scroll_id;
+            Ok(next.hits.hits)
+        });
+
+        if let Err(error) = self.clear_scroll(&scroll_id) {
+            trace!("Failed to clear pagination scroll: {error:?}");
+        }
+        let hits = result?;
+
+        Ok(OpenSearchSearchResponse {
+            took: first.took,
+            timed_out: first.timed_out,
+            hits: OpenSearchHits {
+                total: first.hits.total,
+                max_score: first.hits.max_score,
+                hits,
+            },
+            aggregations: first.aggregations,
+            pit_id: None,
+        })
+    }
+
     pub fn scroll(
         &self,
         scroll_id: &str,
@@ -499,16 +608,20 @@ impl OpenSearchApi {
 
         let url = format!("{}/_search/scroll", self.base_url);
 
-        let response = self.execute_with_retry_sync(|| {
-            let scroll_request = ScrollRequest {
-                scroll: scroll_timeout.to_string(),
-                scroll_id: scroll_id.to_string(),
-            };
-
-            self.create_request(Method::POST, &url)
-                .json(&scroll_request)
-                .send()
-        })?;
+        let scroll_request = ScrollRequest {
+            scroll: scroll_timeout.to_string(),
+            scroll_id: scroll_id.to_string(),
+        };
+
+        let response = self
+            .retry_policy
+            .execute(|| {
+                self.send(
+                    self.create_request(Method::POST, &url)
+                        .json(&scroll_request),
+                )
+            })
+            .map_err(|e| from_send_error("Failed to scroll", e))?;
 
         parse_response(response)
     }
@@ -521,16 +634,140 @@ impl OpenSearchApi {
             "scroll_id": scroll_id
         });
 
-        let response = self.execute_with_retry_sync(|| {
-            self.create_request(Method::DELETE, &url)
-                .json(&request_body)
-                .send()
-        })?;
+        let response = self
+            .retry_policy
+            .execute(|| {
+                self.send(
+                    self.create_request(Method::DELETE, &url)
+                        .json(&request_body),
+                )
+            })
+            .map_err(|e| from_send_error("Failed to clear scroll", e))?;
+
+        if response.status().is_success() {
+            Ok(())
+        } else {
+            Err(search_error_from_failed_response(response))
+        }
+    }
+
+    /// Creates a point in time of the index, a view of its documents as they are now which stays
+    /// consistent across the searches made within `keep_alive` of each other
+    pub fn create_point_in_time(
+        &self,
+        index_name: &str,
+        keep_alive: &str,
+    ) -> Result<String, SearchError> {
+        trace!("Creating point in time of index {index_name}, keep alive: {keep_alive}");
+
+        let url = format!(
+            "{}/{}/_search/point_in_time?keep_alive={}",
+            self.base_url, index_name, keep_alive
+        );
+
+        let response = self
+            .retry_policy
+            .execute(|| self.send(self.create_request(Method::POST, &url)))
+            .map_err(|e| from_send_error("Failed to create point in time", e))?;
+
+        parse_response::<OpenSearchPointInTimeResponse>(response).map(|pit| pit.pit_id)
+    }
+
+    /// Searches the point in time of the `pit` of the query
+    pub fn search_point_in_time(
+        &self,
+        query: &OpenSearchQuery,
+    ) -> Result<OpenSearchSearchResponse, SearchError> {
+        trace!("Searching point in time with query: {query:?}");
+
+        let url = format!("{}/_search", self.base_url);
+
+        let response = self
+            .retry_policy
+            .execute(|| self.send(self.create_request(Method::POST, &url).json(query)))
+            .map_err(|e| from_send_error("Failed to search point in time", e))?;
+
+        parse_response(response)
+    }
+
+    pub fn delete_point_in_time(&self, pit_id: &str) -> Result<(), SearchError> {
+        trace!("Deleting point in time");
+
+        let url = format!("{}/_search/point_in_time", self.base_url);
+
+        let delete_request = json!({
+            "pit_id": [pit_id]
+        });
+
+        let response = self
+            .retry_policy
+            .execute(|| {
+                self.send(
+                    self.create_request(Method::DELETE, &url)
+                        .json(&delete_request),
+                )
+            })
+            .map_err(|e| from_send_error("Failed to delete point in time", e))?;
+
+        if !response.status().is_success() {
+            trace!("Failed to delete point in time: {}", response.status());
+        }
+        Ok(())
+    }
+
+    /// Runs a query of the SQL plugin, which names the indices it reads itself
+    pub fn sql(&self, query: &str) -> Result<OpenSearchSqlResponse, SearchError> {
+        trace!("Running SQL query: {query}");
+
+        let url = format!("{}/_plugins/_sql?format=jdbc", self.base_url);
+
+        let body = json!({
+            "query": query
+        });
+
+        let response = self
+            .retry_policy
+            .execute(|| self.send(self.create_request(Method::POST, &url).json(&body)))
+            .map_err(|e| from_send_error("Failed to run SQL query", e))?;
+
+        parse_response(response)
+    }
+
+    pub fn put_index_template(
+        &self,
+        name: &str,
+        template: &OpenSearchIndexTemplate,
+    ) -> Result<(), SearchError> {
+        trace!("Putting index template: {name}");
+
+        let url = format!("{}/_index_template/{}", self.base_url, name);
+
+        let response = self
+            .retry_policy
+            .execute(|| self.send(self.create_request(Method::PUT, &url).json(template)))
+            .map_err(|e| from_send_error("Failed to put index template", e))?;
+
+        if response.status().is_success() {
+            Ok(())
+        } else {
+            Err(search_error_from_failed_response(response))
+        }
+    }
+
+    pub fn delete_index_template(&self, name: &str) -> Result<(), SearchError> {
+        trace!("Deleting index template: {name}");
+
+        let url = format!("{}/_index_template/{}", self.base_url, name);
+
+        let response = self
+            .retry_policy
+            .execute(|| self.send(self.create_request(Method::DELETE, &url)))
+            .map_err(|e| from_send_error("Failed to delete index template", e))?;
 
         if response.status().is_success() {
             Ok(())
         } else {
-            Err(search_error_from_status(response.status()))
+            Err(search_error_from_failed_response(response))
         }
     }
 
@@ -539,8 +776,10 @@ impl OpenSearchApi {
 
         let url = format!("{}/{}/_mapping", self.base_url, index_name);
 
-        let response =
-            self.execute_with_retry_sync(|| self.create_request(Method::GET, &url).send())?;
+        let response = self
+            .retry_policy
+            .execute(|| self.send(self.create_request(Method::GET, &url)))
+            .map_err(|e| from_send_error("Failed to get mappings", e))?;
 
         parse_response(response)
     }
@@ -554,14 +793,15 @@ impl OpenSearchApi {
 
         let url = format!("{}/{}/_mapping", self.base_url, index_name);
 
-        let response = self.execute_with_retry_sync(|| {
-            self.create_request(Method::PUT, &url).json(mappings).send()
-        })?;
+        let response = self
+            .retry_policy
+            .execute(|| self.send(self.create_request(Method::PUT, &url).json(mappings)))
+            .map_err(|e| from_send_error("Failed to put mappings", e))?;
 
         if response.status().is_success() {
             Ok(())
         } else {
-            Err(search_error_from_status(response.status()))
+            Err(search_error_from_failed_response(response))
         }
     }
 }
@@ -569,7 +809,10 @@ impl OpenSearchApi {
 fn parse_response<T: DeserializeOwned + Debug>(response: Response) -> Result<T, SearchError> {
     let status = response.status();
 
-    trace!("Received response from OpenSearch API: {response:?}");
+    trace!(
+        "Received response from OpenSearch API: {:?}",
+        RedactedResponse(&response)
+    );
 
     if status.is_success() {
         let body = response
@@ -580,12 +823,10 @@ fn parse_response<T: DeserializeOwned + Debug>(response: Response) -> Result<T,
 
         Ok(body)
     } else {
-        let error_body = response
-            .text()
-            .map_err(|err| from_reqwest_error("Failed to receive error response body", err))?;
+        let error = search_error_from_failed_response(response);
 
-        trace!("Received {status} response from OpenSearch API: {error_body:?}");
+        trace!("Received {status} response from OpenSearch API: {error:?}");
 
-        Err(search_error_from_status(status))
+        Err(error)
     }
 }
@@ -1,13 +1,34 @@
 use crate::client::{
-    OpenSearchMappings, OpenSearchQuery, OpenSearchScrollResponse, OpenSearchSearchResponse,
-    OpenSearchSettings,
+    OpenSearchMappings, OpenSearchQuery, OpenSearchSearchResponse, OpenSearchSettings,
+    OpenSearchSqlResponse, SecurityContext,
 };
+use golem_search::config::search_filters_match_any;
+use golem_search::error::invalid_query;
 use golem_search::golem::search::types::{
-    Doc, FieldType, Schema, SchemaField, SearchHit, SearchQuery, SearchResults,
+    Doc, FieldType, Schema, SchemaField, SearchError, SearchHit, SearchQuery, SearchResults,
 };
-use serde_json::{Map, Value};
+use serde_json::{json, Map, Value};
 use std::collections::HashMap;
 
+/// Document field holding the vector embedding of a document, mapped as a `knn_vector` in
+/// indexes created with vector options
+pub const VECTOR_FIELD: &str = "vector";
+
+/// Most neighbors the k-NN plugin returns for a query
+const MAX_KNN_K: u32 = 10_000;
+
+/// How the vectors of the documents are mapped and indexed by the k-NN plugin
+#[derive(Debug, Clone)]
+pub struct VectorOptions {
+    pub dimension: u32,
+    /// The approximate search algorithm, such as `hnsw` or `ivf`
+    pub method: String,
+    /// The distance between vectors, such as `cosinesimil`, `l2` or `innerproduct`
+    pub space_type: String,
+    /// The library implementing the method, such as `lucene` or `faiss`
+    pub engine: String,
+}
+
 pub fn doc_to_opensearch_document(doc: Doc) -> Result<Value, String> {
     let mut opensearch_doc = Map::new();
 
@@ -52,6 +73,7 @@ pub fn opensearch_document_to_doc(document: Value) -> Doc {
 }
 
 pub fn search_query_to_opensearch_request(query: SearchQuery) -> OpenSearchQuery {
+    let match_any = search_filters_match_any(&query);
     let mut opensearch_query = OpenSearchQuery {
         query: None,
         from: query.offset,
@@ -60,6 +82,8 @@ pub fn search_query_to_opensearch_request(query: SearchQuery) -> OpenSearchQuery
         highlight: None,
         aggs: None,
         _source: None,
+        pit: None,
+        search_after: None,
     };
 
     if let Some(q) = query.q {
@@ -112,6 +136,16 @@ pub fn search_query_to_opensearch_request(query: SearchQuery) -> OpenSearchQuery
             }
         }
 
+        if match_any {
+            let filters = bool_query["bool"]["filter"].take();
+            bool_query["bool"]["filter"] = serde_json::json!([{
+                "bool": {
+                    "should": filters,
+                    "minimum_should_match": 1
+                }
+            }]);
+        }
+
         opensearch_query.query = Some(bool_query);
     }
 
@@ -193,6 +227,37 @@ pub fn search_query_to_opensearch_request(query: SearchQuery) -> OpenSearchQuery
     opensearch_query
 }
 
+/// The query of a page of a stream over the point in time `pit_id`, continuing after the hit with
+/// the `search_after` sort values when given. The sort of the query, or relevance without one,
+/// ends with the `_id` tiebreaker, so that hits with equal sort values are neither skipped nor
+/// repeated across pages. Only the first page skips the offset of the query and counts facets.
+pub fn search_query_to_point_in_time_query(
+    query: SearchQuery,
+    pit_id: &str,
+    keep_alive: &str,
+    search_after: Option<Vec<Value>>,
+) -> OpenSearchQuery {
+    let size = query.per_page.unwrap_or(100);
+    let mut opensearch_query = search_query_to_opensearch_request(query);
+
+    let mut sort = opensearch_query
+        .sort
+        .take()
+        .unwrap_or_else(|| vec![json!({ "_score": { "order": "desc" } })]);
+    sort.push(json!({ "_id": { "order": "asc" } }));
+    opensearch_query.sort = Some(sort);
+    opensearch_query.size = Some(size);
+    opensearch_query.pit = Some(json!({ "id": pit_id, "keep_alive": keep_alive }));
+
+    if search_after.is_some() {
+        opensearch_query.from = None;
+        opensearch_query.aggs = None;
+    }
+    opensearch_query.search_after = search_after;
+
+    opensearch_query
+}
+
 pub fn opensearch_response_to_search_results(response: OpenSearchSearchResponse) -> SearchResults {
     let hits: Vec<SearchHit> = response
         .hits
@@ -260,29 +325,22 @@ pub fn opensearch_response_to_search_results(response: OpenSearchSearchResponse)
 
     SearchResults {
         total: Some(total),
-        page: None,     // OpenSearch uses offset/size, not page numbers
-        per_page: None, // We'll calculate this from the request
+        page: None, // OpenSearch uses offset/size, not page numbers
+        per_page: None,
         hits,
         facets: Some(serde_json::to_string(&facets).unwrap_or_else(|_| "{}".to_string())),
         took_ms: Some(response.took),
+        relaxation: None,
+        navigation: None,
     }
 }
 
-pub fn opensearch_scroll_response_to_search_results(
-    response: OpenSearchScrollResponse,
-) -> SearchResults {
-    // Convert scroll response to regular search response format
-    let regular_response = OpenSearchSearchResponse {
-        took: response.took,
-        timed_out: response.timed_out,
-        hits: response.hits,
-        aggregations: response.aggregations,
-    };
-
-    opensearch_response_to_search_results(regular_response)
-}
-
-pub fn schema_to_opensearch_settings(schema: Schema) -> OpenSearchSettings {
+/// The settings of an index with the fields of a schema and, given vector options, the k-NN
+/// mapping of the vectors of the documents
+pub fn schema_to_opensearch_settings(
+    schema: Schema,
+    vector_options: Option<&VectorOptions>,
+) -> OpenSearchSettings {
     let mut properties = Map::new();
 
     for field in schema.fields {
@@ -312,6 +370,21 @@ pub fn schema_to_opensearch_settings(schema: Schema) -> OpenSearchSettings {
         properties.insert(field.name, Value::Object(field_mapping));
     }
 
+    if let Some(options) = vector_options {
+        properties.insert(
+            VECTOR_FIELD.to_string(),
+            json!({
+                "type": "knn_vector",
+                "dimension": options.dimension,
+                "method": {
+                    "name": options.method,
+                    "space_type": options.space_type,
+                    "engine": options.engine
+                }
+            }),
+        );
+    }
+
     let mappings = OpenSearchMappings {
         properties: Some(properties),
         dynamic: Some(true),
@@ -326,6 +399,9 @@ pub fn schema_to_opensearch_settings(schema: Schema) -> OpenSearchSettings {
         "number_of_replicas".to_string(),
         Value::Number(serde_json::Number::from(0)),
     );
+    if vector_options.is_some() {
+        index_settings.insert("knn".to_string(), Value::Bool(true));
+    }
 
     OpenSearchSettings {
         mappings: Some(mappings),
@@ -345,6 +421,12 @@ pub fn opensearch_mappings_to_schema(
                 if let Some(Value::Object(props)) = mappings.get("properties") {
                     for (field_name, field_def) in props {
                         if let Value::Object(field_map) = field_def {
+                            // Vectors are part of the documents, not fields of the schema
+                            if field_map.get("type").and_then(|t| t.as_str()) == Some("knn_vector")
+                            {
+                                continue;
+                            }
+
                             let field_type = field_map
                                 .get("type")
                                 .and_then(|t| t.as_str())
@@ -383,6 +465,175 @@ pub fn opensearch_mappings_to_schema(
     }
 }
 
+/// The raw SQL query of the `sql` string of a query's provider parameters, run by the SQL plugin
+/// instead of converting the query to the query DSL
+pub fn query_sql(query: &SearchQuery) -> Result<Option<String>, SearchError> {
+    let Some(params) = query
+        .config
+        .as_ref()
+        .and_then(|config| config.provider_params.as_deref())
+    else {
+        return Ok(None);
+    };
+    let Ok(params) = serde_json::from_str::<Map<String, Value>>(params) else {
+        return Ok(None);
+    };
+
+    match params.get("sql") {
+        None => Ok(None),
+        Some(Value::String(sql)) if !sql.trim().is_empty() => Ok(Some(sql.clone())),
+        Some(_) => Err(invalid_query("The query `sql` must be a non-empty string")),
+    }
+}
+
+/// The rows of an SQL result set as hits, whose content maps the columns, by alias when they
+/// have one, to their values. Hits are identified by their `_id` or `id` column when selected, and
+/// by their row number otherwise.
+pub fn opensearch_sql_response_to_search_results(response: OpenSearchSqlResponse) -> SearchResults {
+    let columns = response
+        .schema
+        .iter()
+        .map(|column| column.alias.clone().unwrap_or_else(|| column.name.clone()))
+        .collect::<Vec<_>>();
+
+    let hits = response
+        .datarows
+        .into_iter()
+        .enumerate()
+        .map(|(row, values)| {
+            let content = columns
+                .iter()
+                .cloned()
+                .zip(values)
+                .collect::<Map<String, Value>>();
+            let id = ["_id", "id"]
+                .iter()
+                .find_map(|column| match content.get(*column)? {
+                    Value::String(id) => Some(id.clone()),
+                    Value::Number(id) => Some(id.to_string()),
+                    _ => None,
+                })
+                .unwrap_or_else(|| row.to_string());
+
+            SearchHit {
+                id,
+                score: None,
+                content: Some(Value::Object(content).to_string()),
+                highlights: None,
+            }
+        })
+        .collect::<Vec<_>>();
+
+    SearchResults {
+        total: response.total.or(Some(hits.len() as u32)),
+        page: None,
+        per_page: None,
+        hits,
+        facets: None,
+        took_ms: None,
+        relaxation: None,
+        navigation: None,
+    }
+}
+
+/// The tenant and impersonated user of a query, from the `tenant` and `impersonate_as` strings of
+/// its provider parameters. Parameters which aren't a JSON object set neither.
+pub fn query_security_context(query: &SearchQuery) -> SecurityContext {
+    query
+        .config
+        .as_ref()
+        .and_then(|config| config.provider_params.as_deref())
+        .and_then(|params| serde_json::from_str::<Map<String, Value>>(params).ok())
+        .map(|params| {
+            let param = |name: &str| params.get(name).and_then(Value::as_str).map(str::to_string);
+            SecurityContext {
+                tenant: param("tenant"),
+                impersonate_as: param("impersonate_as"),
+            }
+        })
+        .unwrap_or_default()
+}
+
+/// The k-NN query clause of the query vector, given as `{"vector": [...]}` in the provider
+/// parameters of the query along with the optional number `k` of neighbors, by default the `depth`
+/// of the requested page
+pub fn query_to_knn(query: &SearchQuery, depth: u32) -> Result<Option<Value>, SearchError> {
+    let Some(params) = query
+        .config
+        .as_ref()
+        .and_then(|config| config.provider_params.as_deref())
+    else {
+        return Ok(None);
+    };
+    let params = serde_json::from_str::<Map<String, Value>>(params)
+        .map_err(|e| invalid_query(format!("Invalid provider parameters: {e}")))?;
+
+    let Some(vector) = params.get(VECTOR_FIELD) else {
+        return Ok(None);
+    };
+    let vector = vector
+        .as_array()
+        .filter(|values| !values.is_empty() && values.iter().all(Value::is_number))
+        .ok_or_else(|| invalid_query("The query `vector` must be a non-empty array of numbers"))?;
+
+    let k = match params.get("k") {
+        None => depth.clamp(1, MAX_KNN_K),
+        Some(k) => k
+            .as_u64()
+            .filter(|k| *k > 0)
+            .map(|k| k.min(MAX_KNN_K as u64) as u32)
+            .ok_or_else(|| invalid_query("The query `k` must be a positive integer"))?,
+    };
+
+    Ok(Some(json!({
+        "knn": {
+            VECTOR_FIELD: {
+                "vector": vector,
+                "k": k
+            }
+        }
+    })))
+}
+
+/// Replaces the query of a request by a k-NN query. The filters of the query restrict the
+/// neighbors, and the text query, if any, adds its score to the one of the neighbors matching it.
+pub fn with_knn(mut request: OpenSearchQuery, mut knn: Value) -> OpenSearchQuery {
+    let (text_query, filter) = match request.query.take() {
+        Some(Value::Object(mut query)) if query.contains_key("bool") => {
+            let mut bool_query = query.remove("bool").unwrap_or_default();
+            (bool_query["must"].take(), bool_query["filter"].take())
+        }
+        Some(query) => (query, Value::Null),
+        None => (Value::Null, Value::Null),
+    };
+
+    let filter = filter
+        .as_array()
+        .filter(|filter| !filter.is_empty())
+        .map(|filter| json!({ "bool": { "filter": filter } }));
+    if let Some(filter) = &filter {
+        knn["knn"][VECTOR_FIELD]["filter"] = filter.clone();
+    }
+
+    let has_text_query = !text_query.is_null() && text_query.get("match_all").is_none();
+    request.query = if has_text_query {
+        let mut bool_query = json!({
+            "bool": {
+                "should": [knn, text_query],
+                "minimum_should_match": 1
+            }
+        });
+        if let Some(filter) = filter {
+            bool_query["bool"]["filter"] = filter;
+        }
+        Some(bool_query)
+    } else {
+        Some(knn)
+    };
+
+    request
+}
+
 pub fn create_retry_query(original_query: &SearchQuery, partial_hits: &[SearchHit]) -> SearchQuery {
     let mut retry_query = original_query.clone();
 
@@ -393,10 +644,52 @@ pub fn create_retry_query(original_query: &SearchQuery, partial_hits: &[SearchHi
     retry_query
 }
 
+/// The body of a bulk request indexing the documents, each under the id of its document
+pub fn build_bulk_operations(index_name: &str, docs: Vec<Doc>) -> Result<String, String> {
+    let mut bulk_operations = Vec::new();
+    for doc in docs {
+        let opensearch_doc = doc_to_opensearch_document(doc)?;
+
+        let doc_id = opensearch_doc
+            .get("id")
+            .and_then(|v| v.as_str())
+            .unwrap_or("unknown")
+            .to_string();
+
+        let action = json!({
+            "index": {
+                "_index": index_name,
+                "_id": doc_id
+            }
+        });
+        bulk_operations.push(serde_json::to_string(&action).map_err(|e| e.to_string())?);
+        bulk_operations.push(serde_json::to_string(&opensearch_doc).map_err(|e| e.to_string())?);
+    }
+
+    Ok(bulk_operations.join("\n") + "\n")
+}
+
+pub fn build_bulk_delete_operations(index_name: &str, ids: &[String]) -> String {
+    let bulk_operations = ids
+        .iter()
+        .map(|id| {
+            json!({
+                "delete": {
+                    "_index": index_name,
+                    "_id": id
+                }
+            })
+            .to_string()
+        })
+        .collect::<Vec<_>>();
+
+    bulk_operations.join("\n") + "\n"
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use golem_search::golem::search::types::HighlightConfig;
+    use golem_search::golem::search::types::{HighlightConfig, SearchConfig};
 
     #[test]
     fn test_doc_to_opensearch_document() {
@@ -474,6 +767,141 @@ mod tests {
         assert!(opensearch_query.highlight.is_some());
     }
 
+    fn vector_query(q: Option<&str>, filters: Vec<String>, params: &str) -> SearchQuery {
+        SearchQuery {
+            q: q.map(str::to_string),
+            filters,
+            sort: vec![],
+            facets: vec![],
+            page: None,
+            per_page: Some(5),
+            offset: None,
+            highlight: None,
+            config: Some(SearchConfig {
+                attributes_to_retrieve: vec![],
+                typo_tolerance: None,
+                timeout_ms: None,
+                boost_fields: vec![],
+                exact_match_boost: None,
+                language: None,
+                provider_params: Some(params.to_string()),
+                profile: None,
+                filter_mode: None,
+                post_filters: vec![],
+                rescore: None,
+                fallbacks: vec![],
+            }),
+        }
+    }
+
+    #[test]
+    fn test_search_query_to_point_in_time_query() {
+        let search_query = SearchQuery {
+            q: Some("laptop".to_string()),
+            filters: vec![],
+            sort: vec![],
+            facets: vec!["category".to_string()],
+            page: None,
+            per_page: Some(50),
+            offset: Some(10),
+            highlight: None,
+            config: None,
+        };
+
+        let first = search_query_to_point_in_time_query(search_query.clone(), "pit-1", "1m", None);
+        assert_eq!(first.from, Some(10));
+        assert_eq!(first.size, Some(50));
+        assert!(first.aggs.is_some());
+        assert_eq!(
+            first.pit,
+            Some(serde_json::json!({ "id": "pit-1", "keep_alive": "1m" }))
+        );
+        assert_eq!(
+            first.sort,
+            Some(vec![
+                serde_json::json!({ "_score": { "order": "desc" } }),
+                serde_json::json!({ "_id": { "order": "asc" } }),
+            ])
+        );
+        assert_eq!(first.search_after, None);
+
+        let next = search_query_to_point_in_time_query(
+            search_query,
+            "pit-1",
+            "1m",
+            Some(vec![serde_json::json!(1.5), serde_json::json!("42")]),
+        );
+        assert_eq!(next.from, None);
+        assert!(next.aggs.is_none());
+        assert_eq!(
+            next.search_after,
+            Some(vec![serde_json::json!(1.5), serde_json::json!("42")])
+        );
+    }
+
+    #[test]
+    fn test_vector_query_to_knn() {
+        let query = vector_query(
+            None,
+            vec!["category:books".to_string()],
+            r#"{"vector": [0.1, 0.2, 0.3]}"#,
+        );
+
+        let knn = query_to_knn(&query, 5).unwrap().unwrap();
+        assert_eq!(knn["knn"][VECTOR_FIELD]["k"], 5);
+
+        let request = with_knn(search_query_to_opensearch_request(query), knn);
+        let knn = &request.query.unwrap()["knn"][VECTOR_FIELD];
+        assert_eq!(
+            knn["filter"]["bool"]["filter"][0]["term"]["category"],
+            "books"
+        );
+    }
+
+    #[test]
+    fn test_hybrid_vector_query_keeps_text_query() {
+        let query = vector_query(Some("rust"), vec![], r#"{"vector": [0.1, 0.2], "k": 20}"#);
+
+        let knn = query_to_knn(&query, 5).unwrap().unwrap();
+        let request = with_knn(search_query_to_opensearch_request(query), knn);
+        let should = &request.query.unwrap()["bool"]["should"];
+        assert_eq!(should[0]["knn"][VECTOR_FIELD]["k"], 20);
+        assert_eq!(should[1]["multi_match"]["query"], "rust");
+    }
+
+    #[test]
+    fn test_invalid_vector_query() {
+        let query = vector_query(None, vec![], r#"{"vector": [], "k": 3}"#);
+        assert!(query_to_knn(&query, 5).is_err());
+
+        let query = vector_query(None, vec![], r#"{"vector": [1.0], "k": 0}"#);
+        assert!(query_to_knn(&query, 5).is_err());
+    }
+
+    #[test]
+    fn test_schema_to_opensearch_settings_with_vectors() {
+        let schema = Schema {
+            fields: vec![],
+            primary_key: None,
+        };
+        let options = VectorOptions {
+            dimension: 384,
+            method: "hnsw".to_string(),
+            space_type: "cosinesimil".to_string(),
+            engine: "lucene".to_string(),
+        };
+
+        let settings = schema_to_opensearch_settings(schema, Some(&options));
+        let properties = settings.mappings.unwrap().properties.unwrap();
+        assert_eq!(properties[VECTOR_FIELD]["type"], "knn_vector");
+        assert_eq!(properties[VECTOR_FIELD]["dimension"], 384);
+        assert_eq!(
+            properties[VECTOR_FIELD]["method"]["space_type"],
+            "cosinesimil"
+        );
+        assert_eq!(settings.settings.unwrap()["knn"], true);
+    }
+
     #[test]
     fn test_create_retry_query() {
         let original_query = SearchQuery {
@@ -498,4 +926,69 @@ mod tests {
         let retry_query = create_retry_query(&original_query, &partial_hits);
         assert_eq!(retry_query.offset, Some(21)); // 20 + 1 hit received
     }
+
+    #[test]
+    fn test_query_security_context() {
+        let query = vector_query(Some("laptop"), vec![], "[1, 2]");
+        assert_eq!(query_security_context(&query), SecurityContext::default());
+
+        let query = vector_query(Some("laptop"), vec![], r#"{"tenant": "analytics", "k": 5}"#);
+        let security = query_security_context(&query);
+        assert_eq!(security.tenant.as_deref(), Some("analytics"));
+        assert_eq!(security.impersonate_as, None);
+
+        let configured = SecurityContext {
+            tenant: Some("global_tenant".to_string()),
+            impersonate_as: Some("reporting".to_string()),
+        };
+        assert_eq!(
+            security.or(configured),
+            SecurityContext {
+                tenant: Some("analytics".to_string()),
+                impersonate_as: Some("reporting".to_string()),
+            }
+        );
+    }
+
+    #[test]
+    fn test_query_sql() {
+        let query = vector_query(
+            None,
+            vec![],
+            r#"{"sql": "SELECT category, count(*) AS n FROM products GROUP BY category"}"#,
+        );
+        assert_eq!(
+            query_sql(&query).unwrap().as_deref(),
+            Some("SELECT category, count(*) AS n FROM products GROUP BY category")
+        );
+
+        let query = vector_query(None, vec![], r#"{"sql": 42}"#);
+        assert!(query_sql(&query).is_err());
+
+        let query = vector_query(None, vec![], r#"{"k": 5}"#);
+        assert_eq!(query_sql(&query).unwrap(), None);
+    }
+
+    #[test]
+    fn test_opensearch_sql_response_to_search_results() {
+        let response: OpenSearchSqlResponse = serde_json::from_value(json!({
+            "schema": [
+                { "name": "category", "type": "keyword" },
+                { "name": "count(*)", "alias": "n", "type": "integer" }
+            ],
+            "datarows": [["books", 12], ["music", 3]],
+            "total": 2,
+            "size": 2,
+            "status": 200
+        }))
+        .unwrap();
+
+        let results = opensearch_sql_response_to_search_results(response);
+        assert_eq!(results.total, Some(2));
+        assert_eq!(results.hits.len(), 2);
+        assert_eq!(results.hits[1].id, "1");
+        let content: Value =
+            serde_json::from_str(results.hits[0].content.as_deref().unwrap()).unwrap();
+        assert_eq!(content, json!({ "category": "books", "n": 12 }));
+    }
 }
@@ -0,0 +1,167 @@
+//! Index templates.
+//!
+//! The component exports the `golem:search-opensearch/index-templates` interface, installing
+//! composable index templates derived from a schema, so that the indices OpenSearch creates
+//! implicitly on the first write of a document come up with the mappings, k-NN and shard settings
+//! of the schema instead of dynamically mapped fields.
+
+use crate::client::OpenSearchIndexTemplate;
+use crate::conversions::{schema_to_opensearch_settings, VectorOptions};
+use crate::OpenSearchComponent;
+use golem_search::error::invalid_query;
+use golem_search::golem::search::types::SearchError;
+use golem_search::LOGGING_STATE;
+use serde_json::{json, Map, Value};
+
+#[allow(dead_code)]
+mod bindings {
+    wit_bindgen::generate!({
+        path: "wit",
+        world: "golem:search-opensearch/index-templates-library@1.0.0",
+        with: {
+            "golem:search/types@1.0.0": golem_search::golem::search::types,
+        },
+        generate_all,
+    });
+}
+
+use bindings::exports::golem::search_opensearch::index_templates::{Guest, IndexTemplate};
+
+struct OpenSearchIndexTemplates;
+
+impl Guest for OpenSearchIndexTemplates {
+    fn put_index_template(template: IndexTemplate) -> Result<(), SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = OpenSearchComponent::create_client()?;
+        let name = template.name.clone();
+        let template =
+            index_template_to_opensearch(template, OpenSearchComponent::vector_options().as_ref())?;
+
+        client.put_index_template(&name, &template)
+    }
+
+    fn delete_index_template(name: String) -> Result<(), SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = OpenSearchComponent::create_client()?;
+
+        client.delete_index_template(&name)
+    }
+}
+
+fn index_template_to_opensearch(
+    template: IndexTemplate,
+    vector_options: Option<&VectorOptions>,
+) -> Result<OpenSearchIndexTemplate, SearchError> {
+    let mut index_settings = schema_to_opensearch_settings(template.schema, vector_options);
+    let settings = index_settings.settings.get_or_insert_with(Map::new);
+
+    if let Some(shards) = template.number_of_shards {
+        settings.insert("number_of_shards".to_string(), json!(shards));
+    }
+    if let Some(replicas) = template.number_of_replicas {
+        settings.insert("number_of_replicas".to_string(), json!(replicas));
+    }
+    if let Some(analyzer) = template.analyzer {
+        settings.insert(
+            "analysis".to_string(),
+            json!({
+                "analyzer": {
+                    "default": {
+                        "type": analyzer
+                    }
+                }
+            }),
+        );
+    }
+
+    if let Some(params) = template.provider_params {
+        let params = serde_json::from_str::<Map<String, Value>>(&params)
+            .map_err(|e| invalid_query(format!("Invalid provider params: {e}")))?;
+        settings.extend(params);
+    }
+
+    Ok(OpenSearchIndexTemplate {
+        index_patterns: template.index_patterns,
+        priority: template.priority,
+        template: index_settings,
+    })
+}
+
+bindings::export!(OpenSearchIndexTemplates with_types_in bindings);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use golem_search::golem::search::types::{FieldType, Schema, SchemaField};
+
+    fn template(analyzer: Option<&str>, provider_params: Option<&str>) -> IndexTemplate {
+        IndexTemplate {
+            name: "logs".to_string(),
+            index_patterns: vec!["logs-*".to_string()],
+            schema: Schema {
+                fields: vec![SchemaField {
+                    name: "message".to_string(),
+                    field_type: FieldType::Text,
+                    required: false,
+                    facet: false,
+                    sort: false,
+                    index: true,
+                }],
+                primary_key: None,
+            },
+            priority: Some(100),
+            number_of_shards: Some(2),
+            number_of_replicas: None,
+            analyzer: analyzer.map(str::to_string),
+            provider_params: provider_params.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn templates_carry_the_schema_mappings_and_index_settings() {
+        let template = index_template_to_opensearch(
+            template(Some("english"), Some(r#"{"refresh_interval": "30s"}"#)),
+            None,
+        )
+        .unwrap();
+        let body = serde_json::to_value(&template).unwrap();
+
+        assert_eq!(body["index_patterns"], json!(["logs-*"]));
+        assert_eq!(body["priority"], 100);
+        assert_eq!(
+            body["template"]["mappings"]["properties"]["message"]["type"],
+            "text"
+        );
+        assert_eq!(body["template"]["settings"]["number_of_shards"], 2);
+        assert_eq!(body["template"]["settings"]["refresh_interval"], "30s");
+        assert_eq!(
+            body["template"]["settings"]["analysis"]["analyzer"]["default"]["type"],
+            "english"
+        );
+    }
+
+    #[test]
+    fn templates_map_the_document_vectors() {
+        let options = VectorOptions {
+            dimension: 384,
+            method: "hnsw".to_string(),
+            space_type: "cosinesimil".to_string(),
+            engine: "lucene".to_string(),
+        };
+        let template = index_template_to_opensearch(template(None, None), Some(&options)).unwrap();
+        let body = serde_json::to_value(&template).unwrap();
+
+        assert_eq!(body["template"]["settings"]["knn"], true);
+        assert_eq!(
+            body["template"]["mappings"]["properties"]["vector"]["type"],
+            "knn_vector"
+        );
+    }
+
+    #[test]
+    fn invalid_provider_params_are_rejected() {
+        assert!(index_template_to_opensearch(template(None, Some("[1, 2]")), None).is_err());
+    }
+}
@@ -1,102 +1,138 @@
-use crate::client::OpenSearchApi;
+use crate::client::{OpenSearchApi, SecurityContext, MAX_RESULT_WINDOW};
 use crate::conversions::{
-    create_retry_query, doc_to_opensearch_document, opensearch_document_to_doc,
-    opensearch_mappings_to_schema, opensearch_response_to_search_results,
-    opensearch_scroll_response_to_search_results, schema_to_opensearch_settings,
-    search_query_to_opensearch_request,
+    build_bulk_delete_operations, build_bulk_operations, create_retry_query,
+    doc_to_opensearch_document, opensearch_document_to_doc, opensearch_mappings_to_schema,
+    opensearch_response_to_search_results, opensearch_sql_response_to_search_results,
+    query_security_context, query_sql, query_to_knn, schema_to_opensearch_settings,
+    search_query_to_opensearch_request, search_query_to_point_in_time_query, with_knn,
+    VectorOptions,
 };
+use crate::sigv4::SigV4Signer;
 use golem_rust::wasm_rpc::Pollable;
-use golem_search::config::with_config_keys;
+use golem_search::bulk::{bulk_failures, bulk_failures_to_search_error};
+use golem_search::config::{
+    get_optional_config, profile_key, search_headers, search_profile, search_timeout,
+    with_profile_config_keys, worker_headers, worker_profile,
+};
 use golem_search::durability::{DurableSearch, ExtendedGuest};
+use golem_search::error::invalid_query;
 use golem_search::golem::search::core::{Guest, GuestSearchStream, SearchStream};
 use golem_search::golem::search::types::{
-    Doc, DocumentId, IndexName, Schema, SearchError, SearchHit, SearchQuery, SearchResults,
+    Doc, DocumentId, IndexName, IndexPage, PageRequest, Schema, SearchError, SearchHit,
+    SearchQuery, SearchResults,
 };
+use golem_search::pagination::{index_page, page_source, PageSource};
 use golem_search::LOGGING_STATE;
 use log::trace;
+use serde_json::Value;
 use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
 
+mod bulk;
 mod client;
 mod conversions;
+mod index_templates;
+mod sigv4;
 
-/// Uses scroll API for streaming large result sets with fallback to pagination
+/// Streams the hits of a query through a point in time, paginated with `search_after`, which
+/// neither holds a scroll context open nor is limited by the index's `max_result_window`. Falls
+/// back to `from`/`size` pagination when the point in time can't be created or searched.
 struct OpenSearchSearchStream {
     client: OpenSearchApi,
     index_name: String,
     query: SearchQuery,
-    scroll_id: RefCell<Option<String>>,
+    pit_id: RefCell<Option<String>>,
+    /// The sort values of the last hit streamed from the point in time
+    search_after: RefCell<Option<Vec<Value>>>,
     finished: Cell<bool>,
     current_offset: Cell<u32>,
-    use_scroll: Cell<bool>,
-    scroll_failed: Cell<bool>,
+    pit_failed: Cell<bool>,
 }
 
 impl OpenSearchSearchStream {
+    const PIT_KEEP_ALIVE: &'static str = "1m";
+
     pub fn new(client: OpenSearchApi, index_name: String, query: SearchQuery) -> Self {
         Self {
             client,
             index_name,
             query: query.clone(),
-            scroll_id: RefCell::new(None),
+            pit_id: RefCell::new(None),
+            search_after: RefCell::new(None),
             finished: Cell::new(false),
             current_offset: Cell::new(query.offset.unwrap_or(0)),
-            use_scroll: Cell::new(true), // Start with scroll, fallback to pagination if needed
-            scroll_failed: Cell::new(false),
+            pit_failed: Cell::new(false),
         }
     }
 
     pub fn subscribe(&self) -> Pollable {
         golem_rust::bindings::wasi::clocks::monotonic_clock::subscribe_duration(0)
     }
+
+    fn delete_point_in_time(&self) {
+        if let Some(pit_id) = self.pit_id.borrow_mut().take() {
+            let _ = self.client.delete_point_in_time(&pit_id);
+        }
+    }
 }
 
 impl OpenSearchSearchStream {
-    fn try_scroll_next(&self) -> Option<Option<Vec<SearchHit>>> {
-        if self.scroll_id.borrow().is_none() {
-            let mut os_query = search_query_to_opensearch_request(self.query.clone());
-            os_query.from = Some(0);
-            os_query.size = Some(self.query.per_page.unwrap_or(100)); // Larger page size for scroll
-
+    /// Returns none when the point in time failed before streaming any hit, so that the stream
+    /// can fall back to pagination
+    fn try_point_in_time_next(&self) -> Option<Option<Vec<SearchHit>>> {
+        if self.pit_id.borrow().is_none() {
             match self
                 .client
-                .search_with_scroll(&self.index_name, &os_query, "1m")
+                .create_point_in_time(&self.index_name, Self::PIT_KEEP_ALIVE)
             {
-                Ok(response) => {
-                    let scroll_id = response.scroll_id.clone();
-                    *self.scroll_id.borrow_mut() = Some(scroll_id);
-
-                    let search_results = opensearch_scroll_response_to_search_results(response);
-
-                    if search_results.hits.is_empty() {
-                        self.finished.set(true);
-                        return Some(Some(vec![]));
-                    }
-
-                    Some(Some(search_results.hits))
-                }
+                Ok(pit_id) => *self.pit_id.borrow_mut() = Some(pit_id),
                 Err(e) => {
-                    trace!("Initial scroll search failed: {e:?}");
-                    None
+                    trace!("Creating point in time failed: {e:?}");
+                    return None;
                 }
             }
-        } else {
-            let scroll_id = self.scroll_id.borrow().clone().unwrap();
+        }
 
-            match self.client.scroll(&scroll_id, "1m") {
-                Ok(response) => {
-                    let search_results = opensearch_scroll_response_to_search_results(response);
+        let pit_id = self.pit_id.borrow().clone().unwrap();
+        let search_after = self.search_after.borrow().clone();
+        let started = search_after.is_some();
+        let os_query = search_query_to_point_in_time_query(
+            self.query.clone(),
+            &pit_id,
+            Self::PIT_KEEP_ALIVE,
+            search_after,
+        );
+
+        match self.client.search_point_in_time(&os_query) {
+            Ok(mut response) => {
+                if let Some(pit_id) = response.pit_id.take() {
+                    *self.pit_id.borrow_mut() = Some(pit_id);
+                }
+                *self.search_after.borrow_mut() =
+                    response.hits.hits.last().and_then(|hit| hit.sort.clone());
 
-                    if search_results.hits.is_empty() {
-                        self.finished.set(true);
-                        return Some(Some(vec![]));
-                    }
+                let search_results = opensearch_response_to_search_results(response);
 
-                    Some(Some(search_results.hits))
-                }
-                Err(e) => {
-                    trace!("Scroll continuation failed: {e:?}");
-                    None
+                let received_count = search_results.hits.len() as u32;
+                self.current_offset
+                    .set(self.current_offset.get() + received_count);
+
+                if search_results.hits.is_empty() || self.search_after.borrow().is_none() {
+                    self.finished.set(true);
+                    self.delete_point_in_time();
                 }
+
+                Some(Some(search_results.hits))
+            }
+            Err(e) if started => {
+                trace!("Point in time continuation failed: {e:?}");
+                self.finished.set(true);
+                self.delete_point_in_time();
+                Some(Some(vec![]))
+            }
+            Err(e) => {
+                trace!("Initial point in time search failed: {e:?}");
+                None
             }
         }
     }
@@ -142,11 +178,11 @@ impl GuestSearchStream for OpenSearchSearchStream {
             return Some(vec![]);
         }
 
-        if self.use_scroll.get() && !self.scroll_failed.get() {
-            self.try_scroll_next().unwrap_or_else(|| {
-                trace!("Scroll failed, falling back to pagination");
-                self.scroll_failed.set(true);
-                self.use_scroll.set(false);
+        if !self.pit_failed.get() {
+            self.try_point_in_time_next().unwrap_or_else(|| {
+                trace!("Point in time search failed, falling back to pagination");
+                self.pit_failed.set(true);
+                self.delete_point_in_time();
                 self.try_pagination_next()
             })
         } else {
@@ -161,14 +197,74 @@ impl GuestSearchStream for OpenSearchSearchStream {
 
 struct OpenSearchComponent;
 
+thread_local! {
+    /// The provider clients by configuration profile, reused across calls instead of being rebuilt
+    /// for each of them
+    static CLIENTS: RefCell<HashMap<Option<String>, OpenSearchApi>> = RefCell::new(HashMap::new());
+}
+
 impl OpenSearchComponent {
     const BASE_URL_ENV_VAR: &'static str = "OPENSEARCH_BASE_URL";
     const USERNAME_ENV_VAR: &'static str = "OPENSEARCH_USERNAME";
     const PASSWORD_ENV_VAR: &'static str = "OPENSEARCH_PASSWORD";
     const API_KEY_ENV_VAR: &'static str = "OPENSEARCH_API_KEY";
+    /// Set to the AWS service, `es` for domains or `aoss` for serverless collections, to sign the
+    /// requests with the `AWS_*` credentials instead of the other credentials
+    const AWS_SIGV4_SERVICE_ENV_VAR: &'static str = "OPENSEARCH_AWS_SIGV4_SERVICE";
+    /// The tenant of the security plugin addressed by default, such as `global_tenant`
+    const TENANT_ENV_VAR: &'static str = "OPENSEARCH_TENANT";
+    /// The user whose roles the requests run with by default, impersonated by the configured one
+    const IMPERSONATE_AS_ENV_VAR: &'static str = "OPENSEARCH_IMPERSONATE_AS";
+    const VECTOR_DIMENSION_ENV_VAR: &'static str = "OPENSEARCH_VECTOR_DIMENSION";
+    const VECTOR_METHOD_ENV_VAR: &'static str = "OPENSEARCH_VECTOR_METHOD";
+    const VECTOR_SPACE_TYPE_ENV_VAR: &'static str = "OPENSEARCH_VECTOR_SPACE_TYPE";
+    const VECTOR_ENGINE_ENV_VAR: &'static str = "OPENSEARCH_VECTOR_ENGINE";
+
+    /// The k-NN mapping of the document vectors, when their dimension is configured. The method
+    /// defaults to HNSW with the cosine similarity of the Lucene engine, which supports filters.
+    fn vector_options() -> Option<VectorOptions> {
+        let profile = worker_profile();
+        let dimension = get_optional_config(profile_key(
+            Self::VECTOR_DIMENSION_ENV_VAR,
+            profile.as_deref(),
+        ))
+        .and_then(|d| d.trim().parse().ok())?;
+        let config = |key: &str, default: &str| {
+            get_optional_config(profile_key(key, profile.as_deref()))
+                .filter(|value| !value.is_empty())
+                .unwrap_or_else(|| default.to_string())
+        };
+
+        Some(VectorOptions {
+            dimension,
+            method: config(Self::VECTOR_METHOD_ENV_VAR, "hnsw"),
+            space_type: config(Self::VECTOR_SPACE_TYPE_ENV_VAR, "cosinesimil"),
+            engine: config(Self::VECTOR_ENGINE_ENV_VAR, "lucene"),
+        })
+    }
 
     fn create_client() -> Result<OpenSearchApi, SearchError> {
-        with_config_keys(&[Self::BASE_URL_ENV_VAR], |keys| {
+        Self::create_profile_client(worker_profile().as_deref())
+            .map(|client| client.with_headers(worker_headers()))
+    }
+
+    /// Returns the client of `profile` cached for this worker, creating it from the configuration
+    /// on first use
+    fn create_profile_client(profile: Option<&str>) -> Result<OpenSearchApi, SearchError> {
+        CLIENTS.with_borrow_mut(|clients| {
+            let profile = profile.map(str::to_string);
+            if let Some(client) = clients.get(&profile) {
+                return Ok(client.clone());
+            }
+
+            let client = Self::new_client(profile.as_deref())?;
+            clients.insert(profile, client.clone());
+            Ok(client)
+        })
+    }
+
+    fn new_client(profile: Option<&str>) -> Result<OpenSearchApi, SearchError> {
+        with_profile_config_keys(profile, &[Self::BASE_URL_ENV_VAR], |keys| {
             if keys.is_empty() {
                 return Err(SearchError::Internal(
                     "Missing OpenSearch base URL".to_string(),
@@ -177,12 +273,28 @@ impl OpenSearchComponent {
 
             let base_url = keys[0].clone();
 
-            let username = std::env::var(Self::USERNAME_ENV_VAR).ok();
-            let password = std::env::var(Self::PASSWORD_ENV_VAR).ok();
-            let api_key = std::env::var(Self::API_KEY_ENV_VAR).ok();
-            {
-                Ok(OpenSearchApi::new(base_url, username, password, api_key))
-            }
+            let username = get_optional_config(profile_key(Self::USERNAME_ENV_VAR, profile));
+            let password = get_optional_config(profile_key(Self::PASSWORD_ENV_VAR, profile));
+            let api_key = get_optional_config(profile_key(Self::API_KEY_ENV_VAR, profile));
+            let sigv4 =
+                match get_optional_config(profile_key(Self::AWS_SIGV4_SERVICE_ENV_VAR, profile))
+                    .filter(|service| !service.is_empty())
+                {
+                    Some(service) => Some(SigV4Signer::load_from_env(service, profile)?),
+                    None => None,
+                };
+
+            let config = |key: &str| {
+                get_optional_config(profile_key(key, profile)).filter(|value| !value.is_empty())
+            };
+            let security = SecurityContext {
+                tenant: config(Self::TENANT_ENV_VAR),
+                impersonate_as: config(Self::IMPERSONATE_AS_ENV_VAR),
+            };
+
+            Ok(OpenSearchApi::new(base_url, username, password, api_key)
+                .with_sigv4(sigv4)
+                .with_security(security))
         })
     }
 }
@@ -195,7 +307,9 @@ impl Guest for OpenSearchComponent {
 
         let client = Self::create_client()?;
 
-        let settings = schema.map(schema_to_opensearch_settings);
+        let vector_options = Self::vector_options();
+        let settings =
+            schema.map(|schema| schema_to_opensearch_settings(schema, vector_options.as_ref()));
         client.create_index(&name, settings)?;
 
         Ok(())
@@ -210,12 +324,12 @@ impl Guest for OpenSearchComponent {
         Ok(())
     }
 
-    fn list_indexes() -> Result<Vec<IndexName>, SearchError> {
+    fn list_indexes(page: PageRequest) -> Result<IndexPage, SearchError> {
         LOGGING_STATE.with_borrow_mut(|state| state.init());
 
         let client = Self::create_client()?;
         let indices = client.list_indices()?;
-        Ok(indices.into_iter().map(|idx| idx.index).collect())
+        index_page(indices.into_iter().map(|idx| idx.index).collect(), &page)
     }
 
     fn upsert(index: IndexName, doc: Doc) -> Result<(), SearchError> {
@@ -244,32 +358,16 @@ impl Guest for OpenSearchComponent {
             return Ok(());
         }
 
-        let mut bulk_operations = Vec::new();
-        for doc in docs {
-            let opensearch_doc =
-                doc_to_opensearch_document(doc).map_err(SearchError::InvalidQuery)?;
-
-            let doc_id = opensearch_doc
-                .get("id")
-                .and_then(|v| v.as_str())
-                .unwrap_or("unknown")
-                .to_string();
-
-            let action = serde_json::json!({
-                "index": {
-                    "_index": index,
-                    "_id": doc_id
-                }
-            });
-            bulk_operations.push(serde_json::to_string(&action).unwrap());
-            bulk_operations.push(serde_json::to_string(&opensearch_doc).unwrap());
-        }
-
-        let bulk_body = bulk_operations.join("\n") + "\n";
+        let operations = docs.len();
+        let bulk_body = build_bulk_operations(&index, docs).map_err(SearchError::InvalidQuery)?;
 
-        let _result = client.bulk_index(&bulk_body)?;
-
-        Ok(())
+        let response = client.bulk_index(&bulk_body)?;
+        let failures = bulk_failures(&response.items);
+        if failures.is_empty() {
+            Ok(())
+        } else {
+            Err(bulk_failures_to_search_error(&failures, operations))
+        }
     }
 
     fn delete(index: IndexName, id: DocumentId) -> Result<(), SearchError> {
@@ -290,21 +388,15 @@ impl Guest for OpenSearchComponent {
             return Ok(());
         }
 
-        let mut bulk_operations = Vec::new();
-        for id in ids {
-            let action = serde_json::json!({
-                "delete": {
-                    "_index": index,
-                    "_id": id
-                }
-            });
-            bulk_operations.push(serde_json::to_string(&action).unwrap());
-        }
+        let bulk_body = build_bulk_delete_operations(&index, &ids);
 
-        let bulk_body = bulk_operations.join("\n") + "\n";
-        client.bulk_index(&bulk_body)?;
-
-        Ok(())
+        let response = client.bulk_index(&bulk_body)?;
+        let failures = bulk_failures(&response.items);
+        if failures.is_empty() {
+            Ok(())
+        } else {
+            Err(bulk_failures_to_search_error(&failures, ids.len()))
+        }
     }
 
     fn get(index: IndexName, id: DocumentId) -> Result<Option<Doc>, SearchError> {
@@ -321,17 +413,53 @@ impl Guest for OpenSearchComponent {
     fn search(index: IndexName, query: SearchQuery) -> Result<SearchResults, SearchError> {
         LOGGING_STATE.with_borrow_mut(|state| state.init());
 
-        let client = Self::create_client()?;
-        let opensearch_request = search_query_to_opensearch_request(query);
-
-        let response = client.search(&index, &opensearch_request)?;
-        Ok(opensearch_response_to_search_results(response))
+        let client = Self::create_profile_client(search_profile(&query).as_deref())?
+            .with_request_timeout(search_timeout(&query))
+            .with_headers(search_headers(&query))
+            .with_security(query_security_context(&query));
+        if let Some(sql) = query_sql(&query)? {
+            return Ok(opensearch_sql_response_to_search_results(client.sql(&sql)?));
+        }
+        let knn_query = query.clone();
+        let mut opensearch_request = search_query_to_opensearch_request(query);
+
+        // OpenSearch returns 10 hits when the query sets no size
+        let per_page = opensearch_request.size.unwrap_or(10);
+        let depth = opensearch_request
+            .from
+            .unwrap_or(0)
+            .saturating_add(per_page);
+        if let Some(knn) = query_to_knn(&knn_query, depth)? {
+            opensearch_request = with_knn(opensearch_request, knn);
+        }
+        let response = match page_source(depth, MAX_RESULT_WINDOW, true)? {
+            PageSource::Search => client.search(&index, &opensearch_request)?,
+            PageSource::Cursor => {
+                client.search_with_scroll_pagination(&index, &opensearch_request)?
+            }
+        };
+        let mut results = opensearch_response_to_search_results(response);
+        results.per_page = Some(per_page);
+        Ok(results)
     }
 
     fn stream_search(index: IndexName, query: SearchQuery) -> Result<SearchStream, SearchError> {
         LOGGING_STATE.with_borrow_mut(|state| state.init());
 
-        let client = Self::create_client()?;
+        let client = Self::create_profile_client(search_profile(&query).as_deref())?
+            .with_request_timeout(search_timeout(&query))
+            .with_headers(search_headers(&query))
+            .with_security(query_security_context(&query));
+        if query_to_knn(&query, 0)?.is_some() {
+            return Err(invalid_query(
+                "Vector queries return the nearest neighbors only and can't be streamed",
+            ));
+        }
+        if query_sql(&query)?.is_some() {
+            return Err(invalid_query(
+                "SQL queries return their whole result set at once and can't be streamed",
+            ));
+        }
         let stream = OpenSearchSearchStream::new(client, index, query);
         Ok(SearchStream::new(stream))
     }
@@ -352,7 +480,7 @@ impl Guest for OpenSearchComponent {
         LOGGING_STATE.with_borrow_mut(|state| state.init());
 
         let client = Self::create_client()?;
-        let settings = schema_to_opensearch_settings(schema);
+        let settings = schema_to_opensearch_settings(schema, Self::vector_options().as_ref());
 
         if let Some(mappings) = settings.mappings {
             client.put_mappings(&index, &mappings)?;
@@ -366,9 +494,12 @@ impl ExtendedGuest for OpenSearchComponent {
     fn unwrapped_stream(index: IndexName, query: SearchQuery) -> Self::SearchStream {
         LOGGING_STATE.with_borrow_mut(|state| state.init());
 
-        let client = Self::create_client().unwrap_or_else(|_| {
-            OpenSearchApi::new("http://localhost:9200".to_string(), None, None, None)
-        });
+        let client = Self::create_profile_client(search_profile(&query).as_deref())
+            .unwrap_or_else(|_| {
+                OpenSearchApi::new("http://localhost:9200".to_string(), None, None, None)
+            })
+            .with_headers(search_headers(&query))
+            .with_security(query_security_context(&query));
 
         OpenSearchSearchStream::new(client, index, query)
     }
@@ -384,10 +515,8 @@ impl ExtendedGuest for OpenSearchComponent {
 
 impl Drop for OpenSearchSearchStream {
     fn drop(&mut self) {
-        // Clear any active scroll when the stream is dropped
-        if let Some(scroll_id) = self.scroll_id.borrow().as_ref() {
-            let _ = self.client.clear_scroll(scroll_id);
-        }
+        // Delete any point in time when the stream is dropped
+        self.delete_point_in_time();
     }
 }
 
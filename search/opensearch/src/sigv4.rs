@@ -0,0 +1,298 @@
+//! AWS Signature Version 4 signing of the requests to Amazon OpenSearch Service domains.
+//!
+//! The credentials are read from the same `AWS_*` configuration keys as the Bedrock component,
+//! or from the keys of the client's profile, such as `AWS_TENANT_A_ACCESS_KEY_ID`, so that the
+//! domains of a profile can live in another account or region. The requests are signed right
+//! before being sent, once their body is known.
+
+use chrono::{DateTime, Utc};
+use golem_search::config::{get_optional_config, profile_key, validate_config_key};
+use golem_search::golem::search::types::SearchError;
+use hmac::{Hmac, Mac};
+use reqwest::header::HeaderValue;
+use reqwest::{Request, Url};
+use sha2::{Digest, Sha256};
+
+const ALGORITHM: &str = "AWS4-HMAC-SHA256";
+
+/// Signs requests with the credentials of an AWS identity for a service of a region
+#[derive(Debug, Clone)]
+pub struct SigV4Signer {
+    access_key_id: String,
+    secret_access_key: String,
+    session_token: Option<String>,
+    region: String,
+    /// `es` for managed domains, `aoss` for serverless collections
+    service: String,
+}
+
+impl SigV4Signer {
+    pub fn new(
+        access_key_id: String,
+        secret_access_key: String,
+        session_token: Option<String>,
+        region: String,
+        service: String,
+    ) -> Self {
+        Self {
+            access_key_id,
+            secret_access_key,
+            session_token,
+            region,
+            service,
+        }
+    }
+
+    /// Reads the credentials and the region of `profile`
+    pub fn load_from_env(service: String, profile: Option<&str>) -> Result<Self, SearchError> {
+        let key = |key: &str| profile_key(key, profile);
+        Ok(Self::new(
+            validate_config_key(key("AWS_ACCESS_KEY_ID"))?,
+            validate_config_key(key("AWS_SECRET_ACCESS_KEY"))?,
+            get_optional_config(key("AWS_SESSION_TOKEN")).filter(|token| !token.is_empty()),
+            validate_config_key(key("AWS_REGION"))?,
+            service,
+        ))
+    }
+
+    /// Adds the date, payload hash, session token and authorization headers of the signature of
+    /// the request at the given time
+    pub fn sign(&self, request: &mut Request, now: DateTime<Utc>) {
+        let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+
+        let payload_hash = hex_sha256(
+            request
+                .body()
+                .and_then(|body| body.as_bytes())
+                .unwrap_or_default(),
+        );
+
+        let url = request.url();
+        let host = match url.port() {
+            Some(port) => format!("{}:{port}", url.host_str().unwrap_or_default()),
+            None => url.host_str().unwrap_or_default().to_string(),
+        };
+
+        let mut headers = vec![
+            ("host", host.clone()),
+            ("x-amz-content-sha256", payload_hash.clone()),
+            ("x-amz-date", amz_date.clone()),
+        ];
+        if let Some(token) = &self.session_token {
+            headers.push(("x-amz-security-token", token.clone()));
+        }
+        let authorization =
+            self.authorization(request.method().as_str(), url, &headers, &payload_hash, now);
+
+        let request_headers = request.headers_mut();
+        for (name, value) in headers
+            .into_iter()
+            .chain([("authorization", authorization)])
+        {
+            if let Ok(value) = HeaderValue::from_str(&value) {
+                request_headers.insert(name, value);
+            }
+        }
+    }
+
+    /// The authorization header of a request whose signed headers are `headers`, sorted by name
+    fn authorization(
+        &self,
+        method: &str,
+        url: &Url,
+        headers: &[(&str, String)],
+        payload_hash: &str,
+        now: DateTime<Utc>,
+    ) -> String {
+        let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+        let date = now.format("%Y%m%d").to_string();
+
+        let canonical_headers = headers
+            .iter()
+            .map(|(name, value)| format!("{name}:{}\n", value.trim()))
+            .collect::<String>();
+        let signed_headers = headers
+            .iter()
+            .map(|(name, _)| *name)
+            .collect::<Vec<_>>()
+            .join(";");
+
+        let canonical_request = format!(
+            "{method}\n{}\n{}\n{canonical_headers}\n{signed_headers}\n{payload_hash}",
+            canonical_uri(url.path()),
+            canonical_query(url),
+        );
+
+        let scope = format!("{date}/{}/{}/aws4_request", self.region, self.service);
+        let string_to_sign = format!(
+            "{ALGORITHM}\n{amz_date}\n{scope}\n{}",
+            hex_sha256(canonical_request.as_bytes())
+        );
+
+        let signing_key = [
+            date.as_str(),
+            self.region.as_str(),
+            self.service.as_str(),
+            "aws4_request",
+        ]
+        .iter()
+        .fold(
+            format!("AWS4{}", self.secret_access_key).into_bytes(),
+            |key, part| hmac_sha256(&key, part.as_bytes()),
+        );
+        let signature = hex(&hmac_sha256(&signing_key, string_to_sign.as_bytes()));
+
+        format!(
+            "{ALGORITHM} Credential={}/{scope}, SignedHeaders={signed_headers}, Signature={signature}",
+            self.access_key_id
+        )
+    }
+}
+
+/// The path of the URL, whose segments are already encoded once, encoded again as AWS expects
+/// for every service but S3
+fn canonical_uri(path: &str) -> String {
+    if path.is_empty() {
+        return "/".to_string();
+    }
+    path.split('/')
+        .map(uri_encode)
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// The query parameters of the URL, encoded and sorted by name then value
+fn canonical_query(url: &Url) -> String {
+    let mut params = url
+        .query_pairs()
+        .map(|(name, value)| (uri_encode(&name), uri_encode(&value)))
+        .collect::<Vec<_>>();
+    params.sort();
+    params
+        .into_iter()
+        .map(|(name, value)| format!("{name}={value}"))
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+/// Percent-encodes every byte but the unreserved characters of RFC 3986
+fn uri_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{byte:02X}"),
+        })
+        .collect()
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+fn hex_sha256(data: &[u8]) -> String {
+    hex(&Sha256::digest(data))
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn uri_encoding_keeps_unreserved_characters_only() {
+        assert_eq!(uri_encode("logs-2024.01_a~b"), "logs-2024.01_a~b");
+        assert_eq!(uri_encode("a b/c"), "a%20b%2Fc");
+        assert_eq!(canonical_uri("/my%20index/_doc/1"), "/my%2520index/_doc/1");
+    }
+
+    /// Credentials of the AWS SigV4 test suite
+    fn test_suite_signer() -> SigV4Signer {
+        SigV4Signer::new(
+            "AKIDEXAMPLE".to_string(),
+            "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY".to_string(),
+            None,
+            "us-east-1".to_string(),
+            "service".to_string(),
+        )
+    }
+
+    fn test_suite_authorization(url: &str) -> String {
+        let now = Utc.with_ymd_and_hms(2015, 8, 30, 12, 36, 0).unwrap();
+        test_suite_signer().authorization(
+            "GET",
+            &url.parse().unwrap(),
+            &[
+                ("host", "example.amazonaws.com".to_string()),
+                ("x-amz-date", "20150830T123600Z".to_string()),
+            ],
+            &hex_sha256(b""),
+            now,
+        )
+    }
+
+    #[test]
+    fn get_vanilla_matches_the_aws_test_suite() {
+        assert_eq!(
+            test_suite_authorization("https://example.amazonaws.com/"),
+            "AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20150830/us-east-1/service/aws4_request, \
+             SignedHeaders=host;x-amz-date, \
+             Signature=5fa00fa31553b73ebf1942676e86291e8372ff2a2260956d9b8aae1d763fbf31"
+        );
+    }
+
+    #[test]
+    fn get_vanilla_query_order_key_case_matches_the_aws_test_suite() {
+        assert_eq!(
+            test_suite_authorization("https://example.amazonaws.com/?Param2=value2&Param1=value1"),
+            "AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20150830/us-east-1/service/aws4_request, \
+             SignedHeaders=host;x-amz-date, \
+             Signature=b97d918cfa904a5beff61c982a1b6f458b799221646efd99d3219ec94cdf2500"
+        );
+    }
+
+    #[test]
+    fn requests_carry_the_signed_headers() {
+        let signer = SigV4Signer {
+            session_token: Some("session".to_string()),
+            ..test_suite_signer()
+        };
+        let url: Url = "https://example.amazonaws.com:9200/products/_search?size=10"
+            .parse()
+            .unwrap();
+        let now = Utc.with_ymd_and_hms(2015, 8, 30, 12, 36, 0).unwrap();
+        let mut request = Request::new(reqwest::Method::GET, url.clone());
+
+        signer.sign(&mut request, now);
+
+        let headers = request.headers();
+        assert_eq!(headers["x-amz-date"], "20150830T123600Z");
+        assert_eq!(headers["x-amz-security-token"], "session");
+        assert_eq!(headers["x-amz-content-sha256"], hex_sha256(b""));
+        assert_eq!(
+            headers["authorization"],
+            signer
+                .authorization(
+                    "GET",
+                    &url,
+                    &[
+                        ("host", "example.amazonaws.com:9200".to_string()),
+                        ("x-amz-content-sha256", hex_sha256(b"")),
+                        ("x-amz-date", "20150830T123600Z".to_string()),
+                        ("x-amz-security-token", "session".to_string()),
+                    ],
+                    &hex_sha256(b""),
+                    now,
+                )
+                .as_str()
+        );
+    }
+}
@@ -0,0 +1,376 @@
+use golem_search::correlation::{self, CORRELATION_ID_HEADER};
+use golem_search::error::{
+    from_reqwest_error, from_send_error, internal_error, search_error_from_failed_response,
+};
+use golem_search::golem::search::types::SearchError;
+use golem_search::logging::{redact_secret, RedactedResponse};
+use golem_search::retry::RetryPolicy;
+use log::trace;
+use reqwest::{Client, Method, RequestBuilder, Response};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map as JsonMap, Value as JsonValue};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::rc::Rc;
+use std::time::Duration;
+
+/// Version of the Pinecone API the requests are written against
+const API_VERSION: &str = "2025-01";
+
+#[derive(Clone)]
+pub struct PineconeApi {
+    client: Client,
+    control_plane_url: String,
+    api_key: String,
+    retry_policy: RetryPolicy,
+    request_timeout: Option<Duration>,
+    headers: Vec<(String, String)>,
+    /// Data plane hosts of the indexes, looked up once per index
+    hosts: Rc<RefCell<HashMap<String, String>>>,
+}
+
+impl Debug for PineconeApi {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PineconeApi")
+            .field("control_plane_url", &self.control_plane_url)
+            .field("api_key", &redact_secret(&self.api_key))
+            .field("retry_policy", &self.retry_policy)
+            .field("request_timeout", &self.request_timeout)
+            .finish()
+    }
+}
+
+pub type Metadata = JsonMap<String, JsonValue>;
+
+#[derive(Debug, Deserialize)]
+pub struct IndexStatus {
+    pub ready: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct IndexModel {
+    pub name: String,
+    #[serde(default)]
+    pub host: String,
+    #[serde(default)]
+    pub status: Option<IndexStatus>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct IndexList {
+    #[serde(default)]
+    pub indexes: Vec<IndexModel>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ServerlessSpec {
+    pub cloud: String,
+    pub region: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct IndexSpec {
+    pub serverless: ServerlessSpec,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CreateIndexRequest {
+    pub name: String,
+    pub dimension: u32,
+    pub metric: String,
+    pub spec: IndexSpec,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Vector {
+    pub id: String,
+    #[serde(default)]
+    pub values: Vec<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<Metadata>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UpsertRequest {
+    pub vectors: Vec<Vector>,
+    pub namespace: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeleteRequest {
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub ids: Vec<String>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub delete_all: bool,
+    pub namespace: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FetchResponse {
+    #[serde(default)]
+    pub vectors: HashMap<String, Vector>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryRequest {
+    pub namespace: String,
+    pub vector: Vec<f32>,
+    pub top_k: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filter: Option<JsonValue>,
+    pub include_metadata: bool,
+    pub include_values: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ScoredVector {
+    pub id: String,
+    #[serde(default)]
+    pub score: Option<f64>,
+    #[serde(default)]
+    pub metadata: Option<Metadata>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct QueryResponse {
+    #[serde(default)]
+    pub matches: Vec<ScoredVector>,
+}
+
+impl PineconeApi {
+    pub fn new(control_plane_url: String, api_key: String) -> Self {
+        let client = Client::builder()
+            .build()
+            .expect("Failed to initialize HTTP client");
+
+        Self {
+            client,
+            control_plane_url: control_plane_url.trim_end_matches('/').to_string(),
+            api_key,
+            retry_policy: RetryPolicy::from_config(),
+            request_timeout: None,
+            headers: Vec::new(),
+            hosts: Rc::new(RefCell::new(HashMap::new())),
+        }
+    }
+
+    /// Applies a timeout to every request sent by this client
+    pub fn with_request_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.request_timeout = timeout;
+        self
+    }
+
+    /// Adds custom headers to every request sent by this client
+    pub fn with_headers(mut self, headers: Vec<(String, String)>) -> Self {
+        self.headers = headers;
+        self
+    }
+
+    fn create_request(&self, method: Method, url: &str) -> RequestBuilder {
+        trace!("[Pinecone] HTTP {method} {url}");
+
+        let mut req = self
+            .client
+            .request(method, url)
+            .header("Api-Key", &self.api_key)
+            .header("X-Pinecone-API-Version", API_VERSION)
+            .header("Content-Type", "application/json")
+            .header(CORRELATION_ID_HEADER, correlation::current());
+
+        if let Some(timeout) = self.request_timeout {
+            req = req.timeout(timeout);
+        }
+
+        for (name, value) in &self.headers {
+            req = req.header(name, value);
+        }
+
+        req
+    }
+
+    fn send(
+        &self,
+        method: Method,
+        url: &str,
+        body: Option<&impl Serialize>,
+        operation: &str,
+    ) -> Result<Response, SearchError> {
+        self.retry_policy
+            .execute(|| {
+                let req = self.create_request(method.clone(), url);
+                match body {
+                    Some(body) => req.json(body).send(),
+                    None => req.send(),
+                }
+            })
+            .map_err(|e| from_send_error(format!("Failed to {operation}"), e))
+    }
+
+    /// The data plane URL of an index
+    fn data_plane_url(&self, index: &str) -> Result<String, SearchError> {
+        if let Some(host) = self.hosts.borrow().get(index) {
+            return Ok(format!("https://{host}"));
+        }
+
+        let host = self.describe_index(index)?.host;
+        self.hosts
+            .borrow_mut()
+            .insert(index.to_string(), host.clone());
+        Ok(format!("https://{host}"))
+    }
+}
+
+fn parse_response<T: DeserializeOwned + Debug>(response: Response) -> Result<T, SearchError> {
+    let status = response.status();
+
+    trace!(
+        "Received response from Pinecone API: {:?}",
+        RedactedResponse(&response)
+    );
+
+    if status.is_success() {
+        let body = response
+            .json::<T>()
+            .map_err(|err| from_reqwest_error("Failed to decode response body", err))?;
+
+        trace!("Received response from Pinecone API: {body:?}");
+
+        Ok(body)
+    } else {
+        let error = search_error_from_failed_response(response);
+
+        trace!("Received {status} response from Pinecone API: {error:?}");
+
+        Err(error)
+    }
+}
+
+/// Checks the status of a response with no body of interest
+fn check_response(response: Response) -> Result<(), SearchError> {
+    if response.status().is_success() {
+        Ok(())
+    } else {
+        Err(search_error_from_failed_response(response))
+    }
+}
+
+impl PineconeApi {
+    pub fn list_indexes(&self) -> Result<IndexList, SearchError> {
+        trace!("Listing indexes");
+
+        let url = format!("{}/indexes", self.control_plane_url);
+        parse_response(self.send(Method::GET, &url, None::<&()>, "list indexes")?)
+    }
+
+    pub fn describe_index(&self, index: &str) -> Result<IndexModel, SearchError> {
+        trace!("Describing index: {index}");
+
+        let url = format!("{}/indexes/{}", self.control_plane_url, index);
+        parse_response(self.send(Method::GET, &url, None::<&()>, "describe index")?)
+    }
+
+    pub fn create_index(&self, request: &CreateIndexRequest) -> Result<IndexModel, SearchError> {
+        trace!("Creating index: {}", request.name);
+
+        let url = format!("{}/indexes", self.control_plane_url);
+        let response = self
+            .create_request(Method::POST, &url)
+            .json(request)
+            .send()
+            .map_err(|e| from_send_error("Failed to create index", e))?;
+
+        parse_response(response)
+    }
+
+    /// Waits until a newly created index is ready to accept vectors
+    pub fn wait_until_ready(&self, index: &str) -> Result<(), SearchError> {
+        const MAX_ATTEMPTS: u32 = 60;
+
+        for attempt in 1..=MAX_ATTEMPTS {
+            let model = self.describe_index(index)?;
+            if model.status.is_some_and(|status| status.ready) {
+                trace!("Index {index} is ready after {attempt} attempts");
+                self.hosts
+                    .borrow_mut()
+                    .insert(index.to_string(), model.host);
+                return Ok(());
+            }
+            std::thread::sleep(Duration::from_secs(1));
+        }
+
+        Err(internal_error(format!(
+            "Index {index} was not ready after {MAX_ATTEMPTS} attempts"
+        )))
+    }
+
+    pub fn delete_index(&self, index: &str) -> Result<(), SearchError> {
+        trace!("Deleting index: {index}");
+
+        let url = format!("{}/indexes/{}", self.control_plane_url, index);
+        check_response(self.send(Method::DELETE, &url, None::<&()>, "delete index")?)?;
+        self.hosts.borrow_mut().remove(index);
+        Ok(())
+    }
+
+    pub fn upsert(&self, index: &str, request: &UpsertRequest) -> Result<(), SearchError> {
+        trace!(
+            "Upserting {} vectors to index: {index}",
+            request.vectors.len()
+        );
+
+        let url = format!("{}/vectors/upsert", self.data_plane_url(index)?);
+        let response = self
+            .create_request(Method::POST, &url)
+            .json(request)
+            .send()
+            .map_err(|e| from_send_error("Failed to upsert vectors", e))?;
+
+        check_response(response)
+    }
+
+    pub fn delete(&self, index: &str, request: &DeleteRequest) -> Result<(), SearchError> {
+        trace!("Deleting vectors from index: {index}");
+
+        let url = format!("{}/vectors/delete", self.data_plane_url(index)?);
+        check_response(self.send(Method::POST, &url, Some(request), "delete vectors")?)
+    }
+
+    pub fn fetch(
+        &self,
+        index: &str,
+        namespace: &str,
+        id: &str,
+    ) -> Result<Option<Vector>, SearchError> {
+        trace!("Fetching vector {id} from index: {index}");
+
+        let url = format!(
+            "{}/vectors/fetch?ids={}&namespace={}",
+            self.data_plane_url(index)?,
+            urlencoding::encode(id),
+            urlencoding::encode(namespace)
+        );
+        let response = self
+            .retry_policy
+            .execute(|| self.create_request(Method::GET, &url).send())
+            .map_err(|e| from_send_error("Failed to fetch vector", e))?;
+
+        let mut response = parse_response::<FetchResponse>(response)?;
+        Ok(response.vectors.remove(id))
+    }
+
+    pub fn query(&self, index: &str, request: &QueryRequest) -> Result<QueryResponse, SearchError> {
+        trace!("Querying index: {index}");
+
+        let url = format!("{}/query", self.data_plane_url(index)?);
+        let response = self
+            .retry_policy
+            .execute(|| self.create_request(Method::POST, &url).json(request).send())
+            .map_err(|e| from_send_error("Failed to search", e))?;
+
+        parse_response(response)
+    }
+}
@@ -0,0 +1,481 @@
+use crate::client::{Metadata, QueryRequest, ScoredVector, Vector};
+use golem_search::config::{get_max_pagination_depth_config, search_filters_match_any};
+use golem_search::error::{invalid_query, unsupported};
+use golem_search::golem::search::types::{Doc, SearchError, SearchHit, SearchQuery};
+use serde_json::{json, Value as JsonValue};
+
+/// Document field holding the vector embedding, it is sent as the vector values and the other
+/// fields as its metadata
+pub const VECTOR_FIELD: &str = "vector";
+
+/// Most matches a Pinecone query can return
+const MAX_TOP_K: u32 = 10000;
+
+const DEFAULT_PER_PAGE: u32 = 20;
+
+/// Splits an index name into the Pinecone index and namespace. `index/namespace` addresses a
+/// namespace of an index, a plain name its default namespace.
+pub fn split_index_name(name: &str) -> (&str, &str) {
+    name.split_once('/').unwrap_or((name, ""))
+}
+
+fn parse_values(value: &JsonValue) -> Option<Vec<f32>> {
+    value
+        .as_array()?
+        .iter()
+        .map(|value| value.as_f64().map(|value| value as f32))
+        .collect()
+}
+
+/// Checks that a metadata value is one of the types Pinecone accepts: strings, numbers, booleans
+/// and lists of strings
+fn is_valid_metadata(value: &JsonValue) -> bool {
+    match value {
+        JsonValue::String(_) | JsonValue::Number(_) | JsonValue::Bool(_) => true,
+        JsonValue::Array(values) => values.iter().all(JsonValue::is_string),
+        JsonValue::Null | JsonValue::Object(_) => false,
+    }
+}
+
+pub fn doc_to_vector(doc: Doc) -> Result<Vector, SearchError> {
+    let mut metadata = match serde_json::from_str::<JsonValue>(&doc.content) {
+        Ok(JsonValue::Object(metadata)) => metadata,
+        Ok(_) => return Err(invalid_query("Document content must be a JSON object")),
+        Err(e) => return Err(invalid_query(format!("Invalid document content: {e}"))),
+    };
+
+    let values = metadata
+        .remove(VECTOR_FIELD)
+        .as_ref()
+        .and_then(parse_values)
+        .ok_or_else(|| {
+            invalid_query(format!(
+                "Document {} needs a `{VECTOR_FIELD}` array of numbers",
+                doc.id
+            ))
+        })?;
+
+    if let Some((field, _)) = metadata.iter().find(|(_, value)| !is_valid_metadata(value)) {
+        return Err(invalid_query(format!(
+            "Field `{field}` of document {} is not a string, number, boolean or list of strings",
+            doc.id
+        )));
+    }
+
+    Ok(Vector {
+        id: doc.id,
+        values,
+        metadata: (!metadata.is_empty()).then_some(metadata),
+    })
+}
+
+pub fn vector_to_doc(vector: Vector) -> Doc {
+    let mut content = vector.metadata.unwrap_or_default();
+    content.insert(VECTOR_FIELD.to_string(), json!(vector.values));
+    Doc {
+        id: vector.id,
+        content: JsonValue::Object(content).to_string(),
+    }
+}
+
+/// Keeps the requested attributes of the metadata of the hits
+fn retrieved_metadata(metadata: Metadata, attributes: &[String]) -> Metadata {
+    if attributes.is_empty() {
+        return metadata;
+    }
+    metadata
+        .into_iter()
+        .filter(|(key, _)| attributes.contains(key))
+        .collect()
+}
+
+pub fn scored_vector_to_hit(vector: ScoredVector, query: &SearchQuery) -> SearchHit {
+    let attributes = query
+        .config
+        .as_ref()
+        .map(|config| config.attributes_to_retrieve.as_slice())
+        .unwrap_or_default();
+    let metadata = vector
+        .metadata
+        .map(|metadata| retrieved_metadata(metadata, attributes))
+        .unwrap_or_default();
+
+    SearchHit {
+        id: vector.id,
+        score: vector.score,
+        content: Some(JsonValue::Object(metadata).to_string()),
+        highlights: None,
+    }
+}
+
+/// The query vector, given as `{"vector": [...]}` in the provider parameters of the query
+fn query_vector(query: &SearchQuery) -> Result<Vec<f32>, SearchError> {
+    let params = query
+        .config
+        .as_ref()
+        .and_then(|config| config.provider_params.as_deref())
+        .ok_or_else(|| {
+            invalid_query("Pinecone queries need a `vector` in the provider parameters")
+        })?;
+    let params = serde_json::from_str::<JsonValue>(params)
+        .map_err(|e| invalid_query(format!("Invalid provider parameters: {e}")))?;
+
+    params
+        .get(VECTOR_FIELD)
+        .and_then(parse_values)
+        .filter(|values| !values.is_empty())
+        .ok_or_else(|| invalid_query("The query `vector` must be a non-empty array of numbers"))
+}
+
+fn parse_value(value: &str) -> JsonValue {
+    let value = value.trim();
+    if let Some(quoted) = value
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+    {
+        return JsonValue::String(quoted.to_string());
+    }
+    match serde_json::from_str::<JsonValue>(value) {
+        Ok(value @ (JsonValue::Number(_) | JsonValue::Bool(_))) => value,
+        _ => JsonValue::String(value.to_string()),
+    }
+}
+
+/// Converts a filter to a Pinecone metadata filter. Filters are either Pinecone filters in JSON,
+/// or `field:value`, `field=value`, `field!=value`, and `field>value` style comparisons with `>`,
+/// `>=`, `<` and `<=`.
+fn filter_to_condition(filter: &str) -> Result<JsonValue, SearchError> {
+    let filter = filter.trim();
+    if filter.starts_with('{') {
+        return serde_json::from_str(filter)
+            .map_err(|e| invalid_query(format!("Invalid Pinecone filter `{filter}`: {e}")));
+    }
+
+    let position = filter
+        .find([':', '=', '!', '<', '>'])
+        .ok_or_else(|| invalid_query(format!("Unsupported filter `{filter}`")))?;
+    let key = filter[..position].trim();
+    let rest = &filter[position..];
+    let (op, value) = [">=", "<=", "!=", ">", "<", "=", ":"]
+        .into_iter()
+        .find_map(|op| Some((op, rest.strip_prefix(op)?)))
+        .ok_or_else(|| invalid_query(format!("Unsupported filter `{filter}`")))?;
+    if key.is_empty() || value.trim().is_empty() {
+        return Err(invalid_query(format!("Unsupported filter `{filter}`")));
+    }
+    let value = parse_value(value);
+
+    let op = match op {
+        ":" | "=" => "$eq",
+        "!=" => "$ne",
+        ">" => "$gt",
+        ">=" => "$gte",
+        "<" => "$lt",
+        _ => "$lte",
+    };
+    if !matches!(op, "$eq" | "$ne") && !value.is_number() {
+        return Err(invalid_query(format!(
+            "Range filter `{filter}` needs a numeric value"
+        )));
+    }
+    Ok(json!({ key: { op: value } }))
+}
+
+/// Builds the Pinecone metadata filter of a query
+pub fn query_to_filter(query: &SearchQuery) -> Result<Option<JsonValue>, SearchError> {
+    let mut conditions = query
+        .filters
+        .iter()
+        .map(|filter| filter_to_condition(filter))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(match conditions.len() {
+        0 => None,
+        1 => conditions.pop(),
+        _ if search_filters_match_any(query) => Some(json!({ "$or": conditions })),
+        _ => Some(json!({ "$and": conditions })),
+    })
+}
+
+/// The offset and page size of a query
+pub fn query_window(query: &SearchQuery) -> Result<(u32, u32), SearchError> {
+    let per_page = query.per_page.unwrap_or(DEFAULT_PER_PAGE);
+    let offset = query.offset.unwrap_or_else(|| {
+        query
+            .page
+            .unwrap_or(1)
+            .saturating_sub(1)
+            .saturating_mul(per_page)
+    });
+    let max_depth = get_max_pagination_depth_config().min(MAX_TOP_K);
+    if offset.saturating_add(per_page) > max_depth {
+        return Err(invalid_query(format!(
+            "Requested hits beyond the maximum pagination depth of {max_depth}"
+        )));
+    }
+    Ok((offset, per_page))
+}
+
+/// Builds the query request of a search. Pinecone only runs nearest neighbour queries, so full
+/// text, sorting, facets and highlighting are not supported. Queries can't skip matches, so the
+/// request includes the `offset` skipped hits, which are dropped from the response.
+pub fn query_to_request(query: &SearchQuery, namespace: &str) -> Result<QueryRequest, SearchError> {
+    if query.q.as_deref().is_some_and(|q| !q.trim().is_empty()) {
+        return Err(unsupported("full-text queries"));
+    }
+    if !query.sort.is_empty() {
+        return Err(unsupported("sorting"));
+    }
+    if !query.facets.is_empty() {
+        return Err(unsupported("facets"));
+    }
+    if query.highlight.is_some() {
+        return Err(unsupported("highlighting"));
+    }
+
+    let (offset, per_page) = query_window(query)?;
+
+    Ok(QueryRequest {
+        namespace: namespace.to_string(),
+        vector: query_vector(query)?,
+        top_k: offset + per_page,
+        filter: query_to_filter(query)?,
+        include_metadata: true,
+        include_values: false,
+    })
+}
+
+pub fn create_retry_query(original_query: &SearchQuery, partial_hits: &[SearchHit]) -> SearchQuery {
+    let mut retry_query = original_query.clone();
+
+    let current_offset = original_query.offset.unwrap_or(0);
+    let per_page = original_query.per_page.unwrap_or(DEFAULT_PER_PAGE);
+    let hits_received = partial_hits.len() as u32;
+
+    retry_query.offset = Some(current_offset + hits_received.min(per_page));
+
+    retry_query
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use golem_search::golem::search::types::{FilterMode, HighlightConfig, SearchConfig};
+
+    fn config(provider_params: Option<&str>, filter_mode: Option<FilterMode>) -> SearchConfig {
+        SearchConfig {
+            timeout_ms: None,
+            boost_fields: vec![],
+            attributes_to_retrieve: vec![],
+            language: None,
+            typo_tolerance: None,
+            exact_match_boost: None,
+            provider_params: provider_params.map(str::to_string),
+            profile: None,
+            filter_mode,
+            post_filters: vec![],
+            rescore: None,
+            fallbacks: vec![],
+        }
+    }
+
+    fn query(filters: &[&str]) -> SearchQuery {
+        SearchQuery {
+            q: None,
+            filters: filters.iter().map(|filter| filter.to_string()).collect(),
+            sort: vec![],
+            facets: vec![],
+            page: None,
+            per_page: None,
+            offset: None,
+            highlight: None,
+            config: Some(config(Some(r#"{"vector": [0.1, 0.2]}"#), None)),
+        }
+    }
+
+    #[test]
+    fn test_split_index_name() {
+        assert_eq!(split_index_name("books"), ("books", ""));
+        assert_eq!(split_index_name("books/fiction"), ("books", "fiction"));
+    }
+
+    #[test]
+    fn test_doc_to_vector_and_back() {
+        let doc = Doc {
+            id: "doc1".to_string(),
+            content: r#"{"title":"Dune","vector":[0.5,1.0]}"#.to_string(),
+        };
+        let vector = doc_to_vector(doc.clone()).unwrap();
+        assert_eq!(vector.values, vec![0.5, 1.0]);
+        assert_eq!(
+            vector.metadata.as_ref().unwrap().get("title").unwrap(),
+            "Dune"
+        );
+        assert!(!vector.metadata.as_ref().unwrap().contains_key(VECTOR_FIELD));
+
+        let doc_back = vector_to_doc(vector);
+        assert_eq!(
+            serde_json::from_str::<JsonValue>(&doc_back.content).unwrap(),
+            serde_json::from_str::<JsonValue>(&doc.content).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_doc_to_vector_rejects_invalid_content() {
+        for content in [
+            "[1, 2]",
+            r#"{"title": "Dune"}"#,
+            r#"{"vector": ["a"]}"#,
+            r#"{"vector": [1.0], "author": {"name": "Herbert"}}"#,
+            r#"{"vector": [1.0], "years": [1965, 1966]}"#,
+        ] {
+            let doc = Doc {
+                id: "doc1".to_string(),
+                content: content.to_string(),
+            };
+            assert!(
+                matches!(doc_to_vector(doc), Err(SearchError::InvalidQuery(_))),
+                "{content}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_query_to_filter() {
+        assert_eq!(query_to_filter(&query(&[])).unwrap(), None);
+        assert_eq!(
+            query_to_filter(&query(&["genre:fiction"]))
+                .unwrap()
+                .unwrap(),
+            json!({ "genre": { "$eq": "fiction" } })
+        );
+        assert_eq!(
+            query_to_filter(&query(&["year>=1960", "genre!=\"1984\""]))
+                .unwrap()
+                .unwrap(),
+            json!({ "$and": [
+                { "year": { "$gte": 1960 } },
+                { "genre": { "$ne": "1984" } },
+            ] })
+        );
+        assert_eq!(
+            query_to_filter(&query(&[r#"{"genre": {"$in": ["a", "b"]}}"#]))
+                .unwrap()
+                .unwrap(),
+            json!({ "genre": { "$in": ["a", "b"] } })
+        );
+        assert!(matches!(
+            query_to_filter(&query(&["year>recent"])),
+            Err(SearchError::InvalidQuery(_))
+        ));
+    }
+
+    #[test]
+    fn test_query_to_filter_matching_any() {
+        let mut query = query(&["genre:fiction", "genre:romance"]);
+        query.config = Some(config(None, Some(FilterMode::Any)));
+
+        assert_eq!(
+            query_to_filter(&query).unwrap().unwrap(),
+            json!({ "$or": [
+                { "genre": { "$eq": "fiction" } },
+                { "genre": { "$eq": "romance" } },
+            ] })
+        );
+    }
+
+    #[test]
+    fn test_query_to_request() {
+        let mut query = query(&["genre:fiction"]);
+        query.page = Some(3);
+        query.per_page = Some(10);
+
+        let request = query_to_request(&query, "fiction").unwrap();
+        assert_eq!(
+            request,
+            QueryRequest {
+                namespace: "fiction".to_string(),
+                vector: vec![0.1, 0.2],
+                top_k: 30,
+                filter: Some(json!({ "genre": { "$eq": "fiction" } })),
+                include_metadata: true,
+                include_values: false,
+            }
+        );
+    }
+
+    #[test]
+    fn test_query_to_request_needs_a_vector() {
+        let mut query = query(&[]);
+        query.config = None;
+        assert!(matches!(
+            query_to_request(&query, ""),
+            Err(SearchError::InvalidQuery(_))
+        ));
+    }
+
+    #[test]
+    fn test_query_to_request_rejects_unsupported_features() {
+        let mut text = query(&[]);
+        text.q = Some("dune".to_string());
+        let mut sorted = query(&[]);
+        sorted.sort = vec!["year".to_string()];
+        let mut faceted = query(&[]);
+        faceted.facets = vec!["genre".to_string()];
+        let mut highlighted = query(&[]);
+        highlighted.highlight = Some(HighlightConfig {
+            fields: vec![],
+            pre_tag: None,
+            post_tag: None,
+            max_length: None,
+        });
+
+        for query in [text, sorted, faceted, highlighted] {
+            assert_eq!(query_to_request(&query, ""), Err(SearchError::Unsupported));
+        }
+    }
+
+    #[test]
+    fn test_query_window_is_capped_by_top_k() {
+        let mut query = query(&[]);
+        query.offset = Some(MAX_TOP_K);
+        assert!(matches!(
+            query_window(&query),
+            Err(SearchError::InvalidQuery(_))
+        ));
+    }
+
+    #[test]
+    fn test_hit_keeps_retrieved_attributes() {
+        let mut query = query(&[]);
+        query.config.as_mut().unwrap().attributes_to_retrieve = vec!["title".to_string()];
+        let vector = ScoredVector {
+            id: "doc1".to_string(),
+            score: Some(0.9),
+            metadata: json!({ "title": "Dune", "year": 1965 })
+                .as_object()
+                .cloned(),
+        };
+
+        let hit = scored_vector_to_hit(vector, &query);
+        assert_eq!(hit.score, Some(0.9));
+        assert_eq!(hit.content.as_deref(), Some(r#"{"title":"Dune"}"#));
+    }
+
+    #[test]
+    fn test_create_retry_query() {
+        let mut original = query(&[]);
+        original.offset = Some(10);
+        original.per_page = Some(5);
+        let hits = vec![scored_vector_to_hit(
+            ScoredVector {
+                id: "doc1".to_string(),
+                score: None,
+                metadata: None,
+            },
+            &original,
+        )];
+
+        assert_eq!(create_retry_query(&original, &hits).offset, Some(11));
+    }
+}
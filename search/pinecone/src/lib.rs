@@ -0,0 +1,373 @@
+use crate::client::{
+    CreateIndexRequest, DeleteRequest, IndexSpec, PineconeApi, ServerlessSpec, UpsertRequest,
+};
+use crate::conversions::{
+    create_retry_query, doc_to_vector, query_to_request, query_window, scored_vector_to_hit,
+    split_index_name, vector_to_doc,
+};
+use golem_rust::wasm_rpc::Pollable;
+use golem_search::config::{
+    get_config_with_default, get_optional_config, profile_key, search_headers, search_profile,
+    search_timeout, with_profile_config_keys, worker_headers, worker_profile,
+};
+use golem_search::durability::{DurableSearch, ExtendedGuest};
+use golem_search::error::{invalid_query, unsupported};
+use golem_search::golem::search::core::{Guest, GuestSearchStream, SearchStream};
+use golem_search::golem::search::types::{
+    Doc, DocumentId, IndexName, IndexPage, PageRequest, Schema, SearchError, SearchHit,
+    SearchQuery, SearchResults,
+};
+use golem_search::pagination::index_page;
+use golem_search::LOGGING_STATE;
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+
+mod client;
+mod conversions;
+
+/// Search stream over the pages of a query, as Pinecone has no native streaming
+struct PineconeSearchStream {
+    client: PineconeApi,
+    index_name: String,
+    query: SearchQuery,
+    offset: Cell<u32>,
+    finished: Cell<bool>,
+}
+
+impl PineconeSearchStream {
+    pub fn new(client: PineconeApi, index_name: String, query: SearchQuery) -> Self {
+        let offset = query_window(&query).map(|(offset, _)| offset).unwrap_or(0);
+        Self {
+            client,
+            index_name,
+            query,
+            offset: Cell::new(offset),
+            finished: Cell::new(false),
+        }
+    }
+
+    pub fn subscribe(&self) -> Pollable {
+        golem_rust::bindings::wasi::clocks::monotonic_clock::subscribe_duration(0)
+    }
+}
+
+impl GuestSearchStream for PineconeSearchStream {
+    fn get_next(&self) -> Option<Vec<SearchHit>> {
+        if self.finished.get() {
+            return Some(vec![]);
+        }
+
+        let mut query = self.query.clone();
+        query.page = None;
+        query.offset = Some(self.offset.get());
+        let per_page = query_window(&query)
+            .map(|(_, per_page)| per_page)
+            .unwrap_or(0);
+
+        match PineconeComponent::search_page(&self.client, &self.index_name, &query) {
+            Ok(results) => {
+                let received = results.hits.len() as u32;
+                if received < per_page || received == 0 {
+                    self.finished.set(true);
+                }
+                self.offset.set(self.offset.get() + received);
+                Some(results.hits)
+            }
+            Err(_) => {
+                self.finished.set(true);
+                Some(vec![])
+            }
+        }
+    }
+
+    fn blocking_get_next(&self) -> Vec<SearchHit> {
+        self.get_next().unwrap_or_default()
+    }
+}
+
+struct PineconeComponent;
+
+thread_local! {
+    /// The provider clients by configuration profile, reused across calls instead of being rebuilt
+    /// for each of them
+    static CLIENTS: RefCell<HashMap<Option<String>, PineconeApi>> = RefCell::new(HashMap::new());
+}
+
+impl PineconeComponent {
+    const API_KEY_ENV_VAR: &'static str = "PINECONE_API_KEY";
+    const CONTROL_PLANE_URL_ENV_VAR: &'static str = "PINECONE_CONTROL_PLANE_URL";
+    const DIMENSION_ENV_VAR: &'static str = "PINECONE_DIMENSION";
+    const METRIC_ENV_VAR: &'static str = "PINECONE_METRIC";
+    const CLOUD_ENV_VAR: &'static str = "PINECONE_CLOUD";
+    const REGION_ENV_VAR: &'static str = "PINECONE_REGION";
+
+    const DEFAULT_CONTROL_PLANE_URL: &'static str = "https://api.pinecone.io";
+
+    fn create_client() -> Result<PineconeApi, SearchError> {
+        Self::create_profile_client(worker_profile().as_deref())
+            .map(|client| client.with_headers(worker_headers()))
+    }
+
+    /// Returns the client of `profile` cached for this worker, creating it from the configuration
+    /// on first use
+    fn create_profile_client(profile: Option<&str>) -> Result<PineconeApi, SearchError> {
+        CLIENTS.with_borrow_mut(|clients| {
+            let profile = profile.map(str::to_string);
+            if let Some(client) = clients.get(&profile) {
+                return Ok(client.clone());
+            }
+
+            let client = Self::new_client(profile.as_deref())?;
+            clients.insert(profile, client.clone());
+            Ok(client)
+        })
+    }
+
+    fn new_client(profile: Option<&str>) -> Result<PineconeApi, SearchError> {
+        with_profile_config_keys(profile, &[Self::API_KEY_ENV_VAR], |keys| {
+            if keys.is_empty() {
+                return Err(SearchError::Internal(
+                    "Missing Pinecone API key".to_string(),
+                ));
+            }
+
+            let api_key = keys[0].clone();
+
+            let control_plane_url =
+                get_optional_config(profile_key(Self::CONTROL_PLANE_URL_ENV_VAR, profile))
+                    .unwrap_or_else(|| Self::DEFAULT_CONTROL_PLANE_URL.to_string());
+
+            Ok(PineconeApi::new(control_plane_url, api_key))
+        })
+    }
+
+    /// The serverless index created for a new index name, configured by the `PINECONE_DIMENSION`,
+    /// `PINECONE_METRIC`, `PINECONE_CLOUD` and `PINECONE_REGION` keys
+    fn create_index_request(name: &str) -> Result<CreateIndexRequest, SearchError> {
+        let profile = worker_profile();
+        let key = |key: &str| profile_key(key, profile.as_deref());
+        let dimension = get_optional_config(key(Self::DIMENSION_ENV_VAR))
+            .ok_or_else(|| {
+                invalid_query(format!(
+                    "Creating a Pinecone index needs the {} configuration key",
+                    Self::DIMENSION_ENV_VAR
+                ))
+            })?
+            .parse::<u32>()
+            .map_err(|e| invalid_query(format!("Invalid {}: {e}", Self::DIMENSION_ENV_VAR)))?;
+
+        Ok(CreateIndexRequest {
+            name: name.to_string(),
+            dimension,
+            metric: get_config_with_default(key(Self::METRIC_ENV_VAR), "cosine"),
+            spec: IndexSpec {
+                serverless: ServerlessSpec {
+                    cloud: get_config_with_default(key(Self::CLOUD_ENV_VAR), "aws"),
+                    region: get_config_with_default(key(Self::REGION_ENV_VAR), "us-east-1"),
+                },
+            },
+        })
+    }
+
+    /// Runs a query as a nearest neighbour query of the namespace. Pinecone doesn't report the
+    /// number of matches, so the results have no total.
+    fn search_page(
+        client: &PineconeApi,
+        index: &str,
+        query: &SearchQuery,
+    ) -> Result<SearchResults, SearchError> {
+        let (index, namespace) = split_index_name(index);
+        let (offset, per_page) = query_window(query)?;
+
+        let request = query_to_request(query, namespace)?;
+        let response = client.query(index, &request)?;
+        let hits = response
+            .matches
+            .into_iter()
+            .skip(offset as usize)
+            .map(|vector| scored_vector_to_hit(vector, query))
+            .collect();
+
+        Ok(SearchResults {
+            total: None,
+            page: Some(offset / per_page.max(1) + 1),
+            per_page: Some(per_page),
+            hits,
+            facets: None,
+            took_ms: None,
+            relaxation: None,
+            navigation: None,
+        })
+    }
+}
+
+impl Guest for PineconeComponent {
+    type SearchStream = PineconeSearchStream;
+
+    fn create_index(name: IndexName, schema: Option<Schema>) -> Result<(), SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        if schema.is_some() {
+            return Err(unsupported("index schemas"));
+        }
+
+        let (index, namespace) = split_index_name(&name);
+        if !namespace.is_empty() {
+            // Namespaces are created by their first upsert
+            return Ok(());
+        }
+
+        let client = Self::create_client()?;
+        client.create_index(&Self::create_index_request(index)?)?;
+        client.wait_until_ready(index)
+    }
+
+    fn delete_index(name: IndexName) -> Result<(), SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = Self::create_client()?;
+        let (index, namespace) = split_index_name(&name);
+        if namespace.is_empty() {
+            client.delete_index(index)
+        } else {
+            client.delete(
+                index,
+                &DeleteRequest {
+                    ids: vec![],
+                    delete_all: true,
+                    namespace: namespace.to_string(),
+                },
+            )
+        }
+    }
+
+    fn list_indexes(page: PageRequest) -> Result<IndexPage, SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = Self::create_client()?;
+
+        let response = client.list_indexes()?;
+        index_page(
+            response
+                .indexes
+                .into_iter()
+                .map(|index| index.name)
+                .collect(),
+            &page,
+        )
+    }
+
+    fn upsert(index: IndexName, doc: Doc) -> Result<(), SearchError> {
+        Self::upsert_many(index, vec![doc])
+    }
+
+    fn upsert_many(index: IndexName, docs: Vec<Doc>) -> Result<(), SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = Self::create_client()?;
+        let (index, namespace) = split_index_name(&index);
+        let vectors = docs
+            .into_iter()
+            .map(doc_to_vector)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        client.upsert(
+            index,
+            &UpsertRequest {
+                vectors,
+                namespace: namespace.to_string(),
+            },
+        )
+    }
+
+    fn delete(index: IndexName, id: DocumentId) -> Result<(), SearchError> {
+        Self::delete_many(index, vec![id])
+    }
+
+    fn delete_many(index: IndexName, ids: Vec<DocumentId>) -> Result<(), SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        if ids.is_empty() {
+            return Ok(());
+        }
+
+        let client = Self::create_client()?;
+        let (index, namespace) = split_index_name(&index);
+
+        client.delete(
+            index,
+            &DeleteRequest {
+                ids,
+                delete_all: false,
+                namespace: namespace.to_string(),
+            },
+        )
+    }
+
+    fn get(index: IndexName, id: DocumentId) -> Result<Option<Doc>, SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = Self::create_client()?;
+        let (index, namespace) = split_index_name(&index);
+
+        Ok(client.fetch(index, namespace, &id)?.map(vector_to_doc))
+    }
+
+    fn search(index: IndexName, query: SearchQuery) -> Result<SearchResults, SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = Self::create_profile_client(search_profile(&query).as_deref())?
+            .with_request_timeout(search_timeout(&query))
+            .with_headers(search_headers(&query));
+
+        Self::search_page(&client, &index, &query)
+    }
+
+    fn stream_search(index: IndexName, query: SearchQuery) -> Result<SearchStream, SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = Self::create_profile_client(search_profile(&query).as_deref())?
+            .with_request_timeout(search_timeout(&query))
+            .with_headers(search_headers(&query));
+        let stream = PineconeSearchStream::new(client, index, query);
+        Ok(SearchStream::new(stream))
+    }
+
+    fn get_schema(_index: IndexName) -> Result<Schema, SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        Err(unsupported("index schemas"))
+    }
+
+    fn update_schema(_index: IndexName, _schema: Schema) -> Result<(), SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        Err(unsupported("index schemas"))
+    }
+}
+
+impl ExtendedGuest for PineconeComponent {
+    fn unwrapped_stream(index: IndexName, query: SearchQuery) -> Self::SearchStream {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = Self::create_profile_client(search_profile(&query).as_deref())
+            .unwrap_or_else(|_| {
+                PineconeApi::new(Self::DEFAULT_CONTROL_PLANE_URL.to_string(), "".to_string())
+            })
+            .with_headers(search_headers(&query));
+
+        PineconeSearchStream::new(client, index, query)
+    }
+
+    fn retry_query(original_query: &SearchQuery, partial_hits: &[SearchHit]) -> SearchQuery {
+        create_retry_query(original_query, partial_hits)
+    }
+
+    fn subscribe(stream: &Self::SearchStream) -> Pollable {
+        stream.subscribe()
+    }
+}
+
+type DurablePineconeComponent = DurableSearch<PineconeComponent>;
+
+golem_search::export_search!(DurablePineconeComponent with_types_in golem_search);
@@ -0,0 +1,65 @@
+// Generated by `wit-bindgen` 0.41.0. DO NOT EDIT!
+// Options used:
+//   * runtime_path: "wit_bindgen_rt"
+//   * with "golem:search/core@1.0.0" = "golem_search::golem::search::core"
+//   * with "golem:search/types@1.0.0" = "golem_search::golem::search::types"
+//   * generate_unused_types
+use golem_search::golem::search::types as __with_name0;
+use golem_search::golem::search::core as __with_name1;
+#[cfg(target_arch = "wasm32")]
+#[unsafe(
+    link_section = "component-type:wit-bindgen:0.41.0:golem:search-postgres@1.0.0:search-library:encoded world"
+)]
+#[doc(hidden)]
+#[allow(clippy::octal_escapes)]
+pub static __WIT_BINDGEN_COMPONENT_TYPE: [u8; 1882] = *b"\
+\0asm\x0d\0\x01\0\0\x19\x16wit-component-encoding\x04\0\x07\xd5\x0d\x01A\x02\
+\x01A\x0c\x01B'\x01q\x06\x0findex-not-found\0\0\x0dinvalid-query\x01s\0\x0bunsu\
+pported\0\0\x08internal\x01s\0\x07timeout\0\0\x0crate-limited\0\0\x04\0\x0csear\
+ch-error\x03\0\0\x01s\x04\0\x0aindex-name\x03\0\x02\x01s\x04\0\x0bdocument-id\
+\x03\0\x04\x01s\x04\0\x04json\x03\0\x06\x01r\x02\x02id\x05\x07content\x07\x04\0\
+\x03doc\x03\0\x08\x01ps\x01ks\x01ky\x01r\x04\x06fields\x0a\x07pre-tag\x0b\x08po\
+st-tag\x0b\x0amax-length\x0c\x04\0\x10highlight-config\x03\0\x0d\x01o\x02sv\x01\
+p\x0f\x01k\x7f\x01kv\x01k\x07\x01r\x07\x0atimeout-ms\x0c\x0cboost-fields\x10\
+\x16attributes-to-retrieve\x0a\x08language\x0b\x0etypo-tolerance\x11\x11exact-m\
+atch-boost\x12\x0fprovider-params\x13\x04\0\x0dsearch-config\x03\0\x14\x01k\x0e\
+\x01k\x15\x01r\x09\x01q\x0b\x07filters\x0a\x04sort\x0a\x06facets\x0a\x04page\
+\x0c\x08per-page\x0c\x06offset\x0c\x09highlight\x16\x06config\x17\x04\0\x0csear\
+ch-query\x03\0\x18\x01ku\x01r\x04\x02id\x05\x05score\x1a\x07content\x13\x0ahigh\
+lights\x13\x04\0\x0asearch-hit\x03\0\x1b\x01p\x1c\x01r\x06\x05total\x0c\x04page\
+\x0c\x08per-page\x0c\x04hits\x1d\x06facets\x13\x07took-ms\x0c\x04\0\x0esearch-r\
+esults\x03\0\x1e\x01m\x07\x04text\x07keyword\x07integer\x05float\x07boolean\x04\
+date\x09geo-point\x04\0\x0afield-type\x03\0 \x01r\x06\x04names\x0afield-type!\
+\x08required\x7f\x05facet\x7f\x04sort\x7f\x05index\x7f\x04\0\x0cschema-field\
+\x03\0\"\x01p#\x01r\x02\x06fields$\x0bprimary-key\x0b\x04\0\x06schema\x03\0%\
+\x04\0\x18golem:search/types@1.0.0\x05\0\x02\x03\0\0\x0aindex-name\x02\x03\0\0\
+\x0bdocument-id\x02\x03\0\0\x03doc\x02\x03\0\0\x0csearch-query\x02\x03\0\0\x0es\
+earch-results\x02\x03\0\0\x0asearch-hit\x02\x03\0\0\x06schema\x02\x03\0\0\x0cse\
+arch-error\x01B<\x02\x03\x02\x01\x01\x04\0\x0aindex-name\x03\0\0\x02\x03\x02\
+\x01\x02\x04\0\x0bdocument-id\x03\0\x02\x02\x03\x02\x01\x03\x04\0\x03doc\x03\0\
+\x04\x02\x03\x02\x01\x04\x04\0\x0csearch-query\x03\0\x06\x02\x03\x02\x01\x05\
+\x04\0\x0esearch-results\x03\0\x08\x02\x03\x02\x01\x06\x04\0\x0asearch-hit\x03\
+\0\x0a\x02\x03\x02\x01\x07\x04\0\x06schema\x03\0\x0c\x02\x03\x02\x01\x08\x04\0\
+\x0csearch-error\x03\0\x0e\x04\0\x0dsearch-stream\x03\x01\x01h\x10\x01p\x0b\x01\
+k\x12\x01@\x01\x04self\x11\0\x13\x04\0\x1e[method]search-stream.get-next\x01\
+\x14\x01@\x01\x04self\x11\0\x12\x04\0'[method]search-stream.blocking-get-next\
+\x01\x15\x01k\x0d\x01j\0\x01\x0f\x01@\x02\x04name\x01\x06schema\x16\0\x17\x04\0\
+\x0ccreate-index\x01\x18\x01@\x01\x04name\x01\0\x17\x04\0\x0cdelete-index\x01\
+\x19\x01p\x01\x01j\x01\x1a\x01\x0f\x01@\0\0\x1b\x04\0\x0clist-indexes\x01\x1c\
+\x01@\x02\x05index\x01\x03doc\x05\0\x17\x04\0\x06upsert\x01\x1d\x01p\x05\x01@\
+\x02\x05index\x01\x04docs\x1e\0\x17\x04\0\x0bupsert-many\x01\x1f\x01@\x02\x05in\
+dex\x01\x02id\x03\0\x17\x04\0\x06delete\x01 \x01p\x03\x01@\x02\x05index\x01\x03\
+ids!\0\x17\x04\0\x0bdelete-many\x01\"\x01k\x05\x01j\x01#\x01\x0f\x01@\x02\x05in\
+dex\x01\x02id\x03\0$\x04\0\x03get\x01%\x01j\x01\x09\x01\x0f\x01@\x02\x05index\
+\x01\x05query\x07\0&\x04\0\x06search\x01'\x01i\x10\x01j\x01(\x01\x0f\x01@\x02\
+\x05index\x01\x05query\x07\0)\x04\0\x0dstream-search\x01*\x01j\x01\x0d\x01\x0f\
+\x01@\x01\x05index\x01\0+\x04\0\x0aget-schema\x01,\x01@\x02\x05index\x01\x06sch\
+ema\x0d\0\x17\x04\0\x0dupdate-schema\x01-\x04\0\x17golem:search/core@1.0.0\x05\
+\x09\x04\0\x2agolem:search-postgres/search-library@1.0.0\x04\0\x0b\x14\x01\0\x0esear\
+ch-library\x03\0\0\0G\x09producers\x01\x0cprocessed-by\x02\x0dwit-component\x07\
+0.227.1\x10wit-bindgen-rust\x060.41.0";
+#[inline(never)]
+#[doc(hidden)]
+pub fn __link_custom_section_describing_imports() {
+    wit_bindgen_rt::maybe_link_cabi_realloc();
+}
@@ -0,0 +1,224 @@
+use golem_search::correlation::{self, CORRELATION_ID_HEADER};
+use golem_search::error::{from_reqwest_error, from_send_error, search_error_from_failed_response};
+use golem_search::golem::search::types::SearchError;
+use golem_search::logging::{redact_secret, RedactedResponse};
+use golem_search::retry::RetryPolicy;
+use log::trace;
+use reqwest::{Client, Method, RequestBuilder, Response};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map as JsonMap, Value as JsonValue};
+use std::fmt::Debug;
+use std::time::Duration;
+
+/// The Postgres client, running SQL statements through the SQL over HTTP endpoint of a Neon
+/// compatible gateway, which connects to the database of the connection string given with each
+/// request
+#[derive(Clone)]
+pub struct PostgresApi {
+    client: Client,
+    sql_url: String,
+    connection_string: String,
+    retry_policy: RetryPolicy,
+    request_timeout: Option<Duration>,
+    headers: Vec<(String, String)>,
+}
+
+impl Debug for PostgresApi {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PostgresApi")
+            .field("sql_url", &self.sql_url)
+            .field("connection_string", &redact_secret(&self.connection_string))
+            .field("retry_policy", &self.retry_policy)
+            .field("request_timeout", &self.request_timeout)
+            .finish()
+    }
+}
+
+/// An SQL statement and the values of its `$1`, `$2`, ... parameters, sent as text and typed
+/// by the casts of the statement
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Statement {
+    pub query: String,
+    pub params: Vec<JsonValue>,
+}
+
+impl Statement {
+    pub fn new(query: impl Into<String>) -> Self {
+        Self {
+            query: query.into(),
+            params: Vec::new(),
+        }
+    }
+}
+
+pub type Row = JsonMap<String, JsonValue>;
+
+/// The rows returned by a statement, keyed by column name
+#[derive(Debug, Default, Deserialize)]
+pub struct QueryResult {
+    #[serde(default)]
+    pub rows: Vec<Row>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BatchResult {
+    #[serde(default)]
+    results: Vec<QueryResult>,
+}
+
+#[derive(Serialize)]
+struct BatchRequest<'a> {
+    queries: &'a [Statement],
+}
+
+impl PostgresApi {
+    pub fn new(url: String, connection_string: String) -> Self {
+        let client = Client::builder()
+            .build()
+            .expect("Failed to initialize HTTP client");
+
+        Self {
+            client,
+            sql_url: format!("{}/sql", url.trim_end_matches('/')),
+            connection_string,
+            retry_policy: RetryPolicy::from_config(),
+            request_timeout: None,
+            headers: Vec::new(),
+        }
+    }
+
+    /// Applies a timeout to every request sent by this client
+    pub fn with_request_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.request_timeout = timeout;
+        self
+    }
+
+    /// Adds custom headers to every request sent by this client
+    pub fn with_headers(mut self, headers: Vec<(String, String)>) -> Self {
+        self.headers = headers;
+        self
+    }
+
+    fn create_request(&self, method: Method, url: &str) -> RequestBuilder {
+        trace!("[Postgres] HTTP {method} {url}");
+
+        let mut req = self
+            .client
+            .request(method, url)
+            .header("Content-Type", "application/json")
+            .header("Accept", "application/json")
+            .header(CORRELATION_ID_HEADER, correlation::current())
+            .header("Neon-Connection-String", &self.connection_string)
+            .header("Neon-Array-Mode", "false");
+
+        if let Some(timeout) = self.request_timeout {
+            req = req.timeout(timeout);
+        }
+
+        for (name, value) in &self.headers {
+            req = req.header(name, value);
+        }
+
+        req
+    }
+
+    fn send(&self, body: &impl Serialize) -> Result<Response, reqwest::Error> {
+        self.retry_policy.execute(|| {
+            self.create_request(Method::POST, &self.sql_url)
+                .json(body)
+                .send()
+        })
+    }
+}
+
+fn parse_response<T: DeserializeOwned + Debug>(response: Response) -> Result<T, SearchError> {
+    let status = response.status();
+
+    trace!(
+        "Received response from Postgres API: {:?}",
+        RedactedResponse(&response)
+    );
+
+    if status.is_success() {
+        let body = response
+            .json::<T>()
+            .map_err(|err| from_reqwest_error("Failed to decode response body", err))?;
+
+        trace!("Received response from Postgres API: {body:?}");
+
+        Ok(body)
+    } else {
+        let error = sql_error(response);
+
+        trace!("Received {status} response from Postgres API: {error:?}");
+
+        Err(error)
+    }
+}
+
+/// The error of a failed statement. Statements on tables which don't exist fail with
+/// `relation "..." does not exist`, which means the index doesn't exist, and statements cancelled
+/// by the `statement_timeout` of the database ran out of time.
+fn sql_error(response: Response) -> SearchError {
+    match search_error_from_failed_response(response) {
+        SearchError::InvalidQuery(message)
+            if message.starts_with("relation ") && message.ends_with("does not exist") =>
+        {
+            SearchError::IndexNotFound
+        }
+        SearchError::InvalidQuery(message) if message.contains("statement timeout") => {
+            SearchError::Timeout
+        }
+        error => error,
+    }
+}
+
+impl PostgresApi {
+    /// Runs a statement, returning its rows
+    pub fn execute(&self, statement: &Statement, operation: &str) -> Result<Vec<Row>, SearchError> {
+        trace!("Running {operation} statement: {}", statement.query);
+
+        let response = self
+            .send(statement)
+            .map_err(|e| from_send_error(format!("Failed to {operation}"), e))?;
+        Ok(parse_response::<QueryResult>(response)?.rows)
+    }
+
+    /// Runs statements in a single transaction, returning the rows of each of them
+    pub fn transaction(
+        &self,
+        statements: &[Statement],
+        operation: &str,
+    ) -> Result<Vec<Vec<Row>>, SearchError> {
+        trace!("Running {} {operation} statements", statements.len());
+
+        let response = self
+            .send(&BatchRequest {
+                queries: statements,
+            })
+            .map_err(|e| from_send_error(format!("Failed to {operation}"), e))?;
+        Ok(parse_batch(parse_response::<BatchResult>(response)?))
+    }
+
+    /// Runs the statements of a search in a single transaction, mapping requests that ran out of
+    /// time to `SearchError::Timeout`
+    pub fn search(&self, statements: &[Statement]) -> Result<Vec<Vec<Row>>, SearchError> {
+        trace!("Searching: {statements:?}");
+
+        let response = self
+            .send(&BatchRequest {
+                queries: statements,
+            })
+            .map_err(|e| from_send_error("Failed to search", e))?;
+        Ok(parse_batch(parse_response::<BatchResult>(response)?))
+    }
+}
+
+fn parse_batch(batch: BatchResult) -> Vec<Vec<Row>> {
+    batch
+        .results
+        .into_iter()
+        .map(|result| result.rows)
+        .collect()
+}
@@ -0,0 +1,1169 @@
+use crate::client::{Row, Statement};
+use golem_search::config::{get_max_pagination_depth_config, search_filters_match_any};
+use golem_search::error::invalid_query;
+use golem_search::golem::search::types::{
+    Doc, FieldType, HighlightConfig, Schema, SchemaField, SearchError, SearchHit, SearchQuery,
+};
+use serde_json::{json, Map as JsonMap, Value as JsonValue};
+
+const DEFAULT_PER_PAGE: u32 = 20;
+
+/// Maximum number of values counted per facet
+const MAX_FACET_VALUES: u32 = 100;
+
+/// Number of lists of the ivfflat indexes of the vector embeddings
+const VECTOR_INDEX_LISTS: u32 = 100;
+
+/// Document field holding the vector embedding of a document, which is copied to the embedding
+/// column of tables created with vector dimensions
+pub const VECTOR_FIELD: &str = "vector";
+
+const DEFAULT_PRE_TAG: &str = "<em>";
+const DEFAULT_POST_TAG: &str = "</em>";
+
+/// Lists the tables of the current schema having the columns of an index
+const LIST_TABLES_QUERY: &str = "SELECT c.relname AS name FROM pg_class c \
+     JOIN pg_namespace n ON n.oid = c.relnamespace \
+     WHERE n.nspname = current_schema() AND c.relkind = 'r' AND EXISTS (\
+     SELECT 1 FROM pg_attribute a WHERE a.attrelid = c.oid AND a.attname = 'search' \
+     AND a.atttypid = 'tsvector'::regtype AND NOT a.attisdropped) \
+     ORDER BY c.relname";
+
+/// How the tables of the indexes are created and searched
+#[derive(Debug, Clone)]
+pub struct TableOptions {
+    /// The text search configuration, such as `english` or `simple`, building the text search
+    /// vectors of the documents and the text search queries
+    pub text_search_config: String,
+    /// The dimensions of the vector embeddings, if the tables of new indexes have an embedding
+    /// column
+    pub vector_dimensions: Option<u32>,
+}
+
+/// Quotes an SQL identifier, such as the table of an index
+fn quote_ident(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+/// Quotes an SQL string literal, for the statements which can't take parameters
+fn quote_literal(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+fn is_field_name(key: &str) -> bool {
+    !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.')
+}
+
+/// The JSON value of a document field, where dots separate the keys of nested fields
+fn json_path(field: &str) -> String {
+    if field.contains('.') {
+        format!("doc #> '{{{}}}'", field.replace('.', ","))
+    } else {
+        format!("doc -> '{field}'")
+    }
+}
+
+/// The text of a document field, where dots separate the keys of nested fields
+fn text_path(field: &str) -> String {
+    if field.contains('.') {
+        format!("doc #>> '{{{}}}'", field.replace('.', ","))
+    } else {
+        format!("doc ->> '{field}'")
+    }
+}
+
+/// The parameters of a statement, bound in order to `$1`, `$2`, ...
+#[derive(Debug, Clone, Default)]
+struct Params(Vec<JsonValue>);
+
+impl Params {
+    /// Adds a parameter, returning its placeholder
+    fn bind(&mut self, value: impl Into<String>) -> String {
+        self.0.push(JsonValue::String(value.into()));
+        format!("${}", self.0.len())
+    }
+
+    fn statement(&self, query: String) -> Statement {
+        Statement {
+            query,
+            params: self.0.clone(),
+        }
+    }
+}
+
+fn doc_content(doc: &Doc) -> Result<JsonMap<String, JsonValue>, SearchError> {
+    match serde_json::from_str::<JsonValue>(&doc.content) {
+        Ok(JsonValue::Object(fields)) => Ok(fields),
+        Ok(_) => Err(invalid_query("Document content must be a JSON object")),
+        Err(e) => Err(invalid_query(format!("Invalid JSON in document: {e}"))),
+    }
+}
+
+/// The statement inserting documents into the table of an index, replacing the documents of the
+/// same ids
+pub fn upsert_statement(index: &str, docs: &[Doc]) -> Result<Statement, SearchError> {
+    let rows = docs
+        .iter()
+        .map(|doc| Ok(json!({ "id": doc.id, "doc": doc_content(doc)? })))
+        .collect::<Result<Vec<_>, SearchError>>()?;
+
+    let mut params = Params::default();
+    let rows = params.bind(JsonValue::Array(rows).to_string());
+    Ok(params.statement(format!(
+        "INSERT INTO {} (id, doc) SELECT item ->> 'id', item -> 'doc' \
+         FROM jsonb_array_elements({rows}::jsonb) AS item \
+         ON CONFLICT (id) DO UPDATE SET doc = EXCLUDED.doc",
+        quote_ident(index)
+    )))
+}
+
+pub fn delete_statement(index: &str, ids: &[String]) -> Statement {
+    let mut params = Params::default();
+    let ids = params.bind(json!(ids).to_string());
+    params.statement(format!(
+        "DELETE FROM {} WHERE id IN (SELECT jsonb_array_elements_text({ids}::jsonb))",
+        quote_ident(index)
+    ))
+}
+
+pub fn get_statement(index: &str, id: &str) -> Statement {
+    let mut params = Params::default();
+    let id = params.bind(id);
+    params.statement(format!(
+        "SELECT id, doc FROM {} WHERE id = {id}",
+        quote_ident(index)
+    ))
+}
+
+pub fn drop_statement(index: &str) -> Statement {
+    Statement::new(format!("DROP TABLE {}", quote_ident(index)))
+}
+
+pub fn list_statement() -> Statement {
+    Statement::new(LIST_TABLES_QUERY)
+}
+
+/// The statement reading the schema kept in the comment of the table of an index
+pub fn schema_statement(index: &str) -> Statement {
+    let mut params = Params::default();
+    let table = params.bind(quote_ident(index));
+    params.statement(format!(
+        "SELECT obj_description({table}::regclass, 'pg_class') AS schema"
+    ))
+}
+
+/// The value of a `jsonb` column, which is returned as text
+fn column_json(value: &JsonValue) -> JsonValue {
+    match value {
+        JsonValue::String(text) => serde_json::from_str(text).unwrap_or_else(|_| value.clone()),
+        value => value.clone(),
+    }
+}
+
+/// The value of a numeric column, which is returned as text
+fn column_f64(value: &JsonValue) -> Option<f64> {
+    match value {
+        JsonValue::String(text) => text.parse().ok(),
+        value => value.as_f64(),
+    }
+}
+
+fn column_text(value: &JsonValue) -> Option<String> {
+    match value {
+        JsonValue::Null => None,
+        JsonValue::String(text) => Some(text.clone()),
+        value => Some(value.to_string()),
+    }
+}
+
+pub fn row_to_doc(row: &Row) -> Doc {
+    Doc {
+        id: row.get("id").and_then(column_text).unwrap_or_default(),
+        content: row
+            .get("doc")
+            .map(column_json)
+            .unwrap_or_else(|| json!({}))
+            .to_string(),
+    }
+}
+
+pub fn row_to_index_name(row: &Row) -> Option<String> {
+    row.get("name").and_then(column_text)
+}
+
+fn field_type_name(field_type: FieldType) -> &'static str {
+    match field_type {
+        FieldType::Text => "text",
+        FieldType::Keyword => "keyword",
+        FieldType::Integer => "integer",
+        FieldType::Float => "float",
+        FieldType::Boolean => "boolean",
+        FieldType::Date => "date",
+        FieldType::GeoPoint => "geo-point",
+    }
+}
+
+fn field_type_from_name(name: &str) -> Option<FieldType> {
+    match name {
+        "text" => Some(FieldType::Text),
+        "keyword" => Some(FieldType::Keyword),
+        "integer" => Some(FieldType::Integer),
+        "float" => Some(FieldType::Float),
+        "boolean" => Some(FieldType::Boolean),
+        "date" => Some(FieldType::Date),
+        "geo-point" => Some(FieldType::GeoPoint),
+        _ => None,
+    }
+}
+
+/// The schema as the JSON kept in the comment of the table of an index
+fn schema_to_json(schema: &Schema) -> JsonValue {
+    json!({
+        "fields": schema
+            .fields
+            .iter()
+            .map(|field| json!({
+                "name": field.name,
+                "type": field_type_name(field.field_type),
+                "required": field.required,
+                "facet": field.facet,
+                "sort": field.sort,
+                "index": field.index,
+            }))
+            .collect::<Vec<_>>(),
+        "primary_key": schema.primary_key,
+    })
+}
+
+fn json_to_schema_field(field: &JsonValue) -> Option<SchemaField> {
+    let flag = |name: &str| field.get(name).and_then(JsonValue::as_bool);
+    Some(SchemaField {
+        name: field.get("name")?.as_str()?.to_string(),
+        field_type: field_type_from_name(field.get("type")?.as_str()?)?,
+        required: flag("required").unwrap_or(false),
+        facet: flag("facet").unwrap_or(false),
+        sort: flag("sort").unwrap_or(false),
+        index: flag("index").unwrap_or(true),
+    })
+}
+
+/// The schema of an index, from the comment of its table. Tables created without a schema have
+/// no fields.
+pub fn row_to_schema(row: Option<&Row>) -> Schema {
+    let schema = row
+        .and_then(|row| row.get("schema"))
+        .map(column_json)
+        .unwrap_or(JsonValue::Null);
+    Schema {
+        fields: schema
+            .get("fields")
+            .and_then(JsonValue::as_array)
+            .map(|fields| fields.iter().filter_map(json_to_schema_field).collect())
+            .unwrap_or_default(),
+        primary_key: schema
+            .get("primary_key")
+            .and_then(JsonValue::as_str)
+            .map(str::to_string)
+            .or_else(|| Some("id".to_string())),
+    }
+}
+
+fn validate_schema(schema: &Schema) -> Result<(), SearchError> {
+    match schema
+        .fields
+        .iter()
+        .find(|field| !is_field_name(&field.name))
+    {
+        Some(field) => Err(invalid_query(format!(
+            "Invalid field name `{}`",
+            field.name
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// The expression of the text search vector of the documents: the indexed text fields of the
+/// schema, or every string of the documents without text fields
+fn search_expression(schema: Option<&Schema>, options: &TableOptions) -> String {
+    let config = format!("{}::regconfig", quote_literal(&options.text_search_config));
+    let text_fields = schema
+        .map(|schema| {
+            schema
+                .fields
+                .iter()
+                .filter(|field| field.index && field.field_type == FieldType::Text)
+                .map(|field| format!("coalesce({}, '')", text_path(&field.name)))
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+
+    if text_fields.is_empty() {
+        format!("jsonb_to_tsvector({config}, doc, '[\"string\"]')")
+    } else {
+        format!("to_tsvector({config}, {})", text_fields.join(" || ' ' || "))
+    }
+}
+
+/// The statements creating the index of the search vectors, and the indexes of the fields which
+/// are sorted or faceted
+fn index_statements(index: &str, schema: Option<&Schema>) -> Vec<Statement> {
+    let table = quote_ident(index);
+    let mut statements = vec![Statement::new(format!(
+        "CREATE INDEX IF NOT EXISTS {} ON {table} USING gin (search)",
+        quote_ident(&format!("{index}_search_idx"))
+    ))];
+    statements.extend(
+        schema
+            .map(|schema| schema.fields.as_slice())
+            .unwrap_or_default()
+            .iter()
+            .filter(|field| field.facet || field.sort)
+            .map(|field| {
+                Statement::new(format!(
+                    "CREATE INDEX IF NOT EXISTS {} ON {table} (({}))",
+                    quote_ident(&format!("{index}_{}_idx", field.name.replace('.', "_"))),
+                    json_path(&field.name)
+                ))
+            }),
+    );
+    statements
+}
+
+fn comment_statement(index: &str, schema: &Schema) -> Statement {
+    Statement::new(format!(
+        "COMMENT ON TABLE {} IS {}",
+        quote_ident(index),
+        quote_literal(&schema_to_json(schema).to_string())
+    ))
+}
+
+/// The statements creating the table of an index: the documents are kept as `jsonb` by id, with
+/// a generated text search vector and, given vector dimensions, a generated embedding of their
+/// `vector` field. The schema is kept in the comment of the table.
+pub fn create_statements(
+    index: &str,
+    schema: Option<&Schema>,
+    options: &TableOptions,
+) -> Result<Vec<Statement>, SearchError> {
+    if let Some(schema) = schema {
+        validate_schema(schema)?;
+    }
+    let table = quote_ident(index);
+
+    let mut columns = vec![
+        "id text PRIMARY KEY".to_string(),
+        "doc jsonb NOT NULL".to_string(),
+        format!(
+            "search tsvector GENERATED ALWAYS AS ({}) STORED",
+            search_expression(schema, options)
+        ),
+    ];
+    if let Some(dimensions) = options.vector_dimensions {
+        columns.push(format!(
+            "embedding vector({dimensions}) GENERATED ALWAYS AS (CASE WHEN \
+             jsonb_typeof(doc -> '{VECTOR_FIELD}') = 'array' \
+             THEN (doc ->> '{VECTOR_FIELD}')::vector({dimensions}) END) STORED"
+        ));
+    }
+
+    let mut statements = vec![
+        Statement::new(format!("CREATE TABLE {table} ({})", columns.join(", "))),
+        Statement::new(format!(
+            "CREATE INDEX {} ON {table} USING gin (doc jsonb_path_ops)",
+            quote_ident(&format!("{index}_doc_idx"))
+        )),
+    ];
+    if options.vector_dimensions.is_some() {
+        statements.push(Statement::new(format!(
+            "CREATE INDEX {} ON {table} USING ivfflat (embedding vector_cosine_ops) \
+             WITH (lists = {VECTOR_INDEX_LISTS})",
+            quote_ident(&format!("{index}_embedding_idx"))
+        )));
+    }
+    statements.extend(index_statements(index, schema));
+    if let Some(schema) = schema {
+        statements.push(comment_statement(index, schema));
+    }
+    Ok(statements)
+}
+
+/// The statements applying a new schema to the table of an index, regenerating its text search
+/// vectors from the new text fields and indexing the new sorted and faceted fields
+pub fn update_schema_statements(
+    index: &str,
+    schema: &Schema,
+    options: &TableOptions,
+) -> Result<Vec<Statement>, SearchError> {
+    validate_schema(schema)?;
+    let table = quote_ident(index);
+
+    let mut statements = vec![
+        Statement::new(format!("ALTER TABLE {table} DROP COLUMN search")),
+        Statement::new(format!(
+            "ALTER TABLE {table} ADD COLUMN search tsvector GENERATED ALWAYS AS ({}) STORED",
+            search_expression(Some(schema), options)
+        )),
+    ];
+    statements.extend(index_statements(index, Some(schema)));
+    statements.push(comment_statement(index, schema));
+    Ok(statements)
+}
+
+/// Whether a value looks like an ISO 8601 date, such as `2020-01-01` or `2020-01-01T00:00:00Z`
+fn is_date(value: &str) -> bool {
+    let bytes = value.as_bytes();
+    bytes.len() >= 10
+        && bytes[..4].iter().all(u8::is_ascii_digit)
+        && bytes[4] == b'-'
+        && bytes[5..7].iter().all(u8::is_ascii_digit)
+        && bytes[7] == b'-'
+        && bytes[8..10].iter().all(u8::is_ascii_digit)
+}
+
+/// The value of a filter: quoted values are strings, and numbers and booleans keep their type
+fn filter_value(value: &str) -> JsonValue {
+    let value = value.trim();
+    if let Some(unquoted) = value
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+    {
+        return json!(unquoted);
+    }
+    match serde_json::from_str::<JsonValue>(value) {
+        Ok(value @ (JsonValue::Number(_) | JsonValue::Bool(_))) => value,
+        _ => json!(value),
+    }
+}
+
+/// The document containing a field of the given value, for matching with `@>`
+fn containment(key: &str, value: JsonValue) -> JsonValue {
+    key.rsplit('.')
+        .fold(value, |value, key| json!({ key: value }))
+}
+
+/// Converts a filter to an SQL condition. Filters are `field:value`, `field=value`,
+/// `field!=value`, and `field>value` style comparisons with `>`, `>=`, `<` and `<=`. Equality
+/// filters match with the containment operator of the `jsonb_path_ops` index of the documents,
+/// and range filters compare numbers, dates or strings depending on the value.
+fn filter_to_condition(filter: &str, params: &mut Params) -> Result<String, SearchError> {
+    let filter = filter.trim();
+    let position = filter
+        .find([':', '=', '!', '<', '>'])
+        .ok_or_else(|| invalid_query(format!("Unsupported filter `{filter}`")))?;
+    let key = filter[..position].trim();
+    let rest = &filter[position..];
+    let (op, value) = [">=", "<=", "!=", ">", "<", "=", ":"]
+        .into_iter()
+        .find_map(|op| Some((op, rest.strip_prefix(op)?)))
+        .ok_or_else(|| invalid_query(format!("Unsupported filter `{filter}`")))?;
+    if !is_field_name(key) || value.trim().is_empty() {
+        return Err(invalid_query(format!("Unsupported filter `{filter}`")));
+    }
+    let value = filter_value(value);
+
+    match (op, value) {
+        (":" | "=", value) => {
+            let contained = params.bind(containment(key, value).to_string());
+            Ok(format!("doc @> {contained}::jsonb"))
+        }
+        ("!=", value) => {
+            let contained = params.bind(containment(key, value).to_string());
+            Ok(format!("NOT doc @> {contained}::jsonb"))
+        }
+        (op, JsonValue::Number(value)) => Ok(format!(
+            "({})::numeric {op} {}::numeric",
+            text_path(key),
+            params.bind(value.to_string())
+        )),
+        (op, JsonValue::String(value)) if is_date(&value) => Ok(format!(
+            "({})::timestamptz {op} {}::timestamptz",
+            text_path(key),
+            params.bind(value)
+        )),
+        (op, JsonValue::String(value)) => {
+            Ok(format!("{} {op} {}", text_path(key), params.bind(value)))
+        }
+        _ => Err(invalid_query(format!("Unsupported filter `{filter}`"))),
+    }
+}
+
+/// The query vector, given as `{"vector": [...]}` in the provider parameters of the query
+fn query_vector(query: &SearchQuery) -> Result<Option<Vec<f32>>, SearchError> {
+    let Some(params) = query
+        .config
+        .as_ref()
+        .and_then(|config| config.provider_params.as_deref())
+    else {
+        return Ok(None);
+    };
+    let params = serde_json::from_str::<JsonMap<String, JsonValue>>(params)
+        .map_err(|e| invalid_query(format!("Invalid provider parameters: {e}")))?;
+
+    match params.get(VECTOR_FIELD) {
+        None => Ok(None),
+        Some(vector) => vector
+            .as_array()
+            .and_then(|values| {
+                values
+                    .iter()
+                    .map(|value| value.as_f64().map(|value| value as f32))
+                    .collect::<Option<Vec<_>>>()
+            })
+            .filter(|values| !values.is_empty())
+            .map(Some)
+            .ok_or_else(|| {
+                invalid_query("The query `vector` must be a non-empty array of numbers")
+            }),
+    }
+}
+
+/// The text search query of a search, and the placeholder of its text search configuration
+struct TextQuery {
+    config: String,
+    tsquery: String,
+}
+
+/// The condition selecting the matches of a query: its text must match the search vector and
+/// its filters restrict the matches, either all of them or, when any filter may match, at least
+/// one of them. Vector searches only match documents with an embedding.
+fn query_to_condition(
+    query: &SearchQuery,
+    options: &TableOptions,
+    params: &mut Params,
+) -> Result<(String, Option<TextQuery>), SearchError> {
+    let mut conditions = Vec::new();
+
+    let text = match query.q.as_deref().map(str::trim).filter(|q| !q.is_empty()) {
+        Some(q) => {
+            let config = params.bind(options.text_search_config.clone());
+            let tsquery = format!(
+                "websearch_to_tsquery({config}::regconfig, {})",
+                params.bind(q)
+            );
+            conditions.push(format!("search @@ {tsquery}"));
+            Some(TextQuery { config, tsquery })
+        }
+        None => None,
+    };
+
+    let filters = query
+        .filters
+        .iter()
+        .map(|filter| filter_to_condition(filter, params))
+        .collect::<Result<Vec<_>, _>>()?;
+    if search_filters_match_any(query) && filters.len() > 1 {
+        conditions.push(format!("({})", filters.join(" OR ")));
+    } else {
+        conditions.extend(filters);
+    }
+
+    if query_vector(query)?.is_some() {
+        conditions.push("embedding IS NOT NULL".to_string());
+    }
+
+    let condition = if conditions.is_empty() {
+        "TRUE".to_string()
+    } else {
+        conditions.join(" AND ")
+    };
+    Ok((condition, text))
+}
+
+/// The `ORDER BY` entries of a query's `field`, `field:asc`, `field:desc` or `-field` sort
+/// entries, missing values last
+fn query_to_order(sort: &[String]) -> Result<Vec<String>, SearchError> {
+    sort.iter()
+        .map(|sort| {
+            let sort = sort.trim();
+            let (key, order) = if let Some(key) = sort.strip_prefix('-') {
+                (key, "DESC")
+            } else {
+                match sort.rsplit_once(':') {
+                    Some((key, order)) if order.eq_ignore_ascii_case("asc") => (key, "ASC"),
+                    Some((key, order)) if order.eq_ignore_ascii_case("desc") => (key, "DESC"),
+                    Some(_) => return Err(invalid_query(format!("Invalid sort `{sort}`"))),
+                    None => (sort, "ASC"),
+                }
+            };
+            if !is_field_name(key) {
+                return Err(invalid_query(format!("Invalid sort `{sort}`")));
+            }
+            Ok(format!("{} {order} NULLS LAST", json_path(key)))
+        })
+        .collect()
+}
+
+/// The `ts_headline` options of a highlight, with its tags quoted
+fn headline_options(highlight: &HighlightConfig) -> String {
+    let quote = |tag: &str| format!("\"{}\"", tag.replace('\\', "\\\\").replace('"', "\\\""));
+    format!(
+        "StartSel={}, StopSel={}",
+        quote(highlight.pre_tag.as_deref().unwrap_or(DEFAULT_PRE_TAG)),
+        quote(highlight.post_tag.as_deref().unwrap_or(DEFAULT_POST_TAG))
+    )
+}
+
+/// Number of hits to skip and number of hits per page of a query
+pub fn query_window(query: &SearchQuery) -> Result<(u32, u32), SearchError> {
+    let per_page = query.per_page.unwrap_or(DEFAULT_PER_PAGE);
+    let offset = query.offset.unwrap_or_else(|| {
+        query
+            .page
+            .unwrap_or(1)
+            .saturating_sub(1)
+            .saturating_mul(per_page)
+    });
+    if offset.saturating_add(per_page) > get_max_pagination_depth_config() {
+        return Err(invalid_query(format!(
+            "Requested hits beyond the maximum pagination depth of {}",
+            get_max_pagination_depth_config()
+        )));
+    }
+    Ok((offset, per_page))
+}
+
+/// Builds the statements of a search on the table of an index: the page of hits, read with
+/// `LIMIT` and `OFFSET`, then the count of the matches when `count` is set, then the counts of
+/// each facet. Text searches are ranked with `ts_rank`, unless the provider parameters give a
+/// query vector, `{"vector": [...]}`, which ranks the matches by cosine similarity.
+pub fn search_statements(
+    index: &str,
+    query: &SearchQuery,
+    options: &TableOptions,
+    count: bool,
+) -> Result<Vec<Statement>, SearchError> {
+    let (offset, per_page) = query_window(query)?;
+    let table = quote_ident(index);
+
+    let mut params = Params::default();
+    let (condition, text) = query_to_condition(query, options, &mut params)?;
+    let condition_params = params.clone();
+
+    let vector = query_vector(query)?
+        .map(|vector| params.bind(json!(vector).to_string()))
+        .map(|vector| format!("(embedding <=> {vector}::vector)"));
+    let score = match (&vector, &text) {
+        (Some(distance), _) => format!("1 - {distance}"),
+        (None, Some(text)) => format!("ts_rank(search, {})", text.tsquery),
+        (None, None) => "NULL::real".to_string(),
+    };
+    let mut columns = format!("id, doc, {score} AS score");
+    if let (Some(highlight), Some(text)) = (&query.highlight, &text) {
+        columns.push_str(&format!(
+            ", ts_headline({}::regconfig, doc, {}, {}) AS highlights",
+            text.config,
+            text.tsquery,
+            params.bind(headline_options(highlight))
+        ));
+    }
+
+    let mut order = query_to_order(&query.sort)?;
+    if order.is_empty() {
+        match (&vector, &text) {
+            (Some(distance), _) => order.push(distance.clone()),
+            (None, Some(_)) => order.push("score DESC".to_string()),
+            (None, None) => {}
+        }
+    }
+    order.push("id".to_string());
+
+    let mut statements = vec![params.statement(format!(
+        "SELECT {columns} FROM {table} WHERE {condition} ORDER BY {} LIMIT {per_page} OFFSET {offset}",
+        order.join(", ")
+    ))];
+    if count {
+        statements.push(condition_params.statement(format!(
+            "SELECT count(*) AS total FROM {table} WHERE {condition}"
+        )));
+    }
+    for facet in &query.facets {
+        if !is_field_name(facet) {
+            return Err(invalid_query(format!("Invalid facet `{facet}`")));
+        }
+        let value = text_path(facet);
+        statements.push(condition_params.statement(format!(
+            "SELECT {value} AS value, count(*) AS count FROM {table} \
+             WHERE ({condition}) AND {value} IS NOT NULL \
+             GROUP BY 1 ORDER BY 2 DESC, 1 LIMIT {MAX_FACET_VALUES}"
+        )));
+    }
+    Ok(statements)
+}
+
+/// Highlights in the same shape as the other providers: `{"field": ["snippet"]}`, from the
+/// highlighted document returned by `ts_headline`. Only the highlighted fields, or the top level
+/// fields when none are given, with a match are kept.
+fn highlights_to_snippets(highlighted: &JsonValue, query: &SearchQuery) -> Option<String> {
+    let highlight = query.highlight.as_ref()?;
+    let pre_tag = highlight.pre_tag.as_deref().unwrap_or(DEFAULT_PRE_TAG);
+    let fields = if highlight.fields.is_empty() {
+        highlighted.as_object()?.keys().cloned().collect()
+    } else {
+        highlight.fields.clone()
+    };
+
+    let snippets = fields
+        .into_iter()
+        .filter_map(|field| {
+            let pointer = format!("/{}", field.replace('.', "/"));
+            let snippet = highlighted.pointer(&pointer)?.as_str()?;
+            if !snippet.contains(pre_tag) {
+                return None;
+            }
+            let snippet = match highlight.max_length {
+                Some(max_length) => snippet.chars().take(max_length as usize).collect(),
+                None => snippet.to_string(),
+            };
+            Some((field, json!([snippet])))
+        })
+        .collect::<JsonMap<_, _>>();
+    (!snippets.is_empty()).then(|| JsonValue::Object(snippets).to_string())
+}
+
+fn row_to_hit(row: &Row, query: &SearchQuery) -> SearchHit {
+    let mut content = row.get("doc").map(column_json).unwrap_or_else(|| json!({}));
+    let attributes = query
+        .config
+        .as_ref()
+        .map(|config| config.attributes_to_retrieve.as_slice())
+        .unwrap_or_default();
+    if let (false, JsonValue::Object(fields)) = (attributes.is_empty(), &mut content) {
+        fields.retain(|key, _| attributes.contains(key));
+    }
+
+    SearchHit {
+        id: row.get("id").and_then(column_text).unwrap_or_default(),
+        score: row.get("score").and_then(column_f64),
+        content: Some(content.to_string()),
+        highlights: row
+            .get("highlights")
+            .map(column_json)
+            .and_then(|highlighted| highlights_to_snippets(&highlighted, query)),
+    }
+}
+
+/// Facet counts in the same shape as the other providers: `{"field": {"value": count}}`
+fn rows_to_facets(facets: &[(&String, Vec<Row>)]) -> Option<String> {
+    let counts = facets
+        .iter()
+        .map(|(facet, rows)| {
+            let counts = rows
+                .iter()
+                .filter_map(|row| {
+                    let value = row.get("value").and_then(column_text)?;
+                    let count = row.get("count").and_then(column_f64)? as u64;
+                    Some((value, json!(count)))
+                })
+                .collect::<JsonMap<_, _>>();
+            (facet.to_string(), JsonValue::Object(counts))
+        })
+        .collect::<JsonMap<_, _>>();
+    (!counts.is_empty()).then(|| JsonValue::Object(counts).to_string())
+}
+
+/// The results of the statements of a search
+pub struct ParsedResults {
+    pub hits: Vec<SearchHit>,
+    pub total: Option<u32>,
+    pub facets: Option<String>,
+}
+
+/// Parses the rows of the statements built by [`search_statements`] with the same `count`
+pub fn parse_search(results: Vec<Vec<Row>>, query: &SearchQuery, count: bool) -> ParsedResults {
+    let mut results = results.into_iter();
+    let hits = results
+        .next()
+        .unwrap_or_default()
+        .iter()
+        .map(|row| row_to_hit(row, query))
+        .collect();
+    let total = if count {
+        results
+            .next()
+            .and_then(|rows| rows.first()?.get("total").and_then(column_f64))
+            .map(|total| total as u32)
+    } else {
+        None
+    };
+    let facets = query.facets.iter().zip(results).collect::<Vec<_>>();
+
+    ParsedResults {
+        hits,
+        total,
+        facets: rows_to_facets(&facets),
+    }
+}
+
+pub fn create_retry_query(original_query: &SearchQuery, partial_hits: &[SearchHit]) -> SearchQuery {
+    let mut retry_query = original_query.clone();
+
+    let current_offset = original_query.offset.unwrap_or(0);
+    let per_page = original_query.per_page.unwrap_or(DEFAULT_PER_PAGE);
+    let hits_received = partial_hits.len() as u32;
+
+    retry_query.offset = Some(current_offset + hits_received.min(per_page));
+
+    retry_query
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use golem_search::golem::search::types::{FilterMode, SearchConfig};
+
+    fn query(q: Option<&str>, filters: &[&str]) -> SearchQuery {
+        SearchQuery {
+            q: q.map(str::to_string),
+            filters: filters.iter().map(|filter| filter.to_string()).collect(),
+            sort: vec![],
+            facets: vec![],
+            page: None,
+            per_page: None,
+            offset: None,
+            highlight: None,
+            config: None,
+        }
+    }
+
+    fn field(name: &str, field_type: FieldType, facet: bool, sort: bool) -> SchemaField {
+        SchemaField {
+            name: name.to_string(),
+            field_type,
+            required: false,
+            facet,
+            sort,
+            index: true,
+        }
+    }
+
+    fn config(provider_params: Option<&str>, filter_mode: Option<FilterMode>) -> SearchConfig {
+        SearchConfig {
+            timeout_ms: None,
+            boost_fields: vec![],
+            attributes_to_retrieve: vec![],
+            language: None,
+            typo_tolerance: None,
+            exact_match_boost: None,
+            provider_params: provider_params.map(str::to_string),
+            profile: None,
+            filter_mode,
+            post_filters: vec![],
+            rescore: None,
+            fallbacks: vec![],
+        }
+    }
+
+    fn options(vector_dimensions: Option<u32>) -> TableOptions {
+        TableOptions {
+            text_search_config: "english".to_string(),
+            vector_dimensions,
+        }
+    }
+
+    fn row(value: JsonValue) -> Row {
+        value.as_object().unwrap().clone()
+    }
+
+    #[test]
+    fn test_doc_statements() {
+        let docs = vec![Doc {
+            id: "doc1".to_string(),
+            content: r#"{"title":"Dune"}"#.to_string(),
+        }];
+        let statement = upsert_statement("books", &docs).unwrap();
+        assert_eq!(
+            statement.query,
+            "INSERT INTO \"books\" (id, doc) SELECT item ->> 'id', item -> 'doc' \
+             FROM jsonb_array_elements($1::jsonb) AS item \
+             ON CONFLICT (id) DO UPDATE SET doc = EXCLUDED.doc"
+        );
+        assert_eq!(
+            serde_json::from_str::<JsonValue>(statement.params[0].as_str().unwrap()).unwrap(),
+            json!([{ "id": "doc1", "doc": { "title": "Dune" } }])
+        );
+
+        let invalid = vec![Doc {
+            id: "doc1".to_string(),
+            content: "[]".to_string(),
+        }];
+        assert!(matches!(
+            upsert_statement("books", &invalid),
+            Err(SearchError::InvalidQuery(_))
+        ));
+
+        let doc = row_to_doc(&row(json!({ "id": "doc1", "doc": r#"{"title":"Dune"}"# })));
+        assert_eq!(doc.id, "doc1");
+        assert_eq!(doc.content, r#"{"title":"Dune"}"#);
+
+        assert_eq!(quote_ident("my\"books"), "\"my\"\"books\"");
+        assert_eq!(schema_statement("books").params, vec![json!("\"books\"")]);
+    }
+
+    #[test]
+    fn test_create_statements() {
+        let schema = Schema {
+            fields: vec![
+                field("title", FieldType::Text, false, false),
+                field("summary", FieldType::Text, false, false),
+                field("genre", FieldType::Keyword, true, false),
+                field("year", FieldType::Integer, false, true),
+            ],
+            primary_key: None,
+        };
+        let statements = create_statements("books", Some(&schema), &options(Some(3))).unwrap();
+        let queries = statements
+            .iter()
+            .map(|statement| statement.query.as_str())
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            queries[0],
+            "CREATE TABLE \"books\" (id text PRIMARY KEY, doc jsonb NOT NULL, \
+             search tsvector GENERATED ALWAYS AS (to_tsvector('english'::regconfig, \
+             coalesce(doc ->> 'title', '') || ' ' || coalesce(doc ->> 'summary', ''))) STORED, \
+             embedding vector(3) GENERATED ALWAYS AS (CASE WHEN \
+             jsonb_typeof(doc -> 'vector') = 'array' \
+             THEN (doc ->> 'vector')::vector(3) END) STORED)"
+        );
+        assert_eq!(
+            &queries[1..],
+            &[
+                "CREATE INDEX \"books_doc_idx\" ON \"books\" USING gin (doc jsonb_path_ops)",
+                "CREATE INDEX \"books_embedding_idx\" ON \"books\" USING ivfflat \
+                 (embedding vector_cosine_ops) WITH (lists = 100)",
+                "CREATE INDEX IF NOT EXISTS \"books_search_idx\" ON \"books\" USING gin (search)",
+                "CREATE INDEX IF NOT EXISTS \"books_genre_idx\" ON \"books\" ((doc -> 'genre'))",
+                "CREATE INDEX IF NOT EXISTS \"books_year_idx\" ON \"books\" ((doc -> 'year'))",
+                comment_statement("books", &schema).query.as_str(),
+            ]
+        );
+
+        let statements = create_statements("books", None, &options(None)).unwrap();
+        assert_eq!(statements.len(), 3);
+        assert!(statements[0]
+            .query
+            .contains("jsonb_to_tsvector('english'::regconfig, doc, '[\"string\"]')"));
+
+        let invalid = Schema {
+            fields: vec![field("title'); DROP", FieldType::Text, false, false)],
+            primary_key: None,
+        };
+        assert!(matches!(
+            create_statements("books", Some(&invalid), &options(None)),
+            Err(SearchError::InvalidQuery(_))
+        ));
+    }
+
+    #[test]
+    fn test_schema_round_trip() {
+        let schema = Schema {
+            fields: vec![
+                field("title", FieldType::Text, false, false),
+                field("genre", FieldType::Keyword, true, true),
+                field("location", FieldType::GeoPoint, false, false),
+            ],
+            primary_key: Some("isbn".to_string()),
+        };
+        let comment = schema_to_json(&schema).to_string();
+        let parsed = row_to_schema(Some(&row(json!({ "schema": comment }))));
+        assert_eq!(parsed, schema);
+
+        let parsed = row_to_schema(Some(&row(json!({ "schema": null }))));
+        assert!(parsed.fields.is_empty());
+        assert_eq!(parsed.primary_key, Some("id".to_string()));
+
+        let statement = comment_statement("books", &schema);
+        assert!(statement
+            .query
+            .starts_with("COMMENT ON TABLE \"books\" IS '{"));
+    }
+
+    #[test]
+    fn test_filters_to_conditions() {
+        let mut params = Params::default();
+        let conditions = [
+            "genre:sci-fi",
+            "author.name=Herbert",
+            "year!=1965",
+            "year>=1950",
+            "published<2000-01-01",
+            "title>\"M\"",
+        ]
+        .iter()
+        .map(|filter| filter_to_condition(filter, &mut params).unwrap())
+        .collect::<Vec<_>>();
+
+        assert_eq!(
+            conditions,
+            vec![
+                "doc @> $1::jsonb",
+                "doc @> $2::jsonb",
+                "NOT doc @> $3::jsonb",
+                "(doc ->> 'year')::numeric >= $4::numeric",
+                "(doc ->> 'published')::timestamptz < $5::timestamptz",
+                "doc ->> 'title' > $6",
+            ]
+        );
+        assert_eq!(
+            params.0,
+            vec![
+                json!(r#"{"genre":"sci-fi"}"#),
+                json!(r#"{"author":{"name":"Herbert"}}"#),
+                json!(r#"{"year":1965}"#),
+                json!("1950"),
+                json!("2000-01-01"),
+                json!("M"),
+            ]
+        );
+
+        for filter in ["genre", "bad key:1", "title':1", "available>true"] {
+            assert!(
+                filter_to_condition(filter, &mut Params::default()).is_err(),
+                "{filter}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_search_statements() {
+        let mut search = query(Some("desert planet"), &["genre:sci-fi", "year>1950"]);
+        search.sort = vec!["-year".to_string()];
+        search.facets = vec!["genre".to_string()];
+        search.page = Some(2);
+        search.per_page = Some(10);
+        search.highlight = Some(HighlightConfig {
+            fields: vec![],
+            pre_tag: None,
+            post_tag: None,
+            max_length: None,
+        });
+        search.config = Some(config(None, Some(FilterMode::Any)));
+
+        let statements = search_statements("books", &search, &options(None), true).unwrap();
+        let condition = "search @@ websearch_to_tsquery($1::regconfig, $2) \
+                         AND (doc @> $3::jsonb OR (doc ->> 'year')::numeric > $4::numeric)";
+        assert_eq!(
+            statements[0].query,
+            format!(
+                "SELECT id, doc, ts_rank(search, websearch_to_tsquery($1::regconfig, $2)) AS score, \
+                 ts_headline($1::regconfig, doc, websearch_to_tsquery($1::regconfig, $2), $5) \
+                 AS highlights FROM \"books\" WHERE {condition} \
+                 ORDER BY doc -> 'year' DESC NULLS LAST, id LIMIT 10 OFFSET 10"
+            )
+        );
+        assert_eq!(
+            statements[0].params,
+            vec![
+                json!("english"),
+                json!("desert planet"),
+                json!(r#"{"genre":"sci-fi"}"#),
+                json!("1950"),
+                json!(r#"StartSel="<em>", StopSel="</em>""#),
+            ]
+        );
+        assert_eq!(
+            statements[1].query,
+            format!("SELECT count(*) AS total FROM \"books\" WHERE {condition}")
+        );
+        assert_eq!(statements[1].params.len(), 4);
+        assert_eq!(
+            statements[2].query,
+            format!(
+                "SELECT doc ->> 'genre' AS value, count(*) AS count FROM \"books\" \
+                 WHERE ({condition}) AND doc ->> 'genre' IS NOT NULL \
+                 GROUP BY 1 ORDER BY 2 DESC, 1 LIMIT 100"
+            )
+        );
+
+        let statements =
+            search_statements("books", &query(None, &[]), &options(None), false).unwrap();
+        assert_eq!(statements.len(), 1);
+        assert_eq!(
+            statements[0].query,
+            "SELECT id, doc, NULL::real AS score FROM \"books\" WHERE TRUE \
+             ORDER BY id LIMIT 20 OFFSET 0"
+        );
+    }
+
+    #[test]
+    fn test_vector_search_statements() {
+        let mut search = query(None, &["genre:sci-fi"]);
+        search.config = Some(config(Some(r#"{"vector": [0.5, 1]}"#), None));
+
+        let statements = search_statements("books", &search, &options(Some(2)), false).unwrap();
+        assert_eq!(
+            statements[0].query,
+            "SELECT id, doc, 1 - (embedding <=> $2::vector) AS score FROM \"books\" \
+             WHERE doc @> $1::jsonb AND embedding IS NOT NULL \
+             ORDER BY (embedding <=> $2::vector), id LIMIT 20 OFFSET 0"
+        );
+        assert_eq!(statements[0].params[1], json!("[0.5,1.0]"));
+
+        search.config = Some(config(Some(r#"{"vector": []}"#), None));
+        assert!(matches!(
+            search_statements("books", &search, &options(Some(2)), false),
+            Err(SearchError::InvalidQuery(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_search() {
+        let mut search = query(Some("dune"), &[]);
+        search.facets = vec!["genre".to_string()];
+        search.highlight = Some(HighlightConfig {
+            fields: vec!["title".to_string()],
+            pre_tag: None,
+            post_tag: None,
+            max_length: Some(10),
+        });
+
+        let results = parse_search(
+            vec![
+                vec![row(json!({
+                    "id": "doc1",
+                    "doc": r#"{"title":"Dune","genre":"sci-fi"}"#,
+                    "score": "0.0607927",
+                    "highlights": r#"{"title":"<em>Dune</em> Messiah","genre":"sci-fi"}"#,
+                }))],
+                vec![row(json!({ "total": "1" }))],
+                vec![row(json!({ "value": "sci-fi", "count": "1" }))],
+            ],
+            &search,
+            true,
+        );
+
+        assert_eq!(results.total, Some(1));
+        assert_eq!(results.hits.len(), 1);
+        let hit = &results.hits[0];
+        assert_eq!(hit.id, "doc1");
+        assert_eq!(hit.score, Some(0.0607927));
+        assert_eq!(
+            hit.content.as_deref(),
+            Some(r#"{"genre":"sci-fi","title":"Dune"}"#)
+        );
+        assert_eq!(
+            hit.highlights.as_deref(),
+            Some(r#"{"title":["<em>Dune</"]}"#)
+        );
+        assert_eq!(results.facets.as_deref(), Some(r#"{"genre":{"sci-fi":1}}"#));
+    }
+
+    #[test]
+    fn test_create_retry_query() {
+        let mut original = query(Some("dune"), &[]);
+        original.offset = Some(10);
+        original.per_page = Some(5);
+        let hits = (0..7)
+            .map(|i| SearchHit {
+                id: i.to_string(),
+                score: None,
+                content: None,
+                highlights: None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(create_retry_query(&original, &hits).offset, Some(15));
+    }
+}
@@ -0,0 +1,341 @@
+use crate::client::PostgresApi;
+use crate::conversions::{
+    create_retry_query, create_statements, delete_statement, drop_statement, get_statement,
+    list_statement, parse_search, query_window, row_to_doc, row_to_index_name, row_to_schema,
+    schema_statement, search_statements, update_schema_statements, upsert_statement, TableOptions,
+};
+use golem_rust::wasm_rpc::Pollable;
+use golem_search::config::{
+    get_config_with_default, get_optional_config, profile_key, search_headers, search_profile,
+    search_timeout, worker_headers, worker_profile,
+};
+use golem_search::durability::{DurableSearch, ExtendedGuest};
+use golem_search::golem::search::core::{Guest, GuestSearchStream, SearchStream};
+use golem_search::golem::search::types::{
+    Doc, DocumentId, IndexName, IndexPage, PageRequest, Schema, SearchError, SearchHit,
+    SearchQuery, SearchResults,
+};
+use golem_search::pagination::index_page;
+use golem_search::LOGGING_STATE;
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+
+mod client;
+mod conversions;
+
+/// Search stream over the matches of a query, paged through with `LIMIT` and `OFFSET`
+struct PostgresSearchStream {
+    client: PostgresApi,
+    index_name: String,
+    query: SearchQuery,
+    offset: Cell<u32>,
+    finished: Cell<bool>,
+}
+
+impl PostgresSearchStream {
+    pub fn new(client: PostgresApi, index_name: String, query: SearchQuery) -> Self {
+        let offset = query_window(&query).map(|(offset, _)| offset).unwrap_or(0);
+        Self {
+            client,
+            index_name,
+            query,
+            offset: Cell::new(offset),
+            finished: Cell::new(false),
+        }
+    }
+
+    pub fn subscribe(&self) -> Pollable {
+        golem_rust::bindings::wasi::clocks::monotonic_clock::subscribe_duration(0)
+    }
+
+    /// Reads the next page of hits, and whether there may be more
+    fn next_page(&self, per_page: u32) -> Result<(Vec<SearchHit>, bool), SearchError> {
+        let mut query = self.query.clone();
+        query.page = None;
+        query.offset = Some(self.offset.get());
+        query.facets = vec![];
+
+        let statements = search_statements(
+            &self.index_name,
+            &query,
+            &PostgresComponent::table_options(search_profile(&self.query).as_deref()),
+            false,
+        )?;
+        let results = parse_search(self.client.search(&statements)?, &query, false);
+        let more = results.hits.len() as u32 >= per_page;
+        Ok((results.hits, more))
+    }
+}
+
+impl GuestSearchStream for PostgresSearchStream {
+    fn get_next(&self) -> Option<Vec<SearchHit>> {
+        if self.finished.get() {
+            return Some(vec![]);
+        }
+
+        let per_page = query_window(&self.query)
+            .map(|(_, per_page)| per_page)
+            .unwrap_or(0);
+
+        match self.next_page(per_page) {
+            Ok((hits, more)) => {
+                if !more || hits.is_empty() {
+                    self.finished.set(true);
+                }
+                self.offset.set(self.offset.get() + hits.len() as u32);
+                Some(hits)
+            }
+            Err(_) => {
+                self.finished.set(true);
+                Some(vec![])
+            }
+        }
+    }
+
+    fn blocking_get_next(&self) -> Vec<SearchHit> {
+        self.get_next().unwrap_or_default()
+    }
+}
+
+struct PostgresComponent;
+
+thread_local! {
+    /// The provider clients by configuration profile, reused across calls instead of being rebuilt
+    /// for each of them
+    static CLIENTS: RefCell<HashMap<Option<String>, PostgresApi>> = RefCell::new(HashMap::new());
+}
+
+impl PostgresComponent {
+    const URL_ENV_VAR: &'static str = "POSTGRES_HTTP_URL";
+    const CONNECTION_STRING_ENV_VAR: &'static str = "POSTGRES_CONNECTION_STRING";
+    const TEXT_SEARCH_CONFIG_ENV_VAR: &'static str = "POSTGRES_TEXT_SEARCH_CONFIG";
+    const VECTOR_DIMENSIONS_ENV_VAR: &'static str = "POSTGRES_VECTOR_DIMENSIONS";
+
+    fn create_client() -> Result<PostgresApi, SearchError> {
+        Self::create_profile_client(worker_profile().as_deref())
+            .map(|client| client.with_headers(worker_headers()))
+    }
+
+    /// Returns the client of `profile` cached for this worker, creating it from the configuration
+    /// on first use
+    fn create_profile_client(profile: Option<&str>) -> Result<PostgresApi, SearchError> {
+        CLIENTS.with_borrow_mut(|clients| {
+            let profile = profile.map(str::to_string);
+            if let Some(client) = clients.get(&profile) {
+                return Ok(client.clone());
+            }
+
+            let client = Self::new_client(profile.as_deref())?;
+            clients.insert(profile, client.clone());
+            Ok(client)
+        })
+    }
+
+    /// Creates a client from the `POSTGRES_HTTP_URL` of the SQL over HTTP gateway, such as
+    /// `https://<endpoint>.neon.tech`, and the `POSTGRES_CONNECTION_STRING` of the database
+    fn new_client(profile: Option<&str>) -> Result<PostgresApi, SearchError> {
+        let required = |name: &str, what: &str| {
+            get_optional_config(profile_key(name, profile))
+                .filter(|value| !value.is_empty())
+                .ok_or_else(|| SearchError::Internal(format!("Missing Postgres {what}")))
+        };
+
+        let url = required(Self::URL_ENV_VAR, "HTTP URL")?;
+        let connection_string = required(Self::CONNECTION_STRING_ENV_VAR, "connection string")?;
+
+        Ok(PostgresApi::new(url, connection_string))
+    }
+
+    /// The text search configuration of `POSTGRES_TEXT_SEARCH_CONFIG`, `english` by default, and
+    /// the `POSTGRES_VECTOR_DIMENSIONS` of the embeddings of the documents, which adds an
+    /// embedding column to the tables of new indexes and needs the `vector` extension
+    fn table_options(profile: Option<&str>) -> TableOptions {
+        TableOptions {
+            text_search_config: get_config_with_default(
+                profile_key(Self::TEXT_SEARCH_CONFIG_ENV_VAR, profile),
+                "english",
+            ),
+            vector_dimensions: get_optional_config(profile_key(
+                Self::VECTOR_DIMENSIONS_ENV_VAR,
+                profile,
+            ))
+            .and_then(|dimensions| dimensions.trim().parse().ok()),
+        }
+    }
+
+    fn search_page(
+        client: &PostgresApi,
+        index: &str,
+        query: &SearchQuery,
+    ) -> Result<SearchResults, SearchError> {
+        let (offset, per_page) = query_window(query)?;
+        let statements = search_statements(
+            index,
+            query,
+            &Self::table_options(search_profile(query).as_deref()),
+            true,
+        )?;
+
+        let results = parse_search(client.search(&statements)?, query, true);
+
+        Ok(SearchResults {
+            total: results.total,
+            page: Some(offset / per_page.max(1) + 1),
+            per_page: Some(per_page),
+            hits: results.hits,
+            facets: results.facets,
+            took_ms: None,
+            relaxation: None,
+            navigation: None,
+        })
+    }
+}
+
+impl Guest for PostgresComponent {
+    type SearchStream = PostgresSearchStream;
+
+    /// Creates the table of the index, with a GIN index of the text search vectors of its
+    /// documents and, when the embeddings have dimensions, an ivfflat index of their vectors
+    fn create_index(name: IndexName, schema: Option<Schema>) -> Result<(), SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = Self::create_client()?;
+        let statements = create_statements(
+            &name,
+            schema.as_ref(),
+            &Self::table_options(worker_profile().as_deref()),
+        )?;
+        client.transaction(&statements, "create index")?;
+        Ok(())
+    }
+
+    fn delete_index(name: IndexName) -> Result<(), SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = Self::create_client()?;
+        client.execute(&drop_statement(&name), "delete index")?;
+        Ok(())
+    }
+
+    fn list_indexes(page: PageRequest) -> Result<IndexPage, SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = Self::create_client()?;
+        let names = client
+            .execute(&list_statement(), "list indexes")?
+            .iter()
+            .filter_map(row_to_index_name)
+            .collect();
+        index_page(names, &page)
+    }
+
+    fn upsert(index: IndexName, doc: Doc) -> Result<(), SearchError> {
+        Self::upsert_many(index, vec![doc])
+    }
+
+    fn upsert_many(index: IndexName, docs: Vec<Doc>) -> Result<(), SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        if docs.is_empty() {
+            return Ok(());
+        }
+
+        let client = Self::create_client()?;
+        client.execute(&upsert_statement(&index, &docs)?, "upsert documents")?;
+        Ok(())
+    }
+
+    fn delete(index: IndexName, id: DocumentId) -> Result<(), SearchError> {
+        Self::delete_many(index, vec![id])
+    }
+
+    fn delete_many(index: IndexName, ids: Vec<DocumentId>) -> Result<(), SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        if ids.is_empty() {
+            return Ok(());
+        }
+
+        let client = Self::create_client()?;
+        client.execute(&delete_statement(&index, &ids), "delete documents")?;
+        Ok(())
+    }
+
+    fn get(index: IndexName, id: DocumentId) -> Result<Option<Doc>, SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = Self::create_client()?;
+        Ok(client
+            .execute(&get_statement(&index, &id), "get document")?
+            .first()
+            .map(row_to_doc))
+    }
+
+    fn search(index: IndexName, query: SearchQuery) -> Result<SearchResults, SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = Self::create_profile_client(search_profile(&query).as_deref())?
+            .with_request_timeout(search_timeout(&query))
+            .with_headers(search_headers(&query));
+
+        Self::search_page(&client, &index, &query)
+    }
+
+    fn stream_search(index: IndexName, query: SearchQuery) -> Result<SearchStream, SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = Self::create_profile_client(search_profile(&query).as_deref())?
+            .with_request_timeout(search_timeout(&query))
+            .with_headers(search_headers(&query));
+        let stream = PostgresSearchStream::new(client, index, query);
+        Ok(SearchStream::new(stream))
+    }
+
+    /// Returns the schema kept in the comment of the table of the index
+    fn get_schema(index: IndexName) -> Result<Schema, SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = Self::create_client()?;
+        let rows = client.execute(&schema_statement(&index), "get schema")?;
+        Ok(row_to_schema(rows.first()))
+    }
+
+    /// Regenerates the text search vectors of the documents from the text fields of the schema,
+    /// and indexes its sorted and faceted fields. The embedding column is kept as it is.
+    fn update_schema(index: IndexName, schema: Schema) -> Result<(), SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = Self::create_client()?;
+        let statements = update_schema_statements(
+            &index,
+            &schema,
+            &Self::table_options(worker_profile().as_deref()),
+        )?;
+        client.transaction(&statements, "update schema")?;
+        Ok(())
+    }
+}
+
+impl ExtendedGuest for PostgresComponent {
+    fn unwrapped_stream(index: IndexName, query: SearchQuery) -> Self::SearchStream {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = Self::create_profile_client(search_profile(&query).as_deref())
+            .unwrap_or_else(|_| PostgresApi::new("".to_string(), "".to_string()))
+            .with_headers(search_headers(&query));
+
+        PostgresSearchStream::new(client, index, query)
+    }
+
+    fn retry_query(original_query: &SearchQuery, partial_hits: &[SearchHit]) -> SearchQuery {
+        create_retry_query(original_query, partial_hits)
+    }
+
+    fn subscribe(stream: &Self::SearchStream) -> Pollable {
+        stream.subscribe()
+    }
+}
+
+type DurablePostgresComponent = DurableSearch<PostgresComponent>;
+
+golem_search::export_search!(DurablePostgresComponent with_types_in golem_search);
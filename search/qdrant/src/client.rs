@@ -0,0 +1,440 @@
+use golem_search::correlation::{self, CORRELATION_ID_HEADER};
+use golem_search::error::{from_reqwest_error, from_send_error, search_error_from_failed_response};
+use golem_search::golem::search::types::SearchError;
+use golem_search::logging::{redact_secret, RedactedResponse};
+use golem_search::retry::RetryPolicy;
+use log::trace;
+use reqwest::{Client, Method, RequestBuilder, Response};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map as JsonMap, Value as JsonValue};
+use std::collections::HashMap;
+use std::fmt::{Debug, Display};
+use std::time::Duration;
+
+#[derive(Clone)]
+pub struct QdrantApi {
+    client: Client,
+    base_url: String,
+    api_key: Option<String>,
+    retry_policy: RetryPolicy,
+    request_timeout: Option<Duration>,
+    headers: Vec<(String, String)>,
+}
+
+impl Debug for QdrantApi {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("QdrantApi")
+            .field("base_url", &self.base_url)
+            .field("api_key", &self.api_key.as_deref().map(redact_secret))
+            .field("retry_policy", &self.retry_policy)
+            .field("request_timeout", &self.request_timeout)
+            .finish()
+    }
+}
+
+/// Envelope of every Qdrant response
+#[derive(Debug, Deserialize)]
+pub struct QdrantResponse<T> {
+    pub result: T,
+    /// Server side processing time, in seconds
+    #[serde(default)]
+    pub time: Option<f64>,
+}
+
+/// Identifier of a point, either an unsigned integer or a UUID
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum PointId {
+    Num(u64),
+    Uuid(String),
+}
+
+impl Display for PointId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PointId::Num(id) => write!(f, "{id}"),
+            PointId::Uuid(id) => write!(f, "{id}"),
+        }
+    }
+}
+
+pub type Payload = JsonMap<String, JsonValue>;
+
+#[derive(Debug, Deserialize)]
+pub struct CollectionDescription {
+    pub name: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CollectionsList {
+    pub collections: Vec<CollectionDescription>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PayloadIndexInfo {
+    pub data_type: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CollectionInfo {
+    #[serde(default)]
+    pub payload_schema: HashMap<String, PayloadIndexInfo>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CreateCollectionRequest {
+    /// Named vectors of the collection, empty for payload-only collections
+    pub vectors: JsonMap<String, JsonValue>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CreateFieldIndexRequest {
+    pub field_name: String,
+    pub field_schema: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Point {
+    pub id: PointId,
+    pub vector: JsonMap<String, JsonValue>,
+    pub payload: Payload,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UpsertPointsRequest {
+    pub points: Vec<Point>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PointsSelector {
+    pub points: Vec<PointId>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Record {
+    pub id: PointId,
+    #[serde(default)]
+    pub payload: Option<Payload>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct OrderBy {
+    pub key: String,
+    pub direction: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ScrollRequest {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filter: Option<JsonValue>,
+    pub limit: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub order_by: Option<OrderBy>,
+    /// `true`, or the list of payload fields to return
+    pub with_payload: JsonValue,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ScrollResult {
+    pub points: Vec<Record>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CountRequest {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filter: Option<JsonValue>,
+    pub exact: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CountResult {
+    pub count: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FacetRequest {
+    pub key: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filter: Option<JsonValue>,
+    pub exact: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FacetHit {
+    pub value: JsonValue,
+    pub count: u64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FacetResult {
+    pub hits: Vec<FacetHit>,
+}
+
+impl QdrantApi {
+    pub fn new(base_url: String, api_key: Option<String>) -> Self {
+        let client = Client::builder()
+            .build()
+            .expect("Failed to initialize HTTP client");
+
+        Self {
+            client,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            api_key,
+            retry_policy: RetryPolicy::from_config(),
+            request_timeout: None,
+            headers: Vec::new(),
+        }
+    }
+
+    /// Applies a timeout to every request sent by this client
+    pub fn with_request_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.request_timeout = timeout;
+        self
+    }
+
+    /// Adds custom headers to every request sent by this client
+    pub fn with_headers(mut self, headers: Vec<(String, String)>) -> Self {
+        self.headers = headers;
+        self
+    }
+
+    fn create_request(&self, method: Method, url: &str) -> RequestBuilder {
+        trace!("[Qdrant] HTTP {method} {url}");
+
+        let mut req = self
+            .client
+            .request(method, url)
+            .header("Content-Type", "application/json")
+            .header(CORRELATION_ID_HEADER, correlation::current());
+
+        if let Some(api_key) = &self.api_key {
+            req = req.header("api-key", api_key);
+        }
+
+        if let Some(timeout) = self.request_timeout {
+            req = req.timeout(timeout);
+        }
+
+        for (name, value) in &self.headers {
+            req = req.header(name, value);
+        }
+
+        req
+    }
+
+    fn send<T: DeserializeOwned + Debug>(
+        &self,
+        method: Method,
+        url: &str,
+        body: Option<&impl Serialize>,
+        operation: &str,
+    ) -> Result<QdrantResponse<T>, SearchError> {
+        let response = self
+            .retry_policy
+            .execute(|| {
+                let req = self.create_request(method.clone(), url);
+                match body {
+                    Some(body) => req.json(body).send(),
+                    None => req.send(),
+                }
+            })
+            .map_err(|e| from_send_error(format!("Failed to {operation}"), e))?;
+
+        parse_response(response)
+    }
+}
+
+fn parse_response<T: DeserializeOwned + Debug>(
+    response: Response,
+) -> Result<QdrantResponse<T>, SearchError> {
+    let status = response.status();
+
+    trace!(
+        "Received response from Qdrant API: {:?}",
+        RedactedResponse(&response)
+    );
+
+    if status.is_success() {
+        let body = response
+            .json::<QdrantResponse<T>>()
+            .map_err(|err| from_reqwest_error("Failed to decode response body", err))?;
+
+        trace!("Received response from Qdrant API: {body:?}");
+
+        Ok(body)
+    } else {
+        let error = search_error_from_failed_response(response);
+
+        trace!("Received {status} response from Qdrant API: {error:?}");
+
+        Err(error)
+    }
+}
+
+impl QdrantApi {
+    pub fn list_collections(&self) -> Result<CollectionsList, SearchError> {
+        trace!("Listing collections");
+
+        let url = format!("{}/collections", self.base_url);
+        self.send(Method::GET, &url, None::<&()>, "list collections")
+            .map(|response| response.result)
+    }
+
+    pub fn create_collection(
+        &self,
+        name: &str,
+        request: &CreateCollectionRequest,
+    ) -> Result<(), SearchError> {
+        trace!("Creating collection: {name}");
+
+        let url = format!("{}/collections/{}", self.base_url, name);
+        let response = self
+            .create_request(Method::PUT, &url)
+            .json(request)
+            .send()
+            .map_err(|e| from_send_error("Failed to create collection", e))?;
+
+        parse_response::<bool>(response).map(|_| ())
+    }
+
+    pub fn delete_collection(&self, name: &str) -> Result<(), SearchError> {
+        trace!("Deleting collection: {name}");
+
+        let url = format!("{}/collections/{}", self.base_url, name);
+        self.send::<bool>(Method::DELETE, &url, None::<&()>, "delete collection")
+            .map(|_| ())
+    }
+
+    pub fn get_collection(&self, name: &str) -> Result<CollectionInfo, SearchError> {
+        trace!("Getting collection: {name}");
+
+        let url = format!("{}/collections/{}", self.base_url, name);
+        self.send(Method::GET, &url, None::<&()>, "get collection")
+            .map(|response| response.result)
+    }
+
+    pub fn create_field_index(
+        &self,
+        collection: &str,
+        request: &CreateFieldIndexRequest,
+    ) -> Result<(), SearchError> {
+        trace!(
+            "Creating {} index on {} in collection: {collection}",
+            request.field_schema,
+            request.field_name
+        );
+
+        let url = format!(
+            "{}/collections/{}/index?wait=true",
+            self.base_url, collection
+        );
+        self.send::<JsonValue>(Method::PUT, &url, Some(request), "create field index")
+            .map(|_| ())
+    }
+
+    pub fn delete_field_index(
+        &self,
+        collection: &str,
+        field_name: &str,
+    ) -> Result<(), SearchError> {
+        trace!("Deleting index on {field_name} in collection: {collection}");
+
+        let url = format!(
+            "{}/collections/{}/index/{}?wait=true",
+            self.base_url, collection, field_name
+        );
+        self.send::<JsonValue>(Method::DELETE, &url, None::<&()>, "delete field index")
+            .map(|_| ())
+    }
+
+    pub fn upsert_points(&self, collection: &str, points: Vec<Point>) -> Result<(), SearchError> {
+        trace!(
+            "Upserting {} points to collection: {collection}",
+            points.len()
+        );
+
+        let url = format!(
+            "{}/collections/{}/points?wait=true",
+            self.base_url, collection
+        );
+        let request = UpsertPointsRequest { points };
+        let response = self
+            .create_request(Method::PUT, &url)
+            .json(&request)
+            .send()
+            .map_err(|e| from_send_error("Failed to upsert points", e))?;
+
+        parse_response::<JsonValue>(response).map(|_| ())
+    }
+
+    pub fn delete_points(&self, collection: &str, ids: Vec<PointId>) -> Result<(), SearchError> {
+        trace!(
+            "Deleting {} points from collection: {collection}",
+            ids.len()
+        );
+
+        let url = format!(
+            "{}/collections/{}/points/delete?wait=true",
+            self.base_url, collection
+        );
+        let request = PointsSelector { points: ids };
+        self.send::<JsonValue>(Method::POST, &url, Some(&request), "delete points")
+            .map(|_| ())
+    }
+
+    pub fn get_point(&self, collection: &str, id: &PointId) -> Result<Option<Record>, SearchError> {
+        trace!("Getting point {id} from collection: {collection}");
+
+        let url = format!("{}/collections/{}/points/{}", self.base_url, collection, id);
+        let response = self
+            .retry_policy
+            .execute(|| self.create_request(Method::GET, &url).send())
+            .map_err(|e| from_send_error("Failed to get point", e))?;
+
+        if response.status() == 404 {
+            Ok(None)
+        } else {
+            Ok(Some(parse_response(response)?.result))
+        }
+    }
+
+    pub fn scroll(
+        &self,
+        collection: &str,
+        request: &ScrollRequest,
+    ) -> Result<QdrantResponse<ScrollResult>, SearchError> {
+        trace!("Scrolling points of collection: {collection}");
+
+        let url = format!("{}/collections/{}/points/scroll", self.base_url, collection);
+        let response = self
+            .retry_policy
+            .execute(|| self.create_request(Method::POST, &url).json(request).send())
+            .map_err(|e| from_send_error("Failed to search", e))?;
+
+        parse_response(response)
+    }
+
+    pub fn count(&self, collection: &str, request: &CountRequest) -> Result<u64, SearchError> {
+        trace!("Counting points of collection: {collection}");
+
+        let url = format!("{}/collections/{}/points/count", self.base_url, collection);
+        self.send::<CountResult>(Method::POST, &url, Some(request), "count points")
+            .map(|response| response.result.count)
+    }
+
+    pub fn facet(
+        &self,
+        collection: &str,
+        request: &FacetRequest,
+    ) -> Result<FacetResult, SearchError> {
+        trace!(
+            "Counting values of {} in collection: {collection}",
+            request.key
+        );
+
+        let url = format!("{}/collections/{}/facet", self.base_url, collection);
+        self.send(Method::POST, &url, Some(request), "get facet counts")
+            .map(|response| response.result)
+    }
+}
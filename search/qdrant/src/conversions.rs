@@ -0,0 +1,622 @@
+use crate::client::{
+    CollectionInfo, CreateFieldIndexRequest, OrderBy, Payload, Point, PointId, Record,
+    ScrollRequest,
+};
+use golem_search::config::{get_max_pagination_depth_config, search_filters_match_any};
+use golem_search::error::invalid_query;
+use golem_search::golem::search::types::{
+    Doc, FieldType, Schema, SchemaField, SearchError, SearchHit, SearchQuery,
+};
+use serde_json::{json, Map as JsonMap, Value as JsonValue};
+use uuid::Uuid;
+
+/// Payload field holding the document id, as Qdrant point ids can only be integers or UUIDs
+pub const DOC_ID_FIELD: &str = "_doc_id";
+
+const DEFAULT_PER_PAGE: u32 = 20;
+
+/// The point id of a document. Integer and UUID document ids are used as they are, other ids are
+/// mapped to a name-based UUID so that they can be looked up without a search.
+pub fn point_id(doc_id: &str) -> PointId {
+    if let Ok(id) = doc_id.parse::<u64>() {
+        PointId::Num(id)
+    } else if let Ok(id) = Uuid::parse_str(doc_id) {
+        PointId::Uuid(id.to_string())
+    } else {
+        PointId::Uuid(Uuid::new_v5(&Uuid::NAMESPACE_OID, doc_id.as_bytes()).to_string())
+    }
+}
+
+pub fn doc_to_point(doc: Doc) -> Result<Point, SearchError> {
+    let mut payload = match serde_json::from_str::<JsonValue>(&doc.content) {
+        Ok(JsonValue::Object(payload)) => payload,
+        Ok(_) => return Err(invalid_query("Document content must be a JSON object")),
+        Err(e) => return Err(invalid_query(format!("Invalid document content: {e}"))),
+    };
+    payload.insert(DOC_ID_FIELD.to_string(), JsonValue::String(doc.id.clone()));
+
+    Ok(Point {
+        id: point_id(&doc.id),
+        vector: JsonMap::new(),
+        payload,
+    })
+}
+
+fn split_payload(record: Record) -> (String, Payload) {
+    let mut payload = record.payload.unwrap_or_default();
+    let id = match payload.remove(DOC_ID_FIELD) {
+        Some(JsonValue::String(id)) => id,
+        _ => record.id.to_string(),
+    };
+    (id, payload)
+}
+
+pub fn record_to_doc(record: Record) -> Doc {
+    let (id, payload) = split_payload(record);
+    Doc {
+        id,
+        content: JsonValue::Object(payload).to_string(),
+    }
+}
+
+pub fn record_to_hit(record: Record) -> SearchHit {
+    let (id, payload) = split_payload(record);
+    SearchHit {
+        id,
+        score: None,
+        content: Some(JsonValue::Object(payload).to_string()),
+        highlights: None,
+    }
+}
+
+/// The payload index type of a field, `None` for fields that are neither searched, filtered nor
+/// sorted on. Qdrant supports one index per field, so text fields used as facets get a keyword
+/// index instead of a full-text one.
+fn field_schema(field: &SchemaField) -> Option<&'static str> {
+    if !(field.index || field.facet || field.sort) {
+        return None;
+    }
+    Some(match field.field_type {
+        FieldType::Text if field.index && !field.facet => "text",
+        FieldType::Text | FieldType::Keyword => "keyword",
+        FieldType::Integer => "integer",
+        FieldType::Float => "float",
+        FieldType::Boolean => "bool",
+        FieldType::Date => "datetime",
+        FieldType::GeoPoint => "geo",
+    })
+}
+
+pub fn schema_to_field_indexes(schema: &Schema) -> Vec<CreateFieldIndexRequest> {
+    schema
+        .fields
+        .iter()
+        .filter_map(|field| {
+            Some(CreateFieldIndexRequest {
+                field_name: field.name.clone(),
+                field_schema: field_schema(field)?.to_string(),
+            })
+        })
+        .collect()
+}
+
+pub fn collection_to_schema(collection: CollectionInfo) -> Schema {
+    let mut fields = collection
+        .payload_schema
+        .into_iter()
+        .map(|(name, index)| {
+            let (field_type, index, facet, sort) = match index.data_type.as_str() {
+                "text" => (FieldType::Text, true, false, false),
+                "integer" => (FieldType::Integer, false, true, true),
+                "float" => (FieldType::Float, false, true, true),
+                "bool" => (FieldType::Boolean, false, true, false),
+                "datetime" => (FieldType::Date, false, false, true),
+                "geo" => (FieldType::GeoPoint, false, false, false),
+                _ => (FieldType::Keyword, false, true, false),
+            };
+            SchemaField {
+                name,
+                field_type,
+                required: false,
+                facet,
+                sort,
+                index,
+            }
+        })
+        .collect::<Vec<_>>();
+    fields.sort_by(|a, b| a.name.cmp(&b.name));
+
+    Schema {
+        fields,
+        primary_key: None,
+    }
+}
+
+/// Names of the fields with a full-text index, searched by the query text
+pub fn text_fields(collection: &CollectionInfo) -> Vec<String> {
+    let mut fields = collection
+        .payload_schema
+        .iter()
+        .filter(|(_, index)| index.data_type == "text")
+        .map(|(name, _)| name.clone())
+        .collect::<Vec<_>>();
+    fields.sort();
+    fields
+}
+
+fn parse_value(value: &str) -> JsonValue {
+    let value = value.trim();
+    if let Some(quoted) = value
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+    {
+        return JsonValue::String(quoted.to_string());
+    }
+    match serde_json::from_str::<JsonValue>(value) {
+        Ok(value @ (JsonValue::Number(_) | JsonValue::Bool(_))) => value,
+        _ => JsonValue::String(value.to_string()),
+    }
+}
+
+/// Converts a filter to a Qdrant condition. Filters are either Qdrant conditions in JSON, or
+/// `field:value`, `field=value`, `field!=value`, and `field>value` style comparisons with `>`,
+/// `>=`, `<` and `<=`.
+fn filter_to_condition(filter: &str) -> Result<JsonValue, SearchError> {
+    let filter = filter.trim();
+    if filter.starts_with('{') {
+        return serde_json::from_str(filter)
+            .map_err(|e| invalid_query(format!("Invalid Qdrant filter `{filter}`: {e}")));
+    }
+
+    let position = filter
+        .find([':', '=', '!', '<', '>'])
+        .ok_or_else(|| invalid_query(format!("Unsupported filter `{filter}`")))?;
+    let key = filter[..position].trim();
+    let rest = &filter[position..];
+    let (op, value) = [">=", "<=", "!=", ">", "<", "=", ":"]
+        .into_iter()
+        .find_map(|op| Some((op, rest.strip_prefix(op)?)))
+        .ok_or_else(|| invalid_query(format!("Unsupported filter `{filter}`")))?;
+    if key.is_empty() || value.trim().is_empty() {
+        return Err(invalid_query(format!("Unsupported filter `{filter}`")));
+    }
+    let value = parse_value(value);
+
+    let range = match op {
+        ":" | "=" => return Ok(json!({ "key": key, "match": { "value": value } })),
+        "!=" => return Ok(json!({ "must_not": [{ "key": key, "match": { "value": value } }] })),
+        ">" => "gt",
+        ">=" => "gte",
+        "<" => "lt",
+        _ => "lte",
+    };
+    if !value.is_number() {
+        return Err(invalid_query(format!(
+            "Range filter `{filter}` needs a numeric value"
+        )));
+    }
+    Ok(json!({ "key": key, "range": { range: value } }))
+}
+
+/// Builds the Qdrant filter of a query. The query text matches the points where any of the
+/// full-text indexed `text_fields` contains it.
+pub fn query_to_filter(
+    query: &SearchQuery,
+    text_fields: &[String],
+) -> Result<Option<JsonValue>, SearchError> {
+    let mut must = Vec::new();
+
+    if let Some(q) = query.q.as_deref().map(str::trim).filter(|q| !q.is_empty()) {
+        if text_fields.is_empty() {
+            return Err(invalid_query(
+                "Full-text queries need a text index on at least one payload field",
+            ));
+        }
+        let should = text_fields
+            .iter()
+            .map(|field| json!({ "key": field, "match": { "text": q } }))
+            .collect::<Vec<_>>();
+        must.push(json!({ "should": should }));
+    }
+
+    let conditions = query
+        .filters
+        .iter()
+        .map(|filter| filter_to_condition(filter))
+        .collect::<Result<Vec<_>, _>>()?;
+    if search_filters_match_any(query) && !conditions.is_empty() {
+        must.push(json!({ "should": conditions }));
+    } else {
+        must.extend(conditions);
+    }
+
+    Ok((!must.is_empty()).then(|| json!({ "must": must })))
+}
+
+/// The ordering of a query, from its first `field`, `field:asc`, `field:desc` or `-field` sort
+/// entry. Qdrant orders scrolled points by a single field.
+fn sort_to_order_by(sort: &[String]) -> Result<Option<OrderBy>, SearchError> {
+    let Some(sort) = sort.first().map(|sort| sort.trim()) else {
+        return Ok(None);
+    };
+    let (key, direction) = if let Some(key) = sort.strip_prefix('-') {
+        (key, "desc")
+    } else {
+        match sort.rsplit_once(':') {
+            Some((key, direction)) if direction.eq_ignore_ascii_case("asc") => (key, "asc"),
+            Some((key, direction)) if direction.eq_ignore_ascii_case("desc") => (key, "desc"),
+            Some(_) => return Err(invalid_query(format!("Invalid sort `{sort}`"))),
+            None => (sort, "asc"),
+        }
+    };
+    Ok(Some(OrderBy {
+        key: key.to_string(),
+        direction: direction.to_string(),
+    }))
+}
+
+/// Number of hits to skip and number of hits per page of a query
+pub fn query_window(query: &SearchQuery) -> Result<(u32, u32), SearchError> {
+    let per_page = query.per_page.unwrap_or(DEFAULT_PER_PAGE);
+    let offset = query.offset.unwrap_or_else(|| {
+        query
+            .page
+            .unwrap_or(1)
+            .saturating_sub(1)
+            .saturating_mul(per_page)
+    });
+    if offset.saturating_add(per_page) > get_max_pagination_depth_config() {
+        return Err(invalid_query(format!(
+            "Requested hits beyond the maximum pagination depth of {}",
+            get_max_pagination_depth_config()
+        )));
+    }
+    Ok((offset, per_page))
+}
+
+/// Builds the scroll request of a query. Scrolling can't skip points when ordering by a field,
+/// so the request includes the `offset` skipped hits, which are dropped from the response.
+pub fn query_to_scroll_request(
+    query: &SearchQuery,
+    filter: Option<JsonValue>,
+) -> Result<ScrollRequest, SearchError> {
+    let (offset, per_page) = query_window(query)?;
+
+    let attributes = query
+        .config
+        .as_ref()
+        .map(|config| config.attributes_to_retrieve.clone())
+        .unwrap_or_default();
+    let with_payload = if attributes.is_empty() {
+        JsonValue::Bool(true)
+    } else {
+        let mut attributes = attributes;
+        attributes.push(DOC_ID_FIELD.to_string());
+        json!(attributes)
+    };
+
+    Ok(ScrollRequest {
+        filter,
+        limit: offset + per_page,
+        order_by: sort_to_order_by(&query.sort)?,
+        with_payload,
+    })
+}
+
+/// Facet counts in the same shape as the other providers: `{"field": {"value": count}}`
+pub fn facet_counts(facets: Vec<(String, Vec<(JsonValue, u64)>)>) -> Option<String> {
+    if facets.is_empty() {
+        return None;
+    }
+    let facets = facets
+        .into_iter()
+        .map(|(field, hits)| {
+            let counts = hits
+                .into_iter()
+                .map(|(value, count)| {
+                    let value = match value {
+                        JsonValue::String(value) => value,
+                        value => value.to_string(),
+                    };
+                    (value, JsonValue::from(count))
+                })
+                .collect::<JsonMap<_, _>>();
+            (field, JsonValue::Object(counts))
+        })
+        .collect::<JsonMap<_, _>>();
+    Some(JsonValue::Object(facets).to_string())
+}
+
+pub fn create_retry_query(original_query: &SearchQuery, partial_hits: &[SearchHit]) -> SearchQuery {
+    let mut retry_query = original_query.clone();
+
+    let current_offset = original_query.offset.unwrap_or(0);
+    let per_page = original_query.per_page.unwrap_or(DEFAULT_PER_PAGE);
+    let hits_received = partial_hits.len() as u32;
+
+    retry_query.offset = Some(current_offset + hits_received.min(per_page));
+
+    retry_query
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::PayloadIndexInfo;
+    use golem_search::golem::search::types::{FilterMode, SearchConfig};
+    use std::collections::HashMap;
+
+    fn query(q: Option<&str>, filters: &[&str]) -> SearchQuery {
+        SearchQuery {
+            q: q.map(str::to_string),
+            filters: filters.iter().map(|filter| filter.to_string()).collect(),
+            sort: vec![],
+            facets: vec![],
+            page: None,
+            per_page: None,
+            offset: None,
+            highlight: None,
+            config: None,
+        }
+    }
+
+    fn field(name: &str, field_type: FieldType, facet: bool, sort: bool) -> SchemaField {
+        SchemaField {
+            name: name.to_string(),
+            field_type,
+            required: false,
+            facet,
+            sort,
+            index: true,
+        }
+    }
+
+    #[test]
+    fn test_point_ids() {
+        assert_eq!(point_id("42"), PointId::Num(42));
+        assert_eq!(
+            point_id("936DA01F-9ABD-4D9D-80C7-02AF85C822A8"),
+            PointId::Uuid("936da01f-9abd-4d9d-80c7-02af85c822a8".to_string())
+        );
+        assert_eq!(point_id("doc1"), point_id("doc1"));
+        assert_ne!(point_id("doc1"), point_id("doc2"));
+    }
+
+    #[test]
+    fn test_doc_to_point_and_back() {
+        let doc = Doc {
+            id: "doc1".to_string(),
+            content: r#"{"title": "Dune"}"#.to_string(),
+        };
+        let point = doc_to_point(doc.clone()).unwrap();
+        assert_eq!(point.payload.get(DOC_ID_FIELD).unwrap(), "doc1");
+
+        let record = Record {
+            id: point.id,
+            payload: Some(point.payload),
+        };
+        assert_eq!(record_to_doc(record), doc);
+    }
+
+    #[test]
+    fn test_doc_to_point_rejects_invalid_content() {
+        let doc = Doc {
+            id: "doc1".to_string(),
+            content: "[1, 2]".to_string(),
+        };
+        assert!(matches!(
+            doc_to_point(doc),
+            Err(SearchError::InvalidQuery(_))
+        ));
+    }
+
+    #[test]
+    fn test_record_without_doc_id_uses_point_id() {
+        let record = Record {
+            id: PointId::Num(7),
+            payload: None,
+        };
+        let hit = record_to_hit(record);
+        assert_eq!(hit.id, "7");
+        assert_eq!(hit.content.as_deref(), Some("{}"));
+    }
+
+    #[test]
+    fn test_schema_to_field_indexes() {
+        let schema = Schema {
+            fields: vec![
+                field("title", FieldType::Text, false, false),
+                field("genre", FieldType::Text, true, false),
+                field("year", FieldType::Integer, false, true),
+                SchemaField {
+                    index: false,
+                    ..field("notes", FieldType::Text, false, false)
+                },
+            ],
+            primary_key: Some("id".to_string()),
+        };
+
+        let indexes = schema_to_field_indexes(&schema)
+            .into_iter()
+            .map(|index| (index.field_name, index.field_schema))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            indexes,
+            vec![
+                ("title".to_string(), "text".to_string()),
+                ("genre".to_string(), "keyword".to_string()),
+                ("year".to_string(), "integer".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_collection_to_schema() {
+        let collection = CollectionInfo {
+            payload_schema: HashMap::from([
+                (
+                    "title".to_string(),
+                    PayloadIndexInfo {
+                        data_type: "text".to_string(),
+                    },
+                ),
+                (
+                    "year".to_string(),
+                    PayloadIndexInfo {
+                        data_type: "integer".to_string(),
+                    },
+                ),
+            ]),
+        };
+
+        assert_eq!(text_fields(&collection), vec!["title".to_string()]);
+        let schema = collection_to_schema(collection);
+        assert_eq!(schema.fields.len(), 2);
+        assert_eq!(schema.fields[0].name, "title");
+        assert!(schema.fields[0].index);
+        assert_eq!(schema.fields[1].field_type, FieldType::Integer);
+        assert!(schema.fields[1].sort);
+    }
+
+    #[test]
+    fn test_filters_to_conditions() {
+        assert_eq!(
+            filter_to_condition("genre:fiction").unwrap(),
+            json!({ "key": "genre", "match": { "value": "fiction" } })
+        );
+        assert_eq!(
+            filter_to_condition("genre = \"science fiction\"").unwrap(),
+            json!({ "key": "genre", "match": { "value": "science fiction" } })
+        );
+        assert_eq!(
+            filter_to_condition("in_stock:true").unwrap(),
+            json!({ "key": "in_stock", "match": { "value": true } })
+        );
+        assert_eq!(
+            filter_to_condition("year>=1950").unwrap(),
+            json!({ "key": "year", "range": { "gte": 1950 } })
+        );
+        assert_eq!(
+            filter_to_condition("genre!=romance").unwrap(),
+            json!({ "must_not": [{ "key": "genre", "match": { "value": "romance" } }] })
+        );
+        assert_eq!(
+            filter_to_condition(r#"{"key": "year", "range": {"lt": 2000}}"#).unwrap(),
+            json!({ "key": "year", "range": { "lt": 2000 } })
+        );
+        assert!(filter_to_condition("year>recent").is_err());
+        assert!(filter_to_condition("no operator").is_err());
+    }
+
+    #[test]
+    fn test_query_to_filter() {
+        let text_fields = vec!["description".to_string(), "title".to_string()];
+        let filter = query_to_filter(&query(Some("gatsby"), &["genre:fiction"]), &text_fields)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            filter,
+            json!({ "must": [
+                { "should": [
+                    { "key": "description", "match": { "text": "gatsby" } },
+                    { "key": "title", "match": { "text": "gatsby" } },
+                ] },
+                { "key": "genre", "match": { "value": "fiction" } },
+            ] })
+        );
+
+        assert_eq!(query_to_filter(&query(Some(" "), &[]), &[]).unwrap(), None);
+        assert!(matches!(
+            query_to_filter(&query(Some("gatsby"), &[]), &[]),
+            Err(SearchError::InvalidQuery(_))
+        ));
+    }
+
+    #[test]
+    fn test_query_to_filter_matching_any() {
+        let mut query = query(None, &["genre:fiction", "genre:romance"]);
+        query.config = Some(SearchConfig {
+            timeout_ms: None,
+            boost_fields: vec![],
+            attributes_to_retrieve: vec![],
+            language: None,
+            typo_tolerance: None,
+            exact_match_boost: None,
+            provider_params: None,
+            profile: None,
+            filter_mode: Some(FilterMode::Any),
+            post_filters: vec![],
+            rescore: None,
+            fallbacks: vec![],
+        });
+
+        assert_eq!(
+            query_to_filter(&query, &[]).unwrap().unwrap(),
+            json!({ "must": [{ "should": [
+                { "key": "genre", "match": { "value": "fiction" } },
+                { "key": "genre", "match": { "value": "romance" } },
+            ] }] })
+        );
+    }
+
+    #[test]
+    fn test_query_to_scroll_request() {
+        let mut query = query(None, &[]);
+        query.sort = vec!["year:desc".to_string()];
+        query.page = Some(2);
+        query.per_page = Some(5);
+
+        let request = query_to_scroll_request(&query, None).unwrap();
+        assert_eq!(request.limit, 10);
+        assert_eq!(
+            request.order_by,
+            Some(OrderBy {
+                key: "year".to_string(),
+                direction: "desc".to_string(),
+            })
+        );
+        assert_eq!(request.with_payload, JsonValue::Bool(true));
+        assert_eq!(query_window(&query).unwrap(), (5, 5));
+    }
+
+    #[test]
+    fn test_sort_to_order_by() {
+        let order_by = |sort: &str| sort_to_order_by(&[sort.to_string()]).unwrap().unwrap();
+        assert_eq!(order_by("-year").direction, "desc");
+        assert_eq!(order_by("year").direction, "asc");
+        assert_eq!(order_by("year:ASC").direction, "asc");
+        assert!(sort_to_order_by(&["year:sideways".to_string()]).is_err());
+        assert_eq!(sort_to_order_by(&[]).unwrap(), None);
+    }
+
+    #[test]
+    fn test_facet_counts() {
+        let facets = facet_counts(vec![(
+            "genre".to_string(),
+            vec![(json!("fiction"), 3), (json!(1925), 1)],
+        )])
+        .unwrap();
+        assert_eq!(
+            serde_json::from_str::<JsonValue>(&facets).unwrap(),
+            json!({ "genre": { "fiction": 3, "1925": 1 } })
+        );
+        assert_eq!(facet_counts(vec![]), None);
+    }
+
+    #[test]
+    fn test_create_retry_query() {
+        let mut original_query = query(Some("test"), &[]);
+        original_query.per_page = Some(10);
+        original_query.offset = Some(20);
+
+        let partial_hits = vec![SearchHit {
+            id: "doc1".to_string(),
+            score: None,
+            content: None,
+            highlights: None,
+        }];
+
+        let retry_query = create_retry_query(&original_query, &partial_hits);
+        assert_eq!(retry_query.offset, Some(21));
+    }
+}
@@ -0,0 +1,370 @@
+use crate::client::{CountRequest, CreateCollectionRequest, FacetRequest, QdrantApi};
+use crate::conversions::{
+    collection_to_schema, create_retry_query, doc_to_point, facet_counts, point_id,
+    query_to_filter, query_to_scroll_request, query_window, record_to_doc, record_to_hit,
+    schema_to_field_indexes, text_fields,
+};
+use golem_rust::wasm_rpc::Pollable;
+use golem_search::config::{
+    get_optional_config, profile_key, search_headers, search_profile, search_timeout,
+    with_profile_config_keys, worker_headers, worker_profile,
+};
+use golem_search::durability::{DurableSearch, ExtendedGuest};
+use golem_search::golem::search::core::{Guest, GuestSearchStream, SearchStream};
+use golem_search::golem::search::types::{
+    Doc, DocumentId, IndexName, IndexPage, PageRequest, Schema, SearchError, SearchHit,
+    SearchQuery, SearchResults,
+};
+use golem_search::pagination::index_page;
+use golem_search::LOGGING_STATE;
+use serde_json::Map as JsonMap;
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+
+mod client;
+mod conversions;
+
+/// Search stream over the pages of a query, as Qdrant has no native streaming
+struct QdrantSearchStream {
+    client: QdrantApi,
+    index_name: String,
+    query: SearchQuery,
+    offset: Cell<u32>,
+    finished: Cell<bool>,
+}
+
+impl QdrantSearchStream {
+    pub fn new(client: QdrantApi, index_name: String, query: SearchQuery) -> Self {
+        let offset = query_window(&query).map(|(offset, _)| offset).unwrap_or(0);
+        Self {
+            client,
+            index_name,
+            query,
+            offset: Cell::new(offset),
+            finished: Cell::new(false),
+        }
+    }
+
+    pub fn subscribe(&self) -> Pollable {
+        golem_rust::bindings::wasi::clocks::monotonic_clock::subscribe_duration(0)
+    }
+}
+
+impl GuestSearchStream for QdrantSearchStream {
+    fn get_next(&self) -> Option<Vec<SearchHit>> {
+        if self.finished.get() {
+            return Some(vec![]);
+        }
+
+        let mut query = self.query.clone();
+        query.page = None;
+        query.offset = Some(self.offset.get());
+        let per_page = query_window(&query)
+            .map(|(_, per_page)| per_page)
+            .unwrap_or(0);
+
+        match QdrantComponent::search_page(&self.client, &self.index_name, &query, false) {
+            Ok(results) => {
+                let received = results.hits.len() as u32;
+                if received < per_page || received == 0 {
+                    self.finished.set(true);
+                }
+                self.offset.set(self.offset.get() + received);
+                Some(results.hits)
+            }
+            Err(_) => {
+                self.finished.set(true);
+                Some(vec![])
+            }
+        }
+    }
+
+    fn blocking_get_next(&self) -> Vec<SearchHit> {
+        self.get_next().unwrap_or_default()
+    }
+}
+
+struct QdrantComponent;
+
+thread_local! {
+    /// The provider clients by configuration profile, reused across calls instead of being rebuilt
+    /// for each of them
+    static CLIENTS: RefCell<HashMap<Option<String>, QdrantApi>> = RefCell::new(HashMap::new());
+}
+
+impl QdrantComponent {
+    const BASE_URL_ENV_VAR: &'static str = "QDRANT_BASE_URL";
+    const API_KEY_ENV_VAR: &'static str = "QDRANT_API_KEY";
+
+    fn create_client() -> Result<QdrantApi, SearchError> {
+        Self::create_profile_client(worker_profile().as_deref())
+            .map(|client| client.with_headers(worker_headers()))
+    }
+
+    /// Returns the client of `profile` cached for this worker, creating it from the configuration
+    /// on first use
+    fn create_profile_client(profile: Option<&str>) -> Result<QdrantApi, SearchError> {
+        CLIENTS.with_borrow_mut(|clients| {
+            let profile = profile.map(str::to_string);
+            if let Some(client) = clients.get(&profile) {
+                return Ok(client.clone());
+            }
+
+            let client = Self::new_client(profile.as_deref())?;
+            clients.insert(profile, client.clone());
+            Ok(client)
+        })
+    }
+
+    fn new_client(profile: Option<&str>) -> Result<QdrantApi, SearchError> {
+        with_profile_config_keys(profile, &[Self::BASE_URL_ENV_VAR], |keys| {
+            if keys.is_empty() {
+                return Err(SearchError::Internal("Missing Qdrant base URL".to_string()));
+            }
+
+            let base_url = keys[0].clone();
+
+            let api_key = get_optional_config(profile_key(Self::API_KEY_ENV_VAR, profile));
+
+            Ok(QdrantApi::new(base_url, api_key))
+        })
+    }
+
+    /// Runs a query as a filtered scroll over the collection. The total number of hits and the
+    /// facet counts take extra requests, so they are only fetched when `with_totals` is set.
+    fn search_page(
+        client: &QdrantApi,
+        index: &str,
+        query: &SearchQuery,
+        with_totals: bool,
+    ) -> Result<SearchResults, SearchError> {
+        let has_text = query.q.as_deref().is_some_and(|q| !q.trim().is_empty());
+        let text_fields = if has_text {
+            text_fields(&client.get_collection(index)?)
+        } else {
+            vec![]
+        };
+        let filter = query_to_filter(query, &text_fields)?;
+        let (offset, per_page) = query_window(query)?;
+
+        let request = query_to_scroll_request(query, filter.clone())?;
+        let response = client.scroll(index, &request)?;
+        let hits = response
+            .result
+            .points
+            .into_iter()
+            .skip(offset as usize)
+            .map(record_to_hit)
+            .collect();
+
+        let (total, facets) = if with_totals {
+            let total = client.count(
+                index,
+                &CountRequest {
+                    filter: filter.clone(),
+                    exact: true,
+                },
+            )?;
+            let mut facets = Vec::new();
+            for key in &query.facets {
+                let result = client.facet(
+                    index,
+                    &FacetRequest {
+                        key: key.clone(),
+                        filter: filter.clone(),
+                        exact: true,
+                    },
+                )?;
+                let counts = result
+                    .hits
+                    .into_iter()
+                    .map(|hit| (hit.value, hit.count))
+                    .collect();
+                facets.push((key.clone(), counts));
+            }
+            (Some(total as u32), facet_counts(facets))
+        } else {
+            (None, None)
+        };
+
+        Ok(SearchResults {
+            total,
+            page: Some(offset / per_page.max(1) + 1),
+            per_page: Some(per_page),
+            hits,
+            facets,
+            took_ms: response.time.map(|time| (time * 1000.0) as u32),
+            relaxation: None,
+            navigation: None,
+        })
+    }
+
+    /// Makes the payload indexes of the collection match the schema, dropping the indexes of the
+    /// fields that are no longer part of it
+    fn apply_schema(client: &QdrantApi, index: &str, schema: &Schema) -> Result<(), SearchError> {
+        let existing = client.get_collection(index)?.payload_schema;
+        let wanted = schema_to_field_indexes(schema);
+
+        for (field_name, info) in &existing {
+            let keep = wanted.iter().any(|field| {
+                &field.field_name == field_name && field.field_schema == info.data_type
+            });
+            if !keep {
+                client.delete_field_index(index, field_name)?;
+            }
+        }
+
+        for field in wanted {
+            let exists = existing
+                .get(&field.field_name)
+                .is_some_and(|info| info.data_type == field.field_schema);
+            if !exists {
+                client.create_field_index(index, &field)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Guest for QdrantComponent {
+    type SearchStream = QdrantSearchStream;
+
+    fn create_index(name: IndexName, schema: Option<Schema>) -> Result<(), SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = Self::create_client()?;
+
+        client.create_collection(
+            &name,
+            &CreateCollectionRequest {
+                vectors: JsonMap::new(),
+            },
+        )?;
+
+        if let Some(schema) = schema {
+            Self::apply_schema(&client, &name, &schema)?;
+        }
+
+        Ok(())
+    }
+
+    fn delete_index(name: IndexName) -> Result<(), SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = Self::create_client()?;
+        client.delete_collection(&name)
+    }
+
+    fn list_indexes(page: PageRequest) -> Result<IndexPage, SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = Self::create_client()?;
+
+        let response = client.list_collections()?;
+        index_page(
+            response
+                .collections
+                .into_iter()
+                .map(|collection| collection.name)
+                .collect(),
+            &page,
+        )
+    }
+
+    fn upsert(index: IndexName, doc: Doc) -> Result<(), SearchError> {
+        Self::upsert_many(index, vec![doc])
+    }
+
+    fn upsert_many(index: IndexName, docs: Vec<Doc>) -> Result<(), SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = Self::create_client()?;
+        let points = docs
+            .into_iter()
+            .map(doc_to_point)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        client.upsert_points(&index, points)
+    }
+
+    fn delete(index: IndexName, id: DocumentId) -> Result<(), SearchError> {
+        Self::delete_many(index, vec![id])
+    }
+
+    fn delete_many(index: IndexName, ids: Vec<DocumentId>) -> Result<(), SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = Self::create_client()?;
+        let ids = ids.iter().map(|id| point_id(id)).collect();
+
+        client.delete_points(&index, ids)
+    }
+
+    fn get(index: IndexName, id: DocumentId) -> Result<Option<Doc>, SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = Self::create_client()?;
+
+        Ok(client.get_point(&index, &point_id(&id))?.map(record_to_doc))
+    }
+
+    fn search(index: IndexName, query: SearchQuery) -> Result<SearchResults, SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = Self::create_profile_client(search_profile(&query).as_deref())?
+            .with_request_timeout(search_timeout(&query))
+            .with_headers(search_headers(&query));
+
+        Self::search_page(&client, &index, &query, true)
+    }
+
+    fn stream_search(index: IndexName, query: SearchQuery) -> Result<SearchStream, SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = Self::create_profile_client(search_profile(&query).as_deref())?
+            .with_request_timeout(search_timeout(&query))
+            .with_headers(search_headers(&query));
+        let stream = QdrantSearchStream::new(client, index, query);
+        Ok(SearchStream::new(stream))
+    }
+
+    fn get_schema(index: IndexName) -> Result<Schema, SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = Self::create_client()?;
+
+        Ok(collection_to_schema(client.get_collection(&index)?))
+    }
+
+    fn update_schema(index: IndexName, schema: Schema) -> Result<(), SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = Self::create_client()?;
+        Self::apply_schema(&client, &index, &schema)
+    }
+}
+
+impl ExtendedGuest for QdrantComponent {
+    fn unwrapped_stream(index: IndexName, query: SearchQuery) -> Self::SearchStream {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = Self::create_profile_client(search_profile(&query).as_deref())
+            .unwrap_or_else(|_| QdrantApi::new("http://localhost:6333".to_string(), None))
+            .with_headers(search_headers(&query));
+
+        QdrantSearchStream::new(client, index, query)
+    }
+
+    fn retry_query(original_query: &SearchQuery, partial_hits: &[SearchHit]) -> SearchQuery {
+        create_retry_query(original_query, partial_hits)
+    }
+
+    fn subscribe(stream: &Self::SearchStream) -> Pollable {
+        stream.subscribe()
+    }
+}
+
+type DurableQdrantComponent = DurableSearch<QdrantComponent>;
+
+golem_search::export_search!(DurableQdrantComponent with_types_in golem_search);
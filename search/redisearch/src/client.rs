@@ -0,0 +1,308 @@
+use golem_search::correlation::{self, CORRELATION_ID_HEADER};
+use golem_search::error::{
+    from_reqwest_error, from_send_error, index_not_found, invalid_query,
+    search_error_from_failed_response,
+};
+use golem_search::golem::search::types::SearchError;
+use golem_search::logging::{redact_secret, RedactedResponse};
+use golem_search::retry::RetryPolicy;
+use log::trace;
+use reqwest::{Client, Method, RequestBuilder, Response};
+use serde::Deserialize;
+use serde_json::Value as JsonValue;
+use std::fmt::Debug;
+use std::time::Duration;
+
+/// The Redis client, sending commands to a REST gateway in the format of the Upstash REST API:
+/// a command is posted as a JSON array of its arguments, and its reply is returned as
+/// `{"result": ...}` or `{"error": "..."}`. Self-hosted Redis Stack servers can be reached
+/// through a gateway speaking the same protocol, such as `serverless-redis-http`.
+#[derive(Clone)]
+pub struct RediSearchApi {
+    client: Client,
+    url: String,
+    token: Option<String>,
+    retry_policy: RetryPolicy,
+    request_timeout: Option<Duration>,
+    headers: Vec<(String, String)>,
+}
+
+impl Debug for RediSearchApi {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RediSearchApi")
+            .field("url", &self.url)
+            .field("token", &self.token.as_deref().map(redact_secret))
+            .field("retry_policy", &self.retry_policy)
+            .field("request_timeout", &self.request_timeout)
+            .finish()
+    }
+}
+
+/// The reply of a command
+#[derive(Debug, Deserialize)]
+pub struct CommandReply {
+    #[serde(default)]
+    pub result: Option<JsonValue>,
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+impl CommandReply {
+    fn into_result(self) -> Result<JsonValue, SearchError> {
+        match self.error {
+            Some(error) => Err(command_error(error)),
+            None => Ok(self.result.unwrap_or(JsonValue::Null)),
+        }
+    }
+}
+
+/// The error of a command Redis rejected
+fn command_error(message: String) -> SearchError {
+    let lowercase = message.to_lowercase();
+    if lowercase.contains("unknown index name") || lowercase.contains("no such index") {
+        index_not_found()
+    } else {
+        invalid_query(message)
+    }
+}
+
+/// Maps the errors of failed responses about unknown indexes to `SearchError::IndexNotFound`
+fn map_index_error(error: SearchError) -> SearchError {
+    match error {
+        SearchError::InvalidQuery(message) => command_error(message),
+        error => error,
+    }
+}
+
+impl RediSearchApi {
+    pub fn new(url: String, token: Option<String>) -> Self {
+        let client = Client::builder()
+            .build()
+            .expect("Failed to initialize HTTP client");
+
+        Self {
+            client,
+            url: url.trim_end_matches('/').to_string(),
+            token,
+            retry_policy: RetryPolicy::from_config(),
+            request_timeout: None,
+            headers: Vec::new(),
+        }
+    }
+
+    /// Applies a timeout to every request sent by this client
+    pub fn with_request_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.request_timeout = timeout;
+        self
+    }
+
+    /// Adds custom headers to every request sent by this client
+    pub fn with_headers(mut self, headers: Vec<(String, String)>) -> Self {
+        self.headers = headers;
+        self
+    }
+
+    fn create_request(&self, method: Method, url: &str) -> RequestBuilder {
+        trace!("[RediSearch] HTTP {method} {url}");
+
+        let mut req = self
+            .client
+            .request(method, url)
+            .header("Content-Type", "application/json")
+            .header(CORRELATION_ID_HEADER, correlation::current());
+
+        if let Some(token) = &self.token {
+            req = req.bearer_auth(token);
+        }
+
+        if let Some(timeout) = self.request_timeout {
+            req = req.timeout(timeout);
+        }
+
+        for (name, value) in &self.headers {
+            req = req.header(name, value);
+        }
+
+        req
+    }
+
+    /// Runs a command, returning its reply
+    fn command(&self, args: &[String], operation: &str) -> Result<JsonValue, SearchError> {
+        trace!(
+            "Running command: {}",
+            args.first().map_or("", String::as_str)
+        );
+
+        let response = self
+            .retry_policy
+            .execute(|| {
+                self.create_request(Method::POST, &self.url)
+                    .json(args)
+                    .send()
+            })
+            .map_err(|e| from_send_error(format!("Failed to {operation}"), e))?;
+
+        parse_response::<CommandReply>(response)?.into_result()
+    }
+
+    /// Runs a search command, mapping requests that ran out of time to `SearchError::Timeout`
+    fn search_command(&self, args: &[String]) -> Result<JsonValue, SearchError> {
+        trace!("Running search command: {args:?}");
+
+        let response = self
+            .retry_policy
+            .execute(|| {
+                self.create_request(Method::POST, &self.url)
+                    .json(args)
+                    .send()
+            })
+            .map_err(|e| from_send_error("Failed to search", e))?;
+
+        parse_response::<CommandReply>(response)?.into_result()
+    }
+
+    /// Runs commands in a single request, failing with the first error among their replies
+    fn pipeline(&self, commands: &[Vec<String>], operation: &str) -> Result<(), SearchError> {
+        trace!("Running {} commands", commands.len());
+
+        let url = format!("{}/pipeline", self.url);
+        let response = self
+            .create_request(Method::POST, &url)
+            .json(commands)
+            .send()
+            .map_err(|e| from_send_error(format!("Failed to {operation}"), e))?;
+
+        for reply in parse_response::<Vec<CommandReply>>(response)? {
+            reply.into_result()?;
+        }
+        Ok(())
+    }
+}
+
+fn parse_response<T: serde::de::DeserializeOwned + Debug>(
+    response: Response,
+) -> Result<T, SearchError> {
+    let status = response.status();
+
+    trace!(
+        "Received response from RediSearch API: {:?}",
+        RedactedResponse(&response)
+    );
+
+    if status.is_success() {
+        let body = response
+            .json::<T>()
+            .map_err(|err| from_reqwest_error("Failed to decode response body", err))?;
+
+        trace!("Received response from RediSearch API: {body:?}");
+
+        Ok(body)
+    } else {
+        let error = map_index_error(search_error_from_failed_response(response));
+
+        trace!("Received {status} response from RediSearch API: {error:?}");
+
+        Err(error)
+    }
+}
+
+fn args<const N: usize>(args: [&str; N]) -> Vec<String> {
+    args.into_iter().map(str::to_string).collect()
+}
+
+impl RediSearchApi {
+    /// Runs an `FT.CREATE` command
+    pub fn create_index(&self, create: Vec<String>) -> Result<(), SearchError> {
+        self.command(&create, "create index").map(|_| ())
+    }
+
+    /// Drops an index along with its documents
+    pub fn drop_index(&self, index: &str) -> Result<(), SearchError> {
+        trace!("Dropping index: {index}");
+
+        self.command(&args(["FT.DROPINDEX", index, "DD"]), "drop index")
+            .map(|_| ())
+    }
+
+    pub fn list_indexes(&self) -> Result<Vec<String>, SearchError> {
+        trace!("Listing indexes");
+
+        let reply = self.command(&args(["FT._LIST"]), "list indexes")?;
+        Ok(reply
+            .as_array()
+            .map(|names| {
+                names
+                    .iter()
+                    .filter_map(|name| name.as_str().map(str::to_string))
+                    .collect()
+            })
+            .unwrap_or_default())
+    }
+
+    /// Returns the `FT.INFO` reply of an index
+    pub fn info(&self, index: &str) -> Result<JsonValue, SearchError> {
+        trace!("Getting info of index: {index}");
+
+        self.command(&args(["FT.INFO", index]), "get index info")
+    }
+
+    /// Adds attributes to an index, given as `FT.ALTER` schema arguments
+    pub fn add_attributes(&self, index: &str, attributes: Vec<String>) -> Result<(), SearchError> {
+        trace!("Adding attributes to index: {index}");
+
+        let mut alter = args(["FT.ALTER", index, "SCHEMA", "ADD"]);
+        alter.extend(attributes);
+        self.command(&alter, "alter index").map(|_| ())
+    }
+
+    /// Stores JSON documents under their keys
+    pub fn set_documents(&self, documents: Vec<(String, String)>) -> Result<(), SearchError> {
+        trace!("Storing {} documents", documents.len());
+
+        let commands = documents
+            .into_iter()
+            .map(|(key, json)| vec!["JSON.SET".to_string(), key, "$".to_string(), json])
+            .collect::<Vec<_>>();
+        self.pipeline(&commands, "store documents")
+    }
+
+    pub fn delete_keys(&self, keys: Vec<String>) -> Result<(), SearchError> {
+        trace!("Deleting {} keys", keys.len());
+
+        let mut del = args(["DEL"]);
+        del.extend(keys);
+        self.command(&del, "delete documents").map(|_| ())
+    }
+
+    /// Gets the serialized JSON document stored under a key
+    pub fn get_document(&self, key: &str) -> Result<Option<String>, SearchError> {
+        trace!("Getting document: {key}");
+
+        let reply = self.command(&args(["JSON.GET", key]), "get document")?;
+        Ok(reply.as_str().map(str::to_string))
+    }
+
+    /// Runs an `FT.SEARCH` or `FT.AGGREGATE` command
+    pub fn search(&self, command: Vec<String>) -> Result<JsonValue, SearchError> {
+        self.search_command(&command)
+    }
+
+    /// Reads the next rows of an aggregation cursor
+    pub fn read_cursor(
+        &self,
+        index: &str,
+        cursor: u64,
+        count: u32,
+    ) -> Result<JsonValue, SearchError> {
+        let cursor = cursor.to_string();
+        let count = count.to_string();
+        self.search_command(&args([
+            "FT.CURSOR",
+            "READ",
+            index,
+            &cursor,
+            "COUNT",
+            &count,
+        ]))
+    }
+}
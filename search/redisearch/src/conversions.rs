@@ -0,0 +1,1005 @@
+use golem_search::config::{get_max_pagination_depth_config, search_filters_match_any};
+use golem_search::error::{invalid_query, unsupported};
+use golem_search::golem::search::types::{
+    Doc, FieldType, Schema, SchemaField, SearchError, SearchHit, SearchQuery,
+};
+use serde_json::{Map as JsonMap, Value as JsonValue};
+use std::collections::HashMap;
+
+const DEFAULT_PER_PAGE: u32 = 20;
+
+/// Maximum number of values counted per facet
+const MAX_FACET_VALUES: u32 = 100;
+
+/// The query dialect of every search. Dialect 2 allows purely negative queries and `$` loads.
+const DIALECT: &str = "2";
+
+/// The alias of the document counts of facet aggregations
+const COUNT_ALIAS: &str = "__count";
+
+/// The attributes of an index by name, with their types
+pub type Attributes = HashMap<String, Attribute>;
+
+/// An attribute of an index, indexing the JSON field of the same name
+#[derive(Debug, Clone, PartialEq)]
+pub struct Attribute {
+    pub name: String,
+    pub r#type: String,
+    pub sortable: bool,
+    pub indexed: bool,
+}
+
+/// The key of a document. Documents of an index are stored as JSON under keys prefixed with the
+/// index name, which the index covers with `PREFIX`.
+pub fn document_key(index: &str, id: &str) -> String {
+    format!("{index}:{id}")
+}
+
+fn key_prefix(index: &str) -> String {
+    format!("{index}:")
+}
+
+fn key_to_id(index: &str, key: &str) -> String {
+    key.strip_prefix(&key_prefix(index))
+        .unwrap_or(key)
+        .to_string()
+}
+
+/// The key and serialized JSON of a document
+pub fn doc_to_json(index: &str, doc: Doc) -> Result<(String, String), SearchError> {
+    match serde_json::from_str::<JsonValue>(&doc.content) {
+        Ok(JsonValue::Object(fields)) => Ok((
+            document_key(index, &doc.id),
+            JsonValue::Object(fields).to_string(),
+        )),
+        Ok(_) => Err(invalid_query("Document content must be a JSON object")),
+        Err(e) => Err(invalid_query(format!("Invalid JSON in document: {e}"))),
+    }
+}
+
+/// The document of a `JSON.GET` reply
+pub fn json_to_doc(id: String, json: &str) -> Doc {
+    let content = match serde_json::from_str::<JsonValue>(json) {
+        Ok(content) => content.to_string(),
+        Err(_) => json.to_string(),
+    };
+    Doc { id, content }
+}
+
+fn is_field_name(key: &str) -> bool {
+    !key.is_empty() && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn field_type_name(field_type: FieldType) -> &'static str {
+    match field_type {
+        FieldType::Text => "TEXT",
+        FieldType::Keyword | FieldType::Boolean | FieldType::Date => "TAG",
+        FieldType::Integer | FieldType::Float => "NUMERIC",
+        FieldType::GeoPoint => "GEO",
+    }
+}
+
+/// The field type of an attribute type. Several field types share an attribute type, so
+/// `TAG` attributes read back as keywords and `NUMERIC` attributes as floats.
+fn parse_field_type(name: &str) -> FieldType {
+    match name.to_ascii_uppercase().as_str() {
+        "TAG" => FieldType::Keyword,
+        "NUMERIC" => FieldType::Float,
+        "GEO" => FieldType::GeoPoint,
+        _ => FieldType::Text,
+    }
+}
+
+/// The `SCHEMA` arguments of a field, indexing its JSON path. Fields that are neither indexed,
+/// faceted nor sorted get no attribute and are only stored in the documents.
+fn schema_field_to_args(field: &SchemaField) -> Result<Vec<String>, SearchError> {
+    if !is_field_name(&field.name) {
+        return Err(invalid_query(format!(
+            "Invalid field name `{}`",
+            field.name
+        )));
+    }
+    let mut args = vec![
+        format!("$.{}", field.name),
+        "AS".to_string(),
+        field.name.clone(),
+        field_type_name(field.field_type).to_string(),
+    ];
+    if field.sort {
+        args.push("SORTABLE".to_string());
+    }
+    if !field.index && !field.facet {
+        args.push("NOINDEX".to_string());
+    }
+    Ok(args)
+}
+
+fn has_attribute(field: &SchemaField) -> bool {
+    field.index || field.facet || field.sort
+}
+
+/// The `FT.CREATE` command of an index over the JSON documents of its key prefix
+pub fn schema_to_create(index: &str, schema: &Schema) -> Result<Vec<String>, SearchError> {
+    let mut create = vec![
+        "FT.CREATE".to_string(),
+        index.to_string(),
+        "ON".to_string(),
+        "JSON".to_string(),
+        "PREFIX".to_string(),
+        "1".to_string(),
+        key_prefix(index),
+        "SCHEMA".to_string(),
+    ];
+    let mut attributes = 0;
+    for field in schema.fields.iter().filter(|field| has_attribute(field)) {
+        create.extend(schema_field_to_args(field)?);
+        attributes += 1;
+    }
+    if attributes == 0 {
+        return Err(invalid_query(
+            "RediSearch indexes need a schema with at least one indexed field",
+        ));
+    }
+    Ok(create)
+}
+
+/// The `FT.ALTER` arguments adding the fields of `schema` missing from the index. Fields
+/// missing from `schema` are kept, and changing the type of an attribute is rejected, as
+/// attributes can't be altered.
+pub fn schema_changes(
+    attributes: &Attributes,
+    schema: &Schema,
+) -> Result<Vec<String>, SearchError> {
+    let mut added = Vec::new();
+    for field in schema.fields.iter().filter(|field| has_attribute(field)) {
+        match attributes.get(&field.name) {
+            None => added.extend(schema_field_to_args(field)?),
+            Some(attribute) if attribute.r#type != field_type_name(field.field_type) => {
+                return Err(invalid_query(format!(
+                    "Changing the type of field `{}` is not supported",
+                    field.name
+                )))
+            }
+            Some(_) => {}
+        }
+    }
+    Ok(added)
+}
+
+/// The value following `name` in a reply of alternating names and values, or the value of
+/// `name` in a map reply
+fn reply_field<'a>(reply: &'a JsonValue, name: &str) -> Option<&'a JsonValue> {
+    match reply {
+        JsonValue::Object(map) => map.get(name),
+        JsonValue::Array(items) => items
+            .iter()
+            .position(|item| item.as_str() == Some(name))
+            .and_then(|position| items.get(position + 1)),
+        _ => None,
+    }
+}
+
+fn reply_str(value: &JsonValue) -> Option<String> {
+    match value {
+        JsonValue::String(value) => Some(value.clone()),
+        JsonValue::Number(value) => Some(value.to_string()),
+        _ => None,
+    }
+}
+
+fn reply_number(value: &JsonValue) -> Option<f64> {
+    match value {
+        JsonValue::Number(value) => value.as_f64(),
+        JsonValue::String(value) => value.parse().ok(),
+        _ => None,
+    }
+}
+
+fn parse_attribute(reply: &JsonValue) -> Option<Attribute> {
+    let flag = |flag: &str| match reply {
+        JsonValue::Array(items) => items.iter().any(|item| item.as_str() == Some(flag)),
+        JsonValue::Object(map) => map
+            .get("flags")
+            .and_then(JsonValue::as_array)
+            .is_some_and(|flags| flags.iter().any(|item| item.as_str() == Some(flag))),
+        _ => false,
+    };
+    Some(Attribute {
+        name: reply_str(reply_field(reply, "attribute")?)?,
+        r#type: reply_str(reply_field(reply, "type")?)?.to_ascii_uppercase(),
+        sortable: flag("SORTABLE"),
+        indexed: !flag("NOINDEX"),
+    })
+}
+
+/// The attributes of an `FT.INFO` reply
+pub fn info_to_attributes(info: &JsonValue) -> Attributes {
+    reply_field(info, "attributes")
+        .and_then(JsonValue::as_array)
+        .map(|attributes| {
+            attributes
+                .iter()
+                .filter_map(parse_attribute)
+                .map(|attribute| (attribute.name.clone(), attribute))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// The schema of an index's attributes, ordered by name. Documents are keyed by their id, which
+/// is not an attribute.
+pub fn attributes_to_schema(attributes: &Attributes) -> Schema {
+    let mut fields = attributes
+        .values()
+        .map(|attribute| SchemaField {
+            name: attribute.name.clone(),
+            field_type: parse_field_type(&attribute.r#type),
+            required: false,
+            facet: attribute.r#type == "TAG" || attribute.sortable,
+            sort: attribute.sortable,
+            index: attribute.indexed,
+        })
+        .collect::<Vec<_>>();
+    fields.sort_by(|a, b| a.name.cmp(&b.name));
+    Schema {
+        fields,
+        primary_key: None,
+    }
+}
+
+/// Escapes the punctuation and spaces of a tag value
+fn escape_tag(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if !c.is_alphanumeric() && c != '_' {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+fn unquote(value: &str) -> &str {
+    let value = value.trim();
+    value
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+        .unwrap_or(value)
+}
+
+fn numeric_bound(value: &str, filter: &str) -> Result<String, SearchError> {
+    let value = unquote(value);
+    value.parse::<f64>().map_err(|_| {
+        invalid_query(format!(
+            "Filter `{filter}` compares with a non-numeric value"
+        ))
+    })?;
+    Ok(value.to_string())
+}
+
+/// Converts a filter to a query clause, using the attribute types of the index. Filters are
+/// either RediSearch clauses starting with `@`, `-@` or `(`, or `field:value`, `field=value`,
+/// `field!=value`, and `field>value` style comparisons with `>`, `>=`, `<` and `<=`, which
+/// become numeric ranges and are only supported on `NUMERIC` attributes.
+fn filter_to_clause(filter: &str, attributes: &Attributes) -> Result<String, SearchError> {
+    let filter = filter.trim();
+    if filter.starts_with('@') || filter.starts_with("-@") || filter.starts_with('(') {
+        return Ok(filter.to_string());
+    }
+
+    let position = filter
+        .find([':', '=', '!', '<', '>'])
+        .ok_or_else(|| invalid_query(format!("Unsupported filter `{filter}`")))?;
+    let key = filter[..position].trim();
+    let rest = &filter[position..];
+    let (op, value) = [">=", "<=", "!=", ">", "<", "=", ":"]
+        .into_iter()
+        .find_map(|op| Some((op, rest.strip_prefix(op)?)))
+        .ok_or_else(|| invalid_query(format!("Unsupported filter `{filter}`")))?;
+    if !is_field_name(key) || value.trim().is_empty() {
+        return Err(invalid_query(format!("Unsupported filter `{filter}`")));
+    }
+    let attribute = attributes
+        .get(key)
+        .filter(|attribute| attribute.indexed)
+        .ok_or_else(|| invalid_query(format!("Filter `{filter}` uses an unindexed field")))?;
+
+    let clause = match (attribute.r#type.as_str(), op) {
+        ("NUMERIC", ":" | "=" | "!=") => {
+            let value = numeric_bound(value, filter)?;
+            format!("@{key}:[{value} {value}]")
+        }
+        ("NUMERIC", ">") => format!("@{key}:[({} +inf]", numeric_bound(value, filter)?),
+        ("NUMERIC", ">=") => format!("@{key}:[{} +inf]", numeric_bound(value, filter)?),
+        ("NUMERIC", "<") => format!("@{key}:[-inf ({}]", numeric_bound(value, filter)?),
+        ("NUMERIC", _) => format!("@{key}:[-inf {}]", numeric_bound(value, filter)?),
+        (_, ">" | ">=" | "<" | "<=") => {
+            return Err(invalid_query(format!(
+                "Filter `{filter}` compares a field that is not numeric"
+            )))
+        }
+        ("TAG", _) => format!("@{key}:{{{}}}", escape_tag(unquote(value))),
+        ("TEXT", _) => format!(
+            "@{key}:\"{}\"",
+            unquote(value).replace('\\', "\\\\").replace('"', "\\\"")
+        ),
+        _ => return Err(invalid_query(format!("Unsupported filter `{filter}`"))),
+    };
+
+    Ok(if op == "!=" {
+        format!("-{clause}")
+    } else {
+        clause
+    })
+}
+
+/// The query string of a search: its text intersected with its filters, which are combined
+/// into a union when any filter may match
+pub fn query_to_string(
+    query: &SearchQuery,
+    attributes: &Attributes,
+) -> Result<String, SearchError> {
+    let mut clauses = Vec::new();
+    if let Some(q) = query.q.as_deref().map(str::trim).filter(|q| !q.is_empty()) {
+        clauses.push(format!("({q})"));
+    }
+
+    let filters = query
+        .filters
+        .iter()
+        .map(|filter| filter_to_clause(filter, attributes))
+        .collect::<Result<Vec<_>, _>>()?;
+    if search_filters_match_any(query) && filters.len() > 1 {
+        clauses.push(format!(
+            "({})",
+            filters
+                .iter()
+                .map(|filter| format!("({filter})"))
+                .collect::<Vec<_>>()
+                .join(" | ")
+        ));
+    } else {
+        clauses.extend(filters);
+    }
+
+    Ok(if clauses.is_empty() {
+        "*".to_string()
+    } else {
+        clauses.join(" ")
+    })
+}
+
+/// The `SORTBY` arguments of a query, from a single `field`, `field:asc`, `field:desc` or
+/// `-field` sort entry, as RediSearch sorts by a single attribute
+fn query_to_sort(sort: &[String]) -> Result<Option<Vec<String>>, SearchError> {
+    let sort = match sort {
+        [] => return Ok(None),
+        [sort] => sort.trim(),
+        _ => return Err(invalid_query("RediSearch sorts by a single field")),
+    };
+    let (key, order) = if let Some(key) = sort.strip_prefix('-') {
+        (key, "DESC")
+    } else {
+        match sort.rsplit_once(':') {
+            Some((key, order)) if order.eq_ignore_ascii_case("asc") => (key, "ASC"),
+            Some((key, order)) if order.eq_ignore_ascii_case("desc") => (key, "DESC"),
+            Some(_) => return Err(invalid_query(format!("Invalid sort `{sort}`"))),
+            None => (sort, "ASC"),
+        }
+    };
+    if !is_field_name(key) {
+        return Err(invalid_query(format!("Invalid sort `{sort}`")));
+    }
+    Ok(Some(vec![
+        "SORTBY".to_string(),
+        format!("@{key}"),
+        order.to_string(),
+    ]))
+}
+
+/// The additional arguments of the provider parameters, for example `{"args": ["SLOP", "1"]}`
+fn provider_args(query: &SearchQuery) -> Result<Vec<String>, SearchError> {
+    let Some(provider_params) = query
+        .config
+        .as_ref()
+        .and_then(|config| config.provider_params.as_deref())
+    else {
+        return Ok(vec![]);
+    };
+    let provider_params = serde_json::from_str::<JsonMap<String, JsonValue>>(provider_params)
+        .map_err(|e| invalid_query(format!("Invalid provider parameters: {e}")))?;
+    match provider_params.get("args") {
+        None => Ok(vec![]),
+        Some(JsonValue::Array(args)) => args
+            .iter()
+            .map(|arg| {
+                reply_str(arg).ok_or_else(|| {
+                    invalid_query("Provider parameter `args` must be a list of strings")
+                })
+            })
+            .collect(),
+        Some(_) => Err(invalid_query(
+            "Provider parameter `args` must be a list of strings",
+        )),
+    }
+}
+
+/// Number of hits to skip and number of hits per page of a query
+pub fn query_window(query: &SearchQuery) -> Result<(u32, u32), SearchError> {
+    let per_page = query.per_page.unwrap_or(DEFAULT_PER_PAGE);
+    let offset = query.offset.unwrap_or_else(|| {
+        query
+            .page
+            .unwrap_or(1)
+            .saturating_sub(1)
+            .saturating_mul(per_page)
+    });
+    if offset.saturating_add(per_page) > get_max_pagination_depth_config() {
+        return Err(invalid_query(format!(
+            "Requested hits beyond the maximum pagination depth of {}",
+            get_max_pagination_depth_config()
+        )));
+    }
+    Ok((offset, per_page))
+}
+
+/// Builds the `FT.SEARCH` command of a page of hits. Highlighting is not supported, as
+/// RediSearch only highlights hash documents.
+pub fn query_to_search(
+    index: &str,
+    query: &SearchQuery,
+    attributes: &Attributes,
+) -> Result<Vec<String>, SearchError> {
+    if query.highlight.is_some() {
+        return Err(unsupported("highlighting"));
+    }
+    let (offset, per_page) = query_window(query)?;
+
+    let mut search = vec![
+        "FT.SEARCH".to_string(),
+        index.to_string(),
+        query_to_string(query, attributes)?,
+        "WITHSCORES".to_string(),
+    ];
+    if let Some(sort) = query_to_sort(&query.sort)? {
+        search.extend(sort);
+    }
+    if let Some(language) = query
+        .config
+        .as_ref()
+        .and_then(|config| config.language.as_deref())
+    {
+        search.extend(["LANGUAGE".to_string(), language.to_string()]);
+    }
+    search.extend(provider_args(query)?);
+    search.extend([
+        "LIMIT".to_string(),
+        offset.to_string(),
+        per_page.to_string(),
+        "DIALECT".to_string(),
+        DIALECT.to_string(),
+    ]);
+    Ok(search)
+}
+
+/// Builds the `FT.AGGREGATE` command counting the values of a facet among the query's matches
+pub fn facet_to_aggregate(
+    index: &str,
+    query_string: &str,
+    facet: &str,
+) -> Result<Vec<String>, SearchError> {
+    if !is_field_name(facet) {
+        return Err(invalid_query(format!("Invalid facet `{facet}`")));
+    }
+    Ok([
+        "FT.AGGREGATE",
+        index,
+        query_string,
+        "GROUPBY",
+        "1",
+        &format!("@{facet}"),
+        "REDUCE",
+        "COUNT",
+        "0",
+        "AS",
+        COUNT_ALIAS,
+        "SORTBY",
+        "2",
+        &format!("@{COUNT_ALIAS}"),
+        "DESC",
+        "MAX",
+        &MAX_FACET_VALUES.to_string(),
+        "DIALECT",
+        DIALECT,
+    ]
+    .into_iter()
+    .map(str::to_string)
+    .collect())
+}
+
+/// Builds the `FT.AGGREGATE` command of a cursor over the keys and documents of the query's
+/// matches, reading `count` rows at a time
+pub fn query_to_cursor(
+    index: &str,
+    query: &SearchQuery,
+    attributes: &Attributes,
+    count: u32,
+) -> Result<Vec<String>, SearchError> {
+    Ok([
+        "FT.AGGREGATE",
+        index,
+        &query_to_string(query, attributes)?,
+        "LOAD",
+        "2",
+        "@__key",
+        "$",
+        "WITHCURSOR",
+        "COUNT",
+        &count.to_string(),
+        "DIALECT",
+        DIALECT,
+    ]
+    .into_iter()
+    .map(str::to_string)
+    .collect())
+}
+
+/// The document fields of a `$` load, restricted to the attributes to retrieve when any
+fn retrieved_content(json: &JsonValue, attributes_to_retrieve: &[String]) -> Option<String> {
+    let json = match json {
+        JsonValue::String(json) => serde_json::from_str::<JsonValue>(json).ok()?,
+        json => json.clone(),
+    };
+    let json = match json {
+        JsonValue::Object(fields) if !attributes_to_retrieve.is_empty() => JsonValue::Object(
+            fields
+                .into_iter()
+                .filter(|(name, _)| attributes_to_retrieve.contains(name))
+                .collect(),
+        ),
+        json => json,
+    };
+    Some(json.to_string())
+}
+
+fn attributes_to_retrieve(query: &SearchQuery) -> &[String] {
+    query
+        .config
+        .as_ref()
+        .map(|config| config.attributes_to_retrieve.as_slice())
+        .unwrap_or_default()
+}
+
+/// The hits and total of an `FT.SEARCH WITHSCORES` reply, made of the total followed by the
+/// key, score and loaded fields of each hit
+pub fn parse_search(
+    index: &str,
+    query: &SearchQuery,
+    reply: &JsonValue,
+) -> (Vec<SearchHit>, Option<u32>) {
+    let Some(items) = reply.as_array() else {
+        return (vec![], None);
+    };
+    let total = items
+        .first()
+        .and_then(reply_number)
+        .map(|total| total as u32);
+    let hits = items
+        .get(1..)
+        .unwrap_or_default()
+        .chunks(3)
+        .filter_map(|hit| {
+            let key = hit.first().and_then(JsonValue::as_str)?;
+            Some(SearchHit {
+                id: key_to_id(index, key),
+                score: hit.get(1).and_then(reply_number),
+                content: hit
+                    .get(2)
+                    .and_then(|fields| reply_field(fields, "$"))
+                    .and_then(|json| retrieved_content(json, attributes_to_retrieve(query))),
+                highlights: None,
+            })
+        })
+        .collect();
+    (hits, total)
+}
+
+/// The hits and next cursor of an `FT.AGGREGATE WITHCURSOR` or `FT.CURSOR READ` reply, made of
+/// the rows and the cursor id, which is 0 once the cursor is exhausted. Cursors don't score
+/// their rows.
+pub fn parse_cursor(
+    index: &str,
+    query: &SearchQuery,
+    reply: &JsonValue,
+) -> (Vec<SearchHit>, Option<u64>) {
+    let Some([rows, cursor]) = reply.as_array().map(Vec::as_slice) else {
+        return (vec![], None);
+    };
+    let hits = rows
+        .as_array()
+        .and_then(|rows| rows.get(1..))
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|row| {
+            let key = reply_field(row, "__key").and_then(JsonValue::as_str)?;
+            Some(SearchHit {
+                id: key_to_id(index, key),
+                score: None,
+                content: reply_field(row, "$")
+                    .and_then(|json| retrieved_content(json, attributes_to_retrieve(query))),
+                highlights: None,
+            })
+        })
+        .collect();
+    let cursor = reply_number(cursor)
+        .map(|cursor| cursor as u64)
+        .filter(|cursor| *cursor != 0);
+    (hits, cursor)
+}
+
+/// The value counts of an `FT.AGGREGATE` reply of `facet_to_aggregate`
+pub fn parse_facet(facet: &str, reply: &JsonValue) -> JsonMap<String, JsonValue> {
+    reply
+        .as_array()
+        .and_then(|rows| rows.get(1..))
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|row| {
+            let value = reply_str(reply_field(row, facet)?)?;
+            let count = reply_number(reply_field(row, COUNT_ALIAS)?)? as u64;
+            Some((value, JsonValue::from(count)))
+        })
+        .collect()
+}
+
+/// Facet counts in the same shape as the other providers: `{"field": {"value": count}}`
+pub fn facets_to_counts(facets: JsonMap<String, JsonValue>) -> Option<String> {
+    (!facets.is_empty()).then(|| JsonValue::Object(facets).to_string())
+}
+
+pub fn create_retry_query(original_query: &SearchQuery, partial_hits: &[SearchHit]) -> SearchQuery {
+    let mut retry_query = original_query.clone();
+
+    let current_offset = original_query.offset.unwrap_or(0);
+    let per_page = original_query.per_page.unwrap_or(DEFAULT_PER_PAGE);
+    let hits_received = partial_hits.len() as u32;
+
+    retry_query.offset = Some(current_offset + hits_received.min(per_page));
+
+    retry_query
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use golem_search::golem::search::types::{FilterMode, SearchConfig};
+    use serde_json::json;
+
+    fn query(q: Option<&str>, filters: &[&str]) -> SearchQuery {
+        SearchQuery {
+            q: q.map(str::to_string),
+            filters: filters.iter().map(|filter| filter.to_string()).collect(),
+            sort: vec![],
+            facets: vec![],
+            page: None,
+            per_page: None,
+            offset: None,
+            highlight: None,
+            config: None,
+        }
+    }
+
+    fn field(name: &str, field_type: FieldType, facet: bool, sort: bool) -> SchemaField {
+        SchemaField {
+            name: name.to_string(),
+            field_type,
+            required: false,
+            facet,
+            sort,
+            index: true,
+        }
+    }
+
+    fn config(provider_params: Option<&str>, filter_mode: Option<FilterMode>) -> SearchConfig {
+        SearchConfig {
+            timeout_ms: None,
+            boost_fields: vec![],
+            attributes_to_retrieve: vec![],
+            language: None,
+            typo_tolerance: None,
+            exact_match_boost: None,
+            provider_params: provider_params.map(str::to_string),
+            profile: None,
+            filter_mode,
+            post_filters: vec![],
+            rescore: None,
+            fallbacks: vec![],
+        }
+    }
+
+    fn attributes() -> Attributes {
+        info_to_attributes(&json!([
+            "index_name",
+            "books",
+            "attributes",
+            [
+                [
+                    "identifier",
+                    "$.title",
+                    "attribute",
+                    "title",
+                    "type",
+                    "TEXT",
+                    "WEIGHT",
+                    "1"
+                ],
+                [
+                    "identifier",
+                    "$.genre",
+                    "attribute",
+                    "genre",
+                    "type",
+                    "TAG",
+                    "SEPARATOR",
+                    ","
+                ],
+                [
+                    "identifier",
+                    "$.year",
+                    "attribute",
+                    "year",
+                    "type",
+                    "NUMERIC",
+                    "SORTABLE"
+                ],
+            ],
+            "num_docs",
+            "3",
+        ]))
+    }
+
+    #[test]
+    fn test_doc_conversions() {
+        let (key, json) = doc_to_json(
+            "books",
+            Doc {
+                id: "doc1".to_string(),
+                content: r#"{"title": "Dune"}"#.to_string(),
+            },
+        )
+        .unwrap();
+        assert_eq!(key, "books:doc1");
+        assert_eq!(json, r#"{"title":"Dune"}"#);
+
+        let doc = json_to_doc("doc1".to_string(), &json);
+        assert_eq!(doc.content, r#"{"title":"Dune"}"#);
+
+        assert!(doc_to_json(
+            "books",
+            Doc {
+                id: "doc1".to_string(),
+                content: "[]".to_string(),
+            }
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_schema_round_trip() {
+        let schema = Schema {
+            fields: vec![
+                field("title", FieldType::Text, false, false),
+                field("genre", FieldType::Keyword, true, false),
+                field("year", FieldType::Integer, false, true),
+            ],
+            primary_key: None,
+        };
+        assert_eq!(
+            schema_to_create("books", &schema).unwrap().join(" "),
+            "FT.CREATE books ON JSON PREFIX 1 books: SCHEMA $.title AS title TEXT \
+             $.genre AS genre TAG $.year AS year NUMERIC SORTABLE"
+        );
+        assert!(schema_to_create(
+            "books",
+            &Schema {
+                fields: vec![],
+                primary_key: None,
+            }
+        )
+        .is_err());
+
+        let round_trip = attributes_to_schema(&attributes());
+        let fields = round_trip
+            .fields
+            .iter()
+            .map(|field| {
+                (
+                    field.name.as_str(),
+                    field.field_type,
+                    field.facet,
+                    field.sort,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            fields,
+            vec![
+                ("genre", FieldType::Keyword, true, false),
+                ("title", FieldType::Text, false, false),
+                ("year", FieldType::Float, true, true),
+            ]
+        );
+
+        let updated = Schema {
+            fields: vec![
+                field("genre", FieldType::Boolean, true, false),
+                field("pages", FieldType::Integer, false, false),
+            ],
+            primary_key: None,
+        };
+        assert_eq!(
+            schema_changes(&attributes(), &updated).unwrap().join(" "),
+            "$.pages AS pages NUMERIC"
+        );
+        let changed = Schema {
+            fields: vec![field("year", FieldType::Keyword, false, false)],
+            primary_key: None,
+        };
+        assert!(schema_changes(&attributes(), &changed).is_err());
+    }
+
+    #[test]
+    fn test_filters_to_clauses() {
+        let attributes = attributes();
+        let clause = |filter| filter_to_clause(filter, &attributes);
+        assert_eq!(
+            clause("genre:science fiction").unwrap(),
+            "@genre:{science\\ fiction}"
+        );
+        assert_eq!(clause("genre!=\"sci-fi\"").unwrap(), "-@genre:{sci\\-fi}");
+        assert_eq!(clause("title=dune").unwrap(), "@title:\"dune\"");
+        assert_eq!(clause("year:1965").unwrap(), "@year:[1965 1965]");
+        assert_eq!(clause("year>1950").unwrap(), "@year:[(1950 +inf]");
+        assert_eq!(clause("year>=1950").unwrap(), "@year:[1950 +inf]");
+        assert_eq!(clause("year<1950").unwrap(), "@year:[-inf (1950]");
+        assert_eq!(clause("year<=1950").unwrap(), "@year:[-inf 1950]");
+        assert_eq!(clause("@year:[1950 1960]").unwrap(), "@year:[1950 1960]");
+        assert!(clause("year>old").is_err());
+        assert!(clause("genre>a").is_err());
+        assert!(clause("pages:100").is_err());
+        assert!(clause("no operator").is_err());
+    }
+
+    #[test]
+    fn test_query_to_search() {
+        let attributes = attributes();
+        let mut query = query(Some("dune"), &["genre:fiction", "year>1950"]);
+        query.sort = vec!["-year".to_string()];
+        query.page = Some(3);
+        query.per_page = Some(10);
+        let mut config = config(Some(r#"{"args": ["SLOP", 1]}"#), None);
+        config.language = Some("english".to_string());
+        query.config = Some(config);
+
+        assert_eq!(
+            query_to_search("books", &query, &attributes).unwrap(),
+            vec![
+                "FT.SEARCH",
+                "books",
+                "(dune) @genre:{fiction} @year:[(1950 +inf]",
+                "WITHSCORES",
+                "SORTBY",
+                "@year",
+                "DESC",
+                "LANGUAGE",
+                "english",
+                "SLOP",
+                "1",
+                "LIMIT",
+                "20",
+                "10",
+                "DIALECT",
+                "2",
+            ]
+        );
+
+        query.sort = vec!["year".to_string(), "title".to_string()];
+        assert!(query_to_search("books", &query, &attributes).is_err());
+
+        let mut any = self::query(None, &["genre:fiction", "genre:poetry"]);
+        any.config = Some(self::config(None, Some(FilterMode::Any)));
+        assert_eq!(
+            query_to_string(&any, &attributes).unwrap(),
+            "((@genre:{fiction}) | (@genre:{poetry}))"
+        );
+        assert_eq!(
+            query_to_string(&self::query(None, &[]), &attributes).unwrap(),
+            "*"
+        );
+    }
+
+    #[test]
+    fn test_parse_search() {
+        let mut query = query(Some("dune"), &[]);
+        let mut config = config(None, None);
+        config.attributes_to_retrieve = vec!["title".to_string()];
+        query.config = Some(config);
+
+        let (hits, total) = parse_search(
+            "books",
+            &query,
+            &json!([
+                12,
+                "books:doc1",
+                "1.5",
+                ["$", r#"{"title":"Dune","year":1965}"#],
+                "books:doc2",
+                "0.5",
+                ["$", r#"{"title":"Dune Messiah","year":1969}"#],
+            ]),
+        );
+        assert_eq!(total, Some(12));
+        assert_eq!(hits.len(), 2);
+        assert_eq!(hits[0].id, "doc1");
+        assert_eq!(hits[0].score, Some(1.5));
+        assert_eq!(
+            hits[1].content.as_deref(),
+            Some(r#"{"title":"Dune Messiah"}"#)
+        );
+    }
+
+    #[test]
+    fn test_parse_cursor_and_facets() {
+        let (hits, cursor) = parse_cursor(
+            "books",
+            &query(None, &[]),
+            &json!([
+                [2, ["__key", "books:doc1", "$", r#"{"title":"Dune"}"#]],
+                913_370_127,
+            ]),
+        );
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].id, "doc1");
+        assert_eq!(hits[0].content.as_deref(), Some(r#"{"title":"Dune"}"#));
+        assert_eq!(cursor, Some(913_370_127));
+
+        let (_, cursor) = parse_cursor("books", &query(None, &[]), &json!([[0], 0]));
+        assert_eq!(cursor, None);
+
+        assert_eq!(
+            facet_to_aggregate("books", "*", "genre").unwrap().join(" "),
+            "FT.AGGREGATE books * GROUPBY 1 @genre REDUCE COUNT 0 AS __count \
+             SORTBY 2 @__count DESC MAX 100 DIALECT 2"
+        );
+        let counts = parse_facet(
+            "genre",
+            &json!([
+                2,
+                ["genre", "fiction", "__count", "8"],
+                ["genre", "poetry", "__count", "3"],
+            ]),
+        );
+        let mut facets = JsonMap::new();
+        facets.insert("genre".to_string(), JsonValue::Object(counts));
+        assert_eq!(
+            facets_to_counts(facets).as_deref(),
+            Some(r#"{"genre":{"fiction":8,"poetry":3}}"#)
+        );
+    }
+
+    #[test]
+    fn test_create_retry_query() {
+        let mut original = query(Some("dune"), &[]);
+        original.offset = Some(20);
+        let hits = vec![
+            SearchHit {
+                id: "doc1".to_string(),
+                score: None,
+                content: None,
+                highlights: None,
+            };
+            5
+        ];
+        assert_eq!(create_retry_query(&original, &hits).offset, Some(25));
+    }
+}
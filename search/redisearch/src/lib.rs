@@ -0,0 +1,359 @@
+use crate::client::RediSearchApi;
+use crate::conversions::{
+    attributes_to_schema, create_retry_query, doc_to_json, document_key, facet_to_aggregate,
+    facets_to_counts, info_to_attributes, json_to_doc, parse_cursor, parse_facet, parse_search,
+    query_to_cursor, query_to_search, query_to_string, query_window, schema_changes,
+    schema_to_create, Attributes,
+};
+use golem_rust::wasm_rpc::Pollable;
+use golem_search::config::{
+    get_optional_config, profile_key, search_headers, search_profile, search_timeout,
+    worker_headers, worker_profile,
+};
+use golem_search::durability::{DurableSearch, ExtendedGuest};
+use golem_search::error::invalid_query;
+use golem_search::golem::search::core::{Guest, GuestSearchStream, SearchStream};
+use golem_search::golem::search::types::{
+    Doc, DocumentId, IndexName, IndexPage, PageRequest, Schema, SearchError, SearchHit,
+    SearchQuery, SearchResults,
+};
+use golem_search::pagination::index_page;
+use golem_search::LOGGING_STATE;
+use serde_json::Map as JsonMap;
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+
+mod client;
+mod conversions;
+
+/// Search stream over the matches of a query. Unsorted streams starting at the first hit are
+/// read with an aggregation cursor, other streams are paged through with offsets, as cursors
+/// can't skip or sort hits.
+struct RediSearchSearchStream {
+    client: RediSearchApi,
+    index_name: String,
+    query: SearchQuery,
+    offset: Cell<u32>,
+    /// Whether the stream reads an aggregation cursor
+    cursor_stream: bool,
+    /// The id of the open cursor, `None` before the first page
+    cursor: Cell<Option<u64>>,
+    /// The attributes of the index, fetched with the first page
+    attributes: RefCell<Option<Attributes>>,
+    finished: Cell<bool>,
+}
+
+impl RediSearchSearchStream {
+    pub fn new(client: RediSearchApi, index_name: String, query: SearchQuery) -> Self {
+        let offset = query_window(&query).map(|(offset, _)| offset).unwrap_or(0);
+        Self {
+            client,
+            index_name,
+            cursor_stream: offset == 0 && query.sort.is_empty(),
+            query,
+            offset: Cell::new(offset),
+            cursor: Cell::new(None),
+            attributes: RefCell::new(None),
+            finished: Cell::new(false),
+        }
+    }
+
+    pub fn subscribe(&self) -> Pollable {
+        golem_rust::bindings::wasi::clocks::monotonic_clock::subscribe_duration(0)
+    }
+
+    fn attributes(&self) -> Result<Attributes, SearchError> {
+        if let Some(attributes) = self.attributes.borrow().as_ref() {
+            return Ok(attributes.clone());
+        }
+        let attributes = info_to_attributes(&self.client.info(&self.index_name)?);
+        *self.attributes.borrow_mut() = Some(attributes.clone());
+        Ok(attributes)
+    }
+
+    /// Reads the next page of hits, and whether there may be more
+    fn next_page(&self, per_page: u32) -> Result<(Vec<SearchHit>, bool), SearchError> {
+        let mut query = self.query.clone();
+        query.page = None;
+        query.facets = vec![];
+
+        if self.cursor_stream {
+            let reply = match self.cursor.get() {
+                Some(cursor) => self
+                    .client
+                    .read_cursor(&self.index_name, cursor, per_page)?,
+                None => {
+                    let attributes = self.attributes()?;
+                    let command = query_to_cursor(&self.index_name, &query, &attributes, per_page)?;
+                    self.client.search(command)?
+                }
+            };
+            let (hits, cursor) = parse_cursor(&self.index_name, &query, &reply);
+            self.cursor.set(cursor);
+            Ok((hits, cursor.is_some()))
+        } else {
+            query.offset = Some(self.offset.get());
+            let attributes = self.attributes()?;
+            let results = RediSearchComponent::search_page(
+                &self.client,
+                &self.index_name,
+                &query,
+                &attributes,
+            )?;
+            let more = results.hits.len() as u32 >= per_page;
+            Ok((results.hits, more))
+        }
+    }
+}
+
+impl GuestSearchStream for RediSearchSearchStream {
+    fn get_next(&self) -> Option<Vec<SearchHit>> {
+        if self.finished.get() {
+            return Some(vec![]);
+        }
+
+        let per_page = query_window(&self.query)
+            .map(|(_, per_page)| per_page)
+            .unwrap_or(0);
+
+        match self.next_page(per_page) {
+            Ok((hits, more)) => {
+                if !more || (hits.is_empty() && !self.cursor_stream) {
+                    self.finished.set(true);
+                }
+                self.offset.set(self.offset.get() + hits.len() as u32);
+                Some(hits)
+            }
+            Err(_) => {
+                self.finished.set(true);
+                Some(vec![])
+            }
+        }
+    }
+
+    fn blocking_get_next(&self) -> Vec<SearchHit> {
+        self.get_next().unwrap_or_default()
+    }
+}
+
+struct RediSearchComponent;
+
+thread_local! {
+    /// The provider clients by configuration profile, reused across calls instead of being rebuilt
+    /// for each of them
+    static CLIENTS: RefCell<HashMap<Option<String>, RediSearchApi>> = RefCell::new(HashMap::new());
+}
+
+impl RediSearchComponent {
+    const URL_ENV_VAR: &'static str = "REDISEARCH_URL";
+    const TOKEN_ENV_VAR: &'static str = "REDISEARCH_TOKEN";
+
+    fn create_client() -> Result<RediSearchApi, SearchError> {
+        Self::create_profile_client(worker_profile().as_deref())
+            .map(|client| client.with_headers(worker_headers()))
+    }
+
+    /// Returns the client of `profile` cached for this worker, creating it from the configuration
+    /// on first use
+    fn create_profile_client(profile: Option<&str>) -> Result<RediSearchApi, SearchError> {
+        CLIENTS.with_borrow_mut(|clients| {
+            let profile = profile.map(str::to_string);
+            if let Some(client) = clients.get(&profile) {
+                return Ok(client.clone());
+            }
+
+            let client = Self::new_client(profile.as_deref())?;
+            clients.insert(profile, client.clone());
+            Ok(client)
+        })
+    }
+
+    /// Creates a client from the `REDISEARCH_URL` of the REST gateway, for example an Upstash
+    /// REST URL or `http://localhost:8079` for a `serverless-redis-http` proxy in front of Redis
+    /// Stack, and the optional `REDISEARCH_TOKEN` sent as a bearer token
+    fn new_client(profile: Option<&str>) -> Result<RediSearchApi, SearchError> {
+        let url = get_optional_config(profile_key(Self::URL_ENV_VAR, profile))
+            .ok_or_else(|| SearchError::Internal("Missing RediSearch URL".to_string()))?;
+        let token = get_optional_config(profile_key(Self::TOKEN_ENV_VAR, profile))
+            .filter(|token| !token.is_empty());
+
+        Ok(RediSearchApi::new(url, token))
+    }
+
+    fn attributes(client: &RediSearchApi, index: &str) -> Result<Attributes, SearchError> {
+        Ok(info_to_attributes(&client.info(index)?))
+    }
+
+    /// Searches a page of hits, then counts the values of each facet with an aggregation
+    fn search_page(
+        client: &RediSearchApi,
+        index: &str,
+        query: &SearchQuery,
+        attributes: &Attributes,
+    ) -> Result<SearchResults, SearchError> {
+        let (offset, per_page) = query_window(query)?;
+        let search = query_to_search(index, query, attributes)?;
+
+        let reply = client.search(search)?;
+        let (hits, total) = parse_search(index, query, &reply);
+
+        let mut facets = JsonMap::new();
+        if !query.facets.is_empty() {
+            let query_string = query_to_string(query, attributes)?;
+            for facet in &query.facets {
+                let reply = client.search(facet_to_aggregate(index, &query_string, facet)?)?;
+                facets.insert(facet.clone(), parse_facet(facet, &reply).into());
+            }
+        }
+
+        Ok(SearchResults {
+            total,
+            page: Some(offset / per_page.max(1) + 1),
+            per_page: Some(per_page),
+            hits,
+            facets: facets_to_counts(facets),
+            took_ms: None,
+            relaxation: None,
+            navigation: None,
+        })
+    }
+}
+
+impl Guest for RediSearchComponent {
+    type SearchStream = RediSearchSearchStream;
+
+    /// Creates an index over the JSON documents stored under the `{name}:` key prefix. RediSearch
+    /// indexes need at least one attribute, so a schema is required.
+    fn create_index(name: IndexName, schema: Option<Schema>) -> Result<(), SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let schema = schema.ok_or_else(|| invalid_query("RediSearch indexes need a schema"))?;
+        let client = Self::create_client()?;
+        client.create_index(schema_to_create(&name, &schema)?)
+    }
+
+    /// Drops the index along with its documents
+    fn delete_index(name: IndexName) -> Result<(), SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = Self::create_client()?;
+        client.drop_index(&name)
+    }
+
+    fn list_indexes(page: PageRequest) -> Result<IndexPage, SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = Self::create_client()?;
+        index_page(client.list_indexes()?, &page)
+    }
+
+    fn upsert(index: IndexName, doc: Doc) -> Result<(), SearchError> {
+        Self::upsert_many(index, vec![doc])
+    }
+
+    fn upsert_many(index: IndexName, docs: Vec<Doc>) -> Result<(), SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        if docs.is_empty() {
+            return Ok(());
+        }
+
+        let client = Self::create_client()?;
+        let documents = docs
+            .into_iter()
+            .map(|doc| doc_to_json(&index, doc))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        client.set_documents(documents)
+    }
+
+    fn delete(index: IndexName, id: DocumentId) -> Result<(), SearchError> {
+        Self::delete_many(index, vec![id])
+    }
+
+    fn delete_many(index: IndexName, ids: Vec<DocumentId>) -> Result<(), SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        if ids.is_empty() {
+            return Ok(());
+        }
+
+        let client = Self::create_client()?;
+        let keys = ids.iter().map(|id| document_key(&index, id)).collect();
+        client.delete_keys(keys)
+    }
+
+    fn get(index: IndexName, id: DocumentId) -> Result<Option<Doc>, SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = Self::create_client()?;
+        let json = client.get_document(&document_key(&index, &id))?;
+        Ok(json.map(|json| json_to_doc(id, &json)))
+    }
+
+    fn search(index: IndexName, query: SearchQuery) -> Result<SearchResults, SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = Self::create_profile_client(search_profile(&query).as_deref())?
+            .with_request_timeout(search_timeout(&query))
+            .with_headers(search_headers(&query));
+
+        let attributes = Self::attributes(&client, &index)?;
+        Self::search_page(&client, &index, &query, &attributes)
+    }
+
+    fn stream_search(index: IndexName, query: SearchQuery) -> Result<SearchStream, SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = Self::create_profile_client(search_profile(&query).as_deref())?
+            .with_request_timeout(search_timeout(&query))
+            .with_headers(search_headers(&query));
+        let stream = RediSearchSearchStream::new(client, index, query);
+        Ok(SearchStream::new(stream))
+    }
+
+    fn get_schema(index: IndexName) -> Result<Schema, SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = Self::create_client()?;
+        Ok(attributes_to_schema(&Self::attributes(&client, &index)?))
+    }
+
+    /// Adds the new fields of the schema to the index. Fields missing from the schema are kept,
+    /// and the type of existing fields can't be changed.
+    fn update_schema(index: IndexName, schema: Schema) -> Result<(), SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = Self::create_client()?;
+        let attributes = Self::attributes(&client, &index)?;
+        let added = schema_changes(&attributes, &schema)?;
+        if added.is_empty() {
+            return Ok(());
+        }
+        client.add_attributes(&index, added)
+    }
+}
+
+impl ExtendedGuest for RediSearchComponent {
+    fn unwrapped_stream(index: IndexName, query: SearchQuery) -> Self::SearchStream {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = Self::create_profile_client(search_profile(&query).as_deref())
+            .unwrap_or_else(|_| RediSearchApi::new("".to_string(), None))
+            .with_headers(search_headers(&query));
+
+        RediSearchSearchStream::new(client, index, query)
+    }
+
+    fn retry_query(original_query: &SearchQuery, partial_hits: &[SearchHit]) -> SearchQuery {
+        create_retry_query(original_query, partial_hits)
+    }
+
+    fn subscribe(stream: &Self::SearchStream) -> Pollable {
+        stream.subscribe()
+    }
+}
+
+type DurableRediSearchComponent = DurableSearch<RediSearchComponent>;
+
+golem_search::export_search!(DurableRediSearchComponent with_types_in golem_search);
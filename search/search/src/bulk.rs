@@ -0,0 +1,147 @@
+//! Failures of bulk writes.
+//!
+//! Bulk APIs report the outcome of each operation of a request separately, so that a request can
+//! partly fail. Providers describe the items of their bulk responses with `BulkItem`, and the
+//! failed operations are then either returned per document or reported as a single error.
+
+use crate::golem::search::types::{ProviderErrorDetails, SearchError};
+use serde_json::Value;
+
+/// Code of the provider error of bulk requests some operations of which failed
+pub const BULK_FAILURE_CODE: &str = "bulk_partial_failure";
+
+/// An operation of a bulk request which failed
+#[derive(Debug, Clone, PartialEq)]
+pub struct BulkFailure {
+    pub id: String,
+    pub status: u16,
+    /// The type of the error, such as `mapper_parsing_exception`
+    pub code: Option<String>,
+    pub reason: String,
+}
+
+/// An item of a bulk response, reporting the outcome of one operation
+pub trait BulkItem {
+    /// The failure of the operation, none when it succeeded
+    fn failure(&self) -> Option<BulkFailure>;
+}
+
+/// Items reporting each operation under its action, as the bulk responses of Elasticsearch and
+/// OpenSearch do: `{"index": {"_id": "1", "status": 400, "error": {"type": "...", "reason": "..."}}}`
+impl BulkItem for Value {
+    fn failure(&self) -> Option<BulkFailure> {
+        let result = self.as_object()?.values().next()?;
+        let error = result.get("error")?;
+        Some(BulkFailure {
+            id: result
+                .get("_id")
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_string(),
+            status: result.get("status").and_then(Value::as_u64).unwrap_or(500) as u16,
+            code: error
+                .get("type")
+                .and_then(Value::as_str)
+                .map(str::to_string),
+            reason: error
+                .get("reason")
+                .and_then(Value::as_str)
+                .map(str::to_string)
+                .unwrap_or_else(|| error.to_string()),
+        })
+    }
+}
+
+/// The failed operations among the items of a bulk response
+pub fn bulk_failures<I: BulkItem>(items: &[I]) -> Vec<BulkFailure> {
+    items.iter().filter_map(BulkItem::failure).collect()
+}
+
+/// The error of a bulk request some operations of which failed, a provider error whose message
+/// lists the ids of the failed documents with their reasons, and whose status is the one of the
+/// first failure
+pub fn bulk_failures_to_search_error(failures: &[BulkFailure], operations: usize) -> SearchError {
+    let details = failures
+        .iter()
+        .map(|failure| match &failure.code {
+            Some(code) => format!("{} ({code}: {})", failure.id, failure.reason),
+            None => format!("{} ({})", failure.id, failure.reason),
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    SearchError::ProviderError(ProviderErrorDetails {
+        status: failures.first().map_or(500, |failure| failure.status),
+        code: Some(BULK_FAILURE_CODE.to_string()),
+        message: format!(
+            "{} of {operations} bulk operations failed: {details}",
+            failures.len()
+        ),
+        request_id: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_bulk_failures_of_action_items() {
+        let items = vec![
+            json!({ "index": { "_id": "doc1", "status": 201, "result": "created" } }),
+            json!({
+                "index": {
+                    "_id": "doc2",
+                    "status": 400,
+                    "error": {
+                        "type": "mapper_parsing_exception",
+                        "reason": "failed to parse field [year]"
+                    }
+                }
+            }),
+            json!({ "delete": { "_id": "doc3", "status": 404, "result": "not_found" } }),
+        ];
+
+        assert_eq!(
+            bulk_failures(&items),
+            vec![BulkFailure {
+                id: "doc2".to_string(),
+                status: 400,
+                code: Some("mapper_parsing_exception".to_string()),
+                reason: "failed to parse field [year]".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_bulk_failures_to_search_error() {
+        let failures = vec![
+            BulkFailure {
+                id: "doc2".to_string(),
+                status: 400,
+                code: Some("mapper_parsing_exception".to_string()),
+                reason: "failed to parse field [year]".to_string(),
+            },
+            BulkFailure {
+                id: "doc5".to_string(),
+                status: 429,
+                code: None,
+                reason: "rejected".to_string(),
+            },
+        ];
+
+        match bulk_failures_to_search_error(&failures, 5) {
+            SearchError::ProviderError(details) => {
+                assert_eq!(details.status, 400);
+                assert_eq!(details.code.as_deref(), Some(BULK_FAILURE_CODE));
+                assert_eq!(
+                    details.message,
+                    "2 of 5 bulk operations failed: doc2 (mapper_parsing_exception: failed to \
+                     parse field [year]), doc5 (rejected)"
+                );
+            }
+            other => panic!("Unexpected error {other:?}"),
+        }
+    }
+}
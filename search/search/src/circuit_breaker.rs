@@ -0,0 +1,208 @@
+//! Circuit breaker protecting the worker from a provider that keeps failing.
+//!
+//! After `failure_threshold` consecutive failures the circuit opens and every operation fails
+//! immediately with `search-error.circuit-open` instead of waiting for the provider to time out.
+//! Once `open_duration` has elapsed a single probe is let through: if it succeeds the circuit
+//! closes again, otherwise it stays open for another `open_duration`.
+//!
+//! Only timeouts, internal errors and server errors of the provider count as failures. Errors
+//! such as an invalid query or a missing index are answers from a healthy provider and reset the
+//! failure count.
+//!
+//! The breaker reads the monotonic clock directly rather than through `deterministic`: it only
+//! guards calls actually sent to the provider, which the durable wrappers never make while
+//! replaying, as they read the results back from the oplog. Its state is not part of the worker's
+//! durable state either, so a recovered worker starts with a closed circuit and persisting the
+//! clock reads would only add two oplog entries to every call.
+
+use crate::config::get_config_with_default;
+use crate::golem::search::types::SearchError;
+use log::warn;
+use std::cell::RefCell;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CircuitBreakerPolicy {
+    /// Number of consecutive failures opening the circuit, `0` disables the circuit breaker
+    pub failure_threshold: u32,
+    pub open_duration: Duration,
+}
+
+impl Default for CircuitBreakerPolicy {
+    fn default() -> Self {
+        Self {
+            failure_threshold: 5,
+            open_duration: Duration::from_secs(30),
+        }
+    }
+}
+
+impl CircuitBreakerPolicy {
+    /// Creates the policy from `SEARCH_PROVIDER_CIRCUIT_BREAKER_THRESHOLD` and
+    /// `SEARCH_PROVIDER_CIRCUIT_BREAKER_OPEN_SECS`
+    pub fn from_config() -> Self {
+        let defaults = Self::default();
+        let failure_threshold = get_config_with_default(
+            "SEARCH_PROVIDER_CIRCUIT_BREAKER_THRESHOLD",
+            defaults.failure_threshold.to_string(),
+        )
+        .parse()
+        .unwrap_or(defaults.failure_threshold);
+        let open_secs = get_config_with_default(
+            "SEARCH_PROVIDER_CIRCUIT_BREAKER_OPEN_SECS",
+            defaults.open_duration.as_secs().to_string(),
+        )
+        .parse()
+        .unwrap_or(defaults.open_duration.as_secs());
+
+        Self {
+            failure_threshold,
+            open_duration: Duration::from_secs(open_secs),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum State {
+    Closed { failures: u32 },
+    Open { until: Instant },
+    HalfOpen,
+}
+
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    policy: CircuitBreakerPolicy,
+    state: State,
+}
+
+impl CircuitBreaker {
+    pub fn new(policy: CircuitBreakerPolicy) -> Self {
+        Self {
+            policy,
+            state: State::Closed { failures: 0 },
+        }
+    }
+
+    /// Checks whether an operation may be sent to the provider at `now`
+    fn before_call(&mut self, now: Instant) -> Result<(), SearchError> {
+        match self.state {
+            State::Closed { .. } => Ok(()),
+            State::Open { until } if now >= until => {
+                self.state = State::HalfOpen;
+                Ok(())
+            }
+            State::Open { until } => Err(SearchError::CircuitOpen(
+                until.duration_since(now).as_secs_f64().ceil() as u32,
+            )),
+            State::HalfOpen => Err(SearchError::CircuitOpen(0)),
+        }
+    }
+
+    /// Records the outcome of an operation sent to the provider at `now`
+    fn after_call(&mut self, error: Option<&SearchError>, now: Instant) {
+        if self.policy.failure_threshold == 0 {
+            return;
+        }
+
+        let failed = error.is_some_and(is_provider_failure);
+        self.state = match (self.state, failed) {
+            (_, false) => State::Closed { failures: 0 },
+            (State::Closed { failures }, true) if failures + 1 < self.policy.failure_threshold => {
+                State::Closed {
+                    failures: failures + 1,
+                }
+            }
+            (_, true) => {
+                warn!(
+                    "Search provider failed {} times in a row, suspending calls for {:?}",
+                    self.policy.failure_threshold, self.policy.open_duration
+                );
+                State::Open {
+                    until: now + self.policy.open_duration,
+                }
+            }
+        };
+    }
+}
+
+fn is_provider_failure(error: &SearchError) -> bool {
+    match error {
+        SearchError::Timeout | SearchError::Internal(_) => true,
+        SearchError::ProviderError(details) => details.status >= 500,
+        _ => false,
+    }
+}
+
+thread_local! {
+    static CIRCUIT_BREAKER: RefCell<CircuitBreaker> =
+        RefCell::new(CircuitBreaker::new(CircuitBreakerPolicy::from_config()));
+}
+
+/// Runs a provider operation through the worker's circuit breaker, failing fast with
+/// `SearchError::CircuitOpen` while the circuit is open
+pub fn guard<T>(f: impl FnOnce() -> Result<T, SearchError>) -> Result<T, SearchError> {
+    CIRCUIT_BREAKER.with_borrow_mut(|breaker| breaker.before_call(Instant::now()))?;
+    let result = f();
+    CIRCUIT_BREAKER
+        .with_borrow_mut(|breaker| breaker.after_call(result.as_ref().err(), Instant::now()));
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn breaker() -> CircuitBreaker {
+        CircuitBreaker::new(CircuitBreakerPolicy {
+            failure_threshold: 2,
+            open_duration: Duration::from_secs(10),
+        })
+    }
+
+    #[test]
+    fn opens_after_consecutive_failures() {
+        let mut breaker = breaker();
+        let now = Instant::now();
+
+        breaker.after_call(Some(&SearchError::Timeout), now);
+        breaker.after_call(None, now);
+        breaker.after_call(Some(&SearchError::Timeout), now);
+        assert!(breaker.before_call(now).is_ok());
+
+        breaker.after_call(Some(&SearchError::Internal("down".to_string())), now);
+        assert_eq!(
+            breaker.before_call(now + Duration::from_millis(500)),
+            Err(SearchError::CircuitOpen(10))
+        );
+    }
+
+    #[test]
+    fn healthy_provider_errors_do_not_count() {
+        let mut breaker = breaker();
+        let now = Instant::now();
+
+        breaker.after_call(Some(&SearchError::IndexNotFound), now);
+        breaker.after_call(Some(&SearchError::InvalidQuery("bad".to_string())), now);
+        breaker.after_call(Some(&SearchError::Timeout), now);
+        assert!(breaker.before_call(now).is_ok());
+    }
+
+    #[test]
+    fn half_open_probe_closes_or_reopens() {
+        let mut breaker = breaker();
+        let now = Instant::now();
+        breaker.after_call(Some(&SearchError::Timeout), now);
+        breaker.after_call(Some(&SearchError::Timeout), now);
+
+        let later = now + Duration::from_secs(10);
+        assert!(breaker.before_call(later).is_ok());
+        assert_eq!(breaker.before_call(later), Err(SearchError::CircuitOpen(0)));
+        breaker.after_call(Some(&SearchError::Timeout), later);
+        assert!(breaker.before_call(later).is_err());
+
+        let even_later = later + Duration::from_secs(10);
+        assert!(breaker.before_call(even_later).is_ok());
+        breaker.after_call(None, even_later);
+        assert_eq!(breaker.state, State::Closed { failures: 0 });
+    }
+}
@@ -1,5 +1,46 @@
-use crate::golem::search::types::SearchError;
+//! Configuration lookup shared by the search providers.
+//!
+//! Every key is resolved through a chain of sources: `wasi:config` is consulted first, so Golem
+//! deployments can manage credentials without putting them in the worker environment, and the
+//! environment variables are used as a fallback.
+//!
+//! A worker can also talk to several deployments of the same provider through named profiles.
+//! The keys of a profile carry its name after the provider prefix, so the `analytics` profile of
+//! Elasticsearch reads `ELASTICSEARCH_ANALYTICS_URL` instead of `ELASTICSEARCH_URL`. The profile of
+//! a worker is set by `SEARCH_PROVIDER_PROFILE` and applies to every operation, index and document
+//! operations included; the `profile` of a query's `search-config` only overrides it for that query.
+//! Provider-specific settings, such as index mappings or vector dimensions, are read from the
+//! profile of the operation as well, through `profile_key`.
+
+use crate::golem::search::types::{FilterMode, SearchError, SearchQuery};
+use crate::logging::CREDENTIAL_HEADERS;
 use std::ffi::OsStr;
+use std::time::Duration;
+
+/// Looks up `key` in `wasi:config`, then in the environment variables
+pub fn get_config_value(key: impl AsRef<OsStr>) -> Option<String> {
+    let key = key.as_ref();
+    wasi_config_value(&key.to_string_lossy()).or_else(|| std::env::var(key).ok())
+}
+
+#[cfg(target_arch = "wasm32")]
+fn wasi_config_value(key: &str) -> Option<String> {
+    match crate::wasi::config::store::get(key) {
+        Ok(value) => value,
+        Err(error) => {
+            log::warn!(
+                "Failed to read {key} from wasi:config, falling back to the environment: {error:?}"
+            );
+            None
+        }
+    }
+}
+
+/// `wasi:config` is only available inside a component, native builds only see the environment
+#[cfg(not(target_arch = "wasm32"))]
+fn wasi_config_value(_key: &str) -> Option<String> {
+    None
+}
 
 pub fn with_config_key<R>(
     key: impl AsRef<OsStr>,
@@ -7,9 +48,9 @@ pub fn with_config_key<R>(
     succeed: impl FnOnce(String) -> R,
 ) -> R {
     let key_str = key.as_ref().to_string_lossy().to_string();
-    match std::env::var(key) {
-        Ok(value) => succeed(value),
-        Err(_) => {
+    match get_config_value(key) {
+        Some(value) => succeed(value),
+        None => {
             let error = SearchError::Internal(format!("Missing config key: {key_str}"));
             fail(error)
         }
@@ -17,24 +58,25 @@ pub fn with_config_key<R>(
 }
 
 pub fn get_optional_config(key: impl AsRef<OsStr>) -> Option<String> {
-    std::env::var(key).ok()
+    get_config_value(key)
 }
 
 pub fn get_config_with_default(key: impl AsRef<OsStr>, default: impl Into<String>) -> String {
-    std::env::var(key).unwrap_or_else(|_| default.into())
+    get_config_value(key).unwrap_or_else(|| default.into())
 }
 
 pub fn validate_config_key(key: impl AsRef<OsStr>) -> Result<String, SearchError> {
     let key_str = key.as_ref().to_string_lossy().to_string();
-    std::env::var(key).map_err(|_| SearchError::Internal(format!("Missing config key: {key_str}")))
+    get_config_value(key)
+        .ok_or_else(|| SearchError::Internal(format!("Missing config key: {key_str}")))
 }
 
 pub fn with_config_keys<R>(keys: &[&str], callback: impl FnOnce(Vec<String>) -> R) -> R {
     let mut values = Vec::new();
     for key in keys {
-        match std::env::var(key) {
-            Ok(value) => values.push(value),
-            Err(_) => {
+        match get_config_value(key) {
+            Some(value) => values.push(value),
+            None => {
                 return callback(Vec::new());
             }
         }
@@ -42,6 +84,131 @@ pub fn with_config_keys<R>(keys: &[&str], callback: impl FnOnce(Vec<String>) ->
     callback(values)
 }
 
+/// Same as `with_config_keys`, but reads the keys of `profile` when one is given
+pub fn with_profile_config_keys<R>(
+    profile: Option<&str>,
+    keys: &[&str],
+    callback: impl FnOnce(Vec<String>) -> R,
+) -> R {
+    let keys = keys
+        .iter()
+        .map(|key| profile_key(key, profile))
+        .collect::<Vec<_>>();
+    with_config_keys(
+        &keys.iter().map(String::as_str).collect::<Vec<_>>(),
+        callback,
+    )
+}
+
+/// Returns the name of `key` in `profile`, or `key` itself for the default profile
+pub fn profile_key(key: &str, profile: Option<&str>) -> String {
+    let Some(profile) = profile else {
+        return key.to_string();
+    };
+    let profile = profile.to_uppercase().replace('-', "_");
+    match key.split_once('_') {
+        Some((prefix, rest)) => format!("{prefix}_{profile}_{rest}"),
+        None => format!("{key}_{profile}"),
+    }
+}
+
+/// The configuration profile of the worker, used by every operation that doesn't select its own
+pub fn worker_profile() -> Option<String> {
+    get_config_value("SEARCH_PROVIDER_PROFILE").filter(|profile| !profile.is_empty())
+}
+
+/// The configuration profile selected in the query's `search-config`, or the worker's profile
+pub fn search_profile(query: &SearchQuery) -> Option<String> {
+    query
+        .config
+        .as_ref()
+        .and_then(|config| config.profile.clone())
+        .or_else(worker_profile)
+}
+
+/// Whether a hit only has to match one of the query's filters instead of all of them
+pub fn search_filters_match_any(query: &SearchQuery) -> bool {
+    query
+        .config
+        .as_ref()
+        .and_then(|config| config.filter_mode)
+        .is_some_and(|mode| mode == FilterMode::Any)
+}
+
+/// Headers set by the providers themselves for signing, tracing, tenancy and content
+/// negotiation, in addition to the credential headers. Custom headers with these names are
+/// dropped instead of clashing with them.
+const RESERVED_HEADERS: &[&str] = &[
+    "content-type",
+    "content-length",
+    "host",
+    "x-request-id",
+    "x-opaque-id",
+    "x-algolia-application-id",
+    "securitytenant",
+    "opendistro_security_impersonate_as",
+    "x-amz-date",
+    "x-amz-content-sha256",
+];
+
+/// Whether `name` is a header the providers set themselves, compared case-insensitively
+pub fn is_reserved_header(name: &str) -> bool {
+    RESERVED_HEADERS
+        .iter()
+        .chain(CREDENTIAL_HEADERS)
+        .any(|reserved| reserved.eq_ignore_ascii_case(name))
+}
+
+/// Custom headers for every request of the worker, from the JSON object of
+/// `SEARCH_PROVIDER_HEADERS`
+pub fn worker_headers() -> Vec<(String, String)> {
+    get_config_value("SEARCH_PROVIDER_HEADERS")
+        .and_then(|headers| serde_json::from_str::<serde_json::Value>(&headers).ok())
+        .map(|headers| custom_headers(&headers))
+        .unwrap_or_default()
+}
+
+/// Custom headers for the requests of a search: the worker's headers, overridden by the `headers`
+/// object of the query's `provider-params`
+pub fn search_headers(query: &SearchQuery) -> Vec<(String, String)> {
+    let query_headers = query
+        .config
+        .as_ref()
+        .and_then(|config| config.provider_params.as_ref())
+        .and_then(|params| serde_json::from_str::<serde_json::Value>(params).ok())
+        .and_then(|params| params.get("headers").map(custom_headers))
+        .unwrap_or_default();
+
+    let mut headers = worker_headers();
+    headers.retain(|(name, _)| {
+        !query_headers
+            .iter()
+            .any(|(query_name, _)| query_name.eq_ignore_ascii_case(name))
+    });
+    headers.extend(query_headers);
+    headers
+}
+
+/// The string entries of a JSON object of headers, except the reserved ones
+fn custom_headers(headers: &serde_json::Value) -> Vec<(String, String)> {
+    headers
+        .as_object()
+        .map(|headers| {
+            headers
+                .iter()
+                .filter(|(name, _)| {
+                    let reserved = is_reserved_header(name);
+                    if reserved {
+                        log::warn!("Ignoring the custom {name} header, set by the provider");
+                    }
+                    !reserved
+                })
+                .filter_map(|(name, value)| Some((name.clone(), value.as_str()?.to_string())))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
 pub fn get_timeout_config() -> u64 {
     get_config_with_default("SEARCH_PROVIDER_TIMEOUT", "30")
         .parse()
@@ -53,3 +220,106 @@ pub fn get_max_retries_config() -> u32 {
         .parse()
         .unwrap_or(3)
 }
+
+/// The deepest hit a search may request. Pages beyond a provider's own limit and up to this depth
+/// are served by iterating over the results inside the component.
+pub fn get_max_pagination_depth_config() -> u32 {
+    get_config_with_default("SEARCH_PROVIDER_MAX_PAGINATION_DEPTH", "100000")
+        .parse()
+        .unwrap_or(100_000)
+}
+
+/// Number of documents sent per persisted chunk by the durable `upsert-many`
+pub fn get_upsert_chunk_size_config() -> usize {
+    get_config_with_default("SEARCH_PROVIDER_UPSERT_CHUNK_SIZE", "1000")
+        .parse()
+        .unwrap_or(1000)
+        .max(1)
+}
+
+/// The request timeout set in the query's `search-config`, if any
+pub fn search_timeout(query: &SearchQuery) -> Option<Duration> {
+    query
+        .config
+        .as_ref()?
+        .timeout_ms
+        .map(|timeout_ms| Duration::from_millis(timeout_ms as u64))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::golem::search::types::SearchConfig;
+
+    fn query_with_provider_params(provider_params: &str) -> SearchQuery {
+        SearchQuery {
+            q: None,
+            filters: vec![],
+            sort: vec![],
+            facets: vec![],
+            page: None,
+            per_page: None,
+            offset: None,
+            highlight: None,
+            config: Some(SearchConfig {
+                timeout_ms: None,
+                boost_fields: vec![],
+                attributes_to_retrieve: vec![],
+                language: None,
+                typo_tolerance: None,
+                exact_match_boost: None,
+                provider_params: Some(provider_params.to_string()),
+                profile: None,
+                filter_mode: None,
+                post_filters: vec![],
+                rescore: None,
+                fallbacks: vec![],
+            }),
+        }
+    }
+
+    #[test]
+    fn profile_key_inserts_profile_after_provider_prefix() {
+        assert_eq!(profile_key("ELASTICSEARCH_URL", None), "ELASTICSEARCH_URL");
+        assert_eq!(
+            profile_key("ELASTICSEARCH_URL", Some("analytics")),
+            "ELASTICSEARCH_ANALYTICS_URL"
+        );
+        assert_eq!(
+            profile_key("ALGOLIA_APPLICATION_ID", Some("eu-west")),
+            "ALGOLIA_EU_WEST_APPLICATION_ID"
+        );
+    }
+
+    #[test]
+    fn query_profile_overrides_worker_profile() {
+        let mut query = query_with_provider_params("{}");
+        query.config.as_mut().unwrap().profile = Some("analytics".to_string());
+        assert_eq!(search_profile(&query).as_deref(), Some("analytics"));
+    }
+
+    #[test]
+    fn search_headers_are_read_from_provider_params() {
+        let query = query_with_provider_params(
+            r#"{"headers": {"X-Tenant-Id": "acme", "X-Ignored": 1}, "other": true}"#,
+        );
+        assert_eq!(
+            search_headers(&query),
+            vec![("X-Tenant-Id".to_string(), "acme".to_string())]
+        );
+
+        assert!(search_headers(&query_with_provider_params(r#"{"other": true}"#)).is_empty());
+        assert!(search_headers(&query_with_provider_params("not json")).is_empty());
+    }
+
+    #[test]
+    fn reserved_search_headers_are_dropped() {
+        let query = query_with_provider_params(
+            r#"{"headers": {"authorization": "Bearer x", "Content-Type": "text/plain", "X-Algolia-API-Key": "k", "X-Tenant-Id": "acme"}}"#,
+        );
+        assert_eq!(
+            search_headers(&query),
+            vec![("X-Tenant-Id".to_string(), "acme".to_string())]
+        );
+    }
+}
@@ -0,0 +1,97 @@
+//! Correlation IDs identifying the worker's logical operations in the providers' logs.
+//!
+//! Every operation sent to the provider starts a new correlation ID. The clients send it in the
+//! `X-Request-Id` header of all the requests made for the operation, including retries, it is
+//! logged when the operation starts, and it is appended to the message of the errors returned by
+//! the operation, so an entry of the worker's oplog can be matched with the provider-side logs.
+
+use crate::deterministic;
+use crate::golem::search::types::{ProviderErrorDetails, SearchError};
+use log::debug;
+use std::cell::RefCell;
+
+/// Header carrying the correlation ID of the outgoing requests
+pub const CORRELATION_ID_HEADER: &str = "X-Request-Id";
+
+thread_local! {
+    static CORRELATION_ID: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// Runs a provider operation with a new correlation ID, annotating its errors with the ID
+pub fn scope<T>(
+    operation: &str,
+    f: impl FnOnce() -> Result<T, SearchError>,
+) -> Result<T, SearchError> {
+    let id = generate();
+    debug!("Starting {operation} with correlation ID {id}");
+    CORRELATION_ID.with_borrow_mut(|current| *current = Some(id.clone()));
+    f().map_err(|error| annotate(error, &id))
+}
+
+/// The correlation ID of the current operation, starting one if there is none yet
+pub fn current() -> String {
+    CORRELATION_ID.with_borrow_mut(|current| current.get_or_insert_with(generate).clone())
+}
+
+/// Appends the correlation ID to the message of the errors carrying one
+pub fn annotate(error: SearchError, id: &str) -> SearchError {
+    let suffix = |message: String| {
+        if message.contains(id) {
+            message
+        } else {
+            format!("{message} (correlation ID: {id})")
+        }
+    };
+    match error {
+        SearchError::InvalidQuery(message) => SearchError::InvalidQuery(suffix(message)),
+        SearchError::Internal(message) => SearchError::Internal(suffix(message)),
+        SearchError::ProviderError(details) => SearchError::ProviderError(ProviderErrorDetails {
+            message: suffix(details.message),
+            ..details
+        }),
+        error => error,
+    }
+}
+
+/// A random 128 bit identifier in hexadecimal, stable across replays
+fn generate() -> String {
+    deterministic::random_id()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn operations_get_distinct_ids() {
+        let mut first = None;
+        let _ = scope("search", || {
+            first = Some(current());
+            Ok(())
+        });
+        let second = scope("search", || Ok(current())).unwrap();
+
+        let first = first.unwrap();
+        assert_eq!(first.len(), 32);
+        assert_ne!(first, second);
+    }
+
+    #[test]
+    fn errors_are_annotated_with_the_operation_id() {
+        let mut id = String::new();
+        let error = scope::<()>("get", || {
+            id = current();
+            Err(SearchError::Internal("Server error: 500".to_string()))
+        })
+        .unwrap_err();
+
+        assert_eq!(
+            error,
+            SearchError::Internal(format!("Server error: 500 (correlation ID: {id})"))
+        );
+        assert_eq!(
+            annotate(SearchError::IndexNotFound, &id),
+            SearchError::IndexNotFound
+        );
+    }
+}
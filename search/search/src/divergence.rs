@@ -0,0 +1,158 @@
+//! Detection of durable replay divergences.
+//!
+//! A durable search stream replays the hits persisted in the oplog, then continues with a live
+//! query that skips them. When the documents of the index changed in the meantime, the live
+//! continuation doesn't line up with the persisted hits anymore and returns some of them again.
+//! Such divergences are logged with a report comparing digests of the persisted and live hits, and
+//! counted in the metrics of the operation as `replay-divergence` errors.
+
+use crate::golem::search::types::SearchHit;
+use crate::metrics;
+use log::warn;
+use std::collections::HashSet;
+use std::fmt::{Display, Formatter};
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+/// A difference between the results persisted in the oplog and the live results of the provider
+#[derive(Debug, Clone, PartialEq)]
+pub struct DivergenceReport {
+    pub operation: &'static str,
+    pub index: String,
+    pub persisted_hits: usize,
+    pub persisted_digest: String,
+    pub live_hits: usize,
+    pub live_digest: String,
+    /// Number of live hits that were already delivered from the oplog
+    pub repeated_hits: usize,
+}
+
+impl Display for DivergenceReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "operation={} index={} persisted_hits={} persisted_digest={} live_hits={} live_digest={} repeated_hits={}",
+            self.operation,
+            self.index,
+            self.persisted_hits,
+            self.persisted_digest,
+            self.live_hits,
+            self.live_digest,
+            self.repeated_hits
+        )
+    }
+}
+
+/// A stable digest of the ids of a list of hits, in order: the 64-bit FNV-1a hash of the ids, in
+/// hexadecimal
+pub fn digest<'a>(ids: impl IntoIterator<Item = &'a str>) -> String {
+    fnv_digest(ids)
+}
+
+/// Compares the hits persisted in the oplog with the ids of the hits the live continuation of the
+/// stream received from the provider, returning a report when some of them were already persisted
+pub fn compare_hits(
+    operation: &'static str,
+    index: &str,
+    persisted: &[SearchHit],
+    live_ids: &[String],
+) -> Option<DivergenceReport> {
+    let persisted_ids: HashSet<&str> = persisted.iter().map(|hit| hit.id.as_str()).collect();
+    let repeated_hits = live_ids
+        .iter()
+        .filter(|id| persisted_ids.contains(id.as_str()))
+        .count();
+    (repeated_hits > 0).then(|| DivergenceReport {
+        operation,
+        index: index.to_string(),
+        persisted_hits: persisted.len(),
+        persisted_digest: digest(persisted.iter().map(|hit| hit.id.as_str())),
+        live_hits: live_ids.len(),
+        live_digest: digest(live_ids.iter().map(String::as_str)),
+        repeated_hits,
+    })
+}
+
+/// Logs a divergence and counts it in the metrics of its operation
+pub fn report(report: &DivergenceReport) {
+    warn!("Durable replay diverged from the provider's live results: {report}");
+    metrics::record_divergence(report.operation);
+}
+
+/// The 64-bit FNV-1a hash of `parts`, each followed by a newline, in hexadecimal.
+///
+/// golem-search and golem-llm keep identical copies of this function in their `divergence`
+/// modules, so that the digests of both report the same way.
+fn fnv_digest<'a>(parts: impl IntoIterator<Item = &'a str>) -> String {
+    let mut hash = FNV_OFFSET_BASIS;
+    for part in parts {
+        for byte in part.bytes().chain(std::iter::once(b'\n')) {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(FNV_PRIME);
+        }
+    }
+    format!("{hash:016x}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hits(ids: &[&str]) -> Vec<SearchHit> {
+        ids.iter()
+            .map(|id| SearchHit {
+                id: id.to_string(),
+                score: None,
+                content: None,
+                highlights: None,
+            })
+            .collect()
+    }
+
+    fn ids(ids: &[&str]) -> Vec<String> {
+        ids.iter().map(|id| id.to_string()).collect()
+    }
+
+    #[test]
+    fn digests_depend_on_ids_and_their_order() {
+        assert_eq!(digest(["a", "b"]), digest(["a", "b"]));
+        assert_ne!(digest(["a", "b"]), digest(["b", "a"]));
+        assert_ne!(digest(["ab"]), digest(["a", "b"]));
+        assert_eq!(digest([]), format!("{FNV_OFFSET_BASIS:016x}"));
+    }
+
+    #[test]
+    fn matching_continuations_are_not_divergences() {
+        assert_eq!(
+            compare_hits(
+                "stream_search",
+                "books",
+                &hits(&["a", "b"]),
+                &ids(&["c", "d"])
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn repeated_hits_are_divergences() {
+        let report = compare_hits(
+            "stream_search",
+            "books",
+            &hits(&["a", "b"]),
+            &ids(&["b", "c"]),
+        )
+        .unwrap();
+        assert_eq!(report.operation, "stream_search");
+        assert_eq!(report.index, "books");
+        assert_eq!(report.persisted_hits, 2);
+        assert_eq!(report.persisted_digest, digest(["a", "b"]));
+        assert_eq!(report.live_hits, 2);
+        assert_eq!(report.live_digest, digest(["b", "c"]));
+        assert_eq!(report.repeated_hits, 1);
+        assert!(report
+            .to_string()
+            .starts_with("operation=stream_search index=books persisted_hits=2"));
+    }
+}
@@ -1,12 +1,74 @@
+use crate::config::get_config_with_default;
 use crate::golem::search::core::Guest;
-use crate::golem::search::types::{IndexName, SearchHit, SearchQuery};
+use crate::golem::search::types::{IndexName, SearchError, SearchHit, SearchQuery};
+use crate::{circuit_breaker, correlation, metrics, retry};
 use golem_rust::wasm_rpc::Pollable;
 use std::marker::PhantomData;
+use std::time::{Duration, Instant};
 
 pub struct DurableSearch<Impl> {
     phantom: PhantomData<Impl>,
 }
 
+/// The search stream of the components built with the `no-streaming` feature. It has no values,
+/// as `stream-search` fails with `unsupported` instead of opening a stream, so the streaming code
+/// of the provider is left out of the component.
+#[cfg(feature = "no-streaming")]
+pub enum NoSearchStream {}
+
+#[cfg(feature = "no-streaming")]
+impl crate::golem::search::core::GuestSearchStream for NoSearchStream {
+    fn get_next(&self) -> Option<Vec<SearchHit>> {
+        match *self {}
+    }
+
+    fn blocking_get_next(&self) -> Vec<SearchHit> {
+        match *self {}
+    }
+}
+
+/// The search stream of the components built without durability: the provider's stream, with
+/// the post-filters of the query applied to each batch as the durable stream does
+#[cfg(all(not(feature = "durability"), not(feature = "no-streaming")))]
+pub struct PostFilteredSearchStream<Stream> {
+    stream: Stream,
+    config: Option<crate::golem::search::types::SearchConfig>,
+}
+
+#[cfg(all(not(feature = "durability"), not(feature = "no-streaming")))]
+impl<Stream: crate::golem::search::core::GuestSearchStream> PostFilteredSearchStream<Stream> {
+    /// Applies the post-filters to a batch, none if they removed all of its hits, as an empty
+    /// batch would end the stream
+    fn post_filter(&self, batch: Vec<SearchHit>) -> Option<Vec<SearchHit>> {
+        if batch.is_empty() {
+            return Some(batch);
+        }
+        let batch = crate::post_processing::filter_hits(self.config.as_ref(), batch);
+        (!batch.is_empty()).then_some(batch)
+    }
+}
+
+#[cfg(all(not(feature = "durability"), not(feature = "no-streaming")))]
+impl<Stream: crate::golem::search::core::GuestSearchStream>
+    crate::golem::search::core::GuestSearchStream for PostFilteredSearchStream<Stream>
+{
+    fn get_next(&self) -> Option<Vec<SearchHit>> {
+        loop {
+            if let Some(batch) = self.post_filter(self.stream.get_next()?) {
+                return Some(batch);
+            }
+        }
+    }
+
+    fn blocking_get_next(&self) -> Vec<SearchHit> {
+        loop {
+            if let Some(batch) = self.post_filter(self.stream.blocking_get_next()) {
+                return batch;
+            }
+        }
+    }
+}
+
 pub trait ExtendedGuest: Guest + 'static {
     fn unwrapped_stream(index: IndexName, query: SearchQuery) -> Self::SearchStream;
 
@@ -30,86 +92,234 @@ pub trait ExtendedGuest: Guest + 'static {
     fn subscribe(stream: &Self::SearchStream) -> Pollable;
 }
 
+/// Sends an operation to the provider through the circuit breaker with a new correlation ID,
+/// recording its metrics. The requests of the operation share the retry budget of the invocation.
+fn call_provider<T>(
+    operation: &'static str,
+    f: impl FnOnce() -> Result<T, SearchError>,
+) -> Result<T, SearchError> {
+    retry::invocation_scope(|| {
+        correlation::scope(operation, || {
+            circuit_breaker::guard(|| metrics::record(operation, f))
+        })
+    })
+}
+
+/// Controls how often a durable search stream persists the hits it received.
+///
+/// By default every batch is persisted as its own oplog entry. With a `min_hits` threshold, the
+/// stream keeps reading batches from the provider until it has at least that many hits (or
+/// `max_delay` has elapsed, or the stream has no more hits ready) and persists them as a single
+/// entry. The batches are still returned to the caller one by one, and every hit is persisted
+/// before it is returned, so replay delivers exactly the same batches.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StreamCheckpointPolicy {
+    pub min_hits: usize,
+    pub max_delay: Option<Duration>,
+}
+
+impl StreamCheckpointPolicy {
+    /// Creates the policy from `SEARCH_PROVIDER_STREAM_CHECKPOINT_HITS` and
+    /// `SEARCH_PROVIDER_STREAM_CHECKPOINT_MS`
+    pub fn from_config() -> Self {
+        let min_hits = get_config_with_default("SEARCH_PROVIDER_STREAM_CHECKPOINT_HITS", "0")
+            .parse()
+            .unwrap_or(0);
+        let max_delay_ms: u64 =
+            get_config_with_default("SEARCH_PROVIDER_STREAM_CHECKPOINT_MS", "0")
+                .parse()
+                .unwrap_or(0);
+
+        Self {
+            min_hits,
+            max_delay: (max_delay_ms > 0).then(|| Duration::from_millis(max_delay_ms)),
+        }
+    }
+
+    /// Reads the batches to persist in the next checkpoint. Always reads at least one batch, and
+    /// stops after an empty or missing one.
+    pub fn collect(
+        &self,
+        mut next: impl FnMut() -> Option<Vec<SearchHit>>,
+    ) -> Vec<Option<Vec<SearchHit>>> {
+        let start = Instant::now();
+        let mut batches = Vec::new();
+        let mut hits = 0;
+
+        loop {
+            let batch = next();
+            let exhausted = match &batch {
+                Some(batch) if !batch.is_empty() => {
+                    hits += batch.len();
+                    false
+                }
+                _ => true,
+            };
+            batches.push(batch);
+
+            let timed_out = self
+                .max_delay
+                .is_some_and(|max_delay| start.elapsed() >= max_delay);
+            if exhausted || hits >= self.min_hits || timed_out {
+                break batches;
+            }
+        }
+    }
+}
+
 /// When the durability feature flag is off, wrapping with `DurableSearch` is just a passthrough
 #[cfg(not(feature = "durability"))]
 mod passthrough_impl {
-    use crate::durability::{DurableSearch, ExtendedGuest};
+    use crate::durability::{call_provider, DurableSearch, ExtendedGuest};
+    #[cfg(any(feature = "no-streaming", feature = "no-schema-ops"))]
+    use crate::error::unsupported;
     use crate::golem::search::core::{Guest, SearchStream};
     use crate::golem::search::types::{
-        Doc, DocumentId, IndexName, Schema, SearchError, SearchQuery, SearchResults,
+        Doc, DocumentId, IndexName, IndexPage, PageRequest, Schema, SearchError, SearchQuery,
+        SearchResults,
     };
+    use crate::{fallback, index_registry, pagination, post_processing, retry};
 
     impl<Impl: ExtendedGuest> Guest for DurableSearch<Impl> {
-        type SearchStream = Impl::SearchStream;
+        #[cfg(not(feature = "no-streaming"))]
+        type SearchStream = crate::durability::PostFilteredSearchStream<Impl::SearchStream>;
+        #[cfg(feature = "no-streaming")]
+        type SearchStream = crate::durability::NoSearchStream;
 
         fn create_index(name: IndexName, schema: Option<Schema>) -> Result<(), SearchError> {
-            Impl::create_index(name, schema)
+            call_provider("create_index", || {
+                Impl::create_index(name.clone(), schema.clone())
+            })
+            .inspect(|_| {
+                index_registry::applied(&name, schema);
+                index_registry::created(&name);
+            })
         }
 
         fn delete_index(name: IndexName) -> Result<(), SearchError> {
-            Impl::delete_index(name)
+            call_provider("delete_index", || Impl::delete_index(name.clone()))
+                .inspect(|_| index_registry::forget(&name))
         }
 
-        fn list_indexes() -> Result<Vec<IndexName>, SearchError> {
-            Impl::list_indexes()
+        fn list_indexes(page: PageRequest) -> Result<IndexPage, SearchError> {
+            call_provider("list_indexes", || Impl::list_indexes(page))
         }
 
         fn upsert(index: IndexName, doc: Doc) -> Result<(), SearchError> {
-            Impl::upsert(index, doc)
+            call_provider("upsert", || Impl::upsert(index, doc))
         }
 
         fn upsert_many(index: IndexName, docs: Vec<Doc>) -> Result<(), SearchError> {
-            Impl::upsert_many(index, docs)
+            call_provider("upsert_many", || Impl::upsert_many(index, docs))
         }
 
         fn delete(index: IndexName, id: DocumentId) -> Result<(), SearchError> {
-            Impl::delete(index, id)
+            call_provider("delete", || Impl::delete(index, id))
         }
 
         fn delete_many(index: IndexName, ids: Vec<DocumentId>) -> Result<(), SearchError> {
-            Impl::delete_many(index, ids)
+            call_provider("delete_many", || Impl::delete_many(index, ids))
         }
 
         fn get(index: IndexName, id: DocumentId) -> Result<Option<Doc>, SearchError> {
-            Impl::get(index, id)
+            call_provider("get", || Impl::get(index, id))
         }
 
         fn search(index: IndexName, query: SearchQuery) -> Result<SearchResults, SearchError> {
-            Impl::search(index, query)
+            #[cfg(feature = "query-intent")]
+            let query = crate::query_intent::rewrite_query(query);
+            let original_query = query.clone();
+            retry::invocation_scope(|| {
+                fallback::search_with_fallbacks(query, |query| {
+                    call_provider("search", || Impl::search(index.clone(), query))
+                })
+            })
+            .map(|results| {
+                let results = pagination::with_navigation(&original_query, results);
+                post_processing::apply(original_query.config.as_ref(), results)
+            })
         }
 
+        #[cfg(not(feature = "no-streaming"))]
         fn stream_search(
             index: IndexName,
             query: SearchQuery,
         ) -> Result<SearchStream, SearchError> {
-            Impl::stream_search(index, query)
+            #[cfg(feature = "query-intent")]
+            let query = crate::query_intent::rewrite_query(query);
+            post_processing::check_stream_query(&query)?;
+            let config = query.config.clone();
+            call_provider("stream_search", || Impl::stream_search(index, query)).map(|stream| {
+                SearchStream::new(crate::durability::PostFilteredSearchStream {
+                    stream: stream.into_inner::<Impl::SearchStream>(),
+                    config,
+                })
+            })
+        }
+
+        #[cfg(feature = "no-streaming")]
+        fn stream_search(
+            _index: IndexName,
+            _query: SearchQuery,
+        ) -> Result<SearchStream, SearchError> {
+            Err(unsupported("stream-search"))
         }
 
+        #[cfg(not(feature = "no-schema-ops"))]
         fn get_schema(index: IndexName) -> Result<Schema, SearchError> {
-            Impl::get_schema(index)
+            call_provider("get_schema", || Impl::get_schema(index))
         }
 
+        #[cfg(feature = "no-schema-ops")]
+        fn get_schema(_index: IndexName) -> Result<Schema, SearchError> {
+            Err(unsupported("get-schema"))
+        }
+
+        #[cfg(not(feature = "no-schema-ops"))]
         fn update_schema(index: IndexName, schema: Schema) -> Result<(), SearchError> {
-            Impl::update_schema(index, schema)
+            call_provider("update_schema", || {
+                Impl::update_schema(index.clone(), schema.clone())
+            })
+            .inspect(|_| index_registry::applied(&index, Some(schema)))
+        }
+
+        #[cfg(feature = "no-schema-ops")]
+        fn update_schema(_index: IndexName, _schema: Schema) -> Result<(), SearchError> {
+            Err(unsupported("update-schema"))
         }
     }
 }
 
 #[cfg(feature = "durability")]
 mod durable_impl {
-    use crate::durability::{DurableSearch, ExtendedGuest};
-    use crate::golem::search::core::{Guest, GuestSearchStream, SearchStream};
+    use crate::config::get_upsert_chunk_size_config;
+    use crate::durability::{call_provider, DurableSearch, ExtendedGuest};
+    use crate::golem::search::core::{Guest, SearchStream};
     use crate::golem::search::types::{
-        Doc, DocumentId, IndexName, Schema, SearchError, SearchHit, SearchQuery, SearchResults,
-    };
-    use golem_rust::bindings::golem::durability::durability::{
-        DurableFunctionType, LazyInitializedPollable,
+        Doc, DocumentId, IndexName, IndexPage, PageRequest, Schema, SearchError, SearchQuery,
+        SearchResults,
     };
+    use crate::{fallback, index_registry, pagination, post_processing, retry};
+    use golem_rust::bindings::golem::durability::durability::DurableFunctionType;
     use golem_rust::durability::Durability;
-    use golem_rust::wasm_rpc::Pollable;
     use golem_rust::{with_persistence_level, FromValueAndType, IntoValue, PersistenceLevel};
-    use std::cell::RefCell;
-    use std::fmt::{Display, Formatter};
+
+    #[cfg(any(feature = "no-streaming", feature = "no-schema-ops"))]
+    use crate::error::unsupported;
+
+    #[cfg(not(feature = "no-streaming"))]
+    use {
+        crate::divergence,
+        crate::durability::StreamCheckpointPolicy,
+        crate::golem::search::core::GuestSearchStream,
+        crate::golem::search::types::{SearchConfig, SearchHit},
+        crate::metrics,
+        golem_rust::bindings::golem::durability::durability::LazyInitializedPollable,
+        golem_rust::wasm_rpc::Pollable,
+        std::cell::RefCell,
+        std::collections::{HashSet, VecDeque},
+        std::fmt::{Display, Formatter},
+    };
 
     #[derive(Debug, Clone, IntoValue)]
     struct CreateIndexInput {
@@ -122,6 +332,11 @@ mod durable_impl {
         name: IndexName,
     }
 
+    #[derive(Debug, Clone, IntoValue)]
+    struct ListIndexesInput {
+        page: PageRequest,
+    }
+
     #[derive(Debug, Clone, IntoValue)]
     struct UpsertInput {
         index: IndexName,
@@ -158,32 +373,39 @@ mod durable_impl {
         query: SearchQuery,
     }
 
+    #[cfg(not(feature = "no-streaming"))]
     #[derive(Debug, Clone, IntoValue)]
     struct StreamSearchInput {
         index: IndexName,
         query: SearchQuery,
     }
 
+    #[cfg(not(feature = "no-schema-ops"))]
     #[derive(Debug, Clone, IntoValue)]
     struct GetSchemaInput {
         index: IndexName,
     }
 
+    #[cfg(not(feature = "no-schema-ops"))]
     #[derive(Debug, Clone, IntoValue)]
     struct UpdateSchemaInput {
         index: IndexName,
         schema: Schema,
     }
 
+    #[cfg(not(feature = "no-streaming"))]
     #[derive(Debug, IntoValue)]
     struct NoInput;
 
+    #[cfg(not(feature = "no-streaming"))]
     #[derive(Debug, Clone, FromValueAndType, IntoValue)]
     struct NoOutput;
 
+    #[cfg(not(feature = "no-streaming"))]
     #[derive(Debug, FromValueAndType, IntoValue)]
     struct UnusedError;
 
+    #[cfg(not(feature = "no-streaming"))]
     impl Display for UnusedError {
         fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
             write!(f, "UnusedError")
@@ -194,8 +416,8 @@ mod durable_impl {
     struct VoidResult;
 
     #[derive(Debug, Clone, FromValueAndType, IntoValue)]
-    struct IndexNamesResult {
-        names: Vec<IndexName>,
+    struct IndexPageResult {
+        page: IndexPage,
     }
 
     #[derive(Debug, Clone, FromValueAndType, IntoValue)]
@@ -208,13 +430,17 @@ mod durable_impl {
         results: SearchResults,
     }
 
+    #[cfg(not(feature = "no-schema-ops"))]
     #[derive(Debug, Clone, FromValueAndType, IntoValue)]
     struct SchemaWrapper {
         schema: Schema,
     }
 
     impl<Impl: ExtendedGuest> Guest for DurableSearch<Impl> {
+        #[cfg(not(feature = "no-streaming"))]
         type SearchStream = DurableSearchStream<Impl>;
+        #[cfg(feature = "no-streaming")]
+        type SearchStream = crate::durability::NoSearchStream;
 
         fn create_index(name: IndexName, schema: Option<Schema>) -> Result<(), SearchError> {
             let durability = Durability::<VoidResult, SearchError>::new(
@@ -224,18 +450,30 @@ mod durable_impl {
             );
             if durability.is_live() {
                 let result = with_persistence_level(PersistenceLevel::PersistNothing, || {
-                    Impl::create_index(name.clone(), schema.clone())
+                    call_provider("create_index", || {
+                        Impl::create_index(name.clone(), schema.clone())
+                    })
                 });
                 match result {
                     Ok(()) => {
-                        let _ = durability
-                            .persist_infallible(CreateIndexInput { name, schema }, VoidResult);
+                        index_registry::applied(&name, schema.clone());
+                        let _ = durability.persist_infallible(
+                            CreateIndexInput {
+                                name: name.clone(),
+                                schema,
+                            },
+                            VoidResult,
+                        );
+                        // After the persisted result, where the replay reads the time back
+                        index_registry::created(&name);
                         Ok(())
                     }
                     Err(e) => Err(e),
                 }
             } else {
                 let _: VoidResult = durability.replay_infallible();
+                index_registry::applied(&name, schema);
+                index_registry::created(&name);
                 Ok(())
             }
         }
@@ -248,10 +486,11 @@ mod durable_impl {
             );
             if durability.is_live() {
                 let result = with_persistence_level(PersistenceLevel::PersistNothing, || {
-                    Impl::delete_index(name.clone())
+                    call_provider("delete_index", || Impl::delete_index(name.clone()))
                 });
                 match result {
                     Ok(()) => {
+                        index_registry::forget(&name);
                         let _ =
                             durability.persist_infallible(DeleteIndexInput { name }, VoidResult);
                         Ok(())
@@ -260,35 +499,36 @@ mod durable_impl {
                 }
             } else {
                 let _: VoidResult = durability.replay_infallible();
+                index_registry::forget(&name);
                 Ok(())
             }
         }
 
-        fn list_indexes() -> Result<Vec<IndexName>, SearchError> {
-            let durability = Durability::<IndexNamesResult, SearchError>::new(
+        fn list_indexes(page: PageRequest) -> Result<IndexPage, SearchError> {
+            let durability = Durability::<IndexPageResult, SearchError>::new(
                 "golem_search",
                 "list_indexes",
                 DurableFunctionType::ReadRemote,
             );
             if durability.is_live() {
                 let result = with_persistence_level(PersistenceLevel::PersistNothing, || {
-                    Impl::list_indexes()
+                    call_provider("list_indexes", || Impl::list_indexes(page.clone()))
                 });
                 match result {
-                    Ok(names) => {
+                    Ok(index_page) => {
                         let _ = durability.persist_infallible(
-                            NoInput,
-                            IndexNamesResult {
-                                names: names.clone(),
+                            ListIndexesInput { page },
+                            IndexPageResult {
+                                page: index_page.clone(),
                             },
                         );
-                        Ok(names)
+                        Ok(index_page)
                     }
                     Err(e) => Err(e),
                 }
             } else {
-                let wrapper: IndexNamesResult = durability.replay_infallible();
-                Ok(wrapper.names)
+                let wrapper: IndexPageResult = durability.replay_infallible();
+                Ok(wrapper.page)
             }
         }
 
@@ -300,7 +540,7 @@ mod durable_impl {
             );
             if durability.is_live() {
                 let result = with_persistence_level(PersistenceLevel::PersistNothing, || {
-                    Impl::upsert(index.clone(), doc.clone())
+                    call_provider("upsert", || Impl::upsert(index.clone(), doc.clone()))
                 });
                 match result {
                     Ok(()) => {
@@ -316,28 +556,21 @@ mod durable_impl {
             }
         }
 
+        /// Large batches are sent in chunks, each persisted on its own, so that a crash in the
+        /// middle of the batch resumes from the last acknowledged chunk instead of sending
+        /// everything again
         fn upsert_many(index: IndexName, docs: Vec<Doc>) -> Result<(), SearchError> {
-            let durability = Durability::<VoidResult, SearchError>::new(
-                "golem_search",
-                "upsert_many",
-                DurableFunctionType::WriteRemote,
-            );
-            if durability.is_live() {
-                let result = with_persistence_level(PersistenceLevel::PersistNothing, || {
-                    Impl::upsert_many(index.clone(), docs.clone())
-                });
-                match result {
-                    Ok(()) => {
-                        let _ = durability
-                            .persist_infallible(UpsertManyInput { index, docs }, VoidResult);
-                        Ok(())
-                    }
-                    Err(e) => Err(e),
+            let chunk_size = get_upsert_chunk_size_config();
+            if docs.len() <= chunk_size {
+                return upsert_chunk::<Impl>(index, docs);
+            }
+
+            retry::invocation_scope(|| {
+                for chunk in docs.chunks(chunk_size) {
+                    upsert_chunk::<Impl>(index.clone(), chunk.to_vec())?;
                 }
-            } else {
-                let _: VoidResult = durability.replay_infallible();
                 Ok(())
-            }
+            })
         }
 
         fn delete(index: IndexName, id: DocumentId) -> Result<(), SearchError> {
@@ -348,7 +581,7 @@ mod durable_impl {
             );
             if durability.is_live() {
                 let result = with_persistence_level(PersistenceLevel::PersistNothing, || {
-                    Impl::delete(index.clone(), id.clone())
+                    call_provider("delete", || Impl::delete(index.clone(), id.clone()))
                 });
                 match result {
                     Ok(()) => {
@@ -372,7 +605,9 @@ mod durable_impl {
             );
             if durability.is_live() {
                 let result = with_persistence_level(PersistenceLevel::PersistNothing, || {
-                    Impl::delete_many(index.clone(), ids.clone())
+                    call_provider("delete_many", || {
+                        Impl::delete_many(index.clone(), ids.clone())
+                    })
                 });
                 match result {
                     Ok(()) => {
@@ -396,7 +631,7 @@ mod durable_impl {
             );
             if durability.is_live() {
                 let result = with_persistence_level(PersistenceLevel::PersistNothing, || {
-                    Impl::get(index.clone(), id.clone())
+                    call_provider("get", || Impl::get(index.clone(), id.clone()))
                 });
                 match result {
                     Ok(doc) => {
@@ -415,6 +650,9 @@ mod durable_impl {
         }
 
         fn search(index: IndexName, query: SearchQuery) -> Result<SearchResults, SearchError> {
+            #[cfg(feature = "query-intent")]
+            let query = crate::query_intent::rewrite_query(query);
+            let config = query.config.clone();
             let durability = Durability::<SearchResultsWrapper, SearchError>::new(
                 "golem_search",
                 "search",
@@ -422,30 +660,39 @@ mod durable_impl {
             );
             if durability.is_live() {
                 let result = with_persistence_level(PersistenceLevel::PersistNothing, || {
-                    Impl::search(index.clone(), query.clone())
+                    retry::invocation_scope(|| {
+                        fallback::search_with_fallbacks(query.clone(), |query| {
+                            call_provider("search", || Impl::search(index.clone(), query))
+                        })
+                    })
                 });
                 match result {
                     Ok(results) => {
+                        let results = pagination::with_navigation(&query, results);
                         let _ = durability.persist_infallible(
                             SearchInput { index, query },
                             SearchResultsWrapper {
                                 results: results.clone(),
                             },
                         );
-                        Ok(results)
+                        Ok(post_processing::apply(config.as_ref(), results))
                     }
                     Err(e) => Err(e),
                 }
             } else {
                 let wrapper: SearchResultsWrapper = durability.replay_infallible();
-                Ok(wrapper.results)
+                Ok(post_processing::apply(config.as_ref(), wrapper.results))
             }
         }
 
+        #[cfg(not(feature = "no-streaming"))]
         fn stream_search(
             index: IndexName,
             query: SearchQuery,
         ) -> Result<SearchStream, SearchError> {
+            #[cfg(feature = "query-intent")]
+            let query = crate::query_intent::rewrite_query(query);
+            post_processing::check_stream_query(&query)?;
             let durability = Durability::<NoOutput, UnusedError>::new(
                 "golem_search",
                 "stream_search",
@@ -453,10 +700,12 @@ mod durable_impl {
             );
             if durability.is_live() {
                 let result = with_persistence_level(PersistenceLevel::PersistNothing, || {
-                    SearchStream::new(DurableSearchStream::<Impl>::live(Impl::unwrapped_stream(
-                        index.clone(),
-                        query.clone(),
-                    )))
+                    metrics::record_infallible("stream_search", || {
+                        SearchStream::new(DurableSearchStream::<Impl>::live(
+                            Impl::unwrapped_stream(index.clone(), query.clone()),
+                            query.config.clone(),
+                        ))
+                    })
                 });
                 let _ = durability.persist_infallible(StreamSearchInput { index, query }, NoOutput);
                 Ok(result)
@@ -468,6 +717,16 @@ mod durable_impl {
             }
         }
 
+        /// Fails without a durable function call, so there is nothing to replay
+        #[cfg(feature = "no-streaming")]
+        fn stream_search(
+            _index: IndexName,
+            _query: SearchQuery,
+        ) -> Result<SearchStream, SearchError> {
+            Err(unsupported("stream-search"))
+        }
+
+        #[cfg(not(feature = "no-schema-ops"))]
         fn get_schema(index: IndexName) -> Result<Schema, SearchError> {
             let durability = Durability::<SchemaWrapper, SearchError>::new(
                 "golem_search",
@@ -476,7 +735,7 @@ mod durable_impl {
             );
             if durability.is_live() {
                 let result = with_persistence_level(PersistenceLevel::PersistNothing, || {
-                    Impl::get_schema(index.clone())
+                    call_provider("get_schema", || Impl::get_schema(index.clone()))
                 });
                 match result {
                     Ok(schema) => {
@@ -496,6 +755,12 @@ mod durable_impl {
             }
         }
 
+        #[cfg(feature = "no-schema-ops")]
+        fn get_schema(_index: IndexName) -> Result<Schema, SearchError> {
+            Err(unsupported("get-schema"))
+        }
+
+        #[cfg(not(feature = "no-schema-ops"))]
         fn update_schema(index: IndexName, schema: Schema) -> Result<(), SearchError> {
             let durability = Durability::<VoidResult, SearchError>::new(
                 "golem_search",
@@ -504,10 +769,13 @@ mod durable_impl {
             );
             if durability.is_live() {
                 let result = with_persistence_level(PersistenceLevel::PersistNothing, || {
-                    Impl::update_schema(index.clone(), schema.clone())
+                    call_provider("update_schema", || {
+                        Impl::update_schema(index.clone(), schema.clone())
+                    })
                 });
                 match result {
                     Ok(()) => {
+                        index_registry::applied(&index, Some(schema.clone()));
                         let _ = durability
                             .persist_infallible(UpdateSchemaInput { index, schema }, VoidResult);
                         Ok(())
@@ -516,9 +784,44 @@ mod durable_impl {
                 }
             } else {
                 let _: VoidResult = durability.replay_infallible();
+                index_registry::applied(&index, Some(schema));
                 Ok(())
             }
         }
+
+        #[cfg(feature = "no-schema-ops")]
+        fn update_schema(_index: IndexName, _schema: Schema) -> Result<(), SearchError> {
+            Err(unsupported("update-schema"))
+        }
+    }
+
+    fn upsert_chunk<Impl: ExtendedGuest>(
+        index: IndexName,
+        docs: Vec<Doc>,
+    ) -> Result<(), SearchError> {
+        let durability = Durability::<VoidResult, SearchError>::new(
+            "golem_search",
+            "upsert_many",
+            DurableFunctionType::WriteRemote,
+        );
+        if durability.is_live() {
+            let result = with_persistence_level(PersistenceLevel::PersistNothing, || {
+                call_provider("upsert_many", || {
+                    Impl::upsert_many(index.clone(), docs.clone())
+                })
+            });
+            match result {
+                Ok(()) => {
+                    let _ =
+                        durability.persist_infallible(UpsertManyInput { index, docs }, VoidResult);
+                    Ok(())
+                }
+                Err(e) => Err(e),
+            }
+        } else {
+            let _: VoidResult = durability.replay_infallible();
+            Ok(())
+        }
     }
 
     /// Represents the durable search stream's state
@@ -533,10 +836,13 @@ mod durable_impl {
     /// When reaching the end of the replay mode, if the replayed stream was not finished yet,
     /// the retry query implemented in `ExtendedGuest` is used to create a new Search response
     /// stream and continue the search seamlessly.
+    #[cfg(not(feature = "no-streaming"))]
     enum DurableSearchStreamState<Impl: ExtendedGuest> {
         Live {
             stream: Impl::SearchStream,
             pollables: Vec<LazyInitializedPollable>,
+            /// IDs of the hits delivered so far, tracked only after resuming with a retry query
+            seen: Option<HashSet<String>>,
         },
         Replay {
             index: IndexName,
@@ -547,23 +853,35 @@ mod durable_impl {
         },
     }
 
+    #[cfg(not(feature = "no-streaming"))]
     pub struct DurableSearchStream<Impl: ExtendedGuest> {
         state: RefCell<Option<DurableSearchStreamState<Impl>>>,
         subscription: RefCell<Option<Pollable>>,
+        checkpoint_policy: StreamCheckpointPolicy,
+        /// Batches of the last checkpoint that have not been returned yet
+        buffered: RefCell<VecDeque<Option<Vec<SearchHit>>>>,
+        /// Search config of the query, whose post-filters are applied to every batch
+        config: Option<SearchConfig>,
     }
 
+    #[cfg(not(feature = "no-streaming"))]
     impl<Impl: ExtendedGuest> DurableSearchStream<Impl> {
-        fn live(stream: Impl::SearchStream) -> Self {
+        fn live(stream: Impl::SearchStream, config: Option<SearchConfig>) -> Self {
             Self {
                 state: RefCell::new(Some(DurableSearchStreamState::Live {
                     stream,
                     pollables: Vec::new(),
+                    seen: None,
                 })),
                 subscription: RefCell::new(None),
+                checkpoint_policy: StreamCheckpointPolicy::from_config(),
+                buffered: RefCell::new(VecDeque::new()),
+                config,
             }
         }
 
         fn replay(index: IndexName, query: SearchQuery) -> Self {
+            let config = query.config.clone();
             Self {
                 state: RefCell::new(Some(DurableSearchStreamState::Replay {
                     index,
@@ -573,9 +891,20 @@ mod durable_impl {
                     finished: false,
                 })),
                 subscription: RefCell::new(None),
+                checkpoint_policy: StreamCheckpointPolicy::from_config(),
+                buffered: RefCell::new(VecDeque::new()),
+                config,
             }
         }
 
+        /// Returns the first batch of a checkpoint and buffers the rest for the next calls
+        fn take_first(&self, batches: Vec<Option<Vec<SearchHit>>>) -> Option<Vec<SearchHit>> {
+            let mut batches = VecDeque::from(batches);
+            let first = batches.pop_front().flatten();
+            *self.buffered.borrow_mut() = batches;
+            first
+        }
+
         fn subscribe(&self) -> Pollable {
             let mut state = self.state.borrow_mut();
             match &mut *state {
@@ -593,6 +922,7 @@ mod durable_impl {
         }
     }
 
+    #[cfg(not(feature = "no-streaming"))]
     impl<Impl: ExtendedGuest> Drop for DurableSearchStream<Impl> {
         fn drop(&mut self) {
             let _ = self.subscription.take();
@@ -600,6 +930,7 @@ mod durable_impl {
                 Some(DurableSearchStreamState::Live {
                     mut pollables,
                     stream,
+                    ..
                 }) => {
                     with_persistence_level(PersistenceLevel::PersistNothing, move || {
                         pollables.clear();
@@ -614,22 +945,61 @@ mod durable_impl {
         }
     }
 
-    impl<Impl: ExtendedGuest> GuestSearchStream for DurableSearchStream<Impl> {
-        fn get_next(&self) -> Option<Vec<SearchHit>> {
-            let durability = Durability::<Option<Vec<SearchHit>>, UnusedError>::new(
+    /// Reads the next batch of a resumed stream, dropping the hits that were already delivered.
+    ///
+    /// Continuing with an offset can return some documents again if the index changed in the
+    /// meantime. Batches that only contain such duplicates are skipped, so an empty batch still
+    /// means the end of the stream. The ids of all the hits read from the provider are added to
+    /// `received`, when given, to detect such divergences.
+    #[cfg(not(feature = "no-streaming"))]
+    fn next_unseen<Impl: ExtendedGuest>(
+        stream: &Impl::SearchStream,
+        seen: &mut HashSet<String>,
+        mut received: Option<&mut Vec<String>>,
+    ) -> Option<Vec<SearchHit>> {
+        loop {
+            let mut batch = stream.get_next()?;
+            if batch.is_empty() {
+                return Some(batch);
+            }
+            if let Some(received) = received.as_deref_mut() {
+                received.extend(batch.iter().map(|hit| hit.id.clone()));
+            }
+
+            batch.retain(|hit| seen.insert(hit.id.clone()));
+            if !batch.is_empty() {
+                return Some(batch);
+            }
+        }
+    }
+
+    #[cfg(not(feature = "no-streaming"))]
+    impl<Impl: ExtendedGuest> DurableSearchStream<Impl> {
+        /// Reads the next batch of the stream as the provider returned it, before post-filtering
+        fn next_batch(&self) -> Option<Vec<SearchHit>> {
+            if let Some(batch) = self.buffered.borrow_mut().pop_front() {
+                return batch;
+            }
+
+            let durability = Durability::<Vec<Option<Vec<SearchHit>>>, UnusedError>::new(
                 "golem_search",
-                "get_next",
+                "stream_search",
                 DurableFunctionType::ReadRemote,
             );
             if durability.is_live() {
                 let mut state = self.state.borrow_mut();
-                let (result, new_live_stream) = match &*state {
-                    Some(DurableSearchStreamState::Live { stream, .. }) => {
-                        let result =
+                let (result, new_live_stream) = match &mut *state {
+                    Some(DurableSearchStreamState::Live { stream, seen, .. }) => {
+                        let batches =
                             with_persistence_level(PersistenceLevel::PersistNothing, || {
-                                stream.get_next()
+                                retry::invocation_scope(|| {
+                                    self.checkpoint_policy.collect(|| match seen {
+                                        Some(seen) => next_unseen::<Impl>(stream, seen, None),
+                                        None => stream.get_next(),
+                                    })
+                                })
                             });
-                        (durability.persist_infallible(NoInput, result.clone()), None)
+                        (durability.persist_infallible(NoInput, batches), None)
                     }
                     Some(DurableSearchStreamState::Replay {
                         index,
@@ -639,11 +1009,13 @@ mod durable_impl {
                         finished,
                     }) => {
                         if *finished {
-                            (None, None)
+                            (vec![None], None)
                         } else {
                             let extended_query = Impl::retry_query(query, partial_result);
+                            let mut seen: HashSet<String> =
+                                partial_result.iter().map(|hit| hit.id.clone()).collect();
 
-                            let (stream, first_live_result) =
+                            let (stream, first_live_batches) =
                                 with_persistence_level(PersistenceLevel::PersistNothing, || {
                                     let stream = <Impl as ExtendedGuest>::unwrapped_stream(
                                         index.clone(),
@@ -654,12 +1026,29 @@ mod durable_impl {
                                         lazy_initialized_pollable.set(Impl::subscribe(&stream));
                                     }
 
-                                    let next = stream.get_next();
-                                    (stream, next)
+                                    let mut received = Vec::new();
+                                    let batches = retry::invocation_scope(|| {
+                                        self.checkpoint_policy.collect(|| {
+                                            next_unseen::<Impl>(
+                                                &stream,
+                                                &mut seen,
+                                                Some(&mut received),
+                                            )
+                                        })
+                                    });
+                                    if let Some(report) = divergence::compare_hits(
+                                        "stream_search",
+                                        index,
+                                        partial_result,
+                                        &received,
+                                    ) {
+                                        divergence::report(&report);
+                                    }
+                                    (stream, batches)
                                 });
-                            durability.persist_infallible(NoInput, first_live_result.clone());
+                            durability.persist_infallible(NoInput, first_live_batches.clone());
 
-                            (first_live_result, Some(stream))
+                            (first_live_batches, Some((stream, seen)))
                         }
                     }
                     None => {
@@ -667,7 +1056,7 @@ mod durable_impl {
                     }
                 };
 
-                if let Some(stream) = new_live_stream {
+                if let Some((stream, seen)) = new_live_stream {
                     let pollables = match state.take() {
                         Some(DurableSearchStreamState::Live { pollables, .. }) => pollables,
                         Some(DurableSearchStreamState::Replay { pollables, .. }) => pollables,
@@ -675,12 +1064,16 @@ mod durable_impl {
                             unreachable!()
                         }
                     };
-                    *state = Some(DurableSearchStreamState::Live { stream, pollables });
+                    *state = Some(DurableSearchStreamState::Live {
+                        stream,
+                        pollables,
+                        seen: Some(seen),
+                    });
                 }
 
-                result
+                self.take_first(result)
             } else {
-                let result: Option<Vec<SearchHit>> = durability.replay_infallible();
+                let batches: Vec<Option<Vec<SearchHit>>> = durability.replay_infallible();
                 let mut state = self.state.borrow_mut();
                 match &mut *state {
                     Some(DurableSearchStreamState::Live { .. }) => {
@@ -691,21 +1084,52 @@ mod durable_impl {
                         finished,
                         ..
                     }) => {
-                        if let Some(ref result) = result {
-                            partial_result.extend_from_slice(result);
-                        } else {
-                            *finished = true;
+                        for batch in &batches {
+                            if let Some(batch) = batch {
+                                partial_result.extend_from_slice(batch);
+                            } else {
+                                *finished = true;
+                            }
                         }
                     }
                     None => {
                         unreachable!()
                     }
                 }
-                result
+                drop(state);
+                self.take_first(batches)
+            }
+        }
+
+        /// Applies the post-filters to a batch, none if they removed all of its hits, as an empty
+        /// batch would end the stream
+        fn post_filter(&self, batch: Vec<SearchHit>) -> Option<Vec<SearchHit>> {
+            if batch.is_empty() {
+                return Some(batch);
+            }
+            let batch = post_processing::filter_hits(self.config.as_ref(), batch);
+            (!batch.is_empty()).then_some(batch)
+        }
+    }
+
+    #[cfg(not(feature = "no-streaming"))]
+    impl<Impl: ExtendedGuest> GuestSearchStream for DurableSearchStream<Impl> {
+        fn get_next(&self) -> Option<Vec<SearchHit>> {
+            loop {
+                if let Some(batch) = self.post_filter(self.next_batch()?) {
+                    return Some(batch);
+                }
             }
         }
 
         fn blocking_get_next(&self) -> Vec<SearchHit> {
+            // Batches already read from the provider must not wait for the stream's pollable
+            while let Some(Some(hits)) = self.buffered.borrow_mut().pop_front() {
+                if let Some(hits) = self.post_filter(hits) {
+                    return hits;
+                }
+            }
+
             let mut subscription = self.subscription.borrow_mut();
             if subscription.is_none() {
                 *subscription = Some(self.subscribe());
@@ -750,7 +1174,8 @@ mod durable_impl {
                 "database connection failed".to_string(),
             ));
             roundtrip_test(SearchError::Timeout);
-            roundtrip_test(SearchError::RateLimited);
+            roundtrip_test(SearchError::RateLimited(None));
+            roundtrip_test(SearchError::RateLimited(Some(30)));
         }
 
         #[test]
@@ -794,6 +1219,11 @@ mod durable_impl {
                 typo_tolerance: Some(true),
                 exact_match_boost: Some(1.5),
                 provider_params: Some(r#"{"custom_param": "value"}"#.to_string()),
+                profile: Some("analytics".to_string()),
+                filter_mode: None,
+                post_filters: vec![],
+                rescore: None,
+                fallbacks: vec![],
             };
             roundtrip_test(config);
 
@@ -806,6 +1236,11 @@ mod durable_impl {
                 typo_tolerance: None,
                 exact_match_boost: None,
                 provider_params: None,
+                profile: None,
+                filter_mode: None,
+                post_filters: vec![],
+                rescore: None,
+                fallbacks: vec![],
             };
             roundtrip_test(minimal_config);
         }
@@ -834,6 +1269,11 @@ mod durable_impl {
                     typo_tolerance: Some(false),
                     exact_match_boost: Some(2.0),
                     provider_params: None,
+                    profile: None,
+                    filter_mode: None,
+                    post_filters: vec![],
+                    rescore: None,
+                    fallbacks: vec![],
                 }),
             };
             roundtrip_test(query);
@@ -897,6 +1337,8 @@ mod durable_impl {
                 ],
                 facets: Some(r#"{"category": {"programming": 50, "tutorial": 25}}"#.to_string()),
                 took_ms: Some(15),
+                relaxation: None,
+                navigation: None,
             };
             roundtrip_test(results);
 
@@ -908,6 +1350,8 @@ mod durable_impl {
                 hits: vec![],
                 facets: None,
                 took_ms: Some(5),
+                relaxation: None,
+                navigation: None,
             };
             roundtrip_test(empty_results);
         }
@@ -1015,6 +1459,57 @@ mod durable_impl {
             roundtrip_test(minimal_schema);
         }
 
+        fn hits(ids: &[&str]) -> Vec<SearchHit> {
+            ids.iter()
+                .map(|id| SearchHit {
+                    id: id.to_string(),
+                    score: None,
+                    content: None,
+                    highlights: None,
+                })
+                .collect()
+        }
+
+        #[test]
+        fn checkpoint_policy_coalesces_batches() {
+            let mut batches = vec![
+                Some(hits(&["1", "2"])),
+                Some(hits(&["3"])),
+                Some(hits(&["4", "5"])),
+                Some(hits(&["6"])),
+            ]
+            .into_iter();
+
+            let policy = StreamCheckpointPolicy {
+                min_hits: 3,
+                max_delay: None,
+            };
+            let checkpoint = policy.collect(|| batches.next().flatten());
+            assert_eq!(
+                checkpoint,
+                vec![Some(hits(&["1", "2"])), Some(hits(&["3"]))]
+            );
+
+            let default = StreamCheckpointPolicy::default();
+            assert_eq!(
+                default.collect(|| batches.next().flatten()),
+                vec![Some(hits(&["4", "5"]))]
+            );
+        }
+
+        #[test]
+        fn checkpoint_policy_stops_at_end_of_stream() {
+            let mut batches = vec![Some(hits(&["1"])), None].into_iter();
+            let policy = StreamCheckpointPolicy {
+                min_hits: 100,
+                max_delay: None,
+            };
+            assert_eq!(
+                policy.collect(|| batches.next().flatten()),
+                vec![Some(hits(&["1"])), None]
+            );
+        }
+
         #[test]
         fn retry_query_logic_test() {
             // Test the retry query logic directly without implementing the full trait
@@ -1143,6 +1638,11 @@ mod durable_impl {
                         r#"{"index_settings": {"similarity": "BM25", "k1": 1.5, "b": 0.75}}"#
                             .to_string(),
                     ),
+                    profile: None,
+                    filter_mode: None,
+                    post_filters: vec![],
+                    rescore: None,
+                    fallbacks: vec![],
                 }),
             };
             roundtrip_test(complex_query);
@@ -1,5 +1,15 @@
-use crate::golem::search::types::SearchError;
-use reqwest::StatusCode;
+use crate::golem::search::types::{ProviderErrorDetails, SearchError};
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use reqwest::{Response, StatusCode};
+use serde_json::Value;
+
+/// Headers carrying the provider's identifier of a request
+const REQUEST_ID_HEADERS: &[&str] = &[
+    "x-request-id",
+    "x-amzn-requestid",
+    "x-opaque-id",
+    "request-id",
+];
 
 pub fn unsupported(_what: impl AsRef<str>) -> SearchError {
     SearchError::Unsupported
@@ -21,26 +31,290 @@ pub fn timeout() -> SearchError {
     SearchError::Timeout
 }
 
-pub fn rate_limited() -> SearchError {
-    SearchError::RateLimited
+pub fn rate_limited(retry_after_secs: Option<u32>) -> SearchError {
+    SearchError::RateLimited(retry_after_secs)
 }
 
 pub fn from_reqwest_error(details: impl AsRef<str>, err: reqwest::Error) -> SearchError {
     SearchError::Internal(format!("{}: {err}", details.as_ref()))
 }
 
+/// Like `from_reqwest_error`, but maps requests that ran out of time to `SearchError::Timeout`
+pub fn from_send_error(details: impl AsRef<str>, err: reqwest::Error) -> SearchError {
+    if err.is_timeout() {
+        SearchError::Timeout
+    } else {
+        from_reqwest_error(details, err)
+    }
+}
+
 pub fn search_error_from_status(status: StatusCode) -> SearchError {
+    search_error_from_details(status, None, ProviderErrorBody::default(), None)
+}
+
+/// Builds the error of a failed provider response from its status, its headers and the code and
+/// message found in its body. Consumes the response.
+pub fn search_error_from_failed_response(response: Response) -> SearchError {
+    let status = response.status();
+    let retry_after = parse_retry_after(response.headers());
+    let request_id = parse_request_id(response.headers());
+    let body = match response.text() {
+        Ok(body) => ProviderErrorBody::parse(&body),
+        Err(_) => ProviderErrorBody::default(),
+    };
+    search_error_from_details(status, retry_after, body, request_id)
+}
+
+fn search_error_from_details(
+    status: StatusCode,
+    retry_after: Option<u32>,
+    body: ProviderErrorBody,
+    request_id: Option<String>,
+) -> SearchError {
     match status {
-        StatusCode::TOO_MANY_REQUESTS => SearchError::RateLimited,
+        StatusCode::TOO_MANY_REQUESTS => SearchError::RateLimited(retry_after),
         StatusCode::REQUEST_TIMEOUT | StatusCode::GATEWAY_TIMEOUT => SearchError::Timeout,
         StatusCode::NOT_FOUND => SearchError::IndexNotFound,
-        StatusCode::BAD_REQUEST => SearchError::InvalidQuery("Bad request".to_string()),
-        StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN | StatusCode::PAYMENT_REQUIRED => {
-            SearchError::Internal("Authentication failed".to_string())
+        StatusCode::BAD_REQUEST => {
+            SearchError::InvalidQuery(body.message.unwrap_or_else(|| "Bad request".to_string()))
         }
-        _ if status.is_client_error() => {
-            SearchError::InvalidQuery(format!("Client error: {status}"))
+        StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN | StatusCode::PAYMENT_REQUIRED => {
+            SearchError::ProviderError(ProviderErrorDetails {
+                status: status.as_u16(),
+                code: body.code,
+                message: body
+                    .message
+                    .unwrap_or_else(|| "Authentication failed".to_string()),
+                request_id,
+            })
         }
-        _ => SearchError::Internal(format!("Server error: {status}")),
+        _ if status.is_client_error() => SearchError::InvalidQuery(
+            body.message
+                .unwrap_or_else(|| format!("Client error: {status}")),
+        ),
+        _ => SearchError::ProviderError(ProviderErrorDetails {
+            status: status.as_u16(),
+            code: body.code,
+            message: body
+                .message
+                .unwrap_or_else(|| format!("Server error: {status}")),
+            request_id,
+        }),
+    }
+}
+
+/// The error code and message of a provider's error response body, found in the fields used by
+/// the supported providers: `message` and `code` (Algolia, Meilisearch, Typesense, Marqo),
+/// `error.reason` and `error.type` (Elasticsearch, OpenSearch), `status.error` (Qdrant),
+/// `error[0].message` (Weaviate), `root.errors[0].message` and `root.errors[0].code` (Vespa),
+/// `error.msg` (Solr), or `detail` and `errorCode` (Atlas Administration API) and `error` and
+/// `error_code` (Atlas Data API), or `messages[0]` (Vectara).
+#[derive(Debug, Default, PartialEq)]
+struct ProviderErrorBody {
+    code: Option<String>,
+    message: Option<String>,
+}
+
+impl ProviderErrorBody {
+    fn parse(body: &str) -> Self {
+        let Ok(json) = serde_json::from_str::<Value>(body) else {
+            let body = body.trim();
+            return Self {
+                code: None,
+                message: (!body.is_empty()).then(|| body.to_string()),
+            };
+        };
+
+        let error = json.get("error");
+        let root_error = json.pointer("/root/errors/0");
+        let message = json
+            .get("message")
+            .or_else(|| error.and_then(|error| error.get("reason")))
+            .or_else(|| error.and_then(|error| error.get("message")))
+            .or_else(|| error.and_then(|error| error.get("msg")))
+            .or_else(|| error.and_then(|error| error.get(0)?.get("message")))
+            .or_else(|| error.filter(|error| error.is_string()))
+            .or_else(|| json.get("status").and_then(|status| status.get("error")))
+            .or_else(|| root_error.and_then(|error| error.get("message")))
+            .or_else(|| json.get("detail"))
+            .or_else(|| json.get("messages").and_then(|messages| messages.get(0)))
+            .and_then(Value::as_str)
+            .map(str::to_string);
+        let code = json
+            .get("code")
+            .or_else(|| error.and_then(|error| error.get("type")))
+            .or_else(|| root_error.and_then(|error| error.get("code")))
+            .or_else(|| json.get("errorCode"))
+            .or_else(|| json.get("error_code"))
+            .and_then(|code| match code {
+                Value::String(code) => Some(code.clone()),
+                Value::Number(code) => Some(code.to_string()),
+                _ => None,
+            });
+
+        Self { code, message }
+    }
+}
+
+fn parse_request_id(headers: &HeaderMap) -> Option<String> {
+    REQUEST_ID_HEADERS
+        .iter()
+        .find_map(|name| headers.get(*name)?.to_str().ok())
+        .map(str::to_string)
+}
+
+/// Like `search_error_from_status`, but also picks up the `Retry-After` header of rate limited
+/// responses. Must be called before the response body is consumed.
+pub fn search_error_from_response(response: &Response) -> SearchError {
+    match response.status() {
+        StatusCode::TOO_MANY_REQUESTS => rate_limited(parse_retry_after(response.headers())),
+        status => search_error_from_status(status),
+    }
+}
+
+/// Parses the delta-seconds form of the `Retry-After` header. The HTTP-date form is not
+/// supported and results in `None`.
+pub fn parse_retry_after(headers: &HeaderMap) -> Option<u32> {
+    headers
+        .get(RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse::<u32>()
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    #[test]
+    fn retry_after_seconds_are_parsed() {
+        let mut headers = HeaderMap::new();
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("120"));
+        assert_eq!(parse_retry_after(&headers), Some(120));
+    }
+
+    #[test]
+    fn retry_after_http_date_is_ignored() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            RETRY_AFTER,
+            HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"),
+        );
+        assert_eq!(parse_retry_after(&headers), None);
+        assert_eq!(parse_retry_after(&HeaderMap::new()), None);
+    }
+
+    #[test]
+    fn provider_error_bodies_are_parsed() {
+        assert_eq!(
+            ProviderErrorBody::parse(
+                r#"{"message": "Index `products` not found.", "code": "index_not_found"}"#
+            ),
+            ProviderErrorBody {
+                code: Some("index_not_found".to_string()),
+                message: Some("Index `products` not found.".to_string()),
+            }
+        );
+        assert_eq!(
+            ProviderErrorBody::parse(
+                r#"{"error": {"type": "search_phase_execution_exception", "reason": "all shards failed"}, "status": 503}"#
+            ),
+            ProviderErrorBody {
+                code: Some("search_phase_execution_exception".to_string()),
+                message: Some("all shards failed".to_string()),
+            }
+        );
+        assert_eq!(
+            ProviderErrorBody::parse(
+                r#"{"status": {"error": "Not found: Collection `products` doesn't exist!"}, "time": 0.001}"#
+            ),
+            ProviderErrorBody {
+                code: None,
+                message: Some("Not found: Collection `products` doesn't exist!".to_string()),
+            }
+        );
+        assert_eq!(
+            ProviderErrorBody::parse(
+                r#"{"error": [{"message": "class name \"Products\" does not exist"}]}"#
+            ),
+            ProviderErrorBody {
+                code: None,
+                message: Some("class name \"Products\" does not exist".to_string()),
+            }
+        );
+        assert_eq!(
+            ProviderErrorBody::parse(
+                r#"{"root": {"errors": [{"code": 4, "summary": "Invalid query parameter", "message": "Could not parse YQL"}]}}"#
+            ),
+            ProviderErrorBody {
+                code: Some("4".to_string()),
+                message: Some("Could not parse YQL".to_string()),
+            }
+        );
+        assert_eq!(
+            ProviderErrorBody::parse(
+                r#"{"responseHeader": {"status": 400}, "error": {"metadata": ["error-class", "org.apache.solr.common.SolrException"], "msg": "undefined field genre", "code": 400}}"#
+            ),
+            ProviderErrorBody {
+                code: None,
+                message: Some("undefined field genre".to_string()),
+            }
+        );
+        assert_eq!(
+            ProviderErrorBody::parse(
+                r#"{"detail": "No search index named default exists.", "error": 404, "errorCode": "ATLAS_SEARCH_INDEX_NOT_FOUND", "reason": "Not Found"}"#
+            ),
+            ProviderErrorBody {
+                code: Some("ATLAS_SEARCH_INDEX_NOT_FOUND".to_string()),
+                message: Some("No search index named default exists.".to_string()),
+            }
+        );
+        assert_eq!(
+            ProviderErrorBody::parse(
+                r#"{"error": "invalid session: access token expired", "error_code": "InvalidSession"}"#
+            ),
+            ProviderErrorBody {
+                code: Some("InvalidSession".to_string()),
+                message: Some("invalid session: access token expired".to_string()),
+            }
+        );
+        assert_eq!(
+            ProviderErrorBody::parse(
+                r#"{"messages": ["Corpus books does not exist."], "request_id": "abc"}"#
+            ),
+            ProviderErrorBody {
+                code: None,
+                message: Some("Corpus books does not exist.".to_string()),
+            }
+        );
+        assert_eq!(
+            ProviderErrorBody::parse("upstream connect error"),
+            ProviderErrorBody {
+                code: None,
+                message: Some("upstream connect error".to_string()),
+            }
+        );
+    }
+
+    #[test]
+    fn server_errors_keep_provider_details() {
+        let error = search_error_from_details(
+            StatusCode::SERVICE_UNAVAILABLE,
+            None,
+            ProviderErrorBody::parse(r#"{"message": "Cluster is restarting", "code": 17}"#),
+            Some("req-42".to_string()),
+        );
+        assert_eq!(
+            error,
+            SearchError::ProviderError(ProviderErrorDetails {
+                status: 503,
+                code: Some("17".to_string()),
+                message: "Cluster is restarting".to_string(),
+                request_id: Some("req-42".to_string()),
+            })
+        );
     }
 }
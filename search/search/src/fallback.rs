@@ -0,0 +1,284 @@
+//! Zero-result fallbacks of search queries.
+//!
+//! When a `search` matches no documents, `DurableSearch` retries it with the `fallbacks` of its
+//! `search-config`, in order. Each strategy keeps the relaxations of the previous ones, and the
+//! first results with hits are returned along with the `relaxation` that found them. When no
+//! relaxation finds any hit, the results of the original query are returned.
+
+use crate::golem::search::types::{
+    FallbackStrategy, QueryRelaxation, SearchError, SearchQuery, SearchResults,
+};
+use log::{trace, warn};
+
+/// Runs `query` with `search`, then its relaxations while it matches no documents
+pub fn search_with_fallbacks(
+    query: SearchQuery,
+    mut search: impl FnMut(SearchQuery) -> Result<SearchResults, SearchError>,
+) -> Result<SearchResults, SearchError> {
+    let fallbacks = query
+        .config
+        .as_ref()
+        .map(|config| config.fallbacks.clone())
+        .unwrap_or_default();
+
+    let results = search(query.clone())?;
+    if !results.hits.is_empty() || fallbacks.is_empty() {
+        return Ok(results);
+    }
+
+    match relax(query, &fallbacks, &mut search) {
+        Ok(Some(relaxed)) => Ok(relaxed),
+        Ok(None) => Ok(results),
+        Err(error) => {
+            warn!("Relaxed query failed, returning the results of the original query: {error:?}");
+            Ok(results)
+        }
+    }
+}
+
+/// Tries the relaxations of `fallbacks` in order, returning the first results with hits
+fn relax(
+    mut query: SearchQuery,
+    fallbacks: &[FallbackStrategy],
+    search: &mut impl FnMut(SearchQuery) -> Result<SearchResults, SearchError>,
+) -> Result<Option<SearchResults>, SearchError> {
+    let mut relaxation = QueryRelaxation {
+        strategies: vec![],
+        removed_filters: vec![],
+        q: None,
+    };
+
+    for &strategy in fallbacks {
+        match strategy {
+            FallbackStrategy::RelaxFilters => {
+                if query.filters.is_empty() {
+                    continue;
+                }
+                relaxation.strategies.push(strategy);
+                while let Some(filter) = query.filters.pop() {
+                    trace!("Searching again without the filter `{filter}`");
+                    relaxation.removed_filters.push(filter);
+                    let results = search(query.clone())?;
+                    if !results.hits.is_empty() {
+                        return Ok(Some(with_relaxation(results, relaxation)));
+                    }
+                }
+                continue;
+            }
+            FallbackStrategy::TypoTolerance => {
+                let Some(config) = query.config.as_mut() else {
+                    continue;
+                };
+                if config.typo_tolerance == Some(true) || !has_text(query.q.as_deref()) {
+                    continue;
+                }
+                config.typo_tolerance = Some(true);
+            }
+            FallbackStrategy::PrefixSearch => {
+                let Some(prefixed) = query.q.as_deref().and_then(prefix_query) else {
+                    continue;
+                };
+                relaxation.q = Some(prefixed.clone());
+                query.q = Some(prefixed);
+            }
+        }
+
+        trace!("Searching again with {strategy:?}");
+        relaxation.strategies.push(strategy);
+        let results = search(query.clone())?;
+        if !results.hits.is_empty() {
+            return Ok(Some(with_relaxation(results, relaxation)));
+        }
+    }
+
+    Ok(None)
+}
+
+fn has_text(q: Option<&str>) -> bool {
+    q.is_some_and(|q| !q.trim().is_empty())
+}
+
+fn with_relaxation(mut results: SearchResults, relaxation: QueryRelaxation) -> SearchResults {
+    results.relaxation = Some(relaxation);
+    results
+}
+
+/// Rewrites the words of a query text as `word*` prefix terms. Texts with phrases, and texts
+/// whose words are all prefix terms already, are not rewritten.
+fn prefix_query(q: &str) -> Option<String> {
+    if q.contains('"') {
+        return None;
+    }
+    let mut rewritten = false;
+    let words = q
+        .split_whitespace()
+        .map(|word| {
+            if word.ends_with(|c: char| c.is_alphanumeric()) {
+                rewritten = true;
+                format!("{word}*")
+            } else {
+                word.to_string()
+            }
+        })
+        .collect::<Vec<_>>();
+    rewritten.then(|| words.join(" "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::golem::search::types::{SearchConfig, SearchHit};
+
+    fn query(q: &str, filters: &[&str], fallbacks: Vec<FallbackStrategy>) -> SearchQuery {
+        SearchQuery {
+            q: Some(q.to_string()),
+            filters: filters.iter().map(|filter| filter.to_string()).collect(),
+            sort: vec![],
+            facets: vec![],
+            page: None,
+            per_page: None,
+            offset: None,
+            highlight: None,
+            config: Some(SearchConfig {
+                timeout_ms: None,
+                boost_fields: vec![],
+                attributes_to_retrieve: vec![],
+                language: None,
+                typo_tolerance: None,
+                exact_match_boost: None,
+                provider_params: None,
+                profile: None,
+                filter_mode: None,
+                post_filters: vec![],
+                rescore: None,
+                fallbacks,
+            }),
+        }
+    }
+
+    fn results(hits: usize) -> SearchResults {
+        SearchResults {
+            total: Some(hits as u32),
+            page: None,
+            per_page: None,
+            hits: (0..hits)
+                .map(|i| SearchHit {
+                    id: i.to_string(),
+                    score: None,
+                    content: None,
+                    highlights: None,
+                })
+                .collect(),
+            facets: None,
+            took_ms: None,
+            relaxation: None,
+            navigation: None,
+        }
+    }
+
+    #[test]
+    fn results_with_hits_are_not_relaxed() {
+        let mut calls = 0;
+        let results = search_with_fallbacks(
+            query("dune", &["year>1950"], vec![FallbackStrategy::RelaxFilters]),
+            |_| {
+                calls += 1;
+                Ok(results(2))
+            },
+        )
+        .unwrap();
+        assert_eq!(calls, 1);
+        assert_eq!(results.relaxation, None);
+    }
+
+    #[test]
+    fn filters_are_relaxed_from_the_last_one() {
+        let mut searched = vec![];
+        let results = search_with_fallbacks(
+            query(
+                "dune",
+                &["genre:fiction", "year>1950", "lang:fr"],
+                vec![FallbackStrategy::RelaxFilters],
+            ),
+            |query| {
+                searched.push(query.filters.clone());
+                Ok(results(if query.filters.len() == 1 { 1 } else { 0 }))
+            },
+        )
+        .unwrap();
+        assert_eq!(searched.len(), 3);
+        assert_eq!(
+            results.relaxation,
+            Some(QueryRelaxation {
+                strategies: vec![FallbackStrategy::RelaxFilters],
+                removed_filters: vec!["lang:fr".to_string(), "year>1950".to_string()],
+                q: None,
+            })
+        );
+    }
+
+    #[test]
+    fn strategies_keep_previous_relaxations() {
+        let mut last = None;
+        let results = search_with_fallbacks(
+            query(
+                "dun",
+                &[],
+                vec![
+                    FallbackStrategy::RelaxFilters,
+                    FallbackStrategy::TypoTolerance,
+                    FallbackStrategy::PrefixSearch,
+                ],
+            ),
+            |query| {
+                let prefixed = query.q.as_deref() == Some("dun*");
+                last = Some(query);
+                Ok(results(if prefixed { 1 } else { 0 }))
+            },
+        )
+        .unwrap();
+        assert_eq!(last.unwrap().config.unwrap().typo_tolerance, Some(true));
+        assert_eq!(
+            results.relaxation,
+            Some(QueryRelaxation {
+                strategies: vec![
+                    FallbackStrategy::TypoTolerance,
+                    FallbackStrategy::PrefixSearch
+                ],
+                removed_filters: vec![],
+                q: Some("dun*".to_string()),
+            })
+        );
+    }
+
+    #[test]
+    fn original_results_are_returned_when_no_relaxation_matches() {
+        let mut calls = 0;
+        let results = search_with_fallbacks(
+            query("dune", &["year>1950"], vec![FallbackStrategy::RelaxFilters]),
+            |query| {
+                calls += 1;
+                if query.filters.is_empty() {
+                    Err(SearchError::Internal("unavailable".to_string()))
+                } else {
+                    Ok(results(0))
+                }
+            },
+        )
+        .unwrap();
+        assert_eq!(calls, 2);
+        assert!(results.hits.is_empty());
+        assert_eq!(results.relaxation, None);
+    }
+
+    #[test]
+    fn prefix_query_rewrites_words() {
+        assert_eq!(
+            prefix_query("space  oper"),
+            Some("space* oper*".to_string())
+        );
+        assert_eq!(prefix_query("dun* -war"), Some("dun* -war*".to_string()));
+        assert_eq!(prefix_query("\"space opera\""), None);
+        assert_eq!(prefix_query("dun*"), None);
+    }
+}
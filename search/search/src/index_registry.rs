@@ -0,0 +1,258 @@
+//! Registry of the index settings applied through this component.
+//!
+//! `DurableSearch` remembers the last schema applied to each index with `create-index` or
+//! `update-schema`. The registry lives in the worker's memory and is rebuilt from the oplog on
+//! replay, so it survives restarts without being persisted separately. `reconcile` compares the
+//! remembered schema with the one reported by the provider and re-applies it when it drifted.
+//! The registry also remembers when indexes were created through this component, which
+//! `cleanup` uses to only delete the indexes older than a given age.
+
+use crate::deterministic;
+use crate::durability::{DurableSearch, ExtendedGuest};
+use crate::error::invalid_query;
+use crate::golem::search::core::Guest as CoreGuest;
+use crate::golem::search::index_registry::{Guest, IndexSettings};
+use crate::golem::search::types::{IndexName, PageRequest, Schema, SearchError};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+thread_local! {
+    static REGISTRY: RefCell<HashMap<IndexName, IndexSettings>> = RefCell::new(HashMap::new());
+    /// When indexes were created through this component, in milliseconds since the Unix epoch
+    static CREATED: RefCell<HashMap<IndexName, u64>> = RefCell::new(HashMap::new());
+}
+
+/// Records a schema successfully applied to `index`. The version is only bumped when the schema
+/// differs from the remembered one.
+pub fn applied(index: &IndexName, schema: Option<Schema>) {
+    let Some(schema) = schema else {
+        return;
+    };
+    REGISTRY.with_borrow_mut(|registry| match registry.get_mut(index) {
+        Some(settings) if settings.schema == schema => {}
+        Some(settings) => {
+            settings.schema = schema;
+            settings.version += 1;
+        }
+        None => {
+            registry.insert(index.clone(), IndexSettings { schema, version: 1 });
+        }
+    });
+}
+
+/// Records the creation of `index` at the current time, which is stable across replays
+pub fn created(index: &IndexName) {
+    let now = millis_since_epoch(deterministic::now());
+    CREATED.with_borrow_mut(|created| created.insert(index.clone(), now));
+}
+
+/// Forgets the settings of a deleted index
+pub fn forget(index: &IndexName) {
+    REGISTRY.with_borrow_mut(|registry| registry.remove(index));
+    CREATED.with_borrow_mut(|created| created.remove(index));
+}
+
+fn created_at(index: &IndexName) -> Option<u64> {
+    CREATED.with_borrow(|created| created.get(index).copied())
+}
+
+fn millis_since_epoch(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+/// The indexes to delete among `names`: the ones starting with `prefix` and, given a minimum
+/// age, created at least `older_than_ms` before `now_ms`. Indexes of unknown age are kept when
+/// a minimum age is given.
+fn expired_indexes(
+    names: Vec<IndexName>,
+    prefix: &str,
+    older_than_ms: Option<u64>,
+    now_ms: u64,
+    created_at: impl Fn(&IndexName) -> Option<u64>,
+) -> Vec<IndexName> {
+    names
+        .into_iter()
+        .filter(|name| name.starts_with(prefix))
+        .filter(|name| match older_than_ms {
+            Some(older_than_ms) => created_at(name)
+                .is_some_and(|created| now_ms.saturating_sub(created) >= older_than_ms),
+            None => true,
+        })
+        .collect()
+}
+
+pub fn settings(index: &IndexName) -> Option<IndexSettings> {
+    REGISTRY.with_borrow(|registry| registry.get(index).cloned())
+}
+
+/// Whether the provider's schema lost any of the expected fields or changed their definition.
+/// Extra fields added by the provider, and the order of the fields, are not considered a drift.
+fn has_drifted(expected: &Schema, actual: &Schema) -> bool {
+    if expected.primary_key.is_some() && expected.primary_key != actual.primary_key {
+        return true;
+    }
+
+    expected.fields.iter().any(|expected_field| {
+        actual
+            .fields
+            .iter()
+            .find(|field| field.name == expected_field.name)
+            != Some(expected_field)
+    })
+}
+
+impl<Impl: ExtendedGuest> Guest for DurableSearch<Impl> {
+    fn get_settings(index: IndexName) -> Option<IndexSettings> {
+        settings(&index)
+    }
+
+    fn reconcile(index: IndexName) -> Result<bool, SearchError> {
+        let Some(expected) = settings(&index) else {
+            return Ok(false);
+        };
+
+        let actual = <Self as CoreGuest>::get_schema(index.clone())?;
+        if has_drifted(&expected.schema, &actual) {
+            <Self as CoreGuest>::update_schema(index, expected.schema)?;
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    fn cleanup(prefix: String, older_than_ms: Option<u64>) -> Result<Vec<IndexName>, SearchError> {
+        if prefix.is_empty() {
+            return Err(invalid_query(
+                "The prefix of the indexes to clean up is empty",
+            ));
+        }
+
+        let mut names = Vec::new();
+        let mut page = PageRequest {
+            token: None,
+            size: None,
+        };
+        loop {
+            let index_page = <Self as CoreGuest>::list_indexes(page)?;
+            names.extend(index_page.items);
+            match index_page.next_page_token {
+                Some(token) => {
+                    page = PageRequest {
+                        token: Some(token),
+                        size: None,
+                    }
+                }
+                None => break,
+            }
+        }
+
+        let now_ms = millis_since_epoch(deterministic::now());
+        let mut deleted = Vec::new();
+        for name in expired_indexes(names, &prefix, older_than_ms, now_ms, created_at) {
+            match <Self as CoreGuest>::delete_index(name.clone()) {
+                Ok(()) | Err(SearchError::IndexNotFound) => deleted.push(name),
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(deleted)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::golem::search::types::{FieldType, SchemaField};
+
+    fn field(name: &str, field_type: FieldType) -> SchemaField {
+        SchemaField {
+            name: name.to_string(),
+            field_type,
+            required: false,
+            facet: false,
+            sort: false,
+            index: true,
+        }
+    }
+
+    fn schema(fields: Vec<SchemaField>) -> Schema {
+        Schema {
+            fields,
+            primary_key: Some("id".to_string()),
+        }
+    }
+
+    #[test]
+    fn version_is_bumped_only_on_change() {
+        let index = "products".to_string();
+        let v1 = schema(vec![field("title", FieldType::Text)]);
+        let v2 = schema(vec![field("title", FieldType::Keyword)]);
+
+        applied(&index, Some(v1.clone()));
+        applied(&index, Some(v1));
+        assert_eq!(settings(&index).unwrap().version, 1);
+
+        applied(&index, Some(v2.clone()));
+        let current = settings(&index).unwrap();
+        assert_eq!(current.version, 2);
+        assert_eq!(current.schema, v2);
+
+        forget(&index);
+        assert!(settings(&index).is_none());
+    }
+
+    #[test]
+    fn expired_indexes_match_prefix_and_age() {
+        let names = vec![
+            "test-solr-index-test1".to_string(),
+            "test-solr-index-test2".to_string(),
+            "test-solr-index-test3".to_string(),
+            "products".to_string(),
+        ];
+        let created_at = |name: &IndexName| match name.as_str() {
+            "test-solr-index-test1" => Some(1_000),
+            "test-solr-index-test2" => Some(9_000),
+            _ => None,
+        };
+
+        assert_eq!(
+            expired_indexes(names.clone(), "test-", None, 10_000, created_at),
+            vec![
+                "test-solr-index-test1".to_string(),
+                "test-solr-index-test2".to_string(),
+                "test-solr-index-test3".to_string(),
+            ]
+        );
+        assert_eq!(
+            expired_indexes(names.clone(), "test-", Some(5_000), 10_000, created_at),
+            vec!["test-solr-index-test1".to_string()]
+        );
+        assert!(expired_indexes(names, "staging-", None, 10_000, created_at).is_empty());
+    }
+
+    #[test]
+    fn drift_ignores_extra_fields_and_order() {
+        let expected = schema(vec![
+            field("title", FieldType::Text),
+            field("price", FieldType::Float),
+        ]);
+
+        let reordered = schema(vec![
+            field("price", FieldType::Float),
+            field("category", FieldType::Keyword),
+            field("title", FieldType::Text),
+        ]);
+        assert!(!has_drifted(&expected, &reordered));
+
+        let changed = schema(vec![
+            field("title", FieldType::Keyword),
+            field("price", FieldType::Float),
+        ]);
+        assert!(has_drifted(&expected, &changed));
+
+        let missing = schema(vec![field("title", FieldType::Text)]);
+        assert!(has_drifted(&expected, &missing));
+    }
+}
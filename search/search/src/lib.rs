@@ -1,6 +1,21 @@
+pub mod bulk;
+pub mod circuit_breaker;
 pub mod config;
+pub mod correlation;
+pub mod divergence;
 pub mod durability;
 pub mod error;
+pub mod fallback;
+pub mod index_registry;
+pub mod logging;
+pub mod metrics;
+pub mod pagination;
+pub mod post_processing;
+#[cfg(feature = "query-intent")]
+pub mod query_intent;
+pub mod retry;
+
+pub use golem_deterministic as deterministic;
 
 wit_bindgen::generate!({
     path: "../wit",
@@ -31,7 +46,7 @@ impl LoggingState {
         if !self.logging_initialized {
             let _ = wasi_logger::Logger::install();
             let max_level: log::LevelFilter = log::LevelFilter::from_str(
-                &std::env::var("SEARCH_PROVIDER_LOG_LEVEL").unwrap_or_default(),
+                &config::get_config_value("SEARCH_PROVIDER_LOG_LEVEL").unwrap_or_default(),
             )
             .unwrap_or(log::LevelFilter::Info);
             log::set_max_level(max_level);
@@ -0,0 +1,168 @@
+//! Helpers for logging provider traffic without leaking credentials.
+//!
+//! Responses, headers and URLs are often useful in trace logs, but they can carry API keys,
+//! tokens or cookies. The wrappers in this module format them the same way as their `Debug`
+//! implementations, with the values of sensitive headers and query parameters replaced.
+
+use reqwest::header::HeaderMap;
+use reqwest::{Response, Url};
+use std::fmt::{Debug, Formatter};
+
+const REDACTED: &str = "[REDACTED]";
+
+/// Headers carrying credentials, redacted in logs. The providers set the ones they use
+/// themselves, so `config` also reserves them against custom headers.
+pub const CREDENTIAL_HEADERS: &[&str] = &[
+    "authorization",
+    "proxy-authorization",
+    "cookie",
+    "set-cookie",
+    "api-key",
+    "apikey",
+    "x-api-key",
+    "x-algolia-api-key",
+    "x-typesense-api-key",
+    "x-amz-security-token",
+    "neon-connection-string",
+];
+
+const SENSITIVE_QUERY_PARAMS: &[&str] = &[
+    "api_key",
+    "apikey",
+    "x-algolia-api-key",
+    "x-typesense-api-key",
+    "token",
+    "access_token",
+];
+
+/// Masks a secret, keeping only its last characters when it is long enough to tell values apart
+pub fn redact_secret(secret: &str) -> String {
+    let chars = secret.chars().count();
+    if chars > 12 {
+        let suffix: String = secret.chars().skip(chars - 4).collect();
+        format!("****{suffix}")
+    } else {
+        "****".to_string()
+    }
+}
+
+/// Formats a header map with the values of sensitive headers redacted
+pub struct RedactedHeaders<'a>(pub &'a HeaderMap);
+
+impl Debug for RedactedHeaders<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_map()
+            .entries(self.0.iter().map(|(name, value)| {
+                let value = if is_sensitive_header(name.as_str()) {
+                    REDACTED.to_string()
+                } else {
+                    String::from_utf8_lossy(value.as_bytes()).to_string()
+                };
+                (name.as_str(), value)
+            }))
+            .finish()
+    }
+}
+
+/// Formats a URL with the values of sensitive query parameters redacted
+pub struct RedactedUrl<'a>(pub &'a Url);
+
+impl Debug for RedactedUrl<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let url = self.0;
+        if !url
+            .query_pairs()
+            .any(|(key, _)| is_sensitive_query_param(&key))
+        {
+            return write!(f, "{url}");
+        }
+
+        let mut redacted = url.clone();
+        let pairs = url
+            .query_pairs()
+            .map(|(key, value)| {
+                let value = if is_sensitive_query_param(&key) {
+                    REDACTED.into()
+                } else {
+                    value
+                };
+                (key, value)
+            })
+            .collect::<Vec<_>>();
+        redacted.query_pairs_mut().clear().extend_pairs(pairs);
+        write!(f, "{redacted}")
+    }
+}
+
+/// Formats a response like its `Debug` implementation, with credentials redacted
+pub struct RedactedResponse<'a>(pub &'a Response);
+
+impl Debug for RedactedResponse<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Response")
+            .field("url", &RedactedUrl(self.0.url()))
+            .field("status", &self.0.status())
+            .field("headers", &RedactedHeaders(self.0.headers()))
+            .finish()
+    }
+}
+
+fn is_sensitive_header(name: &str) -> bool {
+    CREDENTIAL_HEADERS
+        .iter()
+        .any(|sensitive| name.eq_ignore_ascii_case(sensitive))
+}
+
+fn is_sensitive_query_param(name: &str) -> bool {
+    SENSITIVE_QUERY_PARAMS
+        .iter()
+        .any(|sensitive| name.eq_ignore_ascii_case(sensitive))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::{HeaderValue, AUTHORIZATION, CONTENT_TYPE};
+
+    #[test]
+    fn sensitive_headers_are_redacted() {
+        let mut headers = HeaderMap::new();
+        headers.insert(AUTHORIZATION, HeaderValue::from_static("Bearer secret"));
+        headers.insert("x-algolia-api-key", HeaderValue::from_static("secret"));
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+
+        let formatted = format!("{:?}", RedactedHeaders(&headers));
+        assert!(!formatted.contains("secret"), "{formatted}");
+        assert!(formatted.contains("application/json"), "{formatted}");
+    }
+
+    #[test]
+    fn provider_credential_headers_are_redacted() {
+        let mut headers = HeaderMap::new();
+        for name in [
+            "api-key",
+            "apikey",
+            "x-amz-security-token",
+            "neon-connection-string",
+        ] {
+            headers.insert(name, HeaderValue::from_static("secret"));
+        }
+
+        let formatted = format!("{:?}", RedactedHeaders(&headers));
+        assert!(!formatted.contains("secret"), "{formatted}");
+    }
+
+    #[test]
+    fn sensitive_query_params_are_redacted() {
+        let url = Url::parse("https://example.com/search?q=shoes&api_key=secret").unwrap();
+        let formatted = format!("{:?}", RedactedUrl(&url));
+        assert!(!formatted.contains("secret"), "{formatted}");
+        assert!(formatted.contains("q=shoes"), "{formatted}");
+    }
+
+    #[test]
+    fn secrets_keep_only_a_short_suffix() {
+        assert_eq!(redact_secret("short"), "****");
+        assert_eq!(redact_secret("0123456789abcdef"), "****cdef");
+    }
+}
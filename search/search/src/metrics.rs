@@ -0,0 +1,169 @@
+//! Worker-level metrics of the operations sent to the search provider.
+//!
+//! `DurableSearch` counts the requests, the errors by type and the latency of every operation it
+//! forwards to the provider, and exposes snapshots through the `golem:search/metrics` export.
+//! Only calls actually sent to the provider are measured, results replayed from the oplog are
+//! not. Replays that diverged from the live results of the provider are counted as
+//! `replay-divergence` errors of their operation, see `divergence`.
+
+use crate::durability::{DurableSearch, ExtendedGuest};
+use crate::golem::search::metrics::{ErrorCount, Guest, OperationMetrics};
+use crate::golem::search::types::SearchError;
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+#[derive(Default)]
+struct OperationStats {
+    requests: u64,
+    errors: BTreeMap<&'static str, u64>,
+    total_latency: Duration,
+    max_latency: Duration,
+}
+
+thread_local! {
+    static METRICS: RefCell<BTreeMap<&'static str, OperationStats>> =
+        const { RefCell::new(BTreeMap::new()) };
+}
+
+/// Runs a provider operation, recording its latency and outcome under `operation`
+pub fn record<T>(
+    operation: &'static str,
+    f: impl FnOnce() -> Result<T, SearchError>,
+) -> Result<T, SearchError> {
+    let start = Instant::now();
+    let result = f();
+    observe(operation, start.elapsed(), result.as_ref().err());
+    result
+}
+
+/// Same as `record`, for operations that cannot fail
+pub fn record_infallible<T>(operation: &'static str, f: impl FnOnce() -> T) -> T {
+    let start = Instant::now();
+    let result = f();
+    observe(operation, start.elapsed(), None);
+    result
+}
+
+/// Counts a divergence of a replayed operation from the live results of the provider
+pub fn record_divergence(operation: &'static str) {
+    METRICS.with_borrow_mut(|metrics| {
+        let stats = metrics.entry(operation).or_default();
+        *stats.errors.entry("replay-divergence").or_default() += 1;
+    });
+}
+
+fn observe(operation: &'static str, latency: Duration, error: Option<&SearchError>) {
+    METRICS.with_borrow_mut(|metrics| {
+        let stats = metrics.entry(operation).or_default();
+        stats.requests += 1;
+        stats.total_latency += latency;
+        stats.max_latency = stats.max_latency.max(latency);
+        if let Some(error) = error {
+            *stats.errors.entry(error_type(error)).or_default() += 1;
+        }
+    });
+}
+
+fn error_type(error: &SearchError) -> &'static str {
+    match error {
+        SearchError::IndexNotFound => "index-not-found",
+        SearchError::InvalidQuery(_) => "invalid-query",
+        SearchError::Unsupported => "unsupported",
+        SearchError::Internal(_) => "internal",
+        SearchError::Timeout => "timeout",
+        SearchError::RateLimited(_) => "rate-limited",
+        SearchError::CircuitOpen(_) => "circuit-open",
+        SearchError::ProviderError(_) => "provider-error",
+    }
+}
+
+pub fn snapshot() -> Vec<OperationMetrics> {
+    METRICS.with_borrow(|metrics| {
+        metrics
+            .iter()
+            .map(|(operation, stats)| OperationMetrics {
+                operation: operation.to_string(),
+                requests: stats.requests,
+                errors: stats
+                    .errors
+                    .iter()
+                    .map(|(error, count)| ErrorCount {
+                        error: error.to_string(),
+                        count: *count,
+                    })
+                    .collect(),
+                total_latency_ms: stats.total_latency.as_millis() as u64,
+                max_latency_ms: stats.max_latency.as_millis() as u64,
+            })
+            .collect()
+    })
+}
+
+pub fn reset() {
+    METRICS.with_borrow_mut(|metrics| metrics.clear());
+}
+
+impl<Impl: ExtendedGuest> Guest for DurableSearch<Impl> {
+    fn snapshot() -> Vec<OperationMetrics> {
+        snapshot()
+    }
+
+    fn reset() {
+        reset()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn requests_and_errors_are_counted_per_operation() {
+        reset();
+
+        let _ = record("search", || Ok::<_, SearchError>(()));
+        let _ = record("search", || Err::<(), _>(SearchError::Timeout));
+        let _ = record("upsert", || Err::<(), _>(SearchError::RateLimited(None)));
+        record_infallible("stream_search", || ());
+
+        let metrics = snapshot();
+        let operations: Vec<_> = metrics.iter().map(|m| m.operation.as_str()).collect();
+        assert_eq!(operations, vec!["search", "stream_search", "upsert"]);
+
+        assert_eq!(metrics[0].requests, 2);
+        assert_eq!(
+            metrics[0].errors,
+            vec![ErrorCount {
+                error: "timeout".to_string(),
+                count: 1
+            }]
+        );
+        assert!(metrics[1].errors.is_empty());
+        assert_eq!(metrics[2].errors[0].error, "rate-limited");
+
+        reset();
+        assert!(snapshot().is_empty());
+    }
+
+    #[test]
+    fn divergences_are_counted_as_errors() {
+        reset();
+
+        record_divergence("stream_search");
+        record_divergence("stream_search");
+
+        let metrics = snapshot();
+        assert_eq!(metrics[0].operation, "stream_search");
+        assert_eq!(metrics[0].requests, 0);
+        assert_eq!(
+            metrics[0].errors,
+            vec![ErrorCount {
+                error: "replay-divergence".to_string(),
+                count: 2
+            }]
+        );
+
+        reset();
+    }
+}
@@ -0,0 +1,470 @@
+//! The pagination conventions of listings and search results.
+//!
+//! A listing takes a `page-request` and returns a page record with the items and the token of the
+//! next page. The tokens handed out here encode the offset of the page in the listing, which
+//! providers without native pagination apply to the full listing with `paginate`.
+//!
+//! Search results get their `page-navigation` from `search_navigation`, the same way for every
+//! provider, from the window the provider reports having applied: the page starts at the query's
+//! `offset`, or else at the reported `page` counted from 1. Providers which don't report the size of
+//! their pages get no navigation.
+//!
+//! Providers cap the hits a regular search can reach. `page_source` decides how a page is served
+//! against that cap, and `collect_page` cuts the page out of the batches of a cursor, such as a
+//! scroll, for the pages beyond it.
+
+use crate::config::get_max_pagination_depth_config;
+use crate::error::invalid_query;
+use crate::golem::search::types::{
+    IndexName, IndexPage, PageNavigation, PageRequest, PageToken, SearchError, SearchQuery,
+    SearchResults,
+};
+
+/// Number of items in a page when the request doesn't set a size
+pub const DEFAULT_PAGE_SIZE: u32 = 100;
+
+/// The number of items requested, at least one
+pub fn page_size(page: &PageRequest) -> u32 {
+    page.size.unwrap_or(DEFAULT_PAGE_SIZE).max(1)
+}
+
+/// The offset of the requested page in the listing
+pub fn page_offset(page: &PageRequest) -> Result<u32, SearchError> {
+    match &page.token {
+        None => Ok(0),
+        Some(token) => token
+            .parse()
+            .map_err(|_| invalid_query(format!("Invalid page token `{token}`"))),
+    }
+}
+
+/// The token of the page following the page at `offset`, none if that page was the last one
+pub fn next_page_token(offset: u32, received: u32, has_more: bool) -> Option<PageToken> {
+    (has_more && received > 0).then(|| (offset + received).to_string())
+}
+
+/// Returns the requested page of a complete listing, and the token of the next page
+pub fn paginate<T>(
+    items: Vec<T>,
+    page: &PageRequest,
+) -> Result<(Vec<T>, Option<PageToken>), SearchError> {
+    let offset = page_offset(page)?;
+    let size = page_size(page);
+    let total = items.len();
+
+    let items = items
+        .into_iter()
+        .skip(offset as usize)
+        .take(size as usize)
+        .collect::<Vec<_>>();
+    let received = items.len() as u32;
+    let has_more = (offset as usize).saturating_add(received as usize) < total;
+
+    Ok((items, next_page_token(offset, received, has_more)))
+}
+
+/// Returns the requested page of a complete list of index names
+pub fn index_page(names: Vec<IndexName>, page: &PageRequest) -> Result<IndexPage, SearchError> {
+    let (items, next_page_token) = paginate(names, page)?;
+    Ok(IndexPage {
+        items,
+        next_page_token,
+    })
+}
+
+/// The navigation from a page of search results to the next ones, none when the provider reports
+/// no page size or the start of the page can't be known. Without a total, a page is assumed to be
+/// followed by more hits when it is full.
+///
+/// Providers report the `page` they applied only when they paginate by page; the page of one
+/// which doesn't starts at the query's offset, and has no next page number.
+pub fn search_navigation(query: &SearchQuery, results: &SearchResults) -> Option<PageNavigation> {
+    let per_page = results.per_page.filter(|per_page| *per_page > 0)?;
+    let offset = match (query.offset, results.page) {
+        (Some(offset), _) => offset,
+        (None, Some(page)) => page.saturating_sub(1).checked_mul(per_page)?,
+        (None, None) => 0,
+    };
+    let received = results.hits.len() as u32;
+    let end = offset.saturating_add(received);
+
+    let has_next = received > 0
+        && match results.total {
+            Some(total) => end < total,
+            None => received >= per_page,
+        };
+    let next_page = (has_next && results.page.is_some() && offset % per_page == 0)
+        .then(|| (offset / per_page).checked_add(2))
+        .flatten();
+    Some(PageNavigation {
+        total_pages: results.total.map(|total| total.div_ceil(per_page)),
+        has_next,
+        next_page,
+        next_offset: has_next.then_some(end),
+    })
+}
+
+/// Sets the navigation of search results, see `search_navigation`
+pub fn with_navigation(query: &SearchQuery, mut results: SearchResults) -> SearchResults {
+    results.navigation = search_navigation(query, &results);
+    results
+}
+
+/// How a page of search results is served
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PageSource {
+    /// A regular search, the page being within the result window of the provider
+    Search,
+    /// Iterating over the results with a cursor, the page being beyond the result window
+    Cursor,
+}
+
+/// Chooses how to serve a page ending at hit `depth` of a provider whose regular searches reach
+/// `result_window` hits. Deeper pages are served by a cursor when the provider has one, up to the
+/// configured maximum pagination depth, and fail with `InvalidQuery` otherwise.
+pub fn page_source(
+    depth: u32,
+    result_window: u32,
+    has_cursor: bool,
+) -> Result<PageSource, SearchError> {
+    if depth <= result_window {
+        return Ok(PageSource::Search);
+    }
+    if !has_cursor {
+        return Err(invalid_query(format!(
+            "Requested page ends at hit {depth}, beyond the provider's limit of {result_window}"
+        )));
+    }
+    let max_depth = get_max_pagination_depth_config();
+    if depth <= max_depth {
+        Ok(PageSource::Cursor)
+    } else {
+        Err(invalid_query(format!(
+            "Requested page ends at hit {depth}, beyond the maximum pagination depth of {max_depth}"
+        )))
+    }
+}
+
+/// Collects the `size` hits starting at hit `from` of a cursor, out of its `first` batch and the
+/// batches `next_batch` returns until one is empty
+pub fn collect_page<H>(
+    from: usize,
+    size: usize,
+    first: Vec<H>,
+    mut next_batch: impl FnMut() -> Result<Vec<H>, SearchError>,
+) -> Result<Vec<H>, SearchError> {
+    let mut batch = first;
+    let mut skipped = 0;
+    let mut hits = Vec::new();
+
+    while !batch.is_empty() {
+        if skipped + batch.len() <= from {
+            skipped += batch.len();
+        } else {
+            let start = from - skipped;
+            skipped = from;
+            let remaining = size - hits.len();
+            hits.extend(batch.drain(start..).take(remaining));
+            if hits.len() >= size {
+                break;
+            }
+        }
+        batch = next_batch()?;
+    }
+
+    Ok(hits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::golem::search::types::SearchHit;
+
+    fn search_query(page: Option<u32>, per_page: Option<u32>, offset: Option<u32>) -> SearchQuery {
+        SearchQuery {
+            q: None,
+            filters: vec![],
+            sort: vec![],
+            facets: vec![],
+            page,
+            per_page,
+            offset,
+            highlight: None,
+            config: None,
+        }
+    }
+
+    fn results(
+        hits: usize,
+        total: Option<u32>,
+        page: Option<u32>,
+        per_page: Option<u32>,
+    ) -> SearchResults {
+        SearchResults {
+            total,
+            page,
+            per_page,
+            hits: (0..hits)
+                .map(|i| SearchHit {
+                    id: format!("doc-{i}"),
+                    score: None,
+                    content: None,
+                    highlights: None,
+                })
+                .collect(),
+            facets: None,
+            took_ms: None,
+            relaxation: None,
+            navigation: None,
+        }
+    }
+
+    fn request(token: Option<&str>, size: Option<u32>) -> PageRequest {
+        PageRequest {
+            token: token.map(str::to_string),
+            size,
+        }
+    }
+
+    fn names(n: usize) -> Vec<IndexName> {
+        (0..n).map(|i| format!("index-{i}")).collect()
+    }
+
+    #[test]
+    fn test_pages_cover_the_listing() {
+        let mut page = request(None, Some(2));
+        let mut seen = Vec::new();
+        loop {
+            let result = index_page(names(5), &page).unwrap();
+            assert!(result.items.len() <= 2);
+            seen.extend(result.items);
+            match result.next_page_token {
+                Some(token) => page.token = Some(token),
+                None => break,
+            }
+        }
+        assert_eq!(seen, names(5));
+    }
+
+    #[test]
+    fn test_last_full_page_has_no_next_token() {
+        let result = index_page(names(4), &request(Some("2"), Some(2))).unwrap();
+        assert_eq!(result.items, vec!["index-2", "index-3"]);
+        assert_eq!(result.next_page_token, None);
+    }
+
+    #[test]
+    fn test_default_page_size() {
+        let result = index_page(names(150), &request(None, None)).unwrap();
+        assert_eq!(result.items.len(), DEFAULT_PAGE_SIZE as usize);
+        assert_eq!(result.next_page_token.as_deref(), Some("100"));
+
+        assert_eq!(page_size(&request(None, Some(0))), 1);
+    }
+
+    #[test]
+    fn test_token_past_the_end() {
+        let result = index_page(names(3), &request(Some("10"), None)).unwrap();
+        assert!(result.items.is_empty());
+        assert_eq!(result.next_page_token, None);
+    }
+
+    #[test]
+    fn test_invalid_token() {
+        assert!(matches!(
+            index_page(names(3), &request(Some("next"), None)),
+            Err(SearchError::InvalidQuery(_))
+        ));
+    }
+
+    #[test]
+    fn test_search_navigation_with_total() {
+        let navigation = search_navigation(
+            &search_query(Some(2), Some(10), None),
+            &results(10, Some(25), Some(2), Some(10)),
+        );
+        assert_eq!(
+            navigation,
+            Some(PageNavigation {
+                total_pages: Some(3),
+                has_next: true,
+                next_page: Some(3),
+                next_offset: Some(20),
+            })
+        );
+
+        let last = search_navigation(
+            &search_query(Some(3), Some(10), None),
+            &results(5, Some(25), Some(3), Some(10)),
+        );
+        assert_eq!(
+            last,
+            Some(PageNavigation {
+                total_pages: Some(3),
+                has_next: false,
+                next_page: None,
+                next_offset: None,
+            })
+        );
+    }
+
+    #[test]
+    fn test_search_navigation_without_total() {
+        let full = search_navigation(
+            &search_query(None, None, None),
+            &results(20, None, Some(1), Some(20)),
+        )
+        .unwrap();
+        assert_eq!(full.total_pages, None);
+        assert!(full.has_next);
+        assert_eq!(full.next_page, Some(2));
+        assert_eq!(full.next_offset, Some(20));
+
+        let partial = search_navigation(
+            &search_query(None, None, None),
+            &results(7, None, Some(1), Some(20)),
+        )
+        .unwrap();
+        assert!(!partial.has_next);
+        assert_eq!(partial.next_offset, None);
+    }
+
+    #[test]
+    fn test_search_navigation_from_unaligned_offset() {
+        let navigation = search_navigation(
+            &search_query(None, Some(10), Some(5)),
+            &results(10, Some(40), Some(1), Some(10)),
+        )
+        .unwrap();
+        assert_eq!(navigation.total_pages, Some(4));
+        assert!(navigation.has_next);
+        assert_eq!(navigation.next_page, None);
+        assert_eq!(navigation.next_offset, Some(15));
+    }
+
+    #[test]
+    fn test_empty_results_have_no_next_page() {
+        let navigation = search_navigation(
+            &search_query(Some(1), Some(10), None),
+            &results(0, Some(0), Some(1), Some(10)),
+        )
+        .unwrap();
+        assert_eq!(navigation.total_pages, Some(0));
+        assert!(!navigation.has_next);
+    }
+
+    #[test]
+    fn test_search_navigation_without_page_size() {
+        assert_eq!(
+            search_navigation(
+                &search_query(Some(2), Some(10), None),
+                &results(10, Some(25), None, None),
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_search_navigation_of_provider_ignoring_pages() {
+        // The provider applied the query's offset, or none, with its default size of 10
+        let navigation = search_navigation(
+            &search_query(Some(3), None, None),
+            &results(10, Some(25), None, Some(10)),
+        )
+        .unwrap();
+        assert_eq!(navigation.total_pages, Some(3));
+        assert!(navigation.has_next);
+        assert_eq!(navigation.next_page, None);
+        assert_eq!(navigation.next_offset, Some(10));
+
+        let navigation = search_navigation(
+            &search_query(None, None, Some(20)),
+            &results(5, Some(25), None, Some(10)),
+        )
+        .unwrap();
+        assert!(!navigation.has_next);
+        assert_eq!(navigation.next_offset, None);
+    }
+
+    #[test]
+    fn test_search_navigation_of_unreachable_page() {
+        assert_eq!(
+            search_navigation(
+                &search_query(Some(u32::MAX), Some(1000), None),
+                &results(0, None, Some(u32::MAX), Some(1000)),
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_page_source_crosses_over_to_the_cursor() {
+        assert_eq!(
+            page_source(10_000, 10_000, true).unwrap(),
+            PageSource::Search
+        );
+        assert_eq!(
+            page_source(10_001, 10_000, true).unwrap(),
+            PageSource::Cursor
+        );
+        assert!(matches!(
+            page_source(get_max_pagination_depth_config() + 1, 10_000, true),
+            Err(SearchError::InvalidQuery(_))
+        ));
+    }
+
+    #[test]
+    fn test_page_source_without_cursor() {
+        assert_eq!(
+            page_source(1_000, 1_000, false).unwrap(),
+            PageSource::Search
+        );
+        assert!(matches!(
+            page_source(1_001, 1_000, false),
+            Err(SearchError::InvalidQuery(_))
+        ));
+    }
+
+    /// The batches of `size` numbers of a cursor over `0..total`, after the first one
+    fn batches(size: usize, total: usize) -> impl FnMut() -> Result<Vec<usize>, SearchError> {
+        let mut next = size;
+        move || {
+            let batch = (next..total.min(next + size)).collect::<Vec<_>>();
+            next += size;
+            Ok(batch)
+        }
+    }
+
+    #[test]
+    fn test_collect_page_across_batches() {
+        let page = collect_page(4, 5, (0..3).collect(), batches(3, 20)).unwrap();
+        assert_eq!(page, vec![4, 5, 6, 7, 8]);
+    }
+
+    #[test]
+    fn test_collect_page_at_the_end_of_the_results() {
+        let page = collect_page(8, 5, (0..3).collect(), batches(3, 10)).unwrap();
+        assert_eq!(page, vec![8, 9]);
+
+        let page = collect_page(12, 5, (0..3).collect(), batches(3, 10)).unwrap();
+        assert!(page.is_empty());
+    }
+
+    #[test]
+    fn test_collect_page_stops_fetching_once_full() {
+        let mut fetched = 0;
+        let page = collect_page(0, 2, vec![0, 1, 2], || {
+            fetched += 1;
+            Ok(vec![3])
+        })
+        .unwrap();
+        assert_eq!(page, vec![0, 1]);
+        assert_eq!(fetched, 0);
+    }
+
+    #[test]
+    fn test_collect_page_fails_with_the_cursor() {
+        let result = collect_page(5, 5, vec![0, 1, 2], || Err(SearchError::Timeout));
+        assert_eq!(result, Err(SearchError::Timeout));
+    }
+}
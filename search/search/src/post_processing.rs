@@ -0,0 +1,283 @@
+//! Client-side post-filtering and re-scoring of search results.
+//!
+//! The `post-filters` and `rescore` settings of `search-config` are applied by `DurableSearch`
+//! to the hits returned by `search`, the same way for every provider. They are meant for small
+//! adjustments of a page of results: the hits removed by a post-filter are not replaced, and the
+//! `total` reported by the provider is left as is.
+//!
+//! The post-filters are also applied to each batch of `stream-search`, with or without durability.
+//! Re-scoring needs all the hits to re-order them, so streams reject it.
+
+use crate::error::invalid_query;
+use crate::golem::search::types::{
+    PostFilter, PostFilterOp, RescoreConfig, SearchConfig, SearchError, SearchHit, SearchQuery,
+    SearchResults,
+};
+use serde_json::Value;
+use std::cmp::Ordering;
+
+/// Applies the post-filters and the re-scoring formula of `config` to `results`
+pub fn apply(config: Option<&SearchConfig>, mut results: SearchResults) -> SearchResults {
+    let Some(config) = config else {
+        return results;
+    };
+    if config.post_filters.is_empty() && config.rescore.is_none() {
+        return results;
+    }
+
+    let mut hits = Vec::with_capacity(results.hits.len());
+    for mut hit in results.hits {
+        let content = content(&hit);
+        if !matches_all(&config.post_filters, &content) {
+            continue;
+        }
+        if let Some(rescore) = &config.rescore {
+            hit.score = Some(rescore_hit(rescore, hit.score, &content));
+        }
+        hits.push(hit);
+    }
+
+    if config.rescore.is_some() {
+        hits.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(Ordering::Equal));
+    }
+    results.hits = hits;
+    results
+}
+
+/// Applies the post-filters of `config` to a batch of hits of a stream
+pub fn filter_hits(config: Option<&SearchConfig>, mut hits: Vec<SearchHit>) -> Vec<SearchHit> {
+    if let Some(config) = config.filter(|config| !config.post_filters.is_empty()) {
+        hits.retain(|hit| matches_all(&config.post_filters, &content(hit)));
+    }
+    hits
+}
+
+/// Rejects the settings that cannot be applied to the batches of a stream
+pub fn check_stream_query(query: &SearchQuery) -> Result<(), SearchError> {
+    match &query.config {
+        Some(config) if config.rescore.is_some() => Err(invalid_query(
+            "Rescore is not supported by stream-search, it needs all the hits to re-order them",
+        )),
+        _ => Ok(()),
+    }
+}
+
+fn content(hit: &SearchHit) -> Value {
+    hit.content
+        .as_deref()
+        .and_then(|content| serde_json::from_str::<Value>(content).ok())
+        .unwrap_or(Value::Null)
+}
+
+fn matches_all(filters: &[PostFilter], content: &Value) -> bool {
+    filters.iter().all(|filter| matches(filter, content))
+}
+
+fn field<'a>(content: &'a Value, path: &str) -> Option<&'a Value> {
+    path.split('.')
+        .try_fold(content, |value, name| value.get(name))
+        .filter(|value| !value.is_null())
+}
+
+fn matches(filter: &PostFilter, content: &Value) -> bool {
+    let actual = field(content, &filter.field);
+    if filter.op == PostFilterOp::Exists {
+        return actual.is_some();
+    }
+
+    let expected = filter.value.as_deref().map(|value| {
+        serde_json::from_str(value).unwrap_or_else(|_| Value::String(value.to_string()))
+    });
+    let ordering = match (actual, &expected) {
+        (Some(actual), Some(expected)) => compare(actual, expected),
+        _ => None,
+    };
+
+    match filter.op {
+        PostFilterOp::Eq => ordering == Some(Ordering::Equal),
+        PostFilterOp::Ne => ordering != Some(Ordering::Equal),
+        PostFilterOp::Gt => ordering == Some(Ordering::Greater),
+        PostFilterOp::Gte => matches!(ordering, Some(Ordering::Greater | Ordering::Equal)),
+        PostFilterOp::Lt => ordering == Some(Ordering::Less),
+        PostFilterOp::Lte => matches!(ordering, Some(Ordering::Less | Ordering::Equal)),
+        PostFilterOp::Exists => unreachable!(),
+    }
+}
+
+/// Compares numbers and strings with each other, other values can only be equal
+fn compare(actual: &Value, expected: &Value) -> Option<Ordering> {
+    match (actual, expected) {
+        (Value::Number(a), Value::Number(b)) => a.as_f64()?.partial_cmp(&b.as_f64()?),
+        (Value::String(a), Value::String(b)) => Some(a.cmp(b)),
+        (a, b) if a == b => Some(Ordering::Equal),
+        _ => None,
+    }
+}
+
+fn rescore_hit(rescore: &RescoreConfig, score: Option<f64>, content: &Value) -> f64 {
+    let fields: f64 = rescore
+        .field_weights
+        .iter()
+        .map(|(name, weight)| {
+            field(content, name)
+                .and_then(Value::as_f64)
+                .unwrap_or_default()
+                * weight
+        })
+        .sum();
+    score.unwrap_or_default() * rescore.score_weight + fields
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(post_filters: Vec<PostFilter>, rescore: Option<RescoreConfig>) -> SearchConfig {
+        SearchConfig {
+            timeout_ms: None,
+            boost_fields: vec![],
+            attributes_to_retrieve: vec![],
+            language: None,
+            typo_tolerance: None,
+            exact_match_boost: None,
+            provider_params: None,
+            profile: None,
+            filter_mode: None,
+            post_filters,
+            rescore,
+            fallbacks: vec![],
+        }
+    }
+
+    fn results(hits: &[(&str, f64, &str)]) -> SearchResults {
+        SearchResults {
+            total: Some(hits.len() as u32),
+            page: None,
+            per_page: None,
+            hits: hits
+                .iter()
+                .map(|(id, score, content)| SearchHit {
+                    id: id.to_string(),
+                    score: Some(*score),
+                    content: Some(content.to_string()),
+                    highlights: None,
+                })
+                .collect(),
+            facets: None,
+            took_ms: None,
+            relaxation: None,
+            navigation: None,
+        }
+    }
+
+    fn ids(results: &SearchResults) -> Vec<&str> {
+        results.hits.iter().map(|hit| hit.id.as_str()).collect()
+    }
+
+    #[test]
+    fn post_filters_remove_non_matching_hits() {
+        let config = config(
+            vec![
+                PostFilter {
+                    field: "stock.available".to_string(),
+                    op: PostFilterOp::Gt,
+                    value: Some("0".to_string()),
+                },
+                PostFilter {
+                    field: "brand".to_string(),
+                    op: PostFilterOp::Ne,
+                    value: Some("\"acme\"".to_string()),
+                },
+            ],
+            None,
+        );
+        let results = results(&[
+            (
+                "1",
+                3.0,
+                r#"{"brand": "globex", "stock": {"available": 2}}"#,
+            ),
+            ("2", 2.0, r#"{"brand": "acme", "stock": {"available": 5}}"#),
+            (
+                "3",
+                1.0,
+                r#"{"brand": "initech", "stock": {"available": 0}}"#,
+            ),
+            ("4", 0.5, r#"{"brand": "initech"}"#),
+        ]);
+
+        let filtered = apply(Some(&config), results);
+        assert_eq!(ids(&filtered), vec!["1"]);
+        assert_eq!(filtered.total, Some(4));
+    }
+
+    #[test]
+    fn rescore_reorders_hits() {
+        let config = config(
+            vec![],
+            Some(RescoreConfig {
+                score_weight: 1.0,
+                field_weights: vec![("rating".to_string(), 0.5)],
+            }),
+        );
+        let results = results(&[
+            ("1", 2.0, r#"{"rating": 1}"#),
+            ("2", 1.0, r#"{"rating": 4}"#),
+            ("3", 1.5, r#"{}"#),
+        ]);
+
+        let rescored = apply(Some(&config), results);
+        assert_eq!(ids(&rescored), vec!["2", "1", "3"]);
+        assert_eq!(rescored.hits[0].score, Some(3.0));
+    }
+
+    #[test]
+    fn post_filters_apply_to_stream_batches() {
+        let config = config(
+            vec![PostFilter {
+                field: "brand".to_string(),
+                op: PostFilterOp::Eq,
+                value: Some("\"acme\"".to_string()),
+            }],
+            None,
+        );
+        let batch = results(&[
+            ("1", 1.0, r#"{"brand": "acme"}"#),
+            ("2", 1.0, r#"{"brand": "globex"}"#),
+        ])
+        .hits;
+
+        let filtered = filter_hits(Some(&config), batch);
+        assert_eq!(
+            filtered
+                .iter()
+                .map(|hit| hit.id.as_str())
+                .collect::<Vec<_>>(),
+            vec!["1"]
+        );
+    }
+
+    #[test]
+    fn streams_reject_rescore() {
+        let query = |rescore: Option<RescoreConfig>| SearchQuery {
+            q: None,
+            filters: vec![],
+            sort: vec![],
+            facets: vec![],
+            page: None,
+            per_page: None,
+            offset: None,
+            highlight: None,
+            config: Some(config(vec![], rescore)),
+        };
+
+        assert!(check_stream_query(&query(None)).is_ok());
+        assert!(matches!(
+            check_stream_query(&query(Some(RescoreConfig {
+                score_weight: 1.0,
+                field_weights: vec![],
+            }))),
+            Err(SearchError::InvalidQuery(_))
+        ));
+    }
+}
@@ -0,0 +1,402 @@
+//! Optional pre-search hook classifying free-text queries with an LLM.
+//!
+//! When the `query-intent` feature is enabled and `SEARCH_QUERY_INTENT_MODEL` is set, the
+//! `q` of every search query is sent to the imported `golem:llm/llm` interface (satisfied by
+//! composing the search component with one of the golem-llm provider components). The model
+//! classifies the query and extracts structured constraints, so that for example
+//! "laptops under 1000 dollars" becomes `q: "laptops"` with a `price:<1000` filter.
+//!
+//! Classifications are cached per worker and, with the `durability` feature, persisted in the
+//! oplog so a replayed worker sees exactly the same rewrite as the original execution.
+
+use crate::config::get_optional_config;
+use crate::golem::search::types::SearchQuery;
+use golem_rust::{FromValueAndType, IntoValue};
+use log::{trace, warn};
+use serde_json::Value;
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+#[allow(dead_code)]
+mod bindings {
+    wit_bindgen::generate!({
+        inline: r#"
+            package golem:search-query-intent@1.0.0;
+
+            world query-intent-client {
+                import golem:llm/llm@1.0.0;
+            }
+        "#,
+        path: "../../llm/wit",
+        world: "golem:search-query-intent/query-intent-client@1.0.0",
+        generate_all,
+    });
+}
+
+use bindings::golem::llm::llm::{self, ChatEvent, Config, ContentPart, Message, Role};
+
+const MODEL_ENV_VAR: &str = "SEARCH_QUERY_INTENT_MODEL";
+const FIELDS_ENV_VAR: &str = "SEARCH_QUERY_INTENT_FIELDS";
+const CACHE_SIZE_ENV_VAR: &str = "SEARCH_QUERY_INTENT_CACHE_SIZE";
+const DEFAULT_CACHE_SIZE: usize = 256;
+
+/// The kind of search need expressed by a query
+#[derive(Debug, Clone, Copy, PartialEq, IntoValue, FromValueAndType)]
+pub enum IntentKind {
+    /// The user is looking for one specific document
+    Navigational,
+    /// The user is exploring a topic
+    Informational,
+    /// The user wants to buy, book or download something
+    Transactional,
+    Unknown,
+}
+
+/// Result of classifying a free-text query
+#[derive(Debug, Clone, PartialEq, IntoValue, FromValueAndType)]
+pub struct QueryIntent {
+    pub kind: IntentKind,
+    /// The remaining free-text part of the query, with the extracted constraints removed
+    pub q: Option<String>,
+    /// Filters detected in the query, in the `field:value` / `field:>value` syntax
+    pub filters: Vec<String>,
+    /// Sort order detected in the query, in the `field:asc` / `field:desc` syntax
+    pub sort: Vec<String>,
+}
+
+thread_local! {
+    static INTENT_CACHE: RefCell<HashMap<String, Option<QueryIntent>>> = RefCell::new(HashMap::new());
+}
+
+/// Rewrites the query based on its classified intent.
+///
+/// Returns the query unchanged if the hook is not configured, the query has no free-text part,
+/// or the classification failed.
+pub fn rewrite_query(query: SearchQuery) -> SearchQuery {
+    let Some(model) = get_optional_config(MODEL_ENV_VAR) else {
+        return query;
+    };
+    let q = match &query.q {
+        Some(q) if !q.trim().is_empty() => q.trim().to_string(),
+        _ => return query,
+    };
+
+    let intent = match INTENT_CACHE.with_borrow(|cache| cache.get(&q).cloned()) {
+        Some(cached) => cached,
+        None => {
+            let intent = classify_durably(&q, &model);
+            INTENT_CACHE.with_borrow_mut(|cache| {
+                if cache.len() >= cache_size() {
+                    cache.clear();
+                }
+                cache.insert(q.clone(), intent.clone());
+            });
+            intent
+        }
+    };
+
+    match intent {
+        Some(intent) => {
+            trace!("Rewriting query {q:?} using classified intent {intent:?}");
+            apply_intent(query, &intent)
+        }
+        None => query,
+    }
+}
+
+fn cache_size() -> usize {
+    get_optional_config(CACHE_SIZE_ENV_VAR)
+        .and_then(|size| size.parse().ok())
+        .unwrap_or(DEFAULT_CACHE_SIZE)
+}
+
+#[cfg(not(feature = "durability"))]
+fn classify_durably(q: &str, model: &str) -> Option<QueryIntent> {
+    classify(q, model)
+}
+
+#[cfg(feature = "durability")]
+fn classify_durably(q: &str, model: &str) -> Option<QueryIntent> {
+    use golem_rust::bindings::golem::durability::durability::DurableFunctionType;
+    use golem_rust::durability::Durability;
+    use golem_rust::{with_persistence_level, PersistenceLevel};
+    use std::fmt::{Display, Formatter};
+
+    #[derive(Debug, IntoValue)]
+    struct ClassifyQueryInput {
+        q: String,
+        model: String,
+    }
+
+    #[derive(Debug, Clone, FromValueAndType, IntoValue)]
+    struct ClassifyQueryOutput {
+        intent: Option<QueryIntent>,
+    }
+
+    #[derive(Debug, FromValueAndType, IntoValue)]
+    struct UnusedError;
+
+    impl Display for UnusedError {
+        fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+            write!(f, "UnusedError")
+        }
+    }
+
+    let durability = Durability::<ClassifyQueryOutput, UnusedError>::new(
+        "golem_search",
+        "classify_query",
+        DurableFunctionType::WriteRemote,
+    );
+    if durability.is_live() {
+        let intent =
+            with_persistence_level(PersistenceLevel::PersistNothing, || classify(q, model));
+        let output = durability.persist_infallible(
+            ClassifyQueryInput {
+                q: q.to_string(),
+                model: model.to_string(),
+            },
+            ClassifyQueryOutput { intent },
+        );
+        output.intent
+    } else {
+        let output: ClassifyQueryOutput = durability.replay_infallible();
+        output.intent
+    }
+}
+
+fn classify(q: &str, model: &str) -> Option<QueryIntent> {
+    let messages = vec![
+        Message {
+            role: Role::System,
+            name: None,
+            content: vec![ContentPart::Text(system_prompt())],
+        },
+        Message {
+            role: Role::User,
+            name: None,
+            content: vec![ContentPart::Text(q.to_string())],
+        },
+    ];
+    let config = Config {
+        model: model.to_string(),
+        temperature: Some(0.0),
+        max_tokens: Some(256),
+        stop_sequences: None,
+        tools: vec![],
+        tool_choice: None,
+        provider_options: vec![],
+    };
+
+    match llm::send(&messages, &config) {
+        ChatEvent::Message(response) => {
+            let text = response
+                .content
+                .into_iter()
+                .filter_map(|part| match part {
+                    ContentPart::Text(text) => Some(text),
+                    ContentPart::Image(_) => None,
+                })
+                .collect::<Vec<_>>()
+                .join("");
+            let intent = parse_intent_response(&text, &allowed_fields());
+            if intent.is_none() {
+                warn!("Could not parse query intent classification: {text}");
+            }
+            intent
+        }
+        ChatEvent::ToolRequest(_) => {
+            warn!("Query intent classification returned an unexpected tool request");
+            None
+        }
+        ChatEvent::Error(error) => {
+            warn!("Query intent classification failed: {}", error.message);
+            None
+        }
+    }
+}
+
+fn allowed_fields() -> Vec<String> {
+    get_optional_config(FIELDS_ENV_VAR)
+        .map(|fields| {
+            fields
+                .split(',')
+                .map(|field| field.trim().to_string())
+                .filter(|field| !field.is_empty())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn system_prompt() -> String {
+    let fields = allowed_fields();
+    let field_hint = if fields.is_empty() {
+        String::new()
+    } else {
+        format!(
+            " Only use the following fields in filters and sort: {}.",
+            fields.join(", ")
+        )
+    };
+
+    format!(
+        "You classify search engine queries. Respond with a single JSON object and nothing else, \
+         with the following keys: \"intent\" (one of \"navigational\", \"informational\", \
+         \"transactional\"), \"query\" (the remaining free-text keywords, with the detected \
+         constraints removed), \"filters\" (a list of constraints such as price ranges or dates, \
+         each written as \"field:value\", \"field:>value\", \"field:>=value\", \"field:<value\" \
+         or \"field:<=value\") and \"sort\" (a list of \"field:asc\" or \"field:desc\" entries if \
+         the query asks for an ordering).{field_hint}"
+    )
+}
+
+/// Extracts the classification from the model's answer, tolerating surrounding prose and
+/// markdown code fences.
+fn parse_intent_response(text: &str, allowed_fields: &[String]) -> Option<QueryIntent> {
+    let start = text.find('{')?;
+    let end = text.rfind('}')?;
+    if end < start {
+        return None;
+    }
+    let json: Value = serde_json::from_str(&text[start..=end]).ok()?;
+    let obj = json.as_object()?;
+
+    let kind = match obj.get("intent").and_then(|v| v.as_str()) {
+        Some("navigational") => IntentKind::Navigational,
+        Some("informational") => IntentKind::Informational,
+        Some("transactional") => IntentKind::Transactional,
+        _ => IntentKind::Unknown,
+    };
+
+    let q = obj
+        .get("query")
+        .and_then(|v| v.as_str())
+        .map(|q| q.trim().to_string())
+        .filter(|q| !q.is_empty());
+
+    let field_allowed = |entry: &String| {
+        let field = entry.split(':').next().unwrap_or_default().trim();
+        !field.is_empty()
+            && entry.contains(':')
+            && (allowed_fields.is_empty() || allowed_fields.iter().any(|f| f == field))
+    };
+
+    let string_list = |key: &str| -> Vec<String> {
+        obj.get(key)
+            .and_then(|v| v.as_array())
+            .map(|items| {
+                items
+                    .iter()
+                    .filter_map(|item| item.as_str())
+                    .map(|item| item.trim().to_string())
+                    .filter(field_allowed)
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+
+    Some(QueryIntent {
+        kind,
+        q,
+        filters: string_list("filters"),
+        sort: string_list("sort"),
+    })
+}
+
+/// Merges the classified intent into the original query. Explicitly provided filters and sort
+/// orders are kept, detected ones are only added on top of them.
+fn apply_intent(mut query: SearchQuery, intent: &QueryIntent) -> SearchQuery {
+    if intent.q.is_some() || !intent.filters.is_empty() {
+        query.q = intent.q.clone();
+    }
+
+    for filter in &intent.filters {
+        if !query.filters.contains(filter) {
+            query.filters.push(filter.clone());
+        }
+    }
+
+    if query.sort.is_empty() {
+        query.sort = intent.sort.clone();
+    }
+
+    query
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn query(q: &str) -> SearchQuery {
+        SearchQuery {
+            q: Some(q.to_string()),
+            filters: vec![],
+            sort: vec![],
+            facets: vec![],
+            page: None,
+            per_page: None,
+            offset: None,
+            highlight: None,
+            config: None,
+        }
+    }
+
+    #[test]
+    fn parse_intent_from_fenced_json() {
+        let text = "Sure!\n```json\n{\"intent\": \"transactional\", \"query\": \"laptops\", \"filters\": [\"price:<1000\"], \"sort\": [\"price:asc\"]}\n```";
+        let intent = parse_intent_response(text, &[]).unwrap();
+
+        assert_eq!(intent.kind, IntentKind::Transactional);
+        assert_eq!(intent.q, Some("laptops".to_string()));
+        assert_eq!(intent.filters, vec!["price:<1000".to_string()]);
+        assert_eq!(intent.sort, vec!["price:asc".to_string()]);
+    }
+
+    #[test]
+    fn parse_intent_drops_unknown_fields() {
+        let text = r#"{"intent": "informational", "query": "novels", "filters": ["year:>1950", "color:red"], "sort": []}"#;
+        let intent = parse_intent_response(text, &["year".to_string()]).unwrap();
+
+        assert_eq!(intent.kind, IntentKind::Informational);
+        assert_eq!(intent.filters, vec!["year:>1950".to_string()]);
+    }
+
+    #[test]
+    fn parse_intent_rejects_non_json() {
+        assert_eq!(parse_intent_response("I don't know", &[]), None);
+    }
+
+    #[test]
+    fn apply_intent_keeps_explicit_constraints() {
+        let mut original = query("cheap fiction books sorted by year");
+        original.filters = vec!["genre:fiction".to_string()];
+        original.sort = vec!["title:asc".to_string()];
+
+        let intent = QueryIntent {
+            kind: IntentKind::Transactional,
+            q: Some("books".to_string()),
+            filters: vec!["genre:fiction".to_string(), "price:<10".to_string()],
+            sort: vec!["year:desc".to_string()],
+        };
+
+        let rewritten = apply_intent(original, &intent);
+        assert_eq!(rewritten.q, Some("books".to_string()));
+        assert_eq!(
+            rewritten.filters,
+            vec!["genre:fiction".to_string(), "price:<10".to_string()]
+        );
+        assert_eq!(rewritten.sort, vec!["title:asc".to_string()]);
+    }
+
+    #[test]
+    fn apply_intent_without_extracted_constraints_keeps_query_text() {
+        let intent = QueryIntent {
+            kind: IntentKind::Navigational,
+            q: None,
+            filters: vec![],
+            sort: vec![],
+        };
+
+        let rewritten = apply_intent(query("the great gatsby"), &intent);
+        assert_eq!(rewritten.q, Some("the great gatsby".to_string()));
+    }
+}
@@ -0,0 +1,292 @@
+use crate::config::{get_config_with_default, get_max_retries_config};
+use crate::deterministic;
+use crate::error::parse_retry_after;
+use log::trace;
+use reqwest::{Response, StatusCode};
+use std::cell::RefCell;
+use std::time::Duration;
+
+/// Retry policy shared by the provider clients.
+///
+/// Only use it for idempotent operations: a request is sent again when it fails with a timeout
+/// or connection error, or when the provider answers with 429 or a transient 5xx status. Delays
+/// grow exponentially with jitter, honor `Retry-After` when present, and the total time spent
+/// waiting for a single request never exceeds the retry budget. Retries are also taken from the
+/// `InvocationBudget` of the current invocation, when there is one.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+    pub budget: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            base_delay: Duration::from_millis(200),
+            max_delay: Duration::from_secs(30),
+            budget: Duration::from_secs(60),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Creates the policy from `SEARCH_PROVIDER_MAX_RETRIES` and `SEARCH_PROVIDER_RETRY_BUDGET`
+    /// (in seconds)
+    pub fn from_config() -> Self {
+        let budget_secs = get_config_with_default("SEARCH_PROVIDER_RETRY_BUDGET", "60")
+            .parse()
+            .unwrap_or(60);
+
+        Self {
+            max_retries: get_max_retries_config(),
+            budget: Duration::from_secs(budget_secs),
+            ..Self::default()
+        }
+    }
+
+    /// Sends the request built by `operation`, retrying it according to the policy.
+    ///
+    /// When all attempts are used up, the last response or error is returned as is so the caller
+    /// can map it the same way as a non-retried one.
+    pub fn execute<F>(&self, operation: F) -> Result<Response, reqwest::Error>
+    where
+        F: Fn() -> Result<Response, reqwest::Error>,
+    {
+        let mut waited = Duration::ZERO;
+        let mut attempt = 0;
+
+        loop {
+            let result = operation();
+
+            let delay = match &result {
+                Ok(response) if is_retryable_status(response.status()) => {
+                    let rate_limited = response.status() == StatusCode::TOO_MANY_REQUESTS;
+                    parse_retry_after(response.headers())
+                        .map(|secs| Duration::from_secs(secs as u64))
+                        .unwrap_or_else(|| self.backoff_delay(attempt, rate_limited))
+                }
+                Err(error) if is_retryable_error(error) => self.backoff_delay(attempt, false),
+                _ => return result,
+            };
+
+            if attempt >= self.max_retries || waited + delay > self.budget {
+                trace!("Giving up after {} attempts", attempt + 1);
+                return result;
+            }
+            if !spend_invocation_budget(delay) {
+                trace!(
+                    "Giving up after {} attempts, the retry budget of the invocation is used up",
+                    attempt + 1
+                );
+                return result;
+            }
+
+            match &result {
+                Ok(response) => trace!(
+                    "Received {} response, retrying in {delay:?} (attempt {}/{})",
+                    response.status(),
+                    attempt + 1,
+                    self.max_retries + 1
+                ),
+                Err(error) => trace!(
+                    "Request failed, retrying in {delay:?} (attempt {}/{}): {error:?}",
+                    attempt + 1,
+                    self.max_retries + 1
+                ),
+            }
+
+            std::thread::sleep(delay);
+            waited += delay;
+            attempt += 1;
+        }
+    }
+
+    /// Exponential backoff with equal jitter: half of the delay is fixed, the other half random
+    fn backoff_delay(&self, attempt: u32, rate_limited: bool) -> Duration {
+        let base = if rate_limited {
+            self.base_delay * 5
+        } else {
+            self.base_delay
+        };
+        let delay = base
+            .saturating_mul(2_u32.saturating_pow(attempt))
+            .min(self.max_delay);
+
+        let half = delay / 2;
+        half + half.mul_f64(jitter())
+    }
+}
+
+/// Retries left to all the requests of one invocation of the component.
+///
+/// A single exported function call can send many requests, each retried on its own by the
+/// `RetryPolicy`. The invocation budget caps the retries and the time spent backing off across
+/// all of them, so a struggling provider makes the call fail within a predictable time instead of
+/// exhausting the retries of every request in turn.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InvocationBudget {
+    pub retries: u32,
+    pub backoff: Duration,
+}
+
+impl Default for InvocationBudget {
+    fn default() -> Self {
+        Self {
+            retries: 10,
+            backoff: Duration::from_secs(60),
+        }
+    }
+}
+
+impl InvocationBudget {
+    /// Creates the budget from `SEARCH_PROVIDER_INVOCATION_MAX_RETRIES` and
+    /// `SEARCH_PROVIDER_INVOCATION_RETRY_BUDGET` (in seconds)
+    pub fn from_config() -> Self {
+        let default = Self::default();
+        let retries = get_config_with_default(
+            "SEARCH_PROVIDER_INVOCATION_MAX_RETRIES",
+            default.retries.to_string(),
+        )
+        .parse()
+        .unwrap_or(default.retries);
+        let backoff_secs = get_config_with_default(
+            "SEARCH_PROVIDER_INVOCATION_RETRY_BUDGET",
+            default.backoff.as_secs().to_string(),
+        )
+        .parse()
+        .unwrap_or(default.backoff.as_secs());
+
+        Self {
+            retries,
+            backoff: Duration::from_secs(backoff_secs),
+        }
+    }
+
+    /// Takes a retry waiting `delay` from the budget, unless the budget can't cover it
+    fn try_spend(&mut self, delay: Duration) -> bool {
+        if self.retries == 0 || delay > self.backoff {
+            return false;
+        }
+        self.retries -= 1;
+        self.backoff -= delay;
+        true
+    }
+}
+
+thread_local! {
+    static INVOCATION_BUDGET: RefCell<Option<InvocationBudget>> = const { RefCell::new(None) };
+}
+
+/// Runs `f` as one invocation, whose requests share a fresh `InvocationBudget`. Nested scopes
+/// share the budget of the outermost one.
+pub fn invocation_scope<T>(f: impl FnOnce() -> T) -> T {
+    struct Reset;
+
+    impl Drop for Reset {
+        fn drop(&mut self) {
+            INVOCATION_BUDGET.with_borrow_mut(|budget| *budget = None);
+        }
+    }
+
+    let outermost = INVOCATION_BUDGET.with_borrow_mut(|budget| {
+        if budget.is_some() {
+            return false;
+        }
+        *budget = Some(InvocationBudget::from_config());
+        true
+    });
+    let _reset = outermost.then_some(Reset);
+    f()
+}
+
+/// Takes a retry from the budget of the current invocation. Requests sent outside of an
+/// invocation scope are only limited by their own `RetryPolicy`.
+fn spend_invocation_budget(delay: Duration) -> bool {
+    INVOCATION_BUDGET
+        .with_borrow_mut(|budget| budget.as_mut().is_none_or(|budget| budget.try_spend(delay)))
+}
+
+fn is_retryable_status(status: StatusCode) -> bool {
+    matches!(
+        status,
+        StatusCode::TOO_MANY_REQUESTS
+            | StatusCode::INTERNAL_SERVER_ERROR
+            | StatusCode::BAD_GATEWAY
+            | StatusCode::SERVICE_UNAVAILABLE
+            | StatusCode::GATEWAY_TIMEOUT
+    )
+}
+
+fn is_retryable_error(error: &reqwest::Error) -> bool {
+    error.is_timeout() || error.is_request()
+}
+
+/// A random number in `[0, 1)` spreading out retries, stable across replays
+fn jitter() -> f64 {
+    deterministic::random_f64()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_grows_and_is_capped() {
+        let policy = RetryPolicy::default();
+
+        for attempt in 0..10 {
+            let delay = policy.backoff_delay(attempt, false);
+            let full = (policy.base_delay * 2_u32.pow(attempt)).min(policy.max_delay);
+            assert!(delay >= full / 2, "attempt {attempt}: {delay:?}");
+            assert!(delay <= full, "attempt {attempt}: {delay:?}");
+        }
+    }
+
+    #[test]
+    fn rate_limited_backoff_is_longer() {
+        let policy = RetryPolicy::default();
+        assert!(policy.backoff_delay(0, true) >= Duration::from_millis(500));
+        assert!(policy.backoff_delay(0, false) <= Duration::from_millis(200));
+    }
+
+    #[test]
+    fn invocation_budget_caps_retries_and_backoff() {
+        let mut budget = InvocationBudget {
+            retries: 2,
+            backoff: Duration::from_secs(3),
+        };
+        assert!(budget.try_spend(Duration::from_secs(2)));
+        assert!(!budget.try_spend(Duration::from_secs(2)));
+        assert!(budget.try_spend(Duration::from_secs(1)));
+        assert!(!budget.try_spend(Duration::ZERO));
+    }
+
+    #[test]
+    fn nested_invocation_scopes_share_the_budget() {
+        assert!(spend_invocation_budget(Duration::from_secs(3600)));
+
+        invocation_scope(|| {
+            let retries = InvocationBudget::from_config().retries;
+            for _ in 0..retries - 1 {
+                assert!(spend_invocation_budget(Duration::ZERO));
+            }
+            invocation_scope(|| assert!(spend_invocation_budget(Duration::ZERO)));
+            assert!(!spend_invocation_budget(Duration::ZERO));
+        });
+
+        assert!(invocation_scope(|| spend_invocation_budget(Duration::ZERO)));
+    }
+
+    #[test]
+    fn only_transient_statuses_are_retried() {
+        assert!(is_retryable_status(StatusCode::TOO_MANY_REQUESTS));
+        assert!(is_retryable_status(StatusCode::SERVICE_UNAVAILABLE));
+        assert!(is_retryable_status(StatusCode::BAD_GATEWAY));
+        assert!(!is_retryable_status(StatusCode::BAD_REQUEST));
+        assert!(!is_retryable_status(StatusCode::NOT_FOUND));
+        assert!(!is_retryable_status(StatusCode::NOT_IMPLEMENTED));
+    }
+}
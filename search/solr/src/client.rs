@@ -0,0 +1,407 @@
+use golem_search::correlation::{self, CORRELATION_ID_HEADER};
+use golem_search::error::{from_reqwest_error, from_send_error, search_error_from_failed_response};
+use golem_search::golem::search::types::SearchError;
+use golem_search::logging::{redact_secret, RedactedResponse};
+use golem_search::retry::RetryPolicy;
+use log::trace;
+use reqwest::{Client, Method, RequestBuilder, Response};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map as JsonMap, Value as JsonValue};
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+use std::time::Duration;
+
+/// The Solr client, managing cores of a standalone Solr server or collections of a SolrCloud
+/// cluster
+#[derive(Clone)]
+pub struct SolrApi {
+    client: Client,
+    base_url: String,
+    username: Option<String>,
+    password: Option<String>,
+    cloud: bool,
+    retry_policy: RetryPolicy,
+    request_timeout: Option<Duration>,
+    headers: Vec<(String, String)>,
+}
+
+impl Debug for SolrApi {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SolrApi")
+            .field("base_url", &self.base_url)
+            .field("username", &self.username)
+            .field("password", &self.password.as_deref().map(redact_secret))
+            .field("cloud", &self.cloud)
+            .field("retry_policy", &self.retry_policy)
+            .field("request_timeout", &self.request_timeout)
+            .finish()
+    }
+}
+
+pub type SolrDoc = JsonMap<String, JsonValue>;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SolrField {
+    pub name: String,
+    pub r#type: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub indexed: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stored: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub doc_values: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub multi_valued: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub required: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FieldsResponse {
+    #[serde(default)]
+    pub fields: Vec<SolrField>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CollectionsResponse {
+    #[serde(default)]
+    pub collections: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CoresResponse {
+    #[serde(default)]
+    pub status: BTreeMap<String, JsonValue>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RealTimeGetResponse {
+    #[serde(default)]
+    pub doc: Option<SolrDoc>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DocList {
+    pub num_found: u64,
+    #[serde(default)]
+    pub docs: Vec<SolrDoc>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryResponse {
+    pub response: DocList,
+    #[serde(default)]
+    pub facets: Option<JsonMap<String, JsonValue>>,
+    #[serde(default)]
+    pub highlighting: Option<JsonMap<String, JsonValue>>,
+    #[serde(default)]
+    pub next_cursor_mark: Option<String>,
+}
+
+impl SolrApi {
+    pub fn new(
+        base_url: String,
+        username: Option<String>,
+        password: Option<String>,
+        cloud: bool,
+    ) -> Self {
+        let client = Client::builder()
+            .build()
+            .expect("Failed to initialize HTTP client");
+
+        Self {
+            client,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            username,
+            password,
+            cloud,
+            retry_policy: RetryPolicy::from_config(),
+            request_timeout: None,
+            headers: Vec::new(),
+        }
+    }
+
+    /// Applies a timeout to every request sent by this client
+    pub fn with_request_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.request_timeout = timeout;
+        self
+    }
+
+    /// Adds custom headers to every request sent by this client
+    pub fn with_headers(mut self, headers: Vec<(String, String)>) -> Self {
+        self.headers = headers;
+        self
+    }
+
+    /// Whether the client manages the collections of a SolrCloud cluster rather than the cores
+    /// of a standalone server
+    pub fn is_cloud(&self) -> bool {
+        self.cloud
+    }
+
+    fn create_request(&self, method: Method, url: &str) -> RequestBuilder {
+        trace!("[Solr] HTTP {method} {url}");
+
+        let mut req = self
+            .client
+            .request(method, url)
+            .header("Content-Type", "application/json")
+            .header(CORRELATION_ID_HEADER, correlation::current());
+
+        if let Some(username) = &self.username {
+            req = req.basic_auth(username, self.password.as_ref());
+        }
+
+        if let Some(timeout) = self.request_timeout {
+            req = req.timeout(timeout);
+        }
+
+        for (name, value) in &self.headers {
+            req = req.header(name, value);
+        }
+
+        req
+    }
+
+    fn send(
+        &self,
+        method: Method,
+        url: &str,
+        body: Option<&impl Serialize>,
+        operation: &str,
+    ) -> Result<Response, SearchError> {
+        self.retry_policy
+            .execute(|| {
+                let req = self.create_request(method.clone(), url);
+                match body {
+                    Some(body) => req.json(body).send(),
+                    None => req.send(),
+                }
+            })
+            .map_err(|e| from_send_error(format!("Failed to {operation}"), e))
+    }
+
+    fn collection_url(&self, collection: &str) -> String {
+        format!("{}/{}", self.base_url, urlencoding::encode(collection))
+    }
+}
+
+fn parse_response<T: DeserializeOwned + Debug>(response: Response) -> Result<T, SearchError> {
+    let status = response.status();
+
+    trace!(
+        "Received response from Solr API: {:?}",
+        RedactedResponse(&response)
+    );
+
+    if status.is_success() {
+        let body = response
+            .json::<T>()
+            .map_err(|err| from_reqwest_error("Failed to decode response body", err))?;
+
+        trace!("Received response from Solr API: {body:?}");
+
+        Ok(body)
+    } else {
+        let error = search_error_from_failed_response(response);
+
+        trace!("Received {status} response from Solr API: {error:?}");
+
+        Err(error)
+    }
+}
+
+/// Checks the status of a response with no body of interest
+fn check_response(response: Response) -> Result<(), SearchError> {
+    if response.status().is_success() {
+        Ok(())
+    } else {
+        Err(search_error_from_failed_response(response))
+    }
+}
+
+impl SolrApi {
+    /// Creates a collection with the Collections API in SolrCloud mode, or a core with the
+    /// CoreAdmin API otherwise. Without a configset, SolrCloud copies the `_default` configset
+    /// for the new collection, while a standalone core needs one to be named.
+    pub fn create_collection(
+        &self,
+        collection: &str,
+        configset: Option<&str>,
+    ) -> Result<(), SearchError> {
+        trace!("Creating collection: {collection}");
+
+        let name = urlencoding::encode(collection);
+        let mut url = if self.cloud {
+            format!(
+                "{}/admin/collections?action=CREATE&name={name}&numShards=1",
+                self.base_url
+            )
+        } else {
+            format!(
+                "{}/admin/cores?action=CREATE&name={name}&instanceDir={name}",
+                self.base_url
+            )
+        };
+        match (configset, self.cloud) {
+            (Some(configset), true) => url.push_str(&format!(
+                "&collection.configName={}",
+                urlencoding::encode(configset)
+            )),
+            (Some(configset), false) => {
+                url.push_str(&format!("&configSet={}", urlencoding::encode(configset)))
+            }
+            (None, _) => {}
+        }
+
+        let response = self
+            .create_request(Method::GET, &url)
+            .send()
+            .map_err(|e| from_send_error("Failed to create collection", e))?;
+
+        check_response(response)
+    }
+
+    /// Deletes a collection, or unloads a core along with its index and instance directory
+    pub fn delete_collection(&self, collection: &str) -> Result<(), SearchError> {
+        trace!("Deleting collection: {collection}");
+
+        let name = urlencoding::encode(collection);
+        let url = if self.cloud {
+            format!(
+                "{}/admin/collections?action=DELETE&name={name}",
+                self.base_url
+            )
+        } else {
+            format!(
+                "{}/admin/cores?action=UNLOAD&core={name}&deleteIndex=true&deleteDataDir=true&deleteInstanceDir=true",
+                self.base_url
+            )
+        };
+        check_response(self.send(Method::GET, &url, None::<&()>, "delete collection")?)
+    }
+
+    pub fn list_collections(&self) -> Result<Vec<String>, SearchError> {
+        trace!("Listing collections");
+
+        if self.cloud {
+            let url = format!("{}/admin/collections?action=LIST", self.base_url);
+            let response: CollectionsResponse =
+                parse_response(self.send(Method::GET, &url, None::<&()>, "list collections")?)?;
+            Ok(response.collections)
+        } else {
+            let url = format!(
+                "{}/admin/cores?action=STATUS&indexInfo=false",
+                self.base_url
+            );
+            let response: CoresResponse =
+                parse_response(self.send(Method::GET, &url, None::<&()>, "list cores")?)?;
+            Ok(response.status.into_keys().collect())
+        }
+    }
+
+    /// The explicitly defined fields of a collection's schema, with the properties inherited from
+    /// their field types
+    pub fn get_fields(&self, collection: &str) -> Result<Vec<SolrField>, SearchError> {
+        trace!("Getting fields of collection: {collection}");
+
+        let url = format!(
+            "{}/schema/fields?showDefaults=true",
+            self.collection_url(collection)
+        );
+        let response: FieldsResponse =
+            parse_response(self.send(Method::GET, &url, None::<&()>, "get fields")?)?;
+        Ok(response.fields)
+    }
+
+    /// Adds and replaces fields of a collection's schema in a single Schema API request
+    pub fn update_fields(
+        &self,
+        collection: &str,
+        added: Vec<SolrField>,
+        replaced: Vec<SolrField>,
+    ) -> Result<(), SearchError> {
+        trace!(
+            "Adding {} and replacing {} fields of collection: {collection}",
+            added.len(),
+            replaced.len()
+        );
+
+        if added.is_empty() && replaced.is_empty() {
+            return Ok(());
+        }
+
+        let url = format!("{}/schema", self.collection_url(collection));
+        let mut commands = JsonMap::new();
+        if !added.is_empty() {
+            commands.insert("add-field".to_string(), json!(added));
+        }
+        if !replaced.is_empty() {
+            commands.insert("replace-field".to_string(), json!(replaced));
+        }
+        check_response(self.send(Method::POST, &url, Some(&commands), "update schema")?)
+    }
+
+    /// Adds or replaces documents, committing them so they are visible to the next search
+    pub fn add_documents(&self, collection: &str, docs: Vec<SolrDoc>) -> Result<(), SearchError> {
+        trace!(
+            "Adding {} documents to collection: {collection}",
+            docs.len()
+        );
+
+        let url = format!("{}/update?commit=true", self.collection_url(collection));
+        let response = self
+            .create_request(Method::POST, &url)
+            .json(&docs)
+            .send()
+            .map_err(|e| from_send_error("Failed to add documents", e))?;
+
+        check_response(response)
+    }
+
+    pub fn delete_documents(&self, collection: &str, ids: &[String]) -> Result<(), SearchError> {
+        trace!(
+            "Deleting {} documents from collection: {collection}",
+            ids.len()
+        );
+
+        let url = format!("{}/update?commit=true", self.collection_url(collection));
+        let request = json!({ "delete": ids });
+        check_response(self.send(Method::POST, &url, Some(&request), "delete documents")?)
+    }
+
+    /// Gets the latest version of a document with the real-time get handler
+    pub fn get_document(&self, collection: &str, id: &str) -> Result<Option<SolrDoc>, SearchError> {
+        trace!("Getting document {id} from collection: {collection}");
+
+        let url = format!(
+            "{}/get?id={}",
+            self.collection_url(collection),
+            urlencoding::encode(id)
+        );
+        let response: RealTimeGetResponse =
+            parse_response(self.send(Method::GET, &url, None::<&()>, "get document")?)?;
+        Ok(response.doc)
+    }
+
+    /// Runs a JSON Request API query
+    pub fn query(
+        &self,
+        collection: &str,
+        request: &JsonValue,
+    ) -> Result<QueryResponse, SearchError> {
+        trace!("Querying collection {collection}: {request}");
+
+        let url = format!("{}/query", self.collection_url(collection));
+        let response = self
+            .retry_policy
+            .execute(|| self.create_request(Method::POST, &url).json(request).send())
+            .map_err(|e| from_send_error("Failed to search", e))?;
+
+        parse_response(response)
+    }
+}
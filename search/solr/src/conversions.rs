@@ -0,0 +1,757 @@
+use crate::client::{QueryResponse, SolrDoc, SolrField};
+use golem_search::config::{get_max_pagination_depth_config, search_filters_match_any};
+use golem_search::error::invalid_query;
+use golem_search::golem::search::types::{
+    Doc, FieldType, Schema, SchemaField, SearchError, SearchHit, SearchQuery,
+};
+use serde_json::{json, Map as JsonMap, Value as JsonValue};
+
+const DEFAULT_PER_PAGE: u32 = 20;
+
+/// Maximum number of values counted per facet
+const MAX_FACET_VALUES: u32 = 100;
+
+/// The unique key field of the collections
+pub const ID_FIELD: &str = "id";
+
+/// Fields Solr adds to documents and schemas, hidden from documents and schemas
+const SYSTEM_FIELDS: [&str; 5] = ["_version_", "_root_", "_nest_path_", "_text_", "score"];
+
+/// The cursor mark of the first page of a cursor
+pub const FIRST_CURSOR_MARK: &str = "*";
+
+pub fn doc_to_solr(doc: Doc) -> Result<SolrDoc, SearchError> {
+    let mut fields = match serde_json::from_str::<JsonValue>(&doc.content) {
+        Ok(JsonValue::Object(fields)) => fields,
+        Ok(_) => return Err(invalid_query("Document content must be a JSON object")),
+        Err(e) => return Err(invalid_query(format!("Invalid JSON in document: {e}"))),
+    };
+    fields.insert(ID_FIELD.to_string(), JsonValue::String(doc.id));
+    Ok(fields)
+}
+
+/// Splits a Solr document into its id and its fields
+fn split_doc(mut doc: SolrDoc) -> (String, SolrDoc) {
+    let id = match doc.remove(ID_FIELD) {
+        Some(JsonValue::String(id)) => id,
+        Some(id) => id.to_string(),
+        None => String::new(),
+    };
+    for field in SYSTEM_FIELDS {
+        doc.remove(field);
+    }
+    (id, doc)
+}
+
+pub fn solr_to_doc(doc: SolrDoc) -> Doc {
+    let (id, fields) = split_doc(doc);
+    Doc {
+        id,
+        content: JsonValue::Object(fields).to_string(),
+    }
+}
+
+fn field_type_name(field_type: FieldType) -> &'static str {
+    match field_type {
+        FieldType::Text => "text_general",
+        FieldType::Keyword => "string",
+        FieldType::Integer => "plong",
+        FieldType::Float => "pdouble",
+        FieldType::Boolean => "boolean",
+        FieldType::Date => "pdate",
+        FieldType::GeoPoint => "location",
+    }
+}
+
+/// The field type of a Solr field type name, from the types of the `_default` configset
+fn parse_field_type(name: &str) -> FieldType {
+    match name {
+        "string" | "strings" => FieldType::Keyword,
+        "pint" | "pints" | "plong" | "plongs" | "int" | "long" => FieldType::Integer,
+        "pfloat" | "pfloats" | "pdouble" | "pdoubles" | "float" | "double" => FieldType::Float,
+        "boolean" | "booleans" => FieldType::Boolean,
+        "pdate" | "pdates" | "date" => FieldType::Date,
+        "location" | "location_rpt" | "point" => FieldType::GeoPoint,
+        _ => FieldType::Text,
+    }
+}
+
+/// The Solr field of a schema field. Faceted and sorted fields other than text fields get
+/// doc values, which text fields don't support.
+pub fn schema_field_to_solr(field: &SchemaField) -> SolrField {
+    SolrField {
+        name: field.name.clone(),
+        r#type: field_type_name(field.field_type).to_string(),
+        indexed: Some(field.index || field.facet || field.sort),
+        stored: Some(true),
+        doc_values: Some((field.facet || field.sort) && field.field_type != FieldType::Text),
+        multi_valued: Some(false),
+        required: Some(field.required),
+    }
+}
+
+fn solr_to_schema_field(field: SolrField) -> Option<SchemaField> {
+    if field.name == ID_FIELD || SYSTEM_FIELDS.contains(&field.name.as_str()) {
+        return None;
+    }
+    let field_type = parse_field_type(&field.r#type);
+    let doc_values = field.doc_values.unwrap_or(false) && field_type != FieldType::Text;
+    Some(SchemaField {
+        name: field.name,
+        field_type,
+        required: field.required.unwrap_or(false),
+        facet: doc_values,
+        sort: doc_values,
+        index: field.indexed.unwrap_or(true),
+    })
+}
+
+pub fn fields_to_schema(fields: Vec<SolrField>) -> Schema {
+    Schema {
+        fields: fields
+            .into_iter()
+            .filter_map(solr_to_schema_field)
+            .collect(),
+        primary_key: Some(ID_FIELD.to_string()),
+    }
+}
+
+/// The fields to add to and replace in a collection's schema to match `schema`. Fields missing
+/// from `schema` are kept.
+pub fn schema_changes(existing: &[SolrField], schema: &Schema) -> (Vec<SolrField>, Vec<SolrField>) {
+    let mut added = Vec::new();
+    let mut replaced = Vec::new();
+    for field in schema.fields.iter().map(schema_field_to_solr) {
+        match existing.iter().find(|existing| existing.name == field.name) {
+            None => added.push(field),
+            Some(existing)
+                if existing.r#type != field.r#type
+                    || existing.indexed != field.indexed
+                    || existing.doc_values.unwrap_or(false)
+                        != field.doc_values.unwrap_or(false)
+                    || existing.required.unwrap_or(false) != field.required.unwrap_or(false) =>
+            {
+                replaced.push(field)
+            }
+            Some(_) => {}
+        }
+    }
+    (added, replaced)
+}
+
+fn is_field_name(key: &str) -> bool {
+    !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.')
+}
+
+/// A term of the standard query parser, quoted unless it is a number or a boolean
+fn solr_term(value: &str) -> String {
+    let value = value.trim();
+    let unquoted = value
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+        .unwrap_or(value);
+    match serde_json::from_str::<JsonValue>(unquoted) {
+        Ok(JsonValue::Number(_) | JsonValue::Bool(_)) if unquoted == value => value.to_string(),
+        _ => format!(
+            "\"{}\"",
+            unquoted.replace('\\', "\\\\").replace('"', "\\\"")
+        ),
+    }
+}
+
+/// Converts a filter to a filter query. Filters are either Solr queries in parentheses, or
+/// `field:value`, `field=value`, `field!=value`, and `field>value` style comparisons with `>`,
+/// `>=`, `<` and `<=`, which become range queries.
+fn filter_to_fq(filter: &str) -> Result<String, SearchError> {
+    let filter = filter.trim();
+    if filter.starts_with('(') {
+        return Ok(filter.to_string());
+    }
+
+    let position = filter
+        .find([':', '=', '!', '<', '>'])
+        .ok_or_else(|| invalid_query(format!("Unsupported filter `{filter}`")))?;
+    let key = filter[..position].trim();
+    let rest = &filter[position..];
+    let (op, value) = [">=", "<=", "!=", ">", "<", "=", ":"]
+        .into_iter()
+        .find_map(|op| Some((op, rest.strip_prefix(op)?)))
+        .ok_or_else(|| invalid_query(format!("Unsupported filter `{filter}`")))?;
+    if !is_field_name(key) || value.trim().is_empty() {
+        return Err(invalid_query(format!("Unsupported filter `{filter}`")));
+    }
+    let term = solr_term(value);
+
+    Ok(match op {
+        ":" | "=" => format!("{key}:{term}"),
+        // Purely negative clauses match nothing once combined, so they exclude from all documents
+        "!=" => format!("*:* -{key}:{term}"),
+        ">" => format!("{key}:{{{term} TO *]"),
+        ">=" => format!("{key}:[{term} TO *]"),
+        "<" => format!("{key}:[* TO {term}}}"),
+        _ => format!("{key}:[* TO {term}]"),
+    })
+}
+
+/// The filter queries of a query, combined into a single one when any filter may match
+fn query_to_filters(query: &SearchQuery) -> Result<Vec<String>, SearchError> {
+    let filters = query
+        .filters
+        .iter()
+        .map(|filter| filter_to_fq(filter))
+        .collect::<Result<Vec<_>, _>>()?;
+    if search_filters_match_any(query) && filters.len() > 1 {
+        Ok(vec![filters
+            .iter()
+            .map(|filter| format!("({filter})"))
+            .collect::<Vec<_>>()
+            .join(" OR ")])
+    } else {
+        Ok(filters)
+    }
+}
+
+/// The sort parameter of a query, from its `field`, `field:asc`, `field:desc` or `-field` sort
+/// entries. Cursors need the unique key as the last sort criterion.
+fn query_to_sort(sort: &[String], cursor: bool) -> Result<Option<String>, SearchError> {
+    let mut sort = sort
+        .iter()
+        .map(|sort| {
+            let sort = sort.trim();
+            let (key, order) = if let Some(key) = sort.strip_prefix('-') {
+                (key, "desc")
+            } else {
+                match sort.rsplit_once(':') {
+                    Some((key, order)) if order.eq_ignore_ascii_case("asc") => (key, "asc"),
+                    Some((key, order)) if order.eq_ignore_ascii_case("desc") => (key, "desc"),
+                    Some(_) => return Err(invalid_query(format!("Invalid sort `{sort}`"))),
+                    None => (sort, "asc"),
+                }
+            };
+            if !is_field_name(key) {
+                return Err(invalid_query(format!("Invalid sort `{sort}`")));
+            }
+            Ok((key.to_string(), order))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if cursor && !sort.iter().any(|(key, _)| key == ID_FIELD) {
+        if sort.is_empty() {
+            sort.push(("score".to_string(), "desc"));
+        }
+        sort.push((ID_FIELD.to_string(), "asc"));
+    }
+
+    Ok((!sort.is_empty()).then(|| {
+        sort.iter()
+            .map(|(key, order)| format!("{key} {order}"))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }))
+}
+
+/// The terms facets counting the values of the query's facets
+fn query_to_facets(facets: &[String]) -> Result<JsonMap<String, JsonValue>, SearchError> {
+    facets
+        .iter()
+        .map(|facet| {
+            if !is_field_name(facet) {
+                return Err(invalid_query(format!("Invalid facet `{facet}`")));
+            }
+            Ok((
+                facet.clone(),
+                json!({ "type": "terms", "field": facet, "limit": MAX_FACET_VALUES }),
+            ))
+        })
+        .collect()
+}
+
+/// Number of hits to skip and number of hits per page of a query
+pub fn query_window(query: &SearchQuery) -> Result<(u32, u32), SearchError> {
+    let per_page = query.per_page.unwrap_or(DEFAULT_PER_PAGE);
+    let offset = query.offset.unwrap_or_else(|| {
+        query
+            .page
+            .unwrap_or(1)
+            .saturating_sub(1)
+            .saturating_mul(per_page)
+    });
+    if offset.saturating_add(per_page) > get_max_pagination_depth_config() {
+        return Err(invalid_query(format!(
+            "Requested hits beyond the maximum pagination depth of {}",
+            get_max_pagination_depth_config()
+        )));
+    }
+    Ok((offset, per_page))
+}
+
+/// Builds the JSON Request API request of a search, reading the page after `cursor_mark` instead
+/// of skipping hits when given one. The provider parameters are added to the request parameters,
+/// for example `{"mm": "2<75%"}`.
+pub fn query_to_request(
+    query: &SearchQuery,
+    cursor_mark: Option<&str>,
+) -> Result<JsonValue, SearchError> {
+    let (offset, per_page) = query_window(query)?;
+    let config = query.config.as_ref();
+    let mut params = JsonMap::new();
+
+    let mut request = JsonMap::new();
+    match query.q.as_deref().map(str::trim).filter(|q| !q.is_empty()) {
+        Some(q) => {
+            request.insert("query".to_string(), json!(q));
+            params.insert("defType".to_string(), json!("edismax"));
+            if let Some(config) = config.filter(|config| !config.boost_fields.is_empty()) {
+                let qf = config
+                    .boost_fields
+                    .iter()
+                    .map(|(field, boost)| format!("{field}^{boost}"))
+                    .collect::<Vec<_>>();
+                params.insert("qf".to_string(), json!(qf.join(" ")));
+            }
+        }
+        None => {
+            request.insert("query".to_string(), json!("*:*"));
+        }
+    }
+
+    let filters = query_to_filters(query)?;
+    if !filters.is_empty() {
+        request.insert("filter".to_string(), json!(filters));
+    }
+
+    match cursor_mark {
+        Some(cursor_mark) => {
+            params.insert("cursorMark".to_string(), json!(cursor_mark));
+        }
+        None => {
+            request.insert("offset".to_string(), json!(offset));
+        }
+    }
+    request.insert("limit".to_string(), json!(per_page));
+
+    if let Some(sort) = query_to_sort(&query.sort, cursor_mark.is_some())? {
+        request.insert("sort".to_string(), json!(sort));
+    }
+
+    let attributes = config
+        .map(|config| config.attributes_to_retrieve.clone())
+        .unwrap_or_default();
+    let fields = if attributes.is_empty() {
+        vec!["*".to_string(), "score".to_string()]
+    } else {
+        [ID_FIELD.to_string(), "score".to_string()]
+            .into_iter()
+            .chain(attributes)
+            .collect()
+    };
+    request.insert("fields".to_string(), json!(fields));
+
+    let facets = query_to_facets(&query.facets)?;
+    if !facets.is_empty() {
+        request.insert("facet".to_string(), JsonValue::Object(facets));
+    }
+
+    if let Some(highlight) = &query.highlight {
+        params.insert("hl".to_string(), json!(true));
+        params.insert("hl.method".to_string(), json!("unified"));
+        if !highlight.fields.is_empty() {
+            params.insert("hl.fl".to_string(), json!(highlight.fields.join(",")));
+        }
+        if let Some(pre_tag) = &highlight.pre_tag {
+            params.insert("hl.tag.pre".to_string(), json!(pre_tag));
+        }
+        if let Some(post_tag) = &highlight.post_tag {
+            params.insert("hl.tag.post".to_string(), json!(post_tag));
+        }
+        if let Some(max_length) = highlight.max_length {
+            params.insert("hl.fragsize".to_string(), json!(max_length));
+        }
+    }
+
+    if let Some(provider_params) = config.and_then(|config| config.provider_params.as_deref()) {
+        let provider_params =
+            serde_json::from_str::<JsonMap<String, JsonValue>>(provider_params)
+                .map_err(|e| invalid_query(format!("Invalid provider parameters: {e}")))?;
+        params.extend(
+            provider_params
+                .into_iter()
+                .filter(|(key, _)| key != "headers"),
+        );
+    }
+
+    if !params.is_empty() {
+        request.insert("params".to_string(), JsonValue::Object(params));
+    }
+
+    Ok(JsonValue::Object(request))
+}
+
+fn doc_to_hit(doc: SolrDoc, highlighting: Option<&JsonMap<String, JsonValue>>) -> SearchHit {
+    let score = doc.get("score").and_then(JsonValue::as_f64);
+    let (id, fields) = split_doc(doc);
+    let highlights = highlighting
+        .and_then(|highlighting| highlighting.get(&id))
+        .filter(|highlights| highlights.as_object().is_some_and(|map| !map.is_empty()))
+        .map(JsonValue::to_string);
+    SearchHit {
+        id,
+        score,
+        content: Some(JsonValue::Object(fields).to_string()),
+        highlights,
+    }
+}
+
+/// Facet counts in the same shape as the other providers: `{"field": {"value": count}}`
+fn facets_to_counts(facets: &JsonMap<String, JsonValue>) -> Option<String> {
+    let counts = facets
+        .iter()
+        .filter_map(|(field, facet)| {
+            let counts = facet
+                .get("buckets")?
+                .as_array()?
+                .iter()
+                .filter_map(|bucket| {
+                    let value = match bucket.get("val")? {
+                        JsonValue::String(value) => value.clone(),
+                        value => value.to_string(),
+                    };
+                    Some((value, bucket.get("count")?.clone()))
+                })
+                .collect::<JsonMap<_, _>>();
+            Some((field.clone(), JsonValue::Object(counts)))
+        })
+        .collect::<JsonMap<_, _>>();
+    (!counts.is_empty()).then(|| JsonValue::Object(counts).to_string())
+}
+
+/// The hits, total and facet counts of a query response
+pub fn parse_response(response: QueryResponse) -> (Vec<SearchHit>, Option<u32>, Option<String>) {
+    let highlighting = response.highlighting.as_ref();
+    let hits = response
+        .response
+        .docs
+        .into_iter()
+        .map(|doc| doc_to_hit(doc, highlighting))
+        .collect();
+    (
+        hits,
+        Some(response.response.num_found as u32),
+        response.facets.as_ref().and_then(facets_to_counts),
+    )
+}
+
+pub fn create_retry_query(original_query: &SearchQuery, partial_hits: &[SearchHit]) -> SearchQuery {
+    let mut retry_query = original_query.clone();
+
+    let current_offset = original_query.offset.unwrap_or(0);
+    let per_page = original_query.per_page.unwrap_or(DEFAULT_PER_PAGE);
+    let hits_received = partial_hits.len() as u32;
+
+    retry_query.offset = Some(current_offset + hits_received.min(per_page));
+
+    retry_query
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use golem_search::golem::search::types::{FilterMode, HighlightConfig, SearchConfig};
+
+    fn query(q: Option<&str>, filters: &[&str]) -> SearchQuery {
+        SearchQuery {
+            q: q.map(str::to_string),
+            filters: filters.iter().map(|filter| filter.to_string()).collect(),
+            sort: vec![],
+            facets: vec![],
+            page: None,
+            per_page: None,
+            offset: None,
+            highlight: None,
+            config: None,
+        }
+    }
+
+    fn field(name: &str, field_type: FieldType, facet: bool, sort: bool) -> SchemaField {
+        SchemaField {
+            name: name.to_string(),
+            field_type,
+            required: false,
+            facet,
+            sort,
+            index: true,
+        }
+    }
+
+    fn config(provider_params: Option<&str>, filter_mode: Option<FilterMode>) -> SearchConfig {
+        SearchConfig {
+            timeout_ms: None,
+            boost_fields: vec![],
+            attributes_to_retrieve: vec![],
+            language: None,
+            typo_tolerance: None,
+            exact_match_boost: None,
+            provider_params: provider_params.map(str::to_string),
+            profile: None,
+            filter_mode,
+            post_filters: vec![],
+            rescore: None,
+            fallbacks: vec![],
+        }
+    }
+
+    #[test]
+    fn test_doc_conversions() {
+        let doc = doc_to_solr(Doc {
+            id: "doc1".to_string(),
+            content: r#"{"title":"Dune"}"#.to_string(),
+        })
+        .unwrap();
+        assert_eq!(
+            JsonValue::Object(doc.clone()),
+            json!({ "id": "doc1", "title": "Dune" })
+        );
+
+        let mut stored = doc;
+        stored.insert("_version_".to_string(), json!(1790000000000000000u64));
+        let doc = solr_to_doc(stored);
+        assert_eq!(doc.id, "doc1");
+        assert_eq!(doc.content, r#"{"title":"Dune"}"#);
+
+        assert!(doc_to_solr(Doc {
+            id: "doc1".to_string(),
+            content: "[]".to_string(),
+        })
+        .is_err());
+    }
+
+    #[test]
+    fn test_schema_round_trip() {
+        let schema = Schema {
+            fields: vec![
+                field("title", FieldType::Text, false, false),
+                field("genre", FieldType::Keyword, true, false),
+                field("year", FieldType::Integer, false, true),
+                field("published", FieldType::Date, false, false),
+            ],
+            primary_key: None,
+        };
+        let fields = schema
+            .fields
+            .iter()
+            .map(schema_field_to_solr)
+            .collect::<Vec<_>>();
+        assert_eq!(fields[0].r#type, "text_general");
+        assert_eq!(fields[1].doc_values, Some(true));
+        assert_eq!(fields[3].doc_values, Some(false));
+
+        let mut stored = fields.clone();
+        stored.push(SolrField {
+            name: "_version_".to_string(),
+            r#type: "plong".to_string(),
+            indexed: Some(false),
+            stored: Some(false),
+            doc_values: Some(true),
+            multi_valued: None,
+            required: None,
+        });
+        let round_trip = fields_to_schema(stored);
+        let types = round_trip
+            .fields
+            .iter()
+            .map(|field| (field.name.as_str(), field.field_type))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            types,
+            vec![
+                ("title", FieldType::Text),
+                ("genre", FieldType::Keyword),
+                ("year", FieldType::Integer),
+                ("published", FieldType::Date),
+            ]
+        );
+        assert!(round_trip.fields[1].facet);
+        assert!(round_trip.fields[2].sort);
+        assert!(!round_trip.fields[3].sort);
+        assert_eq!(round_trip.primary_key.as_deref(), Some("id"));
+
+        let updated = Schema {
+            fields: vec![
+                field("title", FieldType::Text, false, false),
+                field("genre", FieldType::Keyword, false, false),
+                field("pages", FieldType::Integer, false, true),
+            ],
+            primary_key: None,
+        };
+        let (added, replaced) = schema_changes(&fields, &updated);
+        assert_eq!(
+            added.iter().map(|f| f.name.as_str()).collect::<Vec<_>>(),
+            vec!["pages"]
+        );
+        assert_eq!(
+            replaced.iter().map(|f| f.name.as_str()).collect::<Vec<_>>(),
+            vec!["genre"]
+        );
+    }
+
+    #[test]
+    fn test_filters_to_fq() {
+        assert_eq!(filter_to_fq("genre:fiction").unwrap(), "genre:\"fiction\"");
+        assert_eq!(
+            filter_to_fq("title = \"say \\\"hi\\\"\"").unwrap(),
+            "title:\"say \\\\\\\"hi\\\\\\\"\""
+        );
+        assert_eq!(filter_to_fq("year>=1950").unwrap(), "year:[1950 TO *]");
+        assert_eq!(filter_to_fq("year>1950").unwrap(), "year:{1950 TO *]");
+        assert_eq!(filter_to_fq("year<1950").unwrap(), "year:[* TO 1950}");
+        assert_eq!(
+            filter_to_fq("published<=2020-01-01T00:00:00Z").unwrap(),
+            "published:[* TO \"2020-01-01T00:00:00Z\"]"
+        );
+        assert_eq!(filter_to_fq("in_stock:true").unwrap(), "in_stock:true");
+        assert_eq!(
+            filter_to_fq("genre!=romance").unwrap(),
+            "*:* -genre:\"romance\""
+        );
+        assert_eq!(
+            filter_to_fq("(year:[* TO 2000] OR year:[2010 TO *])").unwrap(),
+            "(year:[* TO 2000] OR year:[2010 TO *])"
+        );
+        assert!(filter_to_fq("no operator").is_err());
+        assert!(filter_to_fq("year) OR (true:1").is_err());
+    }
+
+    #[test]
+    fn test_query_to_request() {
+        let mut query = query(Some("dune"), &["genre:fiction", "year>1950"]);
+        query.sort = vec!["-year".to_string(), "title".to_string()];
+        query.facets = vec!["genre".to_string()];
+        query.page = Some(3);
+        query.per_page = Some(10);
+        query.highlight = Some(HighlightConfig {
+            fields: vec!["title".to_string(), "summary".to_string()],
+            pre_tag: Some("<b>".to_string()),
+            post_tag: Some("</b>".to_string()),
+            max_length: Some(80),
+        });
+        let mut config = config(
+            Some(r#"{"mm": "2<75%", "headers": {"X-Tenant": "a"}}"#),
+            None,
+        );
+        config.boost_fields = vec![("title".to_string(), 2.0), ("summary".to_string(), 1.0)];
+        query.config = Some(config);
+
+        assert_eq!(
+            query_to_request(&query, None).unwrap(),
+            json!({
+                "query": "dune",
+                "filter": ["genre:\"fiction\"", "year:{1950 TO *]"],
+                "offset": 20,
+                "limit": 10,
+                "sort": "year desc, title asc",
+                "fields": ["*", "score"],
+                "facet": { "genre": { "type": "terms", "field": "genre", "limit": 100 } },
+                "params": {
+                    "defType": "edismax",
+                    "qf": "title^2 summary^1",
+                    "hl": true,
+                    "hl.method": "unified",
+                    "hl.fl": "title,summary",
+                    "hl.tag.pre": "<b>",
+                    "hl.tag.post": "</b>",
+                    "hl.fragsize": 80,
+                    "mm": "2<75%",
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn test_query_to_request_with_cursor() {
+        let mut query = query(None, &["genre:fiction", "genre!=poetry"]);
+        query.config = Some(config(None, Some(FilterMode::Any)));
+
+        assert_eq!(
+            query_to_request(&query, Some(FIRST_CURSOR_MARK)).unwrap(),
+            json!({
+                "query": "*:*",
+                "filter": ["(genre:\"fiction\") OR (*:* -genre:\"poetry\")"],
+                "limit": 20,
+                "sort": "score desc, id asc",
+                "fields": ["*", "score"],
+                "params": { "cursorMark": "*" },
+            })
+        );
+
+        let mut sorted = self::query(None, &[]);
+        sorted.sort = vec!["year:desc".to_string()];
+        assert_eq!(
+            query_to_request(&sorted, Some("AoE")).unwrap()["sort"],
+            "year desc, id asc"
+        );
+    }
+
+    #[test]
+    fn test_parse_response() {
+        let response = serde_json::from_value::<QueryResponse>(json!({
+            "responseHeader": { "status": 0, "QTime": 1 },
+            "response": {
+                "numFound": 42,
+                "start": 0,
+                "docs": [
+                    { "id": "doc1", "title": "Dune", "score": 1.5, "_version_": 1 },
+                    { "id": "doc2", "title": "Emma", "score": 0.5 }
+                ]
+            },
+            "facets": {
+                "count": 42,
+                "genre": { "buckets": [{ "val": "fiction", "count": 2 }] },
+                "year": { "buckets": [{ "val": 1965, "count": 1 }] }
+            },
+            "highlighting": {
+                "doc1": { "title": ["<em>Dune</em>"] },
+                "doc2": {}
+            },
+            "nextCursorMark": "AoE"
+        }))
+        .unwrap();
+        assert_eq!(response.next_cursor_mark.as_deref(), Some("AoE"));
+
+        let (hits, total, facets) = parse_response(response);
+        assert_eq!(total, Some(42));
+        assert_eq!(hits.len(), 2);
+        assert_eq!(hits[0].id, "doc1");
+        assert_eq!(hits[0].score, Some(1.5));
+        assert_eq!(hits[0].content.as_deref(), Some(r#"{"title":"Dune"}"#));
+        assert_eq!(
+            hits[0].highlights.as_deref(),
+            Some(r#"{"title":["<em>Dune</em>"]}"#)
+        );
+        assert_eq!(hits[1].highlights, None);
+        assert_eq!(
+            serde_json::from_str::<JsonValue>(&facets.unwrap()).unwrap(),
+            json!({ "genre": { "fiction": 2 }, "year": { "1965": 1 } })
+        );
+    }
+
+    #[test]
+    fn test_create_retry_query() {
+        let mut original = query(Some("dune"), &[]);
+        original.offset = Some(10);
+        original.per_page = Some(5);
+        let hits = vec![
+            SearchHit {
+                id: "doc1".to_string(),
+                score: None,
+                content: None,
+                highlights: None,
+            };
+            7
+        ];
+
+        assert_eq!(create_retry_query(&original, &hits).offset, Some(15));
+    }
+}
@@ -0,0 +1,340 @@
+use crate::client::SolrApi;
+use crate::conversions::{
+    create_retry_query, doc_to_solr, fields_to_schema, parse_response, query_to_request,
+    query_window, schema_changes, schema_field_to_solr, solr_to_doc, FIRST_CURSOR_MARK,
+};
+use golem_rust::wasm_rpc::Pollable;
+use golem_search::config::{
+    get_optional_config, profile_key, search_headers, search_profile, search_timeout,
+    worker_headers, worker_profile,
+};
+use golem_search::durability::{DurableSearch, ExtendedGuest};
+use golem_search::golem::search::core::{Guest, GuestSearchStream, SearchStream};
+use golem_search::golem::search::types::{
+    Doc, DocumentId, IndexName, IndexPage, PageRequest, Schema, SearchError, SearchHit,
+    SearchQuery, SearchResults,
+};
+use golem_search::pagination::index_page;
+use golem_search::LOGGING_STATE;
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+
+mod client;
+mod conversions;
+
+/// Search stream over the matches of a query. Streams starting at the first hit are read with
+/// Solr cursors, streams resumed at an offset are paged through with offsets, as cursors can't
+/// skip hits.
+struct SolrSearchStream {
+    client: SolrApi,
+    index_name: String,
+    query: SearchQuery,
+    offset: Cell<u32>,
+    /// The cursor mark of the next page, `None` for streams paged with offsets
+    cursor_mark: RefCell<Option<String>>,
+    finished: Cell<bool>,
+}
+
+impl SolrSearchStream {
+    pub fn new(client: SolrApi, index_name: String, query: SearchQuery) -> Self {
+        let offset = query_window(&query).map(|(offset, _)| offset).unwrap_or(0);
+        Self {
+            client,
+            index_name,
+            query,
+            offset: Cell::new(offset),
+            cursor_mark: RefCell::new((offset == 0).then(|| FIRST_CURSOR_MARK.to_string())),
+            finished: Cell::new(false),
+        }
+    }
+
+    pub fn subscribe(&self) -> Pollable {
+        golem_rust::bindings::wasi::clocks::monotonic_clock::subscribe_duration(0)
+    }
+
+    /// Reads the next page of hits, and whether there may be more
+    fn next_page(&self, per_page: u32) -> Result<(Vec<SearchHit>, bool), SearchError> {
+        let cursor_mark = self.cursor_mark.borrow().clone();
+        let mut query = self.query.clone();
+        query.page = None;
+
+        if let Some(cursor_mark) = cursor_mark {
+            query.offset = None;
+            let request = query_to_request(&query, Some(&cursor_mark))?;
+            let response = self.client.query(&self.index_name, &request)?;
+            let next_cursor_mark = response.next_cursor_mark.clone();
+            let (hits, _, _) = parse_response(response);
+            let more = next_cursor_mark
+                .as_ref()
+                .is_some_and(|next| *next != cursor_mark);
+            *self.cursor_mark.borrow_mut() = next_cursor_mark;
+            Ok((hits, more))
+        } else {
+            query.offset = Some(self.offset.get());
+            let results = SolrComponent::search_page(&self.client, &self.index_name, &query)?;
+            let more = results.hits.len() as u32 >= per_page;
+            Ok((results.hits, more))
+        }
+    }
+}
+
+impl GuestSearchStream for SolrSearchStream {
+    fn get_next(&self) -> Option<Vec<SearchHit>> {
+        if self.finished.get() {
+            return Some(vec![]);
+        }
+
+        let per_page = query_window(&self.query)
+            .map(|(_, per_page)| per_page)
+            .unwrap_or(0);
+
+        match self.next_page(per_page) {
+            Ok((hits, more)) => {
+                if !more || hits.is_empty() {
+                    self.finished.set(true);
+                }
+                self.offset.set(self.offset.get() + hits.len() as u32);
+                Some(hits)
+            }
+            Err(_) => {
+                self.finished.set(true);
+                Some(vec![])
+            }
+        }
+    }
+
+    fn blocking_get_next(&self) -> Vec<SearchHit> {
+        self.get_next().unwrap_or_default()
+    }
+}
+
+struct SolrComponent;
+
+thread_local! {
+    /// The provider clients by configuration profile, reused across calls instead of being rebuilt
+    /// for each of them
+    static CLIENTS: RefCell<HashMap<Option<String>, SolrApi>> = RefCell::new(HashMap::new());
+}
+
+impl SolrComponent {
+    const BASE_URL_ENV_VAR: &'static str = "SOLR_BASE_URL";
+    const USERNAME_ENV_VAR: &'static str = "SOLR_USERNAME";
+    const PASSWORD_ENV_VAR: &'static str = "SOLR_PASSWORD";
+    const MODE_ENV_VAR: &'static str = "SOLR_MODE";
+    const CONFIGSET_ENV_VAR: &'static str = "SOLR_CONFIGSET";
+
+    fn create_client() -> Result<SolrApi, SearchError> {
+        Self::create_profile_client(worker_profile().as_deref())
+            .map(|client| client.with_headers(worker_headers()))
+    }
+
+    /// Returns the client of `profile` cached for this worker, creating it from the configuration
+    /// on first use
+    fn create_profile_client(profile: Option<&str>) -> Result<SolrApi, SearchError> {
+        CLIENTS.with_borrow_mut(|clients| {
+            let profile = profile.map(str::to_string);
+            if let Some(client) = clients.get(&profile) {
+                return Ok(client.clone());
+            }
+
+            let client = Self::new_client(profile.as_deref())?;
+            clients.insert(profile, client.clone());
+            Ok(client)
+        })
+    }
+
+    /// Creates a client from the `SOLR_BASE_URL` of the server, for example
+    /// `http://localhost:8983/solr`, the optional `SOLR_USERNAME` and `SOLR_PASSWORD` of basic
+    /// authentication, and the `SOLR_MODE`, `standalone` by default or `cloud` for SolrCloud
+    fn new_client(profile: Option<&str>) -> Result<SolrApi, SearchError> {
+        let base_url = get_optional_config(profile_key(Self::BASE_URL_ENV_VAR, profile))
+            .ok_or_else(|| SearchError::Internal("Missing Solr base URL".to_string()))?;
+        let username = get_optional_config(profile_key(Self::USERNAME_ENV_VAR, profile))
+            .filter(|username| !username.is_empty());
+        let password = get_optional_config(profile_key(Self::PASSWORD_ENV_VAR, profile))
+            .filter(|password| !password.is_empty());
+        let cloud = match get_optional_config(profile_key(Self::MODE_ENV_VAR, profile)) {
+            None => false,
+            Some(mode) if mode.eq_ignore_ascii_case("standalone") => false,
+            Some(mode) if mode.eq_ignore_ascii_case("cloud") => true,
+            Some(mode) => {
+                return Err(SearchError::Internal(format!(
+                    "Invalid Solr mode `{mode}`, expected `standalone` or `cloud`"
+                )))
+            }
+        };
+
+        Ok(SolrApi::new(base_url, username, password, cloud))
+    }
+
+    /// The configset of new collections. SolrCloud copies the `_default` configset for each
+    /// collection when none is configured, standalone cores use `_default` itself, so cores
+    /// sharing it also share schema changes unless a configset per core is configured.
+    fn configset(client: &SolrApi) -> Option<String> {
+        get_optional_config(profile_key(
+            Self::CONFIGSET_ENV_VAR,
+            worker_profile().as_deref(),
+        ))
+        .filter(|configset| !configset.is_empty())
+        .or_else(|| (!client.is_cloud()).then(|| "_default".to_string()))
+    }
+
+    fn search_page(
+        client: &SolrApi,
+        index: &str,
+        query: &SearchQuery,
+    ) -> Result<SearchResults, SearchError> {
+        let (offset, per_page) = query_window(query)?;
+        let request = query_to_request(query, None)?;
+
+        let response = client.query(index, &request)?;
+        let (hits, total, facets) = parse_response(response);
+
+        Ok(SearchResults {
+            total,
+            page: Some(offset / per_page.max(1) + 1),
+            per_page: Some(per_page),
+            hits,
+            facets,
+            took_ms: None,
+            relaxation: None,
+            navigation: None,
+        })
+    }
+}
+
+impl Guest for SolrComponent {
+    type SearchStream = SolrSearchStream;
+
+    /// Creates the collection, then adds the fields of the schema with the Schema API
+    fn create_index(name: IndexName, schema: Option<Schema>) -> Result<(), SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = Self::create_client()?;
+        client.create_collection(&name, Self::configset(&client).as_deref())?;
+
+        if let Some(schema) = schema {
+            let fields = schema.fields.iter().map(schema_field_to_solr).collect();
+            client.update_fields(&name, fields, vec![])?;
+        }
+        Ok(())
+    }
+
+    fn delete_index(name: IndexName) -> Result<(), SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = Self::create_client()?;
+        client.delete_collection(&name)
+    }
+
+    fn list_indexes(page: PageRequest) -> Result<IndexPage, SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = Self::create_client()?;
+        index_page(client.list_collections()?, &page)
+    }
+
+    fn upsert(index: IndexName, doc: Doc) -> Result<(), SearchError> {
+        Self::upsert_many(index, vec![doc])
+    }
+
+    fn upsert_many(index: IndexName, docs: Vec<Doc>) -> Result<(), SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        if docs.is_empty() {
+            return Ok(());
+        }
+
+        let client = Self::create_client()?;
+        let docs = docs
+            .into_iter()
+            .map(doc_to_solr)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        client.add_documents(&index, docs)
+    }
+
+    fn delete(index: IndexName, id: DocumentId) -> Result<(), SearchError> {
+        Self::delete_many(index, vec![id])
+    }
+
+    fn delete_many(index: IndexName, ids: Vec<DocumentId>) -> Result<(), SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        if ids.is_empty() {
+            return Ok(());
+        }
+
+        let client = Self::create_client()?;
+        client.delete_documents(&index, &ids)
+    }
+
+    fn get(index: IndexName, id: DocumentId) -> Result<Option<Doc>, SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = Self::create_client()?;
+        Ok(client.get_document(&index, &id)?.map(solr_to_doc))
+    }
+
+    fn search(index: IndexName, query: SearchQuery) -> Result<SearchResults, SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = Self::create_profile_client(search_profile(&query).as_deref())?
+            .with_request_timeout(search_timeout(&query))
+            .with_headers(search_headers(&query));
+
+        Self::search_page(&client, &index, &query)
+    }
+
+    fn stream_search(index: IndexName, query: SearchQuery) -> Result<SearchStream, SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = Self::create_profile_client(search_profile(&query).as_deref())?
+            .with_request_timeout(search_timeout(&query))
+            .with_headers(search_headers(&query));
+        let stream = SolrSearchStream::new(client, index, query);
+        Ok(SearchStream::new(stream))
+    }
+
+    fn get_schema(index: IndexName) -> Result<Schema, SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = Self::create_client()?;
+        Ok(fields_to_schema(client.get_fields(&index)?))
+    }
+
+    /// Adds the new fields of the schema and replaces the changed ones. Fields missing from the
+    /// schema are kept, and documents indexed before a field changed must be reindexed to match it.
+    fn update_schema(index: IndexName, schema: Schema) -> Result<(), SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = Self::create_client()?;
+        let existing = client.get_fields(&index)?;
+        let (added, replaced) = schema_changes(&existing, &schema);
+        client.update_fields(&index, added, replaced)
+    }
+}
+
+impl ExtendedGuest for SolrComponent {
+    fn unwrapped_stream(index: IndexName, query: SearchQuery) -> Self::SearchStream {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = Self::create_profile_client(search_profile(&query).as_deref())
+            .unwrap_or_else(|_| SolrApi::new("".to_string(), None, None, false))
+            .with_headers(search_headers(&query));
+
+        SolrSearchStream::new(client, index, query)
+    }
+
+    fn retry_query(original_query: &SearchQuery, partial_hits: &[SearchHit]) -> SearchQuery {
+        create_retry_query(original_query, partial_hits)
+    }
+
+    fn subscribe(stream: &Self::SearchStream) -> Pollable {
+        stream.subscribe()
+    }
+}
+
+type DurableSolrComponent = DurableSearch<SolrComponent>;
+
+golem_search::export_search!(DurableSolrComponent with_types_in golem_search);
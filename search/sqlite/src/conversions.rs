@@ -0,0 +1,1106 @@
+use crate::database::{Row, Statement, INDEXES_TABLE};
+use golem_search::config::{get_max_pagination_depth_config, search_filters_match_any};
+use golem_search::error::{index_not_found, invalid_query};
+use golem_search::golem::search::types::{
+    Doc, FieldType, Schema, SchemaField, SearchError, SearchHit, SearchQuery,
+};
+use rusqlite::types::Value;
+use serde_json::{json, Map as JsonMap, Value as JsonValue};
+
+const DEFAULT_PER_PAGE: u32 = 20;
+
+/// Maximum number of values counted per facet
+const MAX_FACET_VALUES: u32 = 100;
+
+/// The full-text column of the indexes without text fields in their schema, holding every string
+/// of the documents
+const CONTENT_COLUMN: &str = "content";
+
+const DEFAULT_PRE_TAG: &str = "<em>";
+const DEFAULT_POST_TAG: &str = "</em>";
+
+/// Quotes an SQL identifier, such as the tables of an index
+fn quote_ident(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+/// Quotes an SQL string literal, for the statements which can't take parameters
+fn quote_literal(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+fn is_field_name(key: &str) -> bool {
+    !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.')
+}
+
+/// The value of a field of the document `d`, where dots separate the keys of nested fields
+fn field_value(field: &str) -> String {
+    format!(
+        "json_extract(d.doc, {})",
+        quote_literal(&format!("$.{field}"))
+    )
+}
+
+/// The full-text table of an index, sharing the row ids of its documents table
+fn fts_table(index: &str) -> String {
+    quote_ident(&format!("{index}_fts"))
+}
+
+/// The parameters of a statement, bound to `?1`, `?2`, ...
+#[derive(Debug, Clone, Default)]
+struct Params(Vec<Value>);
+
+impl Params {
+    /// Adds a parameter, returning its placeholder
+    fn bind(&mut self, value: Value) -> String {
+        self.0.push(value);
+        format!("?{}", self.0.len())
+    }
+
+    fn statement(&self, sql: String) -> Statement {
+        Statement {
+            sql,
+            params: self.0.clone(),
+        }
+    }
+}
+
+fn text(value: impl Into<String>) -> Value {
+    Value::Text(value.into())
+}
+
+fn field_type_name(field_type: FieldType) -> &'static str {
+    match field_type {
+        FieldType::Text => "text",
+        FieldType::Keyword => "keyword",
+        FieldType::Integer => "integer",
+        FieldType::Float => "float",
+        FieldType::Boolean => "boolean",
+        FieldType::Date => "date",
+        FieldType::GeoPoint => "geo-point",
+    }
+}
+
+fn field_type_from_name(name: &str) -> Option<FieldType> {
+    match name {
+        "text" => Some(FieldType::Text),
+        "keyword" => Some(FieldType::Keyword),
+        "integer" => Some(FieldType::Integer),
+        "float" => Some(FieldType::Float),
+        "boolean" => Some(FieldType::Boolean),
+        "date" => Some(FieldType::Date),
+        "geo-point" => Some(FieldType::GeoPoint),
+        _ => None,
+    }
+}
+
+/// The schema as the JSON kept in the table of the indexes
+fn schema_to_json(schema: &Schema) -> JsonValue {
+    json!({
+        "fields": schema
+            .fields
+            .iter()
+            .map(|field| json!({
+                "name": field.name,
+                "type": field_type_name(field.field_type),
+                "required": field.required,
+                "facet": field.facet,
+                "sort": field.sort,
+                "index": field.index,
+            }))
+            .collect::<Vec<_>>(),
+        "primary_key": schema.primary_key,
+    })
+}
+
+fn json_to_schema_field(field: &JsonValue) -> Option<SchemaField> {
+    let flag = |name: &str| field.get(name).and_then(JsonValue::as_bool);
+    Some(SchemaField {
+        name: field.get("name")?.as_str()?.to_string(),
+        field_type: field_type_from_name(field.get("type")?.as_str()?)?,
+        required: flag("required").unwrap_or(false),
+        facet: flag("facet").unwrap_or(false),
+        sort: flag("sort").unwrap_or(false),
+        index: flag("index").unwrap_or(true),
+    })
+}
+
+fn json_to_schema(schema: &JsonValue) -> Schema {
+    Schema {
+        fields: schema
+            .get("fields")
+            .and_then(JsonValue::as_array)
+            .map(|fields| fields.iter().filter_map(json_to_schema_field).collect())
+            .unwrap_or_default(),
+        primary_key: schema
+            .get("primary_key")
+            .and_then(JsonValue::as_str)
+            .map(str::to_string),
+    }
+}
+
+fn validate_schema(schema: &Schema) -> Result<(), SearchError> {
+    match schema
+        .fields
+        .iter()
+        .find(|field| !is_field_name(&field.name))
+    {
+        Some(field) => Err(invalid_query(format!(
+            "Invalid field name `{}`",
+            field.name
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// The statement reading the schema of an index
+pub fn schema_statement(index: &str) -> Statement {
+    let mut params = Params::default();
+    let name = params.bind(text(index));
+    params.statement(format!(
+        "SELECT schema FROM {INDEXES_TABLE} WHERE name = {name}"
+    ))
+}
+
+/// The schema of an index from the rows of [`schema_statement`], `None` for indexes created
+/// without a schema
+pub fn rows_to_schema(rows: &[Row]) -> Result<Option<Schema>, SearchError> {
+    match rows.first().and_then(|row| row.first()) {
+        None => Err(index_not_found()),
+        Some(Value::Text(schema)) => serde_json::from_str::<JsonValue>(schema)
+            .map(|schema| Some(json_to_schema(&schema)))
+            .map_err(|e| invalid_query(format!("Invalid schema of the index: {e}"))),
+        Some(_) => Ok(None),
+    }
+}
+
+pub fn list_statement() -> Statement {
+    Statement::new(format!("SELECT name FROM {INDEXES_TABLE} ORDER BY name"))
+}
+
+pub fn rows_to_index_names(rows: Vec<Row>) -> Vec<String> {
+    rows.into_iter()
+        .filter_map(|row| match row.into_iter().next() {
+            Some(Value::Text(name)) => Some(name),
+            _ => None,
+        })
+        .collect()
+}
+
+/// The columns of the full-text table of an index: the indexed text fields of its schema, or a
+/// single `content` column holding every string of the documents
+pub fn text_columns(schema: Option<&Schema>) -> Vec<String> {
+    let columns = schema
+        .map(|schema| {
+            schema
+                .fields
+                .iter()
+                .filter(|field| field.index && field.field_type == FieldType::Text)
+                .map(|field| field.name.clone())
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    if columns.is_empty() {
+        vec![CONTENT_COLUMN.to_string()]
+    } else {
+        columns
+    }
+}
+
+/// Inserts the full-text rows of the documents `d` matching `condition`
+fn fts_insert(index: &str, schema: Option<&Schema>, condition: &str) -> String {
+    let columns = text_columns(schema);
+    let values = if columns == [CONTENT_COLUMN] {
+        vec![
+            "(SELECT group_concat(value, ' ') FROM json_tree(d.doc) WHERE type = 'text')"
+                .to_string(),
+        ]
+    } else {
+        columns.iter().map(|column| field_value(column)).collect()
+    };
+    format!(
+        "INSERT INTO {} (rowid, {}) SELECT d.rowid, {} FROM {} d WHERE {condition}",
+        fts_table(index),
+        columns
+            .iter()
+            .map(|column| quote_ident(column))
+            .collect::<Vec<_>>()
+            .join(", "),
+        values.join(", "),
+        quote_ident(index)
+    )
+}
+
+fn fts_create(index: &str, schema: Option<&Schema>, tokenizer: &str) -> Statement {
+    let columns = text_columns(schema)
+        .iter()
+        .map(|column| quote_ident(column))
+        .collect::<Vec<_>>();
+    Statement::new(format!(
+        "CREATE VIRTUAL TABLE {} USING fts5({}, tokenize = {})",
+        fts_table(index),
+        columns.join(", "),
+        quote_literal(tokenizer)
+    ))
+}
+
+/// The indexes of the fields which are sorted or faceted
+fn field_index_statements(index: &str, schema: Option<&Schema>) -> Vec<Statement> {
+    schema
+        .map(|schema| schema.fields.as_slice())
+        .unwrap_or_default()
+        .iter()
+        .filter(|field| field.facet || field.sort)
+        .map(|field| {
+            Statement::new(format!(
+                "CREATE INDEX IF NOT EXISTS {} ON {} (json_extract(doc, {}))",
+                quote_ident(&format!("{index}_{}_idx", field.name.replace('.', "_"))),
+                quote_ident(index),
+                quote_literal(&format!("$.{}", field.name))
+            ))
+        })
+        .collect()
+}
+
+/// The statements creating an index: its documents are kept as JSON by id, and their text in an
+/// FTS5 table of the same row ids, with a column per text field of the schema
+pub fn create_statements(
+    index: &str,
+    schema: Option<&Schema>,
+    tokenizer: &str,
+) -> Result<Vec<Statement>, SearchError> {
+    if let Some(schema) = schema {
+        validate_schema(schema)?;
+    }
+
+    let mut params = Params::default();
+    let name = params.bind(text(index));
+    let schema_json = params.bind(match schema {
+        Some(schema) => text(schema_to_json(schema).to_string()),
+        None => Value::Null,
+    });
+
+    let mut statements = vec![
+        params.statement(format!(
+            "INSERT INTO {INDEXES_TABLE} (name, schema) VALUES ({name}, {schema_json})"
+        )),
+        Statement::new(format!(
+            "CREATE TABLE {} (id TEXT PRIMARY KEY, doc TEXT NOT NULL)",
+            quote_ident(index)
+        )),
+        fts_create(index, schema, tokenizer),
+    ];
+    statements.extend(field_index_statements(index, schema));
+    Ok(statements)
+}
+
+pub fn delete_index_statements(index: &str) -> Vec<Statement> {
+    let mut params = Params::default();
+    let name = params.bind(text(index));
+    vec![
+        Statement::new(format!("DROP TABLE {}", fts_table(index))),
+        Statement::new(format!("DROP TABLE {}", quote_ident(index))),
+        params.statement(format!("DELETE FROM {INDEXES_TABLE} WHERE name = {name}")),
+    ]
+}
+
+/// The statements applying a new schema to an index, rebuilding its full-text table from the
+/// text fields of the schema
+pub fn update_schema_statements(
+    index: &str,
+    schema: &Schema,
+    tokenizer: &str,
+) -> Result<Vec<Statement>, SearchError> {
+    validate_schema(schema)?;
+
+    let mut params = Params::default();
+    let name = params.bind(text(index));
+    let schema_json = params.bind(text(schema_to_json(schema).to_string()));
+
+    let mut statements = vec![
+        params.statement(format!(
+            "UPDATE {INDEXES_TABLE} SET schema = {schema_json} WHERE name = {name}"
+        )),
+        Statement::new(format!("DROP TABLE {}", fts_table(index))),
+        fts_create(index, Some(schema), tokenizer),
+        Statement::new(fts_insert(index, Some(schema), "TRUE")),
+    ];
+    statements.extend(field_index_statements(index, Some(schema)));
+    Ok(statements)
+}
+
+fn doc_content(doc: &Doc) -> Result<JsonMap<String, JsonValue>, SearchError> {
+    match serde_json::from_str::<JsonValue>(&doc.content) {
+        Ok(JsonValue::Object(fields)) => Ok(fields),
+        Ok(_) => Err(invalid_query("Document content must be a JSON object")),
+        Err(e) => Err(invalid_query(format!("Invalid JSON in document: {e}"))),
+    }
+}
+
+/// The statements replacing documents: their previous text is removed from the full-text table,
+/// the documents are upserted, and their text is added back
+pub fn upsert_statements(
+    index: &str,
+    schema: Option<&Schema>,
+    docs: &[Doc],
+) -> Result<Vec<Statement>, SearchError> {
+    let rows = docs
+        .iter()
+        .map(|doc| Ok(json!({ "id": doc.id, "doc": doc_content(doc)? })))
+        .collect::<Result<Vec<_>, SearchError>>()?;
+
+    let mut params = Params::default();
+    let rows = params.bind(text(JsonValue::Array(rows).to_string()));
+    let ids = format!("SELECT json_extract(value, '$.id') FROM json_each({rows})");
+    let table = quote_ident(index);
+
+    Ok(vec![
+        params.statement(format!(
+            "DELETE FROM {} WHERE rowid IN (SELECT rowid FROM {table} WHERE id IN ({ids}))",
+            fts_table(index)
+        )),
+        params.statement(format!(
+            "INSERT INTO {table} (id, doc) \
+             SELECT json_extract(value, '$.id'), json_extract(value, '$.doc') \
+             FROM json_each({rows}) WHERE TRUE \
+             ON CONFLICT (id) DO UPDATE SET doc = excluded.doc"
+        )),
+        params.statement(fts_insert(index, schema, &format!("d.id IN ({ids})"))),
+    ])
+}
+
+pub fn delete_statements(index: &str, ids: &[String]) -> Vec<Statement> {
+    let mut params = Params::default();
+    let ids = params.bind(text(json!(ids).to_string()));
+    let table = quote_ident(index);
+    vec![
+        params.statement(format!(
+            "DELETE FROM {} WHERE rowid IN (SELECT rowid FROM {table} \
+             WHERE id IN (SELECT value FROM json_each({ids})))",
+            fts_table(index)
+        )),
+        params.statement(format!(
+            "DELETE FROM {table} WHERE id IN (SELECT value FROM json_each({ids}))"
+        )),
+    ]
+}
+
+pub fn get_statement(index: &str, id: &str) -> Statement {
+    let mut params = Params::default();
+    let id = params.bind(text(id));
+    params.statement(format!(
+        "SELECT id, doc FROM {} WHERE id = {id}",
+        quote_ident(index)
+    ))
+}
+
+fn value_text(value: &Value) -> Option<String> {
+    match value {
+        Value::Text(text) => Some(text.clone()),
+        Value::Integer(value) => Some(value.to_string()),
+        Value::Real(value) => Some(value.to_string()),
+        Value::Null | Value::Blob(_) => None,
+    }
+}
+
+fn value_f64(value: &Value) -> Option<f64> {
+    match value {
+        Value::Integer(value) => Some(*value as f64),
+        Value::Real(value) => Some(*value),
+        _ => None,
+    }
+}
+
+pub fn row_to_doc(row: &Row) -> Doc {
+    Doc {
+        id: row.first().and_then(value_text).unwrap_or_default(),
+        content: row
+            .get(1)
+            .and_then(value_text)
+            .unwrap_or_else(|| "{}".to_string()),
+    }
+}
+
+/// The value of a filter: quoted values are strings, numbers keep their type, and booleans are
+/// compared as the integers JSON booleans are extracted as
+fn filter_value(value: &str) -> Value {
+    let value = value.trim();
+    if let Some(unquoted) = value
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+    {
+        return text(unquoted);
+    }
+    match serde_json::from_str::<JsonValue>(value) {
+        Ok(JsonValue::Number(number)) => match number.as_i64() {
+            Some(number) => Value::Integer(number),
+            None => Value::Real(number.as_f64().unwrap_or_default()),
+        },
+        Ok(JsonValue::Bool(value)) => Value::Integer(value as i64),
+        _ => text(value),
+    }
+}
+
+/// Converts a filter to an SQL condition on the documents. Filters are `field:value`,
+/// `field=value`, `field!=value`, and `field>value` style comparisons with `>`, `>=`, `<` and
+/// `<=`. Documents without the field don't match comparisons, but match `!=`.
+fn filter_to_condition(filter: &str, params: &mut Params) -> Result<String, SearchError> {
+    let filter = filter.trim();
+    let position = filter
+        .find([':', '=', '!', '<', '>'])
+        .ok_or_else(|| invalid_query(format!("Unsupported filter `{filter}`")))?;
+    let key = filter[..position].trim();
+    let rest = &filter[position..];
+    let (op, value) = [">=", "<=", "!=", ">", "<", "=", ":"]
+        .into_iter()
+        .find_map(|op| Some((op, rest.strip_prefix(op)?)))
+        .ok_or_else(|| invalid_query(format!("Unsupported filter `{filter}`")))?;
+    if !is_field_name(key) || value.trim().is_empty() {
+        return Err(invalid_query(format!("Unsupported filter `{filter}`")));
+    }
+
+    let op = match op {
+        ":" | "=" => "=",
+        "!=" => "IS NOT",
+        op => op,
+    };
+    let value = params.bind(filter_value(value));
+    Ok(format!("{} {op} {value}", field_value(key)))
+}
+
+/// The FTS5 query matching the terms of a query's text, each quoted as a string so the text
+/// isn't parsed as FTS5 query syntax
+fn fts_query(q: &str) -> Option<String> {
+    let terms = q
+        .split_whitespace()
+        .map(|term| format!("\"{}\"", term.replace('"', "\"\"")))
+        .collect::<Vec<_>>();
+    (!terms.is_empty()).then(|| terms.join(" "))
+}
+
+/// The `ORDER BY` entries of a query's `field`, `field:asc`, `field:desc` or `-field` sort
+/// entries, missing values last
+fn query_to_order(sort: &[String]) -> Result<Vec<String>, SearchError> {
+    sort.iter()
+        .map(|sort| {
+            let sort = sort.trim();
+            let (key, order) = if let Some(key) = sort.strip_prefix('-') {
+                (key, "DESC")
+            } else {
+                match sort.rsplit_once(':') {
+                    Some((key, order)) if order.eq_ignore_ascii_case("asc") => (key, "ASC"),
+                    Some((key, order)) if order.eq_ignore_ascii_case("desc") => (key, "DESC"),
+                    Some(_) => return Err(invalid_query(format!("Invalid sort `{sort}`"))),
+                    None => (sort, "ASC"),
+                }
+            };
+            if !is_field_name(key) {
+                return Err(invalid_query(format!("Invalid sort `{sort}`")));
+            }
+            Ok(format!("{} {order} NULLS LAST", field_value(key)))
+        })
+        .collect()
+}
+
+/// The full-text columns highlighted for a query: the requested fields which are full-text
+/// columns, or all of them
+fn highlight_columns(schema: Option<&Schema>, query: &SearchQuery) -> Vec<String> {
+    let Some(highlight) = &query.highlight else {
+        return vec![];
+    };
+    let columns = text_columns(schema);
+    if highlight.fields.is_empty() {
+        columns
+    } else {
+        columns
+            .into_iter()
+            .filter(|column| highlight.fields.contains(column))
+            .collect()
+    }
+}
+
+/// Number of hits to skip and number of hits per page of a query
+pub fn query_window(query: &SearchQuery) -> Result<(u32, u32), SearchError> {
+    let per_page = query.per_page.unwrap_or(DEFAULT_PER_PAGE);
+    let offset = query.offset.unwrap_or_else(|| {
+        query
+            .page
+            .unwrap_or(1)
+            .saturating_sub(1)
+            .saturating_mul(per_page)
+    });
+    if offset.saturating_add(per_page) > get_max_pagination_depth_config() {
+        return Err(invalid_query(format!(
+            "Requested hits beyond the maximum pagination depth of {}",
+            get_max_pagination_depth_config()
+        )));
+    }
+    Ok((offset, per_page))
+}
+
+/// Builds the statements of a search on an index: the page of hits, read with `LIMIT` and
+/// `OFFSET`, then the count of the matches when `count` is set, then the counts of each facet.
+/// Text searches match the full-text table and are ranked with `bm25`, weighting the columns of
+/// the boosted fields with their boost.
+pub fn search_statements(
+    index: &str,
+    schema: Option<&Schema>,
+    query: &SearchQuery,
+    count: bool,
+) -> Result<Vec<Statement>, SearchError> {
+    let (offset, per_page) = query_window(query)?;
+    let fts = fts_table(index);
+    let config = query.config.as_ref();
+
+    let mut params = Params::default();
+    let mut conditions = Vec::new();
+    let text_query = query.q.as_deref().and_then(fts_query);
+    if let Some(text_query) = &text_query {
+        conditions.push(format!("{fts} MATCH {}", params.bind(text(text_query))));
+    }
+    let filters = query
+        .filters
+        .iter()
+        .map(|filter| filter_to_condition(filter, &mut params))
+        .collect::<Result<Vec<_>, _>>()?;
+    if search_filters_match_any(query) && filters.len() > 1 {
+        conditions.push(format!("({})", filters.join(" OR ")));
+    } else {
+        conditions.extend(filters);
+    }
+    let condition = if conditions.is_empty() {
+        "TRUE".to_string()
+    } else {
+        conditions.join(" AND ")
+    };
+    let condition_params = params.clone();
+
+    let from = match text_query {
+        Some(_) => format!(
+            "{fts} JOIN {} d ON d.rowid = {fts}.rowid",
+            quote_ident(index)
+        ),
+        None => format!("{} d", quote_ident(index)),
+    };
+
+    let mut columns = "d.id, d.doc".to_string();
+    let mut order = query_to_order(&query.sort)?;
+    match text_query {
+        Some(_) => {
+            let columns_weights = config
+                .filter(|config| !config.boost_fields.is_empty())
+                .map(|config| {
+                    text_columns(schema)
+                        .iter()
+                        .map(|column| {
+                            let boost = config
+                                .boost_fields
+                                .iter()
+                                .find(|(field, _)| field == column)
+                                .map(|(_, boost)| *boost)
+                                .unwrap_or(1.0);
+                            format!(", {boost:?}")
+                        })
+                        .collect::<String>()
+                })
+                .unwrap_or_default();
+            columns.push_str(&format!(", -bm25({fts}{columns_weights}) AS score"));
+
+            let highlighted = highlight_columns(schema, query);
+            if let (Some(highlight), false) = (&query.highlight, highlighted.is_empty()) {
+                let pre_tag = params.bind(text(
+                    highlight.pre_tag.as_deref().unwrap_or(DEFAULT_PRE_TAG),
+                ));
+                let post_tag = params.bind(text(
+                    highlight.post_tag.as_deref().unwrap_or(DEFAULT_POST_TAG),
+                ));
+                let all_columns = text_columns(schema);
+                for column in &highlighted {
+                    let position = all_columns
+                        .iter()
+                        .position(|other| other == column)
+                        .unwrap_or_default();
+                    columns.push_str(&format!(
+                        ", highlight({fts}, {position}, {pre_tag}, {post_tag})"
+                    ));
+                }
+            }
+            order.push("score DESC".to_string());
+        }
+        None => columns.push_str(", NULL AS score"),
+    }
+    order.push("d.id".to_string());
+
+    let mut statements = vec![params.statement(format!(
+        "SELECT {columns} FROM {from} WHERE {condition} ORDER BY {} LIMIT {per_page} OFFSET {offset}",
+        order.join(", ")
+    ))];
+    if count {
+        statements.push(
+            condition_params.statement(format!("SELECT count(*) FROM {from} WHERE {condition}")),
+        );
+    }
+    for facet in &query.facets {
+        if !is_field_name(facet) {
+            return Err(invalid_query(format!("Invalid facet `{facet}`")));
+        }
+        let value = field_value(facet);
+        statements.push(condition_params.statement(format!(
+            "SELECT {value}, count(*) FROM {from} WHERE ({condition}) AND {value} IS NOT NULL \
+             GROUP BY 1 ORDER BY 2 DESC, 1 LIMIT {MAX_FACET_VALUES}"
+        )));
+    }
+    Ok(statements)
+}
+
+fn row_to_hit(row: &Row, highlighted: &[String], query: &SearchQuery) -> SearchHit {
+    let mut content = row
+        .get(1)
+        .and_then(value_text)
+        .and_then(|doc| serde_json::from_str::<JsonValue>(&doc).ok())
+        .unwrap_or_else(|| json!({}));
+    let attributes = query
+        .config
+        .as_ref()
+        .map(|config| config.attributes_to_retrieve.as_slice())
+        .unwrap_or_default();
+    if let (false, JsonValue::Object(fields)) = (attributes.is_empty(), &mut content) {
+        fields.retain(|key, _| attributes.contains(key));
+    }
+
+    SearchHit {
+        id: row.first().and_then(value_text).unwrap_or_default(),
+        score: row.get(2).and_then(value_f64),
+        content: Some(content.to_string()),
+        highlights: highlights_to_snippets(&row[3.min(row.len())..], highlighted, query),
+    }
+}
+
+/// Highlights in the same shape as the other providers: `{"field": ["snippet"]}`, keeping the
+/// highlighted columns with a match
+fn highlights_to_snippets(
+    values: &[Value],
+    highlighted: &[String],
+    query: &SearchQuery,
+) -> Option<String> {
+    let highlight = query.highlight.as_ref()?;
+    let pre_tag = highlight.pre_tag.as_deref().unwrap_or(DEFAULT_PRE_TAG);
+
+    let snippets = highlighted
+        .iter()
+        .zip(values)
+        .filter_map(|(column, value)| {
+            let snippet = value_text(value).filter(|snippet| snippet.contains(pre_tag))?;
+            let snippet = match highlight.max_length {
+                Some(max_length) => snippet.chars().take(max_length as usize).collect(),
+                None => snippet,
+            };
+            Some((column.clone(), json!([snippet])))
+        })
+        .collect::<JsonMap<_, _>>();
+    (!snippets.is_empty()).then(|| JsonValue::Object(snippets).to_string())
+}
+
+/// Facet counts in the same shape as the other providers: `{"field": {"value": count}}`
+fn rows_to_facets(facets: &[(&String, Vec<Row>)]) -> Option<String> {
+    let counts = facets
+        .iter()
+        .map(|(facet, rows)| {
+            let counts = rows
+                .iter()
+                .filter_map(|row| {
+                    let value = value_text(row.first()?)?;
+                    let count = value_f64(row.get(1)?)? as u64;
+                    Some((value, json!(count)))
+                })
+                .collect::<JsonMap<_, _>>();
+            (facet.to_string(), JsonValue::Object(counts))
+        })
+        .collect::<JsonMap<_, _>>();
+    (!counts.is_empty()).then(|| JsonValue::Object(counts).to_string())
+}
+
+/// The results of the statements of a search
+pub struct ParsedResults {
+    pub hits: Vec<SearchHit>,
+    pub total: Option<u32>,
+    pub facets: Option<String>,
+}
+
+/// Parses the rows of the statements built by [`search_statements`] with the same schema and
+/// `count`
+pub fn parse_search(
+    results: Vec<Vec<Row>>,
+    schema: Option<&Schema>,
+    query: &SearchQuery,
+    count: bool,
+) -> ParsedResults {
+    let highlighted = match query.q.as_deref().and_then(fts_query) {
+        Some(_) => highlight_columns(schema, query),
+        None => vec![],
+    };
+
+    let mut results = results.into_iter();
+    let hits = results
+        .next()
+        .unwrap_or_default()
+        .iter()
+        .map(|row| row_to_hit(row, &highlighted, query))
+        .collect();
+    let total = if count {
+        results
+            .next()
+            .and_then(|rows| value_f64(rows.first()?.first()?))
+            .map(|total| total as u32)
+    } else {
+        None
+    };
+    let facets = query.facets.iter().zip(results).collect::<Vec<_>>();
+
+    ParsedResults {
+        hits,
+        total,
+        facets: rows_to_facets(&facets),
+    }
+}
+
+pub fn create_retry_query(original_query: &SearchQuery, partial_hits: &[SearchHit]) -> SearchQuery {
+    let mut retry_query = original_query.clone();
+
+    let current_offset = original_query.offset.unwrap_or(0);
+    let per_page = original_query.per_page.unwrap_or(DEFAULT_PER_PAGE);
+    let hits_received = partial_hits.len() as u32;
+
+    retry_query.offset = Some(current_offset + hits_received.min(per_page));
+
+    retry_query
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use golem_search::golem::search::types::{FilterMode, HighlightConfig, SearchConfig};
+
+    fn query(q: Option<&str>, filters: &[&str]) -> SearchQuery {
+        SearchQuery {
+            q: q.map(str::to_string),
+            filters: filters.iter().map(|filter| filter.to_string()).collect(),
+            sort: vec![],
+            facets: vec![],
+            page: None,
+            per_page: None,
+            offset: None,
+            highlight: None,
+            config: None,
+        }
+    }
+
+    fn field(name: &str, field_type: FieldType, facet: bool, sort: bool) -> SchemaField {
+        SchemaField {
+            name: name.to_string(),
+            field_type,
+            required: false,
+            facet,
+            sort,
+            index: true,
+        }
+    }
+
+    fn config(boost_fields: Vec<(String, f32)>, filter_mode: Option<FilterMode>) -> SearchConfig {
+        SearchConfig {
+            timeout_ms: None,
+            boost_fields,
+            attributes_to_retrieve: vec![],
+            language: None,
+            typo_tolerance: None,
+            exact_match_boost: None,
+            provider_params: None,
+            profile: None,
+            filter_mode,
+            post_filters: vec![],
+            rescore: None,
+            fallbacks: vec![],
+        }
+    }
+
+    fn schema() -> Schema {
+        Schema {
+            fields: vec![
+                field("title", FieldType::Text, false, false),
+                field("summary", FieldType::Text, false, false),
+                field("genre", FieldType::Keyword, true, false),
+                field("year", FieldType::Integer, false, true),
+            ],
+            primary_key: None,
+        }
+    }
+
+    fn queries(statements: &[Statement]) -> Vec<&str> {
+        statements
+            .iter()
+            .map(|statement| statement.sql.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_create_statements() {
+        let schema = schema();
+        let statements = create_statements("books", Some(&schema), "porter unicode61").unwrap();
+        assert_eq!(
+            queries(&statements),
+            vec![
+                "INSERT INTO golem_search_indexes (name, schema) VALUES (?1, ?2)",
+                "CREATE TABLE \"books\" (id TEXT PRIMARY KEY, doc TEXT NOT NULL)",
+                "CREATE VIRTUAL TABLE \"books_fts\" USING fts5(\"title\", \"summary\", \
+                 tokenize = 'porter unicode61')",
+                "CREATE INDEX IF NOT EXISTS \"books_genre_idx\" ON \"books\" \
+                 (json_extract(doc, '$.genre'))",
+                "CREATE INDEX IF NOT EXISTS \"books_year_idx\" ON \"books\" \
+                 (json_extract(doc, '$.year'))",
+            ]
+        );
+        let Value::Text(schema_json) = &statements[0].params[1] else {
+            panic!("The schema is not kept as text");
+        };
+        let parsed = rows_to_schema(&[vec![text(schema_json.as_str())]]).unwrap();
+        assert_eq!(parsed, Some(schema));
+
+        let statements = create_statements("books", None, "porter unicode61").unwrap();
+        assert_eq!(statements[0].params[1], Value::Null);
+        assert!(statements[2].sql.contains("fts5(\"content\","));
+        assert_eq!(rows_to_schema(&[vec![Value::Null]]).unwrap(), None);
+        assert!(matches!(
+            rows_to_schema(&[]),
+            Err(SearchError::IndexNotFound)
+        ));
+
+        let invalid = Schema {
+            fields: vec![field("title'); DROP", FieldType::Text, false, false)],
+            primary_key: None,
+        };
+        assert!(matches!(
+            create_statements("books", Some(&invalid), "porter unicode61"),
+            Err(SearchError::InvalidQuery(_))
+        ));
+    }
+
+    #[test]
+    fn test_upsert_statements() {
+        let docs = vec![Doc {
+            id: "doc1".to_string(),
+            content: r#"{"title":"Dune"}"#.to_string(),
+        }];
+        let ids = "SELECT json_extract(value, '$.id') FROM json_each(?1)";
+
+        let statements = upsert_statements("books", Some(&schema()), &docs).unwrap();
+        assert_eq!(
+            queries(&statements),
+            vec![
+                format!(
+                    "DELETE FROM \"books_fts\" WHERE rowid IN \
+                     (SELECT rowid FROM \"books\" WHERE id IN ({ids}))"
+                )
+                .as_str(),
+                "INSERT INTO \"books\" (id, doc) \
+                 SELECT json_extract(value, '$.id'), json_extract(value, '$.doc') \
+                 FROM json_each(?1) WHERE TRUE \
+                 ON CONFLICT (id) DO UPDATE SET doc = excluded.doc",
+                format!(
+                    "INSERT INTO \"books_fts\" (rowid, \"title\", \"summary\") \
+                     SELECT d.rowid, json_extract(d.doc, '$.title'), \
+                     json_extract(d.doc, '$.summary') FROM \"books\" d WHERE d.id IN ({ids})"
+                )
+                .as_str(),
+            ]
+        );
+        assert_eq!(
+            statements[0].params,
+            vec![text(r#"[{"doc":{"title":"Dune"},"id":"doc1"}]"#)]
+        );
+
+        let statements = upsert_statements("books", None, &docs).unwrap();
+        assert!(statements[2].sql.contains(
+            "(rowid, \"content\") SELECT d.rowid, \
+             (SELECT group_concat(value, ' ') FROM json_tree(d.doc) WHERE type = 'text')"
+        ));
+
+        let invalid = vec![Doc {
+            id: "doc1".to_string(),
+            content: "[]".to_string(),
+        }];
+        assert!(matches!(
+            upsert_statements("books", None, &invalid),
+            Err(SearchError::InvalidQuery(_))
+        ));
+    }
+
+    #[test]
+    fn test_filters_to_conditions() {
+        let mut params = Params::default();
+        let conditions = [
+            "genre:sci-fi",
+            "author.name=\"Frank Herbert\"",
+            "year!=1965",
+            "rating>=4.5",
+            "available:true",
+        ]
+        .iter()
+        .map(|filter| filter_to_condition(filter, &mut params).unwrap())
+        .collect::<Vec<_>>();
+
+        assert_eq!(
+            conditions,
+            vec![
+                "json_extract(d.doc, '$.genre') = ?1",
+                "json_extract(d.doc, '$.author.name') = ?2",
+                "json_extract(d.doc, '$.year') IS NOT ?3",
+                "json_extract(d.doc, '$.rating') >= ?4",
+                "json_extract(d.doc, '$.available') = ?5",
+            ]
+        );
+        assert_eq!(
+            params.0,
+            vec![
+                text("sci-fi"),
+                text("Frank Herbert"),
+                Value::Integer(1965),
+                Value::Real(4.5),
+                Value::Integer(1),
+            ]
+        );
+
+        for filter in ["genre", "bad key:1", "title':1"] {
+            assert!(
+                filter_to_condition(filter, &mut Params::default()).is_err(),
+                "{filter}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_search_statements() {
+        let mut search = query(Some("desert \"planet"), &["genre:sci-fi", "year>1950"]);
+        search.sort = vec!["-year".to_string()];
+        search.facets = vec!["genre".to_string()];
+        search.page = Some(2);
+        search.per_page = Some(10);
+        search.highlight = Some(HighlightConfig {
+            fields: vec!["summary".to_string()],
+            pre_tag: None,
+            post_tag: None,
+            max_length: None,
+        });
+        search.config = Some(config(
+            vec![("title".to_string(), 2.0)],
+            Some(FilterMode::Any),
+        ));
+
+        let statements = search_statements("books", Some(&schema()), &search, true).unwrap();
+        let from = "\"books_fts\" JOIN \"books\" d ON d.rowid = \"books_fts\".rowid";
+        let condition = "\"books_fts\" MATCH ?1 AND (json_extract(d.doc, '$.genre') = ?2 \
+                         OR json_extract(d.doc, '$.year') > ?3)";
+        assert_eq!(
+            queries(&statements),
+            vec![
+                format!(
+                    "SELECT d.id, d.doc, -bm25(\"books_fts\", 2.0, 1.0) AS score, \
+                     highlight(\"books_fts\", 1, ?4, ?5) FROM {from} WHERE {condition} \
+                     ORDER BY json_extract(d.doc, '$.year') DESC NULLS LAST, score DESC, d.id \
+                     LIMIT 10 OFFSET 10"
+                ),
+                format!("SELECT count(*) FROM {from} WHERE {condition}"),
+                format!(
+                    "SELECT json_extract(d.doc, '$.genre'), count(*) FROM {from} \
+                     WHERE ({condition}) AND json_extract(d.doc, '$.genre') IS NOT NULL \
+                     GROUP BY 1 ORDER BY 2 DESC, 1 LIMIT 100"
+                ),
+            ]
+        );
+        assert_eq!(
+            statements[0].params,
+            vec![
+                text("\"desert\" \"\"\"planet\""),
+                text("sci-fi"),
+                Value::Integer(1950),
+                text("<em>"),
+                text("</em>"),
+            ]
+        );
+        assert_eq!(statements[1].params.len(), 3);
+
+        let statements = search_statements("books", None, &query(None, &[]), false).unwrap();
+        assert_eq!(
+            queries(&statements),
+            vec![
+                "SELECT d.id, d.doc, NULL AS score FROM \"books\" d WHERE TRUE \
+                 ORDER BY d.id LIMIT 20 OFFSET 0"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_search() {
+        let mut search = query(Some("dune"), &[]);
+        search.facets = vec!["year".to_string()];
+        search.highlight = Some(HighlightConfig {
+            fields: vec![],
+            pre_tag: None,
+            post_tag: None,
+            max_length: Some(10),
+        });
+
+        let results = parse_search(
+            vec![
+                vec![vec![
+                    text("doc1"),
+                    text(r#"{"title":"Dune","year":1965}"#),
+                    Value::Real(1.5),
+                    text("<em>Dune</em> Messiah"),
+                    text("A desert planet"),
+                ]],
+                vec![vec![Value::Integer(1)]],
+                vec![vec![Value::Integer(1965), Value::Integer(1)]],
+            ],
+            Some(&schema()),
+            &search,
+            true,
+        );
+
+        assert_eq!(results.total, Some(1));
+        let hit = &results.hits[0];
+        assert_eq!(hit.id, "doc1");
+        assert_eq!(hit.score, Some(1.5));
+        assert_eq!(
+            hit.content.as_deref(),
+            Some(r#"{"title":"Dune","year":1965}"#)
+        );
+        assert_eq!(
+            hit.highlights.as_deref(),
+            Some(r#"{"title":["<em>Dune</"]}"#)
+        );
+        assert_eq!(results.facets.as_deref(), Some(r#"{"year":{"1965":1}}"#));
+
+        let doc = row_to_doc(&vec![text("doc1"), text(r#"{"title":"Dune"}"#)]);
+        assert_eq!(doc.id, "doc1");
+        assert_eq!(doc.content, r#"{"title":"Dune"}"#);
+    }
+
+    #[test]
+    fn test_create_retry_query() {
+        let mut original = query(Some("dune"), &[]);
+        original.offset = Some(10);
+        original.per_page = Some(5);
+        let hits = (0..7)
+            .map(|i| SearchHit {
+                id: i.to_string(),
+                score: None,
+                content: None,
+                highlights: None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(create_retry_query(&original, &hits).offset, Some(15));
+    }
+}
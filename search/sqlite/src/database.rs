@@ -0,0 +1,132 @@
+use golem_search::config::get_optional_config;
+use golem_search::error::{index_not_found, internal_error, invalid_query};
+use golem_search::golem::search::types::SearchError;
+use log::trace;
+use rusqlite::types::Value;
+use rusqlite::{params_from_iter, Connection, DatabaseName};
+use std::cell::RefCell;
+
+const DATABASE_PATH_ENV_VAR: &str = "SQLITE_DATABASE_PATH";
+
+/// The table of the indexes and their schemas
+pub const INDEXES_TABLE: &str = "golem_search_indexes";
+
+/// An SQL statement and the values of its `?1`, `?2`, ... parameters
+#[derive(Debug, Clone, PartialEq)]
+pub struct Statement {
+    pub sql: String,
+    pub params: Vec<Value>,
+}
+
+impl Statement {
+    pub fn new(sql: impl Into<String>) -> Self {
+        Self {
+            sql: sql.into(),
+            params: Vec::new(),
+        }
+    }
+}
+
+pub type Row = Vec<Value>;
+
+thread_local! {
+    /// The database of the worker, opened on first use
+    static DATABASE: RefCell<Option<Connection>> = const { RefCell::new(None) };
+}
+
+/// Opens the database of `SQLITE_DATABASE_PATH`, a file of the worker's file system, or an
+/// in-memory database when it is not set
+fn open() -> Result<Connection, SearchError> {
+    let connection =
+        match get_optional_config(DATABASE_PATH_ENV_VAR).filter(|path| !path.is_empty()) {
+            Some(path) => Connection::open(&path),
+            None => Connection::open_in_memory(),
+        }
+        .map_err(|e| internal_error(format!("Failed to open SQLite database: {e}")))?;
+
+    connection
+        .execute(
+            &format!(
+                "CREATE TABLE IF NOT EXISTS {INDEXES_TABLE} (name TEXT PRIMARY KEY, schema TEXT)"
+            ),
+            [],
+        )
+        .map_err(sqlite_error)?;
+    Ok(connection)
+}
+
+/// Runs `f` with the database of the worker
+pub fn with_database<T>(
+    f: impl FnOnce(&mut Connection) -> Result<T, SearchError>,
+) -> Result<T, SearchError> {
+    DATABASE.with_borrow_mut(|database| {
+        let connection = match database {
+            Some(connection) => connection,
+            None => database.insert(open()?),
+        };
+        f(connection)
+    })
+}
+
+/// Maps the errors of SQLite: statements on the tables of indexes which don't exist fail with
+/// `no such table`, and the other failures of a statement come from an invalid query, such as a
+/// full-text query FTS5 can't parse
+fn sqlite_error(error: rusqlite::Error) -> SearchError {
+    match &error {
+        rusqlite::Error::SqliteFailure(_, Some(message))
+            if message.starts_with("no such table") =>
+        {
+            index_not_found()
+        }
+        rusqlite::Error::SqliteFailure(_, Some(message)) => invalid_query(message),
+        _ => internal_error(format!("SQLite error: {error}")),
+    }
+}
+
+/// Runs statements in a single transaction
+pub fn execute(connection: &Connection, statements: &[Statement]) -> Result<(), SearchError> {
+    let transaction = connection.unchecked_transaction().map_err(sqlite_error)?;
+    for statement in statements {
+        trace!("Executing {}", statement.sql);
+
+        transaction
+            .execute(&statement.sql, params_from_iter(&statement.params))
+            .map_err(sqlite_error)?;
+    }
+    transaction.commit().map_err(sqlite_error)
+}
+
+/// Runs a query, returning the values of the columns of its rows
+pub fn query(connection: &Connection, statement: &Statement) -> Result<Vec<Row>, SearchError> {
+    trace!("Querying {}", statement.sql);
+
+    let mut prepared = connection.prepare(&statement.sql).map_err(sqlite_error)?;
+    let columns = prepared.column_count();
+    let rows = prepared
+        .query_map(params_from_iter(&statement.params), |row| {
+            (0..columns)
+                .map(|column| row.get::<_, Value>(column))
+                .collect()
+        })
+        .map_err(sqlite_error)?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(sqlite_error)
+}
+
+/// The bytes of the database, saved in the snapshots of the worker
+pub fn save_snapshot() -> Result<Vec<u8>, SearchError> {
+    with_database(|connection| {
+        let data = connection
+            .serialize(DatabaseName::Main)
+            .map_err(|e| internal_error(format!("Failed to serialize SQLite database: {e}")))?;
+        Ok(data.to_vec())
+    })
+}
+
+/// Replaces the database with the bytes of a snapshot
+pub fn load_snapshot(bytes: &[u8]) -> Result<(), SearchError> {
+    with_database(|connection| {
+        connection
+            .deserialize_read_exact(DatabaseName::Main, bytes, bytes.len(), false)
+            .map_err(|e| internal_error(format!("Failed to deserialize SQLite database: {e}")))
+    })
+}
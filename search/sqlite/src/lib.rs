@@ -0,0 +1,264 @@
+use crate::conversions::{
+    create_retry_query, create_statements, delete_index_statements, delete_statements,
+    get_statement, list_statement, parse_search, query_window, row_to_doc, rows_to_index_names,
+    rows_to_schema, schema_statement, search_statements, update_schema_statements,
+    upsert_statements,
+};
+use crate::database::{execute, query, with_database};
+use golem_rust::wasm_rpc::Pollable;
+use golem_search::config::get_config_with_default;
+use golem_search::durability::{DurableSearch, ExtendedGuest};
+use golem_search::golem::search::core::{Guest, GuestSearchStream, SearchStream};
+use golem_search::golem::search::types::{
+    Doc, DocumentId, IndexName, IndexPage, PageRequest, Schema, SearchError, SearchHit,
+    SearchQuery, SearchResults,
+};
+use golem_search::pagination::index_page;
+use golem_search::LOGGING_STATE;
+use rusqlite::Connection;
+use std::cell::Cell;
+
+mod conversions;
+mod database;
+mod snapshot;
+
+/// Search stream over the matches of a query, paged through with `LIMIT` and `OFFSET`
+struct SqliteSearchStream {
+    index_name: String,
+    query: SearchQuery,
+    offset: Cell<u32>,
+    finished: Cell<bool>,
+}
+
+impl SqliteSearchStream {
+    pub fn new(index_name: String, query: SearchQuery) -> Self {
+        let offset = query_window(&query).map(|(offset, _)| offset).unwrap_or(0);
+        Self {
+            index_name,
+            query,
+            offset: Cell::new(offset),
+            finished: Cell::new(false),
+        }
+    }
+
+    pub fn subscribe(&self) -> Pollable {
+        golem_rust::bindings::wasi::clocks::monotonic_clock::subscribe_duration(0)
+    }
+
+    /// Reads the next page of hits, and whether there may be more
+    fn next_page(&self, per_page: u32) -> Result<(Vec<SearchHit>, bool), SearchError> {
+        let mut query = self.query.clone();
+        query.page = None;
+        query.offset = Some(self.offset.get());
+        query.facets = vec![];
+
+        let hits = with_database(|connection| {
+            let schema = SqliteComponent::schema(connection, &self.index_name)?;
+            let statements = search_statements(&self.index_name, schema.as_ref(), &query, false)?;
+            let results = statements
+                .iter()
+                .map(|statement| database::query(connection, statement))
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(parse_search(results, schema.as_ref(), &query, false).hits)
+        })?;
+        let more = hits.len() as u32 >= per_page;
+        Ok((hits, more))
+    }
+}
+
+impl GuestSearchStream for SqliteSearchStream {
+    fn get_next(&self) -> Option<Vec<SearchHit>> {
+        if self.finished.get() {
+            return Some(vec![]);
+        }
+
+        let per_page = query_window(&self.query)
+            .map(|(_, per_page)| per_page)
+            .unwrap_or(0);
+
+        match self.next_page(per_page) {
+            Ok((hits, more)) => {
+                if !more || hits.is_empty() {
+                    self.finished.set(true);
+                }
+                self.offset.set(self.offset.get() + hits.len() as u32);
+                Some(hits)
+            }
+            Err(_) => {
+                self.finished.set(true);
+                Some(vec![])
+            }
+        }
+    }
+
+    fn blocking_get_next(&self) -> Vec<SearchHit> {
+        self.get_next().unwrap_or_default()
+    }
+}
+
+struct SqliteComponent;
+
+impl SqliteComponent {
+    const TOKENIZER_ENV_VAR: &'static str = "SQLITE_FTS_TOKENIZER";
+
+    /// The FTS5 tokenizer of `SQLITE_FTS_TOKENIZER` used by the full-text tables of new indexes and
+    /// of updated schemas, `porter unicode61` by default
+    fn tokenizer() -> String {
+        get_config_with_default(Self::TOKENIZER_ENV_VAR, "porter unicode61")
+    }
+
+    /// The schema of an index, failing with `index-not-found` for indexes which don't exist
+    fn schema(connection: &Connection, index: &str) -> Result<Option<Schema>, SearchError> {
+        rows_to_schema(&query(connection, &schema_statement(index))?)
+    }
+
+    fn search_page(
+        connection: &Connection,
+        index: &str,
+        search: &SearchQuery,
+    ) -> Result<SearchResults, SearchError> {
+        let (offset, per_page) = query_window(search)?;
+        let schema = Self::schema(connection, index)?;
+        let statements = search_statements(index, schema.as_ref(), search, true)?;
+
+        let results = statements
+            .iter()
+            .map(|statement| query(connection, statement))
+            .collect::<Result<Vec<_>, _>>()?;
+        let results = parse_search(results, schema.as_ref(), search, true);
+
+        Ok(SearchResults {
+            total: results.total,
+            page: Some(offset / per_page.max(1) + 1),
+            per_page: Some(per_page),
+            hits: results.hits,
+            facets: results.facets,
+            took_ms: None,
+            relaxation: None,
+            navigation: None,
+        })
+    }
+}
+
+impl Guest for SqliteComponent {
+    type SearchStream = SqliteSearchStream;
+
+    /// Creates the table of the documents of the index and its FTS5 table, with a column per text
+    /// field of the schema
+    fn create_index(name: IndexName, schema: Option<Schema>) -> Result<(), SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let statements = create_statements(&name, schema.as_ref(), &Self::tokenizer())?;
+        with_database(|connection| execute(connection, &statements))
+    }
+
+    fn delete_index(name: IndexName) -> Result<(), SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        with_database(|connection| execute(connection, &delete_index_statements(&name)))
+    }
+
+    fn list_indexes(page: PageRequest) -> Result<IndexPage, SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let names = with_database(|connection| query(connection, &list_statement()))?;
+        index_page(rows_to_index_names(names), &page)
+    }
+
+    fn upsert(index: IndexName, doc: Doc) -> Result<(), SearchError> {
+        Self::upsert_many(index, vec![doc])
+    }
+
+    fn upsert_many(index: IndexName, docs: Vec<Doc>) -> Result<(), SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        if docs.is_empty() {
+            return Ok(());
+        }
+
+        with_database(|connection| {
+            let schema = Self::schema(connection, &index)?;
+            execute(
+                connection,
+                &upsert_statements(&index, schema.as_ref(), &docs)?,
+            )
+        })
+    }
+
+    fn delete(index: IndexName, id: DocumentId) -> Result<(), SearchError> {
+        Self::delete_many(index, vec![id])
+    }
+
+    fn delete_many(index: IndexName, ids: Vec<DocumentId>) -> Result<(), SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        if ids.is_empty() {
+            return Ok(());
+        }
+
+        with_database(|connection| execute(connection, &delete_statements(&index, &ids)))
+    }
+
+    fn get(index: IndexName, id: DocumentId) -> Result<Option<Doc>, SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let rows = with_database(|connection| query(connection, &get_statement(&index, &id)))?;
+        Ok(rows.first().map(row_to_doc))
+    }
+
+    fn search(index: IndexName, query: SearchQuery) -> Result<SearchResults, SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        with_database(|connection| Self::search_page(connection, &index, &query))
+    }
+
+    fn stream_search(index: IndexName, query: SearchQuery) -> Result<SearchStream, SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        Ok(SearchStream::new(SqliteSearchStream::new(index, query)))
+    }
+
+    /// Returns the schema kept in the table of the indexes, or an empty schema for indexes created
+    /// without one
+    fn get_schema(index: IndexName) -> Result<Schema, SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let schema = with_database(|connection| Self::schema(connection, &index))?;
+        Ok(schema.unwrap_or(Schema {
+            fields: vec![],
+            primary_key: Some("id".to_string()),
+        }))
+    }
+
+    /// Rebuilds the FTS5 table of the index from the text fields of the schema, and indexes its
+    /// sorted and faceted fields
+    fn update_schema(index: IndexName, schema: Schema) -> Result<(), SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let statements = update_schema_statements(&index, &schema, &Self::tokenizer())?;
+        with_database(|connection| {
+            Self::schema(connection, &index)?;
+            execute(connection, &statements)
+        })
+    }
+}
+
+impl ExtendedGuest for SqliteComponent {
+    fn unwrapped_stream(index: IndexName, query: SearchQuery) -> Self::SearchStream {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        SqliteSearchStream::new(index, query)
+    }
+
+    fn retry_query(original_query: &SearchQuery, partial_hits: &[SearchHit]) -> SearchQuery {
+        create_retry_query(original_query, partial_hits)
+    }
+
+    fn subscribe(stream: &Self::SearchStream) -> Pollable {
+        stream.subscribe()
+    }
+}
+
+type DurableSqliteComponent = DurableSearch<SqliteComponent>;
+
+golem_search::export_search!(DurableSqliteComponent with_types_in golem_search);
@@ -0,0 +1,51 @@
+//! Snapshots of the worker's database.
+//!
+//! The component exports the `golem:api` snapshot interfaces, so the bytes of the SQLite database
+//! are saved in the snapshots Golem takes of the worker and loaded back into the database when a
+//! worker is restored from one, instead of replaying every invocation which built it.
+
+use crate::database;
+
+#[allow(dead_code)]
+mod bindings {
+    wit_bindgen::generate!({
+        inline: r#"
+            package golem:api@1.1.7;
+
+            interface save-snapshot {
+                save: func() -> list<u8>;
+            }
+
+            interface load-snapshot {
+                load: func(bytes: list<u8>) -> result<_, string>;
+            }
+
+            world snapshots {
+                export save-snapshot;
+                export load-snapshot;
+            }
+        "#,
+        world: "golem:api/snapshots@1.1.7",
+        generate_all,
+    });
+}
+
+struct SqliteSnapshots;
+
+impl bindings::exports::golem::api::save_snapshot::Guest for SqliteSnapshots {
+    /// Fails the snapshot, by trapping, if the database can't be serialized
+    fn save() -> Vec<u8> {
+        match database::save_snapshot() {
+            Ok(bytes) => bytes,
+            Err(error) => panic!("Failed to save the snapshot of the database: {error:?}"),
+        }
+    }
+}
+
+impl bindings::exports::golem::api::load_snapshot::Guest for SqliteSnapshots {
+    fn load(bytes: Vec<u8>) -> Result<(), String> {
+        database::load_snapshot(&bytes).map_err(|error| format!("{error:?}"))
+    }
+}
+
+bindings::export!(SqliteSnapshots with_types_in bindings);
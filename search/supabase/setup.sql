@@ -0,0 +1,180 @@
+-- Functions of the golem:search Supabase component.
+--
+-- The REST interface of Supabase can read and write the rows of tables, but not create them, so
+-- the component manages the tables of its indexes and runs its searches through these functions.
+-- Run this script once in the SQL editor of the project.
+--
+-- The table of an index keeps the documents as `jsonb` by id, with a generated text search
+-- vector and a generated pgvector embedding of their `vector` field. The schema of the index and
+-- its text search configuration are kept in the comment of the table. Row level security is
+-- enabled on the tables without policies, so only the service role key can access them.
+
+create extension if not exists vector with schema extensions;
+
+-- The expression of the text search vector of the documents: the indexed text fields of the
+-- schema, or every string of the documents without text fields
+create or replace function golem_search_search_expression(index_schema jsonb, text_config text)
+returns text
+language sql immutable
+as $$
+  select case
+    when count(*) = 0 then format('jsonb_to_tsvector(%L::regconfig, doc, ''["string"]'')', text_config)
+    else format(
+      'to_tsvector(%L::regconfig, %s)',
+      text_config,
+      string_agg(format('coalesce(doc #>> %L, '''')', string_to_array(field ->> 'name', '.')), ' || '' '' || ')
+    )
+  end
+  from jsonb_array_elements(coalesce(index_schema -> 'fields', '[]'::jsonb)) field
+  where field ->> 'type' = 'text' and coalesce((field ->> 'index')::boolean, true)
+$$;
+
+create or replace function golem_search_create_index(
+  index_name text,
+  index_schema jsonb default null,
+  text_config text default 'english',
+  dimensions integer default null
+)
+returns void
+language plpgsql security definer
+set search_path = public, extensions
+as $$
+begin
+  execute format(
+    'create table %I (id text primary key, doc jsonb not null, '
+    'search tsvector generated always as (%s) stored, '
+    'embedding %s generated always as (case when jsonb_typeof(doc -> ''vector'') = ''array'' '
+    'then (doc ->> ''vector'')::%3$s end) stored)',
+    index_name,
+    golem_search_search_expression(index_schema, text_config),
+    case when dimensions is null then 'vector' else format('vector(%s)', dimensions) end
+  );
+  execute format('alter table %I enable row level security', index_name);
+  execute format('create index %I on %I using gin (doc jsonb_path_ops)', index_name || '_doc_idx', index_name);
+  execute format('create index %I on %I using gin (search)', index_name || '_search_idx', index_name);
+  if dimensions is not null then
+    execute format(
+      'create index %I on %I using hnsw (embedding vector_cosine_ops)',
+      index_name || '_embedding_idx',
+      index_name
+    );
+  end if;
+  execute format(
+    'comment on table %I is %L',
+    index_name,
+    jsonb_build_object('schema', index_schema, 'text_config', text_config)::text
+  );
+  notify pgrst, 'reload schema';
+end;
+$$;
+
+create or replace function golem_search_delete_index(index_name text)
+returns void
+language plpgsql security definer
+set search_path = public, extensions
+as $$
+begin
+  execute format('drop table %I', index_name);
+  notify pgrst, 'reload schema';
+end;
+$$;
+
+-- The tables of the public schema having the columns of an index
+create or replace function golem_search_list_indexes()
+returns setof text
+language sql stable security definer
+set search_path = public, extensions
+as $$
+  select c.relname::text from pg_class c
+  join pg_namespace n on n.oid = c.relnamespace
+  where n.nspname = 'public' and c.relkind = 'r' and exists (
+    select 1 from pg_attribute a where a.attrelid = c.oid and a.attname = 'search'
+    and a.atttypid = 'tsvector'::regtype and not a.attisdropped)
+  order by c.relname
+$$;
+
+-- The comment of the table of an index. Missing tables are reported as undefined tables, which
+-- the REST interface answers with a 404.
+create or replace function golem_search_get_index(index_name text)
+returns jsonb
+language plpgsql stable security definer
+set search_path = public, extensions
+as $$
+begin
+  return coalesce(obj_description(format('%I', index_name)::regclass, 'pg_class')::jsonb, '{}'::jsonb);
+end;
+$$;
+
+-- Applies a new schema to the table of an index, regenerating its text search vectors from the
+-- new text fields
+create or replace function golem_search_update_schema(
+  index_name text,
+  index_schema jsonb,
+  text_config text default 'english'
+)
+returns void
+language plpgsql security definer
+set search_path = public, extensions
+as $$
+begin
+  execute format('alter table %I drop column search', index_name);
+  execute format(
+    'alter table %I add column search tsvector generated always as (%s) stored',
+    index_name,
+    golem_search_search_expression(index_schema, text_config)
+  );
+  execute format('create index if not exists %I on %I using gin (search)', index_name || '_search_idx', index_name);
+  execute format(
+    'comment on table %I is %L',
+    index_name,
+    jsonb_build_object('schema', index_schema, 'text_config', text_config)::text
+  );
+  notify pgrst, 'reload schema';
+end;
+$$;
+
+-- The documents of an index matching a text query, parsed with `websearch_to_tsquery`, and
+-- having an embedding when a query vector is given. Matches are scored by cosine similarity to
+-- the query vector, or else by `ts_rank`, and highlighted with `ts_headline` given its options.
+-- The component filters, sorts and pages the matches through the REST interface.
+create or replace function golem_search_match(
+  index_name text,
+  query_text text default null,
+  query_vector text default null,
+  text_config text default 'english',
+  headline_options text default null
+)
+returns table (id text, doc jsonb, score real, highlights jsonb)
+language plpgsql stable security definer
+set search_path = public, extensions
+as $$
+begin
+  return query execute format(
+    'select t.id, t.doc, %s, %s from %I t where %s',
+    case
+      when query_vector is not null then '(1 - (t.embedding <=> $2::vector))::real'
+      when query_text is not null then 'ts_rank(t.search, websearch_to_tsquery($3::regconfig, $1))'
+      else 'null::real'
+    end,
+    case
+      when query_text is not null and headline_options is not null
+        then 'ts_headline($3::regconfig, t.doc, websearch_to_tsquery($3::regconfig, $1), $4)'
+      else 'null::jsonb'
+    end,
+    index_name,
+    concat_ws(
+      ' and ',
+      'true',
+      case when query_text is not null then 't.search @@ websearch_to_tsquery($3::regconfig, $1)' end,
+      case when query_vector is not null then 't.embedding is not null' end
+    )
+  ) using query_text, query_vector, text_config, headline_options;
+end;
+$$;
+
+revoke execute on function golem_search_create_index, golem_search_delete_index,
+  golem_search_list_indexes, golem_search_get_index, golem_search_update_schema,
+  golem_search_match from public, anon, authenticated;
+grant execute on function golem_search_create_index, golem_search_delete_index,
+  golem_search_list_indexes, golem_search_get_index, golem_search_update_schema,
+  golem_search_match to service_role;
@@ -0,0 +1,300 @@
+use golem_search::correlation::{self, CORRELATION_ID_HEADER};
+use golem_search::error::{
+    from_reqwest_error, from_send_error, internal_error, search_error_from_failed_response,
+};
+use golem_search::golem::search::types::SearchError;
+use golem_search::logging::{redact_secret, RedactedResponse};
+use golem_search::retry::RetryPolicy;
+use log::trace;
+use reqwest::{Client, Method, RequestBuilder, Response};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use std::fmt::Debug;
+use std::time::Duration;
+
+/// The function of the setup script returning the matches of a search
+const MATCH_FUNCTION: &str = "golem_search_match";
+
+/// The Supabase client, using the REST interface of the project for the rows of the tables of
+/// the indexes and the functions of the setup script for everything else
+#[derive(Clone)]
+pub struct SupabaseApi {
+    client: Client,
+    base_url: String,
+    api_key: String,
+    retry_policy: RetryPolicy,
+    request_timeout: Option<Duration>,
+    headers: Vec<(String, String)>,
+}
+
+impl Debug for SupabaseApi {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SupabaseApi")
+            .field("base_url", &self.base_url)
+            .field("api_key", &redact_secret(&self.api_key))
+            .field("retry_policy", &self.retry_policy)
+            .field("request_timeout", &self.request_timeout)
+            .finish()
+    }
+}
+
+/// A document row of the table of an index
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Row {
+    pub id: String,
+    pub doc: JsonValue,
+}
+
+/// A row returned by the match function
+#[derive(Debug, Clone, Deserialize)]
+pub struct MatchRow {
+    pub id: String,
+    pub doc: JsonValue,
+    #[serde(default)]
+    pub score: Option<f64>,
+    #[serde(default)]
+    pub highlights: Option<JsonValue>,
+}
+
+/// The arguments of the match function
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MatchArgs {
+    pub index_name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub query_text: Option<String>,
+    /// The query vector as pgvector text, such as `[0.1,0.2]`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub query_vector: Option<String>,
+    pub text_config: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub headline_options: Option<String>,
+}
+
+impl SupabaseApi {
+    pub fn new(api_key: String, base_url: String) -> Self {
+        let client = Client::builder()
+            .build()
+            .expect("Failed to initialize HTTP client");
+
+        Self {
+            client,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            api_key,
+            retry_policy: RetryPolicy::from_config(),
+            request_timeout: None,
+            headers: Vec::new(),
+        }
+    }
+
+    /// Applies a timeout to every request sent by this client
+    pub fn with_request_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.request_timeout = timeout;
+        self
+    }
+
+    /// Adds custom headers to every request sent by this client
+    pub fn with_headers(mut self, headers: Vec<(String, String)>) -> Self {
+        self.headers = headers;
+        self
+    }
+
+    fn create_request(&self, method: Method, url: &str) -> RequestBuilder {
+        trace!("[Supabase] HTTP {method} {url}");
+
+        let mut req = self
+            .client
+            .request(method, url)
+            .header("Content-Type", "application/json")
+            .header("apikey", &self.api_key)
+            .header("Authorization", format!("Bearer {}", self.api_key))
+            .header(CORRELATION_ID_HEADER, correlation::current());
+
+        if let Some(timeout) = self.request_timeout {
+            req = req.timeout(timeout);
+        }
+
+        for (name, value) in &self.headers {
+            req = req.header(name, value);
+        }
+
+        req
+    }
+
+    /// The URL of a path of the REST interface with the given query parameters
+    fn url(&self, path: &str, params: &[(String, String)]) -> String {
+        let mut url = format!("{}/rest/v1/{path}", self.base_url);
+        for (i, (key, value)) in params.iter().enumerate() {
+            url.push(if i == 0 { '?' } else { '&' });
+            url.push_str(&format!(
+                "{}={}",
+                urlencoding::encode(key),
+                urlencoding::encode(value)
+            ));
+        }
+        url
+    }
+}
+
+fn parse_response<T: DeserializeOwned + Debug>(response: Response) -> Result<T, SearchError> {
+    let status = response.status();
+
+    trace!(
+        "Received response from Supabase API: {:?}",
+        RedactedResponse(&response)
+    );
+
+    if status.is_success() {
+        let body = response
+            .json::<T>()
+            .map_err(|err| from_reqwest_error("Failed to decode response body", err))?;
+
+        trace!("Received response from Supabase API: {body:?}");
+
+        Ok(body)
+    } else {
+        let error = search_error_from_failed_response(response);
+
+        trace!("Received {status} response from Supabase API: {error:?}");
+
+        Err(error)
+    }
+}
+
+/// Checks the status of a response with no body of interest
+fn check_response(response: Response) -> Result<(), SearchError> {
+    if response.status().is_success() {
+        Ok(())
+    } else {
+        Err(search_error_from_failed_response(response))
+    }
+}
+
+/// The total of a `Content-Range` header, such as `0-19/57` or `*/0`
+pub fn content_range_total(content_range: &str) -> Option<u32> {
+    content_range.rsplit_once('/')?.1.trim().parse().ok()
+}
+
+impl SupabaseApi {
+    /// Calls a function of the setup script with a result of no interest
+    pub fn call(&self, function: &str, args: &JsonValue) -> Result<(), SearchError> {
+        trace!("Calling function: {function}");
+
+        let url = self.url(&format!("rpc/{function}"), &[]);
+        let response = self
+            .retry_policy
+            .execute(|| self.create_request(Method::POST, &url).json(args).send())
+            .map_err(|e| internal_error(format!("Failed to call {function}: {e}")))?;
+
+        check_response(response)
+    }
+
+    /// Calls a function of the setup script, returning its result
+    pub fn call_with_result<T: DeserializeOwned + Debug>(
+        &self,
+        function: &str,
+        args: &JsonValue,
+    ) -> Result<T, SearchError> {
+        trace!("Calling function: {function}");
+
+        let url = self.url(&format!("rpc/{function}"), &[]);
+        let response = self
+            .retry_policy
+            .execute(|| self.create_request(Method::POST, &url).json(args).send())
+            .map_err(|e| internal_error(format!("Failed to call {function}: {e}")))?;
+
+        parse_response(response)
+    }
+
+    /// Inserts rows into a table, replacing the rows of the same ids
+    pub fn upsert(&self, table: &str, rows: &[Row]) -> Result<(), SearchError> {
+        trace!("Upserting {} rows into table: {table}", rows.len());
+
+        let url = self.url(table, &[("on_conflict".to_string(), "id".to_string())]);
+        let response = self
+            .retry_policy
+            .execute(|| {
+                self.create_request(Method::POST, &url)
+                    .header("Prefer", "resolution=merge-duplicates,return=minimal")
+                    .json(rows)
+                    .send()
+            })
+            .map_err(|e| from_send_error("Failed to upsert rows", e))?;
+
+        check_response(response)
+    }
+
+    /// Deletes the rows of a table selected by the given filters
+    pub fn delete(&self, table: &str, params: &[(String, String)]) -> Result<(), SearchError> {
+        trace!("Deleting rows from table: {table}");
+
+        let url = self.url(table, params);
+        let response = self
+            .retry_policy
+            .execute(|| {
+                self.create_request(Method::DELETE, &url)
+                    .header("Prefer", "return=minimal")
+                    .send()
+            })
+            .map_err(|e| from_send_error("Failed to delete rows", e))?;
+
+        check_response(response)
+    }
+
+    /// Reads the rows of a table selected by the given filters
+    pub fn select(
+        &self,
+        table: &str,
+        params: &[(String, String)],
+    ) -> Result<Vec<Row>, SearchError> {
+        trace!("Selecting rows from table: {table}");
+
+        let url = self.url(table, params);
+        let response = self
+            .retry_policy
+            .execute(|| self.create_request(Method::GET, &url).send())
+            .map_err(|e| from_send_error("Failed to select rows", e))?;
+
+        parse_response(response)
+    }
+
+    /// Calls the match function, filtering, sorting and paging its matches with the given query
+    /// parameters. Returns the page of matches and, when `count` is set, the total number of
+    /// matches counted by the REST interface.
+    pub fn search(
+        &self,
+        args: &MatchArgs,
+        params: &[(String, String)],
+        count: bool,
+    ) -> Result<(Vec<MatchRow>, Option<u32>), SearchError> {
+        trace!("Searching table: {}", args.index_name);
+
+        let url = self.url(&format!("rpc/{MATCH_FUNCTION}"), params);
+        let response = self
+            .retry_policy
+            .execute(|| {
+                let req = self.create_request(Method::POST, &url).json(args);
+                if count {
+                    req.header("Prefer", "count=exact").send()
+                } else {
+                    req.send()
+                }
+            })
+            .map_err(|e| from_send_error("Failed to search", e))?;
+
+        let total = response
+            .headers()
+            .get("Content-Range")
+            .and_then(|value| value.to_str().ok())
+            .and_then(content_range_total);
+        let rows = parse_response::<Vec<MatchRow>>(response)?;
+        Ok((rows, total))
+    }
+}
+
+/// The comment of the table of an index, keeping its schema
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct IndexInfo {
+    #[serde(default)]
+    pub schema: Option<JsonValue>,
+}
@@ -0,0 +1,842 @@
+use crate::client::{IndexInfo, MatchArgs, MatchRow, Row};
+use golem_search::config::{get_max_pagination_depth_config, search_filters_match_any};
+use golem_search::error::invalid_query;
+use golem_search::golem::search::types::{
+    Doc, FieldType, HighlightConfig, Schema, SchemaField, SearchError, SearchHit, SearchQuery,
+};
+use serde_json::{json, Map as JsonMap, Value as JsonValue};
+
+const DEFAULT_PER_PAGE: u32 = 20;
+
+/// Document field holding the vector embedding of a document, which is copied to the embedding
+/// column of the table of its index
+pub const VECTOR_FIELD: &str = "vector";
+
+/// Postgres truncates longer identifiers
+const MAX_TABLE_NAME_LENGTH: usize = 63;
+
+const DEFAULT_PRE_TAG: &str = "<em>";
+const DEFAULT_POST_TAG: &str = "</em>";
+
+/// How the tables of the indexes are created and searched
+#[derive(Debug, Clone)]
+pub struct TableOptions {
+    /// The text search configuration, such as `english` or `simple`, building the text search
+    /// vectors of the documents and the text search queries
+    pub text_search_config: String,
+    /// The dimensions of the vector embeddings, which are indexed for similarity searches when
+    /// given
+    pub vector_dimensions: Option<u32>,
+}
+
+fn is_field_name(key: &str) -> bool {
+    !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.')
+}
+
+/// Checks that an index name can be used as the name of a table of the REST interface
+pub fn check_index_name(name: &str) -> Result<(), SearchError> {
+    let valid = !name.is_empty()
+        && name.len() <= MAX_TABLE_NAME_LENGTH
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    if valid {
+        Ok(())
+    } else {
+        Err(invalid_query(format!(
+            "Invalid Supabase table name `{name}`"
+        )))
+    }
+}
+
+/// The path of a document field in the filters and ordering of the REST interface, where dots
+/// separate the keys of nested fields. `->>` selects the field as text, and `->` as JSON.
+fn field_path(field: &str, as_text: bool) -> String {
+    let keys = field.split('.').collect::<Vec<_>>();
+    let (last, parents) = keys.split_last().expect("split returns at least one key");
+    let mut path = "doc".to_string();
+    for key in parents {
+        path.push_str(&format!("->{key}"));
+    }
+    path.push_str(if as_text { "->>" } else { "->" });
+    path.push_str(last);
+    path
+}
+
+pub fn doc_to_row(doc: Doc) -> Result<Row, SearchError> {
+    match serde_json::from_str::<JsonValue>(&doc.content) {
+        Ok(content @ JsonValue::Object(_)) => Ok(Row {
+            id: doc.id,
+            doc: content,
+        }),
+        Ok(_) => Err(invalid_query("Document content must be a JSON object")),
+        Err(e) => Err(invalid_query(format!("Invalid JSON in document: {e}"))),
+    }
+}
+
+pub fn row_to_doc(row: Row) -> Doc {
+    Doc {
+        id: row.id,
+        content: row.doc.to_string(),
+    }
+}
+
+/// The query parameters selecting the row of a document
+pub fn id_params(id: &str) -> Vec<(String, String)> {
+    vec![
+        ("select".to_string(), "id,doc".to_string()),
+        ("id".to_string(), format!("eq.{id}")),
+    ]
+}
+
+/// The query parameters selecting the rows of documents, with the ids quoted as the `in`
+/// operator requires for values with commas or parentheses
+pub fn ids_params(ids: &[String]) -> Vec<(String, String)> {
+    let ids = ids.iter().map(|id| quote_value(id)).collect::<Vec<_>>();
+    vec![("id".to_string(), format!("in.({})", ids.join(",")))]
+}
+
+/// Quotes a value of a list or a logical operator of the REST interface
+fn quote_value(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn field_type_name(field_type: FieldType) -> &'static str {
+    match field_type {
+        FieldType::Text => "text",
+        FieldType::Keyword => "keyword",
+        FieldType::Integer => "integer",
+        FieldType::Float => "float",
+        FieldType::Boolean => "boolean",
+        FieldType::Date => "date",
+        FieldType::GeoPoint => "geo-point",
+    }
+}
+
+fn field_type_from_name(name: &str) -> Option<FieldType> {
+    match name {
+        "text" => Some(FieldType::Text),
+        "keyword" => Some(FieldType::Keyword),
+        "integer" => Some(FieldType::Integer),
+        "float" => Some(FieldType::Float),
+        "boolean" => Some(FieldType::Boolean),
+        "date" => Some(FieldType::Date),
+        "geo-point" => Some(FieldType::GeoPoint),
+        _ => None,
+    }
+}
+
+/// The schema as the JSON kept in the comment of the table of an index
+fn schema_to_json(schema: &Schema) -> JsonValue {
+    json!({
+        "fields": schema
+            .fields
+            .iter()
+            .map(|field| json!({
+                "name": field.name,
+                "type": field_type_name(field.field_type),
+                "required": field.required,
+                "facet": field.facet,
+                "sort": field.sort,
+                "index": field.index,
+            }))
+            .collect::<Vec<_>>(),
+        "primary_key": schema.primary_key,
+    })
+}
+
+fn json_to_schema_field(field: &JsonValue) -> Option<SchemaField> {
+    let flag = |name: &str| field.get(name).and_then(JsonValue::as_bool);
+    Some(SchemaField {
+        name: field.get("name")?.as_str()?.to_string(),
+        field_type: field_type_from_name(field.get("type")?.as_str()?)?,
+        required: flag("required").unwrap_or(false),
+        facet: flag("facet").unwrap_or(false),
+        sort: flag("sort").unwrap_or(false),
+        index: flag("index").unwrap_or(true),
+    })
+}
+
+/// The schema of an index, from the comment of its table. Tables created without a schema have
+/// no fields.
+pub fn index_info_to_schema(info: &IndexInfo) -> Schema {
+    let schema = info.schema.as_ref().unwrap_or(&JsonValue::Null);
+    Schema {
+        fields: schema
+            .get("fields")
+            .and_then(JsonValue::as_array)
+            .map(|fields| fields.iter().filter_map(json_to_schema_field).collect())
+            .unwrap_or_default(),
+        primary_key: schema
+            .get("primary_key")
+            .and_then(JsonValue::as_str)
+            .map(str::to_string)
+            .or_else(|| Some("id".to_string())),
+    }
+}
+
+fn validate_schema(schema: &Schema) -> Result<(), SearchError> {
+    match schema
+        .fields
+        .iter()
+        .find(|field| !is_field_name(&field.name))
+    {
+        Some(field) => Err(invalid_query(format!(
+            "Invalid field name `{}`",
+            field.name
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// The arguments of `golem_search_create_index`
+pub fn create_index_args(
+    index: &str,
+    schema: Option<&Schema>,
+    options: &TableOptions,
+) -> Result<JsonValue, SearchError> {
+    check_index_name(index)?;
+    if let Some(schema) = schema {
+        validate_schema(schema)?;
+    }
+    Ok(json!({
+        "index_name": index,
+        "index_schema": schema.map(schema_to_json),
+        "text_config": options.text_search_config,
+        "dimensions": options.vector_dimensions,
+    }))
+}
+
+/// The arguments of `golem_search_update_schema`
+pub fn update_schema_args(
+    index: &str,
+    schema: &Schema,
+    options: &TableOptions,
+) -> Result<JsonValue, SearchError> {
+    check_index_name(index)?;
+    validate_schema(schema)?;
+    Ok(json!({
+        "index_name": index,
+        "index_schema": schema_to_json(schema),
+        "text_config": options.text_search_config,
+    }))
+}
+
+/// The value of a filter: quoted values are strings, and numbers and booleans keep their type
+fn filter_value(value: &str) -> JsonValue {
+    let value = value.trim();
+    if let Some(unquoted) = value
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+    {
+        return json!(unquoted);
+    }
+    match serde_json::from_str::<JsonValue>(value) {
+        Ok(value @ (JsonValue::Number(_) | JsonValue::Bool(_))) => value,
+        _ => json!(value),
+    }
+}
+
+/// A filter of the REST interface, as the path of the filtered field, its operator and its value
+#[derive(Debug, Clone, PartialEq)]
+struct Condition {
+    path: String,
+    operator: &'static str,
+    value: String,
+}
+
+/// Converts a filter to a condition of the REST interface. Filters are `field:value`,
+/// `field=value`, `field!=value`, and `field>value` style comparisons with `>`, `>=`, `<` and
+/// `<=`. Numbers and booleans are compared as JSON, so that numbers compare by value, and strings
+/// as text, which compares ISO 8601 dates in order.
+fn filter_to_condition(filter: &str) -> Result<Condition, SearchError> {
+    let filter = filter.trim();
+    let position = filter
+        .find([':', '=', '!', '<', '>'])
+        .ok_or_else(|| invalid_query(format!("Unsupported filter `{filter}`")))?;
+    let key = filter[..position].trim();
+    let rest = &filter[position..];
+    let (op, value) = [">=", "<=", "!=", ">", "<", "=", ":"]
+        .into_iter()
+        .find_map(|op| Some((op, rest.strip_prefix(op)?)))
+        .ok_or_else(|| invalid_query(format!("Unsupported filter `{filter}`")))?;
+    if !is_field_name(key) || value.trim().is_empty() {
+        return Err(invalid_query(format!("Unsupported filter `{filter}`")));
+    }
+
+    let operator = match op {
+        ">=" => "gte",
+        "<=" => "lte",
+        "!=" => "neq",
+        ">" => "gt",
+        "<" => "lt",
+        _ => "eq",
+    };
+    let (path, value) = match filter_value(value) {
+        JsonValue::String(value) => (field_path(key, true), value),
+        value => (field_path(key, false), value.to_string()),
+    };
+    Ok(Condition {
+        path,
+        operator,
+        value,
+    })
+}
+
+/// The query vector, given as `{"vector": [...]}` in the provider parameters of the query
+fn query_vector(query: &SearchQuery) -> Result<Option<Vec<f32>>, SearchError> {
+    let Some(params) = query
+        .config
+        .as_ref()
+        .and_then(|config| config.provider_params.as_deref())
+    else {
+        return Ok(None);
+    };
+    let params = serde_json::from_str::<JsonMap<String, JsonValue>>(params)
+        .map_err(|e| invalid_query(format!("Invalid provider parameters: {e}")))?;
+
+    match params.get(VECTOR_FIELD) {
+        None => Ok(None),
+        Some(vector) => vector
+            .as_array()
+            .and_then(|values| {
+                values
+                    .iter()
+                    .map(|value| value.as_f64().map(|value| value as f32))
+                    .collect::<Option<Vec<_>>>()
+            })
+            .filter(|values| !values.is_empty())
+            .map(Some)
+            .ok_or_else(|| {
+                invalid_query("The query `vector` must be a non-empty array of numbers")
+            }),
+    }
+}
+
+/// The `order` entries of a query's `field`, `field:asc`, `field:desc` or `-field` sort
+/// entries, missing values last
+fn query_to_order(sort: &[String]) -> Result<Vec<String>, SearchError> {
+    sort.iter()
+        .map(|sort| {
+            let sort = sort.trim();
+            let (key, order) = if let Some(key) = sort.strip_prefix('-') {
+                (key, "desc")
+            } else {
+                match sort.rsplit_once(':') {
+                    Some((key, order)) if order.eq_ignore_ascii_case("asc") => (key, "asc"),
+                    Some((key, order)) if order.eq_ignore_ascii_case("desc") => (key, "desc"),
+                    Some(_) => return Err(invalid_query(format!("Invalid sort `{sort}`"))),
+                    None => (sort, "asc"),
+                }
+            };
+            if !is_field_name(key) {
+                return Err(invalid_query(format!("Invalid sort `{sort}`")));
+            }
+            Ok(format!("{}.{order}.nullslast", field_path(key, false)))
+        })
+        .collect()
+}
+
+/// The `ts_headline` options of a highlight, with its tags quoted
+fn headline_options(highlight: &HighlightConfig) -> String {
+    let quote = |tag: &str| format!("\"{}\"", tag.replace('\\', "\\\\").replace('"', "\\\""));
+    format!(
+        "StartSel={}, StopSel={}",
+        quote(highlight.pre_tag.as_deref().unwrap_or(DEFAULT_PRE_TAG)),
+        quote(highlight.post_tag.as_deref().unwrap_or(DEFAULT_POST_TAG))
+    )
+}
+
+/// Number of hits to skip and number of hits per page of a query
+pub fn query_window(query: &SearchQuery) -> Result<(u32, u32), SearchError> {
+    let per_page = query.per_page.unwrap_or(DEFAULT_PER_PAGE);
+    let offset = query.offset.unwrap_or_else(|| {
+        query
+            .page
+            .unwrap_or(1)
+            .saturating_sub(1)
+            .saturating_mul(per_page)
+    });
+    if offset.saturating_add(per_page) > get_max_pagination_depth_config() {
+        return Err(invalid_query(format!(
+            "Requested hits beyond the maximum pagination depth of {}",
+            get_max_pagination_depth_config()
+        )));
+    }
+    Ok((offset, per_page))
+}
+
+/// The arguments of the match function for a query: its text, parsed with
+/// `websearch_to_tsquery`, its query vector, given as `{"vector": [...]}` in the provider
+/// parameters, and its highlight options. The REST interface has no aggregates, so facets are
+/// not supported.
+pub fn query_to_match_args(
+    index: &str,
+    query: &SearchQuery,
+    options: &TableOptions,
+) -> Result<MatchArgs, SearchError> {
+    if !query.facets.is_empty() {
+        return Err(SearchError::Unsupported);
+    }
+    check_index_name(index)?;
+
+    let query_text = query
+        .q
+        .as_deref()
+        .map(str::trim)
+        .filter(|q| !q.is_empty())
+        .map(str::to_string);
+    let headline_options = match (&query_text, &query.highlight) {
+        (Some(_), Some(highlight)) => Some(headline_options(highlight)),
+        _ => None,
+    };
+
+    Ok(MatchArgs {
+        index_name: index.to_string(),
+        query_text,
+        query_vector: query_vector(query)?.map(|vector| json!(vector).to_string()),
+        text_config: options.text_search_config.clone(),
+        headline_options,
+    })
+}
+
+/// The query parameters filtering, sorting and paging the matches of a query. Its filters
+/// restrict the matches, either all of them or, when any filter may match, at least one of them.
+/// Matches are sorted by the sort of the query, or else by score for text and vector searches,
+/// then by id.
+pub fn query_to_params(
+    query: &SearchQuery,
+    args: &MatchArgs,
+) -> Result<Vec<(String, String)>, SearchError> {
+    let (offset, per_page) = query_window(query)?;
+
+    let mut params = vec![("select".to_string(), "id,doc,score,highlights".to_string())];
+
+    let conditions = query
+        .filters
+        .iter()
+        .map(|filter| filter_to_condition(filter))
+        .collect::<Result<Vec<_>, _>>()?;
+    if search_filters_match_any(query) && conditions.len() > 1 {
+        let conditions = conditions
+            .iter()
+            .map(|condition| {
+                format!(
+                    "{}.{}.{}",
+                    condition.path,
+                    condition.operator,
+                    quote_value(&condition.value)
+                )
+            })
+            .collect::<Vec<_>>();
+        params.push(("or".to_string(), format!("({})", conditions.join(","))));
+    } else {
+        params.extend(conditions.into_iter().map(|condition| {
+            (
+                condition.path,
+                format!("{}.{}", condition.operator, condition.value),
+            )
+        }));
+    }
+
+    let mut order = query_to_order(&query.sort)?;
+    if order.is_empty() && (args.query_text.is_some() || args.query_vector.is_some()) {
+        order.push("score.desc".to_string());
+    }
+    order.push("id".to_string());
+    params.push(("order".to_string(), order.join(",")));
+
+    params.push(("limit".to_string(), per_page.to_string()));
+    params.push(("offset".to_string(), offset.to_string()));
+    Ok(params)
+}
+
+/// Highlights in the same shape as the other providers: `{"field": ["snippet"]}`, from the
+/// highlighted document returned by `ts_headline`. Only the highlighted fields, or the top level
+/// fields when none are given, with a match are kept.
+fn highlights_to_snippets(highlighted: &JsonValue, query: &SearchQuery) -> Option<String> {
+    let highlight = query.highlight.as_ref()?;
+    let pre_tag = highlight.pre_tag.as_deref().unwrap_or(DEFAULT_PRE_TAG);
+    let fields = if highlight.fields.is_empty() {
+        highlighted.as_object()?.keys().cloned().collect()
+    } else {
+        highlight.fields.clone()
+    };
+
+    let snippets = fields
+        .into_iter()
+        .filter_map(|field| {
+            let pointer = format!("/{}", field.replace('.', "/"));
+            let snippet = highlighted.pointer(&pointer)?.as_str()?;
+            if !snippet.contains(pre_tag) {
+                return None;
+            }
+            let snippet = match highlight.max_length {
+                Some(max_length) => snippet.chars().take(max_length as usize).collect(),
+                None => snippet.to_string(),
+            };
+            Some((field, json!([snippet])))
+        })
+        .collect::<JsonMap<_, _>>();
+    (!snippets.is_empty()).then(|| JsonValue::Object(snippets).to_string())
+}
+
+pub fn rows_to_hits(rows: Vec<MatchRow>, query: &SearchQuery) -> Vec<SearchHit> {
+    let attributes = query
+        .config
+        .as_ref()
+        .map(|config| config.attributes_to_retrieve.as_slice())
+        .unwrap_or_default();
+
+    rows.into_iter()
+        .map(|row| {
+            let mut content = row.doc;
+            if let (false, JsonValue::Object(fields)) = (attributes.is_empty(), &mut content) {
+                fields.retain(|key, _| attributes.contains(key));
+            }
+            SearchHit {
+                id: row.id,
+                score: row.score,
+                content: Some(content.to_string()),
+                highlights: row
+                    .highlights
+                    .and_then(|highlighted| highlights_to_snippets(&highlighted, query)),
+            }
+        })
+        .collect()
+}
+
+pub fn create_retry_query(original_query: &SearchQuery, partial_hits: &[SearchHit]) -> SearchQuery {
+    let mut retry_query = original_query.clone();
+
+    let current_offset = original_query.offset.unwrap_or(0);
+    let per_page = original_query.per_page.unwrap_or(DEFAULT_PER_PAGE);
+    let hits_received = partial_hits.len() as u32;
+
+    retry_query.offset = Some(current_offset + hits_received.min(per_page));
+
+    retry_query
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use golem_search::golem::search::types::{FilterMode, SearchConfig};
+
+    fn query(q: Option<&str>, filters: &[&str]) -> SearchQuery {
+        SearchQuery {
+            q: q.map(str::to_string),
+            filters: filters.iter().map(|filter| filter.to_string()).collect(),
+            sort: vec![],
+            facets: vec![],
+            page: None,
+            per_page: None,
+            offset: None,
+            highlight: None,
+            config: None,
+        }
+    }
+
+    fn field(name: &str, field_type: FieldType, facet: bool, sort: bool) -> SchemaField {
+        SchemaField {
+            name: name.to_string(),
+            field_type,
+            required: false,
+            facet,
+            sort,
+            index: true,
+        }
+    }
+
+    fn config(provider_params: Option<&str>, filter_mode: Option<FilterMode>) -> SearchConfig {
+        SearchConfig {
+            timeout_ms: None,
+            boost_fields: vec![],
+            attributes_to_retrieve: vec![],
+            language: None,
+            typo_tolerance: None,
+            exact_match_boost: None,
+            provider_params: provider_params.map(str::to_string),
+            profile: None,
+            filter_mode,
+            post_filters: vec![],
+            rescore: None,
+            fallbacks: vec![],
+        }
+    }
+
+    fn options(vector_dimensions: Option<u32>) -> TableOptions {
+        TableOptions {
+            text_search_config: "english".to_string(),
+            vector_dimensions,
+        }
+    }
+
+    fn param(params: &[(String, String)], key: &str) -> Option<String> {
+        params
+            .iter()
+            .find(|(name, _)| name == key)
+            .map(|(_, value)| value.clone())
+    }
+
+    #[test]
+    fn test_docs_and_rows() {
+        let row = doc_to_row(Doc {
+            id: "doc1".to_string(),
+            content: r#"{"title":"Dune"}"#.to_string(),
+        })
+        .unwrap();
+        assert_eq!(
+            row,
+            Row {
+                id: "doc1".to_string(),
+                doc: json!({ "title": "Dune" }),
+            }
+        );
+        assert_eq!(row_to_doc(row).content, r#"{"title":"Dune"}"#);
+
+        assert!(matches!(
+            doc_to_row(Doc {
+                id: "doc1".to_string(),
+                content: "[]".to_string(),
+            }),
+            Err(SearchError::InvalidQuery(_))
+        ));
+
+        assert_eq!(
+            id_params("doc,1"),
+            vec![
+                ("select".to_string(), "id,doc".to_string()),
+                ("id".to_string(), "eq.doc,1".to_string()),
+            ]
+        );
+        assert_eq!(
+            ids_params(&["doc1".to_string(), "a,\"b\"".to_string()]),
+            vec![("id".to_string(), r#"in.("doc1","a,\"b\"")"#.to_string())]
+        );
+    }
+
+    #[test]
+    fn test_index_args() {
+        let schema = Schema {
+            fields: vec![field("title", FieldType::Text, false, false)],
+            primary_key: Some("id".to_string()),
+        };
+        assert_eq!(
+            create_index_args("books", Some(&schema), &options(Some(3))).unwrap(),
+            json!({
+                "index_name": "books",
+                "index_schema": {
+                    "fields": [{
+                        "name": "title",
+                        "type": "text",
+                        "required": false,
+                        "facet": false,
+                        "sort": false,
+                        "index": true,
+                    }],
+                    "primary_key": "id",
+                },
+                "text_config": "english",
+                "dimensions": 3,
+            })
+        );
+        assert_eq!(
+            create_index_args("books", None, &options(None)).unwrap(),
+            json!({
+                "index_name": "books",
+                "index_schema": null,
+                "text_config": "english",
+                "dimensions": null,
+            })
+        );
+        assert_eq!(
+            update_schema_args("books", &schema, &options(None)).unwrap()["text_config"],
+            json!("english")
+        );
+
+        assert!(create_index_args("my books", None, &options(None)).is_err());
+        assert!(create_index_args(&"a".repeat(64), None, &options(None)).is_err());
+        let invalid = Schema {
+            fields: vec![field("bad field", FieldType::Text, false, false)],
+            primary_key: None,
+        };
+        assert!(create_index_args("books", Some(&invalid), &options(None)).is_err());
+    }
+
+    #[test]
+    fn test_schema_round_trip() {
+        let schema = Schema {
+            fields: vec![
+                field("title", FieldType::Text, false, false),
+                field("genre", FieldType::Keyword, true, false),
+                field("year", FieldType::Integer, false, true),
+            ],
+            primary_key: Some("isbn".to_string()),
+        };
+        let info = IndexInfo {
+            schema: Some(schema_to_json(&schema)),
+        };
+        assert_eq!(index_info_to_schema(&info), schema);
+
+        let empty = index_info_to_schema(&IndexInfo::default());
+        assert!(empty.fields.is_empty());
+        assert_eq!(empty.primary_key.as_deref(), Some("id"));
+    }
+
+    #[test]
+    fn test_filters_to_conditions() {
+        let condition = |filter: &str| {
+            let condition = filter_to_condition(filter).unwrap();
+            format!(
+                "{}={}.{}",
+                condition.path, condition.operator, condition.value
+            )
+        };
+        assert_eq!(condition("genre:scifi"), "doc->>genre=eq.scifi");
+        assert_eq!(condition("genre != \"1984\""), "doc->>genre=neq.1984");
+        assert_eq!(condition("year>=2000"), "doc->year=gte.2000");
+        assert_eq!(condition("rating<4.5"), "doc->rating=lt.4.5");
+        assert_eq!(condition("available=true"), "doc->available=eq.true");
+        assert_eq!(
+            condition("author.name:Herbert"),
+            "doc->author->>name=eq.Herbert"
+        );
+        assert_eq!(
+            condition("published>2020-01-01"),
+            "doc->>published=gt.2020-01-01"
+        );
+
+        assert!(filter_to_condition("genre").is_err());
+        assert!(filter_to_condition("genre:").is_err());
+        assert!(filter_to_condition("bad field:x").is_err());
+    }
+
+    #[test]
+    fn test_query_to_params() {
+        let mut search = query(Some("dune"), &["genre:scifi", "year>2000"]);
+        search.sort = vec!["-year".to_string(), "title".to_string()];
+        search.page = Some(3);
+        search.per_page = Some(10);
+        let args = query_to_match_args("books", &search, &options(None)).unwrap();
+        assert_eq!(
+            query_to_params(&search, &args).unwrap(),
+            vec![
+                ("select".to_string(), "id,doc,score,highlights".to_string()),
+                ("doc->>genre".to_string(), "eq.scifi".to_string()),
+                ("doc->year".to_string(), "gt.2000".to_string()),
+                (
+                    "order".to_string(),
+                    "doc->year.desc.nullslast,doc->title.asc.nullslast,id".to_string()
+                ),
+                ("limit".to_string(), "10".to_string()),
+                ("offset".to_string(), "20".to_string()),
+            ]
+        );
+
+        let mut search = query(Some("dune"), &["genre:sci,fi", "year>2000"]);
+        search.config = Some(config(None, Some(FilterMode::Any)));
+        let args = query_to_match_args("books", &search, &options(None)).unwrap();
+        let params = query_to_params(&search, &args).unwrap();
+        assert_eq!(
+            param(&params, "or").as_deref(),
+            Some(r#"(doc->>genre.eq."sci,fi",doc->year.gt."2000")"#)
+        );
+        assert_eq!(param(&params, "order").as_deref(), Some("score.desc,id"));
+
+        let search = query(None, &[]);
+        let args = query_to_match_args("books", &search, &options(None)).unwrap();
+        let params = query_to_params(&search, &args).unwrap();
+        assert_eq!(param(&params, "order").as_deref(), Some("id"));
+
+        let mut search = query(None, &[]);
+        search.sort = vec!["year:sideways".to_string()];
+        assert!(query_to_params(&search, &args).is_err());
+    }
+
+    #[test]
+    fn test_query_to_match_args() {
+        let mut search = query(Some(" dune "), &[]);
+        search.highlight = Some(HighlightConfig {
+            fields: vec![],
+            pre_tag: Some("<b>".to_string()),
+            post_tag: None,
+            max_length: None,
+        });
+        search.config = Some(config(Some(r#"{"vector": [0.5, 1]}"#), None));
+        assert_eq!(
+            query_to_match_args("books", &search, &options(None)).unwrap(),
+            MatchArgs {
+                index_name: "books".to_string(),
+                query_text: Some("dune".to_string()),
+                query_vector: Some("[0.5,1.0]".to_string()),
+                text_config: "english".to_string(),
+                headline_options: Some("StartSel=\"<b>\", StopSel=\"</em>\"".to_string()),
+            }
+        );
+
+        let mut search = query(None, &[]);
+        search.config = Some(config(Some(r#"{"vector": []}"#), None));
+        assert!(matches!(
+            query_to_match_args("books", &search, &options(None)),
+            Err(SearchError::InvalidQuery(_))
+        ));
+
+        let mut search = query(Some("dune"), &[]);
+        search.facets = vec!["genre".to_string()];
+        assert!(matches!(
+            query_to_match_args("books", &search, &options(None)),
+            Err(SearchError::Unsupported)
+        ));
+    }
+
+    #[test]
+    fn test_rows_to_hits() {
+        let mut search = query(Some("dune"), &[]);
+        search.highlight = Some(HighlightConfig {
+            fields: vec![],
+            pre_tag: None,
+            post_tag: None,
+            max_length: None,
+        });
+        let mut search_config = config(None, None);
+        search_config.attributes_to_retrieve = vec!["title".to_string()];
+        search.config = Some(search_config);
+
+        let rows = vec![MatchRow {
+            id: "doc1".to_string(),
+            doc: json!({ "title": "Dune", "year": 1965 }),
+            score: Some(0.5),
+            highlights: Some(json!({ "title": "<em>Dune</em>", "year": 1965 })),
+        }];
+        let hits = rows_to_hits(rows, &search);
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].id, "doc1");
+        assert_eq!(hits[0].score, Some(0.5));
+        assert_eq!(hits[0].content.as_deref(), Some(r#"{"title":"Dune"}"#));
+        assert_eq!(
+            hits[0].highlights.as_deref(),
+            Some(r#"{"title":["<em>Dune</em>"]}"#)
+        );
+    }
+
+    #[test]
+    fn test_create_retry_query() {
+        let mut search = query(Some("dune"), &[]);
+        search.offset = Some(10);
+        search.per_page = Some(5);
+        let hits = rows_to_hits(
+            vec![MatchRow {
+                id: "doc1".to_string(),
+                doc: json!({}),
+                score: None,
+                highlights: None,
+            }],
+            &search,
+        );
+        assert_eq!(create_retry_query(&search, &hits).offset, Some(11));
+    }
+}
@@ -0,0 +1,336 @@
+use crate::client::{IndexInfo, Row, SupabaseApi};
+use crate::conversions::{
+    check_index_name, create_index_args, create_retry_query, doc_to_row, id_params, ids_params,
+    index_info_to_schema, query_to_match_args, query_to_params, query_window, row_to_doc,
+    rows_to_hits, update_schema_args, TableOptions,
+};
+use golem_rust::wasm_rpc::Pollable;
+use golem_search::config::{
+    get_config_with_default, get_optional_config, profile_key, search_headers, search_profile,
+    search_timeout, worker_headers, worker_profile,
+};
+use golem_search::durability::{DurableSearch, ExtendedGuest};
+use golem_search::golem::search::core::{Guest, GuestSearchStream, SearchStream};
+use golem_search::golem::search::types::{
+    Doc, DocumentId, IndexName, IndexPage, PageRequest, Schema, SearchError, SearchHit,
+    SearchQuery, SearchResults,
+};
+use golem_search::pagination::index_page;
+use golem_search::LOGGING_STATE;
+use serde_json::json;
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+
+mod client;
+mod conversions;
+
+/// Search stream over the pages of a query, as the REST interface has no native streaming
+struct SupabaseSearchStream {
+    client: SupabaseApi,
+    index_name: String,
+    query: SearchQuery,
+    offset: Cell<u32>,
+    finished: Cell<bool>,
+}
+
+impl SupabaseSearchStream {
+    pub fn new(client: SupabaseApi, index_name: String, query: SearchQuery) -> Self {
+        let offset = query_window(&query).map(|(offset, _)| offset).unwrap_or(0);
+        Self {
+            client,
+            index_name,
+            query,
+            offset: Cell::new(offset),
+            finished: Cell::new(false),
+        }
+    }
+
+    pub fn subscribe(&self) -> Pollable {
+        golem_rust::bindings::wasi::clocks::monotonic_clock::subscribe_duration(0)
+    }
+}
+
+impl GuestSearchStream for SupabaseSearchStream {
+    fn get_next(&self) -> Option<Vec<SearchHit>> {
+        if self.finished.get() {
+            return Some(vec![]);
+        }
+
+        let mut query = self.query.clone();
+        query.page = None;
+        query.offset = Some(self.offset.get());
+        let per_page = query_window(&query)
+            .map(|(_, per_page)| per_page)
+            .unwrap_or(0);
+
+        match SupabaseComponent::search_page(&self.client, &self.index_name, &query, false) {
+            Ok(results) => {
+                let received = results.hits.len() as u32;
+                if received < per_page || received == 0 {
+                    self.finished.set(true);
+                }
+                self.offset.set(self.offset.get() + received);
+                Some(results.hits)
+            }
+            Err(_) => {
+                self.finished.set(true);
+                Some(vec![])
+            }
+        }
+    }
+
+    fn blocking_get_next(&self) -> Vec<SearchHit> {
+        self.get_next().unwrap_or_default()
+    }
+}
+
+struct SupabaseComponent;
+
+thread_local! {
+    /// The provider clients by configuration profile, reused across calls instead of being rebuilt
+    /// for each of them
+    static CLIENTS: RefCell<HashMap<Option<String>, SupabaseApi>> = RefCell::new(HashMap::new());
+}
+
+impl SupabaseComponent {
+    const URL_ENV_VAR: &'static str = "SUPABASE_URL";
+    const API_KEY_ENV_VAR: &'static str = "SUPABASE_API_KEY";
+    const TEXT_SEARCH_CONFIG_ENV_VAR: &'static str = "SUPABASE_TEXT_SEARCH_CONFIG";
+    const VECTOR_DIMENSIONS_ENV_VAR: &'static str = "SUPABASE_VECTOR_DIMENSIONS";
+
+    fn create_client() -> Result<SupabaseApi, SearchError> {
+        Self::create_profile_client(worker_profile().as_deref())
+            .map(|client| client.with_headers(worker_headers()))
+    }
+
+    /// Returns the client of `profile` cached for this worker, creating it from the configuration
+    /// on first use
+    fn create_profile_client(profile: Option<&str>) -> Result<SupabaseApi, SearchError> {
+        CLIENTS.with_borrow_mut(|clients| {
+            let profile = profile.map(str::to_string);
+            if let Some(client) = clients.get(&profile) {
+                return Ok(client.clone());
+            }
+
+            let client = Self::new_client(profile.as_deref())?;
+            clients.insert(profile, client.clone());
+            Ok(client)
+        })
+    }
+
+    /// Creates a client from the `SUPABASE_URL` of the project, such as
+    /// `https://<project>.supabase.co`, and the `SUPABASE_API_KEY` of its service role, as the
+    /// tables of the indexes are only accessible to it
+    fn new_client(profile: Option<&str>) -> Result<SupabaseApi, SearchError> {
+        let required = |name: &str, what: &str| {
+            get_optional_config(profile_key(name, profile))
+                .filter(|value| !value.is_empty())
+                .ok_or_else(|| SearchError::Internal(format!("Missing Supabase {what}")))
+        };
+
+        let url = required(Self::URL_ENV_VAR, "URL")?;
+        let api_key = required(Self::API_KEY_ENV_VAR, "API key")?;
+
+        Ok(SupabaseApi::new(api_key, url))
+    }
+
+    /// The text search configuration of `SUPABASE_TEXT_SEARCH_CONFIG`, `english` by default, and
+    /// the `SUPABASE_VECTOR_DIMENSIONS` of the embeddings of the documents, which indexes the
+    /// embeddings of the tables of new indexes
+    fn table_options(profile: Option<&str>) -> TableOptions {
+        TableOptions {
+            text_search_config: get_config_with_default(
+                profile_key(Self::TEXT_SEARCH_CONFIG_ENV_VAR, profile),
+                "english",
+            ),
+            vector_dimensions: get_optional_config(profile_key(
+                Self::VECTOR_DIMENSIONS_ENV_VAR,
+                profile,
+            ))
+            .and_then(|dimensions| dimensions.trim().parse().ok()),
+        }
+    }
+
+    /// Runs a page of a query through the match function of the setup script. The total number
+    /// of matches is only counted when `count` is set, which the streams skip.
+    fn search_page(
+        client: &SupabaseApi,
+        index: &str,
+        query: &SearchQuery,
+        count: bool,
+    ) -> Result<SearchResults, SearchError> {
+        let (offset, per_page) = query_window(query)?;
+        let args = query_to_match_args(
+            index,
+            query,
+            &Self::table_options(search_profile(query).as_deref()),
+        )?;
+        let params = query_to_params(query, &args)?;
+
+        let (rows, total) = client.search(&args, &params, count)?;
+
+        Ok(SearchResults {
+            total,
+            page: Some(offset / per_page.max(1) + 1),
+            per_page: Some(per_page),
+            hits: rows_to_hits(rows, query),
+            facets: None,
+            took_ms: None,
+            relaxation: None,
+            navigation: None,
+        })
+    }
+}
+
+impl Guest for SupabaseComponent {
+    type SearchStream = SupabaseSearchStream;
+
+    /// Creates the table of the index with the setup script, which indexes the text search
+    /// vectors of its documents and, when the embeddings have dimensions, their vectors
+    fn create_index(name: IndexName, schema: Option<Schema>) -> Result<(), SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let args = create_index_args(
+            &name,
+            schema.as_ref(),
+            &Self::table_options(worker_profile().as_deref()),
+        )?;
+        let client = Self::create_client()?;
+        client.call("golem_search_create_index", &args)
+    }
+
+    fn delete_index(name: IndexName) -> Result<(), SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        check_index_name(&name)?;
+        let client = Self::create_client()?;
+        client.call("golem_search_delete_index", &json!({ "index_name": name }))
+    }
+
+    fn list_indexes(page: PageRequest) -> Result<IndexPage, SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = Self::create_client()?;
+
+        let names: Vec<String> =
+            client.call_with_result("golem_search_list_indexes", &json!({}))?;
+        index_page(names, &page)
+    }
+
+    fn upsert(index: IndexName, doc: Doc) -> Result<(), SearchError> {
+        Self::upsert_many(index, vec![doc])
+    }
+
+    fn upsert_many(index: IndexName, docs: Vec<Doc>) -> Result<(), SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        if docs.is_empty() {
+            return Ok(());
+        }
+
+        check_index_name(&index)?;
+        let rows = docs
+            .into_iter()
+            .map(doc_to_row)
+            .collect::<Result<Vec<_>, _>>()?;
+        let client = Self::create_client()?;
+        client.upsert(&index, &rows)
+    }
+
+    fn delete(index: IndexName, id: DocumentId) -> Result<(), SearchError> {
+        Self::delete_many(index, vec![id])
+    }
+
+    fn delete_many(index: IndexName, ids: Vec<DocumentId>) -> Result<(), SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        if ids.is_empty() {
+            return Ok(());
+        }
+
+        check_index_name(&index)?;
+        let client = Self::create_client()?;
+        client.delete(&index, &ids_params(&ids))
+    }
+
+    fn get(index: IndexName, id: DocumentId) -> Result<Option<Doc>, SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        check_index_name(&index)?;
+        let client = Self::create_client()?;
+
+        let rows: Vec<Row> = client.select(&index, &id_params(&id))?;
+        Ok(rows.into_iter().next().map(row_to_doc))
+    }
+
+    fn search(index: IndexName, query: SearchQuery) -> Result<SearchResults, SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = Self::create_profile_client(search_profile(&query).as_deref())?
+            .with_request_timeout(search_timeout(&query))
+            .with_headers(search_headers(&query));
+
+        Self::search_page(&client, &index, &query, true)
+    }
+
+    fn stream_search(index: IndexName, query: SearchQuery) -> Result<SearchStream, SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = Self::create_profile_client(search_profile(&query).as_deref())?
+            .with_request_timeout(search_timeout(&query))
+            .with_headers(search_headers(&query));
+        let stream = SupabaseSearchStream::new(client, index, query);
+        Ok(SearchStream::new(stream))
+    }
+
+    fn get_schema(index: IndexName) -> Result<Schema, SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        check_index_name(&index)?;
+        let client = Self::create_client()?;
+
+        let info: IndexInfo =
+            client.call_with_result("golem_search_get_index", &json!({ "index_name": index }))?;
+        Ok(index_info_to_schema(&info))
+    }
+
+    /// Applies the schema to the table of the index, regenerating the text search vectors of
+    /// its documents from the new text fields
+    fn update_schema(index: IndexName, schema: Schema) -> Result<(), SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let args = update_schema_args(
+            &index,
+            &schema,
+            &Self::table_options(worker_profile().as_deref()),
+        )?;
+        let client = Self::create_client()?;
+        client.call("golem_search_update_schema", &args)
+    }
+}
+
+impl ExtendedGuest for SupabaseComponent {
+    fn unwrapped_stream(index: IndexName, query: SearchQuery) -> Self::SearchStream {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = Self::create_profile_client(search_profile(&query).as_deref())
+            .unwrap_or_else(|_| {
+                SupabaseApi::new(String::new(), "http://localhost:54321".to_string())
+            })
+            .with_headers(search_headers(&query));
+
+        SupabaseSearchStream::new(client, index, query)
+    }
+
+    fn retry_query(original_query: &SearchQuery, partial_hits: &[SearchHit]) -> SearchQuery {
+        create_retry_query(original_query, partial_hits)
+    }
+
+    fn subscribe(stream: &Self::SearchStream) -> Pollable {
+        stream.subscribe()
+    }
+}
+
+type DurableSupabaseComponent = DurableSearch<SupabaseComponent>;
+
+golem_search::export_search!(DurableSupabaseComponent with_types_in golem_search);
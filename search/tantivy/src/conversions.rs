@@ -0,0 +1,787 @@
+use golem_search::config::{get_max_pagination_depth_config, search_filters_match_any};
+use golem_search::error::invalid_query;
+use golem_search::golem::search::types::{
+    Doc, FieldType, Schema, SearchError, SearchHit, SearchQuery,
+};
+use serde_json::{json, Map as JsonMap, Value as JsonValue};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::ops::{Bound, Range};
+use tantivy::query::{AllQuery, BooleanQuery, Occur, Query, QueryParser, RangeQuery, TermQuery};
+use tantivy::schema::{
+    Field, IndexRecordOption, TextFieldIndexing, TextOptions, FAST, INDEXED, STRING,
+};
+use tantivy::{Index, TantivyDocument, Term};
+
+const DEFAULT_PER_PAGE: u32 = 20;
+
+/// Maximum number of values counted per facet
+const MAX_FACET_VALUES: usize = 100;
+
+/// The full-text field of the indexes without text fields in their schema, holding every string
+/// of the documents
+const ALL_FIELD: &str = "_all";
+
+pub const DEFAULT_PRE_TAG: &str = "<em>";
+pub const DEFAULT_POST_TAG: &str = "</em>";
+
+/// The JSON object of a document
+pub type Source = JsonMap<String, JsonValue>;
+
+/// The Tantivy schema of an index and its fields
+pub struct Fields {
+    pub schema: tantivy::schema::Schema,
+    /// The fields of the schema indexed by Tantivy, by name
+    pub indexed: HashMap<String, (Field, FieldType)>,
+    /// The full-text fields searched by text queries: the text fields of the schema, or `_all`
+    pub text: Vec<(String, Field)>,
+    /// The `_all` field of the indexes without text fields
+    pub all: Option<Field>,
+}
+
+/// Builds the Tantivy schema of an index. Text fields are tokenized with `tokenizer`, keyword and
+/// date fields are indexed as single terms, so ISO 8601 dates compare in order, and numbers and
+/// booleans as numeric fields. Geo points are only kept in the documents.
+pub fn build_fields(schema: Option<&Schema>, tokenizer: &str) -> Fields {
+    let text_options = TextOptions::default().set_indexing_options(
+        TextFieldIndexing::default()
+            .set_tokenizer(tokenizer)
+            .set_index_option(IndexRecordOption::WithFreqsAndPositions),
+    );
+
+    let mut builder = tantivy::schema::Schema::builder();
+    let mut indexed = HashMap::new();
+    let mut text = Vec::new();
+    for field in schema
+        .map(|schema| schema.fields.as_slice())
+        .unwrap_or_default()
+        .iter()
+        .filter(|field| field.index)
+    {
+        let tantivy_field = match field.field_type {
+            FieldType::Text => builder.add_text_field(&field.name, text_options.clone()),
+            FieldType::Keyword | FieldType::Date => {
+                builder.add_text_field(&field.name, STRING | FAST)
+            }
+            FieldType::Integer => builder.add_i64_field(&field.name, INDEXED | FAST),
+            FieldType::Float => builder.add_f64_field(&field.name, INDEXED | FAST),
+            FieldType::Boolean => builder.add_bool_field(&field.name, INDEXED | FAST),
+            FieldType::GeoPoint => continue,
+        };
+        if field.field_type == FieldType::Text {
+            text.push((field.name.clone(), tantivy_field));
+        }
+        indexed.insert(field.name.clone(), (tantivy_field, field.field_type));
+    }
+
+    let all = text
+        .is_empty()
+        .then(|| builder.add_text_field(ALL_FIELD, text_options));
+    if let Some(all) = all {
+        text.push((ALL_FIELD.to_string(), all));
+    }
+
+    Fields {
+        schema: builder.build(),
+        indexed,
+        text,
+        all,
+    }
+}
+
+/// Fields are named with letters, digits and `_`, where dots separate the keys of nested
+/// fields. Names starting with `_` are kept for the fields of the provider.
+pub fn validate_schema(schema: &Schema) -> Result<(), SearchError> {
+    match schema.fields.iter().find(|field| {
+        field.name.starts_with('_')
+            || field.name.is_empty()
+            || !field
+                .name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.')
+    }) {
+        Some(field) => Err(invalid_query(format!(
+            "Invalid field name `{}`",
+            field.name
+        ))),
+        None => Ok(()),
+    }
+}
+
+pub fn doc_to_source(doc: &Doc) -> Result<Source, SearchError> {
+    match serde_json::from_str::<JsonValue>(&doc.content) {
+        Ok(JsonValue::Object(source)) => Ok(source),
+        Ok(_) => Err(invalid_query("Document content must be a JSON object")),
+        Err(e) => Err(invalid_query(format!("Invalid JSON in document: {e}"))),
+    }
+}
+
+/// The values of a field of a document, where dots separate the keys of nested fields, and the
+/// elements of arrays are values of their field
+pub fn field_values<'a>(source: &'a Source, field: &str) -> Vec<&'a JsonValue> {
+    let mut keys = field.split('.');
+    let first = keys.next().and_then(|key| source.get(key));
+    let value = keys.try_fold(first, |value, key| Some(value?.get(key)));
+    match value.flatten() {
+        Some(JsonValue::Array(values)) => values.iter().collect(),
+        Some(JsonValue::Null) | None => vec![],
+        Some(value) => vec![value],
+    }
+}
+
+/// Every string of a document, for the `_all` field
+fn strings(value: &JsonValue, result: &mut Vec<String>) {
+    match value {
+        JsonValue::String(text) => result.push(text.clone()),
+        JsonValue::Array(values) => values.iter().for_each(|value| strings(value, result)),
+        JsonValue::Object(fields) => fields.values().for_each(|value| strings(value, result)),
+        _ => {}
+    }
+}
+
+/// The text of a field of a document, for its snippets
+pub fn field_text(source: &Source, field: &str) -> String {
+    let mut result = Vec::new();
+    for value in field_values(source, field) {
+        strings(value, &mut result);
+    }
+    result.join(" ")
+}
+
+/// Converts a document to the Tantivy document of its indexed fields. Values which don't have the
+/// type of their field are only kept in the document.
+pub fn to_tantivy_doc(fields: &Fields, source: &Source) -> TantivyDocument {
+    let mut doc = TantivyDocument::default();
+    for (name, (field, field_type)) in &fields.indexed {
+        for value in field_values(source, name) {
+            match (field_type, value) {
+                (FieldType::Integer, JsonValue::Number(number)) => {
+                    if let Some(number) = number.as_i64() {
+                        doc.add_i64(*field, number);
+                    }
+                }
+                (FieldType::Float, JsonValue::Number(number)) => {
+                    if let Some(number) = number.as_f64() {
+                        doc.add_f64(*field, number);
+                    }
+                }
+                (FieldType::Boolean, JsonValue::Bool(value)) => doc.add_bool(*field, *value),
+                (
+                    FieldType::Text | FieldType::Keyword | FieldType::Date,
+                    JsonValue::String(text),
+                ) => doc.add_text(*field, text),
+                _ => {}
+            }
+        }
+    }
+    if let Some(all) = fields.all {
+        let mut result = Vec::new();
+        source
+            .values()
+            .for_each(|value| strings(value, &mut result));
+        doc.add_text(all, result.join(" "));
+    }
+    doc
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum FilterOp {
+    Eq,
+    Ne,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Filter {
+    field: String,
+    op: FilterOp,
+    value: String,
+}
+
+/// Parses a filter: `field:value`, `field=value`, `field!=value`, and `field>value` style
+/// comparisons with `>`, `>=`, `<` and `<=`. Quotes around the value are removed.
+fn parse_filter(filter: &str) -> Result<Filter, SearchError> {
+    let filter = filter.trim();
+    let unsupported = || invalid_query(format!("Unsupported filter `{filter}`"));
+    let position = filter
+        .find([':', '=', '!', '<', '>'])
+        .ok_or_else(unsupported)?;
+    let field = filter[..position].trim();
+    let rest = &filter[position..];
+    let (op, value) = [
+        (">=", FilterOp::Ge),
+        ("<=", FilterOp::Le),
+        ("!=", FilterOp::Ne),
+        (">", FilterOp::Gt),
+        ("<", FilterOp::Lt),
+        ("=", FilterOp::Eq),
+        (":", FilterOp::Eq),
+    ]
+    .into_iter()
+    .find_map(|(prefix, op)| Some((op, rest.strip_prefix(prefix)?)))
+    .ok_or_else(unsupported)?;
+
+    let value = value.trim();
+    let value = value
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+        .unwrap_or(value);
+    if field.is_empty() || value.is_empty() {
+        return Err(unsupported());
+    }
+    Ok(Filter {
+        field: field.to_string(),
+        op,
+        value: value.to_string(),
+    })
+}
+
+fn bounds<T>(op: FilterOp, value: T) -> (Bound<T>, Bound<T>) {
+    match op {
+        FilterOp::Gt => (Bound::Excluded(value), Bound::Unbounded),
+        FilterOp::Ge => (Bound::Included(value), Bound::Unbounded),
+        FilterOp::Lt => (Bound::Unbounded, Bound::Excluded(value)),
+        _ => (Bound::Unbounded, Bound::Included(value)),
+    }
+}
+
+fn parse_value<T: std::str::FromStr>(filter: &Filter) -> Result<T, SearchError> {
+    filter.value.parse().map_err(|_| {
+        invalid_query(format!(
+            "Invalid value `{}` for the field `{}`",
+            filter.value, filter.field
+        ))
+    })
+}
+
+/// Converts a filter to a Tantivy query on a field of the schema. Text fields match the value as
+/// a phrase, and the other fields match it exactly.
+fn filter_to_query(
+    index: &Index,
+    fields: &Fields,
+    filter: &Filter,
+) -> Result<Box<dyn Query>, SearchError> {
+    let (field, field_type) = fields.indexed.get(&filter.field).ok_or_else(|| {
+        invalid_query(format!(
+            "Can't filter on `{}`, which is not an indexed field of the schema",
+            filter.field
+        ))
+    })?;
+    let (field, name) = (*field, filter.field.clone());
+
+    let query: Box<dyn Query> = match (filter.op, field_type) {
+        (FilterOp::Eq | FilterOp::Ne, FieldType::Text) => {
+            QueryParser::for_index(index, vec![field])
+                .parse_query(&format!("\"{}\"", filter.value.replace('"', " ")))
+                .map_err(|e| invalid_query(format!("Invalid filter value: {e}")))?
+        }
+        (FilterOp::Eq | FilterOp::Ne, field_type) => {
+            let term = match field_type {
+                FieldType::Integer => Term::from_field_i64(field, parse_value(filter)?),
+                FieldType::Float => Term::from_field_f64(field, parse_value(filter)?),
+                FieldType::Boolean => Term::from_field_bool(field, parse_value(filter)?),
+                _ => Term::from_field_text(field, &filter.value),
+            };
+            Box::new(TermQuery::new(term, IndexRecordOption::Basic))
+        }
+        (op, FieldType::Keyword | FieldType::Date) => {
+            let (lower, upper) = bounds(op, filter.value.as_str());
+            Box::new(RangeQuery::new_str_bounds(name, lower, upper))
+        }
+        (op, FieldType::Integer) => {
+            let (lower, upper) = bounds(op, parse_value(filter)?);
+            Box::new(RangeQuery::new_i64_bounds(name, lower, upper))
+        }
+        (op, FieldType::Float) => {
+            let (lower, upper) = bounds(op, parse_value(filter)?);
+            Box::new(RangeQuery::new_f64_bounds(name, lower, upper))
+        }
+        _ => {
+            return Err(invalid_query(format!(
+                "Can't compare the values of `{}`",
+                filter.field
+            )))
+        }
+    };
+
+    Ok(match filter.op {
+        FilterOp::Ne => Box::new(BooleanQuery::new(vec![
+            (Occur::Must, Box::new(AllQuery)),
+            (Occur::MustNot, query),
+        ])),
+        _ => query,
+    })
+}
+
+/// The query of a query's text, in Tantivy's query syntax, on the full-text fields. Boosted fields
+/// are weighted with their boost.
+pub fn text_query(
+    index: &Index,
+    fields: &Fields,
+    query: &SearchQuery,
+) -> Result<Option<Box<dyn Query>>, SearchError> {
+    let Some(q) = query.q.as_deref().filter(|q| !q.trim().is_empty()) else {
+        return Ok(None);
+    };
+
+    let mut parser =
+        QueryParser::for_index(index, fields.text.iter().map(|(_, field)| *field).collect());
+    for (name, boost) in query
+        .config
+        .iter()
+        .flat_map(|config| config.boost_fields.iter())
+    {
+        if let Some((_, field)) = fields.text.iter().find(|(text, _)| text == name) {
+            parser.set_field_boost(*field, *boost);
+        }
+    }
+    parser
+        .parse_query(q)
+        .map(Some)
+        .map_err(|e| invalid_query(format!("Invalid query: {e}")))
+}
+
+/// The query of the matches of a search: the text query and the filters, which are all required
+/// unless the filter mode is `any`
+pub fn search_query(
+    index: &Index,
+    fields: &Fields,
+    text_query: Option<&dyn Query>,
+    query: &SearchQuery,
+) -> Result<Box<dyn Query>, SearchError> {
+    let filters = query
+        .filters
+        .iter()
+        .map(|filter| filter_to_query(index, fields, &parse_filter(filter)?))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut clauses: Vec<(Occur, Box<dyn Query>)> = text_query
+        .map(|text_query| (Occur::Must, text_query.box_clone()))
+        .into_iter()
+        .collect();
+    if search_filters_match_any(query) && filters.len() > 1 {
+        let any = filters
+            .into_iter()
+            .map(|filter| (Occur::Should, filter))
+            .collect();
+        clauses.push((Occur::Must, Box::new(BooleanQuery::new(any))));
+    } else {
+        clauses.extend(filters.into_iter().map(|filter| (Occur::Must, filter)));
+    }
+
+    Ok(if clauses.is_empty() {
+        Box::new(AllQuery)
+    } else {
+        Box::new(BooleanQuery::new(clauses))
+    })
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SortKey {
+    field: String,
+    descending: bool,
+}
+
+/// Parses `field`, `field:asc`, `field:desc` and `-field` sort entries
+pub fn parse_sort(sort: &[String]) -> Result<Vec<SortKey>, SearchError> {
+    sort.iter()
+        .map(|sort| {
+            let sort = sort.trim();
+            let (field, descending) = if let Some(field) = sort.strip_prefix('-') {
+                (field, true)
+            } else {
+                match sort.rsplit_once(':') {
+                    Some((field, order)) if order.eq_ignore_ascii_case("asc") => (field, false),
+                    Some((field, order)) if order.eq_ignore_ascii_case("desc") => (field, true),
+                    Some(_) => return Err(invalid_query(format!("Invalid sort `{sort}`"))),
+                    None => (sort, false),
+                }
+            };
+            if field.is_empty() {
+                return Err(invalid_query(format!("Invalid sort `{sort}`")));
+            }
+            Ok(SortKey {
+                field: field.to_string(),
+                descending,
+            })
+        })
+        .collect()
+}
+
+fn compare_values(a: &JsonValue, b: &JsonValue) -> Ordering {
+    match (a, b) {
+        (JsonValue::Number(a), JsonValue::Number(b)) => a
+            .as_f64()
+            .partial_cmp(&b.as_f64())
+            .unwrap_or(Ordering::Equal),
+        (JsonValue::String(a), JsonValue::String(b)) => a.cmp(b),
+        (JsonValue::Bool(a), JsonValue::Bool(b)) => a.cmp(b),
+        _ => Ordering::Equal,
+    }
+}
+
+/// Compares two documents by the sort keys, documents without a value sorting last in both orders
+pub fn compare_sources(a: &Source, b: &Source, sort: &[SortKey]) -> Ordering {
+    sort.iter()
+        .map(|key| {
+            let a = field_values(a, &key.field).first().copied();
+            let b = field_values(b, &key.field).first().copied();
+            match (a, b) {
+                (Some(a), Some(b)) if key.descending => compare_values(b, a),
+                (Some(a), Some(b)) => compare_values(a, b),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => Ordering::Equal,
+            }
+        })
+        .find(|ordering| ordering.is_ne())
+        .unwrap_or(Ordering::Equal)
+}
+
+/// Facet counts in the same shape as the other providers: `{"field": {"value": count}}`, with the
+/// most frequent values of each facet
+pub fn count_facets<'a>(
+    sources: impl Iterator<Item = &'a Source> + Clone,
+    facets: &[String],
+) -> Option<String> {
+    let counts = facets
+        .iter()
+        .map(|facet| {
+            let mut counts = HashMap::<String, u64>::new();
+            for source in sources.clone() {
+                for value in field_values(source, facet) {
+                    let value = match value {
+                        JsonValue::String(text) => text.clone(),
+                        value => value.to_string(),
+                    };
+                    *counts.entry(value).or_default() += 1;
+                }
+            }
+            let mut counts = counts.into_iter().collect::<Vec<_>>();
+            counts.sort_by(|(a, a_count), (b, b_count)| b_count.cmp(a_count).then(a.cmp(b)));
+            let counts = counts
+                .into_iter()
+                .take(MAX_FACET_VALUES)
+                .map(|(value, count)| (value, json!(count)))
+                .collect::<JsonMap<_, _>>();
+            (facet.clone(), JsonValue::Object(counts))
+        })
+        .collect::<JsonMap<_, _>>();
+    (!counts.is_empty()).then(|| JsonValue::Object(counts).to_string())
+}
+
+/// Wraps the highlighted ranges of a snippet's fragment with the tags
+pub fn snippet_to_html(
+    fragment: &str,
+    highlighted: &[Range<usize>],
+    pre_tag: &str,
+    post_tag: &str,
+) -> String {
+    let mut html = String::new();
+    let mut start = 0;
+    for range in highlighted {
+        html.push_str(&fragment[start..range.start]);
+        html.push_str(pre_tag);
+        html.push_str(&fragment[range.clone()]);
+        html.push_str(post_tag);
+        start = range.end;
+    }
+    html.push_str(&fragment[start..]);
+    html
+}
+
+pub fn hit_content(source: &Source, query: &SearchQuery) -> String {
+    let attributes = query
+        .config
+        .as_ref()
+        .map(|config| config.attributes_to_retrieve.as_slice())
+        .unwrap_or_default();
+    if attributes.is_empty() {
+        JsonValue::Object(source.clone()).to_string()
+    } else {
+        let fields = source
+            .iter()
+            .filter(|(key, _)| attributes.contains(key))
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect::<JsonMap<_, _>>();
+        JsonValue::Object(fields).to_string()
+    }
+}
+
+/// Number of hits to skip and number of hits per page of a query
+pub fn query_window(query: &SearchQuery) -> Result<(u32, u32), SearchError> {
+    let per_page = query.per_page.unwrap_or(DEFAULT_PER_PAGE);
+    let offset = query.offset.unwrap_or_else(|| {
+        query
+            .page
+            .unwrap_or(1)
+            .saturating_sub(1)
+            .saturating_mul(per_page)
+    });
+    if offset.saturating_add(per_page) > get_max_pagination_depth_config() {
+        return Err(invalid_query(format!(
+            "Requested hits beyond the maximum pagination depth of {}",
+            get_max_pagination_depth_config()
+        )));
+    }
+    Ok((offset, per_page))
+}
+
+pub fn create_retry_query(original_query: &SearchQuery, partial_hits: &[SearchHit]) -> SearchQuery {
+    let mut retry_query = original_query.clone();
+
+    let current_offset = original_query.offset.unwrap_or(0);
+    let per_page = original_query.per_page.unwrap_or(DEFAULT_PER_PAGE);
+    let hits_received = partial_hits.len() as u32;
+
+    retry_query.offset = Some(current_offset + hits_received.min(per_page));
+
+    retry_query
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use golem_search::golem::search::types::SchemaField;
+
+    fn query(q: Option<&str>, filters: &[&str]) -> SearchQuery {
+        SearchQuery {
+            q: q.map(str::to_string),
+            filters: filters.iter().map(|filter| filter.to_string()).collect(),
+            sort: vec![],
+            facets: vec![],
+            page: None,
+            per_page: None,
+            offset: None,
+            highlight: None,
+            config: None,
+        }
+    }
+
+    fn field(name: &str, field_type: FieldType) -> SchemaField {
+        SchemaField {
+            name: name.to_string(),
+            field_type,
+            required: false,
+            facet: false,
+            sort: false,
+            index: true,
+        }
+    }
+
+    fn source(json: JsonValue) -> Source {
+        match json {
+            JsonValue::Object(source) => source,
+            _ => panic!("Not an object"),
+        }
+    }
+
+    #[test]
+    fn test_build_fields() {
+        let schema = Schema {
+            fields: vec![
+                field("title", FieldType::Text),
+                field("genre", FieldType::Keyword),
+                field("year", FieldType::Integer),
+                field("location", FieldType::GeoPoint),
+            ],
+            primary_key: None,
+        };
+        let fields = build_fields(Some(&schema), "default");
+        assert_eq!(
+            fields
+                .text
+                .iter()
+                .map(|(name, _)| name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["title"]
+        );
+        assert!(fields.all.is_none());
+        assert!(fields.indexed.contains_key("year"));
+        assert!(!fields.indexed.contains_key("location"));
+
+        let fields = build_fields(None, "default");
+        assert!(fields.all.is_some());
+        assert_eq!(fields.text.len(), 1);
+
+        let invalid = Schema {
+            fields: vec![field("_all", FieldType::Text)],
+            primary_key: None,
+        };
+        assert!(validate_schema(&invalid).is_err());
+        assert!(validate_schema(&schema).is_ok());
+    }
+
+    #[test]
+    fn test_field_values() {
+        let doc = source(json!({
+            "title": "Dune",
+            "author": {"name": "Frank Herbert"},
+            "tags": ["sci-fi", "classic"],
+            "sequel": null
+        }));
+        assert_eq!(field_values(&doc, "title"), vec![&json!("Dune")]);
+        assert_eq!(
+            field_values(&doc, "author.name"),
+            vec![&json!("Frank Herbert")]
+        );
+        assert_eq!(field_values(&doc, "tags").len(), 2);
+        assert!(field_values(&doc, "sequel").is_empty());
+        assert!(field_values(&doc, "title.missing").is_empty());
+        assert_eq!(field_text(&doc, "author"), "Frank Herbert");
+    }
+
+    #[test]
+    fn test_parse_filter() {
+        assert_eq!(
+            parse_filter("genre:sci-fi").unwrap(),
+            Filter {
+                field: "genre".to_string(),
+                op: FilterOp::Eq,
+                value: "sci-fi".to_string(),
+            }
+        );
+        assert_eq!(
+            parse_filter("author.name = \"Frank Herbert\"").unwrap(),
+            Filter {
+                field: "author.name".to_string(),
+                op: FilterOp::Eq,
+                value: "Frank Herbert".to_string(),
+            }
+        );
+        assert_eq!(parse_filter("year>=1965").unwrap().op, FilterOp::Ge);
+        assert_eq!(parse_filter("year!=1965").unwrap().op, FilterOp::Ne);
+        assert_eq!(parse_filter("year<1965").unwrap().op, FilterOp::Lt);
+        for filter in ["genre", ":value", "genre:"] {
+            assert!(parse_filter(filter).is_err(), "{filter}");
+        }
+        assert_eq!(
+            bounds(FilterOp::Gt, 1),
+            (Bound::Excluded(1), Bound::Unbounded)
+        );
+        assert_eq!(
+            bounds(FilterOp::Le, 1),
+            (Bound::Unbounded, Bound::Included(1))
+        );
+    }
+
+    #[test]
+    fn test_filters_need_indexed_fields() {
+        let schema = Schema {
+            fields: vec![
+                field("genre", FieldType::Keyword),
+                field("year", FieldType::Integer),
+                field("available", FieldType::Boolean),
+            ],
+            primary_key: None,
+        };
+        let fields = build_fields(Some(&schema), "default");
+        let index = Index::create_in_ram(fields.schema.clone());
+
+        let search = query(None, &["genre:sci-fi", "year>1950", "available!=false"]);
+        assert!(search_query(&index, &fields, None, &search).is_ok());
+
+        for filter in ["title:dune", "year:recent", "available>true"] {
+            let search = query(None, &[filter]);
+            assert!(
+                search_query(&index, &fields, None, &search).is_err(),
+                "{filter}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_sort() {
+        let sort = parse_sort(&["-year".to_string(), "title:asc".to_string()]).unwrap();
+        assert_eq!(
+            sort,
+            vec![
+                SortKey {
+                    field: "year".to_string(),
+                    descending: true,
+                },
+                SortKey {
+                    field: "title".to_string(),
+                    descending: false,
+                },
+            ]
+        );
+        assert!(parse_sort(&["year:up".to_string()]).is_err());
+
+        let dune = source(json!({"title": "Dune", "year": 1965}));
+        let emma = source(json!({"title": "Emma", "year": 1815}));
+        let untitled = source(json!({"year": 1965}));
+        assert_eq!(compare_sources(&dune, &emma, &sort), Ordering::Less);
+        assert_eq!(compare_sources(&dune, &untitled, &sort), Ordering::Less);
+        assert_eq!(compare_sources(&untitled, &dune, &sort), Ordering::Greater);
+    }
+
+    #[test]
+    fn test_count_facets() {
+        let docs = [
+            source(json!({"genre": "sci-fi", "tags": ["classic"]})),
+            source(json!({"genre": "sci-fi"})),
+            source(json!({"genre": "romance", "tags": ["classic", "novel"]})),
+        ];
+        let facets = count_facets(docs.iter(), &["genre".to_string(), "tags".to_string()]);
+        assert_eq!(
+            facets.as_deref(),
+            Some(r#"{"genre":{"romance":1,"sci-fi":2},"tags":{"classic":2,"novel":1}}"#)
+        );
+        assert_eq!(count_facets(docs.iter(), &[]), None);
+    }
+
+    #[test]
+    fn test_snippet_to_html() {
+        assert_eq!(
+            snippet_to_html("the desert planet", &[4..10, 11..17], "<em>", "</em>"),
+            "the <em>desert</em> <em>planet</em>"
+        );
+        assert_eq!(snippet_to_html("dune", &[], "<em>", "</em>"), "dune");
+    }
+
+    #[test]
+    fn test_hit_content() {
+        let doc = source(json!({"title": "Dune", "year": 1965}));
+        let mut search = query(None, &[]);
+        assert_eq!(
+            hit_content(&doc, &search),
+            r#"{"title":"Dune","year":1965}"#
+        );
+
+        search.config = Some(golem_search::golem::search::types::SearchConfig {
+            timeout_ms: None,
+            boost_fields: vec![],
+            attributes_to_retrieve: vec!["title".to_string()],
+            language: None,
+            typo_tolerance: None,
+            exact_match_boost: None,
+            provider_params: None,
+            profile: None,
+            filter_mode: None,
+            post_filters: vec![],
+            rescore: None,
+            fallbacks: vec![],
+        });
+        assert_eq!(hit_content(&doc, &search), r#"{"title":"Dune"}"#);
+    }
+
+    #[test]
+    fn test_create_retry_query() {
+        let mut original = query(Some("dune"), &[]);
+        original.offset = Some(10);
+        original.per_page = Some(5);
+        let hits = (0..7)
+            .map(|i| SearchHit {
+                id: i.to_string(),
+                score: None,
+                content: None,
+                highlights: None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(create_retry_query(&original, &hits).offset, Some(15));
+    }
+}
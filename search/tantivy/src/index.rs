@@ -0,0 +1,302 @@
+use crate::conversions::{
+    build_fields, compare_sources, count_facets, field_text, hit_content, parse_sort, query_window,
+    search_query, snippet_to_html, text_query, to_tantivy_doc, Fields, Source, DEFAULT_POST_TAG,
+    DEFAULT_PRE_TAG,
+};
+use golem_search::error::{index_not_found, internal_error};
+use golem_search::golem::search::types::{Schema, SearchError, SearchHit, SearchQuery};
+use log::trace;
+use serde_json::Value as JsonValue;
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use tantivy::collector::{Count, TopDocs};
+use tantivy::query::Query;
+use tantivy::snippet::SnippetGenerator;
+use tantivy::{
+    Index, IndexReader, IndexSettings, ReloadPolicy, Searcher, SingleSegmentIndexWriter,
+    TantivyError,
+};
+
+/// Memory used to build the segment of an index, the minimum Tantivy accepts
+const WRITER_MEMORY_BUDGET: usize = 15_000_000;
+
+/// An index of the worker: its schema and documents, which are the source of truth, and the
+/// Tantivy index built from them for searching
+pub struct LocalIndex {
+    pub schema: Option<Schema>,
+    pub docs: BTreeMap<String, Source>,
+    /// Built on the first search after the documents or the schema changed
+    built: Option<BuiltIndex>,
+}
+
+struct BuiltIndex {
+    index: Index,
+    searcher: Searcher,
+    fields: Fields,
+    /// The ids of the documents by their Tantivy document id
+    ids: Vec<String>,
+}
+
+/// A page of hits, with the number of matches and the facet counts of all of them
+pub struct SearchPage {
+    pub hits: Vec<SearchHit>,
+    pub total: u32,
+    pub facets: Option<String>,
+}
+
+thread_local! {
+    /// The indexes of the worker, by name
+    static INDEXES: RefCell<BTreeMap<String, LocalIndex>> = const { RefCell::new(BTreeMap::new()) };
+}
+
+/// Runs `f` with the indexes of the worker
+pub fn with_indexes<T>(f: impl FnOnce(&mut BTreeMap<String, LocalIndex>) -> T) -> T {
+    INDEXES.with_borrow_mut(f)
+}
+
+/// Runs `f` with an index of the worker, failing with `index-not-found` if it doesn't exist
+pub fn with_index<T>(
+    name: &str,
+    f: impl FnOnce(&mut LocalIndex) -> Result<T, SearchError>,
+) -> Result<T, SearchError> {
+    with_indexes(|indexes| f(indexes.get_mut(name).ok_or_else(index_not_found)?))
+}
+
+fn tantivy_error(error: TantivyError) -> SearchError {
+    internal_error(format!("Tantivy error: {error}"))
+}
+
+impl LocalIndex {
+    pub fn new(schema: Option<Schema>) -> Self {
+        Self {
+            schema,
+            docs: BTreeMap::new(),
+            built: None,
+        }
+    }
+
+    pub fn upsert(&mut self, docs: Vec<(String, Source)>) {
+        self.docs.extend(docs);
+        self.built = None;
+    }
+
+    pub fn delete(&mut self, ids: &[String]) {
+        for id in ids {
+            self.docs.remove(id);
+        }
+        self.built = None;
+    }
+
+    pub fn set_schema(&mut self, schema: Schema) {
+        self.schema = Some(schema);
+        self.built = None;
+    }
+
+    /// Builds the Tantivy index of the documents in memory, as a single segment written without
+    /// the indexing threads of Tantivy, which a worker can't start
+    fn build(
+        schema: Option<&Schema>,
+        docs: &BTreeMap<String, Source>,
+        tokenizer: &str,
+    ) -> Result<BuiltIndex, SearchError> {
+        trace!("Building the Tantivy index of {} documents", docs.len());
+
+        let fields = build_fields(schema, tokenizer);
+        let settings = IndexSettings {
+            docstore_compress_dedicated_thread: false,
+            ..IndexSettings::default()
+        };
+        let index = Index::builder()
+            .schema(fields.schema.clone())
+            .settings(settings)
+            .create_in_ram()
+            .map_err(tantivy_error)?;
+
+        if !docs.is_empty() {
+            let mut writer = SingleSegmentIndexWriter::new(index.clone(), WRITER_MEMORY_BUDGET)
+                .map_err(tantivy_error)?;
+            for source in docs.values() {
+                writer
+                    .add_document(to_tantivy_doc(&fields, source))
+                    .map_err(tantivy_error)?;
+            }
+            writer.finalize().map_err(tantivy_error)?;
+        }
+
+        let reader: IndexReader = index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .try_into()
+            .map_err(tantivy_error)?;
+        Ok(BuiltIndex {
+            index,
+            searcher: reader.searcher(),
+            fields,
+            ids: docs.keys().cloned().collect(),
+        })
+    }
+
+    /// Searches the documents: the matches are ranked by Tantivy, then sorted by the sort fields
+    /// of the query, and the page of the query is taken from them
+    pub fn search(
+        &mut self,
+        query: &SearchQuery,
+        tokenizer: &str,
+    ) -> Result<SearchPage, SearchError> {
+        let (offset, per_page) = query_window(query)?;
+        let sort = parse_sort(&query.sort)?;
+        if self.built.is_none() {
+            self.built = Some(Self::build(self.schema.as_ref(), &self.docs, tokenizer)?);
+        }
+        let built = self.built.as_ref().expect("The index was just built");
+
+        let text_query = text_query(&built.index, &built.fields, query)?;
+        let search_query = search_query(&built.index, &built.fields, text_query.as_deref(), query)?;
+
+        let total = built
+            .searcher
+            .search(&*search_query, &Count)
+            .map_err(tantivy_error)?;
+        let mut matches = if total == 0 {
+            vec![]
+        } else {
+            built
+                .searcher
+                .search(&*search_query, &TopDocs::with_limit(total))
+                .map_err(tantivy_error)?
+                .into_iter()
+                .filter_map(|(score, address)| {
+                    let id = built.ids.get(address.doc_id as usize)?;
+                    let score = text_query.as_ref().map(|_| score as f64);
+                    Some((id.as_str(), score, self.docs.get(id)?))
+                })
+                .collect::<Vec<_>>()
+        };
+        matches.sort_by(|(a_id, a_score, a), (b_id, b_score, b)| {
+            compare_sources(a, b, &sort)
+                .then(
+                    b_score
+                        .partial_cmp(a_score)
+                        .unwrap_or(std::cmp::Ordering::Equal),
+                )
+                .then(a_id.cmp(b_id))
+        });
+
+        let facets = count_facets(matches.iter().map(|(_, _, source)| *source), &query.facets);
+        let highlighter = match &text_query {
+            Some(text_query) => Highlighter::new(built, &**text_query, query)?,
+            None => None,
+        };
+        let hits = matches
+            .iter()
+            .skip(offset as usize)
+            .take(per_page as usize)
+            .map(|(id, score, source)| SearchHit {
+                id: id.to_string(),
+                score: *score,
+                content: Some(hit_content(source, query)),
+                highlights: highlighter
+                    .as_ref()
+                    .and_then(|highlighter| highlighter.highlights(source)),
+            })
+            .collect();
+
+        Ok(SearchPage {
+            hits,
+            total: matches.len() as u32,
+            facets,
+        })
+    }
+}
+
+/// Highlights the matches of the text query in the full-text fields of the hits
+struct Highlighter {
+    /// The snippet generators of the highlighted fields, or of `_all` for the indexes without text
+    /// fields, where the top-level strings of the documents are highlighted
+    generators: Vec<(String, SnippetGenerator)>,
+    all: bool,
+    fields: Vec<String>,
+    pre_tag: String,
+    post_tag: String,
+}
+
+impl Highlighter {
+    fn new(
+        built: &BuiltIndex,
+        text_query: &dyn Query,
+        query: &SearchQuery,
+    ) -> Result<Option<Self>, SearchError> {
+        let Some(highlight) = &query.highlight else {
+            return Ok(None);
+        };
+
+        let all = built.fields.all.is_some();
+        let generators = built
+            .fields
+            .text
+            .iter()
+            .filter(|(name, _)| {
+                all || highlight.fields.is_empty() || highlight.fields.contains(name)
+            })
+            .map(|(name, field)| {
+                let mut generator = SnippetGenerator::create(&built.searcher, text_query, *field)
+                    .map_err(tantivy_error)?;
+                if let Some(max_length) = highlight.max_length {
+                    generator.set_max_num_chars(max_length as usize);
+                }
+                Ok((name.clone(), generator))
+            })
+            .collect::<Result<Vec<_>, SearchError>>()?;
+
+        Ok(Some(Self {
+            generators,
+            all,
+            fields: highlight.fields.clone(),
+            pre_tag: highlight
+                .pre_tag
+                .clone()
+                .unwrap_or_else(|| DEFAULT_PRE_TAG.to_string()),
+            post_tag: highlight
+                .post_tag
+                .clone()
+                .unwrap_or_else(|| DEFAULT_POST_TAG.to_string()),
+        }))
+    }
+
+    /// Highlights in the same shape as the other providers: `{"field": ["snippet"]}`, keeping
+    /// the fields with a match
+    fn highlights(&self, source: &Source) -> Option<String> {
+        let texts: Vec<(String, String, &SnippetGenerator)> = if self.all {
+            let (_, generator) = self.generators.first()?;
+            source
+                .iter()
+                .filter(|(name, value)| {
+                    value.is_string() && (self.fields.is_empty() || self.fields.contains(name))
+                })
+                .map(|(name, _)| (name.clone(), field_text(source, name), generator))
+                .collect()
+        } else {
+            self.generators
+                .iter()
+                .map(|(name, generator)| (name.clone(), field_text(source, name), generator))
+                .collect()
+        };
+
+        let snippets = texts
+            .into_iter()
+            .filter_map(|(name, text, generator)| {
+                let snippet = generator.snippet(&text);
+                (!snippet.highlighted().is_empty()).then(|| {
+                    let html = snippet_to_html(
+                        snippet.fragment(),
+                        snippet.highlighted(),
+                        &self.pre_tag,
+                        &self.post_tag,
+                    );
+                    (name, JsonValue::from(vec![html]))
+                })
+            })
+            .collect::<serde_json::Map<_, _>>();
+        (!snippets.is_empty()).then(|| JsonValue::Object(snippets).to_string())
+    }
+}
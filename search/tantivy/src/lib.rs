@@ -0,0 +1,242 @@
+use crate::conversions::{create_retry_query, doc_to_source, query_window, validate_schema};
+use crate::index::{with_index, with_indexes, LocalIndex};
+use golem_rust::wasm_rpc::Pollable;
+use golem_search::config::get_config_with_default;
+use golem_search::durability::{DurableSearch, ExtendedGuest};
+use golem_search::error::invalid_query;
+use golem_search::golem::search::core::{Guest, GuestSearchStream, SearchStream};
+use golem_search::golem::search::types::{
+    Doc, DocumentId, IndexName, IndexPage, PageRequest, Schema, SearchError, SearchHit,
+    SearchQuery, SearchResults,
+};
+use golem_search::pagination::index_page;
+use golem_search::LOGGING_STATE;
+use serde_json::Value as JsonValue;
+use std::cell::Cell;
+
+mod conversions;
+mod index;
+
+/// Search stream over the matches of a query, paged through with offsets
+struct TantivySearchStream {
+    index_name: String,
+    query: SearchQuery,
+    offset: Cell<u32>,
+    finished: Cell<bool>,
+}
+
+impl TantivySearchStream {
+    pub fn new(index_name: String, query: SearchQuery) -> Self {
+        let offset = query_window(&query).map(|(offset, _)| offset).unwrap_or(0);
+        Self {
+            index_name,
+            query,
+            offset: Cell::new(offset),
+            finished: Cell::new(false),
+        }
+    }
+
+    pub fn subscribe(&self) -> Pollable {
+        golem_rust::bindings::wasi::clocks::monotonic_clock::subscribe_duration(0)
+    }
+
+    /// Reads the next page of hits, and whether there may be more
+    fn next_page(&self, per_page: u32) -> Result<(Vec<SearchHit>, bool), SearchError> {
+        let mut query = self.query.clone();
+        query.page = None;
+        query.offset = Some(self.offset.get());
+        query.facets = vec![];
+
+        let page = with_index(&self.index_name, |index| {
+            index.search(&query, &TantivyComponent::tokenizer())
+        })?;
+        let more = page.hits.len() as u32 >= per_page;
+        Ok((page.hits, more))
+    }
+}
+
+impl GuestSearchStream for TantivySearchStream {
+    fn get_next(&self) -> Option<Vec<SearchHit>> {
+        if self.finished.get() {
+            return Some(vec![]);
+        }
+
+        let per_page = query_window(&self.query)
+            .map(|(_, per_page)| per_page)
+            .unwrap_or(0);
+
+        match self.next_page(per_page) {
+            Ok((hits, more)) => {
+                if !more || hits.is_empty() {
+                    self.finished.set(true);
+                }
+                self.offset.set(self.offset.get() + hits.len() as u32);
+                Some(hits)
+            }
+            Err(_) => {
+                self.finished.set(true);
+                Some(vec![])
+            }
+        }
+    }
+
+    fn blocking_get_next(&self) -> Vec<SearchHit> {
+        self.get_next().unwrap_or_default()
+    }
+}
+
+struct TantivyComponent;
+
+impl TantivyComponent {
+    const TOKENIZER_ENV_VAR: &'static str = "TANTIVY_TOKENIZER";
+
+    /// The Tantivy tokenizer of `TANTIVY_TOKENIZER` for the text fields, such as `en_stem` to
+    /// stem English words, `default` by default
+    fn tokenizer() -> String {
+        get_config_with_default(Self::TOKENIZER_ENV_VAR, "default")
+    }
+}
+
+impl Guest for TantivyComponent {
+    type SearchStream = TantivySearchStream;
+
+    fn create_index(name: IndexName, schema: Option<Schema>) -> Result<(), SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        if let Some(schema) = &schema {
+            validate_schema(schema)?;
+        }
+        with_indexes(|indexes| {
+            if indexes.contains_key(&name) {
+                return Err(invalid_query(format!("Index `{name}` already exists")));
+            }
+            indexes.insert(name, LocalIndex::new(schema));
+            Ok(())
+        })
+    }
+
+    fn delete_index(name: IndexName) -> Result<(), SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        with_indexes(|indexes| match indexes.remove(&name) {
+            Some(_) => Ok(()),
+            None => Err(SearchError::IndexNotFound),
+        })
+    }
+
+    fn list_indexes(page: PageRequest) -> Result<IndexPage, SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let names = with_indexes(|indexes| indexes.keys().cloned().collect());
+        index_page(names, &page)
+    }
+
+    fn upsert(index: IndexName, doc: Doc) -> Result<(), SearchError> {
+        Self::upsert_many(index, vec![doc])
+    }
+
+    fn upsert_many(index: IndexName, docs: Vec<Doc>) -> Result<(), SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let docs = docs
+            .iter()
+            .map(|doc| Ok((doc.id.clone(), doc_to_source(doc)?)))
+            .collect::<Result<Vec<_>, SearchError>>()?;
+        with_index(&index, |index| {
+            index.upsert(docs);
+            Ok(())
+        })
+    }
+
+    fn delete(index: IndexName, id: DocumentId) -> Result<(), SearchError> {
+        Self::delete_many(index, vec![id])
+    }
+
+    fn delete_many(index: IndexName, ids: Vec<DocumentId>) -> Result<(), SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        with_index(&index, |index| {
+            index.delete(&ids);
+            Ok(())
+        })
+    }
+
+    fn get(index: IndexName, id: DocumentId) -> Result<Option<Doc>, SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        with_index(&index, |index| {
+            Ok(index.docs.get(&id).map(|source| Doc {
+                id,
+                content: JsonValue::Object(source.clone()).to_string(),
+            }))
+        })
+    }
+
+    fn search(index: IndexName, query: SearchQuery) -> Result<SearchResults, SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let (offset, per_page) = query_window(&query)?;
+        let page = with_index(&index, |index| index.search(&query, &Self::tokenizer()))?;
+
+        Ok(SearchResults {
+            total: Some(page.total),
+            page: Some(offset / per_page.max(1) + 1),
+            per_page: Some(per_page),
+            hits: page.hits,
+            facets: page.facets,
+            took_ms: None,
+            relaxation: None,
+            navigation: None,
+        })
+    }
+
+    fn stream_search(index: IndexName, query: SearchQuery) -> Result<SearchStream, SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        Ok(SearchStream::new(TantivySearchStream::new(index, query)))
+    }
+
+    /// Returns the schema of the index, or an empty schema for indexes created without one
+    fn get_schema(index: IndexName) -> Result<Schema, SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        with_index(&index, |index| {
+            Ok(index.schema.clone().unwrap_or(Schema {
+                fields: vec![],
+                primary_key: Some("id".to_string()),
+            }))
+        })
+    }
+
+    /// Replaces the schema of the index. The Tantivy index of its documents is rebuilt with the
+    /// new fields on the next search.
+    fn update_schema(index: IndexName, schema: Schema) -> Result<(), SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        validate_schema(&schema)?;
+        with_index(&index, |index| {
+            index.set_schema(schema);
+            Ok(())
+        })
+    }
+}
+
+impl ExtendedGuest for TantivyComponent {
+    fn unwrapped_stream(index: IndexName, query: SearchQuery) -> Self::SearchStream {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        TantivySearchStream::new(index, query)
+    }
+
+    fn retry_query(original_query: &SearchQuery, partial_hits: &[SearchHit]) -> SearchQuery {
+        create_retry_query(original_query, partial_hits)
+    }
+
+    fn subscribe(stream: &Self::SearchStream) -> Pollable {
+        stream.subscribe()
+    }
+}
+
+type DurableTantivyComponent = DurableSearch<TantivyComponent>;
+
+golem_search::export_search!(DurableTantivyComponent with_types_in golem_search);
@@ -0,0 +1,91 @@
+//! Canned responses of the providers' APIs, following their documented formats.
+
+/// Responses of the Algolia Search API
+pub mod algolia {
+    use serde_json::{json, Value};
+
+    pub fn search(hits: Vec<Value>) -> Value {
+        let nb_hits = hits.len();
+        json!({
+            "hits": hits,
+            "page": 0,
+            "nbHits": nb_hits,
+            "nbPages": 1,
+            "hitsPerPage": 20,
+            "processingTimeMS": 1,
+            "query": "",
+            "params": ""
+        })
+    }
+
+    pub fn hit(object_id: &str, content: Value) -> Value {
+        let mut hit = json!({ "objectID": object_id });
+        if let (Some(hit), Value::Object(content)) = (hit.as_object_mut(), content) {
+            hit.extend(content);
+        }
+        hit
+    }
+
+    pub fn list_indexes() -> Value {
+        json!({
+            "items": [
+                {
+                    "name": "products",
+                    "createdAt": "2024-01-01T00:00:00.000Z",
+                    "updatedAt": "2024-01-01T00:00:00.000Z",
+                    "entries": 2,
+                    "dataSize": 100,
+                    "fileSize": 200,
+                    "lastBuildTimeS": 0,
+                    "numberOfPendingTasks": 0,
+                    "pendingTask": false
+                }
+            ],
+            "nbPages": 1
+        })
+    }
+
+    pub fn task(task_id: u64) -> Value {
+        json!({
+            "taskID": task_id,
+            "updatedAt": "2024-01-01T00:00:00.000Z"
+        })
+    }
+
+    pub fn error(status: u16, message: &str) -> Value {
+        json!({ "message": message, "status": status })
+    }
+}
+
+/// Responses of the Elasticsearch and OpenSearch REST APIs
+pub mod elasticsearch {
+    use serde_json::{json, Value};
+
+    pub fn search(hits: Vec<(&str, Value)>) -> Value {
+        let total = hits.len();
+        json!({
+            "took": 1,
+            "timed_out": false,
+            "hits": {
+                "total": { "value": total, "relation": "eq" },
+                "max_score": 1.0,
+                "hits": hits
+                    .into_iter()
+                    .map(|(id, source)| json!({
+                        "_index": "products",
+                        "_id": id,
+                        "_score": 1.0,
+                        "_source": source
+                    }))
+                    .collect::<Vec<_>>()
+            }
+        })
+    }
+
+    pub fn error(status: u16, error_type: &str, reason: &str) -> Value {
+        json!({
+            "error": { "type": error_type, "reason": reason },
+            "status": status
+        })
+    }
+}
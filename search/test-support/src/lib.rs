@@ -0,0 +1,282 @@
+//! Test support for the native tests of the search providers.
+//!
+//! `MockServer` is a local HTTP server answering with canned responses, so the provider clients
+//! can be exercised end to end without credentials and without touching real accounts. Point the
+//! client at `MockServer::url`, register the responses of the routes the test calls, and inspect
+//! the recorded requests afterwards.
+
+pub mod canned;
+
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+/// A canned HTTP response
+#[derive(Debug, Clone)]
+pub struct MockResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl MockResponse {
+    pub fn json(status: u16, body: serde_json::Value) -> Self {
+        Self {
+            status,
+            headers: vec![("Content-Type".to_string(), "application/json".to_string())],
+            body: body.to_string(),
+        }
+    }
+
+    pub fn text(status: u16, body: impl Into<String>) -> Self {
+        Self {
+            status,
+            headers: vec![("Content-Type".to_string(), "text/plain".to_string())],
+            body: body.into(),
+        }
+    }
+
+    pub fn with_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+}
+
+/// A request received by the mock server
+#[derive(Debug, Clone)]
+pub struct RecordedRequest {
+    pub method: String,
+    /// The path of the request, without the query string
+    pub path: String,
+    pub query: Option<String>,
+    /// Headers with lowercase names
+    pub headers: HashMap<String, String>,
+    pub body: String,
+}
+
+impl RecordedRequest {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .get(&name.to_ascii_lowercase())
+            .map(String::as_str)
+    }
+
+    pub fn json(&self) -> serde_json::Value {
+        serde_json::from_str(&self.body).expect("request body is not JSON")
+    }
+}
+
+#[derive(Default)]
+struct State {
+    /// Responses per method and path, served in order, the last one repeating
+    routes: HashMap<(String, String), Vec<MockResponse>>,
+    requests: Vec<RecordedRequest>,
+}
+
+/// A local HTTP/1.1 server answering with canned responses
+pub struct MockServer {
+    url: String,
+    state: Arc<Mutex<State>>,
+}
+
+impl MockServer {
+    /// Starts the server on a random local port. It is stopped when the test process exits.
+    pub fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind mock server");
+        let url = format!(
+            "http://{}",
+            listener.local_addr().expect("Mock server has no address")
+        );
+        let state = Arc::new(Mutex::new(State::default()));
+
+        let server_state = state.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let state = server_state.clone();
+                thread::spawn(move || handle(stream, &state));
+            }
+        });
+
+        Self { url, state }
+    }
+
+    /// The base URL of the server, such as `http://127.0.0.1:12345`
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Answers the requests to `path` with `response`. Registering several responses for the same
+    /// route serves them in order, repeating the last one, which allows testing retries.
+    pub fn mock(&self, method: &str, path: &str, response: MockResponse) -> &Self {
+        self.state
+            .lock()
+            .unwrap()
+            .routes
+            .entry((method.to_ascii_uppercase(), path.to_string()))
+            .or_default()
+            .push(response);
+        self
+    }
+
+    /// The requests received so far, in order
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.state.lock().unwrap().requests.clone()
+    }
+}
+
+fn handle(stream: TcpStream, state: &Mutex<State>) {
+    let mut reader = BufReader::new(match stream.try_clone() {
+        Ok(stream) => stream,
+        Err(_) => return,
+    });
+    let Some(request) = read_request(&mut reader) else {
+        return;
+    };
+
+    let response = {
+        let mut state = state.lock().unwrap();
+        state.requests.push(request.clone());
+        match state
+            .routes
+            .get_mut(&(request.method.clone(), request.path.clone()))
+        {
+            Some(responses) if responses.len() > 1 => responses.remove(0),
+            Some(responses) => responses[0].clone(),
+            None => MockResponse::json(
+                404,
+                serde_json::json!({
+                    "message": format!("No canned response for {} {}", request.method, request.path)
+                }),
+            ),
+        }
+    };
+
+    let _ = write_response(stream, &response);
+}
+
+fn read_request(reader: &mut impl BufRead) -> Option<RecordedRequest> {
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line).ok()?;
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next()?.to_string();
+    let target = parts.next()?;
+    let (path, query) = match target.split_once('?') {
+        Some((path, query)) => (path.to_string(), Some(query.to_string())),
+        None => (target.to_string(), None),
+    };
+
+    let mut headers = HashMap::new();
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line).ok()?;
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            headers.insert(name.trim().to_ascii_lowercase(), value.trim().to_string());
+        }
+    }
+
+    let content_length = headers
+        .get("content-length")
+        .and_then(|length| length.parse().ok())
+        .unwrap_or(0);
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body).ok()?;
+
+    Some(RecordedRequest {
+        method,
+        path,
+        query,
+        headers,
+        body: String::from_utf8_lossy(&body).to_string(),
+    })
+}
+
+fn write_response(mut stream: TcpStream, response: &MockResponse) -> std::io::Result<()> {
+    let mut head = format!(
+        "HTTP/1.1 {} {}\r\n",
+        response.status,
+        reason(response.status)
+    );
+    for (name, value) in &response.headers {
+        head.push_str(&format!("{name}: {value}\r\n"));
+    }
+    head.push_str(&format!(
+        "Content-Length: {}\r\nConnection: close\r\n\r\n",
+        response.body.len()
+    ));
+    stream.write_all(head.as_bytes())?;
+    stream.write_all(response.body.as_bytes())?;
+    stream.flush()
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        201 => "Created",
+        204 => "No Content",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        429 => "Too Many Requests",
+        500 => "Internal Server Error",
+        503 => "Service Unavailable",
+        _ => "Unknown",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn send(server: &MockServer, request: &str) -> String {
+        let mut stream = TcpStream::connect(server.url().trim_start_matches("http://")).unwrap();
+        stream.write_all(request.as_bytes()).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    }
+
+    #[test]
+    fn serves_canned_responses_in_order() {
+        let server = MockServer::start();
+        server
+            .mock("GET", "/1/indexes", MockResponse::text(503, "unavailable"))
+            .mock(
+                "GET",
+                "/1/indexes",
+                MockResponse::json(200, canned::algolia::list_indexes()),
+            );
+
+        let request = "GET /1/indexes?page=0 HTTP/1.1\r\nX-Request-Id: abc\r\n\r\n";
+        assert!(send(&server, request).starts_with("HTTP/1.1 503"));
+        assert!(send(&server, request).starts_with("HTTP/1.1 200"));
+        assert!(send(&server, request).starts_with("HTTP/1.1 200"));
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 3);
+        assert_eq!(requests[0].path, "/1/indexes");
+        assert_eq!(requests[0].query.as_deref(), Some("page=0"));
+        assert_eq!(requests[0].header("x-request-id"), Some("abc"));
+    }
+
+    #[test]
+    fn records_bodies_and_answers_unknown_routes_with_404() {
+        let server = MockServer::start();
+
+        let response = send(
+            &server,
+            "POST /1/indexes/products/query HTTP/1.1\r\nContent-Length: 13\r\n\r\n{\"query\":\"a\"}",
+        );
+        assert!(response.starts_with("HTTP/1.1 404"));
+        assert_eq!(
+            server.requests()[0].json(),
+            serde_json::json!({ "query": "a" })
+        );
+    }
+}
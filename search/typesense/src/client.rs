@@ -1,6 +1,9 @@
-use golem_search::error::{from_reqwest_error, internal_error, search_error_from_status};
+use golem_search::correlation::{self, CORRELATION_ID_HEADER};
+use golem_search::error::{from_reqwest_error, from_send_error, search_error_from_failed_response};
 use golem_search::golem::search::types::SearchError;
-use log::trace;
+use golem_search::logging::RedactedResponse;
+use golem_search::retry::RetryPolicy;
+use log::{trace, warn};
 use reqwest::{Client, Method, RequestBuilder, Response};
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
@@ -14,6 +17,7 @@ pub struct TypesenseSearchApi {
     client: Client,
     api_key: String,
     base_url: String,
+    retry_policy: RetryPolicy,
 }
 
 impl TypesenseSearchApi {
@@ -26,12 +30,14 @@ impl TypesenseSearchApi {
             api_key,
             client,
             base_url,
+            retry_policy: RetryPolicy::from_config(),
         }
     }
 
     fn create_request(&self, method: Method, url: &str) -> RequestBuilder {
         self.client
             .request(method, url)
+            .header(CORRELATION_ID_HEADER, correlation::current())
             .header("X-TYPESENSE-API-KEY", &self.api_key)
             .header("Content-Type", "application/json")
     }
@@ -49,7 +55,7 @@ impl TypesenseSearchApi {
             .create_request(Method::POST, &url)
             .json(schema)
             .send()
-            .map_err(|e| internal_error(format!("Failed to create collection: {e}")))?;
+            .map_err(|e| from_send_error("Failed to create collection", e))?;
 
         parse_response(response)
     }
@@ -63,9 +69,9 @@ impl TypesenseSearchApi {
         let url = format!("{}/collections/{}", self.base_url, collection_name);
 
         let response = self
-            .create_request(Method::DELETE, &url)
-            .send()
-            .map_err(|e| internal_error(format!("Failed to delete collection: {e}")))?;
+            .retry_policy
+            .execute(|| self.create_request(Method::DELETE, &url).send())
+            .map_err(|e| from_send_error("Failed to delete collection", e))?;
 
         parse_response(response)
     }
@@ -76,9 +82,9 @@ impl TypesenseSearchApi {
         let url = format!("{}/collections", self.base_url);
 
         let response = self
-            .create_request(Method::GET, &url)
-            .send()
-            .map_err(|e| internal_error(format!("Failed to list collections: {e}")))?;
+            .retry_policy
+            .execute(|| self.create_request(Method::GET, &url).send())
+            .map_err(|e| from_send_error("Failed to list collections", e))?;
 
         parse_response(response)
     }
@@ -99,7 +105,7 @@ impl TypesenseSearchApi {
             .create_request(Method::POST, &url)
             .json(document)
             .send()
-            .map_err(|e| internal_error(format!("HTTP request failed: {e}")))?;
+            .map_err(|e| from_send_error("HTTP request failed", e))?;
 
         parse_response(response)
     }
@@ -130,7 +136,7 @@ impl TypesenseSearchApi {
             .header("Content-Type", "text/plain")
             .body(ndjson)
             .send()
-            .map_err(|e| internal_error(format!("HTTP request failed: {e}")))?;
+            .map_err(|e| from_send_error("HTTP request failed", e))?;
 
         parse_bulk_import_response(response)
     }
@@ -148,10 +154,13 @@ impl TypesenseSearchApi {
         );
 
         let response = self
-            .create_request(Method::POST, &url)
-            .json(document)
-            .send()
-            .map_err(|e| internal_error(format!("HTTP request failed: {e}")))?;
+            .retry_policy
+            .execute(|| {
+                self.create_request(Method::POST, &url)
+                    .json(document)
+                    .send()
+            })
+            .map_err(|e| from_send_error("HTTP request failed", e))?;
 
         parse_response(response)
     }
@@ -169,9 +178,9 @@ impl TypesenseSearchApi {
         );
 
         let response = self
-            .create_request(Method::DELETE, &url)
-            .send()
-            .map_err(|e| internal_error(format!("HTTP request failed: {e}")))?;
+            .retry_policy
+            .execute(|| self.create_request(Method::DELETE, &url).send())
+            .map_err(|e| from_send_error("HTTP request failed", e))?;
 
         parse_response(response)
     }
@@ -189,9 +198,9 @@ impl TypesenseSearchApi {
         );
 
         let response = self
-            .create_request(Method::DELETE, &url)
-            .send()
-            .map_err(|e| internal_error(format!("HTTP request failed: {e}")))?;
+            .retry_policy
+            .execute(|| self.create_request(Method::DELETE, &url).send())
+            .map_err(|e| from_send_error("HTTP request failed", e))?;
 
         parse_response(response)
     }
@@ -216,9 +225,9 @@ impl TypesenseSearchApi {
         };
 
         let response = self
-            .create_request(Method::GET, &full_url)
-            .send()
-            .map_err(|e| internal_error(format!("HTTP request failed: {e}")))?;
+            .retry_policy
+            .execute(|| self.create_request(Method::GET, &full_url).send())
+            .map_err(|e| from_send_error("HTTP request failed", e))?;
 
         parse_response(response)
     }
@@ -259,10 +268,13 @@ impl TypesenseSearchApi {
         let url = format!("{}/multi_search", self.base_url);
 
         let response = self
-            .create_request(Method::POST, &url)
-            .json(searches)
-            .send()
-            .map_err(|e| internal_error(format!("HTTP request failed: {e}")))?;
+            .retry_policy
+            .execute(|| {
+                self.create_request(Method::POST, &url)
+                    .json(searches)
+                    .send()
+            })
+            .map_err(|e| from_send_error("HTTP request failed", e))?;
 
         parse_response(response)
     }
@@ -271,7 +283,10 @@ impl TypesenseSearchApi {
 fn parse_response<T: DeserializeOwned + Debug>(response: Response) -> Result<T, SearchError> {
     let status = response.status();
 
-    trace!("Received response from Typesense API: {response:?}");
+    trace!(
+        "Received response from Typesense API: {:?}",
+        RedactedResponse(&response)
+    );
 
     if status.is_success() {
         let body = response
@@ -282,13 +297,11 @@ fn parse_response<T: DeserializeOwned + Debug>(response: Response) -> Result<T,
 
         Ok(body)
     } else {
-        let error_body = response
-            .text()
-            .map_err(|err| from_reqwest_error("Failed to receive error response body", err))?;
+        let error = search_error_from_failed_response(response);
 
-        trace!("Received {status} response from Typesense API: {error_body:?}");
+        trace!("Received {status} response from Typesense API: {error:?}");
 
-        Err(search_error_from_status(status))
+        Err(error)
     }
 }
 
@@ -318,7 +331,7 @@ fn parse_bulk_import_response(response: Response) -> Result<IndexDocumentsRespon
                         }
                     }
                     Err(e) => {
-                        println!("[Typesense] Failed to parse NDJSON line: {e} | line: {line}");
+                        warn!("Failed to parse NDJSON line of import response: {e}");
                     }
                 }
             }
@@ -331,11 +344,11 @@ fn parse_bulk_import_response(response: Response) -> Result<IndexDocumentsRespon
 
         Ok(response)
     } else {
-        let _error_body = response
-            .text()
-            .map_err(|err| from_reqwest_error("Failed to receive error response body", err))?;
+        let error = search_error_from_failed_response(response);
+
+        trace!("Received {status} response from Typesense API: {error:?}");
 
-        Err(search_error_from_status(status))
+        Err(error)
     }
 }
 
@@ -2,6 +2,7 @@ use crate::client::{
     CollectionField, CollectionSchema, SearchHit as TypesenseSearchHit,
     SearchQuery as TypesenseSearchQuery, SearchResponse, TypesenseDocument,
 };
+use golem_search::config::search_filters_match_any;
 use golem_search::golem::search::types::{
     Doc, FieldType, Schema, SchemaField, SearchHit, SearchQuery, SearchResults,
 };
@@ -69,7 +70,16 @@ pub fn search_query_to_typesense_query(query: SearchQuery) -> TypesenseSearchQue
     };
 
     if !query.filters.is_empty() {
-        typesense_query.filter_by = Some(query.filters.join(" && "));
+        typesense_query.filter_by = Some(if search_filters_match_any(&query) {
+            query
+                .filters
+                .iter()
+                .map(|filter| format!("({filter})"))
+                .collect::<Vec<_>>()
+                .join(" || ")
+        } else {
+            query.filters.join(" && ")
+        });
     }
 
     if !query.sort.is_empty() {
@@ -257,6 +267,8 @@ pub fn typesense_response_to_search_results(response: SearchResponse) -> SearchR
         hits,
         facets,
         took_ms: Some(response.search_time_ms),
+        relaxation: None,
+        navigation: None,
     }
 }
 
@@ -533,6 +545,11 @@ mod tests {
                     r#"{"exhaustive_search": true, "use_cache": false, "max_facet_values": 100}"#
                         .to_string(),
                 ),
+                profile: None,
+                filter_mode: None,
+                post_filters: vec![],
+                rescore: None,
+                fallbacks: vec![],
             }),
         };
 
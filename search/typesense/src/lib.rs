@@ -1,15 +1,18 @@
 use crate::client::{CollectionField, CollectionSchema, TypesenseSearchApi};
 use crate::conversions::*;
 use golem_rust::wasm_rpc::Pollable;
-use golem_search::config::with_config_keys;
+use golem_search::config::{search_profile, with_profile_config_keys, worker_profile};
 use golem_search::durability::{DurableSearch, ExtendedGuest};
 use golem_search::golem::search::core::{Guest, GuestSearchStream, SearchStream};
 use golem_search::golem::search::types::{
-    Doc, DocumentId, IndexName, Schema, SearchError, SearchHit, SearchQuery, SearchResults,
+    Doc, DocumentId, IndexName, IndexPage, PageRequest, Schema, SearchError, SearchHit,
+    SearchQuery, SearchResults,
 };
+use golem_search::pagination::{index_page, search_navigation};
 use golem_search::LOGGING_STATE;
 use log::trace;
 use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
 
 mod client;
 mod conversions;
@@ -45,24 +48,53 @@ impl TypesenseSearchStream {
 
 struct TypesenseComponent;
 
+thread_local! {
+    /// The provider clients by configuration profile, reused across calls instead of being rebuilt
+    /// for each of them
+    static CLIENTS: RefCell<HashMap<Option<String>, TypesenseSearchApi>> = RefCell::new(HashMap::new());
+}
+
 impl TypesenseComponent {
     const API_KEY_ENV_VAR: &'static str = "TYPESENSE_API_KEY";
     const BASE_URL_ENV_VAR: &'static str = "TYPESENSE_BASE_URL";
 
     fn create_client() -> Result<TypesenseSearchApi, SearchError> {
-        with_config_keys(&[Self::API_KEY_ENV_VAR, Self::BASE_URL_ENV_VAR], |keys| {
-            if keys.len() != 2 {
-                return Err(SearchError::Internal(
-                    "Missing Typesense credentials".to_string(),
-                ));
-            }
+        Self::create_profile_client(worker_profile().as_deref())
+    }
 
-            let api_key = keys[0].clone();
-            let base_url = keys[1].clone();
+    /// Returns the client of `profile` cached for this worker, creating it from the configuration
+    /// on first use
+    fn create_profile_client(profile: Option<&str>) -> Result<TypesenseSearchApi, SearchError> {
+        CLIENTS.with_borrow_mut(|clients| {
+            let profile = profile.map(str::to_string);
+            if let Some(client) = clients.get(&profile) {
+                return Ok(client.clone());
+            }
 
-            Ok(TypesenseSearchApi::new(api_key, base_url))
+            let client = Self::new_client(profile.as_deref())?;
+            clients.insert(profile, client.clone());
+            Ok(client)
         })
     }
+
+    fn new_client(profile: Option<&str>) -> Result<TypesenseSearchApi, SearchError> {
+        with_profile_config_keys(
+            profile,
+            &[Self::API_KEY_ENV_VAR, Self::BASE_URL_ENV_VAR],
+            |keys| {
+                if keys.len() != 2 {
+                    return Err(SearchError::Internal(
+                        "Missing Typesense credentials".to_string(),
+                    ));
+                }
+
+                let api_key = keys[0].clone();
+                let base_url = keys[1].clone();
+
+                Ok(TypesenseSearchApi::new(api_key, base_url))
+            },
+        )
+    }
 }
 
 impl GuestSearchStream for TypesenseSearchStream {
@@ -75,25 +107,19 @@ impl GuestSearchStream for TypesenseSearchStream {
         let mut search_query = self.query.clone();
         search_query.page = Some(self.current_page.get());
 
-        let typesense_query = search_query_to_typesense_query(search_query);
+        let typesense_query = search_query_to_typesense_query(search_query.clone());
 
         match self.client.search(&self.index_name, &typesense_query) {
             Ok(response) => {
                 let search_results = typesense_response_to_search_results(response);
 
-                let current_page = self.current_page.get();
-                let per_page = self.query.per_page.unwrap_or(20);
-                let total_pages = if let Some(total) = search_results.total {
-                    total.div_ceil(per_page) // Ceiling division
-                } else {
-                    current_page + 1
-                };
-
-                if current_page >= total_pages || search_results.hits.is_empty() {
+                if search_navigation(&search_query, &search_results)
+                    .is_none_or(|navigation| !navigation.has_next)
+                {
                     self.finished.set(true);
                 }
 
-                self.current_page.set(current_page + 1);
+                self.current_page.set(self.current_page.get() + 1);
 
                 let hits = search_results.hits.clone();
                 *self.last_response.borrow_mut() = Some(search_results);
@@ -150,16 +176,19 @@ impl Guest for TypesenseComponent {
         Ok(())
     }
 
-    fn list_indexes() -> Result<Vec<IndexName>, SearchError> {
+    fn list_indexes(page: PageRequest) -> Result<IndexPage, SearchError> {
         LOGGING_STATE.with_borrow_mut(|state| state.init());
 
         let client = Self::create_client()?;
         let response = client.list_collections()?;
-        Ok(response
-            .0
-            .into_iter()
-            .map(|collection| collection.name)
-            .collect())
+        index_page(
+            response
+                .0
+                .into_iter()
+                .map(|collection| collection.name)
+                .collect(),
+            &page,
+        )
     }
 
     fn upsert(index: IndexName, doc: Doc) -> Result<(), SearchError> {
@@ -234,7 +263,7 @@ impl Guest for TypesenseComponent {
     fn search(index: IndexName, query: SearchQuery) -> Result<SearchResults, SearchError> {
         LOGGING_STATE.with_borrow_mut(|state| state.init());
 
-        let client = Self::create_client()?;
+        let client = Self::create_profile_client(search_profile(&query).as_deref())?;
         let typesense_query = search_query_to_typesense_query(query);
         let response = client.search(&index, &typesense_query)?;
         Ok(typesense_response_to_search_results(response))
@@ -243,7 +272,7 @@ impl Guest for TypesenseComponent {
     fn stream_search(index: IndexName, query: SearchQuery) -> Result<SearchStream, SearchError> {
         LOGGING_STATE.with_borrow_mut(|state| state.init());
 
-        let client = Self::create_client()?;
+        let client = Self::create_profile_client(search_profile(&query).as_deref())?;
 
         let stream = TypesenseSearchStream::new(client, index, query);
 
@@ -315,9 +344,10 @@ impl ExtendedGuest for TypesenseComponent {
     fn unwrapped_stream(index: IndexName, query: SearchQuery) -> Self::SearchStream {
         LOGGING_STATE.with_borrow_mut(|state| state.init());
 
-        let client = Self::create_client().unwrap_or_else(|_e| {
-            TypesenseSearchApi::new("dummy".to_string(), "http://localhost:8108".to_string())
-        });
+        let client =
+            Self::create_profile_client(search_profile(&query).as_deref()).unwrap_or_else(|_e| {
+                TypesenseSearchApi::new("dummy".to_string(), "http://localhost:8108".to_string())
+            });
 
         let simplified_query = SearchQuery {
             q: query.q,
@@ -0,0 +1,391 @@
+use golem_search::correlation::{self, CORRELATION_ID_HEADER};
+use golem_search::error::{from_reqwest_error, from_send_error, search_error_from_failed_response};
+use golem_search::golem::search::types::SearchError;
+use golem_search::logging::{redact_secret, RedactedResponse};
+use golem_search::retry::RetryPolicy;
+use log::trace;
+use reqwest::{Client, Method, RequestBuilder, Response};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map as JsonMap, Value as JsonValue};
+use std::fmt::Debug;
+use std::time::Duration;
+
+#[derive(Clone)]
+pub struct VectaraApi {
+    client: Client,
+    base_url: String,
+    api_key: String,
+    retry_policy: RetryPolicy,
+    request_timeout: Option<Duration>,
+    headers: Vec<(String, String)>,
+}
+
+impl Debug for VectaraApi {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("VectaraApi")
+            .field("base_url", &self.base_url)
+            .field("api_key", &redact_secret(&self.api_key))
+            .field("retry_policy", &self.retry_policy)
+            .field("request_timeout", &self.request_timeout)
+            .finish()
+    }
+}
+
+pub type Metadata = JsonMap<String, JsonValue>;
+
+/// A metadata field of the documents of a corpus that queries can filter on
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FilterAttribute {
+    pub name: String,
+    /// `document` or `part`
+    pub level: String,
+    #[serde(default)]
+    pub indexed: bool,
+    /// `text`, `integer`, `real` or `boolean`
+    #[serde(rename = "type")]
+    pub attribute_type: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Corpus {
+    pub key: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub filter_attributes: Vec<FilterAttribute>,
+}
+
+/// Pagination of the listings, the key of the next page when there is one
+#[derive(Debug, Default, Deserialize)]
+pub struct ListMetadata {
+    #[serde(default)]
+    pub page_key: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CorpusList {
+    #[serde(default)]
+    pub corpora: Vec<Corpus>,
+    #[serde(default)]
+    pub metadata: ListMetadata,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReplaceFilterAttributesRequest {
+    pub filter_attributes: Vec<FilterAttribute>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DocumentPart {
+    pub text: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<Metadata>,
+}
+
+/// A document of the `core` type, uploaded with its parts as they are
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CoreDocument {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub document_type: String,
+    pub metadata: Metadata,
+    pub document_parts: Vec<DocumentPart>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Document {
+    pub id: String,
+    #[serde(default)]
+    pub metadata: Metadata,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DocumentList {
+    #[serde(default)]
+    pub documents: Vec<Document>,
+    #[serde(default)]
+    pub metadata: ListMetadata,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct QueryRequest {
+    pub query: String,
+    /// The search parameters: `metadata_filter`, `limit`, `offset`, `context_configuration`, and
+    /// the provider parameters of the query, such as `reranker` or `lexical_interpolation`
+    pub search: JsonMap<String, JsonValue>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct QueryResult {
+    #[serde(default)]
+    pub text: String,
+    #[serde(default)]
+    pub score: Option<f64>,
+    #[serde(default)]
+    pub document_id: String,
+    #[serde(default)]
+    pub document_metadata: Metadata,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct QueryResponse {
+    #[serde(default)]
+    pub search_results: Vec<QueryResult>,
+}
+
+impl VectaraApi {
+    pub fn new(api_key: String, base_url: String) -> Self {
+        let client = Client::builder()
+            .build()
+            .expect("Failed to initialize HTTP client");
+
+        Self {
+            client,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            api_key,
+            retry_policy: RetryPolicy::from_config(),
+            request_timeout: None,
+            headers: Vec::new(),
+        }
+    }
+
+    /// Applies a timeout to every request sent by this client
+    pub fn with_request_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.request_timeout = timeout;
+        self
+    }
+
+    /// Adds custom headers to every request sent by this client
+    pub fn with_headers(mut self, headers: Vec<(String, String)>) -> Self {
+        self.headers = headers;
+        self
+    }
+
+    fn create_request(&self, method: Method, url: &str) -> RequestBuilder {
+        trace!("[Vectara] HTTP {method} {url}");
+
+        let mut req = self
+            .client
+            .request(method, url)
+            .header("Content-Type", "application/json")
+            .header("x-api-key", &self.api_key)
+            .header(CORRELATION_ID_HEADER, correlation::current());
+
+        if let Some(timeout) = self.request_timeout {
+            req = req.timeout(timeout);
+        }
+
+        for (name, value) in &self.headers {
+            req = req.header(name, value);
+        }
+
+        req
+    }
+
+    fn send(
+        &self,
+        method: Method,
+        url: &str,
+        body: Option<&impl Serialize>,
+        operation: &str,
+    ) -> Result<Response, SearchError> {
+        self.retry_policy
+            .execute(|| {
+                let req = self.create_request(method.clone(), url);
+                match body {
+                    Some(body) => req.json(body).send(),
+                    None => req.send(),
+                }
+            })
+            .map_err(|e| from_send_error(format!("Failed to {operation}"), e))
+    }
+}
+
+fn parse_response<T: DeserializeOwned + Debug>(response: Response) -> Result<T, SearchError> {
+    let status = response.status();
+
+    trace!(
+        "Received response from Vectara API: {:?}",
+        RedactedResponse(&response)
+    );
+
+    if status.is_success() {
+        let body = response
+            .json::<T>()
+            .map_err(|err| from_reqwest_error("Failed to decode response body", err))?;
+
+        trace!("Received response from Vectara API: {body:?}");
+
+        Ok(body)
+    } else {
+        let error = search_error_from_failed_response(response);
+
+        trace!("Received {status} response from Vectara API: {error:?}");
+
+        Err(error)
+    }
+}
+
+/// Checks the status of a response with no body of interest
+fn check_response(response: Response) -> Result<(), SearchError> {
+    if response.status().is_success() {
+        Ok(())
+    } else {
+        Err(search_error_from_failed_response(response))
+    }
+}
+
+impl VectaraApi {
+    pub fn list_corpora(&self, page_key: Option<&str>) -> Result<CorpusList, SearchError> {
+        trace!("Listing corpora");
+
+        let mut url = format!("{}/v2/corpora?limit=100", self.base_url);
+        if let Some(page_key) = page_key {
+            url.push_str(&format!("&page_key={}", urlencoding::encode(page_key)));
+        }
+        parse_response(self.send(Method::GET, &url, None::<&()>, "list corpora")?)
+    }
+
+    pub fn create_corpus(&self, corpus: &Corpus) -> Result<(), SearchError> {
+        trace!("Creating corpus: {}", corpus.key);
+
+        let url = format!("{}/v2/corpora", self.base_url);
+        let response = self
+            .create_request(Method::POST, &url)
+            .json(corpus)
+            .send()
+            .map_err(|e| from_send_error("Failed to create corpus", e))?;
+
+        check_response(response)
+    }
+
+    pub fn delete_corpus(&self, key: &str) -> Result<(), SearchError> {
+        trace!("Deleting corpus: {key}");
+
+        let url = format!("{}/v2/corpora/{}", self.base_url, key);
+        check_response(self.send(Method::DELETE, &url, None::<&()>, "delete corpus")?)
+    }
+
+    pub fn get_corpus(&self, key: &str) -> Result<Corpus, SearchError> {
+        trace!("Getting corpus: {key}");
+
+        let url = format!("{}/v2/corpora/{}", self.base_url, key);
+        parse_response(self.send(Method::GET, &url, None::<&()>, "get corpus")?)
+    }
+
+    /// Replaces the filter attributes of a corpus. Vectara re-indexes the metadata of the existing
+    /// documents in a background job.
+    pub fn replace_filter_attributes(
+        &self,
+        key: &str,
+        filter_attributes: Vec<FilterAttribute>,
+    ) -> Result<(), SearchError> {
+        trace!("Replacing filter attributes of corpus: {key}");
+
+        let url = format!(
+            "{}/v2/corpora/{}/replace_filter_attributes",
+            self.base_url, key
+        );
+        let request = ReplaceFilterAttributesRequest { filter_attributes };
+        check_response(self.send(
+            Method::POST,
+            &url,
+            Some(&request),
+            "replace filter attributes",
+        )?)
+    }
+
+    pub fn create_document(&self, key: &str, document: &CoreDocument) -> Result<(), SearchError> {
+        trace!("Creating document {} in corpus: {key}", document.id);
+
+        let url = format!("{}/v2/corpora/{}/documents", self.base_url, key);
+        let response = self
+            .create_request(Method::POST, &url)
+            .json(document)
+            .send()
+            .map_err(|e| from_send_error("Failed to create document", e))?;
+
+        check_response(response)
+    }
+
+    /// Deletes a document, returning whether it existed
+    pub fn delete_document(&self, key: &str, id: &str) -> Result<bool, SearchError> {
+        trace!("Deleting document {id} from corpus: {key}");
+
+        let url = format!(
+            "{}/v2/corpora/{}/documents/{}",
+            self.base_url,
+            key,
+            urlencoding::encode(id)
+        );
+        let response = self.send(Method::DELETE, &url, None::<&()>, "delete document")?;
+
+        if response.status() == 404 {
+            Ok(false)
+        } else {
+            check_response(response).map(|_| true)
+        }
+    }
+
+    pub fn get_document(&self, key: &str, id: &str) -> Result<Option<Document>, SearchError> {
+        trace!("Getting document {id} from corpus: {key}");
+
+        let url = format!(
+            "{}/v2/corpora/{}/documents/{}",
+            self.base_url,
+            key,
+            urlencoding::encode(id)
+        );
+        let response = self.send(Method::GET, &url, None::<&()>, "get document")?;
+
+        if response.status() == 404 {
+            Ok(None)
+        } else {
+            Ok(Some(parse_response(response)?))
+        }
+    }
+
+    /// Lists the documents of a corpus matching a metadata filter, a page at a time
+    pub fn list_documents(
+        &self,
+        key: &str,
+        metadata_filter: Option<&str>,
+        limit: u32,
+        page_key: Option<&str>,
+    ) -> Result<DocumentList, SearchError> {
+        trace!("Listing documents of corpus: {key}");
+
+        let mut url = format!(
+            "{}/v2/corpora/{}/documents?limit={limit}",
+            self.base_url, key
+        );
+        if let Some(metadata_filter) = metadata_filter {
+            url.push_str(&format!(
+                "&metadata_filter={}",
+                urlencoding::encode(metadata_filter)
+            ));
+        }
+        if let Some(page_key) = page_key {
+            url.push_str(&format!("&page_key={}", urlencoding::encode(page_key)));
+        }
+        let response = self
+            .retry_policy
+            .execute(|| self.create_request(Method::GET, &url).send())
+            .map_err(|e| from_send_error("Failed to search", e))?;
+
+        parse_response(response)
+    }
+
+    pub fn query(&self, key: &str, request: &QueryRequest) -> Result<QueryResponse, SearchError> {
+        trace!("Querying corpus: {key}");
+
+        let url = format!("{}/v2/corpora/{}/query", self.base_url, key);
+        let response = self
+            .retry_policy
+            .execute(|| self.create_request(Method::POST, &url).json(request).send())
+            .map_err(|e| from_send_error("Failed to search", e))?;
+
+        parse_response(response)
+    }
+}
@@ -0,0 +1,642 @@
+use crate::client::{
+    CoreDocument, Document, DocumentPart, FilterAttribute, Metadata, QueryRequest, QueryResult,
+};
+use golem_search::config::{get_max_pagination_depth_config, search_filters_match_any};
+use golem_search::error::{invalid_query, unsupported};
+use golem_search::golem::search::types::{
+    Doc, FieldType, Schema, SchemaField, SearchError, SearchHit, SearchQuery,
+};
+use serde_json::{json, Map as JsonMap, Value as JsonValue};
+use std::collections::HashSet;
+
+const DEFAULT_PER_PAGE: u32 = 20;
+
+/// Key of the matched passages in the highlights of the hits
+const PASSAGE_HIGHLIGHT_FIELD: &str = "text";
+
+/// The filter attribute of a schema field. Keyword, date, numeric and boolean fields are indexed
+/// document metadata that filters can use, and text fields only when they are faceted, as the
+/// text itself is searched through the document parts.
+fn schema_field_to_filter_attribute(
+    field: &SchemaField,
+) -> Result<Option<FilterAttribute>, SearchError> {
+    let attribute_type = match field.field_type {
+        FieldType::Text if field.facet => "text",
+        FieldType::Text => return Ok(None),
+        FieldType::Keyword | FieldType::Date => "text",
+        FieldType::Integer => "integer",
+        FieldType::Float => "real",
+        FieldType::Boolean => "boolean",
+        FieldType::GeoPoint => {
+            return Err(invalid_query(format!(
+                "Vectara has no geo point fields: `{}`",
+                field.name
+            )))
+        }
+    };
+    Ok(Some(FilterAttribute {
+        name: field.name.clone(),
+        level: "document".to_string(),
+        indexed: true,
+        attribute_type: attribute_type.to_string(),
+    }))
+}
+
+/// The filter attributes of a corpus with the fields of a schema
+pub fn schema_to_filter_attributes(
+    schema: Option<&Schema>,
+) -> Result<Vec<FilterAttribute>, SearchError> {
+    let Some(schema) = schema else {
+        return Ok(vec![]);
+    };
+    schema
+        .fields
+        .iter()
+        .filter_map(|field| schema_field_to_filter_attribute(field).transpose())
+        .collect()
+}
+
+/// The schema of a corpus, from its document level filter attributes. Text attributes are read
+/// back as keyword fields.
+pub fn filter_attributes_to_schema(filter_attributes: Vec<FilterAttribute>) -> Schema {
+    let fields = filter_attributes
+        .into_iter()
+        .filter(|attribute| attribute.level == "document")
+        .filter_map(|attribute| {
+            let field_type = match attribute.attribute_type.as_str() {
+                "text" => FieldType::Keyword,
+                "integer" => FieldType::Integer,
+                "real" => FieldType::Float,
+                "boolean" => FieldType::Boolean,
+                _ => return None,
+            };
+            Some(SchemaField {
+                name: attribute.name,
+                field_type,
+                required: false,
+                facet: false,
+                sort: false,
+                index: attribute.indexed,
+            })
+        })
+        .collect();
+
+    Schema {
+        fields,
+        primary_key: None,
+    }
+}
+
+/// A core document with the fields of the document content as its metadata, and a single part
+/// with the text of its string fields, one per line
+pub fn doc_to_vectara(doc: Doc) -> Result<CoreDocument, SearchError> {
+    let metadata = match serde_json::from_str::<JsonValue>(&doc.content) {
+        Ok(JsonValue::Object(metadata)) => metadata,
+        Ok(_) => {
+            return Err(invalid_query(format!(
+                "Content of document {} is not a JSON object",
+                doc.id
+            )))
+        }
+        Err(e) => {
+            return Err(invalid_query(format!(
+                "Invalid JSON content of document {}: {e}",
+                doc.id
+            )))
+        }
+    };
+
+    let text = metadata
+        .values()
+        .filter_map(JsonValue::as_str)
+        .collect::<Vec<_>>()
+        .join("\n");
+    if text.trim().is_empty() {
+        return Err(invalid_query(format!(
+            "Document {} has no text to index",
+            doc.id
+        )));
+    }
+
+    Ok(CoreDocument {
+        id: doc.id,
+        document_type: "core".to_string(),
+        metadata,
+        document_parts: vec![DocumentPart {
+            text,
+            metadata: None,
+        }],
+    })
+}
+
+pub fn vectara_to_doc(document: Document) -> Doc {
+    Doc {
+        id: document.id,
+        content: JsonValue::Object(document.metadata).to_string(),
+    }
+}
+
+/// Highlights in the same shape as the other providers: `{"text": ["snippet"]}`, with the matched
+/// passage as the snippet, cut to the maximum length of the highlight configuration
+fn result_highlights(result: &QueryResult, query: &SearchQuery) -> Option<String> {
+    let highlight = query.highlight.as_ref()?;
+    if result.text.is_empty() {
+        return None;
+    }
+    let text: String = match highlight.max_length {
+        Some(max_length) => result.text.chars().take(max_length as usize).collect(),
+        None => result.text.clone(),
+    };
+    Some(json!({ PASSAGE_HIGHLIGHT_FIELD: [text] }).to_string())
+}
+
+fn metadata_content(metadata: &Metadata) -> String {
+    JsonValue::Object(metadata.clone()).to_string()
+}
+
+/// The hits of the results of a query. Vectara returns matching passages, so the results are
+/// reduced to the best passage of each document.
+pub fn results_to_hits(results: Vec<QueryResult>, query: &SearchQuery) -> Vec<SearchHit> {
+    let mut seen = HashSet::new();
+    results
+        .into_iter()
+        .filter(|result| seen.insert(result.document_id.clone()))
+        .map(|result| SearchHit {
+            id: result.document_id.clone(),
+            score: result.score,
+            content: Some(metadata_content(&result.document_metadata)),
+            highlights: result_highlights(&result, query),
+        })
+        .collect()
+}
+
+/// A hit of a document listed without a query, which has no score
+pub fn document_to_hit(document: Document) -> SearchHit {
+    SearchHit {
+        id: document.id,
+        score: None,
+        content: Some(metadata_content(&document.metadata)),
+        highlights: None,
+    }
+}
+
+/// A value of a metadata filter: numbers and booleans as they are, anything else as a string
+/// literal
+fn filter_value(value: &str) -> String {
+    let value = value.trim();
+    if value.parse::<f64>().is_ok() || value == "true" || value == "false" {
+        return value.to_string();
+    }
+    let value = value
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+        .unwrap_or(value);
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+/// Converts a filter to a Vectara metadata filter on the document metadata. Filters are
+/// `field:value`, `field=value`, `field!=value`, and `field>value` style comparisons with `>`,
+/// `>=`, `<` and `<=`.
+fn filter_to_vectara(filter: &str) -> Result<String, SearchError> {
+    let filter = filter.trim();
+    let position = filter
+        .find([':', '=', '!', '<', '>'])
+        .ok_or_else(|| invalid_query(format!("Unsupported filter `{filter}`")))?;
+    let key = filter[..position].trim();
+    let rest = &filter[position..];
+    let (op, value) = [">=", "<=", "!=", ">", "<", "=", ":"]
+        .into_iter()
+        .find_map(|op| Some((op, rest.strip_prefix(op)?)))
+        .ok_or_else(|| invalid_query(format!("Unsupported filter `{filter}`")))?;
+    if key.is_empty() || value.trim().is_empty() {
+        return Err(invalid_query(format!("Unsupported filter `{filter}`")));
+    }
+    let op = match op {
+        ":" | "=" => "=",
+        "!=" => "<>",
+        op => op,
+    };
+
+    Ok(format!("doc.{key} {op} {}", filter_value(value)))
+}
+
+/// The metadata filter of a query, matching all of its filters, or any of them when its filter
+/// mode asks for it
+pub fn query_to_metadata_filter(query: &SearchQuery) -> Result<Option<String>, SearchError> {
+    let clauses = query
+        .filters
+        .iter()
+        .map(|filter| filter_to_vectara(filter))
+        .collect::<Result<Vec<_>, _>>()?;
+    if clauses.is_empty() {
+        return Ok(None);
+    }
+    if clauses.len() == 1 {
+        return Ok(clauses.into_iter().next());
+    }
+    let operator = if search_filters_match_any(query) {
+        " or "
+    } else {
+        " and "
+    };
+    Ok(Some(
+        clauses
+            .iter()
+            .map(|clause| format!("({clause})"))
+            .collect::<Vec<_>>()
+            .join(operator),
+    ))
+}
+
+/// Number of hits to skip and number of hits per page of a query
+pub fn query_window(query: &SearchQuery) -> Result<(u32, u32), SearchError> {
+    let per_page = query.per_page.unwrap_or(DEFAULT_PER_PAGE);
+    let offset = query.offset.unwrap_or_else(|| {
+        query
+            .page
+            .unwrap_or(1)
+            .saturating_sub(1)
+            .saturating_mul(per_page)
+    });
+    if offset.saturating_add(per_page) > get_max_pagination_depth_config() {
+        return Err(invalid_query(format!(
+            "Requested hits beyond the maximum pagination depth of {}",
+            get_max_pagination_depth_config()
+        )));
+    }
+    Ok((offset, per_page))
+}
+
+/// Checks that a query only uses features Vectara supports: it can't sort results by a field or
+/// count facets
+pub fn check_query(query: &SearchQuery) -> Result<(), SearchError> {
+    if !query.sort.is_empty() {
+        return Err(unsupported("sorting"));
+    }
+    if !query.facets.is_empty() {
+        return Err(unsupported("facets"));
+    }
+    Ok(())
+}
+
+/// Builds the query request of a query with text. The provider parameters of the query are added
+/// to the search parameters, so that `reranker`, `lexical_interpolation` and the other search
+/// options of Vectara can be set, and can override the limit, offset and metadata filter of the
+/// query.
+pub fn query_to_request(query: &SearchQuery, text: &str) -> Result<QueryRequest, SearchError> {
+    check_query(query)?;
+
+    let (offset, per_page) = query_window(query)?;
+
+    let mut search = JsonMap::new();
+    search.insert("limit".to_string(), json!(per_page));
+    search.insert("offset".to_string(), json!(offset));
+    if let Some(metadata_filter) = query_to_metadata_filter(query)? {
+        search.insert("metadata_filter".to_string(), json!(metadata_filter));
+    }
+    if let Some(highlight) = &query.highlight {
+        search.insert(
+            "context_configuration".to_string(),
+            json!({
+                "start_tag": highlight.pre_tag.as_deref().unwrap_or("<em>"),
+                "end_tag": highlight.post_tag.as_deref().unwrap_or("</em>"),
+            }),
+        );
+    }
+
+    if let Some(provider_params) = query
+        .config
+        .as_ref()
+        .and_then(|config| config.provider_params.as_deref())
+    {
+        let provider_params =
+            serde_json::from_str::<JsonMap<String, JsonValue>>(provider_params)
+                .map_err(|e| invalid_query(format!("Invalid provider parameters: {e}")))?;
+        search.extend(
+            provider_params
+                .into_iter()
+                .filter(|(key, _)| key != "headers"),
+        );
+    }
+
+    Ok(QueryRequest {
+        query: text.to_string(),
+        search,
+    })
+}
+
+pub fn create_retry_query(original_query: &SearchQuery, partial_hits: &[SearchHit]) -> SearchQuery {
+    let mut retry_query = original_query.clone();
+
+    let current_offset = original_query.offset.unwrap_or(0);
+    let per_page = original_query.per_page.unwrap_or(DEFAULT_PER_PAGE);
+    let hits_received = partial_hits.len() as u32;
+
+    retry_query.offset = Some(current_offset + hits_received.min(per_page));
+
+    retry_query
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use golem_search::golem::search::types::{FilterMode, HighlightConfig, SearchConfig};
+
+    fn query(q: Option<&str>, filters: &[&str]) -> SearchQuery {
+        SearchQuery {
+            q: q.map(str::to_string),
+            filters: filters.iter().map(|filter| filter.to_string()).collect(),
+            sort: vec![],
+            facets: vec![],
+            page: None,
+            per_page: None,
+            offset: None,
+            highlight: None,
+            config: None,
+        }
+    }
+
+    fn field(name: &str, field_type: FieldType, facet: bool) -> SchemaField {
+        SchemaField {
+            name: name.to_string(),
+            field_type,
+            required: false,
+            facet,
+            sort: false,
+            index: true,
+        }
+    }
+
+    fn config(provider_params: Option<&str>, filter_mode: Option<FilterMode>) -> SearchConfig {
+        SearchConfig {
+            timeout_ms: None,
+            boost_fields: vec![],
+            attributes_to_retrieve: vec![],
+            language: None,
+            typo_tolerance: None,
+            exact_match_boost: None,
+            provider_params: provider_params.map(str::to_string),
+            profile: None,
+            filter_mode,
+            post_filters: vec![],
+            rescore: None,
+            fallbacks: vec![],
+        }
+    }
+
+    fn metadata(value: JsonValue) -> Metadata {
+        match value {
+            JsonValue::Object(metadata) => metadata,
+            _ => panic!("Not a JSON object"),
+        }
+    }
+
+    fn result(document_id: &str, text: &str, score: f64) -> QueryResult {
+        QueryResult {
+            text: text.to_string(),
+            score: Some(score),
+            document_id: document_id.to_string(),
+            document_metadata: metadata(json!({ "title": document_id })),
+        }
+    }
+
+    #[test]
+    fn test_schema_to_filter_attributes() {
+        let schema = Schema {
+            fields: vec![
+                field("title", FieldType::Text, false),
+                field("genre", FieldType::Text, true),
+                field("isbn", FieldType::Keyword, false),
+                field("year", FieldType::Integer, false),
+                field("rating", FieldType::Float, false),
+                field("available", FieldType::Boolean, false),
+            ],
+            primary_key: None,
+        };
+        let attributes = schema_to_filter_attributes(Some(&schema)).unwrap();
+        assert_eq!(
+            serde_json::to_value(&attributes).unwrap(),
+            json!([
+                { "name": "genre", "level": "document", "indexed": true, "type": "text" },
+                { "name": "isbn", "level": "document", "indexed": true, "type": "text" },
+                { "name": "year", "level": "document", "indexed": true, "type": "integer" },
+                { "name": "rating", "level": "document", "indexed": true, "type": "real" },
+                { "name": "available", "level": "document", "indexed": true, "type": "boolean" },
+            ])
+        );
+        assert!(schema_to_filter_attributes(None).unwrap().is_empty());
+
+        let schema = Schema {
+            fields: vec![field("location", FieldType::GeoPoint, false)],
+            primary_key: None,
+        };
+        assert!(matches!(
+            schema_to_filter_attributes(Some(&schema)),
+            Err(SearchError::InvalidQuery(_))
+        ));
+    }
+
+    #[test]
+    fn test_filter_attributes_to_schema() {
+        let schema = Schema {
+            fields: vec![
+                field("isbn", FieldType::Keyword, false),
+                field("year", FieldType::Integer, false),
+                field("rating", FieldType::Float, false),
+                field("available", FieldType::Boolean, false),
+            ],
+            primary_key: None,
+        };
+        let attributes = schema_to_filter_attributes(Some(&schema)).unwrap();
+        assert_eq!(filter_attributes_to_schema(attributes), schema);
+    }
+
+    #[test]
+    fn test_doc_to_vectara() {
+        let doc = Doc {
+            id: "doc1".to_string(),
+            content: r#"{"title":"Dune","author":"Frank Herbert","year":1965}"#.to_string(),
+        };
+        let document = doc_to_vectara(doc).unwrap();
+        assert_eq!(document.id, "doc1");
+        assert_eq!(document.document_type, "core");
+        assert_eq!(
+            document.metadata,
+            metadata(json!({ "title": "Dune", "author": "Frank Herbert", "year": 1965 }))
+        );
+        assert_eq!(document.document_parts.len(), 1);
+        assert!(document.document_parts[0].text.contains("Dune"));
+        assert!(document.document_parts[0].text.contains("Frank Herbert"));
+
+        for content in ["[1, 2]", r#"{"year":1965}"#] {
+            let doc = Doc {
+                id: "doc1".to_string(),
+                content: content.to_string(),
+            };
+            assert!(matches!(
+                doc_to_vectara(doc),
+                Err(SearchError::InvalidQuery(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_vectara_to_doc() {
+        let doc = vectara_to_doc(Document {
+            id: "doc1".to_string(),
+            metadata: metadata(json!({ "title": "Dune" })),
+        });
+        assert_eq!(
+            doc,
+            Doc {
+                id: "doc1".to_string(),
+                content: r#"{"title":"Dune"}"#.to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_results_to_hits() {
+        let results = vec![
+            result("doc1", "The desert planet", 0.9),
+            result("doc2", "A spice merchant", 0.8),
+            result("doc1", "Arrakis", 0.7),
+        ];
+
+        let mut search = query(Some("dune"), &[]);
+        let hits = results_to_hits(results.clone(), &search);
+        assert_eq!(
+            hits.iter().map(|hit| hit.id.as_str()).collect::<Vec<_>>(),
+            vec!["doc1", "doc2"]
+        );
+        assert_eq!(hits[0].score, Some(0.9));
+        assert_eq!(hits[0].content.as_deref(), Some(r#"{"title":"doc1"}"#));
+        assert_eq!(hits[0].highlights, None);
+
+        search.highlight = Some(HighlightConfig {
+            fields: vec![],
+            pre_tag: None,
+            post_tag: None,
+            max_length: Some(10),
+        });
+        assert_eq!(
+            results_to_hits(results, &search)[0].highlights.as_deref(),
+            Some(r#"{"text":["The desert"]}"#)
+        );
+    }
+
+    #[test]
+    fn test_filters_to_vectara() {
+        assert_eq!(
+            filter_to_vectara("genre:sci-fi").unwrap(),
+            "doc.genre = 'sci-fi'"
+        );
+        assert_eq!(
+            filter_to_vectara(r#"title = "Dune Messiah""#).unwrap(),
+            "doc.title = 'Dune Messiah'"
+        );
+        assert_eq!(
+            filter_to_vectara("author=O'Brien").unwrap(),
+            "doc.author = 'O''Brien'"
+        );
+        assert_eq!(
+            filter_to_vectara("genre!=fantasy").unwrap(),
+            "doc.genre <> 'fantasy'"
+        );
+        assert_eq!(
+            filter_to_vectara("available=true").unwrap(),
+            "doc.available = true"
+        );
+        assert_eq!(filter_to_vectara("year>=1965").unwrap(), "doc.year >= 1965");
+        assert_eq!(filter_to_vectara("year<-1.5").unwrap(), "doc.year < -1.5");
+        assert!(filter_to_vectara("year").is_err());
+        assert!(filter_to_vectara("year>").is_err());
+    }
+
+    #[test]
+    fn test_query_to_metadata_filter() {
+        assert_eq!(query_to_metadata_filter(&query(None, &[])).unwrap(), None);
+
+        let mut search = query(None, &["genre:scifi", "year>=1965"]);
+        assert_eq!(
+            query_to_metadata_filter(&search).unwrap(),
+            Some("(doc.genre = 'scifi') and (doc.year >= 1965)".to_string())
+        );
+
+        search.config = Some(config(None, Some(FilterMode::MatchAny)));
+        assert_eq!(
+            query_to_metadata_filter(&search).unwrap(),
+            Some("(doc.genre = 'scifi') or (doc.year >= 1965)".to_string())
+        );
+    }
+
+    #[test]
+    fn test_query_to_request() {
+        let mut search = query(Some("dune"), &["genre:scifi"]);
+        search.page = Some(2);
+        search.per_page = Some(10);
+        search.highlight = Some(HighlightConfig {
+            fields: vec![],
+            pre_tag: Some("<b>".to_string()),
+            post_tag: Some("</b>".to_string()),
+            max_length: None,
+        });
+        search.config = Some(config(
+            Some(
+                r#"{"reranker":{"type":"customer_reranker","reranker_name":"Rerank_Multilingual_v1"},"lexical_interpolation":0.025,"headers":{"x-trace":"1"}}"#,
+            ),
+            None,
+        ));
+
+        let request = query_to_request(&search, "dune").unwrap();
+        assert_eq!(
+            serde_json::to_value(&request).unwrap(),
+            json!({
+                "query": "dune",
+                "search": {
+                    "limit": 10,
+                    "offset": 10,
+                    "metadata_filter": "doc.genre = 'scifi'",
+                    "context_configuration": { "start_tag": "<b>", "end_tag": "</b>" },
+                    "reranker": { "type": "customer_reranker", "reranker_name": "Rerank_Multilingual_v1" },
+                    "lexical_interpolation": 0.025,
+                },
+            })
+        );
+
+        search.config = Some(config(Some("not json"), None));
+        assert!(matches!(
+            query_to_request(&search, "dune"),
+            Err(SearchError::InvalidQuery(_))
+        ));
+    }
+
+    #[test]
+    fn test_query_to_request_rejects_sorting_and_facets() {
+        let mut search = query(Some("dune"), &[]);
+        search.sort = vec!["year:desc".to_string()];
+        assert!(matches!(
+            query_to_request(&search, "dune"),
+            Err(SearchError::Unsupported)
+        ));
+
+        let mut search = query(Some("dune"), &[]);
+        search.facets = vec!["genre".to_string()];
+        assert!(matches!(
+            query_to_request(&search, "dune"),
+            Err(SearchError::Unsupported)
+        ));
+    }
+
+    #[test]
+    fn test_create_retry_query() {
+        let mut search = query(Some("dune"), &[]);
+        search.offset = Some(10);
+        search.per_page = Some(5);
+        let hits = results_to_hits(
+            vec![result("doc1", "a", 0.9), result("doc2", "b", 0.8)],
+            &search,
+        );
+        assert_eq!(create_retry_query(&search, &hits).offset, Some(12));
+    }
+}
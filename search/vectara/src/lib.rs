@@ -0,0 +1,388 @@
+use crate::client::{Corpus, VectaraApi};
+use crate::conversions::{
+    check_query, create_retry_query, doc_to_vectara, document_to_hit, filter_attributes_to_schema,
+    query_to_metadata_filter, query_to_request, query_window, results_to_hits,
+    schema_to_filter_attributes, vectara_to_doc,
+};
+use golem_rust::wasm_rpc::Pollable;
+use golem_search::config::{
+    get_config_with_default, profile_key, search_headers, search_profile, search_timeout,
+    with_profile_config_keys, worker_headers, worker_profile,
+};
+use golem_search::durability::{DurableSearch, ExtendedGuest};
+use golem_search::golem::search::core::{Guest, GuestSearchStream, SearchStream};
+use golem_search::golem::search::types::{
+    Doc, DocumentId, IndexName, IndexPage, PageRequest, Schema, SearchError, SearchHit,
+    SearchQuery, SearchResults,
+};
+use golem_search::pagination::index_page;
+use golem_search::LOGGING_STATE;
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, HashSet};
+
+mod client;
+mod conversions;
+
+const DEFAULT_BASE_URL: &str = "https://api.vectara.io";
+
+/// Number of documents fetched per request when listing the documents of a corpus
+const LIST_PAGE_SIZE: u32 = 100;
+
+/// Search stream over the pages of a query, as Vectara has no native streaming. Vectara pages
+/// through passages rather than documents, so the stream skips the documents it already returned.
+struct VectaraSearchStream {
+    client: VectaraApi,
+    index_name: String,
+    query: SearchQuery,
+    offset: Cell<u32>,
+    finished: Cell<bool>,
+    seen: RefCell<HashSet<String>>,
+}
+
+impl VectaraSearchStream {
+    pub fn new(client: VectaraApi, index_name: String, query: SearchQuery) -> Self {
+        let offset = query_window(&query).map(|(offset, _)| offset).unwrap_or(0);
+        Self {
+            client,
+            index_name,
+            query,
+            offset: Cell::new(offset),
+            finished: Cell::new(false),
+            seen: RefCell::new(HashSet::new()),
+        }
+    }
+
+    pub fn subscribe(&self) -> Pollable {
+        golem_rust::bindings::wasi::clocks::monotonic_clock::subscribe_duration(0)
+    }
+}
+
+impl GuestSearchStream for VectaraSearchStream {
+    fn get_next(&self) -> Option<Vec<SearchHit>> {
+        if self.finished.get() {
+            return Some(vec![]);
+        }
+
+        let mut query = self.query.clone();
+        query.page = None;
+        query.offset = Some(self.offset.get());
+        let per_page = query_window(&query)
+            .map(|(_, per_page)| per_page)
+            .unwrap_or(0);
+
+        match VectaraComponent::search_page(&self.client, &self.index_name, &query) {
+            Ok((results, received)) => {
+                if received < per_page || received == 0 {
+                    self.finished.set(true);
+                }
+                self.offset.set(self.offset.get() + received);
+                let mut seen = self.seen.borrow_mut();
+                Some(
+                    results
+                        .hits
+                        .into_iter()
+                        .filter(|hit| seen.insert(hit.id.clone()))
+                        .collect(),
+                )
+            }
+            Err(_) => {
+                self.finished.set(true);
+                Some(vec![])
+            }
+        }
+    }
+
+    fn blocking_get_next(&self) -> Vec<SearchHit> {
+        self.get_next().unwrap_or_default()
+    }
+}
+
+struct VectaraComponent;
+
+thread_local! {
+    /// The provider clients by configuration profile, reused across calls instead of being rebuilt
+    /// for each of them
+    static CLIENTS: RefCell<HashMap<Option<String>, VectaraApi>> = RefCell::new(HashMap::new());
+}
+
+impl VectaraComponent {
+    const API_KEY_ENV_VAR: &'static str = "VECTARA_API_KEY";
+    const BASE_URL_ENV_VAR: &'static str = "VECTARA_BASE_URL";
+
+    fn create_client() -> Result<VectaraApi, SearchError> {
+        Self::create_profile_client(worker_profile().as_deref())
+            .map(|client| client.with_headers(worker_headers()))
+    }
+
+    /// Returns the client of `profile` cached for this worker, creating it from the configuration
+    /// on first use
+    fn create_profile_client(profile: Option<&str>) -> Result<VectaraApi, SearchError> {
+        CLIENTS.with_borrow_mut(|clients| {
+            let profile = profile.map(str::to_string);
+            if let Some(client) = clients.get(&profile) {
+                return Ok(client.clone());
+            }
+
+            let client = Self::new_client(profile.as_deref())?;
+            clients.insert(profile, client.clone());
+            Ok(client)
+        })
+    }
+
+    fn new_client(profile: Option<&str>) -> Result<VectaraApi, SearchError> {
+        with_profile_config_keys(profile, &[Self::API_KEY_ENV_VAR], |keys| {
+            if keys.is_empty() {
+                return Err(SearchError::Internal("Missing Vectara API key".to_string()));
+            }
+
+            let api_key = keys[0].clone();
+
+            let base_url = get_config_with_default(
+                profile_key(Self::BASE_URL_ENV_VAR, profile),
+                DEFAULT_BASE_URL,
+            );
+
+            Ok(VectaraApi::new(api_key, base_url))
+        })
+    }
+
+    /// Runs a page of a query, returning its results and the number of results Vectara returned
+    /// for the page, before they are reduced to one hit per document. Queries with text use the
+    /// query API, queries without text list the documents matching their filters. Vectara doesn't
+    /// report the number of matches, so the results have no total.
+    fn search_page(
+        client: &VectaraApi,
+        index: &str,
+        query: &SearchQuery,
+    ) -> Result<(SearchResults, u32), SearchError> {
+        let (offset, per_page) = query_window(query)?;
+
+        let (hits, received) = match query.q.as_deref().map(str::trim).filter(|q| !q.is_empty()) {
+            Some(text) => {
+                let request = query_to_request(query, text)?;
+                let response = client.query(index, &request)?;
+                let received = response.search_results.len() as u32;
+                (results_to_hits(response.search_results, query), received)
+            }
+            None => {
+                let hits = Self::list_page(client, index, query, offset, per_page)?;
+                let received = hits.len() as u32;
+                (hits, received)
+            }
+        };
+
+        Ok((
+            SearchResults {
+                total: None,
+                page: Some(offset / per_page.max(1) + 1),
+                per_page: Some(per_page),
+                hits,
+                facets: None,
+                took_ms: None,
+                relaxation: None,
+                navigation: None,
+            },
+            received,
+        ))
+    }
+
+    /// The documents of a page of a query without text, listing the documents matching its
+    /// filters until the offset is skipped and the page is filled
+    fn list_page(
+        client: &VectaraApi,
+        index: &str,
+        query: &SearchQuery,
+        offset: u32,
+        per_page: u32,
+    ) -> Result<Vec<SearchHit>, SearchError> {
+        check_query(query)?;
+
+        let metadata_filter = query_to_metadata_filter(query)?;
+        let mut to_skip = offset as usize;
+        let mut hits = Vec::new();
+        let mut page_key = None;
+
+        while hits.len() < per_page as usize {
+            let response = client.list_documents(
+                index,
+                metadata_filter.as_deref(),
+                LIST_PAGE_SIZE,
+                page_key.as_deref(),
+            )?;
+
+            let documents = response.documents.into_iter();
+            let skipped = documents.len().min(to_skip);
+            to_skip -= skipped;
+            hits.extend(
+                documents
+                    .skip(skipped)
+                    .take(per_page as usize - hits.len())
+                    .map(document_to_hit),
+            );
+
+            match response.metadata.page_key {
+                Some(next) => page_key = Some(next),
+                None => break,
+            }
+        }
+
+        Ok(hits)
+    }
+}
+
+impl Guest for VectaraComponent {
+    type SearchStream = VectaraSearchStream;
+
+    /// Creates a corpus keyed by the index name, with the fields of the schema as filter
+    /// attributes of its documents
+    fn create_index(name: IndexName, schema: Option<Schema>) -> Result<(), SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let corpus = Corpus {
+            key: name.clone(),
+            name: Some(name),
+            filter_attributes: schema_to_filter_attributes(schema.as_ref())?,
+        };
+        let client = Self::create_client()?;
+        client.create_corpus(&corpus)
+    }
+
+    fn delete_index(name: IndexName) -> Result<(), SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = Self::create_client()?;
+        client.delete_corpus(&name)
+    }
+
+    fn list_indexes(page: PageRequest) -> Result<IndexPage, SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = Self::create_client()?;
+
+        let mut names = Vec::new();
+        let mut page_key = None;
+        loop {
+            let response = client.list_corpora(page_key.as_deref())?;
+            names.extend(response.corpora.into_iter().map(|corpus| corpus.key));
+            match response.metadata.page_key {
+                Some(next) => page_key = Some(next),
+                None => break,
+            }
+        }
+
+        index_page(names, &page)
+    }
+
+    fn upsert(index: IndexName, doc: Doc) -> Result<(), SearchError> {
+        Self::upsert_many(index, vec![doc])
+    }
+
+    /// Uploads documents, deleting any previous version first as Vectara rejects documents whose
+    /// id already exists in the corpus
+    fn upsert_many(index: IndexName, docs: Vec<Doc>) -> Result<(), SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let documents = docs
+            .into_iter()
+            .map(doc_to_vectara)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let client = Self::create_client()?;
+        for document in documents {
+            client.delete_document(&index, &document.id)?;
+            client.create_document(&index, &document)?;
+        }
+        Ok(())
+    }
+
+    fn delete(index: IndexName, id: DocumentId) -> Result<(), SearchError> {
+        Self::delete_many(index, vec![id])
+    }
+
+    /// Deletes documents one by one, ignoring the ones that don't exist
+    fn delete_many(index: IndexName, ids: Vec<DocumentId>) -> Result<(), SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        if ids.is_empty() {
+            return Ok(());
+        }
+
+        let client = Self::create_client()?;
+        for id in ids {
+            client.delete_document(&index, &id)?;
+        }
+        Ok(())
+    }
+
+    fn get(index: IndexName, id: DocumentId) -> Result<Option<Doc>, SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = Self::create_client()?;
+
+        Ok(client.get_document(&index, &id)?.map(vectara_to_doc))
+    }
+
+    fn search(index: IndexName, query: SearchQuery) -> Result<SearchResults, SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = Self::create_profile_client(search_profile(&query).as_deref())?
+            .with_request_timeout(search_timeout(&query))
+            .with_headers(search_headers(&query));
+
+        Self::search_page(&client, &index, &query).map(|(results, _)| results)
+    }
+
+    fn stream_search(index: IndexName, query: SearchQuery) -> Result<SearchStream, SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = Self::create_profile_client(search_profile(&query).as_deref())?
+            .with_request_timeout(search_timeout(&query))
+            .with_headers(search_headers(&query));
+        let stream = VectaraSearchStream::new(client, index, query);
+        Ok(SearchStream::new(stream))
+    }
+
+    /// Returns the document level filter attributes of the corpus as the fields of its schema
+    fn get_schema(index: IndexName) -> Result<Schema, SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = Self::create_client()?;
+
+        Ok(filter_attributes_to_schema(
+            client.get_corpus(&index)?.filter_attributes,
+        ))
+    }
+
+    /// Replaces the filter attributes of the corpus with the fields of the schema
+    fn update_schema(index: IndexName, schema: Schema) -> Result<(), SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let filter_attributes = schema_to_filter_attributes(Some(&schema))?;
+        let client = Self::create_client()?;
+        client.replace_filter_attributes(&index, filter_attributes)
+    }
+}
+
+impl ExtendedGuest for VectaraComponent {
+    fn unwrapped_stream(index: IndexName, query: SearchQuery) -> Self::SearchStream {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = Self::create_profile_client(search_profile(&query).as_deref())
+            .unwrap_or_else(|_| VectaraApi::new(String::new(), DEFAULT_BASE_URL.to_string()))
+            .with_headers(search_headers(&query));
+
+        VectaraSearchStream::new(client, index, query)
+    }
+
+    fn retry_query(original_query: &SearchQuery, partial_hits: &[SearchHit]) -> SearchQuery {
+        create_retry_query(original_query, partial_hits)
+    }
+
+    fn subscribe(stream: &Self::SearchStream) -> Pollable {
+        stream.subscribe()
+    }
+}
+
+type DurableVectaraComponent = DurableSearch<VectaraComponent>;
+
+golem_search::export_search!(DurableVectaraComponent with_types_in golem_search);
@@ -0,0 +1,452 @@
+use golem_search::correlation::{self, CORRELATION_ID_HEADER};
+use golem_search::error::{
+    from_reqwest_error, from_send_error, internal_error, search_error_from_failed_response,
+};
+use golem_search::golem::search::types::SearchError;
+use golem_search::logging::{redact_secret, RedactedResponse};
+use golem_search::retry::RetryPolicy;
+use log::trace;
+use reqwest::{Client, Method, RequestBuilder, Response};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map as JsonMap, Value as JsonValue};
+use std::fmt::Debug;
+use std::time::Duration;
+
+/// Path of the active application on a self-hosted config server
+const ACTIVE_APPLICATION_PATH: &str =
+    "application/v2/tenant/default/application/default/environment/prod/region/default/instance/default";
+
+#[derive(Clone)]
+pub struct VespaApi {
+    client: Client,
+    endpoint: String,
+    config_server_url: Option<String>,
+    api_key: Option<String>,
+    retry_policy: RetryPolicy,
+    request_timeout: Option<Duration>,
+    headers: Vec<(String, String)>,
+}
+
+impl Debug for VespaApi {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("VespaApi")
+            .field("endpoint", &self.endpoint)
+            .field("config_server_url", &self.config_server_url)
+            .field("api_key", &self.api_key.as_deref().map(redact_secret))
+            .field("retry_policy", &self.retry_policy)
+            .field("request_timeout", &self.request_timeout)
+            .finish()
+    }
+}
+
+pub type Fields = JsonMap<String, JsonValue>;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Document {
+    #[serde(default, skip_serializing)]
+    pub id: String,
+    #[serde(default)]
+    pub fields: Fields,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VisitResponse {
+    #[serde(default)]
+    pub documents: Vec<Document>,
+    #[serde(default)]
+    pub continuation: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SearchResponse {
+    pub root: ResultNode,
+}
+
+/// A node of the result tree of a query: the root, a hit, or a grouping node
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct ResultNode {
+    #[serde(default)]
+    pub id: String,
+    #[serde(default)]
+    pub relevance: Option<f64>,
+    #[serde(default)]
+    pub label: Option<String>,
+    #[serde(default)]
+    pub value: Option<JsonValue>,
+    #[serde(default)]
+    pub fields: Fields,
+    #[serde(default)]
+    pub children: Vec<ResultNode>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Session {
+    #[serde(rename = "session-id")]
+    pub session_id: String,
+}
+
+impl VespaApi {
+    pub fn new(
+        endpoint: String,
+        config_server_url: Option<String>,
+        api_key: Option<String>,
+    ) -> Self {
+        let client = Client::builder()
+            .build()
+            .expect("Failed to initialize HTTP client");
+
+        Self {
+            client,
+            endpoint: endpoint.trim_end_matches('/').to_string(),
+            config_server_url: config_server_url.map(|url| url.trim_end_matches('/').to_string()),
+            api_key,
+            retry_policy: RetryPolicy::from_config(),
+            request_timeout: None,
+            headers: Vec::new(),
+        }
+    }
+
+    /// Applies a timeout to every request sent by this client
+    pub fn with_request_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.request_timeout = timeout;
+        self
+    }
+
+    /// Adds custom headers to every request sent by this client
+    pub fn with_headers(mut self, headers: Vec<(String, String)>) -> Self {
+        self.headers = headers;
+        self
+    }
+
+    /// Whether a config server is configured, which managing schemas needs
+    pub fn manages_schemas(&self) -> bool {
+        self.config_server_url.is_some()
+    }
+
+    fn create_request(&self, method: Method, url: &str) -> RequestBuilder {
+        trace!("[Vespa] HTTP {method} {url}");
+
+        let mut req = self
+            .client
+            .request(method, url)
+            .header("Content-Type", "application/json")
+            .header(CORRELATION_ID_HEADER, correlation::current());
+
+        if let Some(api_key) = &self.api_key {
+            req = req.bearer_auth(api_key);
+        }
+
+        if let Some(timeout) = self.request_timeout {
+            req = req.timeout(timeout);
+        }
+
+        for (name, value) in &self.headers {
+            req = req.header(name, value);
+        }
+
+        req
+    }
+
+    fn send(
+        &self,
+        method: Method,
+        url: &str,
+        body: Option<&impl Serialize>,
+        operation: &str,
+    ) -> Result<Response, SearchError> {
+        self.retry_policy
+            .execute(|| {
+                let req = self.create_request(method.clone(), url);
+                match body {
+                    Some(body) => req.json(body).send(),
+                    None => req.send(),
+                }
+            })
+            .map_err(|e| from_send_error(format!("Failed to {operation}"), e))
+    }
+
+    fn document_url(&self, namespace: &str, document_type: &str, id: &str) -> String {
+        format!(
+            "{}/document/v1/{}/{}/docid/{}",
+            self.endpoint,
+            namespace,
+            document_type,
+            urlencoding::encode(id)
+        )
+    }
+
+    fn config_server_url(&self) -> Result<&str, SearchError> {
+        self.config_server_url
+            .as_deref()
+            .ok_or_else(|| internal_error("Missing Vespa config server URL"))
+    }
+
+    fn session_url(&self, session: &Session) -> Result<String, SearchError> {
+        Ok(format!(
+            "{}/application/v2/tenant/default/session/{}",
+            self.config_server_url()?,
+            session.session_id
+        ))
+    }
+}
+
+fn parse_response<T: DeserializeOwned + Debug>(response: Response) -> Result<T, SearchError> {
+    let status = response.status();
+
+    trace!(
+        "Received response from Vespa API: {:?}",
+        RedactedResponse(&response)
+    );
+
+    if status.is_success() {
+        let body = response
+            .json::<T>()
+            .map_err(|err| from_reqwest_error("Failed to decode response body", err))?;
+
+        trace!("Received response from Vespa API: {body:?}");
+
+        Ok(body)
+    } else {
+        let error = search_error_from_failed_response(response);
+
+        trace!("Received {status} response from Vespa API: {error:?}");
+
+        Err(error)
+    }
+}
+
+/// Checks the status of a response with no body of interest
+fn check_response(response: Response) -> Result<(), SearchError> {
+    if response.status().is_success() {
+        Ok(())
+    } else {
+        Err(search_error_from_failed_response(response))
+    }
+}
+
+fn parse_text(response: Response) -> Result<String, SearchError> {
+    if response.status().is_success() {
+        response
+            .text()
+            .map_err(|err| from_reqwest_error("Failed to read response body", err))
+    } else {
+        Err(search_error_from_failed_response(response))
+    }
+}
+
+impl VespaApi {
+    /// Creates or replaces a document
+    pub fn put_document(
+        &self,
+        namespace: &str,
+        document_type: &str,
+        id: &str,
+        fields: Fields,
+    ) -> Result<(), SearchError> {
+        trace!("Putting document {id} of type: {document_type}");
+
+        let url = self.document_url(namespace, document_type, id);
+        let document = Document {
+            id: id.to_string(),
+            fields,
+        };
+        check_response(self.send(Method::POST, &url, Some(&document), "put document")?)
+    }
+
+    pub fn delete_document(
+        &self,
+        namespace: &str,
+        document_type: &str,
+        id: &str,
+    ) -> Result<(), SearchError> {
+        trace!("Deleting document {id} of type: {document_type}");
+
+        let url = self.document_url(namespace, document_type, id);
+        check_response(self.send(Method::DELETE, &url, None::<&()>, "delete document")?)
+    }
+
+    pub fn get_document(
+        &self,
+        namespace: &str,
+        document_type: &str,
+        id: &str,
+    ) -> Result<Option<Document>, SearchError> {
+        trace!("Getting document {id} of type: {document_type}");
+
+        let url = self.document_url(namespace, document_type, id);
+        let response = self.send(Method::GET, &url, None::<&()>, "get document")?;
+
+        if response.status() == 404 {
+            Ok(None)
+        } else {
+            Ok(Some(parse_response(response)?))
+        }
+    }
+
+    /// Deletes all the documents of a type, following the continuations of the deletion
+    pub fn delete_all(
+        &self,
+        namespace: &str,
+        document_type: &str,
+        cluster: &str,
+    ) -> Result<(), SearchError> {
+        trace!("Deleting all documents of type: {document_type}");
+
+        let mut continuation = None::<String>;
+        loop {
+            let mut url = format!(
+                "{}/document/v1/{}/{}/docid?selection=true&cluster={}",
+                self.endpoint,
+                namespace,
+                document_type,
+                urlencoding::encode(cluster)
+            );
+            if let Some(continuation) = &continuation {
+                url.push_str(&format!(
+                    "&continuation={}",
+                    urlencoding::encode(continuation)
+                ));
+            }
+            let response = self.send(Method::DELETE, &url, None::<&()>, "delete documents")?;
+            continuation = parse_response::<VisitResponse>(response)?.continuation;
+            if continuation.is_none() {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Visits the documents of a type, from the `continuation` of the previous visit
+    pub fn visit(
+        &self,
+        namespace: &str,
+        document_type: &str,
+        cluster: &str,
+        wanted: u32,
+        continuation: Option<&str>,
+    ) -> Result<VisitResponse, SearchError> {
+        trace!("Visiting documents of type: {document_type}");
+
+        let mut url = format!(
+            "{}/document/v1/{}/{}/docid?cluster={}&wantedDocumentCount={wanted}",
+            self.endpoint,
+            namespace,
+            document_type,
+            urlencoding::encode(cluster)
+        );
+        if let Some(continuation) = continuation {
+            url.push_str(&format!(
+                "&continuation={}",
+                urlencoding::encode(continuation)
+            ));
+        }
+        let response = self
+            .retry_policy
+            .execute(|| self.create_request(Method::GET, &url).send())
+            .map_err(|e| from_send_error("Failed to search", e))?;
+
+        parse_response(response)
+    }
+
+    pub fn search(&self, request: &JsonValue) -> Result<SearchResponse, SearchError> {
+        trace!("Searching: {request}");
+
+        let url = format!("{}/search/", self.endpoint);
+        let response = self
+            .retry_policy
+            .execute(|| self.create_request(Method::POST, &url).json(request).send())
+            .map_err(|e| from_send_error("Failed to search", e))?;
+
+        parse_response(response)
+    }
+
+    /// Lists the schema files of the active application
+    pub fn list_schema_files(&self) -> Result<Vec<String>, SearchError> {
+        trace!("Listing schema files");
+
+        let url = format!(
+            "{}/{ACTIVE_APPLICATION_PATH}/content/schemas/",
+            self.config_server_url()?
+        );
+        let response = self.send(Method::GET, &url, None::<&()>, "list schemas")?;
+        if response.status() == 404 {
+            return Ok(vec![]);
+        }
+        parse_response(response)
+    }
+
+    /// Reads a file of the active application, `None` if there is no such file
+    pub fn get_application_file(&self, path: &str) -> Result<Option<String>, SearchError> {
+        trace!("Getting application file: {path}");
+
+        let url = format!(
+            "{}/{ACTIVE_APPLICATION_PATH}/content/{path}",
+            self.config_server_url()?
+        );
+        let response = self.send(Method::GET, &url, None::<&()>, "get application file")?;
+        if response.status() == 404 {
+            return Ok(None);
+        }
+        parse_text(response).map(Some)
+    }
+
+    /// Creates a deployment session from the active application
+    pub fn create_session(&self) -> Result<Session, SearchError> {
+        trace!("Creating deployment session");
+
+        let config_server_url = self.config_server_url()?;
+        let from = format!("{config_server_url}/{ACTIVE_APPLICATION_PATH}");
+        let url = format!(
+            "{config_server_url}/application/v2/tenant/default/session?from={}",
+            urlencoding::encode(&from)
+        );
+        parse_response(self.send(Method::POST, &url, None::<&()>, "create session")?)
+    }
+
+    pub fn get_session_file(&self, session: &Session, path: &str) -> Result<String, SearchError> {
+        trace!("Getting session file: {path}");
+
+        let url = format!("{}/content/{path}", self.session_url(session)?);
+        parse_text(self.send(Method::GET, &url, None::<&()>, "get session file")?)
+    }
+
+    pub fn put_session_file(
+        &self,
+        session: &Session,
+        path: &str,
+        content: String,
+    ) -> Result<(), SearchError> {
+        trace!("Putting session file: {path}");
+
+        let url = format!("{}/content/{path}", self.session_url(session)?);
+        let response = self
+            .client
+            .put(&url)
+            .header("Content-Type", "text/plain")
+            .header(CORRELATION_ID_HEADER, correlation::current())
+            .body(content)
+            .send()
+            .map_err(|e| from_send_error("Failed to put session file", e))?;
+
+        check_response(response)
+    }
+
+    pub fn delete_session_file(&self, session: &Session, path: &str) -> Result<(), SearchError> {
+        trace!("Deleting session file: {path}");
+
+        let url = format!("{}/content/{path}", self.session_url(session)?);
+        check_response(self.send(Method::DELETE, &url, None::<&()>, "delete session file")?)
+    }
+
+    /// Prepares and activates a session, deploying its changes to the application
+    pub fn activate(&self, session: &Session) -> Result<(), SearchError> {
+        trace!("Activating session: {}", session.session_id);
+
+        let url = format!("{}/prepared", self.session_url(session)?);
+        check_response(self.send(Method::PUT, &url, None::<&()>, "prepare session")?)?;
+
+        let url = format!("{}/active", self.session_url(session)?);
+        check_response(self.send(Method::PUT, &url, None::<&()>, "activate session")?)
+    }
+}
@@ -0,0 +1,956 @@
+use crate::client::{Document, Fields, ResultNode};
+use golem_search::config::{get_max_pagination_depth_config, search_filters_match_any};
+use golem_search::error::{invalid_query, unsupported};
+use golem_search::golem::search::types::{
+    Doc, FieldType, Schema, SchemaField, SearchError, SearchHit, SearchQuery,
+};
+use serde_json::{json, Map as JsonMap, Value as JsonValue};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const DEFAULT_PER_PAGE: u32 = 20;
+
+/// Number of values returned for each facet
+const MAX_FACET_VALUES: u32 = 100;
+
+/// Hit fields added by Vespa rather than stored in the document
+const SYSTEM_FIELDS: [&str; 3] = ["documentid", "sddocname", "summaryfeatures"];
+
+/// The document type of an index. Vespa schema names start with a letter and only contain
+/// letters, digits and underscores.
+pub fn document_type(index: &str) -> String {
+    let name = index
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect::<String>();
+    match name.chars().next() {
+        Some(first) if first.is_ascii_alphabetic() => name,
+        _ => format!("i{name}"),
+    }
+}
+
+/// Path of the schema file of a document type in the application package
+pub fn schema_file(document_type: &str) -> String {
+    format!("schemas/{document_type}.sd")
+}
+
+/// The document types of the schema file URLs listed by the config server
+pub fn schema_names(files: Vec<String>) -> Vec<String> {
+    let mut names = files
+        .iter()
+        .filter_map(|file| file.rsplit('/').next()?.strip_suffix(".sd"))
+        .map(str::to_string)
+        .collect::<Vec<_>>();
+    names.sort();
+    names
+}
+
+/// The user-specified part of a Vespa document id such as `id:namespace:type::user-id`
+pub fn user_id(document_id: &str) -> &str {
+    match document_id.strip_prefix("id:") {
+        Some(rest) => rest.split_once("::").map_or(document_id, |(_, id)| id),
+        None => document_id,
+    }
+}
+
+pub fn doc_to_fields(doc: &Doc) -> Result<Fields, SearchError> {
+    match serde_json::from_str::<JsonValue>(&doc.content) {
+        Ok(JsonValue::Object(fields)) => Ok(fields),
+        Ok(_) => Err(invalid_query("Document content must be a JSON object")),
+        Err(e) => Err(invalid_query(format!("Invalid document content: {e}"))),
+    }
+}
+
+pub fn document_to_doc(id: String, document: Document) -> Doc {
+    Doc {
+        id,
+        content: JsonValue::Object(document.fields).to_string(),
+    }
+}
+
+pub fn document_to_hit(document: Document) -> SearchHit {
+    SearchHit {
+        id: user_id(&document.id).to_string(),
+        score: None,
+        content: Some(JsonValue::Object(document.fields).to_string()),
+        highlights: None,
+    }
+}
+
+fn field_to_sd(field: &SchemaField) -> String {
+    let data_type = match field.field_type {
+        FieldType::Text | FieldType::Keyword => "string",
+        FieldType::Integer => "int",
+        FieldType::Float => "double",
+        FieldType::Boolean => "bool",
+        FieldType::Date => "long",
+        FieldType::GeoPoint => "position",
+    };
+    let mut indexing = vec!["summary"];
+    let mut fast_search = false;
+    match field.field_type {
+        FieldType::Text => {
+            if field.index {
+                indexing.push("index");
+            }
+            if field.facet || field.sort {
+                indexing.push("attribute");
+            }
+        }
+        _ => {
+            indexing.push("attribute");
+            fast_search = field.index || field.facet;
+        }
+    }
+
+    let mut sd = format!(
+        "        field {} type {data_type} {{\n            indexing: {}\n",
+        field.name,
+        indexing.join(" | ")
+    );
+    if fast_search {
+        sd.push_str("            attribute: fast-search\n");
+    }
+    sd.push_str("        }\n");
+    sd
+}
+
+fn searched_fields(schema: &Schema) -> Vec<&str> {
+    schema
+        .fields
+        .iter()
+        .filter(|field| field.field_type == FieldType::Text && field.index)
+        .map(|field| field.name.as_str())
+        .collect()
+}
+
+/// Renders a schema as the Vespa schema definition of a document type. Text fields are indexed
+/// for full-text search and make up the `default` fieldset searched by the query text, other
+/// fields are attributes, and dates are stored as `long` timestamps.
+pub fn schema_to_sd(document_type: &str, schema: &Schema) -> String {
+    let mut sd = format!("schema {document_type} {{\n    document {document_type} {{\n");
+    for field in &schema.fields {
+        sd.push_str(&field_to_sd(field));
+    }
+    sd.push_str("    }\n");
+
+    let searched = searched_fields(schema);
+    if !searched.is_empty() {
+        sd.push_str(&format!(
+            "    fieldset default {{\n        fields: {}\n    }}\n",
+            searched.join(", ")
+        ));
+    }
+    sd.push_str("}\n");
+    sd
+}
+
+/// Reads the fields of a Vespa schema definition
+pub fn sd_to_schema(sd: &str) -> Schema {
+    let mut fields = Vec::new();
+    let mut current: Option<(String, String, String, bool)> = None;
+
+    for line in sd.lines().map(str::trim) {
+        if let Some(rest) = line.strip_prefix("field ") {
+            let mut parts = rest.split_whitespace();
+            let name = parts.next().unwrap_or_default().to_string();
+            let data_type = match parts.next() {
+                Some("type") => parts.next().unwrap_or_default(),
+                _ => "",
+            };
+            current = Some((
+                name,
+                data_type.trim_end_matches('{').to_string(),
+                String::new(),
+                false,
+            ));
+        } else if let Some((_, _, indexing, fast_search)) = &mut current {
+            if let Some(rest) = line.strip_prefix("indexing:") {
+                *indexing = rest.trim().to_string();
+            } else if line.starts_with("attribute:") && line.contains("fast-search") {
+                *fast_search = true;
+            } else if line.starts_with('}') {
+                let (name, data_type, indexing, fast_search) = current.take().unwrap();
+                fields.push(sd_field(name, &data_type, &indexing, fast_search));
+            }
+        }
+    }
+
+    Schema {
+        fields,
+        primary_key: None,
+    }
+}
+
+fn sd_field(name: String, data_type: &str, indexing: &str, fast_search: bool) -> SchemaField {
+    let steps = indexing.split('|').map(str::trim).collect::<Vec<_>>();
+    let indexed = steps.contains(&"index");
+    let attribute = steps.contains(&"attribute");
+
+    let field_type = match data_type {
+        "string" if indexed => FieldType::Text,
+        "string" => FieldType::Keyword,
+        "int" | "byte" => FieldType::Integer,
+        "long" => FieldType::Date,
+        "float" | "double" => FieldType::Float,
+        "bool" => FieldType::Boolean,
+        "position" => FieldType::GeoPoint,
+        _ => FieldType::Keyword,
+    };
+    SchemaField {
+        name,
+        field_type,
+        required: false,
+        facet: attribute && field_type == FieldType::Keyword,
+        sort: attribute && field_type != FieldType::GeoPoint,
+        index: indexed || fast_search,
+    }
+}
+
+/// Position of the `}` closing the block whose `{` is at or after `start`
+fn closing_brace(sd: &str, start: usize) -> Option<usize> {
+    let mut depth = 0;
+    for (i, c) in sd[start..].char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' if depth == 1 => return Some(start + i),
+            '}' => depth -= 1,
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Position of the start of the line containing position `i`
+fn line_start(text: &str, i: usize) -> usize {
+    text[..i].rfind('\n').map_or(0, |newline| newline + 1)
+}
+
+/// Adds new fields to a schema definition, keeping everything else as written. The new text
+/// fields are added to the `default` fieldset.
+pub fn add_fields_to_sd(
+    sd: &str,
+    document_type: &str,
+    fields: &[SchemaField],
+) -> Result<String, SearchError> {
+    let invalid = || invalid_query(format!("Unsupported schema definition of {document_type}"));
+
+    let document = sd
+        .find(&format!("document {document_type}"))
+        .ok_or_else(invalid)?;
+    let document_end = line_start(sd, closing_brace(sd, document).ok_or_else(invalid)?);
+    let mut updated = sd[..document_end].to_string();
+    for field in fields {
+        updated.push_str(&field_to_sd(field));
+    }
+    updated.push_str(&sd[document_end..]);
+
+    let searched = searched_fields(&Schema {
+        fields: fields.to_vec(),
+        primary_key: None,
+    });
+    if searched.is_empty() {
+        return Ok(updated);
+    }
+    match updated.find("fieldset default") {
+        Some(fieldset) => {
+            let fields_line = updated[fieldset..]
+                .find("fields:")
+                .map(|i| fieldset + i)
+                .ok_or_else(invalid)?;
+            let line_end = updated[fields_line..]
+                .find('\n')
+                .map_or(updated.len(), |i| fields_line + i);
+            updated.insert_str(line_end, &format!(", {}", searched.join(", ")));
+        }
+        None => {
+            let schema_end = line_start(&updated, updated.rfind('}').ok_or_else(invalid)?);
+            updated.insert_str(
+                schema_end,
+                &format!(
+                    "    fieldset default {{\n        fields: {}\n    }}\n",
+                    searched.join(", ")
+                ),
+            );
+        }
+    }
+    Ok(updated)
+}
+
+/// Adds a document type to the content cluster of the application's `services.xml`
+pub fn add_document_type(services: &str, document_type: &str) -> Result<String, SearchError> {
+    if services.contains(&format!("<document type=\"{document_type}\"")) {
+        return Ok(services.to_string());
+    }
+
+    let element = format!("<document type=\"{document_type}\" mode=\"index\"/>");
+    if let Some(start) = services.find("<documents/>") {
+        let mut updated = services.to_string();
+        updated.replace_range(
+            start..start + "<documents/>".len(),
+            &format!("<documents>\n            {element}\n        </documents>"),
+        );
+        return Ok(updated);
+    }
+    let start = services.find("<documents").ok_or_else(|| {
+        invalid_query("The application's services.xml has no content cluster with documents")
+    })?;
+    let tag_end = services[start..]
+        .find('>')
+        .map(|i| start + i + 1)
+        .ok_or_else(|| invalid_query("Invalid services.xml"))?;
+
+    let mut updated = services.to_string();
+    updated.insert_str(tag_end, &format!("\n            {element}"));
+    Ok(updated)
+}
+
+/// Removes a document type from the content cluster of the application's `services.xml`
+pub fn remove_document_type(services: &str, document_type: &str) -> String {
+    let Some(start) = services.find(&format!("<document type=\"{document_type}\"")) else {
+        return services.to_string();
+    };
+    let end = match (
+        services[start..].find("/>"),
+        services[start..].find("</document>"),
+    ) {
+        (Some(i), Some(j)) if j < i => start + j + "</document>".len(),
+        (Some(i), _) => start + i + "/>".len(),
+        (None, Some(j)) => start + j + "</document>".len(),
+        (None, None) => return services.to_string(),
+    };
+    let line_start = services[..start]
+        .rfind('\n')
+        .filter(|&i| services[i + 1..start].trim().is_empty())
+        .unwrap_or(start);
+
+    let mut updated = services.to_string();
+    updated.replace_range(line_start..end, "");
+    updated
+}
+
+/// The civil date of a number of days since the Unix epoch
+fn civil_date(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = (if mp < 10 { mp + 3 } else { mp - 9 }) as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+/// Adds an override allowing the removal of a document type until tomorrow to the application's
+/// `validation-overrides.xml`, as Vespa refuses to deploy such removals otherwise
+pub fn allow_document_type_removal(overrides: Option<&str>, now: SystemTime) -> String {
+    let days = now
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs() / 86_400)
+        .unwrap_or_default() as i64;
+    let (year, month, day) = civil_date(days + 1);
+    let allow =
+        format!("<allow until=\"{year:04}-{month:02}-{day:02}\">content-type-removal</allow>");
+
+    match overrides
+        .and_then(|overrides| Some((overrides, overrides.find("</validation-overrides>")?)))
+    {
+        Some((overrides, end)) => {
+            let mut updated = overrides.to_string();
+            updated.insert_str(end, &format!("    {allow}\n"));
+            updated
+        }
+        None => format!("<validation-overrides>\n    {allow}\n</validation-overrides>\n"),
+    }
+}
+
+fn parse_value(value: &str) -> JsonValue {
+    let value = value.trim();
+    if let Some(quoted) = value
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+    {
+        return JsonValue::String(quoted.to_string());
+    }
+    match serde_json::from_str::<JsonValue>(value) {
+        Ok(value @ (JsonValue::Number(_) | JsonValue::Bool(_))) => value,
+        _ => JsonValue::String(value.to_string()),
+    }
+}
+
+/// A YQL string literal
+fn yql_string(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn is_field_name(key: &str) -> bool {
+    !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.')
+}
+
+/// Converts a filter to a YQL condition. Filters are either YQL conditions in parentheses, or
+/// `field:value`, `field=value`, `field!=value`, and `field>value` style comparisons with `>`,
+/// `>=`, `<` and `<=`.
+fn filter_to_yql(filter: &str) -> Result<String, SearchError> {
+    let filter = filter.trim();
+    if filter.starts_with('(') {
+        return Ok(filter.to_string());
+    }
+
+    let position = filter
+        .find([':', '=', '!', '<', '>'])
+        .ok_or_else(|| invalid_query(format!("Unsupported filter `{filter}`")))?;
+    let key = filter[..position].trim();
+    let rest = &filter[position..];
+    let (op, value) = [">=", "<=", "!=", ">", "<", "=", ":"]
+        .into_iter()
+        .find_map(|op| Some((op, rest.strip_prefix(op)?)))
+        .ok_or_else(|| invalid_query(format!("Unsupported filter `{filter}`")))?;
+    if !is_field_name(key) || value.trim().is_empty() {
+        return Err(invalid_query(format!("Unsupported filter `{filter}`")));
+    }
+    let value = parse_value(value);
+
+    let equals = match &value {
+        JsonValue::String(value) => format!("{key} contains {}", yql_string(value)),
+        value => format!("{key} = {value}"),
+    };
+    match op {
+        ":" | "=" => Ok(equals),
+        "!=" => Ok(format!("!({equals})")),
+        op if value.is_number() => Ok(format!("{key} {op} {value}")),
+        _ => Err(invalid_query(format!(
+            "Range filter `{filter}` needs a numeric value"
+        ))),
+    }
+}
+
+/// The YQL condition of a query's text and filters
+fn query_to_condition(query: &SearchQuery) -> Result<String, SearchError> {
+    let mut conditions = Vec::new();
+
+    if query.q.as_deref().is_some_and(|q| !q.trim().is_empty()) {
+        conditions.push("userQuery()".to_string());
+    }
+
+    let filters = query
+        .filters
+        .iter()
+        .map(|filter| filter_to_yql(filter))
+        .collect::<Result<Vec<_>, _>>()?;
+    if search_filters_match_any(query) && filters.len() > 1 {
+        conditions.push(format!("({})", filters.join(" or ")));
+    } else {
+        conditions.extend(filters);
+    }
+
+    Ok(if conditions.is_empty() {
+        "true".to_string()
+    } else {
+        conditions.join(" and ")
+    })
+}
+
+/// The `order by` clause of a query, from its `field`, `field:asc`, `field:desc` or `-field`
+/// sort entries
+fn query_to_order_by(sort: &[String]) -> Result<String, SearchError> {
+    if sort.is_empty() {
+        return Ok(String::new());
+    }
+    let sort = sort
+        .iter()
+        .map(|sort| {
+            let sort = sort.trim();
+            let (key, order) = if let Some(key) = sort.strip_prefix('-') {
+                (key, "desc")
+            } else {
+                match sort.rsplit_once(':') {
+                    Some((key, order)) if order.eq_ignore_ascii_case("asc") => (key, "asc"),
+                    Some((key, order)) if order.eq_ignore_ascii_case("desc") => (key, "desc"),
+                    Some(_) => return Err(invalid_query(format!("Invalid sort `{sort}`"))),
+                    None => (sort, "asc"),
+                }
+            };
+            if !is_field_name(key) {
+                return Err(invalid_query(format!("Invalid sort `{sort}`")));
+            }
+            Ok(format!("{key} {order}"))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(format!(" order by {}", sort.join(", ")))
+}
+
+/// The grouping expression counting the values of the query's facets
+fn query_to_grouping(facets: &[String]) -> Result<String, SearchError> {
+    let groups = facets
+        .iter()
+        .map(|facet| {
+            if !is_field_name(facet) {
+                return Err(invalid_query(format!("Invalid facet `{facet}`")));
+            }
+            Ok(format!(
+                "all(group({facet}) max({MAX_FACET_VALUES}) each(output(count())))"
+            ))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(match groups.len() {
+        0 => String::new(),
+        1 => format!(" | {}", groups[0]),
+        _ => format!(" | all({})", groups.join(" ")),
+    })
+}
+
+/// Number of hits to skip and number of hits per page of a query
+pub fn query_window(query: &SearchQuery) -> Result<(u32, u32), SearchError> {
+    let per_page = query.per_page.unwrap_or(DEFAULT_PER_PAGE);
+    let offset = query.offset.unwrap_or_else(|| {
+        query
+            .page
+            .unwrap_or(1)
+            .saturating_sub(1)
+            .saturating_mul(per_page)
+    });
+    if offset.saturating_add(per_page) > get_max_pagination_depth_config() {
+        return Err(invalid_query(format!(
+            "Requested hits beyond the maximum pagination depth of {}",
+            get_max_pagination_depth_config()
+        )));
+    }
+    Ok((offset, per_page))
+}
+
+/// Builds the query API request of a search. The provider parameters are added to the request
+/// as query API parameters, for example `{"ranking.profile": "bm25"}`.
+pub fn query_to_request(
+    query: &SearchQuery,
+    document_type: &str,
+) -> Result<JsonValue, SearchError> {
+    if query.highlight.is_some() {
+        return Err(unsupported("highlighting"));
+    }
+
+    let (offset, per_page) = query_window(query)?;
+    let config = query.config.as_ref();
+
+    let attributes = config
+        .map(|config| config.attributes_to_retrieve.clone())
+        .unwrap_or_default();
+    let selection = if attributes.is_empty() {
+        "*".to_string()
+    } else {
+        attributes.join(", ")
+    };
+    let yql = format!(
+        "select {selection} from sources {document_type} where {}{}{}",
+        query_to_condition(query)?,
+        query_to_order_by(&query.sort)?,
+        query_to_grouping(&query.facets)?
+    );
+
+    let mut request = JsonMap::new();
+    request.insert("yql".to_string(), json!(yql));
+    request.insert("hits".to_string(), json!(per_page));
+    request.insert("offset".to_string(), json!(offset));
+    if let Some(q) = query.q.as_deref().map(str::trim).filter(|q| !q.is_empty()) {
+        request.insert("query".to_string(), json!(q));
+    }
+
+    if let Some(params) = config.and_then(|config| config.provider_params.as_deref()) {
+        let params = serde_json::from_str::<JsonMap<String, JsonValue>>(params)
+            .map_err(|e| invalid_query(format!("Invalid provider parameters: {e}")))?;
+        request.extend(params.into_iter().filter(|(key, _)| key != "headers"));
+    }
+
+    Ok(JsonValue::Object(request))
+}
+
+fn node_to_hit(node: ResultNode) -> SearchHit {
+    let mut fields = node.fields;
+    let id = match fields.get("documentid") {
+        Some(JsonValue::String(id)) => user_id(id).to_string(),
+        _ => user_id(&node.id).to_string(),
+    };
+    for field in SYSTEM_FIELDS {
+        fields.remove(field);
+    }
+    SearchHit {
+        id,
+        score: node.relevance,
+        content: Some(JsonValue::Object(fields).to_string()),
+        highlights: None,
+    }
+}
+
+/// Facet counts in the same shape as the other providers: `{"field": {"value": count}}`
+fn grouping_to_facets(groupings: &[ResultNode]) -> Option<String> {
+    let facets = groupings
+        .iter()
+        .flat_map(|grouping| &grouping.children)
+        .filter_map(|list| {
+            let field = list
+                .label
+                .clone()
+                .or_else(|| list.id.strip_prefix("grouplist:").map(str::to_string))?;
+            let counts = list
+                .children
+                .iter()
+                .filter_map(|group| {
+                    let value = match group.value.as_ref()? {
+                        JsonValue::String(value) => value.clone(),
+                        value => value.to_string(),
+                    };
+                    Some((value, group.fields.get("count()")?.clone()))
+                })
+                .collect::<JsonMap<_, _>>();
+            Some((field, JsonValue::Object(counts)))
+        })
+        .collect::<JsonMap<_, _>>();
+    (!facets.is_empty()).then(|| JsonValue::Object(facets).to_string())
+}
+
+/// The hits, total and facet counts of the result tree of a query
+pub fn parse_results(root: ResultNode) -> (Vec<SearchHit>, Option<u32>, Option<String>) {
+    let total = root
+        .fields
+        .get("totalCount")
+        .and_then(JsonValue::as_u64)
+        .map(|total| total as u32);
+
+    let (groupings, hits): (Vec<_>, Vec<_>) = root
+        .children
+        .into_iter()
+        .partition(|node| node.id.starts_with("group:"));
+
+    (
+        hits.into_iter().map(node_to_hit).collect(),
+        total,
+        grouping_to_facets(&groupings),
+    )
+}
+
+pub fn create_retry_query(original_query: &SearchQuery, partial_hits: &[SearchHit]) -> SearchQuery {
+    let mut retry_query = original_query.clone();
+
+    let current_offset = original_query.offset.unwrap_or(0);
+    let per_page = original_query.per_page.unwrap_or(DEFAULT_PER_PAGE);
+    let hits_received = partial_hits.len() as u32;
+
+    retry_query.offset = Some(current_offset + hits_received.min(per_page));
+
+    retry_query
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use golem_search::golem::search::types::{FilterMode, SearchConfig};
+    use std::time::Duration;
+
+    fn query(q: Option<&str>, filters: &[&str]) -> SearchQuery {
+        SearchQuery {
+            q: q.map(str::to_string),
+            filters: filters.iter().map(|filter| filter.to_string()).collect(),
+            sort: vec![],
+            facets: vec![],
+            page: None,
+            per_page: None,
+            offset: None,
+            highlight: None,
+            config: None,
+        }
+    }
+
+    fn field(name: &str, field_type: FieldType, facet: bool, sort: bool) -> SchemaField {
+        SchemaField {
+            name: name.to_string(),
+            field_type,
+            required: false,
+            facet,
+            sort,
+            index: true,
+        }
+    }
+
+    fn config(provider_params: Option<&str>, filter_mode: Option<FilterMode>) -> SearchConfig {
+        SearchConfig {
+            timeout_ms: None,
+            boost_fields: vec![],
+            attributes_to_retrieve: vec![],
+            language: None,
+            typo_tolerance: None,
+            exact_match_boost: None,
+            provider_params: provider_params.map(str::to_string),
+            profile: None,
+            filter_mode,
+            post_filters: vec![],
+            rescore: None,
+            fallbacks: vec![],
+        }
+    }
+
+    fn books() -> Schema {
+        Schema {
+            fields: vec![
+                field("title", FieldType::Text, false, false),
+                field("genre", FieldType::Keyword, true, false),
+                field("year", FieldType::Integer, false, true),
+                field("published", FieldType::Date, false, true),
+                field("location", FieldType::GeoPoint, false, false),
+            ],
+            primary_key: None,
+        }
+    }
+
+    #[test]
+    fn test_names_and_ids() {
+        assert_eq!(document_type("books"), "books");
+        assert_eq!(document_type("my-books.v2"), "my_books_v2");
+        assert_eq!(document_type("2024"), "i2024");
+        assert_eq!(user_id("id:golem:books::doc::1"), "doc::1");
+        assert_eq!(user_id("doc1"), "doc1");
+        assert_eq!(
+            schema_names(vec![
+                "http://config:19071/application/v2/.../content/schemas/movies.sd".to_string(),
+                "http://config:19071/application/v2/.../content/schemas/books.sd".to_string(),
+            ]),
+            vec!["books".to_string(), "movies".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_schema_round_trip() {
+        let sd = schema_to_sd("books", &books());
+        assert!(sd.starts_with("schema books {\n    document books {\n"));
+        assert!(sd.contains(
+            "        field genre type string {\n            indexing: summary | attribute\n            attribute: fast-search\n        }\n"
+        ));
+        assert!(sd.contains("    fieldset default {\n        fields: title\n    }\n"));
+
+        let schema = sd_to_schema(&sd);
+        let fields = schema
+            .fields
+            .iter()
+            .map(|field| (field.name.as_str(), field.field_type))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            fields,
+            vec![
+                ("title", FieldType::Text),
+                ("genre", FieldType::Keyword),
+                ("year", FieldType::Integer),
+                ("published", FieldType::Date),
+                ("location", FieldType::GeoPoint),
+            ]
+        );
+        assert!(schema.fields[0].index);
+        assert!(schema.fields[1].facet);
+        assert!(schema.fields[2].sort);
+    }
+
+    #[test]
+    fn test_add_fields_to_sd() {
+        let sd = schema_to_sd("books", &books());
+        let updated = add_fields_to_sd(
+            &sd,
+            "books",
+            &[
+                field("summary", FieldType::Text, false, false),
+                field("pages", FieldType::Integer, false, true),
+            ],
+        )
+        .unwrap();
+
+        let schema = sd_to_schema(&updated);
+        assert_eq!(schema.fields.len(), 7);
+        assert_eq!(schema.fields[5].name, "summary");
+        assert!(updated.contains("fields: title, summary\n"));
+        assert!(add_fields_to_sd(&sd, "movies", &[]).is_err());
+
+        let untexted = schema_to_sd(
+            "plain",
+            &Schema {
+                fields: vec![],
+                primary_key: None,
+            },
+        );
+        let updated = add_fields_to_sd(
+            &untexted,
+            "plain",
+            &[field("title", FieldType::Text, false, false)],
+        )
+        .unwrap();
+        assert!(updated.ends_with("    fieldset default {\n        fields: title\n    }\n}\n"));
+    }
+
+    #[test]
+    fn test_document_types_in_services() {
+        let services = "<services>\n    <content id=\"content\" version=\"1.0\">\n        <documents>\n            <document type=\"movies\" mode=\"index\"/>\n        </documents>\n    </content>\n</services>\n";
+
+        let added = add_document_type(services, "books").unwrap();
+        assert!(added.contains(
+            "<documents>\n            <document type=\"books\" mode=\"index\"/>\n            <document type=\"movies\""
+        ));
+        assert_eq!(add_document_type(&added, "books").unwrap(), added);
+        assert_eq!(remove_document_type(&added, "books"), services);
+
+        assert!(add_document_type("<services/>", "books").is_err());
+        assert!(add_document_type("<documents/>", "books")
+            .unwrap()
+            .contains("<document type=\"books\" mode=\"index\"/>"));
+    }
+
+    #[test]
+    fn test_allow_document_type_removal() {
+        // 2024-02-28T12:00:00Z
+        let now = UNIX_EPOCH + Duration::from_secs(1_709_121_600);
+        assert_eq!(
+            allow_document_type_removal(None, now),
+            "<validation-overrides>\n    <allow until=\"2024-02-29\">content-type-removal</allow>\n</validation-overrides>\n"
+        );
+
+        let existing = "<validation-overrides>\n    <allow until=\"2024-03-01\">field-type-change</allow>\n</validation-overrides>\n";
+        let updated = allow_document_type_removal(Some(existing), now);
+        assert!(updated.contains("field-type-change"));
+        assert!(updated.contains(
+            "<allow until=\"2024-02-29\">content-type-removal</allow>\n</validation-overrides>"
+        ));
+    }
+
+    #[test]
+    fn test_filters_to_yql() {
+        assert_eq!(
+            filter_to_yql("genre:fiction").unwrap(),
+            "genre contains \"fiction\""
+        );
+        assert_eq!(
+            filter_to_yql("title = \"say \\\"hi\\\"\"").unwrap(),
+            "title contains \"say \\\\\\\"hi\\\\\\\"\""
+        );
+        assert_eq!(filter_to_yql("year>=1950").unwrap(), "year >= 1950");
+        assert_eq!(filter_to_yql("in_stock:true").unwrap(), "in_stock = true");
+        assert_eq!(
+            filter_to_yql("genre!=romance").unwrap(),
+            "!(genre contains \"romance\")"
+        );
+        assert_eq!(
+            filter_to_yql("(year < 2000 or year > 2010)").unwrap(),
+            "(year < 2000 or year > 2010)"
+        );
+        assert!(filter_to_yql("year>recent").is_err());
+        assert!(filter_to_yql("no operator").is_err());
+        assert!(filter_to_yql("year) or (true:1").is_err());
+    }
+
+    #[test]
+    fn test_query_to_request() {
+        let mut query = query(Some("dune"), &["genre:fiction", "year>1950"]);
+        query.sort = vec!["-year".to_string(), "title".to_string()];
+        query.facets = vec!["genre".to_string()];
+        query.page = Some(3);
+        query.per_page = Some(10);
+        query.config = Some(config(
+            Some(r#"{"ranking.profile": "bm25", "headers": {"X-Tenant": "a"}}"#),
+            None,
+        ));
+
+        assert_eq!(
+            query_to_request(&query, "books").unwrap(),
+            json!({
+                "yql": "select * from sources books where userQuery() and genre contains \"fiction\" \
+                        and year > 1950 order by year desc, title asc \
+                        | all(group(genre) max(100) each(output(count())))",
+                "hits": 10,
+                "offset": 20,
+                "query": "dune",
+                "ranking.profile": "bm25",
+            })
+        );
+    }
+
+    #[test]
+    fn test_query_to_request_matching_any() {
+        let mut query = query(None, &["genre:fiction", "genre:poetry"]);
+        query.facets = vec!["genre".to_string(), "year".to_string()];
+        query.config = Some(config(None, Some(FilterMode::Any)));
+
+        assert_eq!(
+            query_to_request(&query, "books").unwrap()["yql"],
+            "select * from sources books where (genre contains \"fiction\" or genre contains \"poetry\") \
+             | all(all(group(genre) max(100) each(output(count()))) \
+             all(group(year) max(100) each(output(count()))))"
+        );
+        assert_eq!(
+            query_to_request(&self::query(None, &[]), "books").unwrap()["yql"],
+            "select * from sources books where true"
+        );
+    }
+
+    #[test]
+    fn test_parse_results() {
+        let root = serde_json::from_value::<ResultNode>(json!({
+            "id": "toplevel",
+            "fields": { "totalCount": 42 },
+            "children": [
+                {
+                    "id": "id:golem:books::doc1",
+                    "relevance": 0.5,
+                    "fields": {
+                        "sddocname": "books",
+                        "documentid": "id:golem:books::doc1",
+                        "title": "Dune"
+                    }
+                },
+                {
+                    "id": "group:root:0",
+                    "children": [{
+                        "id": "grouplist:genre",
+                        "label": "genre",
+                        "children": [
+                            { "id": "group:string:fiction", "value": "fiction", "fields": { "count()": 2 } },
+                            { "id": "group:long:1965", "value": 1965, "fields": { "count()": 1 } }
+                        ]
+                    }]
+                }
+            ]
+        }))
+        .unwrap();
+
+        let (hits, total, facets) = parse_results(root);
+        assert_eq!(total, Some(42));
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].id, "doc1");
+        assert_eq!(hits[0].score, Some(0.5));
+        assert_eq!(hits[0].content.as_deref(), Some(r#"{"title":"Dune"}"#));
+        assert_eq!(
+            serde_json::from_str::<JsonValue>(&facets.unwrap()).unwrap(),
+            json!({ "genre": { "fiction": 2, "1965": 1 } })
+        );
+    }
+
+    #[test]
+    fn test_create_retry_query() {
+        let mut original = query(Some("dune"), &[]);
+        original.offset = Some(10);
+        original.per_page = Some(5);
+        let hits = vec![
+            SearchHit {
+                id: "doc1".to_string(),
+                score: None,
+                content: None,
+                highlights: None,
+            };
+            7
+        ];
+
+        assert_eq!(create_retry_query(&original, &hits).offset, Some(15));
+    }
+}
@@ -0,0 +1,439 @@
+use crate::client::VespaApi;
+use crate::conversions::{
+    add_document_type, add_fields_to_sd, allow_document_type_removal, create_retry_query,
+    doc_to_fields, document_to_doc, document_to_hit, document_type, parse_results,
+    query_to_request, query_window, remove_document_type, schema_file, schema_names, schema_to_sd,
+    sd_to_schema,
+};
+use golem_rust::wasm_rpc::Pollable;
+use golem_search::config::{
+    get_config_with_default, get_optional_config, profile_key, search_headers, search_profile,
+    search_timeout, worker_headers, worker_profile,
+};
+use golem_search::deterministic;
+use golem_search::durability::{DurableSearch, ExtendedGuest};
+use golem_search::error::{index_not_found, invalid_query, unsupported};
+use golem_search::golem::search::core::{Guest, GuestSearchStream, SearchStream};
+use golem_search::golem::search::types::{
+    Doc, DocumentId, IndexName, IndexPage, PageRequest, Schema, SearchError, SearchHit,
+    SearchQuery, SearchResults,
+};
+use golem_search::pagination::index_page;
+use golem_search::LOGGING_STATE;
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+
+mod client;
+mod conversions;
+
+/// Search stream over the matches of a query. Queries without text, filters or sort visit the
+/// documents with the Visit API, other queries are paged through with offsets.
+struct VespaSearchStream {
+    client: VespaApi,
+    index_name: String,
+    query: SearchQuery,
+    offset: Cell<u32>,
+    /// The continuation of the visit, empty before the first visit and `None` for streams paged
+    /// with offsets
+    continuation: RefCell<Option<String>>,
+    finished: Cell<bool>,
+}
+
+impl VespaSearchStream {
+    pub fn new(client: VespaApi, index_name: String, query: SearchQuery) -> Self {
+        let offset = query_window(&query).map(|(offset, _)| offset).unwrap_or(0);
+        let visits = query.q.as_deref().is_none_or(|q| q.trim().is_empty())
+            && query.filters.is_empty()
+            && query.sort.is_empty()
+            && offset == 0;
+        Self {
+            client,
+            index_name,
+            query,
+            offset: Cell::new(offset),
+            continuation: RefCell::new(visits.then(String::new)),
+            finished: Cell::new(false),
+        }
+    }
+
+    pub fn subscribe(&self) -> Pollable {
+        golem_rust::bindings::wasi::clocks::monotonic_clock::subscribe_duration(0)
+    }
+
+    /// Reads the next page of hits, and whether there may be more
+    fn next_page(&self, per_page: u32) -> Result<(Vec<SearchHit>, bool), SearchError> {
+        let continuation = self.continuation.borrow().clone();
+        if let Some(continuation) = continuation {
+            let profile = search_profile(&self.query);
+            let response = self.client.visit(
+                &VespaComponent::namespace(profile.as_deref()),
+                &document_type(&self.index_name),
+                &VespaComponent::content_cluster(profile.as_deref()),
+                per_page,
+                (!continuation.is_empty()).then_some(continuation.as_str()),
+            )?;
+            let more = response.continuation.is_some();
+            *self.continuation.borrow_mut() = response.continuation;
+            let hits = response
+                .documents
+                .into_iter()
+                .map(document_to_hit)
+                .collect();
+            Ok((hits, more))
+        } else {
+            let mut query = self.query.clone();
+            query.page = None;
+            query.offset = Some(self.offset.get());
+            let results = VespaComponent::search_page(&self.client, &self.index_name, &query)?;
+            let more = results.hits.len() as u32 >= per_page;
+            Ok((results.hits, more))
+        }
+    }
+}
+
+impl GuestSearchStream for VespaSearchStream {
+    fn get_next(&self) -> Option<Vec<SearchHit>> {
+        if self.finished.get() {
+            return Some(vec![]);
+        }
+
+        let per_page = query_window(&self.query)
+            .map(|(_, per_page)| per_page)
+            .unwrap_or(0);
+
+        match self.next_page(per_page) {
+            Ok((hits, more)) => {
+                if !more {
+                    self.finished.set(true);
+                }
+                self.offset.set(self.offset.get() + hits.len() as u32);
+                Some(hits)
+            }
+            Err(_) => {
+                self.finished.set(true);
+                Some(vec![])
+            }
+        }
+    }
+
+    fn blocking_get_next(&self) -> Vec<SearchHit> {
+        self.get_next().unwrap_or_default()
+    }
+}
+
+struct VespaComponent;
+
+thread_local! {
+    /// The provider clients by configuration profile, reused across calls instead of being rebuilt
+    /// for each of them
+    static CLIENTS: RefCell<HashMap<Option<String>, VespaApi>> = RefCell::new(HashMap::new());
+}
+
+impl VespaComponent {
+    const ENDPOINT_ENV_VAR: &'static str = "VESPA_ENDPOINT";
+    const API_KEY_ENV_VAR: &'static str = "VESPA_API_KEY";
+    const CONFIG_SERVER_URL_ENV_VAR: &'static str = "VESPA_CONFIG_SERVER_URL";
+    const NAMESPACE_ENV_VAR: &'static str = "VESPA_NAMESPACE";
+    const CONTENT_CLUSTER_ENV_VAR: &'static str = "VESPA_CONTENT_CLUSTER";
+
+    fn create_client() -> Result<VespaApi, SearchError> {
+        Self::create_profile_client(worker_profile().as_deref())
+            .map(|client| client.with_headers(worker_headers()))
+    }
+
+    /// Returns the client of `profile` cached for this worker, creating it from the configuration
+    /// on first use
+    fn create_profile_client(profile: Option<&str>) -> Result<VespaApi, SearchError> {
+        CLIENTS.with_borrow_mut(|clients| {
+            let profile = profile.map(str::to_string);
+            if let Some(client) = clients.get(&profile) {
+                return Ok(client.clone());
+            }
+
+            let client = Self::new_client(profile.as_deref())?;
+            clients.insert(profile, client.clone());
+            Ok(client)
+        })
+    }
+
+    /// Creates a client from the `VESPA_ENDPOINT` of the query and document APIs, the optional
+    /// `VESPA_API_KEY` token, and the optional `VESPA_CONFIG_SERVER_URL` managing the schemas
+    fn new_client(profile: Option<&str>) -> Result<VespaApi, SearchError> {
+        let endpoint = get_optional_config(profile_key(Self::ENDPOINT_ENV_VAR, profile))
+            .ok_or_else(|| SearchError::Internal("Missing Vespa endpoint".to_string()))?;
+        let api_key = get_optional_config(profile_key(Self::API_KEY_ENV_VAR, profile))
+            .filter(|api_key| !api_key.is_empty());
+        let config_server_url =
+            get_optional_config(profile_key(Self::CONFIG_SERVER_URL_ENV_VAR, profile))
+                .filter(|url| !url.is_empty());
+
+        Ok(VespaApi::new(endpoint, config_server_url, api_key))
+    }
+
+    /// The namespace of the document ids in `profile`, `golem` by default
+    fn namespace(profile: Option<&str>) -> String {
+        get_config_with_default(profile_key(Self::NAMESPACE_ENV_VAR, profile), "golem")
+    }
+
+    /// The content cluster storing the documents in `profile`, `content` by default
+    fn content_cluster(profile: Option<&str>) -> String {
+        get_config_with_default(
+            profile_key(Self::CONTENT_CLUSTER_ENV_VAR, profile),
+            "content",
+        )
+    }
+
+    /// Returns a client able to manage the schemas of the application. Vespa document types are
+    /// part of the application package, which is deployed through the config server.
+    fn schema_client() -> Result<VespaApi, SearchError> {
+        let client = Self::create_client()?;
+        if client.manages_schemas() {
+            Ok(client)
+        } else {
+            Err(unsupported("schema management without a config server"))
+        }
+    }
+
+    fn get_sd(client: &VespaApi, document_type: &str) -> Result<String, SearchError> {
+        client
+            .get_application_file(&schema_file(document_type))?
+            .ok_or_else(index_not_found)
+    }
+
+    /// Deploys the schema definition of a document type, adding the type to the content cluster
+    fn deploy_schema(
+        client: &VespaApi,
+        document_type: &str,
+        sd: String,
+    ) -> Result<(), SearchError> {
+        let session = client.create_session()?;
+        client.put_session_file(&session, &schema_file(document_type), sd)?;
+        let services = client.get_session_file(&session, "services.xml")?;
+        client.put_session_file(
+            &session,
+            "services.xml",
+            add_document_type(&services, document_type)?,
+        )?;
+        client.activate(&session)
+    }
+
+    fn search_page(
+        client: &VespaApi,
+        index: &str,
+        query: &SearchQuery,
+    ) -> Result<SearchResults, SearchError> {
+        let (offset, per_page) = query_window(query)?;
+        let request = query_to_request(query, &document_type(index))?;
+
+        let response = client.search(&request)?;
+        let (hits, total, facets) = parse_results(response.root);
+
+        Ok(SearchResults {
+            total,
+            page: Some(offset / per_page.max(1) + 1),
+            per_page: Some(per_page),
+            hits,
+            facets,
+            took_ms: None,
+            relaxation: None,
+            navigation: None,
+        })
+    }
+}
+
+impl Guest for VespaComponent {
+    type SearchStream = VespaSearchStream;
+
+    fn create_index(name: IndexName, schema: Option<Schema>) -> Result<(), SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = Self::schema_client()?;
+        let document_type = document_type(&name);
+        let schema = schema.unwrap_or(Schema {
+            fields: vec![],
+            primary_key: None,
+        });
+        Self::deploy_schema(
+            &client,
+            &document_type,
+            schema_to_sd(&document_type, &schema),
+        )
+    }
+
+    /// Deletes the documents of the index and removes its document type from the application
+    fn delete_index(name: IndexName) -> Result<(), SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = Self::schema_client()?;
+        let document_type = document_type(&name);
+        client.delete_all(
+            &Self::namespace(worker_profile().as_deref()),
+            &document_type,
+            &Self::content_cluster(worker_profile().as_deref()),
+        )?;
+
+        let session = client.create_session()?;
+        client.delete_session_file(&session, &schema_file(&document_type))?;
+        let services = client.get_session_file(&session, "services.xml")?;
+        client.put_session_file(
+            &session,
+            "services.xml",
+            remove_document_type(&services, &document_type),
+        )?;
+        let overrides = match client.get_session_file(&session, "validation-overrides.xml") {
+            Ok(overrides) => Some(overrides),
+            Err(SearchError::IndexNotFound) => None,
+            Err(error) => return Err(error),
+        };
+        client.put_session_file(
+            &session,
+            "validation-overrides.xml",
+            allow_document_type_removal(overrides.as_deref(), deterministic::now()),
+        )?;
+        client.activate(&session)
+    }
+
+    fn list_indexes(page: PageRequest) -> Result<IndexPage, SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = Self::schema_client()?;
+        index_page(schema_names(client.list_schema_files()?), &page)
+    }
+
+    fn upsert(index: IndexName, doc: Doc) -> Result<(), SearchError> {
+        Self::upsert_many(index, vec![doc])
+    }
+
+    /// Puts the documents one by one, as the document API has no batch operation
+    fn upsert_many(index: IndexName, docs: Vec<Doc>) -> Result<(), SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = Self::create_client()?;
+        let namespace = Self::namespace(worker_profile().as_deref());
+        let document_type = document_type(&index);
+
+        for doc in docs {
+            let fields = doc_to_fields(&doc)?;
+            client.put_document(&namespace, &document_type, &doc.id, fields)?;
+        }
+        Ok(())
+    }
+
+    fn delete(index: IndexName, id: DocumentId) -> Result<(), SearchError> {
+        Self::delete_many(index, vec![id])
+    }
+
+    fn delete_many(index: IndexName, ids: Vec<DocumentId>) -> Result<(), SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = Self::create_client()?;
+        let namespace = Self::namespace(worker_profile().as_deref());
+        let document_type = document_type(&index);
+
+        for id in ids {
+            client.delete_document(&namespace, &document_type, &id)?;
+        }
+        Ok(())
+    }
+
+    fn get(index: IndexName, id: DocumentId) -> Result<Option<Doc>, SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = Self::create_client()?;
+        let document = client.get_document(
+            &Self::namespace(worker_profile().as_deref()),
+            &document_type(&index),
+            &id,
+        )?;
+        Ok(document.map(|document| document_to_doc(id, document)))
+    }
+
+    fn search(index: IndexName, query: SearchQuery) -> Result<SearchResults, SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = Self::create_profile_client(search_profile(&query).as_deref())?
+            .with_request_timeout(search_timeout(&query))
+            .with_headers(search_headers(&query));
+
+        Self::search_page(&client, &index, &query)
+    }
+
+    fn stream_search(index: IndexName, query: SearchQuery) -> Result<SearchStream, SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = Self::create_profile_client(search_profile(&query).as_deref())?
+            .with_request_timeout(search_timeout(&query))
+            .with_headers(search_headers(&query));
+        let stream = VespaSearchStream::new(client, index, query);
+        Ok(SearchStream::new(stream))
+    }
+
+    fn get_schema(index: IndexName) -> Result<Schema, SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = Self::schema_client()?;
+        Ok(sd_to_schema(&Self::get_sd(
+            &client,
+            &document_type(&index),
+        )?))
+    }
+
+    /// Adds the new schema fields to the document type. Fields missing from the schema are kept,
+    /// and type changes are rejected as they need a reindexing of the documents.
+    fn update_schema(index: IndexName, schema: Schema) -> Result<(), SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = Self::schema_client()?;
+        let document_type = document_type(&index);
+        let sd = Self::get_sd(&client, &document_type)?;
+        let existing = sd_to_schema(&sd);
+
+        let mut new_fields = Vec::new();
+        for field in schema.fields {
+            match existing
+                .fields
+                .iter()
+                .find(|existing| existing.name == field.name)
+            {
+                Some(existing) if existing.field_type != field.field_type => {
+                    return Err(invalid_query(format!(
+                        "Vespa can't change the type of the `{}` field",
+                        field.name
+                    )));
+                }
+                Some(_) => {}
+                None => new_fields.push(field),
+            }
+        }
+        if new_fields.is_empty() {
+            return Ok(());
+        }
+
+        let sd = add_fields_to_sd(&sd, &document_type, &new_fields)?;
+        Self::deploy_schema(&client, &document_type, sd)
+    }
+}
+
+impl ExtendedGuest for VespaComponent {
+    fn unwrapped_stream(index: IndexName, query: SearchQuery) -> Self::SearchStream {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = Self::create_profile_client(search_profile(&query).as_deref())
+            .unwrap_or_else(|_| VespaApi::new("".to_string(), None, None))
+            .with_headers(search_headers(&query));
+
+        VespaSearchStream::new(client, index, query)
+    }
+
+    fn retry_query(original_query: &SearchQuery, partial_hits: &[SearchHit]) -> SearchQuery {
+        create_retry_query(original_query, partial_hits)
+    }
+
+    fn subscribe(stream: &Self::SearchStream) -> Pollable {
+        stream.subscribe()
+    }
+}
+
+type DurableVespaComponent = DurableSearch<VespaComponent>;
+
+golem_search::export_search!(DurableVespaComponent with_types_in golem_search);
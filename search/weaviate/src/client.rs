@@ -0,0 +1,376 @@
+use golem_search::correlation::{self, CORRELATION_ID_HEADER};
+use golem_search::error::{
+    from_reqwest_error, from_send_error, invalid_query, search_error_from_failed_response,
+};
+use golem_search::golem::search::types::SearchError;
+use golem_search::logging::{redact_secret, RedactedResponse};
+use golem_search::retry::RetryPolicy;
+use log::trace;
+use reqwest::{Client, Method, RequestBuilder, Response};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map as JsonMap, Value as JsonValue};
+use std::fmt::Debug;
+use std::time::Duration;
+
+#[derive(Clone)]
+pub struct WeaviateApi {
+    client: Client,
+    base_url: String,
+    api_key: Option<String>,
+    retry_policy: RetryPolicy,
+    request_timeout: Option<Duration>,
+    headers: Vec<(String, String)>,
+}
+
+impl Debug for WeaviateApi {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WeaviateApi")
+            .field("base_url", &self.base_url)
+            .field("api_key", &self.api_key.as_deref().map(redact_secret))
+            .field("retry_policy", &self.retry_policy)
+            .field("request_timeout", &self.request_timeout)
+            .finish()
+    }
+}
+
+pub type Properties = JsonMap<String, JsonValue>;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Property {
+    pub name: String,
+    pub data_type: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tokenization: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub index_filterable: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub index_searchable: Option<bool>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Class {
+    pub class: String,
+    #[serde(default)]
+    pub properties: Vec<Property>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vectorizer: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SchemaResponse {
+    #[serde(default)]
+    pub classes: Vec<Class>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Object {
+    pub class: String,
+    pub id: String,
+    #[serde(default)]
+    pub properties: Properties,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ObjectList {
+    #[serde(default)]
+    pub objects: Vec<Object>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BatchObjectsRequest {
+    pub objects: Vec<Object>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ErrorMessage {
+    pub message: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ErrorList {
+    #[serde(default)]
+    pub error: Vec<ErrorMessage>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BatchResult {
+    #[serde(default)]
+    pub errors: Option<ErrorList>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BatchObjectResult {
+    pub id: String,
+    #[serde(default)]
+    pub result: Option<BatchResult>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BatchDeleteMatch {
+    pub class: String,
+    pub r#where: JsonValue,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BatchDeleteRequest {
+    pub r#match: BatchDeleteMatch,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GraphQLRequest {
+    pub query: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GraphQLResponse {
+    #[serde(default)]
+    pub data: Option<JsonValue>,
+    #[serde(default)]
+    pub errors: Option<Vec<ErrorMessage>>,
+}
+
+impl WeaviateApi {
+    pub fn new(base_url: String, api_key: Option<String>) -> Self {
+        let client = Client::builder()
+            .build()
+            .expect("Failed to initialize HTTP client");
+
+        Self {
+            client,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            api_key,
+            retry_policy: RetryPolicy::from_config(),
+            request_timeout: None,
+            headers: Vec::new(),
+        }
+    }
+
+    /// Applies a timeout to every request sent by this client
+    pub fn with_request_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.request_timeout = timeout;
+        self
+    }
+
+    /// Adds custom headers to every request sent by this client
+    pub fn with_headers(mut self, headers: Vec<(String, String)>) -> Self {
+        self.headers = headers;
+        self
+    }
+
+    fn create_request(&self, method: Method, url: &str) -> RequestBuilder {
+        trace!("[Weaviate] HTTP {method} {url}");
+
+        let mut req = self
+            .client
+            .request(method, url)
+            .header("Content-Type", "application/json")
+            .header(CORRELATION_ID_HEADER, correlation::current());
+
+        if let Some(api_key) = &self.api_key {
+            req = req.bearer_auth(api_key);
+        }
+
+        if let Some(timeout) = self.request_timeout {
+            req = req.timeout(timeout);
+        }
+
+        for (name, value) in &self.headers {
+            req = req.header(name, value);
+        }
+
+        req
+    }
+
+    fn send(
+        &self,
+        method: Method,
+        url: &str,
+        body: Option<&impl Serialize>,
+        operation: &str,
+    ) -> Result<Response, SearchError> {
+        self.retry_policy
+            .execute(|| {
+                let req = self.create_request(method.clone(), url);
+                match body {
+                    Some(body) => req.json(body).send(),
+                    None => req.send(),
+                }
+            })
+            .map_err(|e| from_send_error(format!("Failed to {operation}"), e))
+    }
+}
+
+fn parse_response<T: DeserializeOwned + Debug>(response: Response) -> Result<T, SearchError> {
+    let status = response.status();
+
+    trace!(
+        "Received response from Weaviate API: {:?}",
+        RedactedResponse(&response)
+    );
+
+    if status.is_success() {
+        let body = response
+            .json::<T>()
+            .map_err(|err| from_reqwest_error("Failed to decode response body", err))?;
+
+        trace!("Received response from Weaviate API: {body:?}");
+
+        Ok(body)
+    } else {
+        let error = search_error_from_failed_response(response);
+
+        trace!("Received {status} response from Weaviate API: {error:?}");
+
+        Err(error)
+    }
+}
+
+/// Checks the status of a response with no body of interest
+fn check_response(response: Response) -> Result<(), SearchError> {
+    if response.status().is_success() {
+        Ok(())
+    } else {
+        Err(search_error_from_failed_response(response))
+    }
+}
+
+impl WeaviateApi {
+    pub fn get_schema(&self) -> Result<SchemaResponse, SearchError> {
+        trace!("Getting schema");
+
+        let url = format!("{}/v1/schema", self.base_url);
+        parse_response(self.send(Method::GET, &url, None::<&()>, "get schema")?)
+    }
+
+    pub fn create_class(&self, class: &Class) -> Result<(), SearchError> {
+        trace!("Creating class: {}", class.class);
+
+        let url = format!("{}/v1/schema", self.base_url);
+        let response = self
+            .create_request(Method::POST, &url)
+            .json(class)
+            .send()
+            .map_err(|e| from_send_error("Failed to create class", e))?;
+
+        check_response(response)
+    }
+
+    pub fn delete_class(&self, class: &str) -> Result<(), SearchError> {
+        trace!("Deleting class: {class}");
+
+        let url = format!("{}/v1/schema/{}", self.base_url, class);
+        check_response(self.send(Method::DELETE, &url, None::<&()>, "delete class")?)
+    }
+
+    pub fn get_class(&self, class: &str) -> Result<Class, SearchError> {
+        trace!("Getting class: {class}");
+
+        let url = format!("{}/v1/schema/{}", self.base_url, class);
+        parse_response(self.send(Method::GET, &url, None::<&()>, "get class")?)
+    }
+
+    pub fn add_property(&self, class: &str, property: &Property) -> Result<(), SearchError> {
+        trace!("Adding property {} to class: {class}", property.name);
+
+        let url = format!("{}/v1/schema/{}/properties", self.base_url, class);
+        check_response(self.send(Method::POST, &url, Some(property), "add property")?)
+    }
+
+    /// Creates or replaces objects. Weaviate reports the objects it failed to store in the
+    /// response, the first of these errors is returned.
+    pub fn batch_objects(&self, objects: Vec<Object>) -> Result<(), SearchError> {
+        trace!("Storing {} objects", objects.len());
+
+        let url = format!("{}/v1/batch/objects", self.base_url);
+        let request = BatchObjectsRequest { objects };
+        let response = self
+            .create_request(Method::POST, &url)
+            .json(&request)
+            .send()
+            .map_err(|e| from_send_error("Failed to store objects", e))?;
+
+        let results = parse_response::<Vec<BatchObjectResult>>(response)?;
+        let failure = results.into_iter().find_map(|object| {
+            let error = object.result?.errors?.error.into_iter().next()?;
+            Some((object.id, error.message))
+        });
+        match failure {
+            Some((id, message)) => Err(invalid_query(format!(
+                "Failed to store object {id}: {message}"
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    pub fn batch_delete(&self, class: &str, r#where: JsonValue) -> Result<(), SearchError> {
+        trace!("Deleting objects from class: {class}");
+
+        let url = format!("{}/v1/batch/objects", self.base_url);
+        let request = BatchDeleteRequest {
+            r#match: BatchDeleteMatch {
+                class: class.to_string(),
+                r#where,
+            },
+        };
+        check_response(self.send(Method::DELETE, &url, Some(&request), "delete objects")?)
+    }
+
+    pub fn get_object(&self, class: &str, id: &str) -> Result<Option<Object>, SearchError> {
+        trace!("Getting object {id} from class: {class}");
+
+        let url = format!("{}/v1/objects/{}/{}", self.base_url, class, id);
+        let response = self.send(Method::GET, &url, None::<&()>, "get object")?;
+
+        if response.status() == 404 {
+            Ok(None)
+        } else {
+            Ok(Some(parse_response(response)?))
+        }
+    }
+
+    /// Lists the objects of a class in the order of their ids, starting after the object `after`
+    pub fn list_objects(
+        &self,
+        class: &str,
+        limit: u32,
+        after: Option<&str>,
+    ) -> Result<ObjectList, SearchError> {
+        trace!("Listing objects of class: {class}");
+
+        let mut url = format!("{}/v1/objects?class={}&limit={limit}", self.base_url, class);
+        if let Some(after) = after {
+            url.push_str(&format!("&after={after}"));
+        }
+        let response = self
+            .retry_policy
+            .execute(|| self.create_request(Method::GET, &url).send())
+            .map_err(|e| from_send_error("Failed to search", e))?;
+
+        parse_response(response)
+    }
+
+    /// Runs a GraphQL query, returning its `data`. Weaviate reports invalid queries in the
+    /// `errors` of a successful response.
+    pub fn graphql(&self, query: String) -> Result<JsonValue, SearchError> {
+        trace!("Running GraphQL query: {query}");
+
+        let url = format!("{}/v1/graphql", self.base_url);
+        let request = GraphQLRequest { query };
+        let response = self
+            .retry_policy
+            .execute(|| {
+                self.create_request(Method::POST, &url)
+                    .json(&request)
+                    .send()
+            })
+            .map_err(|e| from_send_error("Failed to search", e))?;
+
+        let response = parse_response::<GraphQLResponse>(response)?;
+        if let Some(error) = response.errors.and_then(|errors| errors.into_iter().next()) {
+            return Err(invalid_query(error.message));
+        }
+        Ok(response.data.unwrap_or_default())
+    }
+}
@@ -0,0 +1,856 @@
+use crate::client::{Class, Object, Properties, Property};
+use golem_search::config::{get_max_pagination_depth_config, search_filters_match_any};
+use golem_search::error::invalid_query;
+use golem_search::golem::search::types::{
+    Doc, FieldType, Schema, SchemaField, SearchError, SearchHit, SearchQuery,
+};
+use serde_json::{json, Map as JsonMap, Value as JsonValue};
+use uuid::Uuid;
+
+/// Property holding the document id, as Weaviate object ids must be UUIDs
+pub const DOC_ID_FIELD: &str = "docId";
+
+const DEFAULT_PER_PAGE: u32 = 20;
+
+/// The class of an index. Weaviate class names start with an uppercase letter and only contain
+/// letters, digits and underscores.
+pub fn class_name(index: &str) -> String {
+    let mut chars = index.chars().map(|c| {
+        if c.is_ascii_alphanumeric() || c == '_' {
+            c
+        } else {
+            '_'
+        }
+    });
+    match chars.next() {
+        Some(first) if first.is_ascii_alphabetic() => {
+            first.to_ascii_uppercase().to_string() + &chars.collect::<String>()
+        }
+        Some(first) => format!("C{first}{}", chars.collect::<String>()),
+        None => String::new(),
+    }
+}
+
+/// The object id of a document. UUID document ids are used as they are, other ids are mapped to
+/// a name-based UUID so that they can be looked up without a search.
+pub fn object_id(doc_id: &str) -> String {
+    match Uuid::parse_str(doc_id) {
+        Ok(id) => id.to_string(),
+        Err(_) => Uuid::new_v5(&Uuid::NAMESPACE_OID, doc_id.as_bytes()).to_string(),
+    }
+}
+
+pub fn doc_to_object(class: &str, doc: Doc) -> Result<Object, SearchError> {
+    let mut properties = match serde_json::from_str::<JsonValue>(&doc.content) {
+        Ok(JsonValue::Object(properties)) => properties,
+        Ok(_) => return Err(invalid_query("Document content must be a JSON object")),
+        Err(e) => return Err(invalid_query(format!("Invalid document content: {e}"))),
+    };
+    properties.insert(DOC_ID_FIELD.to_string(), JsonValue::String(doc.id.clone()));
+
+    Ok(Object {
+        class: class.to_string(),
+        id: object_id(&doc.id),
+        properties,
+    })
+}
+
+fn split_properties(id: String, mut properties: Properties) -> (String, Properties) {
+    let id = match properties.remove(DOC_ID_FIELD) {
+        Some(JsonValue::String(doc_id)) => doc_id,
+        _ => id,
+    };
+    (id, properties)
+}
+
+pub fn object_to_doc(object: Object) -> Doc {
+    let (id, properties) = split_properties(object.id, object.properties);
+    Doc {
+        id,
+        content: JsonValue::Object(properties).to_string(),
+    }
+}
+
+pub fn object_to_hit(object: Object) -> SearchHit {
+    let (id, properties) = split_properties(object.id, object.properties);
+    SearchHit {
+        id,
+        score: None,
+        content: Some(JsonValue::Object(properties).to_string()),
+        highlights: None,
+    }
+}
+
+fn field_to_property(field: &SchemaField) -> Property {
+    let (data_type, tokenization) = match field.field_type {
+        FieldType::Text => ("text", None),
+        FieldType::Keyword => ("text", Some("field")),
+        FieldType::Integer => ("int", None),
+        FieldType::Float => ("number", None),
+        FieldType::Boolean => ("boolean", None),
+        FieldType::Date => ("date", None),
+        FieldType::GeoPoint => ("geoCoordinates", None),
+    };
+    let text = data_type == "text";
+    Property {
+        name: field.name.clone(),
+        data_type: vec![data_type.to_string()],
+        tokenization: tokenization.map(str::to_string),
+        index_filterable: Some(field.index || field.facet || field.sort),
+        index_searchable: text.then_some(field.index),
+    }
+}
+
+fn doc_id_property() -> Property {
+    Property {
+        name: DOC_ID_FIELD.to_string(),
+        data_type: vec!["text".to_string()],
+        tokenization: Some("field".to_string()),
+        index_filterable: Some(true),
+        index_searchable: Some(false),
+    }
+}
+
+pub fn schema_to_properties(schema: &Schema) -> Vec<Property> {
+    schema.fields.iter().map(field_to_property).collect()
+}
+
+/// The class of a new index, with a property for each schema field and the document id
+pub fn schema_to_class(class: &str, schema: Option<&Schema>, vectorizer: String) -> Class {
+    let mut properties = vec![doc_id_property()];
+    properties.extend(schema.map(schema_to_properties).unwrap_or_default());
+    Class {
+        class: class.to_string(),
+        properties,
+        vectorizer: Some(vectorizer),
+    }
+}
+
+pub fn class_to_schema(class: Class) -> Schema {
+    let fields = class
+        .properties
+        .into_iter()
+        .filter(|property| property.name != DOC_ID_FIELD)
+        .map(|property| {
+            let filterable = property.index_filterable.unwrap_or(true);
+            let field_type = match property.data_type.first().map(String::as_str) {
+                Some("text") if property.tokenization.as_deref() == Some("field") => {
+                    FieldType::Keyword
+                }
+                Some("int") => FieldType::Integer,
+                Some("number") => FieldType::Float,
+                Some("boolean") => FieldType::Boolean,
+                Some("date") => FieldType::Date,
+                Some("geoCoordinates") => FieldType::GeoPoint,
+                _ => FieldType::Text,
+            };
+            let index = match field_type {
+                FieldType::Text | FieldType::Keyword => property.index_searchable.unwrap_or(true),
+                _ => filterable,
+            };
+            SchemaField {
+                name: property.name,
+                field_type,
+                required: false,
+                facet: filterable && field_type == FieldType::Keyword,
+                sort: filterable && field_type != FieldType::GeoPoint,
+                index,
+            }
+        })
+        .collect();
+
+    Schema {
+        fields,
+        primary_key: None,
+    }
+}
+
+fn property_type<'a>(class: &'a Class, name: &str) -> Option<&'a str> {
+    class
+        .properties
+        .iter()
+        .find(|property| property.name == name)
+        .and_then(|property| property.data_type.first())
+        .map(String::as_str)
+}
+
+fn parse_value(value: &str) -> JsonValue {
+    let value = value.trim();
+    if let Some(quoted) = value
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+    {
+        return JsonValue::String(quoted.to_string());
+    }
+    match serde_json::from_str::<JsonValue>(value) {
+        Ok(value @ (JsonValue::Number(_) | JsonValue::Bool(_))) => value,
+        _ => JsonValue::String(value.to_string()),
+    }
+}
+
+/// The `value*` key of a compared value, by the type of the property it is compared with
+fn value_key(value: &JsonValue, data_type: Option<&str>) -> &'static str {
+    match (value, data_type) {
+        (_, Some("date")) => "valueDate",
+        (JsonValue::Bool(_), _) => "valueBoolean",
+        (JsonValue::Number(_), Some("number")) => "valueNumber",
+        (JsonValue::Number(number), _) if number.is_i64() || number.is_u64() => "valueInt",
+        (JsonValue::Number(_), _) => "valueNumber",
+        _ => "valueText",
+    }
+}
+
+/// Converts a filter to a Weaviate where filter. Filters are either where filters in JSON, or
+/// `field:value`, `field=value`, `field!=value`, and `field>value` style comparisons with `>`,
+/// `>=`, `<` and `<=`.
+fn filter_to_where(filter: &str, class: &Class) -> Result<JsonValue, SearchError> {
+    let filter = filter.trim();
+    if filter.starts_with('{') {
+        return serde_json::from_str(filter)
+            .map_err(|e| invalid_query(format!("Invalid Weaviate filter `{filter}`: {e}")));
+    }
+
+    let position = filter
+        .find([':', '=', '!', '<', '>'])
+        .ok_or_else(|| invalid_query(format!("Unsupported filter `{filter}`")))?;
+    let key = filter[..position].trim();
+    let rest = &filter[position..];
+    let (op, value) = [">=", "<=", "!=", ">", "<", "=", ":"]
+        .into_iter()
+        .find_map(|op| Some((op, rest.strip_prefix(op)?)))
+        .ok_or_else(|| invalid_query(format!("Unsupported filter `{filter}`")))?;
+    if key.is_empty() || value.trim().is_empty() {
+        return Err(invalid_query(format!("Unsupported filter `{filter}`")));
+    }
+    let value = parse_value(value);
+
+    let operator = match op {
+        ":" | "=" => "Equal",
+        "!=" => "NotEqual",
+        ">" => "GreaterThan",
+        ">=" => "GreaterThanEqual",
+        "<" => "LessThan",
+        _ => "LessThanEqual",
+    };
+    let value_key = value_key(&value, property_type(class, key));
+    if !matches!(operator, "Equal" | "NotEqual") && !(value.is_number() || value_key == "valueDate")
+    {
+        return Err(invalid_query(format!(
+            "Range filter `{filter}` needs a numeric or date value"
+        )));
+    }
+    Ok(json!({ "path": [key], "operator": operator, value_key: value }))
+}
+
+/// Builds the where filter of a query's filters
+pub fn query_to_where(
+    query: &SearchQuery,
+    class: &Class,
+) -> Result<Option<JsonValue>, SearchError> {
+    let mut operands = query
+        .filters
+        .iter()
+        .map(|filter| filter_to_where(filter, class))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(match operands.len() {
+        0 => None,
+        1 => operands.pop(),
+        _ => {
+            let operator = if search_filters_match_any(query) {
+                "Or"
+            } else {
+                "And"
+            };
+            Some(json!({ "operator": operator, "operands": operands }))
+        }
+    })
+}
+
+/// The where filter matching the objects of the given documents
+pub fn ids_to_where(ids: &[String]) -> JsonValue {
+    let ids = ids.iter().map(|id| object_id(id)).collect::<Vec<_>>();
+    json!({ "path": ["id"], "operator": "ContainsAny", "valueTextArray": ids })
+}
+
+/// Writes a JSON value as a GraphQL input value. Object keys are unquoted, and the values of
+/// `operator` and `order` are enum values, which are unquoted as well.
+pub fn to_graphql(value: &JsonValue) -> String {
+    fn write(value: &JsonValue, is_enum: bool, out: &mut String) {
+        match value {
+            JsonValue::String(value) if is_enum => out.push_str(value),
+            JsonValue::Object(entries) => {
+                out.push('{');
+                for (i, (key, value)) in entries.iter().enumerate() {
+                    if i > 0 {
+                        out.push_str(", ");
+                    }
+                    out.push_str(key);
+                    out.push_str(": ");
+                    write(value, matches!(key.as_str(), "operator" | "order"), out);
+                }
+                out.push('}');
+            }
+            JsonValue::Array(values) => {
+                out.push('[');
+                for (i, value) in values.iter().enumerate() {
+                    if i > 0 {
+                        out.push_str(", ");
+                    }
+                    write(value, is_enum, out);
+                }
+                out.push(']');
+            }
+            value => out.push_str(&value.to_string()),
+        }
+    }
+
+    let mut out = String::new();
+    write(value, false, &mut out);
+    out
+}
+
+/// The sort of a query, from its `field`, `field:asc`, `field:desc` or `-field` sort entries
+fn query_to_sort(sort: &[String]) -> Result<Vec<JsonValue>, SearchError> {
+    sort.iter()
+        .map(|sort| {
+            let sort = sort.trim();
+            let (key, order) = if let Some(key) = sort.strip_prefix('-') {
+                (key, "desc")
+            } else {
+                match sort.rsplit_once(':') {
+                    Some((key, order)) if order.eq_ignore_ascii_case("asc") => (key, "asc"),
+                    Some((key, order)) if order.eq_ignore_ascii_case("desc") => (key, "desc"),
+                    Some(_) => return Err(invalid_query(format!("Invalid sort `{sort}`"))),
+                    None => (sort, "asc"),
+                }
+            };
+            Ok(json!({ "path": [key], "order": order }))
+        })
+        .collect()
+}
+
+/// Number of hits to skip and number of hits per page of a query
+pub fn query_window(query: &SearchQuery) -> Result<(u32, u32), SearchError> {
+    let per_page = query.per_page.unwrap_or(DEFAULT_PER_PAGE);
+    let offset = query.offset.unwrap_or_else(|| {
+        query
+            .page
+            .unwrap_or(1)
+            .saturating_sub(1)
+            .saturating_mul(per_page)
+    });
+    if offset.saturating_add(per_page) > get_max_pagination_depth_config() {
+        return Err(invalid_query(format!(
+            "Requested hits beyond the maximum pagination depth of {}",
+            get_max_pagination_depth_config()
+        )));
+    }
+    Ok((offset, per_page))
+}
+
+/// The `alpha` of a hybrid query, read from the `{"hybrid": {"alpha": ...}}` provider parameters
+fn hybrid_alpha(query: &SearchQuery) -> Result<Option<f64>, SearchError> {
+    let Some(params) = query
+        .config
+        .as_ref()
+        .and_then(|config| config.provider_params.as_deref())
+    else {
+        return Ok(None);
+    };
+    let params = serde_json::from_str::<JsonValue>(params)
+        .map_err(|e| invalid_query(format!("Invalid provider parameters: {e}")))?;
+    match params.get("hybrid") {
+        None => Ok(None),
+        Some(hybrid) => Ok(Some(
+            hybrid
+                .get("alpha")
+                .and_then(JsonValue::as_f64)
+                .unwrap_or(0.5),
+        )),
+    }
+}
+
+/// The properties selected by a query, with a sub-selection for geo coordinates
+fn selection(query: &SearchQuery, class: &Class) -> String {
+    let attributes = query
+        .config
+        .as_ref()
+        .map(|config| config.attributes_to_retrieve.clone())
+        .unwrap_or_default();
+
+    class
+        .properties
+        .iter()
+        .filter(|property| {
+            attributes.is_empty()
+                || property.name == DOC_ID_FIELD
+                || attributes.contains(&property.name)
+        })
+        .map(
+            |property| match property.data_type.first().map(String::as_str) {
+                Some("geoCoordinates") => format!("{} {{ latitude longitude }}", property.name),
+                _ => property.name.clone(),
+            },
+        )
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Builds the GraphQL `Get` query of a search. The query text is matched with BM25 over the
+/// boosted fields or all searchable properties, or with a hybrid query when the provider
+/// parameters ask for one.
+pub fn query_to_graphql(
+    query: &SearchQuery,
+    class: &Class,
+    r#where: Option<&JsonValue>,
+) -> Result<String, SearchError> {
+    if query.highlight.is_some() {
+        return Err(invalid_query("Weaviate doesn't support highlighting"));
+    }
+
+    let (offset, per_page) = query_window(query)?;
+    let mut arguments = vec![format!("limit: {per_page}"), format!("offset: {offset}")];
+
+    if let Some(q) = query.q.as_deref().map(str::trim).filter(|q| !q.is_empty()) {
+        let boost_fields = query
+            .config
+            .as_ref()
+            .map(|config| {
+                config
+                    .boost_fields
+                    .iter()
+                    .map(|(field, boost)| format!("{field}^{boost}"))
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        let search = match hybrid_alpha(query)? {
+            Some(alpha) => format!(
+                "hybrid: {}",
+                to_graphql(&json!({ "query": q, "alpha": alpha }))
+            ),
+            None if boost_fields.is_empty() => {
+                format!("bm25: {}", to_graphql(&json!({ "query": q })))
+            }
+            None => format!(
+                "bm25: {}",
+                to_graphql(&json!({ "query": q, "properties": boost_fields }))
+            ),
+        };
+        arguments.push(search);
+    }
+    if let Some(r#where) = r#where {
+        arguments.push(format!("where: {}", to_graphql(r#where)));
+    }
+    let sort = query_to_sort(&query.sort)?;
+    if !sort.is_empty() {
+        arguments.push(format!("sort: {}", to_graphql(&JsonValue::Array(sort))));
+    }
+
+    Ok(format!(
+        "{{ Get {{ {}({}) {{ {} _additional {{ id score }} }} }} }}",
+        class.class,
+        arguments.join(", "),
+        selection(query, class)
+    ))
+}
+
+/// Builds the GraphQL `Aggregate` query counting the matches of a search and its facet values,
+/// with the count aliased `total` and each facet aliased `facet<n>`
+pub fn query_to_aggregate(
+    query: &SearchQuery,
+    class: &Class,
+    r#where: Option<&JsonValue>,
+) -> String {
+    let r#where = r#where
+        .map(|r#where| format!("where: {}", to_graphql(r#where)))
+        .unwrap_or_default();
+    let mut aggregates = vec![format!(
+        "total: {}{} {{ meta {{ count }} }}",
+        class.class,
+        if r#where.is_empty() {
+            String::new()
+        } else {
+            format!("({})", r#where)
+        }
+    )];
+    for (i, facet) in query.facets.iter().enumerate() {
+        let mut arguments = vec![format!("groupBy: {}", to_graphql(&json!([facet])))];
+        if !r#where.is_empty() {
+            arguments.push(r#where.clone());
+        }
+        aggregates.push(format!(
+            "facet{i}: {}({}) {{ groupedBy {{ value }} meta {{ count }} }}",
+            class.class,
+            arguments.join(", ")
+        ));
+    }
+
+    format!("{{ Aggregate {{ {} }} }}", aggregates.join(" "))
+}
+
+/// The hits of a `Get` query response
+pub fn parse_hits(data: JsonValue, class: &str) -> Result<Vec<SearchHit>, SearchError> {
+    let objects = match data.pointer(&format!("/Get/{class}")) {
+        Some(JsonValue::Array(objects)) => objects.clone(),
+        Some(JsonValue::Null) | None => vec![],
+        Some(_) => return Err(invalid_query("Unexpected Weaviate search response")),
+    };
+
+    Ok(objects
+        .into_iter()
+        .map(|object| {
+            let mut properties = match object {
+                JsonValue::Object(properties) => properties,
+                _ => JsonMap::new(),
+            };
+            let additional = properties.remove("_additional").unwrap_or_default();
+            let id = additional
+                .get("id")
+                .and_then(JsonValue::as_str)
+                .unwrap_or_default()
+                .to_string();
+            let score = additional.get("score").and_then(|score| match score {
+                JsonValue::String(score) => score.parse::<f64>().ok(),
+                score => score.as_f64(),
+            });
+            properties.retain(|_, value| !value.is_null());
+            let (id, properties) = split_properties(id, properties);
+            SearchHit {
+                id,
+                score,
+                content: Some(JsonValue::Object(properties).to_string()),
+                highlights: None,
+            }
+        })
+        .collect())
+}
+
+fn meta_count(aggregate: &JsonValue) -> Option<u64> {
+    aggregate.pointer("/meta/count").and_then(JsonValue::as_u64)
+}
+
+/// The total and the facet counts of an `Aggregate` query response. Facet counts have the same
+/// shape as the other providers: `{"field": {"value": count}}`.
+pub fn parse_aggregate(data: &JsonValue, facets: &[String]) -> (Option<u32>, Option<String>) {
+    let total = data
+        .pointer("/Aggregate/total/0")
+        .and_then(meta_count)
+        .map(|count| count as u32);
+
+    if facets.is_empty() {
+        return (total, None);
+    }
+    let facets = facets
+        .iter()
+        .enumerate()
+        .map(|(i, facet)| {
+            let groups = data
+                .pointer(&format!("/Aggregate/facet{i}"))
+                .and_then(JsonValue::as_array)
+                .cloned()
+                .unwrap_or_default();
+            let counts = groups
+                .iter()
+                .filter_map(|group| {
+                    let value = match group.pointer("/groupedBy/value")? {
+                        JsonValue::String(value) => value.clone(),
+                        value => value.to_string(),
+                    };
+                    Some((value, JsonValue::from(meta_count(group)?)))
+                })
+                .collect::<JsonMap<_, _>>();
+            (facet.clone(), JsonValue::Object(counts))
+        })
+        .collect::<JsonMap<_, _>>();
+    (total, Some(JsonValue::Object(facets).to_string()))
+}
+
+pub fn create_retry_query(original_query: &SearchQuery, partial_hits: &[SearchHit]) -> SearchQuery {
+    let mut retry_query = original_query.clone();
+
+    let current_offset = original_query.offset.unwrap_or(0);
+    let per_page = original_query.per_page.unwrap_or(DEFAULT_PER_PAGE);
+    let hits_received = partial_hits.len() as u32;
+
+    retry_query.offset = Some(current_offset + hits_received.min(per_page));
+
+    retry_query
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use golem_search::golem::search::types::{FilterMode, SearchConfig};
+
+    fn query(q: Option<&str>, filters: &[&str]) -> SearchQuery {
+        SearchQuery {
+            q: q.map(str::to_string),
+            filters: filters.iter().map(|filter| filter.to_string()).collect(),
+            sort: vec![],
+            facets: vec![],
+            page: None,
+            per_page: None,
+            offset: None,
+            highlight: None,
+            config: None,
+        }
+    }
+
+    fn field(name: &str, field_type: FieldType, facet: bool, sort: bool) -> SchemaField {
+        SchemaField {
+            name: name.to_string(),
+            field_type,
+            required: false,
+            facet,
+            sort,
+            index: true,
+        }
+    }
+
+    fn config(provider_params: Option<&str>, filter_mode: Option<FilterMode>) -> SearchConfig {
+        SearchConfig {
+            timeout_ms: None,
+            boost_fields: vec![],
+            attributes_to_retrieve: vec![],
+            language: None,
+            typo_tolerance: None,
+            exact_match_boost: None,
+            provider_params: provider_params.map(str::to_string),
+            profile: None,
+            filter_mode,
+            post_filters: vec![],
+            rescore: None,
+            fallbacks: vec![],
+        }
+    }
+
+    fn books() -> Class {
+        let schema = Schema {
+            fields: vec![
+                field("title", FieldType::Text, false, false),
+                field("genre", FieldType::Keyword, true, false),
+                field("year", FieldType::Integer, false, true),
+                field("published", FieldType::Date, false, true),
+                field("location", FieldType::GeoPoint, false, false),
+            ],
+            primary_key: None,
+        };
+        schema_to_class("Books", Some(&schema), "none".to_string())
+    }
+
+    #[test]
+    fn test_class_names() {
+        assert_eq!(class_name("books"), "Books");
+        assert_eq!(class_name("my-books.v2"), "My_books_v2");
+        assert_eq!(class_name("2024_books"), "C2024_books");
+    }
+
+    #[test]
+    fn test_object_ids() {
+        assert_eq!(
+            object_id("936DA01F-9ABD-4D9D-80C7-02AF85C822A8"),
+            "936da01f-9abd-4d9d-80c7-02af85c822a8"
+        );
+        assert_eq!(object_id("doc1"), object_id("doc1"));
+        assert_ne!(object_id("doc1"), object_id("doc2"));
+    }
+
+    #[test]
+    fn test_doc_to_object_and_back() {
+        let doc = Doc {
+            id: "doc1".to_string(),
+            content: r#"{"title":"Dune"}"#.to_string(),
+        };
+        let object = doc_to_object("Books", doc.clone()).unwrap();
+        assert_eq!(object.class, "Books");
+        assert_eq!(object.id, object_id("doc1"));
+        assert_eq!(object.properties.get(DOC_ID_FIELD).unwrap(), "doc1");
+        assert_eq!(object_to_doc(object), doc);
+
+        assert!(matches!(
+            doc_to_object(
+                "Books",
+                Doc {
+                    id: "doc1".to_string(),
+                    content: "[1, 2]".to_string(),
+                }
+            ),
+            Err(SearchError::InvalidQuery(_))
+        ));
+    }
+
+    #[test]
+    fn test_schema_round_trip() {
+        let class = books();
+        assert_eq!(class.properties[0].name, DOC_ID_FIELD);
+        assert_eq!(class.properties[2].tokenization.as_deref(), Some("field"));
+
+        let schema = class_to_schema(class);
+        let fields = schema
+            .fields
+            .iter()
+            .map(|field| (field.name.as_str(), field.field_type))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            fields,
+            vec![
+                ("title", FieldType::Text),
+                ("genre", FieldType::Keyword),
+                ("year", FieldType::Integer),
+                ("published", FieldType::Date),
+                ("location", FieldType::GeoPoint),
+            ]
+        );
+        assert!(schema.fields[1].facet);
+        assert!(schema.fields[2].sort);
+    }
+
+    #[test]
+    fn test_filters_to_where() {
+        let class = books();
+        assert_eq!(
+            filter_to_where("genre:fiction", &class).unwrap(),
+            json!({ "path": ["genre"], "operator": "Equal", "valueText": "fiction" })
+        );
+        assert_eq!(
+            filter_to_where("year>=1950", &class).unwrap(),
+            json!({ "path": ["year"], "operator": "GreaterThanEqual", "valueInt": 1950 })
+        );
+        assert_eq!(
+            filter_to_where("published<2000-01-01T00:00:00Z", &class).unwrap(),
+            json!({
+                "path": ["published"],
+                "operator": "LessThan",
+                "valueDate": "2000-01-01T00:00:00Z"
+            })
+        );
+        assert_eq!(
+            filter_to_where("in_stock!=true", &class).unwrap(),
+            json!({ "path": ["in_stock"], "operator": "NotEqual", "valueBoolean": true })
+        );
+        assert!(filter_to_where("year>recent", &class).is_err());
+        assert!(filter_to_where("no operator", &class).is_err());
+    }
+
+    #[test]
+    fn test_query_to_where() {
+        let class = books();
+        assert_eq!(query_to_where(&query(None, &[]), &class).unwrap(), None);
+
+        let mut query = query(None, &["genre:fiction", "year>1950"]);
+        assert_eq!(
+            query_to_where(&query, &class).unwrap().unwrap()["operator"],
+            "And"
+        );
+        query.config = Some(config(None, Some(FilterMode::Any)));
+        assert_eq!(
+            query_to_where(&query, &class).unwrap().unwrap()["operator"],
+            "Or"
+        );
+    }
+
+    #[test]
+    fn test_to_graphql() {
+        assert_eq!(
+            to_graphql(&json!({
+                "operator": "And",
+                "operands": [{ "path": ["title"], "operator": "Equal", "valueText": "a \"b\"" }]
+            })),
+            r#"{operands: [{operator: Equal, path: ["title"], valueText: "a \"b\""}], operator: And}"#
+        );
+        assert_eq!(
+            to_graphql(&json!([{ "path": ["year"], "order": "desc" }])),
+            r#"[{order: desc, path: ["year"]}]"#
+        );
+    }
+
+    #[test]
+    fn test_query_to_graphql() {
+        let class = books();
+        let mut query = query(Some("dune"), &[]);
+        query.page = Some(2);
+        query.per_page = Some(5);
+        query.sort = vec!["-year".to_string()];
+
+        assert_eq!(
+            query_to_graphql(&query, &class, None).unwrap(),
+            "{ Get { Books(limit: 5, offset: 5, bm25: {query: \"dune\"}, \
+             sort: [{order: desc, path: [\"year\"]}]) { docId title genre year published \
+             location { latitude longitude } _additional { id score } } } }"
+        );
+
+        query.sort = vec![];
+        query.config = Some(config(Some(r#"{"hybrid": {"alpha": 0.25}}"#), None));
+        let graphql = query_to_graphql(&query, &class, None).unwrap();
+        assert!(graphql.contains("hybrid: {alpha: 0.25, query: \"dune\"}"));
+    }
+
+    #[test]
+    fn test_query_to_aggregate() {
+        let class = books();
+        let mut query = query(None, &[]);
+        query.facets = vec!["genre".to_string()];
+        let r#where = json!({ "path": ["year"], "operator": "GreaterThan", "valueInt": 1950 });
+
+        assert_eq!(
+            query_to_aggregate(&query, &class, Some(&r#where)),
+            "{ Aggregate { total: Books(where: {operator: GreaterThan, path: [\"year\"], \
+             valueInt: 1950}) { meta { count } } facet0: Books(groupBy: [\"genre\"], \
+             where: {operator: GreaterThan, path: [\"year\"], valueInt: 1950}) \
+             { groupedBy { value } meta { count } } } }"
+        );
+    }
+
+    #[test]
+    fn test_parse_hits() {
+        let data = json!({ "Get": { "Books": [{
+            "docId": "doc1",
+            "title": "Dune",
+            "year": null,
+            "_additional": { "id": object_id("doc1"), "score": "1.5" }
+        }] } });
+
+        let hits = parse_hits(data, "Books").unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].id, "doc1");
+        assert_eq!(hits[0].score, Some(1.5));
+        assert_eq!(hits[0].content.as_deref(), Some(r#"{"title":"Dune"}"#));
+    }
+
+    #[test]
+    fn test_parse_aggregate() {
+        let data = json!({ "Aggregate": {
+            "total": [{ "meta": { "count": 3 } }],
+            "facet0": [
+                { "groupedBy": { "value": "fiction" }, "meta": { "count": 2 } },
+                { "groupedBy": { "value": "poetry" }, "meta": { "count": 1 } },
+            ],
+        } });
+
+        let (total, facets) = parse_aggregate(&data, &["genre".to_string()]);
+        assert_eq!(total, Some(3));
+        assert_eq!(
+            serde_json::from_str::<JsonValue>(&facets.unwrap()).unwrap(),
+            json!({ "genre": { "fiction": 2, "poetry": 1 } })
+        );
+    }
+
+    #[test]
+    fn test_create_retry_query() {
+        let mut original = query(Some("dune"), &[]);
+        original.offset = Some(10);
+        original.per_page = Some(5);
+        let hits = vec![
+            SearchHit {
+                id: "doc1".to_string(),
+                score: None,
+                content: None,
+                highlights: None,
+            };
+            3
+        ];
+
+        assert_eq!(create_retry_query(&original, &hits).offset, Some(13));
+    }
+}
@@ -0,0 +1,363 @@
+use crate::client::{Class, WeaviateApi};
+use crate::conversions::{
+    class_name, class_to_schema, create_retry_query, doc_to_object, ids_to_where, object_id,
+    object_to_doc, object_to_hit, parse_aggregate, parse_hits, query_to_aggregate,
+    query_to_graphql, query_to_where, query_window, schema_to_class, schema_to_properties,
+};
+use golem_rust::wasm_rpc::Pollable;
+use golem_search::config::{
+    get_config_with_default, get_optional_config, profile_key, search_headers, search_profile,
+    search_timeout, worker_headers, worker_profile,
+};
+use golem_search::durability::{DurableSearch, ExtendedGuest};
+use golem_search::error::invalid_query;
+use golem_search::golem::search::core::{Guest, GuestSearchStream, SearchStream};
+use golem_search::golem::search::types::{
+    Doc, DocumentId, IndexName, IndexPage, PageRequest, Schema, SearchError, SearchHit,
+    SearchQuery, SearchResults,
+};
+use golem_search::pagination::index_page;
+use golem_search::LOGGING_STATE;
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+
+mod client;
+mod conversions;
+
+/// Search stream over the matches of a query. Queries without text, filters or sort iterate over
+/// the class with Weaviate's object cursor, other queries are paged through with offsets.
+struct WeaviateSearchStream {
+    client: WeaviateApi,
+    index_name: String,
+    query: SearchQuery,
+    offset: Cell<u32>,
+    /// The id of the last object listed, `None` for streams paged with offsets
+    cursor: RefCell<Option<String>>,
+    finished: Cell<bool>,
+}
+
+impl WeaviateSearchStream {
+    pub fn new(client: WeaviateApi, index_name: String, query: SearchQuery) -> Self {
+        let offset = query_window(&query).map(|(offset, _)| offset).unwrap_or(0);
+        let lists_class = query.q.as_deref().is_none_or(|q| q.trim().is_empty())
+            && query.filters.is_empty()
+            && query.sort.is_empty()
+            && offset == 0;
+        Self {
+            client,
+            index_name,
+            query,
+            offset: Cell::new(offset),
+            cursor: RefCell::new(lists_class.then(String::new)),
+            finished: Cell::new(false),
+        }
+    }
+
+    pub fn subscribe(&self) -> Pollable {
+        golem_rust::bindings::wasi::clocks::monotonic_clock::subscribe_duration(0)
+    }
+
+    fn next_page(&self, per_page: u32) -> Result<Vec<SearchHit>, SearchError> {
+        let cursor = self.cursor.borrow().clone();
+        if let Some(after) = cursor {
+            let class = class_name(&self.index_name);
+            let after = (!after.is_empty()).then_some(after.as_str());
+            let objects = self.client.list_objects(&class, per_page, after)?.objects;
+            if let Some(last) = objects.last() {
+                *self.cursor.borrow_mut() = Some(last.id.clone());
+            }
+            Ok(objects.into_iter().map(object_to_hit).collect())
+        } else {
+            let mut query = self.query.clone();
+            query.page = None;
+            query.offset = Some(self.offset.get());
+            let results = WeaviateComponent::search_page(&self.client, &self.index_name, &query)?;
+            Ok(results.hits)
+        }
+    }
+}
+
+impl GuestSearchStream for WeaviateSearchStream {
+    fn get_next(&self) -> Option<Vec<SearchHit>> {
+        if self.finished.get() {
+            return Some(vec![]);
+        }
+
+        let per_page = query_window(&self.query)
+            .map(|(_, per_page)| per_page)
+            .unwrap_or(0);
+
+        match self.next_page(per_page) {
+            Ok(hits) => {
+                let received = hits.len() as u32;
+                if received < per_page || received == 0 {
+                    self.finished.set(true);
+                }
+                self.offset.set(self.offset.get() + received);
+                Some(hits)
+            }
+            Err(_) => {
+                self.finished.set(true);
+                Some(vec![])
+            }
+        }
+    }
+
+    fn blocking_get_next(&self) -> Vec<SearchHit> {
+        self.get_next().unwrap_or_default()
+    }
+}
+
+struct WeaviateComponent;
+
+thread_local! {
+    /// The provider clients by configuration profile, reused across calls instead of being rebuilt
+    /// for each of them
+    static CLIENTS: RefCell<HashMap<Option<String>, WeaviateApi>> = RefCell::new(HashMap::new());
+}
+
+impl WeaviateComponent {
+    const BASE_URL_ENV_VAR: &'static str = "WEAVIATE_BASE_URL";
+    const API_KEY_ENV_VAR: &'static str = "WEAVIATE_API_KEY";
+    const VECTORIZER_ENV_VAR: &'static str = "WEAVIATE_VECTORIZER";
+
+    fn create_client() -> Result<WeaviateApi, SearchError> {
+        Self::create_profile_client(worker_profile().as_deref())
+            .map(|client| client.with_headers(worker_headers()))
+    }
+
+    /// Returns the client of `profile` cached for this worker, creating it from the configuration
+    /// on first use
+    fn create_profile_client(profile: Option<&str>) -> Result<WeaviateApi, SearchError> {
+        CLIENTS.with_borrow_mut(|clients| {
+            let profile = profile.map(str::to_string);
+            if let Some(client) = clients.get(&profile) {
+                return Ok(client.clone());
+            }
+
+            let client = Self::new_client(profile.as_deref())?;
+            clients.insert(profile, client.clone());
+            Ok(client)
+        })
+    }
+
+    /// Creates a client from the `WEAVIATE_BASE_URL` and optional `WEAVIATE_API_KEY` keys, as
+    /// local Weaviate instances usually run without authentication
+    fn new_client(profile: Option<&str>) -> Result<WeaviateApi, SearchError> {
+        let base_url = get_optional_config(profile_key(Self::BASE_URL_ENV_VAR, profile))
+            .ok_or_else(|| SearchError::Internal("Missing Weaviate base URL".to_string()))?;
+        let api_key = get_optional_config(profile_key(Self::API_KEY_ENV_VAR, profile))
+            .filter(|api_key| !api_key.is_empty());
+
+        Ok(WeaviateApi::new(base_url, api_key))
+    }
+
+    /// Runs a query as a GraphQL `Get` query, with an `Aggregate` query for the total and facets
+    /// of queries without text, which Weaviate can't aggregate over
+    fn search_page(
+        client: &WeaviateApi,
+        index: &str,
+        query: &SearchQuery,
+    ) -> Result<SearchResults, SearchError> {
+        let has_text = query.q.as_deref().is_some_and(|q| !q.trim().is_empty());
+        if has_text && !query.facets.is_empty() {
+            return Err(invalid_query(
+                "Weaviate can't compute facets of full-text queries",
+            ));
+        }
+
+        let class = client.get_class(&class_name(index))?;
+        let (offset, per_page) = query_window(query)?;
+        let r#where = query_to_where(query, &class)?;
+
+        let data = client.graphql(query_to_graphql(query, &class, r#where.as_ref())?)?;
+        let hits = parse_hits(data, &class.class)?;
+
+        let (total, facets) = if has_text {
+            (None, None)
+        } else {
+            let data = client.graphql(query_to_aggregate(query, &class, r#where.as_ref()))?;
+            parse_aggregate(&data, &query.facets)
+        };
+
+        Ok(SearchResults {
+            total,
+            page: Some(offset / per_page.max(1) + 1),
+            per_page: Some(per_page),
+            hits,
+            facets,
+            took_ms: None,
+            relaxation: None,
+            navigation: None,
+        })
+    }
+}
+
+impl Guest for WeaviateComponent {
+    type SearchStream = WeaviateSearchStream;
+
+    fn create_index(name: IndexName, schema: Option<Schema>) -> Result<(), SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = Self::create_client()?;
+        let vectorizer = get_config_with_default(
+            profile_key(Self::VECTORIZER_ENV_VAR, worker_profile().as_deref()),
+            "none",
+        );
+        client.create_class(&schema_to_class(
+            &class_name(&name),
+            schema.as_ref(),
+            vectorizer,
+        ))
+    }
+
+    fn delete_index(name: IndexName) -> Result<(), SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = Self::create_client()?;
+        client.delete_class(&class_name(&name))
+    }
+
+    fn list_indexes(page: PageRequest) -> Result<IndexPage, SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = Self::create_client()?;
+
+        let response = client.get_schema()?;
+        index_page(
+            response
+                .classes
+                .into_iter()
+                .map(|class| class.class)
+                .collect(),
+            &page,
+        )
+    }
+
+    fn upsert(index: IndexName, doc: Doc) -> Result<(), SearchError> {
+        Self::upsert_many(index, vec![doc])
+    }
+
+    fn upsert_many(index: IndexName, docs: Vec<Doc>) -> Result<(), SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        if docs.is_empty() {
+            return Ok(());
+        }
+
+        let client = Self::create_client()?;
+        let class = class_name(&index);
+        let objects = docs
+            .into_iter()
+            .map(|doc| doc_to_object(&class, doc))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        client.batch_objects(objects)
+    }
+
+    fn delete(index: IndexName, id: DocumentId) -> Result<(), SearchError> {
+        Self::delete_many(index, vec![id])
+    }
+
+    fn delete_many(index: IndexName, ids: Vec<DocumentId>) -> Result<(), SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        if ids.is_empty() {
+            return Ok(());
+        }
+
+        let client = Self::create_client()?;
+        client.batch_delete(&class_name(&index), ids_to_where(&ids))
+    }
+
+    fn get(index: IndexName, id: DocumentId) -> Result<Option<Doc>, SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = Self::create_client()?;
+        let object = client.get_object(&class_name(&index), &object_id(&id))?;
+        Ok(object.map(object_to_doc))
+    }
+
+    fn search(index: IndexName, query: SearchQuery) -> Result<SearchResults, SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = Self::create_profile_client(search_profile(&query).as_deref())?
+            .with_request_timeout(search_timeout(&query))
+            .with_headers(search_headers(&query));
+
+        Self::search_page(&client, &index, &query)
+    }
+
+    fn stream_search(index: IndexName, query: SearchQuery) -> Result<SearchStream, SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = Self::create_profile_client(search_profile(&query).as_deref())?
+            .with_request_timeout(search_timeout(&query))
+            .with_headers(search_headers(&query));
+        let stream = WeaviateSearchStream::new(client, index, query);
+        Ok(SearchStream::new(stream))
+    }
+
+    fn get_schema(index: IndexName) -> Result<Schema, SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = Self::create_client()?;
+        Ok(class_to_schema(client.get_class(&class_name(&index))?))
+    }
+
+    /// Adds the properties of new schema fields. Weaviate can neither remove properties nor
+    /// change their type, so removed fields are kept and type changes are rejected.
+    fn update_schema(index: IndexName, schema: Schema) -> Result<(), SearchError> {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = Self::create_client()?;
+        let Class {
+            class, properties, ..
+        } = client.get_class(&class_name(&index))?;
+
+        for property in schema_to_properties(&schema) {
+            match properties
+                .iter()
+                .find(|existing| existing.name == property.name)
+            {
+                Some(existing)
+                    if existing.data_type != property.data_type
+                        || (existing.tokenization.is_some()
+                            && existing.tokenization != property.tokenization) =>
+                {
+                    return Err(invalid_query(format!(
+                        "Weaviate can't change the type of the `{}` property",
+                        property.name
+                    )));
+                }
+                Some(_) => {}
+                None => client.add_property(&class, &property)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl ExtendedGuest for WeaviateComponent {
+    fn unwrapped_stream(index: IndexName, query: SearchQuery) -> Self::SearchStream {
+        LOGGING_STATE.with_borrow_mut(|state| state.init());
+
+        let client = Self::create_profile_client(search_profile(&query).as_deref())
+            .unwrap_or_else(|_| WeaviateApi::new("".to_string(), None))
+            .with_headers(search_headers(&query));
+
+        WeaviateSearchStream::new(client, index, query)
+    }
+
+    fn retry_query(original_query: &SearchQuery, partial_hits: &[SearchHit]) -> SearchQuery {
+        create_retry_query(original_query, partial_hits)
+    }
+
+    fn subscribe(stream: &Self::SearchStream) -> Pollable {
+        stream.subscribe()
+    }
+}
+
+type DurableWeaviateComponent = DurableSearch<WeaviateComponent>;
+
+golem_search::export_search!(DurableWeaviateComponent with_types_in golem_search);
@@ -0,0 +1,178 @@
+//! Load test of a search provider, measuring the latencies of ingestion, search and streaming
+//! search so that providers can be compared and regressions in the conversion layers caught.
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::bindings::golem::search::core;
+use crate::bindings::golem::search::types::*;
+use crate::capabilities::PROVIDER;
+use crate::conformance::{text_query, Fixture};
+
+/// Number of documents sent in one `upsert-many` call
+const BATCH_SIZE: usize = 100;
+
+/// Longest time to wait for the ingested documents to become searchable
+const INDEXING_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Terms the generated documents are made of, the queries pick from the same list
+const TERMS: [&str; 8] = [
+    "river", "mountain", "forest", "desert", "ocean", "valley", "island", "glacier",
+];
+
+fn generated_documents(n_docs: u32) -> Vec<Doc> {
+    (0..n_docs as usize)
+        .map(|i| {
+            let content = serde_json::json!({
+                "title": format!("{} {} {i}", TERMS[i % TERMS.len()], TERMS[(i / TERMS.len()) % TERMS.len()]),
+                "author": format!("Author {}", i % 50),
+                "year": 1900 + (i % 125),
+                "genre": TERMS[(i * 7) % TERMS.len()],
+                "description": format!("Generated document {i} about the {}", TERMS[(i * 3) % TERMS.len()]),
+            });
+            Doc {
+                id: format!("bench-{i}"),
+                content: content.to_string(),
+            }
+        })
+        .collect()
+}
+
+/// Latencies of a measured operation
+#[derive(Default)]
+struct Latencies {
+    samples: Vec<Duration>,
+    errors: Vec<SearchError>,
+}
+
+impl Latencies {
+    fn measure<T>(&mut self, f: impl FnOnce() -> Result<T, SearchError>) -> Option<T> {
+        let start = Instant::now();
+        let result = f();
+        let elapsed = start.elapsed();
+        match result {
+            Ok(value) => {
+                self.samples.push(elapsed);
+                Some(value)
+            }
+            Err(e) => {
+                self.errors.push(e);
+                None
+            }
+        }
+    }
+
+    /// Nearest-rank percentile of the successful samples
+    fn percentile(&self, p: usize) -> Duration {
+        let mut sorted = self.samples.clone();
+        sorted.sort();
+        let rank = (p * sorted.len()).div_ceil(100).max(1);
+        sorted.get(rank - 1).copied().unwrap_or_default()
+    }
+
+    fn summary(&self, name: &str) -> String {
+        let mut line = format!(
+            "{name}: {} ok, p50 {:.1}ms, p95 {:.1}ms",
+            self.samples.len(),
+            self.percentile(50).as_secs_f64() * 1000.0,
+            self.percentile(95).as_secs_f64() * 1000.0,
+        );
+        if let Some(first_error) = self.errors.first() {
+            line.push_str(&format!(
+                ", {} failed (first: {first_error:?})",
+                self.errors.len()
+            ));
+        }
+        line
+    }
+}
+
+/// Ingests `n_docs` generated documents and runs `n_queries` searches and streaming searches
+/// against them
+pub fn run(n_docs: u32, n_queries: u32) -> String {
+    let mut results = vec![format!(
+        "Benchmark of {} with {n_docs} documents and {n_queries} queries",
+        PROVIDER.name
+    )];
+    let fixture = match Fixture::new("bench", &[]) {
+        Ok(fixture) => fixture,
+        Err(e) => return e,
+    };
+
+    let docs = generated_documents(n_docs);
+    let mut ingestion = Latencies::default();
+    let start = Instant::now();
+    for batch in docs.chunks(BATCH_SIZE) {
+        ingestion.measure(|| core::upsert_many(&fixture.index, batch));
+    }
+    let ingestion_time = start.elapsed();
+    results.push(ingestion.summary(&format!("upsert-many ({BATCH_SIZE} docs)")));
+    results.push(format!(
+        "Ingestion: {:.0} docs/s",
+        n_docs as f64 / ingestion_time.as_secs_f64().max(f64::EPSILON)
+    ));
+
+    // Documents become searchable with a delay on most providers, the queries are only
+    // measured once all of them are
+    let start = Instant::now();
+    loop {
+        let indexed = core::search(&fixture.index, &text_query(""))
+            .ok()
+            .and_then(|results| results.total)
+            .unwrap_or_default();
+        if indexed >= n_docs {
+            results.push(format!(
+                "Indexing completed in {:.1}s",
+                start.elapsed().as_secs_f64()
+            ));
+            break;
+        }
+        if start.elapsed() > INDEXING_TIMEOUT {
+            results.push(format!(
+                "⚠ Only {indexed}/{n_docs} documents searchable after {}s",
+                INDEXING_TIMEOUT.as_secs()
+            ));
+            break;
+        }
+        thread::sleep(Duration::from_millis(500));
+    }
+
+    let queries = (0..n_queries as usize)
+        .map(|i| SearchQuery {
+            per_page: Some(20),
+            ..text_query(TERMS[i % TERMS.len()])
+        })
+        .collect::<Vec<_>>();
+
+    let mut search = Latencies::default();
+    for query in &queries {
+        search.measure(|| core::search(&fixture.index, query));
+    }
+    results.push(search.summary("search"));
+
+    if PROVIDER.streaming {
+        let mut first_batch = Latencies::default();
+        let mut drained = Latencies::default();
+        for query in &queries {
+            let start = Instant::now();
+            let Some(stream) = first_batch.measure(|| {
+                let stream = core::stream_search(&fixture.index, query)?;
+                let hits = stream.blocking_get_next();
+                Ok((stream, hits))
+            }) else {
+                continue;
+            };
+            let (stream, mut hits) = stream;
+            while !hits.is_empty() {
+                hits = stream.blocking_get_next();
+            }
+            drained.samples.push(start.elapsed());
+        }
+        results.push(first_batch.summary("stream-search (first batch)"));
+        results.push(drained.summary("stream-search (all batches)"));
+    } else {
+        results.push("stream-search: not supported by this provider".to_string());
+    }
+
+    results.join("\n")
+}
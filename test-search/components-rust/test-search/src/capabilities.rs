@@ -0,0 +1,111 @@
+//! The capability matrix of the search providers.
+//!
+//! The conformance suite runs the same checks against every provider and uses the capabilities
+//! of the provider under test to decide what to expect from each of them. Adding a new provider
+//! only requires a feature flag and its `PROVIDER` entry here.
+
+/// What a provider supports, as seen through the `golem:search` interface
+pub struct Capabilities {
+    /// Name of the provider, used in the names of the test indexes
+    pub name: &'static str,
+    /// Indexes have to be created with `create-index` before inserting documents, instead of
+    /// being created by the first insertion
+    pub requires_index_creation: bool,
+    /// `update-schema` is implemented
+    pub schema_updates: bool,
+    /// Facet counts are returned for the requested facets
+    pub facets: bool,
+    /// Highlights are returned for the matching hits
+    pub highlighting: bool,
+    /// `stream-search` is implemented
+    pub streaming: bool,
+    /// Builds a filter matching documents where `field` equals `value`
+    pub equality_filter: fn(&str, &str) -> String,
+}
+
+impl Capabilities {
+    /// Name of the index used by one of the tests
+    pub fn index_name(&self, test: &str) -> String {
+        format!("{}index-{test}", self.index_prefix())
+    }
+
+    /// Prefix of the names of all the test indexes of the provider
+    pub fn index_prefix(&self) -> String {
+        format!("test-{}-", self.name)
+    }
+}
+
+fn colon_filter(field: &str, value: &str) -> String {
+    format!("{field}:{value}")
+}
+
+#[cfg(feature = "meilisearch")]
+fn comparison_filter(field: &str, value: &str) -> String {
+    format!("{field} = \"{value}\"")
+}
+
+#[cfg(feature = "algolia")]
+pub const PROVIDER: Capabilities = Capabilities {
+    name: "algolia",
+    requires_index_creation: false,
+    schema_updates: true,
+    facets: true,
+    highlighting: true,
+    streaming: true,
+    equality_filter: colon_filter,
+};
+
+#[cfg(feature = "elasticsearch")]
+pub const PROVIDER: Capabilities = Capabilities {
+    name: "elasticsearch",
+    requires_index_creation: true,
+    schema_updates: true,
+    facets: true,
+    highlighting: true,
+    streaming: true,
+    equality_filter: colon_filter,
+};
+
+#[cfg(feature = "meilisearch")]
+pub const PROVIDER: Capabilities = Capabilities {
+    name: "meilisearch",
+    requires_index_creation: false,
+    schema_updates: true,
+    facets: true,
+    highlighting: true,
+    streaming: true,
+    equality_filter: comparison_filter,
+};
+
+#[cfg(feature = "opensearch")]
+pub const PROVIDER: Capabilities = Capabilities {
+    name: "opensearch",
+    requires_index_creation: true,
+    schema_updates: true,
+    facets: true,
+    highlighting: true,
+    streaming: true,
+    equality_filter: colon_filter,
+};
+
+#[cfg(feature = "typesense")]
+pub const PROVIDER: Capabilities = Capabilities {
+    name: "typesense",
+    requires_index_creation: true,
+    schema_updates: true,
+    facets: true,
+    highlighting: true,
+    streaming: true,
+    equality_filter: colon_filter,
+};
+
+#[cfg(feature = "qdrant")]
+pub const PROVIDER: Capabilities = Capabilities {
+    name: "qdrant",
+    requires_index_creation: true,
+    schema_updates: true,
+    facets: true,
+    highlighting: false,
+    streaming: true,
+    equality_filter: colon_filter,
+};
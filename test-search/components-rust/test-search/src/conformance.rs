@@ -0,0 +1,726 @@
+//! Provider-agnostic conformance suite for the `golem:search` providers.
+//!
+//! Every check runs the same way against all providers; what is expected from a provider is
+//! decided by its entry in the capability matrix instead of by its name. A provider reporting
+//! `unsupported` for a capability it declares, or implementing one it does not declare, is
+//! reported as a failure.
+
+use std::thread;
+use std::time::Duration;
+
+use crate::bindings::golem::search::types::*;
+use crate::bindings::golem::search::{core, index_registry};
+use crate::capabilities::PROVIDER;
+
+/// Lines of the outcome of a suite, returned as the result of the test exports
+#[derive(Default)]
+struct Report {
+    lines: Vec<String>,
+}
+
+impl Report {
+    fn pass(&mut self, line: impl Into<String>) {
+        self.lines.push(format!("✓ {}", line.into()));
+    }
+
+    fn warn(&mut self, line: impl Into<String>) {
+        self.lines.push(format!("⚠ {}", line.into()));
+    }
+
+    fn fail(&mut self, line: impl Into<String>) {
+        self.lines.push(format!("✗ {}", line.into()));
+    }
+
+    fn info(&mut self, line: impl Into<String>) {
+        self.lines.push(format!("  {}", line.into()));
+    }
+
+    fn section(&mut self, title: &str) {
+        if !self.lines.is_empty() {
+            self.lines.push(String::new());
+        }
+        self.lines.push(format!("=== {title} ==="));
+    }
+
+    /// Checks the outcome of an optional feature against the capability declared for it,
+    /// returning the result of the call if it succeeded
+    fn capability<T>(
+        &mut self,
+        feature: &str,
+        declared: bool,
+        result: Result<T, SearchError>,
+    ) -> Option<T> {
+        match result {
+            Ok(value) => {
+                if declared {
+                    self.pass(format!("{feature} supported"));
+                } else {
+                    self.fail(format!(
+                        "{feature} succeeded but is not declared as a capability"
+                    ));
+                }
+                Some(value)
+            }
+            Err(SearchError::Unsupported) if declared => {
+                self.fail(format!(
+                    "{feature} is declared as a capability but reported unsupported"
+                ));
+                None
+            }
+            Err(SearchError::Unsupported) => {
+                self.pass(format!("{feature} reported unsupported as declared"));
+                None
+            }
+            Err(e) => {
+                self.fail(format!("{feature} failed: {e:?}"));
+                None
+            }
+        }
+    }
+
+    fn finish(self) -> String {
+        self.lines.join("\n")
+    }
+}
+
+/// Documents every suite indexes
+pub fn test_documents() -> Vec<Doc> {
+    vec![
+        Doc {
+            id: "doc1".to_string(),
+            content: r#"{"title": "The Great Gatsby", "author": "F. Scott Fitzgerald", "year": 1925, "genre": "fiction", "description": "A classic American novel about the Jazz Age"}"#.to_string(),
+        },
+        Doc {
+            id: "doc2".to_string(),
+            content: r#"{"title": "To Kill a Mockingbird", "author": "Harper Lee", "year": 1960, "genre": "fiction", "description": "A powerful story about racial injustice in the American South"}"#.to_string(),
+        },
+        Doc {
+            id: "doc3".to_string(),
+            content: r#"{"title": "1984", "author": "George Orwell", "year": 1949, "genre": "dystopian", "description": "A dystopian novel about totalitarian surveillance"}"#.to_string(),
+        },
+        Doc {
+            id: "doc4".to_string(),
+            content: r#"{"title": "Pride and Prejudice", "author": "Jane Austen", "year": 1813, "genre": "romance", "description": "A romantic novel about marriage and social class in Georgian England"}"#.to_string(),
+        },
+        Doc {
+            id: "doc5".to_string(),
+            content: r#"{"title": "The Catcher in the Rye", "author": "J.D. Salinger", "year": 1951, "genre": "fiction", "description": "A coming-of-age story about teenage rebellion"}"#.to_string(),
+        },
+    ]
+}
+
+/// Schema matching all the fields of the test documents
+pub fn test_schema() -> Schema {
+    let field = |name: &str, field_type, facet, sort| SchemaField {
+        name: name.to_string(),
+        field_type,
+        required: false,
+        facet,
+        sort,
+        index: true,
+    };
+    Schema {
+        fields: vec![
+            field("title", FieldType::Text, false, false),
+            field("author", FieldType::Text, true, false),
+            field("year", FieldType::Integer, false, true),
+            field("genre", FieldType::Text, true, false),
+            field("description", FieldType::Text, false, false),
+        ],
+        primary_key: Some("id".to_string()),
+    }
+}
+
+/// Query with only the given text set
+pub fn text_query(q: &str) -> SearchQuery {
+    SearchQuery {
+        q: Some(q.to_string()),
+        filters: vec![],
+        sort: vec![],
+        facets: vec![],
+        page: None,
+        per_page: None,
+        offset: None,
+        highlight: None,
+        config: None,
+    }
+}
+
+/// Index prepared for a suite, deleted when dropped
+pub struct Fixture {
+    pub index: String,
+}
+
+impl Fixture {
+    /// Creates the index the way the provider requires, configures its schema and inserts
+    /// the documents
+    pub fn new(test: &str, docs: &[Doc]) -> Result<Self, String> {
+        let fixture = Fixture {
+            index: PROVIDER.index_name(test),
+        };
+        println!("Setting up index: {}", fixture.index);
+
+        if PROVIDER.requires_index_creation {
+            core::create_index(&fixture.index, Some(&test_schema()))
+                .map_err(|e| format!("✗ Index creation failed: {e:?}"))?;
+        }
+        if PROVIDER.schema_updates {
+            // Providers creating indexes on the first insertion may not have the index yet,
+            // the documents are indexed with the detected schema in that case
+            if let Err(e) = core::update_schema(&fixture.index, &test_schema()) {
+                println!("Schema setup failed, proceeding with document insertion: {e:?}");
+            }
+        }
+        if !docs.is_empty() {
+            println!("Inserting {} documents", docs.len());
+            core::upsert_many(&fixture.index, docs)
+                .map_err(|e| format!("✗ Document insertion failed: {e:?}"))?;
+        }
+        Ok(fixture)
+    }
+}
+
+impl Drop for Fixture {
+    fn drop(&mut self) {
+        core::delete_index(&self.index).ok();
+    }
+}
+
+/// Retries `f` once a second until it returns `Some`, as documents become searchable with a
+/// delay on most providers
+fn eventually<T>(
+    attempts: u32,
+    mut f: impl FnMut() -> Result<Option<T>, SearchError>,
+) -> (u32, Result<Option<T>, SearchError>) {
+    for attempt in 1..=attempts {
+        match f() {
+            Ok(None) if attempt < attempts => {
+                println!("Not available yet, retrying... (attempt {attempt}/{attempts})");
+                thread::sleep(Duration::from_millis(1000));
+            }
+            result => return (attempt, result),
+        }
+    }
+    (attempts, Ok(None))
+}
+
+/// Document insertion, retrieval and deletion
+pub fn crud() -> String {
+    let mut report = Report::default();
+    let fixture = match Fixture::new("test1", &test_documents()) {
+        Ok(fixture) => fixture,
+        Err(e) => return e,
+    };
+    report.pass("Documents inserted successfully");
+
+    println!("Retrieving document with ID: doc1");
+    match eventually(5, || core::get(&fixture.index, "doc1")) {
+        (attempt, Ok(Some(doc))) => report.pass(format!(
+            "Document retrieved: {} (attempt {attempt})",
+            doc.id
+        )),
+        (attempts, Ok(None)) => {
+            report.fail(format!("Document not found after {attempts} attempts"));
+            return report.finish();
+        }
+        (_, Err(e)) => {
+            report.fail(format!("Document retrieval failed: {e:?}"));
+            return report.finish();
+        }
+    }
+
+    println!("Deleting document with ID: doc1");
+    match core::delete(&fixture.index, "doc1") {
+        Ok(()) => {
+            report.pass("Document deleted successfully");
+            let deleted = eventually(5, || match core::get(&fixture.index, "doc1") {
+                Ok(Some(_)) => Ok(None),
+                Ok(None) | Err(_) => Ok(Some(())),
+            });
+            match deleted {
+                (attempt, Ok(Some(()))) => {
+                    report.pass(format!("Document deletion verified (attempt {attempt})"))
+                }
+                _ => report.warn("Document still exists after deletion"),
+            }
+        }
+        Err(e) => report.fail(format!("Document deletion failed: {e:?}")),
+    }
+
+    println!("Deleting index: {}", fixture.index);
+    match core::delete_index(&fixture.index) {
+        Ok(()) => report.pass("Index deleted successfully"),
+        Err(e) => report.fail(format!("Index deletion failed: {e:?}")),
+    }
+    report.finish()
+}
+
+/// Full-text search, with and without filters
+pub fn search() -> String {
+    let mut report = Report::default();
+    let fixture = match Fixture::new("test2", &test_documents()) {
+        Ok(fixture) => fixture,
+        Err(e) => return e,
+    };
+
+    println!("Testing basic text search for 'Gatsby'");
+    let query = text_query("Gatsby");
+    let found = eventually(10, || {
+        core::search(&fixture.index, &query).map(|r| Some(r).filter(|r| !r.hits.is_empty()))
+    });
+    match found {
+        (attempt, Ok(Some(results))) => {
+            report.pass(format!(
+                "Search returned {} hits (attempt {attempt})",
+                results.hits.len()
+            ));
+            let first_hit = &results.hits[0];
+            report.info(format!("First hit ID: {}", first_hit.id));
+            if let Some(score) = first_hit.score {
+                report.info(format!("Score: {score:.2}"));
+            }
+        }
+        (attempts, Ok(None)) => {
+            report.warn(format!("Search returned no hits after {attempts} attempts"))
+        }
+        (_, Err(e)) => report.fail(format!("Search failed: {e:?}")),
+    }
+
+    println!("Testing filtered search for fiction genre");
+    let filtered_query = SearchQuery {
+        filters: vec![(PROVIDER.equality_filter)("genre", "fiction")],
+        ..text_query("Gatsby")
+    };
+    match core::search(&fixture.index, &filtered_query) {
+        Ok(results) => {
+            report.pass(format!(
+                "Filtered search returned {} hits",
+                results.hits.len()
+            ));
+            if results.hits.iter().any(|hit| hit.id != "doc1") {
+                report.fail("Filtered search returned documents not matching the query");
+            }
+        }
+        Err(e) => report.fail(format!("Filtered search failed: {e:?}")),
+    }
+    report.finish()
+}
+
+/// Sorting and pagination
+pub fn sort_and_paginate() -> String {
+    let mut report = Report::default();
+    let fixture = match Fixture::new("test3", &test_documents()) {
+        Ok(fixture) => fixture,
+        Err(e) => return e,
+    };
+
+    println!("Testing search with sorting by year");
+    let sorted_query = SearchQuery {
+        q: None,
+        sort: vec!["year:desc".to_string()],
+        ..text_query("")
+    };
+    match core::search(&fixture.index, &sorted_query) {
+        Ok(results) => {
+            report.pass(format!(
+                "Sorted search returned {} hits",
+                results.hits.len()
+            ));
+            let years = results
+                .hits
+                .iter()
+                .filter_map(|hit| hit.content.as_deref())
+                .filter_map(|content| serde_json::from_str::<serde_json::Value>(content).ok())
+                .filter_map(|content| content["year"].as_i64())
+                .collect::<Vec<_>>();
+            if years.windows(2).all(|pair| pair[0] >= pair[1]) {
+                report.info("Hits are sorted by year, descending");
+            } else {
+                report.fail(format!("Hits are not sorted by year: {years:?}"));
+            }
+        }
+        Err(e) => report.fail(format!("Sorted search failed: {e:?}")),
+    }
+
+    println!("Testing pagination with page=1, per_page=2");
+    let paginated_query = SearchQuery {
+        page: Some(1),
+        per_page: Some(2),
+        ..sorted_query
+    };
+    match core::search(&fixture.index, &paginated_query) {
+        Ok(results) => {
+            report.pass(format!(
+                "Paginated search returned {} hits",
+                results.hits.len()
+            ));
+            if results.hits.len() > 2 {
+                report.fail("Paginated search returned more hits than the page size");
+            }
+            if let Some(total) = results.total {
+                report.info(format!("Total documents: {total}"));
+            }
+            if let Some(page) = results.page {
+                report.info(format!("Current page: {page}"));
+            }
+            match &results.navigation {
+                Some(navigation) => report.info(format!(
+                    "Total pages: {:?}, has next: {}, next page: {:?}, next offset: {:?}",
+                    navigation.total_pages,
+                    navigation.has_next,
+                    navigation.next_page,
+                    navigation.next_offset
+                )),
+                None => report.fail("Paginated search returned no page navigation"),
+            }
+        }
+        Err(e) => report.fail(format!("Paginated search failed: {e:?}")),
+    }
+    report.finish()
+}
+
+/// Highlighting and facets
+pub fn highlights_and_facets() -> String {
+    let mut report = Report::default();
+    let fixture = match Fixture::new("test4th", &test_documents()) {
+        Ok(fixture) => fixture,
+        Err(e) => return e,
+    };
+
+    println!("Testing search with highlighting and facets");
+    let query = SearchQuery {
+        facets: vec!["genre".to_string(), "author".to_string()],
+        highlight: Some(HighlightConfig {
+            fields: vec!["title".to_string(), "description".to_string()],
+            pre_tag: Some("<mark>".to_string()),
+            post_tag: Some("</mark>".to_string()),
+            max_length: Some(200),
+        }),
+        ..text_query("American")
+    };
+
+    match core::search(&fixture.index, &query) {
+        Ok(results) => {
+            report.pass(format!(
+                "Highlighted search returned {} hits",
+                results.hits.len()
+            ));
+
+            let highlighted = results.hits.iter().any(|hit| hit.highlights.is_some());
+            match (highlighted, PROVIDER.highlighting) {
+                (true, _) => report.pass("Found highlights in results"),
+                (false, true) if !results.hits.is_empty() => {
+                    report.fail("No highlights returned for a provider declaring highlighting")
+                }
+                (false, _) => report.info("No highlights returned"),
+            }
+
+            match (results.facets.is_some(), PROVIDER.facets) {
+                (true, _) => report.pass("Facet data returned"),
+                (false, true) => {
+                    report.fail("No facet data returned for a provider declaring facets")
+                }
+                (false, false) => report.info("No facet data returned, as declared"),
+            }
+
+            if let Some(took_ms) = results.took_ms {
+                report.info(format!("Query took: {took_ms}ms"));
+            }
+        }
+        Err(e) => report.fail(format!("Highlighted search failed: {e:?}")),
+    }
+    report.finish()
+}
+
+/// Schema inspection and updates
+pub fn schema() -> String {
+    let mut report = Report::default();
+    // Indexes created on the first insertion need a document to exist
+    let docs = if PROVIDER.requires_index_creation {
+        vec![]
+    } else {
+        test_documents().into_iter().take(1).collect()
+    };
+    let fixture = match Fixture::new("test5", &docs) {
+        Ok(fixture) => fixture,
+        Err(e) => return e,
+    };
+
+    println!("Retrieving index schema");
+    match core::get_schema(&fixture.index) {
+        Ok(schema) => {
+            report.pass("Schema retrieved successfully");
+            report.info(format!("Fields count: {}", schema.fields.len()));
+            if let Some(pk) = &schema.primary_key {
+                report.info(format!("Primary key: {pk}"));
+            }
+            for name in ["title", "author"] {
+                if schema.fields.iter().any(|field| field.name == name) {
+                    report.info(format!("✓ Field {name} found"));
+                }
+            }
+        }
+        Err(e) => report.fail(format!("Schema retrieval failed: {e:?}")),
+    }
+
+    println!("Testing schema update");
+    let mut updated_schema = test_schema();
+    updated_schema.fields.push(SchemaField {
+        name: "isbn".to_string(),
+        field_type: FieldType::Text,
+        required: false,
+        facet: false,
+        sort: false,
+        index: true,
+    });
+    report.capability(
+        "Schema update",
+        PROVIDER.schema_updates,
+        core::update_schema(&fixture.index, &updated_schema),
+    );
+    report.finish()
+}
+
+/// Streaming search over more documents than a page holds
+pub fn streaming() -> String {
+    let mut report = Report::default();
+    let mut docs = test_documents();
+    docs.extend((6..=20).map(|i| Doc {
+        id: format!("doc{i}"),
+        content: format!(
+            r#"{{"title": "Book {i}", "author": "Author {i}", "year": {}, "genre": "test", "description": "A test book for streaming search"}}"#,
+            1900 + i
+        ),
+    }));
+    let fixture = match Fixture::new("test6", &docs) {
+        Ok(fixture) => fixture,
+        Err(e) => return e,
+    };
+
+    println!("Testing streaming search");
+    let query = SearchQuery {
+        sort: vec!["year:asc".to_string()],
+        // Small page size so that the results span several batches
+        per_page: Some(5),
+        ..text_query("book")
+    };
+
+    let stream = report.capability(
+        "Streaming search",
+        PROVIDER.streaming,
+        core::stream_search(&fixture.index, &query),
+    );
+    if let Some(stream) = stream {
+        let mut total_hits = 0;
+        let mut batch_count = 0;
+        for _ in 0..5 {
+            let hits = stream.blocking_get_next();
+            if hits.is_empty() {
+                break;
+            }
+            batch_count += 1;
+            total_hits += hits.len();
+            report.info(format!("Batch {batch_count}: {} hits", hits.len()));
+        }
+        report.pass(format!(
+            "Streaming complete: {total_hits} total hits in {batch_count} batches"
+        ));
+    }
+    report.finish()
+}
+
+/// Mapping of invalid input, missing resources and edge cases to search errors
+pub fn error_mapping() -> String {
+    let mut report = Report::default();
+    let fixture = match Fixture::new("test7", &[]) {
+        Ok(fixture) => fixture,
+        Err(e) => return e,
+    };
+    let test_index = fixture.index.as_str();
+
+    report.section("Testing Optional Features");
+    report.capability(
+        "Schema update",
+        PROVIDER.schema_updates,
+        core::update_schema(test_index, &test_schema()),
+    );
+
+    let advanced_query = SearchQuery {
+        filters: vec!["complex_filter:value AND nested.field:value".to_string()],
+        sort: vec!["complex_sort:desc".to_string()],
+        facets: vec!["facet1".to_string(), "facet2".to_string()],
+        page: Some(1),
+        per_page: Some(10),
+        offset: Some(0),
+        highlight: Some(HighlightConfig {
+            fields: vec!["title".to_string(), "content".to_string()],
+            pre_tag: Some("<em>".to_string()),
+            post_tag: Some("</em>".to_string()),
+            max_length: Some(150),
+        }),
+        ..text_query("test")
+    };
+    match core::search(test_index, &advanced_query) {
+        Ok(_) => report.pass("Advanced search features supported"),
+        Err(SearchError::IndexNotFound) => {
+            report.pass("Index not found reported for an empty index")
+        }
+        Err(e) => report.warn(format!("Advanced search failed: {e:?}")),
+    }
+
+    match core::stream_search(test_index, &advanced_query) {
+        Err(SearchError::Unsupported) if PROVIDER.streaming => {
+            report.fail("Streaming search is declared as a capability but reported unsupported")
+        }
+        Ok(_) | Err(_) => report.pass("Streaming search handled as declared"),
+    }
+
+    report.section("Testing Invalid Input Handling");
+    let invalid_doc = Doc {
+        id: "invalid-json".to_string(),
+        content: r#"{"invalid": json, "malformed": true"#.to_string(),
+    };
+    match core::upsert(test_index, &invalid_doc) {
+        Ok(()) => report.fail("Invalid JSON was accepted"),
+        Err(SearchError::InvalidQuery(msg)) => report.pass(format!("Invalid JSON rejected: {msg}")),
+        Err(e) => report.warn(format!(
+            "Invalid JSON rejected with an unexpected error: {e:?}"
+        )),
+    }
+
+    let invalid_query = SearchQuery {
+        q: Some("((unclosed parenthesis AND malformed:".to_string()),
+        filters: vec!["invalid_filter_syntax:::".to_string()],
+        sort: vec!["invalid_sort_field:invalid_direction".to_string()],
+        facets: vec![],
+        page: Some(0),
+        per_page: Some(0),
+        offset: None,
+        highlight: None,
+        config: None,
+    };
+    match core::search(test_index, &invalid_query) {
+        Ok(_) => report.warn("Invalid query was accepted (lenient parsing)"),
+        Err(SearchError::InvalidQuery(msg)) => {
+            report.pass(format!("Invalid query rejected: {msg}"))
+        }
+        Err(e) => report.pass(format!("Invalid query handled: {e:?}")),
+    }
+
+    report.section("Testing Non-Existent Resource Handling");
+    let nonexistent_index = "definitely-does-not-exist-12345";
+    match core::get(nonexistent_index, "any-id") {
+        Ok(None) => report.pass("Non-existent document returns None"),
+        Err(SearchError::IndexNotFound) => report.pass("Non-existent index reports IndexNotFound"),
+        Err(e) => report.warn(format!("Non-existent index reported as: {e:?}")),
+        Ok(Some(_)) => report.fail("Document found in non-existent index"),
+    }
+    match core::delete(nonexistent_index, "non-existent-doc") {
+        Ok(()) => report.pass("Deleting non-existent document succeeds (idempotent)"),
+        Err(SearchError::IndexNotFound) => report.pass("Non-existent index reports IndexNotFound"),
+        Err(e) => report.warn(format!("Non-existent index reported as: {e:?}")),
+    }
+    match core::get_schema(nonexistent_index) {
+        Ok(_) => report.warn("Schema retrieved from non-existent index"),
+        Err(SearchError::IndexNotFound) => report.pass("Schema request reports IndexNotFound"),
+        Err(e) => report.warn(format!("Non-existent index reported as: {e:?}")),
+    }
+
+    report.section("Testing Edge Cases");
+    let empty_doc = Doc {
+        id: "empty-doc".to_string(),
+        content: "{}".to_string(),
+    };
+    match core::upsert(test_index, &empty_doc) {
+        Ok(()) => report.pass("Empty document accepted"),
+        Err(e) => report.pass(format!("Empty document handled: {e:?}")),
+    }
+    let long_id_doc = Doc {
+        id: "a".repeat(1000),
+        content: r#"{"test": "value"}"#.to_string(),
+    };
+    match core::upsert(test_index, &long_id_doc) {
+        Ok(()) => report.pass("Long document ID accepted"),
+        Err(SearchError::InvalidQuery(msg)) => report.pass(format!("Long ID rejected: {msg}")),
+        Err(e) => report.pass(format!("Long ID handled: {e:?}")),
+    }
+    match core::search(test_index, &text_query("")) {
+        Ok(results) => report.pass(format!(
+            "Empty query executed, returned {} hits",
+            results.hits.len()
+        )),
+        Err(e) => report.pass(format!("Empty query handled: {e:?}")),
+    }
+
+    report.section("Testing Index Lifecycle");
+    let first_page = PageRequest {
+        token: None,
+        size: Some(1),
+    };
+    match core::list_indexes(&first_page) {
+        Ok(page) if page.items.len() > 1 => report.fail(format!(
+            "list_indexes returned {} indexes in a page of 1",
+            page.items.len()
+        )),
+        Ok(IndexPage {
+            next_page_token: Some(token),
+            ..
+        }) => {
+            let next_page = PageRequest {
+                token: Some(token),
+                size: Some(1),
+            };
+            match core::list_indexes(&next_page) {
+                Ok(_) => report.pass("list_indexes: Followed the next page token"),
+                Err(e) => report.fail(format!("list_indexes of the next page failed: {e:?}")),
+            }
+        }
+        Ok(_) => report.pass("list_indexes: Operation completed"),
+        Err(e) => report.fail(format!("list_indexes failed: {e:?}")),
+    }
+    let created_index = PROVIDER.index_name("test7-create");
+    match core::create_index(&created_index, Some(&test_schema())) {
+        Ok(()) => report.pass("create_index: Operation completed"),
+        Err(SearchError::Unsupported) if PROVIDER.requires_index_creation => {
+            report.fail("create_index reported unsupported for a provider requiring it")
+        }
+        Err(e) => report.pass(format!("create_index: Error handled gracefully: {e:?}")),
+    }
+    core::delete_index(&created_index).ok();
+    match core::delete_index("non-existent") {
+        Ok(()) => report.pass("delete_index: Operation completed"),
+        Err(e) => report.pass(format!("delete_index: Error handled gracefully: {e:?}")),
+    }
+
+    report.section("Testing System Resilience");
+    let succeeded = (0..5)
+        .filter(|i| {
+            let doc = Doc {
+                id: format!("stress-doc-{i}"),
+                content: format!(r#"{{"value": {i}, "test": "stress"}}"#),
+            };
+            core::upsert(test_index, &doc).is_ok()
+        })
+        .count();
+    report.pass(format!("Stress test: {succeeded}/5 operations succeeded"));
+
+    report.section("Error Handling Test Complete");
+    report.finish()
+}
+
+/// Deletes the test indexes of the provider
+pub fn cleanup(older_than_ms: Option<u64>) -> String {
+    let mut report = Report::default();
+    report.section("Cleaning Up Test Indexes");
+    match index_registry::cleanup(&PROVIDER.index_prefix(), older_than_ms) {
+        Ok(deleted) if deleted.is_empty() => report.pass("No test index to delete"),
+        Ok(deleted) => {
+            for index in &deleted {
+                report.info(format!("Deleted {index}"));
+            }
+            report.pass(format!("Deleted {} test indexes", deleted.len()));
+        }
+        Err(e) => report.fail(format!("Cleanup failed: {e:?}")),
+    }
+    report.finish()
+}
@@ -23,6 +23,10 @@ const MODEL: &'static str = "grok-3-beta";
 const MODEL: &'static str = "openrouter/auto";
 #[cfg(feature = "ollama")]
 const MODEL: &'static str = "qwen3:1.7b";
+#[cfg(feature = "vertex")]
+const MODEL: &'static str = "gemini-2.0-flash";
+#[cfg(feature = "sagemaker")]
+const MODEL: &'static str = "golem-llm-test-endpoint";
 
 #[cfg(feature = "openai")]
 const IMAGE_MODEL: &'static str = "gpt-4o-mini";
@@ -36,6 +40,10 @@ const IMAGE_MODEL: &'static str = "grok-2-vision-latest";
 const IMAGE_MODEL: &'static str = "openrouter/auto";
 #[cfg(feature = "ollama")]
 const IMAGE_MODEL: &'static str = "gemma3:4b";
+#[cfg(feature = "vertex")]
+const IMAGE_MODEL: &'static str = "gemini-2.0-flash";
+#[cfg(feature = "sagemaker")]
+const IMAGE_MODEL: &'static str = "golem-llm-test-endpoint";
 
 impl Guest for Component {
     /// test1 demonstrates a simple, non-streaming text question-answer interaction with the LLM.
@@ -81,6 +89,7 @@ impl Guest for Component {
                                     inline_data.mime_type
                                 ),
                             },
+                            llm::ContentPart::File(file_ref) => format!("[FILE: {}]", file_ref.id),
                         })
                         .collect::<Vec<_>>()
                         .join(", ")
@@ -404,6 +413,7 @@ impl Guest for Component {
                                     inline_data.mime_type
                                 ),
                             },
+                            llm::ContentPart::File(file_ref) => format!("[FILE: {}]", file_ref.id),
                         })
                         .collect::<Vec<_>>()
                         .join(", ")
@@ -486,6 +496,9 @@ impl Guest for Component {
                                         ));
                                     }
                                 },
+                                llm::ContentPart::File(file_ref) => {
+                                    result.push_str(&format!("FILE: {}\n", file_ref.id));
+                                }
                             }
                         }
                     }
@@ -584,6 +597,7 @@ impl Guest for Component {
                                     inline_data.mime_type
                                 ),
                             },
+                            llm::ContentPart::File(file_ref) => format!("[FILE: {}]", file_ref.id),
                         })
                         .collect::<Vec<_>>()
                         .join(", ")
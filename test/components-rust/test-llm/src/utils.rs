@@ -35,6 +35,9 @@ pub fn consume_next_event(stream: &llm::ChatStream) -> Option<String> {
                                 ));
                             }
                         },
+                        llm::ContentPart::File(file_ref) => {
+                            result.push_str(&format!("FILE: {}\n", file_ref.id));
+                        }
                     }
                 }
             }